@@ -0,0 +1,256 @@
+//! This module provides the [`AddCompletionForm`] component.
+
+use crate::{
+    comps::login_form::get_value_from_input_event,
+    web::{format_date_input, parse_date_input, today},
+};
+use std::fmt;
+use test_tracker_shared::{policy::MarksPolicyError, CompletionData};
+use tracing::debug;
+use web_sys::HtmlInputElement;
+use yew::{
+    events::{InputEvent, SubmitEvent},
+    function_component, html, use_effect_with_deps, use_node_ref, use_state, Callback, Html,
+    NodeRef, Properties, UseStateHandle,
+};
+
+/// The props for [`AddCompletionForm`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The ID of the test, to keep the IDs of the inputs unique when there's a form open for more
+    /// than one test.
+    pub test_id: i32,
+
+    /// The total marks to fill in when the form appears, which should come from the most recent
+    /// completion of the test if there is one, or the default in the user's settings. See
+    /// [`SyncedTest::next_total_marks`](test_tracker_shared::SyncedTest::next_total_marks).
+    pub default_total_marks: Option<i32>,
+
+    /// The callback to run with the new completion, once it's passed validation.
+    pub onsubmit: Callback<CompletionData>,
+
+    /// The callback to close the form without adding anything.
+    pub oncancel: Callback<()>,
+}
+
+/// A problem with what's been typed into the form, which gets shown under the field it's about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FormError {
+    /// The field isn't a whole number.
+    NotANumber(&'static str),
+
+    /// The field is a negative number.
+    Negative(&'static str),
+
+    /// The date isn't a valid date.
+    InvalidDate,
+
+    /// The numbers are fine on their own, but don't make sense together.
+    Marks(MarksPolicyError),
+}
+
+impl FormError {
+    /// The name of the field that's invalid.
+    fn field(&self) -> &'static str {
+        match self {
+            Self::NotANumber(field) | Self::Negative(field) => field,
+            Self::InvalidDate => "date",
+            Self::Marks(e) => e.field(),
+        }
+    }
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotANumber(_) => write!(f, "please enter a whole number"),
+            Self::Negative(_) => write!(f, "this must not be negative"),
+            Self::InvalidDate => write!(f, "please enter a valid date"),
+            Self::Marks(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Parse a number of marks from the given field, refusing anything that isn't a non-negative
+/// whole number. Browsers give an empty value for a number input with non-numeric text in it.
+fn parse_marks(field: &'static str, text: &str) -> Result<i32, FormError> {
+    let marks: i32 = text
+        .trim()
+        .parse()
+        .map_err(|_| FormError::NotANumber(field))?;
+    if marks < 0 {
+        return Err(FormError::Negative(field));
+    }
+    Ok(marks)
+}
+
+/// Turn the text of the form into a completion, checking everything that the server would. The
+/// date is ignored if `no_date` is true.
+fn parse_completion(
+    achieved_mark: &str,
+    total_marks: &str,
+    date: &str,
+    no_date: bool,
+    comments: &str,
+) -> Result<CompletionData, FormError> {
+    let completion = CompletionData {
+        achieved_mark: parse_marks("achieved_mark", achieved_mark)?,
+        total_marks: parse_marks("total_marks", total_marks)?,
+        date: match date.trim() {
+            _ if no_date => None,
+            "" => None,
+            date => Some(parse_date_input(date).ok_or(FormError::InvalidDate)?),
+        },
+        comments: (!comments.trim().is_empty()).then(|| comments.to_string()),
+    };
+    completion.validate().map_err(FormError::Marks)?;
+    Ok(completion)
+}
+
+/// Render a labelled input of the given type, bound to the given state, with an error underneath
+/// if there is one.
+fn input(
+    id: &str,
+    label: &str,
+    input_type: &str,
+    state: &UseStateHandle<String>,
+    error: Option<&FormError>,
+    node_ref: NodeRef,
+) -> Html {
+    // This updates on every keystroke rather than on change, so that pressing Enter submits what's
+    // been typed
+    let oninput = {
+        let state = state.clone();
+        move |event: InputEvent| state.set(get_value_from_input_event(event.into()))
+    };
+
+    html! {
+        <div class="label-and-input-box">
+            <label for={id.to_string()}> { label } </label>
+            if input_type == "number" {
+                <input
+                    id={id.to_string()}
+                    ref={node_ref}
+                    type="number"
+                    min="0"
+                    step="1"
+                    value={(**state).clone()}
+                    {oninput} />
+            } else {
+                <input
+                    id={id.to_string()}
+                    ref={node_ref}
+                    type={input_type.to_string()}
+                    value={(**state).clone()}
+                    {oninput} />
+            }
+            if let Some(error) = error {
+                <div class="field-error"> { error.to_string() } </div>
+            }
+        </div>
+    }
+}
+
+/// A form to add a completion to a test, which is usually another attempt at a test that's been
+/// done before. The total marks are filled in from [`Props::default_total_marks`] and the date
+/// defaults to today, unless the "No date" box is ticked. The achieved mark is left empty and
+/// focused, so that logging an attempt is just typing one number and pressing Enter.
+///
+/// Everything is filled in again each time the form appears.
+#[function_component(AddCompletionForm)]
+pub fn add_completion_form(props: &Props) -> Html {
+    let achieved_mark = use_state(String::new);
+    let total_marks = use_state(|| {
+        props
+            .default_total_marks
+            .map_or_else(String::new, |n| n.to_string())
+    });
+    let date = use_state(|| format_date_input(Some(today())));
+    let no_date = use_state(|| false);
+    let comments = use_state(String::new);
+    let error = use_state(|| None::<FormError>);
+
+    let achieved_mark_ref = use_node_ref();
+    {
+        let achieved_mark_ref = achieved_mark_ref.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(input) = achieved_mark_ref.cast::<HtmlInputElement>() {
+                    let _ = input.focus();
+                }
+            },
+            (),
+        );
+    }
+
+    let onsubmit = {
+        let onsubmit = props.onsubmit.clone();
+        let fields = [
+            achieved_mark.clone(),
+            total_marks.clone(),
+            date.clone(),
+            comments.clone(),
+        ];
+        let (no_date, error) = (no_date.clone(), error.clone());
+        move |event: SubmitEvent| {
+            // The form is never really submitted, since that would reload the page
+            event.prevent_default();
+            let [achieved_mark, total_marks, date, comments] = &fields;
+            match parse_completion(achieved_mark, total_marks, date, *no_date, comments) {
+                Ok(completion) => {
+                    error.set(None);
+                    onsubmit.emit(completion);
+                }
+                Err(e) => {
+                    debug!(?e, "New completion is invalid");
+                    error.set(Some(e));
+                }
+            }
+        }
+    };
+
+    let onchange_no_date = {
+        let (date, no_date) = (date.clone(), no_date.clone());
+        move |_event: yew::Event| {
+            // Unticking the box puts today back, since an empty date box would mean no date anyway
+            date.set(if *no_date {
+                format_date_input(Some(today()))
+            } else {
+                String::new()
+            });
+            no_date.set(!*no_date);
+        }
+    };
+
+    let onclick_cancel = {
+        let oncancel = props.oncancel.clone();
+        move |_mouse_event| oncancel.emit(())
+    };
+
+    let error_for = |field: &str| (*error).as_ref().filter(|error| error.field() == field);
+    let id = |name: &str| format!("{name}{}", props.test_id);
+
+    html! {
+        <form class="form add-completion-form" {onsubmit}>
+            { input(&id("achievedMarkBox"), "Achieved mark", "number", &achieved_mark, error_for("achieved_mark"), achieved_mark_ref) }
+            { input(&id("totalMarksBox"), "Total marks", "number", &total_marks, error_for("total_marks"), NodeRef::default()) }
+            if !*no_date {
+                { input(&id("dateBox"), "Date", "date", &date, error_for("date"), NodeRef::default()) }
+            }
+            <div class="label-and-checkbox">
+                <input
+                    id={id("noDateBox")}
+                    type="checkbox"
+                    checked={*no_date}
+                    onchange={onchange_no_date} />
+                <label for={id("noDateBox")}> { "No date" } </label>
+            </div>
+            { input(&id("completionCommentsBox"), "Comments", "text", &comments, None, NodeRef::default()) }
+
+            <div class="buttons">
+                <button type="submit"> { "Log attempt" } </button>
+                <button type="button" onclick={onclick_cancel}> { "Cancel" } </button>
+            </div>
+        </form>
+    }
+}
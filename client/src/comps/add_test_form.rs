@@ -0,0 +1,197 @@
+//! This module provides the [`AddTestForm`] component.
+
+use crate::comps::login_form::get_value_from_input_event;
+use test_tracker_shared::{error::FieldErrors, TestData};
+use tracing::debug;
+use yew::{
+    events::InputEvent, function_component, html, use_effect_with_deps, use_state, Callback, Html,
+    Properties, UseStateHandle,
+};
+
+/// A test that the server refused, with the errors to show under the fields that it refused them
+/// for. The form is filled in with it again, so that the user can fix it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RejectedTest {
+    /// The test that was refused.
+    pub test: TestData,
+
+    /// Why it was refused, keyed by the fields in [`TestData::FIELDS`].
+    pub field_errors: FieldErrors,
+}
+
+/// The props for [`AddTestForm`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The callback to run with the new test, once it's passed validation.
+    pub onsubmit: Callback<TestData>,
+
+    /// The last test that the server refused, if it was refused because of one of its fields.
+    #[prop_or_default]
+    pub rejected: Option<RejectedTest>,
+}
+
+/// The ID of the subject input, which is the first one in the form.
+pub(crate) const SUBJECT_BOX_ID: &str = "subjectBox";
+
+/// Turn the text of an optional input into `None` if it's empty.
+pub(crate) fn optional(text: &str) -> Option<String> {
+    (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+/// Render a labelled text input, bound to the given state, with the error for its field
+/// underneath if there is one. Editing the input clears its error.
+pub(crate) fn text_input(
+    id: &str,
+    label: &str,
+    field: &'static str,
+    state: &UseStateHandle<String>,
+    errors: &UseStateHandle<FieldErrors>,
+    disabled: bool,
+) -> Html {
+    let oninput = {
+        let (state, errors) = (state.clone(), errors.clone());
+        move |event: InputEvent| {
+            state.set(get_value_from_input_event(event.into()));
+
+            let mut new_errors = (*errors).clone();
+            if new_errors.remove(field) {
+                errors.set(new_errors);
+            }
+        }
+    };
+
+    let error = errors.get(field);
+    let error_id = format!("{id}Error");
+
+    html! {
+        <div class="label-and-input-box">
+            <label for={id.to_string()}> { label } </label>
+            <input
+                id={id.to_string()}
+                type="text"
+                value={(**state).clone()}
+                aria-invalid={error.is_some().to_string()}
+                aria-describedby={error.map(|_| error_id.clone())}
+                {oninput}
+                {disabled} />
+            if let Some(error) = error {
+                <div class="field-error" id={error_id}> { error.to_string() } </div>
+            }
+        </div>
+    }
+}
+
+/// A form to add a new test. Subject and date or ID are required, and if either is missing, then
+/// the problem is shown under that field and nothing is submitted. If the server refuses the test
+/// because of one of its fields, then the form is filled in with it again, with the server's
+/// reason under that field.
+#[function_component(AddTestForm)]
+pub fn add_test_form(props: &Props) -> Html {
+    let subject = use_state(String::new);
+    let topic = use_state(String::new);
+    let date_or_id = use_state(String::new);
+    let qualification_level = use_state(String::new);
+    let exam_board = use_state(String::new);
+    let paper_link = use_state(String::new);
+    let mark_scheme_link = use_state(String::new);
+    let comments = use_state(String::new);
+    let errors = use_state(FieldErrors::default);
+
+    let onclick = {
+        let onsubmit = props.onsubmit.clone();
+        let fields = [
+            subject.clone(),
+            topic.clone(),
+            date_or_id.clone(),
+            qualification_level.clone(),
+            exam_board.clone(),
+            paper_link.clone(),
+            mark_scheme_link.clone(),
+            comments.clone(),
+        ];
+        let errors = errors.clone();
+        move |_mouse_event| {
+            let test = TestData {
+                subject: fields[0].to_string(),
+                topic: optional(&fields[1]),
+                date_or_id: fields[2].to_string(),
+                qualification_level: optional(&fields[3]),
+                exam_board: optional(&fields[4]),
+                paper_link: optional(&fields[5]),
+                mark_scheme_link: optional(&fields[6]),
+                comments: optional(&fields[7]),
+            };
+
+            match test.validate() {
+                Ok(()) => {
+                    errors.set(FieldErrors::default());
+                    for field in &fields {
+                        field.set(String::new());
+                    }
+                    onsubmit.emit(test);
+                }
+                Err(e) => {
+                    debug!(?e, "New test is invalid");
+                    let mut new_errors = FieldErrors::default();
+                    new_errors.insert(e.field(), e.to_string());
+                    errors.set(new_errors);
+                }
+            }
+        }
+    };
+
+    // Fill the form in again with a test that the server refused
+    {
+        let fields = [
+            subject.clone(),
+            topic.clone(),
+            date_or_id.clone(),
+            qualification_level.clone(),
+            exam_board.clone(),
+            paper_link.clone(),
+            mark_scheme_link.clone(),
+            comments.clone(),
+        ];
+        let errors = errors.clone();
+        use_effect_with_deps(
+            move |rejected: &Option<RejectedTest>| {
+                if let Some(RejectedTest { test, field_errors }) = rejected {
+                    debug!(?field_errors, "The server refused the new test");
+                    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+                    let values = [
+                        test.subject.clone(),
+                        text(&test.topic),
+                        test.date_or_id.clone(),
+                        text(&test.qualification_level),
+                        text(&test.exam_board),
+                        text(&test.paper_link),
+                        text(&test.mark_scheme_link),
+                        text(&test.comments),
+                    ];
+                    for (field, value) in fields.iter().zip(values) {
+                        field.set(value);
+                    }
+                    errors.set(field_errors.clone());
+                }
+            },
+            props.rejected.clone(),
+        );
+    }
+
+    html! {
+        <div class="form add-test-form">
+            <h3> { "Add test" } </h3>
+
+            { text_input(SUBJECT_BOX_ID, "Subject", "subject", &subject, &errors, false) }
+            { text_input("topicBox", "Topic", "topic", &topic, &errors, false) }
+            { text_input("dateOrIdBox", "Date or ID", "date_or_id", &date_or_id, &errors, false) }
+            { text_input("qualificationLevelBox", "Qualification level", "qualification_level", &qualification_level, &errors, false) }
+            { text_input("examBoardBox", "Exam board", "exam_board", &exam_board, &errors, false) }
+            { text_input("paperLinkBox", "Paper link", "paper_link", &paper_link, &errors, false) }
+            { text_input("markSchemeLinkBox", "Mark scheme link", "mark_scheme_link", &mark_scheme_link, &errors, false) }
+            { text_input("commentsBox", "Comments", "comments", &comments, &errors, false) }
+
+            <button {onclick}> { "Add" } </button>
+        </div>
+    }
+}
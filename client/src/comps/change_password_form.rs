@@ -0,0 +1,181 @@
+//! This module provides the [`ChangePasswordForm`] component.
+
+use crate::comps::login_form::{get_value_from_input_event, strength_meter};
+use test_tracker_shared::policy::password_strength;
+use tracing::debug;
+use yew::{
+    events::InputEvent, function_component, html, use_state, Callback, Html, Properties,
+    UseStateHandle,
+};
+
+/// Why changing the password failed, so that the form can show the error by the right field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangePasswordError {
+    /// The current password was wrong.
+    WrongPassword,
+
+    /// The server refused the new password, for the given reason.
+    InvalidNewPassword(String),
+
+    /// Something else went wrong, like the server being unreachable, with a message to show.
+    Other(String),
+}
+
+/// A callback to change the password. It takes the current password, the new password, and a
+/// callback to run with the result of changing it.
+pub type ChangePasswordCallback =
+    Callback<(String, String, Callback<Result<(), ChangePasswordError>>)>;
+
+/// The props for [`ChangePasswordForm`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The username of the user, since the password policy depends on it.
+    pub username: String,
+
+    /// The callback to change the password, once the new one has passed the password policy.
+    pub onsubmit: ChangePasswordCallback,
+
+    /// Whether the browser is offline, in which case the new password can't be sent, so the button
+    /// is disabled.
+    #[prop_or_default]
+    pub offline: bool,
+}
+
+/// Make a callback to update the given field on every keystroke.
+fn on_input(field: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let field = field.clone();
+    Callback::from(move |event: InputEvent| field.set(get_value_from_input_event(event.into())))
+}
+
+/// A section of the settings to change the user's password, which asks for their current password
+/// and the new one twice. The new password is checked against the password policy as it's typed.
+///
+/// The button is disabled while the change is being sent. If it works, then every field is
+/// cleared, and if it fails, then the new password is kept so that only the mistake needs fixing.
+#[function_component(ChangePasswordForm)]
+pub fn change_password_form(props: &Props) -> Html {
+    let current_password = use_state(String::new);
+    let new_password = use_state(String::new);
+    let confirmed_password = use_state(String::new);
+    let error = use_state(|| None::<ChangePasswordError>);
+    let submitting = use_state(|| false);
+
+    let strength = password_strength(&props.username, &new_password);
+    let mismatched = !confirmed_password.is_empty() && *new_password != *confirmed_password;
+    let disabled = *submitting
+        || props.offline
+        || current_password.is_empty()
+        || new_password.is_empty()
+        || strength.is_err()
+        || *new_password != *confirmed_password;
+
+    let onclick = {
+        let onsubmit = props.onsubmit.clone();
+        let fields = (
+            current_password.clone(),
+            new_password.clone(),
+            confirmed_password.clone(),
+        );
+        let (error, submitting) = (error.clone(), submitting.clone());
+        move |_mouse_event| {
+            error.set(None);
+            submitting.set(true);
+
+            let done = {
+                let (current_password, new_password, confirmed_password) = fields.clone();
+                let (error, submitting) = (error.clone(), submitting.clone());
+                Callback::from(move |result: Result<(), ChangePasswordError>| {
+                    debug!(?result, "Changing the password finished");
+                    submitting.set(false);
+                    match result {
+                        Ok(()) => {
+                            current_password.set(String::new());
+                            new_password.set(String::new());
+                            confirmed_password.set(String::new());
+                        }
+                        Err(e) => {
+                            if e == ChangePasswordError::WrongPassword {
+                                current_password.set(String::new());
+                            }
+                            error.set(Some(e));
+                        }
+                    }
+                })
+            };
+            onsubmit.emit((fields.0.to_string(), fields.1.to_string(), done));
+        }
+    };
+
+    let (current_error, new_error, other_error) = match &*error {
+        Some(ChangePasswordError::WrongPassword) => {
+            (Some("That isn't your current password"), None, None)
+        }
+        Some(ChangePasswordError::InvalidNewPassword(reason)) => {
+            (None, Some(reason.as_str()), None)
+        }
+        Some(ChangePasswordError::Other(message)) => (None, None, Some(message.as_str())),
+        None => (None, None, None),
+    };
+
+    html! {
+        <fieldset class="change-password">
+            <legend> { "Change password" } </legend>
+            <div class="label-and-input-box">
+                <label for="currentPasswordBox"> { "Current password" } </label>
+                <input
+                    id="currentPasswordBox"
+                    type="password"
+                    autocomplete="current-password"
+                    value={(*current_password).clone()}
+                    aria-invalid={current_error.is_some().to_string()}
+                    aria-describedby={current_error.map(|_| "currentPasswordError")}
+                    disabled={*submitting}
+                    oninput={on_input(&current_password)} />
+                if let Some(error) = current_error {
+                    <div class="field-error" id="currentPasswordError"> { error } </div>
+                }
+            </div>
+            <div class="label-and-input-box">
+                <label for="newPasswordBox"> { "New password" } </label>
+                <input
+                    id="newPasswordBox"
+                    type="password"
+                    autocomplete="new-password"
+                    value={(*new_password).clone()}
+                    aria-invalid={new_error.is_some().to_string()}
+                    aria-describedby={new_error.map(|_| "newPasswordError")}
+                    disabled={*submitting}
+                    oninput={on_input(&new_password)} />
+                if let Some(error) = new_error {
+                    <div class="field-error" id="newPasswordError"> { error.to_string() } </div>
+                }
+            </div>
+            if !new_password.is_empty() {
+                { strength_meter(&strength) }
+            }
+            <div class="label-and-input-box">
+                <label for="confirmNewPasswordBox"> { "Confirm new password" } </label>
+                <input
+                    id="confirmNewPasswordBox"
+                    type="password"
+                    autocomplete="new-password"
+                    value={(*confirmed_password).clone()}
+                    aria-invalid={mismatched.to_string()}
+                    aria-describedby={mismatched.then_some("confirmNewPasswordError")}
+                    disabled={*submitting}
+                    oninput={on_input(&confirmed_password)} />
+                if mismatched {
+                    <div class="field-error" id="confirmNewPasswordError">
+                        { "The passwords don't match" }
+                    </div>
+                }
+            </div>
+            if let Some(error) = other_error {
+                <p class="error" role="alert"> { error.to_string() } </p>
+            }
+            <button {disabled} {onclick}>
+                { if *submitting { "Changing..." } else { "Change password" } }
+            </button>
+        </fieldset>
+    }
+}
@@ -1,36 +1,91 @@
 //! This module provides the [`Completion`] component.
 
+use crate::comps::markdown::render_markdown;
 use test_tracker_shared::CompletionData;
-use yew::{function_component, html, Html, Properties};
+use yew::{classes, function_component, html, Html, Properties};
+
+/// Percentages below this are in the lowest band, and shown in red.
+const MIDDLE_BAND_THRESHOLD: f64 = 50.;
+
+/// Percentages at or above this are in the highest band, and shown in green. Anything between
+/// this and [`MIDDLE_BAND_THRESHOLD`] is shown in amber.
+const HIGH_BAND_THRESHOLD: f64 = 70.;
+
+/// Get the CSS class for the band that the given percentage is in.
+fn band_class(percentage: f64) -> &'static str {
+    if percentage < MIDDLE_BAND_THRESHOLD {
+        "low"
+    } else if percentage < HIGH_BAND_THRESHOLD {
+        "middle"
+    } else {
+        "high"
+    }
+}
 
 /// The props for [`Completion`].
 #[derive(Clone, Debug, PartialEq, Eq, Properties)]
 pub struct Props {
     pub data: CompletionData,
+
+    /// Whether this is the personal best completion of its test.
+    #[prop_or_default]
+    pub best: bool,
+
+    /// Whether this is the most recent completion of its test.
+    #[prop_or_default]
+    pub latest: bool,
+
+    /// The grade that this completion got, if its test's qualification level has grade
+    /// boundaries.
+    #[prop_or_default]
+    pub grade: Option<String>,
 }
 
 /// The component to render an individual component.
 #[function_component(Completion)]
-pub fn completion(Props { data }: &Props) -> Html {
+pub fn completion(
+    Props {
+        data,
+        best,
+        latest,
+        grade,
+    }: &Props,
+) -> Html {
     let CompletionData {
         achieved_mark,
         total_marks,
         date,
         comments,
     } = data.clone();
+    // Round before picking the band, so that "(70%)" is never shown in amber
+    let percentage = data.percentage().map(f64::round);
 
     html! {
-        <div class="completion">
+        <div class={classes!("completion", best.then_some("best"))}>
+            if *best {
+                <span class="personal-best"> <span aria-hidden="true"> { "★ " } </span> { "Personal best" } </span>
+            }
+            if *latest {
+                <span class="latest"> { "Latest" } </span>
+            }
             <div class="marks">
                 <span class="achieved-mark"> { achieved_mark } </span>
                 <span class="slash"> { " / " } </span>
                 <span class="total-marks"> { total_marks } </span>
+                if let Some(percentage) = percentage {
+                    <span class={classes!("percentage", band_class(percentage))}>
+                        { format!("({percentage:.0}%)") }
+                    </span>
+                }
+                if let Some(grade) = grade {
+                    <span class="grade"> { grade.clone() } </span>
+                }
             </div>
             if let Some(date) = date {
                 <div class="date"> { date } </div>
             }
             if let Some(comments) = comments {
-                <div class="comments"> { comments } </div>
+                <div class="comments"> { render_markdown(&comments) } </div>
             }
         </div>
     }
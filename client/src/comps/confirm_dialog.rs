@@ -0,0 +1,86 @@
+//! This module provides the [`ConfirmDialog`] component.
+
+use web_sys::HtmlElement;
+use yew::{
+    events::{KeyboardEvent, MouseEvent},
+    function_component, html, use_effect_with_deps, use_node_ref, Callback, Html, Properties,
+};
+
+/// The props for [`ConfirmDialog`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The question to ask the user.
+    pub message: String,
+
+    /// The text of the button that confirms, which should say what will happen.
+    pub confirm_label: String,
+
+    /// The callback to run if the user confirms.
+    pub onconfirm: Callback<()>,
+
+    /// The callback to run if the user cancels, by clicking the cancel button, clicking outside
+    /// the dialog, or pressing Escape.
+    pub oncancel: Callback<()>,
+}
+
+/// A modal dialog which asks the user to confirm something before it happens. Nothing happens
+/// unless the user clicks the confirm button.
+#[function_component(ConfirmDialog)]
+pub fn confirm_dialog(props: &Props) -> Html {
+    let backdrop = use_node_ref();
+
+    // Focus the backdrop when the dialog opens, so that it gets the Escape key
+    {
+        let backdrop = backdrop.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(element) = backdrop.cast::<HtmlElement>() {
+                    let _ = element.focus();
+                }
+            },
+            (),
+        );
+    }
+
+    let onkeydown = {
+        let oncancel = props.oncancel.clone();
+        move |event: KeyboardEvent| {
+            if event.key() == "Escape" {
+                oncancel.emit(());
+            }
+        }
+    };
+    let onclick_backdrop = {
+        let oncancel = props.oncancel.clone();
+        move |_mouse_event| oncancel.emit(())
+    };
+    // Clicks inside the dialog mustn't reach the backdrop
+    let onclick_dialog = |event: MouseEvent| event.stop_propagation();
+    let onclick_confirm = {
+        let onconfirm = props.onconfirm.clone();
+        move |_mouse_event| onconfirm.emit(())
+    };
+    let onclick_cancel = {
+        let oncancel = props.oncancel.clone();
+        move |_mouse_event| oncancel.emit(())
+    };
+
+    html! {
+        <div
+            class="dialog-backdrop"
+            ref={backdrop}
+            tabindex="-1"
+            {onkeydown}
+            onclick={onclick_backdrop}>
+            <div class="dialog" role="alertdialog" onclick={onclick_dialog}>
+                <p> { props.message.clone() } </p>
+                <div class="buttons">
+                    <button class="confirm" onclick={onclick_confirm}>
+                        { props.confirm_label.clone() }
+                    </button>
+                    <button onclick={onclick_cancel}> { "Cancel" } </button>
+                </div>
+            </div>
+        </div>
+    }
+}
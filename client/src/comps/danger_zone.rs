@@ -0,0 +1,49 @@
+//! This module provides the [`DangerZone`] component.
+
+use crate::comps::{delete_account_dialog::DeleteAccountCallback, DeleteAccountDialog};
+use yew::{function_component, html, use_state, Html, Properties};
+
+/// The props for [`DangerZone`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The username of the user who's logged in.
+    pub username: String,
+
+    /// The callback to delete the account, once the user has confirmed it.
+    pub ondeleteaccount: DeleteAccountCallback,
+
+    /// Whether the browser is offline, in which case the account can't be deleted, so the button
+    /// is disabled.
+    #[prop_or_default]
+    pub offline: bool,
+}
+
+/// A section of the settings for things that can't be undone, which is just deleting the account.
+/// Nothing happens until the user confirms it in a [`DeleteAccountDialog`].
+#[function_component(DangerZone)]
+pub fn danger_zone(props: &Props) -> Html {
+    let confirming = use_state(|| false);
+
+    let onclick = {
+        let confirming = confirming.clone();
+        move |_mouse_event| confirming.set(true)
+    };
+    let oncancel = {
+        let confirming = confirming.clone();
+        move |()| confirming.set(false)
+    };
+
+    html! {
+        <fieldset class="danger-zone">
+            <legend> { "Danger zone" } </legend>
+            <p> { "Deleting your account also deletes every test and attempt in it." } </p>
+            <button class="delete-account" disabled={props.offline} {onclick}> { "Delete my account" } </button>
+            if *confirming {
+                <DeleteAccountDialog
+                    username={props.username.clone()}
+                    ondelete={props.ondeleteaccount.clone()}
+                    {oncancel} />
+            }
+        </fieldset>
+    }
+}
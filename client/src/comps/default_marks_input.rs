@@ -0,0 +1,70 @@
+//! This module provides the [`DefaultMarksInput`] component.
+
+use crate::comps::login_form::get_value_from_input_event;
+use test_tracker_shared::policy::check_marks;
+use yew::{function_component, html, use_state, Callback, Html, Properties};
+
+/// The props for [`DefaultMarksInput`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The default total marks that are set now, if any.
+    pub total_marks: Option<i32>,
+
+    /// The callback to run with the new default whenever the user changes it to something valid,
+    /// or `None` if they clear it.
+    pub onchange: Callback<Option<i32>>,
+}
+
+/// A section of the settings to set the total marks to fill in when adding the first completion of
+/// a test.
+#[function_component(DefaultMarksInput)]
+pub fn default_marks_input(props: &Props) -> Html {
+    let error = use_state(|| None::<String>);
+
+    let onchange = {
+        let (error, onchange) = (error.clone(), props.onchange.clone());
+        move |event: yew::Event| {
+            let value = get_value_from_input_event(event);
+            let value = value.trim();
+            if value.is_empty() {
+                error.set(None);
+                onchange.emit(None);
+                return;
+            }
+
+            let checked = value
+                .parse::<i32>()
+                .map_err(|_| "please enter a whole number".to_string())
+                .and_then(|total_marks| {
+                    check_marks(0, total_marks)
+                        .map(|()| total_marks)
+                        .map_err(|e| e.to_string())
+                });
+            match checked {
+                Ok(total_marks) => {
+                    error.set(None);
+                    onchange.emit(Some(total_marks));
+                }
+                Err(message) => error.set(Some(message)),
+            }
+        }
+    };
+
+    html! {
+        <fieldset class="default-marks">
+            <legend> { "Default total marks" } </legend>
+            <label for="defaultTotalMarks">
+                { "Filled in when adding the first attempt at a test" }
+            </label>
+            <input
+                id="defaultTotalMarks"
+                type="number"
+                min="1"
+                value={props.total_marks.map_or_else(String::new, |n| n.to_string())}
+                {onchange} />
+            if let Some(error) = &*error {
+                <p class="error"> { error.clone() } </p>
+            }
+        </fieldset>
+    }
+}
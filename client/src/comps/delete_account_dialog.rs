@@ -0,0 +1,161 @@
+//! This module provides the [`DeleteAccountDialog`] component.
+
+use crate::comps::login_form::get_value_from_input_event;
+use test_tracker_shared::policy::normalize_username;
+use web_sys::HtmlElement;
+use yew::{
+    events::{InputEvent, KeyboardEvent, MouseEvent},
+    function_component, html, use_effect_with_deps, use_node_ref, use_state, Callback, Html,
+    Properties,
+};
+
+/// A callback to delete the account. It takes the user's password and a callback to run with the
+/// result of deleting it, which is an error message to show if it failed.
+pub type DeleteAccountCallback = Callback<(String, Callback<Result<(), String>>)>;
+
+/// The props for [`DeleteAccountDialog`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The username of the user, which they have to type to confirm.
+    pub username: String,
+
+    /// The callback to delete the account, once the user has confirmed it.
+    pub ondelete: DeleteAccountCallback,
+
+    /// The callback to run if the user cancels, by clicking the cancel button, clicking outside
+    /// the dialog, or pressing Escape.
+    pub oncancel: Callback<()>,
+}
+
+/// A modal dialog to confirm deleting the user's account, which needs them to type their username
+/// and enter their password. The delete button stays disabled until the username matches, in the
+/// same way as it does when logging in.
+///
+/// If deleting the account fails, like because the password was wrong, then the dialog stays open
+/// and shows the error. If it works, then the app logs out, which closes it.
+#[function_component(DeleteAccountDialog)]
+pub fn delete_account_dialog(props: &Props) -> Html {
+    let backdrop = use_node_ref();
+    let typed_username = use_state(String::new);
+    let password = use_state(String::new);
+    let error = use_state(|| None::<String>);
+    let deleting = use_state(|| false);
+
+    // Focus the backdrop when the dialog opens, so that it gets the Escape key
+    {
+        let backdrop = backdrop.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(element) = backdrop.cast::<HtmlElement>() {
+                    let _ = element.focus();
+                }
+            },
+            (),
+        );
+    }
+
+    let onkeydown = {
+        let oncancel = props.oncancel.clone();
+        move |event: KeyboardEvent| {
+            if event.key() == "Escape" {
+                // This dialog opens from the settings dialog, which would close as well
+                event.stop_propagation();
+                oncancel.emit(());
+            }
+        }
+    };
+    let onclick_backdrop = {
+        let oncancel = props.oncancel.clone();
+        move |event: MouseEvent| {
+            event.stop_propagation();
+            oncancel.emit(());
+        }
+    };
+    // Clicks inside the dialog mustn't reach the backdrop
+    let onclick_dialog = |event: MouseEvent| event.stop_propagation();
+    let oninput_username = {
+        let typed_username = typed_username.clone();
+        move |event: InputEvent| typed_username.set(get_value_from_input_event(event.into()))
+    };
+    let oninput_password = {
+        let password = password.clone();
+        move |event: InputEvent| password.set(get_value_from_input_event(event.into()))
+    };
+    let onclick_delete = {
+        let ondelete = props.ondelete.clone();
+        let (password, error, deleting) = (password.clone(), error.clone(), deleting.clone());
+        move |_mouse_event| {
+            error.set(None);
+            deleting.set(true);
+
+            let done = {
+                let (error, deleting) = (error.clone(), deleting.clone());
+                Callback::from(move |result: Result<(), String>| {
+                    deleting.set(false);
+                    if let Err(message) = result {
+                        error.set(Some(message));
+                    }
+                })
+            };
+            ondelete.emit((password.to_string(), done));
+        }
+    };
+    let onclick_cancel = {
+        let oncancel = props.oncancel.clone();
+        move |_mouse_event| oncancel.emit(())
+    };
+
+    let disabled =
+        *deleting || normalize_username(&typed_username) != props.username || password.is_empty();
+
+    html! {
+        <div
+            class="dialog-backdrop"
+            ref={backdrop}
+            tabindex="-1"
+            {onkeydown}
+            onclick={onclick_backdrop}>
+            <div
+                class="dialog delete-account-dialog"
+                role="alertdialog"
+                aria-label="Delete my account"
+                onclick={onclick_dialog}>
+                <p>
+                    { "This deletes your account and every test and attempt in it, and can't be \
+                       undone. To confirm, type your username, " }
+                    <strong> { props.username.clone() } </strong>
+                    { ", and enter your password." }
+                </p>
+                <div class="label-and-input-box">
+                    <label for="deleteAccountUsernameBox"> { "Username" } </label>
+                    <input
+                        id="deleteAccountUsernameBox"
+                        type="text"
+                        autocomplete="off"
+                        value={(*typed_username).clone()}
+                        disabled={*deleting}
+                        oninput={oninput_username} />
+                </div>
+                <div class="label-and-input-box">
+                    <label for="deleteAccountPasswordBox"> { "Password" } </label>
+                    <input
+                        id="deleteAccountPasswordBox"
+                        type="password"
+                        autocomplete="current-password"
+                        value={(*password).clone()}
+                        disabled={*deleting}
+                        oninput={oninput_password} />
+                </div>
+                if let Some(error) = &*error {
+                    <p class="error" role="alert"> { error.clone() } </p>
+                }
+                <div class="buttons">
+                    <button class="confirm" {disabled} onclick={onclick_delete}>
+                        { if *deleting { "Deleting..." } else { "Delete my account" } }
+                    </button>
+                    <button disabled={*deleting} onclick={onclick_cancel}> { "Cancel" } </button>
+                </div>
+            </div>
+        </div>
+    }
+}
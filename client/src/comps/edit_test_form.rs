@@ -0,0 +1,132 @@
+//! This module provides the [`EditTestForm`] component.
+
+use crate::comps::add_test_form::{optional, text_input};
+use test_tracker_shared::{error::FieldErrors, TestData};
+use tracing::debug;
+use yew::{function_component, html, use_state, Callback, Html, Properties};
+
+/// A callback to save the changes to a test. It takes the new details and a callback to run with
+/// the result of saving them, which is an error message to show if it failed.
+pub type SaveTestCallback = Callback<(TestData, Callback<Result<(), String>>)>;
+
+/// The props for [`EditTestForm`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The ID of the test, to keep the IDs of the inputs unique when there's a form open for more
+    /// than one test.
+    pub test_id: i32,
+
+    /// The current details of the test, which the form starts with.
+    pub test: TestData,
+
+    /// The callback to save the changes, once they've passed validation.
+    pub onsave: SaveTestCallback,
+
+    /// The callback to close the form, after cancelling or after the changes have been saved.
+    pub onclose: Callback<()>,
+}
+
+/// A form to edit the details of a test, filled in with its current details.
+///
+/// While the changes are being saved, the whole form is disabled. If saving fails, then the form
+/// stays open with the user's changes and shows the error.
+#[function_component(EditTestForm)]
+pub fn edit_test_form(props: &Props) -> Html {
+    let test = &props.test;
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let subject = use_state(|| test.subject.clone());
+    let topic = use_state(|| text(&test.topic));
+    let date_or_id = use_state(|| test.date_or_id.clone());
+    let qualification_level = use_state(|| text(&test.qualification_level));
+    let exam_board = use_state(|| text(&test.exam_board));
+    let paper_link = use_state(|| text(&test.paper_link));
+    let mark_scheme_link = use_state(|| text(&test.mark_scheme_link));
+    let comments = use_state(|| text(&test.comments));
+    let errors = use_state(FieldErrors::default);
+    let save_error = use_state(|| None::<String>);
+    let saving = use_state(|| false);
+
+    let onclick_save = {
+        let (onsave, onclose) = (props.onsave.clone(), props.onclose.clone());
+        let fields = [
+            subject.clone(),
+            topic.clone(),
+            date_or_id.clone(),
+            qualification_level.clone(),
+            exam_board.clone(),
+            paper_link.clone(),
+            mark_scheme_link.clone(),
+            comments.clone(),
+        ];
+        let (errors, save_error, saving) = (errors.clone(), save_error.clone(), saving.clone());
+        move |_mouse_event| {
+            let test = TestData {
+                subject: fields[0].to_string(),
+                topic: optional(&fields[1]),
+                date_or_id: fields[2].to_string(),
+                qualification_level: optional(&fields[3]),
+                exam_board: optional(&fields[4]),
+                paper_link: optional(&fields[5]),
+                mark_scheme_link: optional(&fields[6]),
+                comments: optional(&fields[7]),
+            };
+
+            if let Err(e) = test.validate() {
+                debug!(?e, "Edited test is invalid");
+                let mut new_errors = FieldErrors::default();
+                new_errors.insert(e.field(), e.to_string());
+                errors.set(new_errors);
+                return;
+            }
+
+            errors.set(FieldErrors::default());
+            save_error.set(None);
+            saving.set(true);
+
+            let done = {
+                let (onclose, save_error, saving) =
+                    (onclose.clone(), save_error.clone(), saving.clone());
+                Callback::from(move |result: Result<(), String>| {
+                    saving.set(false);
+                    match result {
+                        Ok(()) => onclose.emit(()),
+                        Err(message) => save_error.set(Some(message)),
+                    }
+                })
+            };
+            onsave.emit((test, done));
+        }
+    };
+
+    let onclick_cancel = {
+        let onclose = props.onclose.clone();
+        move |_mouse_event| onclose.emit(())
+    };
+
+    let disabled = *saving;
+    let id = |name: &str| format!("{name}{}", props.test_id);
+
+    html! {
+        <div class="form edit-test-form">
+            { text_input(&id("editSubjectBox"), "Subject", "subject", &subject, &errors, disabled) }
+            { text_input(&id("editTopicBox"), "Topic", "topic", &topic, &errors, disabled) }
+            { text_input(&id("editDateOrIdBox"), "Date or ID", "date_or_id", &date_or_id, &errors, disabled) }
+            { text_input(&id("editQualificationLevelBox"), "Qualification level", "qualification_level", &qualification_level, &errors, disabled) }
+            { text_input(&id("editExamBoardBox"), "Exam board", "exam_board", &exam_board, &errors, disabled) }
+            { text_input(&id("editPaperLinkBox"), "Paper link", "paper_link", &paper_link, &errors, disabled) }
+            { text_input(&id("editMarkSchemeLinkBox"), "Mark scheme link", "mark_scheme_link", &mark_scheme_link, &errors, disabled) }
+            { text_input(&id("editCommentsBox"), "Comments", "comments", &comments, &errors, disabled) }
+
+            if let Some(message) = &*save_error {
+                <div class="field-error"> { message.clone() } </div>
+            }
+
+            <div class="buttons">
+                <button onclick={onclick_save} {disabled}>
+                    { if disabled { "Saving..." } else { "Save" } }
+                </button>
+                <button onclick={onclick_cancel} {disabled}> { "Cancel" } </button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,40 @@
+//! This module provides the [`EmptyState`] component.
+
+use crate::comps::add_test_form::SUBJECT_BOX_ID;
+use gloo_utils::document;
+use tracing::warn;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use yew::{function_component, html, Html};
+
+/// Scroll to the form to add a test and put the cursor in its first box.
+fn open_add_test_form() {
+    match document()
+        .get_element_by_id(SUBJECT_BOX_ID)
+        .and_then(|element| element.dyn_into::<HtmlElement>().ok())
+    {
+        Some(element) => {
+            element.scroll_into_view();
+            let _ = element.focus();
+        }
+        None => warn!("Couldn't find the add test form"),
+    }
+}
+
+/// What to show instead of the list of tests when the user doesn't have any yet, so that a new
+/// account doesn't just look broken.
+#[function_component(EmptyState)]
+pub fn empty_state() -> Html {
+    html! {
+        <div class="empty-state">
+            <h2> { "No tests yet" } </h2>
+            <p>
+                { "Add the past papers and mock tests that you're doing, and then log your \
+                   attempts at them to see how your scores improve." }
+            </p>
+            <button class="primary" onclick={|_| open_add_test_form()}>
+                { "Add your first test" }
+            </button>
+        </div>
+    }
+}
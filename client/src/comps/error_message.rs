@@ -1,31 +1,76 @@
 //! This module provides a component for an error message box.
 
-use yew::{html, Component, Context, Properties};
+use crate::i18n::t;
+use gloo_timers::callback::Timeout;
+use yew::{html, Callback, Component, Context, Properties};
 
 /// The error message component itself.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct ErrorMessage;
+#[derive(Debug)]
+pub struct ErrorMessage {
+    /// The timeout that dismisses the message by itself, if
+    /// [`auto_dismiss_ms`](Props::auto_dismiss_ms) is set. Dropping this cancels it.
+    timeout: Option<Timeout>,
+}
 
 /// The properties of the error message box.
-#[derive(Clone, Debug, Eq, PartialEq, Properties)]
+#[derive(Clone, Debug, PartialEq, Properties)]
 pub struct Props {
     /// The message to give to the error message.
     pub msg: String,
+
+    /// The callback to run when the message is dismissed. If this is set, then the box gets a
+    /// close button.
+    #[prop_or_default]
+    pub on_dismiss: Option<Callback<()>>,
+
+    /// How long to wait before dismissing the message by itself, in milliseconds. This only does
+    /// anything if [`on_dismiss`](Props::on_dismiss) is set, and it starts again whenever the
+    /// message changes.
+    #[prop_or_default]
+    pub auto_dismiss_ms: Option<u32>,
+}
+
+impl ErrorMessage {
+    /// Start the timeout to dismiss the message by itself, if the props ask for one.
+    fn start_timeout(props: &Props) -> Option<Timeout> {
+        let (on_dismiss, millis) = (props.on_dismiss.clone()?, props.auto_dismiss_ms?);
+        Some(Timeout::new(millis, move || on_dismiss.emit(())))
+    }
 }
 
 impl Component for ErrorMessage {
     type Message = ();
     type Properties = Props;
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        Self {}
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            timeout: Self::start_timeout(ctx.props()),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        if ctx.props() != old_props {
+            self.timeout = Self::start_timeout(ctx.props());
+        }
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> yew::Html {
         let msg = ctx.props().msg.clone();
+        let close_button = match &ctx.props().on_dismiss {
+            Some(on_dismiss) => {
+                let onclick = on_dismiss.reform(|_mouse_event| ());
+                html! {
+                    <button class="dismiss" aria-label={t!("error.dismiss")} {onclick}> { "✕" } </button>
+                }
+            }
+            None => html! {},
+        };
+
         html! {
-            <div class="error-message">
-                <h4> {"ERROR:"} </h4>
+            <div class="error-message" role="alert">
+                {close_button}
+                <h4> { t!("error.title") } </h4>
                 {msg}
             </div>
         }
@@ -0,0 +1,119 @@
+//! This module provides the [`ExamCountdown`] component.
+
+use crate::{
+    comps::login_form::get_value_from_input_event,
+    web::{parse_date_input, today},
+};
+use test_tracker_shared::countdown::{upcoming_exams, ExamDate};
+use yew::{classes, function_component, html, use_state, Callback, Html, InputEvent, Properties};
+
+/// The props for [`ExamCountdown`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// Every exam that the user has entered, including any that have already happened.
+    pub exams: Vec<ExamDate>,
+
+    /// The callback to run with the new list of exams whenever the user changes it.
+    pub onchange: Callback<Vec<ExamDate>>,
+}
+
+/// A strip at the top of the main screen that counts down the days until each upcoming exam,
+/// soonest first, with a collapsible list to add and remove exams. Exams that have already
+/// happened aren't shown in the countdown, but stay in the list until they're removed.
+#[function_component(ExamCountdown)]
+pub fn exam_countdown(Props { exams, onchange }: &Props) -> Html {
+    let new_name = use_state(String::new);
+    let new_date = use_state(String::new);
+
+    let upcoming = upcoming_exams(exams, today());
+    let countdowns: Html = upcoming
+        .iter()
+        .map(|countdown| {
+            html! {
+                <li class={classes!("exam", (countdown.days <= 1).then_some("imminent"))}>
+                    <span class="name"> { countdown.exam.name.clone() } </span>
+                    <span class="days"> { countdown.label() } </span>
+                </li>
+            }
+        })
+        .collect();
+
+    let rows: Html = exams
+        .iter()
+        .enumerate()
+        .map(|(index, exam)| {
+            let onclick = {
+                let (exams, onchange) = (exams.clone(), onchange.clone());
+                move |_mouse_event| {
+                    let mut exams = exams.clone();
+                    exams.remove(index);
+                    onchange.emit(exams);
+                }
+            };
+
+            html! {
+                <li>
+                    { format!("{} on {}", exam.name, exam.date) }
+                    <button aria-label={format!("Remove {}", exam.name)} {onclick}> { "✕" } </button>
+                </li>
+            }
+        })
+        .collect();
+
+    let oninput_name = {
+        let new_name = new_name.clone();
+        move |event: InputEvent| new_name.set(get_value_from_input_event(event.into()))
+    };
+    let onchange_date = {
+        let new_date = new_date.clone();
+        move |event: yew::Event| new_date.set(get_value_from_input_event(event))
+    };
+    let date = parse_date_input(&new_date);
+    let onclick_add = {
+        let (new_name, new_date) = (new_name.clone(), new_date.clone());
+        let (exams, onchange) = (exams.clone(), onchange.clone());
+        move |_mouse_event| {
+            let Some(date) = date else {
+                return;
+            };
+            let mut exams = exams.clone();
+            exams.push(ExamDate {
+                name: new_name.trim().to_string(),
+                date,
+            });
+            onchange.emit(exams);
+            new_name.set(String::new());
+            new_date.set(String::new());
+        }
+    };
+
+    html! {
+        <div class="exam-countdown">
+            if !upcoming.is_empty() {
+                <ul class="countdowns"> {countdowns} </ul>
+            }
+            <details>
+                <summary> { "Exam dates" } </summary>
+                <ul class="exam-dates"> {rows} </ul>
+                <div class="new-exam">
+                    <input
+                        type="text"
+                        placeholder="Exam, like Maths Paper 1"
+                        aria-label="Exam"
+                        value={(*new_name).clone()}
+                        oninput={oninput_name} />
+                    <input
+                        type="date"
+                        aria-label="Exam date"
+                        value={(*new_date).clone()}
+                        onchange={onchange_date} />
+                    <button
+                        disabled={new_name.trim().is_empty() || date.is_none()}
+                        onclick={onclick_add}>
+                        { "Add" }
+                    </button>
+                </div>
+            </details>
+        </div>
+    }
+}
@@ -0,0 +1,148 @@
+//! This module provides the [`GradeBoundariesForm`] component.
+
+use std::collections::BTreeMap;
+use test_tracker_shared::grade::{format_boundaries, parse_boundaries, GradeBoundaries};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlInputElement;
+use yew::{function_component, html, use_state, Callback, Event, Html, InputEvent, Properties};
+
+/// The props for [`GradeBoundariesForm`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The grade boundaries that are set now.
+    pub boundaries: GradeBoundaries,
+
+    /// The callback to run with the new grade boundaries whenever the user changes them.
+    pub onchange: Callback<GradeBoundaries>,
+}
+
+/// Get the value of the input that an event came from.
+fn input_value(event: &Event) -> String {
+    event
+        .target()
+        .unwrap_throw()
+        .dyn_into::<HtmlInputElement>()
+        .unwrap_throw()
+        .value()
+}
+
+/// A section of the settings to set the grade boundaries of each qualification level, written
+/// like "A* 90, A 80, B 70", so that grades can be shown next to percentages.
+///
+/// Boundaries are saved as soon as they're valid. Otherwise, the problem is shown under them and
+/// the old ones are kept.
+#[function_component(GradeBoundariesForm)]
+pub fn grade_boundaries_form(props: &Props) -> Html {
+    let errors = use_state(BTreeMap::<String, String>::new);
+    let new_level = use_state(String::new);
+    let new_boundaries = use_state(String::new);
+
+    let rows: Html = props
+        .boundaries
+        .levels()
+        .map(|(level, boundaries)| {
+            let onchange = {
+                let (level, errors) = (level.to_string(), errors.clone());
+                let (current, onchange) = (props.boundaries.clone(), props.onchange.clone());
+                move |event: Event| {
+                    let mut new_errors = (*errors).clone();
+                    match parse_boundaries(&input_value(&event)) {
+                        Ok(boundaries) => {
+                            new_errors.remove(&level);
+                            let mut new = current.clone();
+                            new.set_level(&level, boundaries);
+                            onchange.emit(new);
+                        }
+                        Err(e) => {
+                            new_errors.insert(level.clone(), e.to_string());
+                        }
+                    }
+                    errors.set(new_errors);
+                }
+            };
+            let onclick_remove = {
+                let level = level.to_string();
+                let (current, onchange) = (props.boundaries.clone(), props.onchange.clone());
+                move |_mouse_event| {
+                    let mut new = current.clone();
+                    new.set_level(&level, vec![]);
+                    onchange.emit(new);
+                }
+            };
+
+            html! {
+                <div class="grade-level" key={level.to_string()}>
+                    <label>
+                        { level.to_string() }
+                        <input type="text" value={format_boundaries(boundaries)} {onchange} />
+                    </label>
+                    <button onclick={onclick_remove}> { "Remove" } </button>
+                    if let Some(error) = errors.get(level) {
+                        <p class="error"> { error.clone() } </p>
+                    }
+                </div>
+            }
+        })
+        .collect();
+
+    let oninput_level = {
+        let new_level = new_level.clone();
+        move |event: InputEvent| new_level.set(input_value(&event))
+    };
+    let oninput_boundaries = {
+        let new_boundaries = new_boundaries.clone();
+        move |event: InputEvent| new_boundaries.set(input_value(&event))
+    };
+    let onclick_add = {
+        let (new_level, new_boundaries, errors) =
+            (new_level.clone(), new_boundaries.clone(), errors.clone());
+        let (current, onchange) = (props.boundaries.clone(), props.onchange.clone());
+        move |_mouse_event| {
+            let mut new_errors = (*errors).clone();
+            match parse_boundaries(&new_boundaries) {
+                Ok(boundaries) => {
+                    new_errors.remove("");
+                    let mut new = current.clone();
+                    new.set_level(&new_level, boundaries);
+                    onchange.emit(new);
+                    new_level.set(String::new());
+                    new_boundaries.set(String::new());
+                }
+                Err(e) => {
+                    new_errors.insert(String::new(), e.to_string());
+                }
+            }
+            errors.set(new_errors);
+        }
+    };
+
+    html! {
+        <fieldset class="grade-boundaries">
+            <legend> { "Grade boundaries" } </legend>
+            <p> { "Set the lowest percentage for each grade, like \"A* 90, A 80, B 70\"." } </p>
+            {rows}
+            <div class="grade-level new">
+                <input
+                    type="text"
+                    placeholder="Qualification level, like A Level"
+                    aria-label="Qualification level"
+                    value={(*new_level).clone()}
+                    oninput={oninput_level} />
+                <input
+                    type="text"
+                    placeholder="A* 90, A 80, B 70"
+                    aria-label="Grade boundaries"
+                    value={(*new_boundaries).clone()}
+                    oninput={oninput_boundaries} />
+                <button
+                    disabled={new_level.trim().is_empty() || new_boundaries.trim().is_empty()}
+                    onclick={onclick_add}>
+                    { "Add" }
+                </button>
+                if let Some(error) = errors.get("") {
+                    <p class="error"> { error.clone() } </p>
+                }
+            </div>
+        </fieldset>
+    }
+}
@@ -0,0 +1,202 @@
+//! This module provides the [`ImportForm`] component.
+
+use std::collections::BTreeSet;
+use test_tracker_shared::{
+    export::rows_to_csv,
+    import::{group_rows, parse_csv, rows_from_tests, ImportRow},
+    TestAndCompletions,
+};
+use tracing::{debug, warn};
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlInputElement;
+use yew::{
+    function_component, html, platform::spawn_local, use_state, Callback, Event, Html, Properties,
+    UseStateHandle,
+};
+
+/// The props for [`ImportForm`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The callback to run with a CSV of the rows that the user picked, in the same format as the
+    /// export, once they confirm the import.
+    pub onimport: Callback<String>,
+
+    /// Whether the browser is offline, in which case the import can't be sent, so the button is
+    /// disabled.
+    #[prop_or_default]
+    pub offline: bool,
+}
+
+/// Parse the contents of a file to import, as RON if the name ends in `.ron`, or CSV otherwise.
+fn parse_file(name: &str, contents: &str) -> Result<Vec<ImportRow>, String> {
+    if name.to_lowercase().ends_with(".ron") {
+        ron::from_str::<Vec<TestAndCompletions>>(contents)
+            .map(rows_from_tests)
+            .map_err(|e| format!("Couldn't read the file: {e}"))
+    } else {
+        parse_csv(contents).map_err(|e| format!("Couldn't read line {}: {}", e.line, e.message))
+    }
+}
+
+/// Read the file that the user picked, and show its rows, or the reason it couldn't be read.
+fn read_file(
+    event: Event,
+    rows: UseStateHandle<Vec<ImportRow>>,
+    selected: UseStateHandle<BTreeSet<usize>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    let input: HtmlInputElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+    let Some(file) = input.files().and_then(|files| files.get(0)) else {
+        return;
+    };
+    let name = file.name();
+    // Clear the input, so that picking the same file again still counts as a change
+    input.set_value("");
+
+    spawn_local(async move {
+        let parsed = match gloo_file::futures::read_as_text(&gloo_file::File::from(file)).await {
+            Ok(contents) => parse_file(&name, &contents),
+            Err(e) => {
+                warn!(?e, "Couldn't read the file to import");
+                Err("Couldn't read the file".to_string())
+            }
+        };
+        debug!(?name, ok = parsed.is_ok(), "Parsed file to import");
+
+        match parsed {
+            Ok(parsed) => {
+                selected.set(
+                    parsed
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, row)| row.row.is_ok())
+                        .map(|(index, _)| index)
+                        .collect(),
+                );
+                rows.set(parsed);
+                error.set(None);
+            }
+            Err(message) => {
+                rows.set(vec![]);
+                selected.set(BTreeSet::new());
+                error.set(Some(message));
+            }
+        }
+    });
+}
+
+/// A form to import tests and attempts from a CSV or RON file. The file is parsed straight away,
+/// and every row is shown with a checkbox, or the problem with it, so that the user can see what
+/// will be imported and leave rows out before anything is sent.
+#[function_component(ImportForm)]
+pub fn import_form(props: &Props) -> Html {
+    let rows = use_state(Vec::<ImportRow>::new);
+    let selected = use_state(BTreeSet::<usize>::new);
+    let error = use_state(|| None::<String>);
+
+    let onchange = {
+        let (rows, selected, error) = (rows.clone(), selected.clone(), error.clone());
+        move |event: Event| read_file(event, rows.clone(), selected.clone(), error.clone())
+    };
+
+    let picked: Vec<_> = rows
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| selected.contains(index))
+        .filter_map(|(_, row)| row.row.as_ref().ok())
+        .collect();
+    let tests = group_rows(picked.iter().copied());
+    let attempts: usize = tests.iter().map(|(_, completions)| completions.len()).sum();
+
+    let onclick_import = {
+        let (rows, selected, onimport) = (rows.clone(), selected.clone(), props.onimport.clone());
+        let csv = rows_to_csv(picked.iter().map(|(test, completion)| (test, completion)));
+        move |_mouse_event| {
+            onimport.emit(csv.clone());
+            rows.set(vec![]);
+            selected.set(BTreeSet::new());
+        }
+    };
+    let onclick_cancel = {
+        let (rows, selected) = (rows.clone(), selected.clone());
+        move |_mouse_event| {
+            rows.set(vec![]);
+            selected.set(BTreeSet::new());
+        }
+    };
+
+    let table_rows: Html = rows
+        .iter()
+        .enumerate()
+        .map(|(index, ImportRow { line, row })| {
+            let onchange = {
+                let selected = selected.clone();
+                move |_event: Event| {
+                    let mut new = (*selected).clone();
+                    if !new.remove(&index) {
+                        new.insert(index);
+                    }
+                    selected.set(new);
+                }
+            };
+
+            match row {
+                Ok((test, completion)) => html! {
+                    <tr>
+                        <td>
+                            <input
+                                type="checkbox"
+                                aria-label={format!("Import line {line}")}
+                                checked={selected.contains(&index)}
+                                {onchange} />
+                        </td>
+                        <td> { line.to_string() } </td>
+                        <td> { test.subject.clone() } </td>
+                        <td> { test.date_or_id.clone() } </td>
+                        <td> { format!("{}/{}", completion.achieved_mark, completion.total_marks) } </td>
+                        <td> { completion.date.map(|date| date.to_string()).unwrap_or_default() } </td>
+                        <td> { completion.comments.clone().unwrap_or_default() } </td>
+                    </tr>
+                },
+                Err(message) => html! {
+                    <tr class="invalid">
+                        <td> <input type="checkbox" aria-label={format!("Line {line} can't be imported")} disabled=true /> </td>
+                        <td> { line.to_string() } </td>
+                        <td colspan="5"> { message.clone() } </td>
+                    </tr>
+                },
+            }
+        })
+        .collect();
+
+    html! {
+        <div class="import-form">
+            <label for="importFile"> { "Import from a CSV or RON file" } </label>
+            <input id="importFile" type="file" accept=".csv,.ron" {onchange} />
+            if let Some(error) = &*error {
+                <p class="error"> { error.clone() } </p>
+            }
+            if !rows.is_empty() {
+                <table class="import-preview">
+                    <thead>
+                        <tr>
+                            <th> { "Import" } </th>
+                            <th> { "Line" } </th>
+                            <th> { "Subject" } </th>
+                            <th> { "Test" } </th>
+                            <th> { "Marks" } </th>
+                            <th> { "Date" } </th>
+                            <th> { "Comments" } </th>
+                        </tr>
+                    </thead>
+                    <tbody> {table_rows} </tbody>
+                </table>
+                <p> { format!("This will import {} tests with {attempts} attempts.", tests.len()) } </p>
+                <button class="primary" disabled={attempts == 0 || props.offline} onclick={onclick_import}>
+                    { "Import" }
+                </button>
+                <button onclick={onclick_cancel}> { "Cancel" } </button>
+            }
+        </div>
+    }
+}
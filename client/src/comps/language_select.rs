@@ -0,0 +1,57 @@
+//! This module provides the [`LanguageSelect`] component.
+
+use crate::i18n::browser_language;
+use test_tracker_shared::i18n::Language;
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The props for [`LanguageSelect`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The language that's currently selected, or `None` if the browser's language is being used.
+    pub language: Option<Language>,
+
+    /// The callback to run with the new language when the user picks one.
+    pub onchange: Callback<Option<Language>>,
+}
+
+/// A section of the settings to pick the language. The first option follows the browser's
+/// language, and the rest are every language that the client can be shown in, each named in
+/// itself.
+#[function_component(LanguageSelect)]
+pub fn language_select(props: &Props) -> Html {
+    let options: Vec<Option<Language>> = [None]
+        .into_iter()
+        .chain(Language::ALL.into_iter().map(Some))
+        .collect();
+
+    let onchange = {
+        let onchange = props.onchange.clone();
+        let options = options.clone();
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a language");
+            if let Some(&language) = usize::try_from(index).ok().and_then(|i| options.get(i)) {
+                onchange.emit(language);
+            }
+        }
+    };
+
+    html! {
+        <fieldset class="language-select">
+            <legend> { "Language" } </legend>
+            <select id="languageSelect" aria-label="Language" {onchange}>
+                { for options.iter().map(|&language| {
+                    let label = match language {
+                        Some(language) => language.to_string(),
+                        None => format!("Follow the browser ({})", browser_language()),
+                    };
+                    html! { <option selected={language == props.language}> { label } </option> }
+                }) }
+            </select>
+        </fieldset>
+    }
+}
@@ -1,32 +1,114 @@
 //! This module provides the [`ListOfTestsAndCompletions`] component.
 
-use crate::comps::TestAndCompletions;
-use test_tracker_shared::TestAndCompletions as SharedTAC;
-use yew::{function_component, html, Html, Properties};
+use crate::comps::{test_and_completions::UpdateTestCallback, TestAndCompletions};
+use std::collections::BTreeSet;
+use test_tracker_shared::{
+    group::group_by_subject, settings::Settings, CompletionData, SyncedTest,
+};
+use yew::{function_component, html, use_state, Callback, Html, Properties};
 
 /// The props for [`ListOfTestsAndCompletions`].
-#[derive(Clone, Debug, PartialEq, Eq, Properties)]
+#[derive(Clone, Debug, PartialEq, Properties)]
 pub struct Props {
     /// The list of tests and completions.
-    pub list: Vec<SharedTAC>,
+    pub list: Vec<SyncedTest>,
+
+    /// Whether to group the tests under headings for their subjects, rather than showing one flat
+    /// list.
+    pub grouped: bool,
+
+    /// The IDs of the tests whose cards are expanded.
+    pub expanded: BTreeSet<i32>,
+
+    /// The callback to run with the ID of a test to expand or collapse its card.
+    pub ontoggleexpanded: Callback<i32>,
+
+    /// The callback to run with the ID of a test and a new completion of it.
+    pub onaddcompletion: Callback<(i32, CompletionData)>,
+
+    /// The callback to save new details for a test. See [`UpdateTestCallback`].
+    pub onupdatetest: UpdateTestCallback,
+
+    /// The callback to run with the ID of a test that the user has confirmed they want to delete.
+    pub ondeletetest: Callback<i32>,
+
+    /// The user's settings.
+    pub settings: Settings,
 }
 
 /// The component to render a list of tests and completions. See [`TestAndCompletions`] for an
 /// individual one.
+///
+/// In the grouped view, the subject headings stick to the top of the screen while their tests are
+/// scrolled past, and each group can be collapsed by clicking its heading.
 #[function_component(ListOfTestsAndCompletions)]
-pub fn list_of_tests_and_completions(Props { list }: &Props) -> Html {
-    let tests: Html = list
-        .iter()
-        .map(|data| {
+pub fn list_of_tests_and_completions(
+    Props {
+        list,
+        grouped,
+        expanded,
+        ontoggleexpanded,
+        onaddcompletion,
+        onupdatetest,
+        ondeletetest,
+        settings,
+    }: &Props,
+) -> Html {
+    let collapsed_groups = use_state(BTreeSet::<String>::new);
+
+    let card = |data: &SyncedTest| {
+        html! {
+            <TestAndCompletions
+                test_and_completions={data.clone()}
+                expanded={expanded.contains(&data.id)}
+                ontoggleexpanded={ontoggleexpanded.clone()}
+                onaddcompletion={onaddcompletion.clone()}
+                onupdatetest={onupdatetest.clone()}
+                ondeletetest={ondeletetest.clone()}
+                settings={settings.clone()} />
+        }
+    };
+
+    if !*grouped {
+        return html! {
+            <div class="tests-list">
+                { for list.iter().map(card) }
+            </div>
+        };
+    }
+
+    let groups: Html = group_by_subject(list)
+        .into_iter()
+        .map(|(subject, tests)| {
+            let collapsed = collapsed_groups.contains(&subject);
+            let onclick = {
+                let (subject, collapsed_groups) = (subject.clone(), collapsed_groups.clone());
+                move |_mouse_event| {
+                    let mut groups = (*collapsed_groups).clone();
+                    if !groups.remove(&subject) {
+                        groups.insert(subject.clone());
+                    }
+                    collapsed_groups.set(groups);
+                }
+            };
+
             html! {
-                <TestAndCompletions test_and_completions={data.clone()} />
+                <section class="subject-group">
+                    <button class="subject-heading" aria-expanded={(!collapsed).to_string()} {onclick}>
+                        <span class="arrow" aria-hidden="true"> { if collapsed { "▸ " } else { "▾ " } } </span>
+                        { format!("{subject} ({})", tests.len()) }
+                    </button>
+                    if !collapsed {
+                        { for tests.iter().map(card) }
+                    }
+                </section>
             }
         })
         .collect();
 
     html! {
-        <div class="tests-list">
-            {tests}
+        <div class="tests-list grouped">
+            {groups}
         </div>
     }
 }
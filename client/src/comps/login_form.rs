@@ -1,16 +1,24 @@
 //! This module handles the login form.
 
+use crate::i18n::t;
 use derive_more::From;
-use tracing::{instrument, trace};
+use test_tracker_shared::{
+    error::FieldErrors,
+    policy::{password_strength, PasswordPolicyError, PasswordStrength},
+};
+use tracing::{debug, instrument, trace};
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlElement, HtmlInputElement};
 use yew::{
-    classes, function_component, html, use_state, Callback, Component, Context, Html, Properties,
+    classes,
+    events::{InputEvent, KeyboardEvent},
+    function_component, html, use_effect_with_deps, use_node_ref, use_state, Callback, Component,
+    Context, Html, NodeRef, Properties,
 };
 
 /// Get the text value from the given input event.
 #[instrument]
-fn get_value_from_input_event(event: yew::Event) -> String {
+pub(crate) fn get_value_from_input_event(event: yew::Event) -> String {
     let event: web_sys::Event = event.dyn_into().unwrap_throw();
     let event_target = event.target().unwrap_throw();
     let target: HtmlInputElement = event_target.dyn_into().unwrap_throw();
@@ -32,6 +40,20 @@ pub struct LoginOrCreateAccountProps {
 
     /// The callback for creating a new account.
     pub onsubmit_create_account: LoginOrCreateAccountCallback,
+
+    /// The callback to show an error message, like when the passwords don't match when creating
+    /// an account.
+    pub onerror: Callback<Option<String>>,
+
+    /// Whether a login or new account request is waiting for the server, which disables
+    /// submitting another one.
+    #[prop_or_default]
+    pub in_flight: bool,
+
+    /// The errors from the server to show under each field, like a password that's too common.
+    /// Only errors for the fields in [`LOGIN_FIELDS`] belong here.
+    #[prop_or_default]
+    pub server_errors: FieldErrors,
 }
 
 /// The tabs for logging in or creating a new account.
@@ -44,21 +66,159 @@ pub enum LoginOrCreateAccountTab {
     CreateAccount,
 }
 
+/// The state of a tab's button, which follows the selected tab. See
+/// [`LoginOrCreateAccountTab::state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TabState {
+    /// Whether this is the selected tab, for `aria-selected`.
+    selected: bool,
+
+    /// The ID of the panel that this tab shows, for `aria-controls`.
+    controls: &'static str,
+
+    /// The `tabindex` of the button. Only the selected tab is in the tab order, and the arrow keys
+    /// reach the other.
+    tabindex: &'static str,
+}
+
+impl LoginOrCreateAccountTab {
+    /// The state of this tab's button while `selected` is the selected tab.
+    fn state(self, selected: Self) -> TabState {
+        let is_selected = self == selected;
+        TabState {
+            selected: is_selected,
+            controls: self.panel_id(),
+            tabindex: if is_selected { "0" } else { "-1" },
+        }
+    }
+
+    /// The ID of this tab's button.
+    fn id(self) -> &'static str {
+        match self {
+            Self::Login => "loginTab",
+            Self::CreateAccount => "createAccountTab",
+        }
+    }
+
+    /// The ID of the panel that this tab shows.
+    fn panel_id(self) -> &'static str {
+        match self {
+            Self::Login => "loginPanel",
+            Self::CreateAccount => "createAccountPanel",
+        }
+    }
+
+    /// The tab for a key pressed while a tab has focus, following the WAI-ARIA tabs pattern, or
+    /// `None` if the key doesn't move between tabs. There are only two tabs, so the arrow keys
+    /// both go to the other one.
+    fn after_key(self, key: &str) -> Option<Self> {
+        match key {
+            "ArrowLeft" | "ArrowRight" => Some(match self {
+                Self::Login => Self::CreateAccount,
+                Self::CreateAccount => Self::Login,
+            }),
+            "Home" => Some(Self::Login),
+            "End" => Some(Self::CreateAccount),
+            _ => None,
+        }
+    }
+}
+
+/// Where to put the focus after switching tabs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FocusAfterSwitch {
+    /// On the tab itself, since the user is moving between tabs with the arrow keys.
+    Tab,
+
+    /// On the first field of the new form, since the user clicked the tab to use it.
+    FirstField,
+}
+
+/// A field of the login form that can have its own error message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoginField {
+    /// The username.
+    Username,
+
+    /// The password.
+    Password,
+}
+
+impl LoginField {
+    /// The name of this field, as used in [`Error::Validation`](test_tracker_shared::Error).
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Username => "username",
+            Self::Password => "password",
+        }
+    }
+}
+
+/// The names of the fields of the login form that can have their own error message.
+pub const LOGIN_FIELDS: &[&str] = &[LoginField::Username.name(), LoginField::Password.name()];
+
+/// Check the fields of the login form before submitting them, to find any that are empty.
+fn check_fields(username: &str, password: &str) -> FieldErrors {
+    let mut errors = FieldErrors::default();
+    if username.is_empty() {
+        errors.insert(LoginField::Username.name(), t!("login.missing-username"));
+    }
+    if password.is_empty() {
+        errors.insert(LoginField::Password.name(), t!("login.missing-password"));
+    }
+    errors
+}
+
 /// A message type for [`LoginOrCreateAccountForm`] to use.
 #[derive(Clone, Debug, Eq, PartialEq, From)]
 pub enum LoginOrCreateAccountMsg {
-    /// Change to the specified tab.
+    /// Change to the specified tab, because the user clicked it.
     ChangeTab(LoginOrCreateAccountTab),
 
-    /// Submit a login or create account request with the given parameters.
+    /// The user pressed the given key while a tab had focus, which might move to another tab.
+    #[from(ignore)]
+    TabKeyDown(String),
+
+    /// The user has typed in the username field. The username is kept here rather than in the
+    /// form, so that it's kept when switching tabs.
+    #[from(ignore)]
+    SetUsername(String),
+
+    /// Submit a login or create account request with the given parameters, unless a field is
+    /// empty.
     Submit(LoginOrCreateAccountTab, (String, String, bool)),
+
+    /// The user has edited the given field, so its error no longer applies.
+    ClearFieldError(LoginField),
+
+    /// Show an error under the given field, like a new password that's not allowed.
+    #[from(ignore)]
+    SetFieldError(LoginField, String),
 }
 
 /// A component to manage logging in and creating accounts, with the options presented in tabs.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The tabs follow the WAI-ARIA tabs pattern, so screen readers announce which one is selected,
+/// and the arrow keys move between them. Each tab gets a fresh form, so passwords aren't carried
+/// over, but the username is.
+#[derive(Clone, Debug, PartialEq)]
 pub struct LoginOrCreateAccountForm {
     /// Which tab is currently selected.
     tab: LoginOrCreateAccountTab,
+
+    /// The username that's been typed, in either tab.
+    username: String,
+
+    /// The errors to show under each field, including the ones from the server. Errors that aren't
+    /// about a field are shown by the app instead.
+    field_errors: FieldErrors,
+
+    /// Where to put the focus once the tab that was just switched to has rendered, or `None` if
+    /// the tab hasn't just been switched.
+    focus: Option<FocusAfterSwitch>,
+
+    /// The buttons for the login tab and the create account tab, to focus them.
+    tab_refs: (NodeRef, NodeRef),
 }
 
 /// Create a callback for the given tab. This callback sends a [`LoginOrCreateAccountMsg`] to
@@ -75,19 +235,73 @@ fn create_onsubmit_callback(
 }
 
 impl LoginOrCreateAccountForm {
-    /// View the login tab.
-    fn view_login_tab(&self, ctx: &Context<Self>) -> Html {
-        let onsubmit = create_onsubmit_callback(ctx, LoginOrCreateAccountTab::Login);
+    /// View the form for the selected tab. It's keyed by the tab, so that switching tabs starts a
+    /// new form.
+    fn view_form(&self, ctx: &Context<Self>) -> Html {
+        let tab = self.tab;
+        let onsubmit = create_onsubmit_callback(ctx, tab);
+        let onerror = ctx.props().onerror.clone();
+        let onedit = ctx
+            .link()
+            .callback(LoginOrCreateAccountMsg::ClearFieldError);
+        let onfielderror = ctx
+            .link()
+            .callback(|(field, error)| LoginOrCreateAccountMsg::SetFieldError(field, error));
+        let onusernameinput = ctx.link().callback(LoginOrCreateAccountMsg::SetUsername);
+        let field_errors = self.field_errors.clone();
+        let (title, creating_account) = match tab {
+            LoginOrCreateAccountTab::Login => (t!("login.login"), false),
+            LoginOrCreateAccountTab::CreateAccount => (t!("login.create-account"), true),
+        };
+
         html! {
-            <InternalLoginForm {onsubmit} title={"Login"} />
+            <InternalLoginForm
+                key={tab.id()}
+                {onsubmit}
+                {onerror}
+                {onedit}
+                {onfielderror}
+                {onusernameinput}
+                {field_errors}
+                {title}
+                {creating_account}
+                username={self.username.clone()}
+                in_flight={ctx.props().in_flight}
+                autofocus={self.focus == Some(FocusAfterSwitch::FirstField)} />
         }
     }
 
-    /// View the create account tab.
-    fn view_create_account_tab(&self, ctx: &Context<Self>) -> Html {
-        let onsubmit = create_onsubmit_callback(ctx, LoginOrCreateAccountTab::CreateAccount);
+    /// View the button for the given tab.
+    fn view_tab(&self, ctx: &Context<Self>, tab: LoginOrCreateAccountTab) -> Html {
+        let TabState {
+            selected,
+            controls,
+            tabindex,
+        } = tab.state(self.tab);
+        let node_ref = match tab {
+            LoginOrCreateAccountTab::Login => self.tab_refs.0.clone(),
+            LoginOrCreateAccountTab::CreateAccount => self.tab_refs.1.clone(),
+        };
+        let label = match tab {
+            LoginOrCreateAccountTab::Login => t!("login.login"),
+            LoginOrCreateAccountTab::CreateAccount => t!("login.create-account"),
+        };
+        let onclick = ctx
+            .link()
+            .callback(move |_event| LoginOrCreateAccountMsg::ChangeTab(tab));
+
         html! {
-            <InternalLoginForm {onsubmit} title={"Create account"} />
+            <button
+                class={classes!("tab", selected.then_some("selected"))}
+                id={tab.id()}
+                ref={node_ref}
+                role="tab"
+                aria-selected={selected.to_string()}
+                aria-controls={controls}
+                {tabindex}
+                {onclick}>
+                { label }
+            </button>
         }
     }
 }
@@ -99,56 +313,101 @@ impl Component for LoginOrCreateAccountForm {
     fn create(_ctx: &Context<Self>) -> Self {
         Self {
             tab: LoginOrCreateAccountTab::Login,
+            username: String::new(),
+            field_errors: FieldErrors::default(),
+            focus: None,
+            tab_refs: (NodeRef::default(), NodeRef::default()),
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let form = match self.tab {
-            LoginOrCreateAccountTab::Login => self.view_login_tab(ctx),
-            LoginOrCreateAccountTab::CreateAccount => self.view_create_account_tab(ctx),
-        };
-
-        let login_tab = ctx
-            .link()
-            .callback(|_event| LoginOrCreateAccountMsg::ChangeTab(LoginOrCreateAccountTab::Login));
-        let create_account_tab = ctx.link().callback(|_event| {
-            LoginOrCreateAccountMsg::ChangeTab(LoginOrCreateAccountTab::CreateAccount)
+        let onkeydown = ctx.link().batch_callback(|event: KeyboardEvent| {
+            let key = event.key();
+            if !matches!(key.as_str(), "ArrowLeft" | "ArrowRight" | "Home" | "End") {
+                return None;
+            }
+            // Stop the arrow keys from scrolling the page as well
+            event.prevent_default();
+            Some(LoginOrCreateAccountMsg::TabKeyDown(key))
         });
 
-        let (login_selected, create_account_selected) = match self.tab {
-            LoginOrCreateAccountTab::Login => (Some("selected"), None),
-            LoginOrCreateAccountTab::CreateAccount => (None, Some("selected")),
-        };
-
         html! {
             <div class="login-or-create-account-form">
-                <div class="tabs">
-                    <button
-                        class={classes!("tab", login_selected)}
-                        onclick={login_tab}
-                    >
-                        { "Login" }
-                    </button>
-                    <button
-                        class={classes!("tab", create_account_selected)}
-                        onclick={create_account_tab}
-                    >
-                        { "Create account" }
-                    </button>
+                <div class="tabs" role="tablist" aria-label={t!("login.login-or-create-account")} {onkeydown}>
+                    { self.view_tab(ctx, LoginOrCreateAccountTab::Login) }
+                    { self.view_tab(ctx, LoginOrCreateAccountTab::CreateAccount) }
                 </div>
 
-                {form}
+                <div
+                    id={self.tab.panel_id()}
+                    role="tabpanel"
+                    aria-labelledby={self.tab.id()}>
+                    { self.view_form(ctx) }
+                </div>
             </div>
         }
     }
 
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        // The app clears the errors from the server whenever the form is submitted, so new ones
+        // are always a change
+        let server_errors = &ctx.props().server_errors;
+        if *server_errors != old_props.server_errors {
+            self.field_errors.merge(server_errors);
+        }
+        true
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        // Focusing the first field is done by the form itself when it's created
+        if self.focus.take() == Some(FocusAfterSwitch::Tab) {
+            let node_ref = match self.tab {
+                LoginOrCreateAccountTab::Login => &self.tab_refs.0,
+                LoginOrCreateAccountTab::CreateAccount => &self.tab_refs.1,
+            };
+            if let Some(button) = node_ref.cast::<HtmlElement>() {
+                let _ = button.focus();
+            }
+        }
+    }
+
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             LoginOrCreateAccountMsg::ChangeTab(tab) => {
+                if tab == self.tab {
+                    return false;
+                }
                 self.tab = tab;
+                self.field_errors = FieldErrors::default();
+                self.focus = Some(FocusAfterSwitch::FirstField);
+                true
+            }
+            LoginOrCreateAccountMsg::TabKeyDown(key) => {
+                let Some(tab) = self.tab.after_key(&key) else {
+                    return false;
+                };
+                if tab == self.tab {
+                    return false;
+                }
+                self.tab = tab;
+                self.field_errors = FieldErrors::default();
+                self.focus = Some(FocusAfterSwitch::Tab);
+                true
+            }
+            LoginOrCreateAccountMsg::SetUsername(username) => {
+                self.username = username;
                 true
             }
             LoginOrCreateAccountMsg::Submit(tab, params) => {
+                if ctx.props().in_flight {
+                    debug!("Not submitting the login form again while it's waiting");
+                    return false;
+                }
+                self.field_errors = check_fields(&params.0, &params.1);
+                if !self.field_errors.is_empty() {
+                    return true;
+                }
+
                 match tab {
                     LoginOrCreateAccountTab::Login => ctx.props().onsubmit_login.emit(params),
                     LoginOrCreateAccountTab::CreateAccount => {
@@ -157,6 +416,13 @@ impl Component for LoginOrCreateAccountForm {
                 };
                 true
             }
+            LoginOrCreateAccountMsg::ClearFieldError(field) => {
+                self.field_errors.remove(field.name())
+            }
+            LoginOrCreateAccountMsg::SetFieldError(field, error) => {
+                self.field_errors.insert(field.name(), error);
+                true
+            }
         }
     }
 }
@@ -170,61 +436,289 @@ struct InternalLoginProps {
 
     /// The title of this form.
     title: String,
+
+    /// Whether this form creates a new account. If it does, then it asks for the password twice
+    /// and refuses to submit unless they match, and shows how strong the password is.
+    #[prop_or_default]
+    creating_account: bool,
+
+    /// The callback to show an error message, or clear it.
+    onerror: Callback<Option<String>>,
+
+    /// The errors to show under each field.
+    field_errors: FieldErrors,
+
+    /// The callback to run when the user edits a field that has an error, to clear it.
+    onedit: Callback<LoginField>,
+
+    /// The callback to show an error under a field.
+    onfielderror: Callback<(LoginField, String)>,
+
+    /// The username, which is kept by [`LoginOrCreateAccountForm`] so that it's kept when
+    /// switching tabs.
+    username: String,
+
+    /// The callback to run with the username whenever the user types in it.
+    onusernameinput: Callback<String>,
+
+    /// Whether to focus the username field when the form appears, like after switching tabs.
+    #[prop_or_default]
+    autofocus: bool,
+
+    /// Whether the form has been submitted and is waiting for the server, which disables the
+    /// submit button and shows a spinner in it.
+    #[prop_or_default]
+    in_flight: bool,
+}
+
+/// Show how strong a new password is, or why it's not allowed.
+pub(crate) fn strength_meter(strength: &Result<PasswordStrength, PasswordPolicyError>) -> Html {
+    let (class, level, text) = match strength {
+        Err(e) => ("invalid", 0, e.to_string()),
+        Ok(PasswordStrength::VeryWeak) => ("very-weak", 1, t!("password.very-weak")),
+        Ok(PasswordStrength::Weak) => ("weak", 2, t!("password.weak")),
+        Ok(PasswordStrength::Fair) => ("fair", 3, t!("password.fair")),
+        Ok(PasswordStrength::Strong) => ("strong", 4, t!("password.strong")),
+    };
+
+    html! {
+        <div class={classes!("password-strength", class)} aria-live="polite">
+            <meter min="0" max="4" value={level.to_string()} />
+            <span class="strength-text"> { text } </span>
+        </div>
+    }
 }
 
 /// An implementation detail for ease of creating login-like forms.
 #[function_component(InternalLoginForm)]
 fn internal_login_form(props: &InternalLoginProps) -> Html {
-    let username = use_state(|| String::new());
-    let password = use_state(|| String::new());
+    let username_ref = use_node_ref();
+    let password = use_state(String::new);
+    let confirmed_password = use_state(String::new);
+    // Whether the user has already been warned that their password is very weak, so the next
+    // click submits it anyway
+    let weak_acknowledged = use_state(|| false);
     let remember_me = use_state(|| false);
 
-    let on_username_changed = {
-        let username = username.clone();
-        move |event: yew::Event| username.set(get_value_from_input_event(event))
+    // Focus the username field when the form appears, if it's asked to
+    {
+        let (username_ref, autofocus) = (username_ref.clone(), props.autofocus);
+        use_effect_with_deps(
+            move |_| {
+                if autofocus {
+                    if let Some(input) = username_ref.cast::<HtmlInputElement>() {
+                        let _ = input.focus();
+                    }
+                }
+            },
+            (),
+        );
+    }
+
+    let on_username_input = {
+        let onusernameinput = props.onusernameinput.clone();
+        let (onedit, has_error) = (
+            props.onedit.clone(),
+            props
+                .field_errors
+                .get(LoginField::Username.name())
+                .is_some(),
+        );
+        move |event: InputEvent| {
+            onusernameinput.emit(get_value_from_input_event(event.into()));
+            if has_error {
+                onedit.emit(LoginField::Username);
+            }
+        }
     };
-    let on_password_changed = {
-        let password = password.clone();
-        move |event: yew::Event| password.set(get_value_from_input_event(event))
+    // This updates on every keystroke, so that the strength meter is live
+    let on_password_input = {
+        let (password, weak_acknowledged) = (password.clone(), weak_acknowledged.clone());
+        let (onedit, has_error) = (
+            props.onedit.clone(),
+            props
+                .field_errors
+                .get(LoginField::Password.name())
+                .is_some(),
+        );
+        move |event: InputEvent| {
+            password.set(get_value_from_input_event(event.into()));
+            weak_acknowledged.set(false);
+            if has_error {
+                onedit.emit(LoginField::Password);
+            }
+        }
+    };
+    let on_confirmed_password_changed = {
+        let confirmed_password = confirmed_password.clone();
+        move |event: yew::Event| confirmed_password.set(get_value_from_input_event(event))
     };
     let on_checkbox_changed = {
         let remember_me = remember_me.clone();
         move |_event| remember_me.set(!*remember_me)
     };
 
+    let username = &props.username;
+    let strength = password_strength(username, &password);
+
     let onclick = {
         let props = props.clone();
+        let password = password.clone();
+        let strength = strength.clone();
+        let weak_acknowledged = weak_acknowledged.clone();
         move |_mouse_event| {
+            if props.creating_account {
+                if *password != *confirmed_password {
+                    props.onerror.emit(Some(t!("login.passwords-dont-match")));
+                    return;
+                }
+
+                match &strength {
+                    Err(e) => {
+                        props
+                            .onfielderror
+                            .emit((LoginField::Password, e.to_string()));
+                        return;
+                    }
+                    Ok(PasswordStrength::VeryWeak) if !*weak_acknowledged => {
+                        weak_acknowledged.set(true);
+                        props.onerror.emit(Some(t!("login.very-weak-password")));
+                        return;
+                    }
+                    Ok(_) => {}
+                }
+            }
+
             props
                 .onsubmit
-                .emit((username.to_string(), password.to_string(), *remember_me));
+                .emit((props.username.clone(), password.to_string(), *remember_me));
         }
     };
 
+    let username_error = props.field_errors.get(LoginField::Username.name());
+    let password_error = props.field_errors.get(LoginField::Password.name());
+
     html! {
         <div class="form">
             <h3> {props.title.clone()} </h3>
 
             <div class="label-and-input-box">
-                <label for="usernameBox"> {"Username"} </label>
-                <input id="usernameBox" type="text" name="username" onchange={on_username_changed} /><br/>
+                <label for="usernameBox"> { t!("login.username") } </label>
+                <input
+                    id="usernameBox"
+                    ref={username_ref}
+                    type="text"
+                    name="username"
+                    value={username.clone()}
+                    aria-invalid={username_error.is_some().to_string()}
+                    aria-describedby={username_error.map(|_| "usernameError")}
+                    oninput={on_username_input} />
+                if let Some(error) = username_error {
+                    <div class="field-error" id="usernameError"> { error.to_string() } </div>
+                }
             </div>
             <div class="label-and-input-box">
-                <label for="passwordBox"> {"Password"} </label>
-                <input id="passwordBox" type="password" name="password" onchange={on_password_changed} /><br/>
+                <label for="passwordBox"> { t!("login.password") } </label>
+                <input
+                    id="passwordBox"
+                    type="password"
+                    name="password"
+                    aria-invalid={password_error.is_some().to_string()}
+                    aria-describedby={password_error.map(|_| "passwordError")}
+                    oninput={on_password_input} />
+                if let Some(error) = password_error {
+                    <div class="field-error" id="passwordError"> { error.to_string() } </div>
+                }
             </div>
+            if props.creating_account && !password.is_empty() {
+                { strength_meter(&strength) }
+            }
+            if props.creating_account {
+                <div class="label-and-input-box">
+                    <label for="confirmPasswordBox"> { t!("login.confirm-password") } </label>
+                    <input
+                        id="confirmPasswordBox"
+                        type="password"
+                        name="confirm-password"
+                        onchange={on_confirmed_password_changed} /><br/>
+                </div>
+            }
 
             <div class="label-and-checkbox">
                 <input
                     type="checkbox"
                     id="remember-me"
-                    aria-label="Remember me"
+                    aria-label={t!("login.remember-me")}
                     value="Remember me"
                     onchange={on_checkbox_changed} />
-                <label for="remember-me"> { "Remember me" } </label>
+                <label for="remember-me"> { t!("login.remember-me") } </label>
             </div>
 
-            <button {onclick}> {"Submit"} </button>
+            <button class="submit" disabled={props.in_flight} aria-busy={props.in_flight.to_string()} {onclick}>
+                if props.in_flight {
+                    <span class="button-spinner" aria-hidden="true"></span>
+                }
+                if *weak_acknowledged && strength == Ok(PasswordStrength::VeryWeak) {
+                    { t!("login.use-weak-password") }
+                } else {
+                    { t!("login.submit") }
+                }
+            </button>
         </div>
     }
 }
+
+/// Tests for the state of the tabs, which doesn't need a browser.
+#[cfg(test)]
+mod tests {
+    use super::{LoginOrCreateAccountTab, TabState};
+
+    /// Both tabs, in the order that they're shown.
+    const TABS: [LoginOrCreateAccountTab; 2] = [
+        LoginOrCreateAccountTab::Login,
+        LoginOrCreateAccountTab::CreateAccount,
+    ];
+
+    #[test]
+    fn only_the_active_tab_is_selected() {
+        for active in TABS {
+            for tab in TABS {
+                let TabState {
+                    selected, tabindex, ..
+                } = tab.state(active);
+                assert_eq!(
+                    selected,
+                    tab == active,
+                    "{tab:?} while {active:?} is active"
+                );
+                assert_eq!(tabindex, if tab == active { "0" } else { "-1" });
+            }
+        }
+    }
+
+    #[test]
+    fn active_tab_controls_the_panel_that_is_shown() {
+        for active in TABS {
+            // The panel is shown with the active tab's panel ID, and is labelled by its button
+            assert_eq!(active.state(active).controls, active.panel_id());
+            assert_ne!(active.panel_id(), active.id());
+        }
+        assert_ne!(TABS[0].panel_id(), TABS[1].panel_id());
+        assert_ne!(TABS[0].id(), TABS[1].id());
+    }
+
+    #[test]
+    fn arrow_keys_move_the_selection() {
+        let (login, create_account) = (TABS[0], TABS[1]);
+        for key in ["ArrowLeft", "ArrowRight"] {
+            let active = login.after_key(key).unwrap();
+            assert_eq!(active, create_account);
+            assert!(create_account.state(active).selected);
+            assert!(!login.state(active).selected);
+            assert_eq!(create_account.after_key(key), Some(login));
+        }
+        assert_eq!(create_account.after_key("Home"), Some(login));
+        assert_eq!(login.after_key("End"), Some(create_account));
+        assert_eq!(login.after_key("Enter"), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! This module provides [`render_markdown`], for comments.
+
+use test_tracker_shared::markdown::{parse_markdown, Block, Inline};
+use yew::{html, Html};
+
+/// Render some inline elements.
+fn render_inlines(inlines: &[Inline]) -> Html {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => html! { { text.clone() } },
+            Inline::LineBreak => html! { <br /> },
+            Inline::Emphasis(children) => html! { <em> { render_inlines(children) } </em> },
+            Inline::Strong(children) => html! { <strong> { render_inlines(children) } </strong> },
+            Inline::Link { href, children } => html! {
+                <a href={href.clone()} target="_blank" rel="noopener noreferrer">
+                    { render_inlines(children) }
+                </a>
+            },
+        })
+        .collect()
+}
+
+/// Render some blocks.
+fn render_blocks(blocks: &[Block]) -> Html {
+    blocks
+        .iter()
+        .map(|block| match block {
+            Block::Paragraph(inlines) => html! { <p> { render_inlines(inlines) } </p> },
+            Block::List { start, items } => {
+                let items: Html = items
+                    .iter()
+                    .map(|item| html! { <li> { render_blocks(item) } </li> })
+                    .collect();
+                match start {
+                    Some(start) => html! { <ol start={start.to_string()}> {items} </ol> },
+                    None => html! { <ul> {items} </ul> },
+                }
+            }
+        })
+        .collect()
+}
+
+/// Render some text with basic Markdown formatting, like emphasis, lists, and links. Every element
+/// is built from the parsed tree rather than set as inner HTML, so raw HTML in the text is shown
+/// as it was written. See [`test_tracker_shared::markdown`].
+pub fn render_markdown(text: &str) -> Html {
+    render_blocks(&parse_markdown(text))
+}
@@ -2,15 +2,58 @@
 
 #![allow(non_camel_case_types)]
 
+pub mod add_completion_form;
+pub mod add_test_form;
+pub mod change_password_form;
 pub mod completion;
+pub mod confirm_dialog;
+pub mod danger_zone;
+pub mod default_marks_input;
+pub mod delete_account_dialog;
+pub mod edit_test_form;
+pub mod empty_state;
 pub mod error_message;
+pub mod exam_countdown;
+pub mod grade_boundaries_form;
+pub mod import_form;
+pub mod language_select;
 pub mod list_of_tests_and_completions;
 pub mod login_form;
+pub mod markdown;
 pub mod navbar;
+pub mod offline_banner;
+pub mod page_controls;
+pub mod print_summary;
+pub mod score_chart;
+pub mod search_box;
+pub mod settings_dialog;
+pub mod sort_select;
+pub mod spinner;
+pub mod startup_error;
+pub mod streak_panel;
+pub mod streak_unit_select;
+pub mod subject_filter;
+pub mod subject_summary;
 pub mod test_and_completions;
+pub mod test_page;
+pub mod theme_select;
+pub mod toast;
+pub mod trend_chart;
 
 pub use self::{
-    completion::Completion, error_message::ErrorMessage,
-    list_of_tests_and_completions::ListOfTestsAndCompletions, login_form::LoginOrCreateAccountForm,
-    navbar::Navbar, test_and_completions::TestAndCompletions,
+    add_completion_form::AddCompletionForm, add_test_form::AddTestForm,
+    change_password_form::ChangePasswordForm, completion::Completion,
+    confirm_dialog::ConfirmDialog, danger_zone::DangerZone, default_marks_input::DefaultMarksInput,
+    delete_account_dialog::DeleteAccountDialog, edit_test_form::EditTestForm,
+    empty_state::EmptyState, error_message::ErrorMessage, exam_countdown::ExamCountdown,
+    grade_boundaries_form::GradeBoundariesForm, import_form::ImportForm,
+    language_select::LanguageSelect, list_of_tests_and_completions::ListOfTestsAndCompletions,
+    login_form::LoginOrCreateAccountForm, navbar::Navbar, offline_banner::OfflineBanner,
+    page_controls::PageControls, print_summary::PrintSummary, score_chart::ScoreChart,
+    search_box::SearchBox, settings_dialog::SettingsDialog, sort_select::SortSelect,
+    spinner::Spinner, startup_error::StartupError, streak_panel::StreakPanel,
+    streak_unit_select::StreakUnitSelect, subject_filter::SubjectFilter,
+    subject_summary::SubjectSummaryPanel, test_and_completions::TestAndCompletions,
+    test_page::TestNotFound, test_page::TestPage, theme_select::ThemeSelect, toast::Toast,
+    trend_chart::TrendChart,
 };
@@ -1,142 +1,205 @@
 //! This module provides the component for the navbar.
 
-use crate::{web::local_storage, STORAGE_KEY_DARK_MODE};
-use gloo_utils::body;
+use crate::i18n::{t, tn};
+use gloo_utils::{body, window};
 use std::fmt;
-use tracing::{debug, instrument, trace};
+use test_tracker_shared::{
+    i18n::Language,
+    streak::StreakUnit,
+    theme::{DarkMode, ThemePreference},
+};
+use tracing::{debug, instrument, trace, warn};
 use tracing_unwrap::ResultExt;
-use wasm_bindgen::JsValue;
-use yew::{html, Component, Html};
-
-/// Dark mode or light mode.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum DarkMode {
-    /// Light mode.
-    Light,
-    /// Dark mode.
-    Dark,
-}
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{MediaQueryList, MediaQueryListEvent};
+use yew::{html, Callback, Component, Html, Properties};
 
-impl Default for DarkMode {
-    fn default() -> Self {
-        Self::Light
-    }
-}
+/// The media query that matches when the operating system is in dark mode.
+const PREFERS_DARK_QUERY: &str = "(prefers-color-scheme: dark)";
 
-impl From<dark_light::Mode> for DarkMode {
-    fn from(value: dark_light::Mode) -> Self {
-        match value {
-            dark_light::Mode::Dark => Self::Dark,
-            dark_light::Mode::Light => Self::Light,
-            dark_light::Mode::Default => Self::default(),
-        }
+/// Set dark mode on the body of the HTML by adding or removing the "dark" class.
+fn set_dark_mode_on_body(dark_mode: DarkMode) -> Result<(), JsValue> {
+    let class_list = body().class_list();
+    let (old, new) = match dark_mode {
+        DarkMode::Light => ("dark", "light"),
+        DarkMode::Dark => ("light", "dark"),
+    };
+
+    if class_list.contains(old) {
+        class_list.remove_1(old)?;
     }
+
+    class_list.add_1(new)?;
+
+    Ok(())
 }
 
-impl From<String> for DarkMode {
-    fn from(value: String) -> Self {
-        match value.to_lowercase() {
-            s if s == "light" => Self::Light,
-            s if s == "dark" => Self::Dark,
-            _ => Self::default(),
-        }
-    }
+/// Watches the operating system's dark mode setting, and calls a callback whenever it changes. The
+/// callback stops being called when this is dropped.
+struct SystemThemeWatcher {
+    /// The media query for [`PREFERS_DARK_QUERY`].
+    query: MediaQueryList,
+
+    /// The handler for the media query changing. This must be kept alive for as long as the
+    /// watcher.
+    _onchange: Closure<dyn FnMut(MediaQueryListEvent)>,
 }
 
-impl fmt::Display for DarkMode {
+impl fmt::Debug for SystemThemeWatcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            DarkMode::Light => "light",
-            DarkMode::Dark => "dark",
-        };
-        write!(f, "{s}")
+        f.debug_struct("SystemThemeWatcher")
+            .field("matches", &self.query.matches())
+            .finish_non_exhaustive()
     }
 }
 
-impl DarkMode {
-    /// Return the opposite mode.
-    fn other(&self) -> Self {
-        match self {
-            Self::Light => Self::Dark,
-            Self::Dark => Self::Light,
-        }
+impl Drop for SystemThemeWatcher {
+    fn drop(&mut self) {
+        self.query.set_onchange(None);
     }
 }
 
-/// Get the value of the dark mode key in `localStorage` if it's available.
-#[instrument]
-fn storage_get_dark_mode() -> Result<Option<DarkMode>, JsValue> {
-    let dark_mode = local_storage().get_item(STORAGE_KEY_DARK_MODE)?;
-    debug!(?dark_mode, "Dark mode key from local storage");
-    Ok(dark_mode.map(|mode| mode.into()))
-}
-
-/// Set the value of the dark mode key in `localStorage`.
-fn storage_set_dark_mode(dark_mode: DarkMode) -> Result<(), JsValue> {
-    local_storage().set_item(STORAGE_KEY_DARK_MODE, &dark_mode.to_string())
-}
+impl SystemThemeWatcher {
+    /// Start watching the operating system's dark mode setting, or return `None` if the browser
+    /// can't tell us about it.
+    fn new(onchange: Callback<DarkMode>) -> Option<Self> {
+        let query = match window().match_media(PREFERS_DARK_QUERY) {
+            Ok(Some(query)) => query,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!(?e, "Couldn't check the system theme");
+                return None;
+            }
+        };
 
-/// Set dark mode on the body of the HTML by adding or removing the "dark" class.
-fn set_dark_mode_on_body(dark_mode: DarkMode) -> Result<(), JsValue> {
-    let class_list = body().class_list();
-    let (old, new) = match dark_mode {
-        DarkMode::Light => ("dark", "light"),
-        DarkMode::Dark => ("light", "dark"),
-    };
+        let onchange =
+            Closure::<dyn FnMut(MediaQueryListEvent)>::new(move |event: MediaQueryListEvent| {
+                onchange.emit(event.matches().into())
+            });
+        query.set_onchange(Some(onchange.as_ref().unchecked_ref()));
 
-    if class_list.contains(old) {
-        class_list.remove_1(old)?;
+        Some(Self {
+            query,
+            _onchange: onchange,
+        })
     }
 
-    class_list.add_1(new)?;
+    /// The operating system's current mode.
+    fn mode(&self) -> DarkMode {
+        self.query.matches().into()
+    }
+}
 
-    Ok(())
+/// Describe a mode, like "dark mode", in the current language.
+fn mode_name(mode: DarkMode) -> String {
+    match mode {
+        DarkMode::Light => t!("navbar.theme-light"),
+        DarkMode::Dark => t!("navbar.theme-dark"),
+    }
 }
 
-/// Get the stored dark mode setting or detect it, and then set that on the body.
-#[instrument]
-fn init_dark_mode() -> Result<DarkMode, JsValue> {
-    let dark_mode: DarkMode = match storage_get_dark_mode() {
-        Ok(Some(mode)) => mode,
-        _ => {
-            let mode = dark_light::detect().into();
-            debug!(?mode, "Detected dark mode");
-            storage_set_dark_mode(mode)?;
-            mode
-        }
-    };
-    set_dark_mode_on_body(dark_mode)?;
-    Ok(dark_mode)
+/// Describe a theme, like "following the system", in the current language.
+fn theme_name(theme: ThemePreference) -> String {
+    match theme {
+        ThemePreference::Light => mode_name(DarkMode::Light),
+        ThemePreference::Dark => mode_name(DarkMode::Dark),
+        ThemePreference::System => t!("navbar.theme-system"),
+    }
 }
 
 /// A message to send to the navbar.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum NavbarMsg {
-    /// Toggle between light mode and dark mode
-    ToggleDarkMode,
+    /// Move on to the next theme, from following the operating system, to light mode, to dark mode,
+    /// and back again.
+    CycleTheme,
+
+    /// The operating system has changed to the given mode.
+    SystemThemeChanged(DarkMode),
 }
 
-/// A simple navbar to go at the top of the page and manage the dark/light mode toggle.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The props for [`Navbar`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The theme that the user picked, from their settings.
+    pub theme: ThemePreference,
+
+    /// The language that the UI is in, so that the navbar's text changes with it.
+    pub language: Language,
+
+    /// The callback to run with the next theme when the user clicks the theme button.
+    pub onchangetheme: Callback<ThemePreference>,
+
+    /// The callback to run when the user clicks the settings button.
+    pub onopensettings: Callback<()>,
+
+    /// The user's current practice streak, which gets a small badge if it's going. See
+    /// [`Streaks::current`](test_tracker_shared::streak::Streaks::current).
+    #[prop_or_default]
+    pub streak: u32,
+
+    /// Whether [`streak`](Props::streak) is in days or weeks.
+    #[prop_or_default]
+    pub streak_unit: StreakUnit,
+}
+
+/// A simple navbar to go at the top of the page, with the dark/light mode toggle, a button to
+/// open the settings, and the current practice streak, if there is one. The theme itself is kept
+/// in the app's settings.
+#[derive(Debug)]
 pub struct Navbar {
-    /// Are we using light mode or dark mode?
-    dark_mode: DarkMode,
+    /// The operating system's mode, or the default if the browser can't tell us.
+    system_mode: DarkMode,
+
+    /// Keeps [`system_mode`](Navbar::system_mode) up to date, if the browser supports it.
+    _system_theme_watcher: Option<SystemThemeWatcher>,
+}
+
+impl Navbar {
+    /// Are we using light mode or dark mode with the given theme?
+    fn dark_mode(&self, theme: ThemePreference) -> DarkMode {
+        theme.resolve(self.system_mode)
+    }
+
+    /// Show the current mode on the body of the HTML.
+    fn apply_dark_mode(&self, theme: ThemePreference) {
+        set_dark_mode_on_body(self.dark_mode(theme))
+            .expect_or_log("We should be able to change the dark mode class on the body");
+    }
 }
 
 impl Component for Navbar {
     type Message = NavbarMsg;
-    type Properties = ();
+    type Properties = Props;
 
-    fn create(_ctx: &yew::Context<Self>) -> Self {
-        Self {
-            dark_mode: init_dark_mode().expect_or_log("Unable to initialise dark mode"),
-        }
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        let theme = ctx.props().theme;
+        let watcher = SystemThemeWatcher::new(ctx.link().callback(NavbarMsg::SystemThemeChanged));
+        let system_mode = watcher
+            .as_ref()
+            .map(SystemThemeWatcher::mode)
+            .unwrap_or_default();
+        debug!(?theme, ?system_mode, "Initialising dark mode");
+
+        let navbar = Self {
+            system_mode,
+            _system_theme_watcher: watcher,
+        };
+        navbar.apply_dark_mode(theme);
+        navbar
+    }
+
+    fn changed(&mut self, ctx: &yew::Context<Self>, _old_props: &Self::Properties) -> bool {
+        self.apply_dark_mode(ctx.props().theme);
+        true
     }
 
     #[instrument]
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
-        trace!(?self.dark_mode, "Showing navbar");
-        let symbol: Html = match self.dark_mode {
+        let theme = ctx.props().theme;
+        let dark_mode = self.dark_mode(theme);
+        trace!(?dark_mode, "Showing navbar");
+        let symbol: Html = match dark_mode {
             DarkMode::Light => html! {
                 <svg alt="" aria-hidden="true" viewBox="0 0 24 24" width="24" height="24">
                     <path fill="currentColor" d="M12,9c1.65,0,3,1.35,3,3s-1.35,3-3,3s-3-1.35-3-3S10.35,9,12,9 M12,7c-2.76,0-5,2.24-5,5s2.24,5,5,5s5-2.24,5-5 S14.76,7,12,7L12,7z M2,13l2,0c0.55,0,1-0.45,1-1s-0.45-1-1-1l-2,0c-0.55,0-1,0.45-1,1S1.45,13,2,13z M20,13l2,0c0.55,0,1-0.45,1-1 s-0.45-1-1-1l-2,0c-0.55,0-1,0.45-1,1S19.45,13,20,13z M11,2v2c0,0.55,0.45,1,1,1s1-0.45,1-1V2c0-0.55-0.45-1-1-1S11,1.45,11,2z M11,20v2c0,0.55,0.45,1,1,1s1-0.45,1-1v-2c0-0.55-0.45-1-1-1C11.45,19,11,19.45,11,20z M5.99,4.58c-0.39-0.39-1.03-0.39-1.41,0 c-0.39,0.39-0.39,1.03,0,1.41l1.06,1.06c0.39,0.39,1.03,0.39,1.41,0s0.39-1.03,0-1.41L5.99,4.58z M18.36,16.95 c-0.39-0.39-1.03-0.39-1.41,0c-0.39,0.39-0.39,1.03,0,1.41l1.06,1.06c0.39,0.39,1.03,0.39,1.41,0c0.39-0.39,0.39-1.03,0-1.41 L18.36,16.95z M19.42,5.99c0.39-0.39,0.39-1.03,0-1.41c-0.39-0.39-1.03-0.39-1.41,0l-1.06,1.06c-0.39,0.39-0.39,1.03,0,1.41 s1.03,0.39,1.41,0L19.42,5.99z M7.05,18.36c0.39-0.39,0.39-1.03,0-1.41c-0.39-0.39-1.03-0.39-1.41,0l-1.06,1.06 c-0.39,0.39-0.39,1.03,0,1.41s1.03,0.39,1.41,0L7.05,18.36z" />
@@ -155,13 +218,29 @@ impl Component for Navbar {
             </svg>
         };
 
-        let onclick = ctx.link().callback(|_event| NavbarMsg::ToggleDarkMode);
+        let onclick = ctx.link().callback(|_event| NavbarMsg::CycleTheme);
+        let onclick_settings = {
+            let onopensettings = ctx.props().onopensettings.clone();
+            move |_mouse_event| onopensettings.emit(())
+        };
 
-        let text = format!(
-            "Toggle to {new} mode (currently {current} mode)",
-            new = self.dark_mode.other(),
-            current = self.dark_mode
+        let current = match theme {
+            ThemePreference::System => {
+                t!("navbar.theme-system-current", mode = mode_name(dark_mode))
+            }
+            theme => theme_name(theme),
+        };
+        let text = t!(
+            "navbar.switch-theme",
+            new = theme_name(theme.next()),
+            current = current,
         );
+        let settings = t!("navbar.settings");
+        let streak = ctx.props().streak;
+        let streak_text = match ctx.props().streak_unit {
+            StreakUnit::Days => tn!("navbar.streak-days", streak as usize),
+            StreakUnit::Weeks => tn!("navbar.streak-weeks", streak as usize),
+        };
 
         html! {
             <navbar>
@@ -169,25 +248,45 @@ impl Component for Navbar {
                     <span id="graduation-cap" role="img" aria-hidden="true"> { graduation_cap } </span>
                     { "TestTracker" }
                 </h1>
-                <button id="toggle-dark-mode" aria-label={text.clone()} title={text} {onclick}>
-                    { symbol }
-                </button>
+                <div class="buttons">
+                    if streak > 0 {
+                        <span class="streak" role="img" aria-label={streak_text.clone()} title={streak_text}>
+                            { format!("🔥 {streak}") }
+                        </span>
+                    }
+                    <button id="toggle-dark-mode" aria-label={text.clone()} title={text} {onclick}>
+                        { symbol }
+                        if theme == ThemePreference::System {
+                            <span class="auto" aria-hidden="true"> { "A" } </span>
+                        }
+                    </button>
+                    <button
+                        id="open-settings"
+                        aria-label={settings.clone()}
+                        title={settings}
+                        onclick={onclick_settings}>
+                        { "⚙" }
+                    </button>
+                </div>
             </navbar>
         }
     }
 
     #[instrument]
     fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        let theme = ctx.props().theme;
         match msg {
-            NavbarMsg::ToggleDarkMode => {
-                trace!(starting_mode = ?self.dark_mode, "Toggling dark mode");
-                self.dark_mode = self.dark_mode.other();
-                storage_set_dark_mode(self.dark_mode)
-                    .expect_or_log("We should be able to set the dark mode value");
-                set_dark_mode_on_body(self.dark_mode)
-                    .expect_or_log("We should be able to change the dark mode class on the body");
-                trace!(ending_mode = ?self.dark_mode, "Toggled dark mode");
-                true
+            NavbarMsg::CycleTheme => {
+                trace!(starting_theme = ?theme, "Changing theme");
+                // The app saves the new theme in the settings, and then passes it back down
+                ctx.props().onchangetheme.emit(theme.next());
+                false
+            }
+            NavbarMsg::SystemThemeChanged(mode) => {
+                debug!(?mode, "System theme changed");
+                self.system_mode = mode;
+                self.apply_dark_mode(theme);
+                theme == ThemePreference::System
             }
         }
     }
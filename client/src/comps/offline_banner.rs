@@ -0,0 +1,74 @@
+//! This module provides the [`OfflineBanner`] component.
+
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The props for [`OfflineBanner`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// Whether the browser is online.
+    pub online: bool,
+
+    /// How many changes were made offline and haven't been sent yet.
+    pub queued: usize,
+
+    /// Whether the queued changes are being sent right now.
+    pub sending: bool,
+
+    /// The callback to send the queued changes again, if sending them stopped part of the way
+    /// through.
+    pub onsend: Callback<()>,
+}
+
+/// Describe how many changes are waiting, like "3 changes".
+fn changes(count: usize) -> String {
+    match count {
+        1 => "1 change".to_string(),
+        count => format!("{count} changes"),
+    }
+}
+
+/// A banner across the top of the page for as long as the browser is offline, or there are
+/// changes from while it was offline that haven't been sent yet. It renders nothing otherwise.
+#[function_component(OfflineBanner)]
+pub fn offline_banner(props: &Props) -> Html {
+    if !props.online {
+        let waiting = match props.queued {
+            0 => String::new(),
+            count => format!(" {} waiting to be saved.", changes(count)),
+        };
+        return html! {
+            <div class="offline-banner" role="status">
+                { "You're offline, so you're seeing your tests from the last time they were \
+                   fetched. Changes to them will be saved when you're back online." }
+                { waiting }
+            </div>
+        };
+    }
+
+    if props.queued == 0 {
+        return html! {};
+    }
+
+    if props.sending {
+        html! {
+            <div class="offline-banner" role="status">
+                { format!("Saving {} that you made while offline…", changes(props.queued)) }
+            </div>
+        }
+    } else {
+        let onclick = {
+            let onsend = props.onsend.clone();
+            move |_mouse_event| onsend.emit(())
+        };
+        html! {
+            <div class="offline-banner" role="status">
+                { format!(
+                    "{} that you made while offline {} been saved yet. ",
+                    changes(props.queued),
+                    if props.queued == 1 { "hasn't" } else { "haven't" }
+                ) }
+                <button {onclick}> { "Save now" } </button>
+            </div>
+        }
+    }
+}
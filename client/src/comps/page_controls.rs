@@ -0,0 +1,68 @@
+//! This module provides the [`PageControls`] component.
+
+use test_tracker_shared::page::{Page, PageSize};
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The props for [`PageControls`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The page that's showing.
+    pub page: Page,
+
+    /// How many tests are on each page.
+    pub size: PageSize,
+
+    /// The callback to run with the index of the page to show.
+    pub onchangepage: Callback<usize>,
+
+    /// The callback to run with the new page size when the user picks one.
+    pub onchangesize: Callback<PageSize>,
+}
+
+/// Buttons to move between pages of tests, and a dropdown to pick how many to show on each.
+#[function_component(PageControls)]
+pub fn page_controls(props: &Props) -> Html {
+    let Page { index, count, .. } = props.page;
+
+    let onclick_previous = {
+        let onchangepage = props.onchangepage.clone();
+        move |_mouse_event| onchangepage.emit(index.saturating_sub(1))
+    };
+    let onclick_next = {
+        let onchangepage = props.onchangepage.clone();
+        move |_mouse_event| onchangepage.emit(index + 1)
+    };
+    let onchange = {
+        let onchangesize = props.onchangesize.clone();
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a page size");
+            if let Some(&size) = usize::try_from(index)
+                .ok()
+                .and_then(|i| PageSize::ALL.get(i))
+            {
+                onchangesize.emit(size);
+            }
+        }
+    };
+
+    html! {
+        <nav class="page-controls" aria-label="Pages of tests">
+            if count > 1 {
+                <button disabled={index == 0} onclick={onclick_previous}> { "‹ Previous" } </button>
+                <span class="page-number"> { format!("Page {} of {count}", index + 1) } </span>
+                <button disabled={index + 1 >= count} onclick={onclick_next}> { "Next ›" } </button>
+            }
+            <label for="pageSizeSelect"> { "Show" } </label>
+            <select id="pageSizeSelect" {onchange}>
+                { for PageSize::ALL.iter().map(|&size| html! {
+                    <option selected={size == props.size}> { size.label() } </option>
+                }) }
+            </select>
+        </nav>
+    }
+}
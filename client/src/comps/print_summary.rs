@@ -0,0 +1,101 @@
+//! This module provides the [`PrintSummary`] component.
+
+use test_tracker_shared::{group::group_by_subject, CompletionData, SyncedTest};
+use yew::{function_component, html, Html, Properties};
+
+/// The props for [`PrintSummary`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The tests to summarise, in the order to show them within each subject.
+    pub tests: Vec<SyncedTest>,
+}
+
+/// Format a completion's marks, like "45/80 (56%)", or a dash if there isn't one.
+fn marks(completion: Option<&CompletionData>) -> String {
+    match completion {
+        Some(completion) => match completion.percentage() {
+            Some(percentage) => format!(
+                "{}/{} ({percentage:.0}%)",
+                completion.achieved_mark, completion.total_marks
+            ),
+            None => format!("{}/{}", completion.achieved_mark, completion.total_marks),
+        },
+        None => "–".to_string(),
+    }
+}
+
+/// The most recent completion of a test. Completions without a date count as older than any with
+/// one, and ties go to the one that was added last.
+fn latest_completion(test: &SyncedTest) -> Option<&CompletionData> {
+    test.completions
+        .iter()
+        .max_by_key(|(id, completion)| (completion.date, *id))
+        .map(|(_, completion)| completion)
+}
+
+/// The dates that a test was attempted on, in order, like "2023-04-01, 2023-04-08".
+fn dates_attempted(test: &SyncedTest) -> String {
+    let mut dates: Vec<_> = test
+        .completions
+        .iter()
+        .filter_map(|(_, completion)| completion.date)
+        .collect();
+    dates.sort_unstable();
+    dates
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A compact table of tests for each subject, with the best and latest marks and the dates of
+/// every attempt, which is meant to be printed as a revision summary.
+#[function_component(PrintSummary)]
+pub fn print_summary(props: &Props) -> Html {
+    let subjects: Html = group_by_subject(&props.tests)
+        .into_iter()
+        .map(|(subject, tests)| {
+            let rows: Html = tests
+                .iter()
+                .map(|test| {
+                    let best = test.personal_best_id().and_then(|best_id| {
+                        test.completions
+                            .iter()
+                            .find(|(id, _)| *id == best_id)
+                            .map(|(_, completion)| completion)
+                    });
+
+                    html! {
+                        <tr key={test.id}>
+                            <td> { test.test.date_or_id.clone() } </td>
+                            <td> { marks(best) } </td>
+                            <td> { marks(latest_completion(test)) } </td>
+                            <td> { dates_attempted(test) } </td>
+                        </tr>
+                    }
+                })
+                .collect();
+
+            html! {
+                <section class="print-subject" key={subject.clone()}>
+                    <h2> { subject } </h2>
+                    <table>
+                        <thead>
+                            <tr>
+                                <th> { "Paper" } </th>
+                                <th> { "Best mark" } </th>
+                                <th> { "Latest mark" } </th>
+                                <th> { "Dates attempted" } </th>
+                            </tr>
+                        </thead>
+                        <tbody> {rows} </tbody>
+                    </table>
+                </section>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class="print-summary"> {subjects} </div>
+    }
+}
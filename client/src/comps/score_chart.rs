@@ -0,0 +1,53 @@
+//! This module provides the [`ScoreChart`] component.
+
+use test_tracker_shared::chart::{plot_points, ChartSize, ScorePoint};
+use yew::{function_component, html, Html, Properties};
+
+/// The props for [`ScoreChart`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The scores to plot, in order. See [`score_points`](test_tracker_shared::chart::score_points).
+    pub points: Vec<ScorePoint>,
+
+    /// The width of the chart, in pixels.
+    #[prop_or(160.)]
+    pub width: f64,
+
+    /// The height of the chart, in pixels.
+    #[prop_or(48.)]
+    pub height: f64,
+}
+
+/// A small line chart of percentages over time, with 0% at the bottom and 100% at the top. Each
+/// point shows its date and percentage when hovered over.
+#[function_component(ScoreChart)]
+pub fn score_chart(props: &Props) -> Html {
+    let size = ChartSize {
+        width: props.width,
+        height: props.height,
+        padding: 4.,
+    };
+    let coords = plot_points(&props.points, size);
+    let line = coords
+        .iter()
+        .map(|(x, y)| format!("{x:.1},{y:.1}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    html! {
+        <svg
+            class="score-chart"
+            width={props.width.to_string()}
+            height={props.height.to_string()}
+            viewBox={format!("0 0 {} {}", props.width, props.height)}
+            role="img"
+            aria-label="Scores over time">
+            <polyline class="line" points={line} />
+            { for props.points.iter().zip(&coords).map(|((date, percentage), (x, y))| html! {
+                <circle class="point" cx={format!("{x:.1}")} cy={format!("{y:.1}")} r="2.5">
+                    <title> { format!("{date}: {percentage:.0}%") } </title>
+                </circle>
+            }) }
+        </svg>
+    }
+}
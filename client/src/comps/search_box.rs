@@ -0,0 +1,89 @@
+//! This module provides the [`SearchBox`] component.
+
+use crate::comps::login_form::get_value_from_input_event;
+use std::time::Duration;
+use yew::{
+    events::InputEvent,
+    function_component, html,
+    platform::{spawn_local, time::sleep},
+    use_effect_with_deps, use_mut_ref, use_state, Callback, Html, Properties,
+};
+
+/// How long to wait after the user stops typing before searching, so that the whole list doesn't
+/// get filtered and rendered again for every keystroke.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+
+/// The props for [`SearchBox`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The search that the list is currently filtered by. If this changes from outside, like when
+    /// the search gets cleared, then the text in the box changes to match.
+    pub search: String,
+
+    /// The callback to run with the new search, once the user has stopped typing.
+    pub onchange: Callback<String>,
+}
+
+/// A text box to search the list of tests as the user types.
+#[function_component(SearchBox)]
+pub fn search_box(props: &Props) -> Html {
+    let text = use_state(|| props.search.clone());
+    // Each keystroke bumps this, so a delayed search only goes through if nothing came after it
+    let keystrokes = use_mut_ref(|| 0_u64);
+
+    {
+        let text = text.clone();
+        use_effect_with_deps(
+            move |search: &String| {
+                if *text != *search {
+                    text.set(search.clone());
+                }
+            },
+            props.search.clone(),
+        );
+    }
+
+    let oninput = {
+        let (text, keystrokes, onchange) =
+            (text.clone(), keystrokes.clone(), props.onchange.clone());
+        move |event: InputEvent| {
+            let value = get_value_from_input_event(event.into());
+            text.set(value.clone());
+
+            *keystrokes.borrow_mut() += 1;
+            let this_keystroke = *keystrokes.borrow();
+            let (keystrokes, onchange) = (keystrokes.clone(), onchange.clone());
+            spawn_local(async move {
+                sleep(DEBOUNCE_DELAY).await;
+                if *keystrokes.borrow() == this_keystroke {
+                    onchange.emit(value);
+                }
+            });
+        }
+    };
+
+    let onclick_clear = {
+        let (text, keystrokes, onchange) =
+            (text.clone(), keystrokes.clone(), props.onchange.clone());
+        move |_mouse_event| {
+            // Cancel any search that's still waiting
+            *keystrokes.borrow_mut() += 1;
+            text.set(String::new());
+            onchange.emit(String::new());
+        }
+    };
+
+    html! {
+        <div class="search-box">
+            <input
+                type="search"
+                placeholder="Search tests"
+                aria-label="Search tests"
+                value={(*text).clone()}
+                {oninput} />
+            if !text.is_empty() {
+                <button onclick={onclick_clear} aria-label="Clear search"> { "✕" } </button>
+            }
+        </div>
+    }
+}
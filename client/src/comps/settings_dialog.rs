@@ -0,0 +1,143 @@
+//! This module provides the [`SettingsDialog`] component.
+
+use crate::comps::{
+    change_password_form::ChangePasswordCallback, delete_account_dialog::DeleteAccountCallback,
+    ChangePasswordForm, DangerZone, DefaultMarksInput, GradeBoundariesForm, LanguageSelect,
+    StreakUnitSelect, ThemeSelect,
+};
+use test_tracker_shared::settings::Settings;
+use web_sys::HtmlElement;
+use yew::{
+    events::{KeyboardEvent, MouseEvent},
+    function_component, html, use_effect_with_deps, use_node_ref, Callback, Html, Properties,
+};
+
+/// The props for [`SettingsDialog`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The settings as they are now.
+    pub settings: Settings,
+
+    /// The callback to run with the new settings whenever any of them change.
+    pub onchange: Callback<Settings>,
+
+    /// The username of the user who's logged in, if anyone is. Their password can only be changed,
+    /// and their account deleted, while they're logged in.
+    pub username: Option<String>,
+
+    /// The callback to change the user's password.
+    pub onchangepassword: ChangePasswordCallback,
+
+    /// The callback to delete the user's account.
+    pub ondeleteaccount: DeleteAccountCallback,
+
+    /// Whether the browser is offline, which disables everything that has to go to the server.
+    #[prop_or_default]
+    pub offline: bool,
+
+    /// The callback to run when the user closes the dialog, by clicking the close button, clicking
+    /// outside the dialog, or pressing Escape.
+    pub onclose: Callback<()>,
+}
+
+/// Make a callback for a section of the dialog, which changes one setting and keeps the rest.
+fn change_setting<T: 'static>(props: &Props, update: fn(&mut Settings, T)) -> Callback<T> {
+    let (settings, onchange) = (props.settings.clone(), props.onchange.clone());
+    Callback::from(move |value| {
+        let mut settings = settings.clone();
+        update(&mut settings, value);
+        onchange.emit(settings);
+    })
+}
+
+/// A modal dialog with every setting, in its own section. Changes are saved straight away, so
+/// there's nothing to confirm when closing it.
+#[function_component(SettingsDialog)]
+pub fn settings_dialog(props: &Props) -> Html {
+    let backdrop = use_node_ref();
+
+    // Focus the backdrop when the dialog opens, so that it gets the Escape key
+    {
+        let backdrop = backdrop.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(element) = backdrop.cast::<HtmlElement>() {
+                    let _ = element.focus();
+                }
+            },
+            (),
+        );
+    }
+
+    let onkeydown = {
+        let onclose = props.onclose.clone();
+        move |event: KeyboardEvent| {
+            if event.key() == "Escape" {
+                onclose.emit(());
+            }
+        }
+    };
+    let onclick_backdrop = {
+        let onclose = props.onclose.clone();
+        move |_mouse_event| onclose.emit(())
+    };
+    // Clicks inside the dialog mustn't reach the backdrop
+    let onclick_dialog = |event: MouseEvent| event.stop_propagation();
+    let onclick_close = {
+        let onclose = props.onclose.clone();
+        move |_mouse_event| onclose.emit(())
+    };
+
+    let onchange_theme = change_setting(props, |settings, theme| settings.theme = theme);
+    let onchange_language = change_setting(props, |settings, language| {
+        settings.language = language;
+    });
+    let onchange_boundaries = change_setting(props, |settings, boundaries| {
+        settings.grade_boundaries = boundaries;
+    });
+    let onchange_marks = change_setting(props, |settings, total_marks| {
+        settings.default_total_marks = total_marks;
+    });
+    let onchange_streak_unit = change_setting(props, |settings, unit| {
+        settings.streak_unit = unit;
+    });
+
+    html! {
+        <div
+            class="dialog-backdrop"
+            ref={backdrop}
+            tabindex="-1"
+            {onkeydown}
+            onclick={onclick_backdrop}>
+            <div
+                class="dialog settings-dialog"
+                role="dialog"
+                aria-label="Settings"
+                onclick={onclick_dialog}>
+                <h2> { "Settings" } </h2>
+                <ThemeSelect theme={props.settings.theme} onchange={onchange_theme} />
+                <LanguageSelect language={props.settings.language} onchange={onchange_language} />
+                <GradeBoundariesForm
+                    boundaries={props.settings.grade_boundaries.clone()}
+                    onchange={onchange_boundaries} />
+                <DefaultMarksInput
+                    total_marks={props.settings.default_total_marks}
+                    onchange={onchange_marks} />
+                <StreakUnitSelect unit={props.settings.streak_unit} onchange={onchange_streak_unit} />
+                if let Some(username) = &props.username {
+                    <ChangePasswordForm
+                        username={username.clone()}
+                        onsubmit={props.onchangepassword.clone()}
+                        offline={props.offline} />
+                    <DangerZone
+                        username={username.clone()}
+                        ondeleteaccount={props.ondeleteaccount.clone()}
+                        offline={props.offline} />
+                }
+                <div class="buttons">
+                    <button onclick={onclick_close}> { "Close" } </button>
+                </div>
+            </div>
+        </div>
+    }
+}
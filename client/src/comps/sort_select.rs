@@ -0,0 +1,58 @@
+//! This module provides the [`SortSelect`] component.
+
+use test_tracker_shared::TestSort;
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The orders that the user can pick from, with the labels to show for them, in the order that
+/// they appear in the dropdown.
+const OPTIONS: [(Option<TestSort>, &str); 6] = [
+    (None, "Oldest added first"),
+    (Some(TestSort::SubjectAsc), "Subject A–Z"),
+    (Some(TestSort::RecentlyAttempted), "Most recently attempted"),
+    (Some(TestSort::BestPercentageAsc), "Lowest best score first"),
+    (
+        Some(TestSort::BestPercentageDesc),
+        "Highest best score first",
+    ),
+    (Some(TestSort::ExamDateAsc), "Oldest exam first"),
+];
+
+/// The props for [`SortSelect`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The order that's currently selected.
+    pub sort: Option<TestSort>,
+
+    /// The callback to run with the new order when the user picks one.
+    pub onchange: Callback<Option<TestSort>>,
+}
+
+/// A dropdown to pick the order of the list of tests.
+#[function_component(SortSelect)]
+pub fn sort_select(props: &Props) -> Html {
+    let onchange = {
+        let onchange = props.onchange.clone();
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a sort");
+            if let Some(&(sort, _)) = usize::try_from(index).ok().and_then(|i| OPTIONS.get(i)) {
+                onchange.emit(sort);
+            }
+        }
+    };
+
+    html! {
+        <div class="sort-select">
+            <label for="sortSelect"> { "Sort by" } </label>
+            <select id="sortSelect" {onchange}>
+                { for OPTIONS.iter().map(|&(sort, label)| html! {
+                    <option selected={sort == props.sort}> { label } </option>
+                }) }
+            </select>
+        </div>
+    }
+}
@@ -0,0 +1,14 @@
+//! This module provides the [`Spinner`] component.
+
+use yew::{function_component, html, Html};
+
+/// A spinning circle to show that something is loading.
+#[function_component(Spinner)]
+pub fn spinner() -> Html {
+    html! {
+        <div class="spinner-box" role="status">
+            <div class="spinner"></div>
+            <span class="spinner-label"> { "Loading..." } </span>
+        </div>
+    }
+}
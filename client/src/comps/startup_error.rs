@@ -0,0 +1,37 @@
+//! This module provides the [`StartupError`] component.
+
+use yew::{function_component, html, Html, Properties};
+
+/// The props for [`StartupError`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// Why the app couldn't start.
+    pub message: String,
+}
+
+/// A screen to show instead of the app when it can't start at all, like when the server URL is
+/// invalid. This is for whoever set up the client as much as for the user, so it says how to fix
+/// it.
+#[function_component(StartupError)]
+pub fn startup_error(props: &Props) -> Html {
+    let reason = format!(
+        "The client couldn't work out where the server is, because {}.",
+        props.message
+    );
+
+    html! {
+        <div id="content">
+            <div class="error-message startup-error" role="alert">
+                <h4> { "TestTracker can't start" } </h4>
+                <p> { reason } </p>
+                <p>
+                    { "If you look after this site, set the server's address in the " }
+                    <code> { "<meta name=\"test-tracker-server\">" } </code>
+                    { " tag in index.html, like " }
+                    <code> { "https://example.com:20519" } </code>
+                    { "." }
+                </p>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,48 @@
+//! This module provides the [`StreakPanel`] component.
+
+use test_tracker_shared::streak::{StreakUnit, Streaks};
+use yew::{function_component, html, Html, Properties};
+
+/// The props for [`StreakPanel`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The user's streaks. See [`streaks`](test_tracker_shared::streak::streaks).
+    pub streaks: Streaks,
+
+    /// Whether the streaks are in days or weeks.
+    pub unit: StreakUnit,
+}
+
+/// Describe a number of days or weeks, like "3 days".
+fn periods(count: u32, unit: StreakUnit) -> String {
+    match (count, unit) {
+        (1, StreakUnit::Days) => "1 day".to_string(),
+        (1, StreakUnit::Weeks) => "1 week".to_string(),
+        (count, StreakUnit::Days) => format!("{count} days"),
+        (count, StreakUnit::Weeks) => format!("{count} weeks"),
+    }
+}
+
+/// A line showing the user's current practice streak and their longest one. It renders nothing
+/// until they've logged an attempt with a date.
+#[function_component(StreakPanel)]
+pub fn streak_panel(props: &Props) -> Html {
+    let Streaks { current, longest } = props.streaks;
+    if longest == 0 {
+        return html! {};
+    }
+
+    html! {
+        <div class="streak-panel">
+            <span class="current">
+                if current > 0 {
+                    <span aria-hidden="true"> { "🔥 " } </span>
+                }
+                { format!("Current streak: {}", periods(current, props.unit)) }
+            </span>
+            <span class="longest">
+                { format!("Longest streak: {}", periods(longest, props.unit)) }
+            </span>
+        </div>
+    }
+}
@@ -0,0 +1,51 @@
+//! This module provides the [`StreakUnitSelect`] component.
+
+use test_tracker_shared::streak::StreakUnit;
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The units that the user can pick from, with the labels to show for them, in the order that
+/// they appear in the dropdown.
+const OPTIONS: [(StreakUnit, &str); 2] = [
+    (StreakUnit::Days, "Days in a row"),
+    (StreakUnit::Weeks, "Weeks in a row"),
+];
+
+/// The props for [`StreakUnitSelect`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The unit that's currently selected.
+    pub unit: StreakUnit,
+
+    /// The callback to run with the new unit when the user picks one.
+    pub onchange: Callback<StreakUnit>,
+}
+
+/// A section of the settings to pick whether practice streaks count days or weeks.
+#[function_component(StreakUnitSelect)]
+pub fn streak_unit_select(props: &Props) -> Html {
+    let onchange = {
+        let onchange = props.onchange.clone();
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a streak unit");
+            if let Some(&(unit, _)) = usize::try_from(index).ok().and_then(|i| OPTIONS.get(i)) {
+                onchange.emit(unit);
+            }
+        }
+    };
+
+    html! {
+        <fieldset class="streak-unit-select">
+            <legend> { "Practice streaks" } </legend>
+            <select id="streakUnitSelect" aria-label="Practice streaks" {onchange}>
+                { for OPTIONS.iter().map(|&(unit, label)| html! {
+                    <option selected={unit == props.unit}> { label } </option>
+                }) }
+            </select>
+        </fieldset>
+    }
+}
@@ -0,0 +1,53 @@
+//! This module provides the [`SubjectFilter`] component.
+
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The props for [`SubjectFilter`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The subjects to pick from, in the order to show them.
+    pub subjects: Vec<String>,
+
+    /// The subject that's currently selected, or `None` for all of them.
+    pub subject: Option<String>,
+
+    /// The callback to run with the new subject when the user picks one, or `None` if they pick
+    /// "All subjects".
+    pub onchange: Callback<Option<String>>,
+}
+
+/// A dropdown to only show the tests of one subject.
+#[function_component(SubjectFilter)]
+pub fn subject_filter(props: &Props) -> Html {
+    let onchange = {
+        let (subjects, onchange) = (props.subjects.clone(), props.onchange.clone());
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a subject");
+            // The first option is "All subjects", so the rest are one along
+            let subject = usize::try_from(index - 1)
+                .ok()
+                .and_then(|index| subjects.get(index))
+                .cloned();
+            onchange.emit(subject);
+        }
+    };
+
+    html! {
+        <div class="subject-filter">
+            <label for="subjectFilter"> { "Subject" } </label>
+            <select id="subjectFilter" {onchange}>
+                <option selected={props.subject.is_none()}> { "All subjects" } </option>
+                { for props.subjects.iter().map(|subject| html! {
+                    <option selected={props.subject.as_ref() == Some(subject)}>
+                        { subject.clone() }
+                    </option>
+                }) }
+            </select>
+        </div>
+    }
+}
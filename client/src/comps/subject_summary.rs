@@ -0,0 +1,87 @@
+//! This module provides the [`SubjectSummaryPanel`] component.
+
+use test_tracker_shared::summary::SubjectSummary;
+use yew::{classes, function_component, html, use_state, Callback, Html, Properties};
+
+/// How many subjects to show before the rest are hidden behind a "show more" button.
+const COLLAPSED_SUBJECTS: usize = 6;
+
+/// The props for [`SubjectSummaryPanel`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The summaries to show, in order. See
+    /// [`subject_summaries`](test_tracker_shared::summary::subject_summaries).
+    pub summaries: Vec<SubjectSummary>,
+
+    /// The subject that the list is filtered by, if any, which gets highlighted.
+    pub selected: Option<String>,
+
+    /// The callback to run with the subject to filter by when a subject is clicked, or `None` if
+    /// the selected subject is clicked again.
+    pub onselect: Callback<Option<String>>,
+}
+
+/// Format a score as a percentage, or a dash if there isn't one.
+fn percentage(score: Option<f64>) -> String {
+    match score {
+        Some(score) => format!("{:.0}%", 100. * score),
+        None => "–".to_string(),
+    }
+}
+
+/// A strip of chips, one for each subject, with the number of papers and attempts and the mean and
+/// best scores in that subject. Clicking a chip filters the list by that subject.
+#[function_component(SubjectSummaryPanel)]
+pub fn subject_summary_panel(props: &Props) -> Html {
+    let expanded = use_state(|| false);
+
+    let hidden = props.summaries.len().saturating_sub(COLLAPSED_SUBJECTS);
+    let shown = if *expanded || hidden == 0 {
+        &props.summaries[..]
+    } else {
+        &props.summaries[..COLLAPSED_SUBJECTS]
+    };
+
+    let chips: Html = shown
+        .iter()
+        .map(|summary| {
+            let selected = props.selected.as_ref() == Some(&summary.subject);
+            let onclick = {
+                let (subject, onselect) = (summary.subject.clone(), props.onselect.clone());
+                move |_mouse_event| onselect.emit((!selected).then(|| subject.clone()))
+            };
+
+            html! {
+                <button class={classes!("subject-chip", selected.then_some("selected"))} {onclick}>
+                    <span class="subject"> { summary.subject.clone() } </span>
+                    <span class="counts">
+                        { format!("{} papers, {} attempts", summary.test_count, summary.completion_count) }
+                    </span>
+                    <span class="scores">
+                        { format!(
+                            "Mean {}, best {}",
+                            percentage(summary.average_score),
+                            percentage(summary.best_score)
+                        ) }
+                    </span>
+                </button>
+            }
+        })
+        .collect();
+
+    let onclick_toggle = {
+        let expanded = expanded.clone();
+        move |_mouse_event| expanded.set(!*expanded)
+    };
+
+    html! {
+        <div class="subject-summary">
+            {chips}
+            if hidden > 0 {
+                <button class="show-more" onclick={onclick_toggle}>
+                    { if *expanded { "Show less".to_string() } else { format!("Show {hidden} more") } }
+                </button>
+            }
+        </div>
+    }
+}
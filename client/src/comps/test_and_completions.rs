@@ -1,24 +1,103 @@
 //! This module provides the [`TestAndCompletions`] component.
 
-use crate::comps::Completion;
-use test_tracker_shared::{TestAndCompletions as SharedTAC, TestData};
+use crate::{
+    comps::{
+        markdown::render_markdown, AddCompletionForm, Completion, ConfirmDialog, EditTestForm,
+        ScoreChart,
+    },
+    i18n::{t, tn},
+};
+use test_tracker_shared::{
+    chart::score_points, policy::is_web_link, route::Route, settings::Settings,
+    sort::sort_completions, CompletionData, SyncedTest, TestData,
+};
 use url::Url;
-use yew::{function_component, html, Html, Properties};
+use yew::{function_component, html, use_state, Callback, Html, Properties};
 
 /// The props for [`TestAndCompletions`].
-#[derive(Clone, Debug, PartialEq, Eq, Properties)]
+#[derive(Clone, Debug, PartialEq, Properties)]
 pub struct Props {
     /// The test and completions to be rendered by this component.
-    pub test_and_completions: SharedTAC,
+    pub test_and_completions: SyncedTest,
+
+    /// Whether the card is expanded to show the details and completions, rather than just a
+    /// summary.
+    pub expanded: bool,
+
+    /// The callback to run with the ID of the test to expand or collapse the card.
+    pub ontoggleexpanded: Callback<i32>,
+
+    /// The callback to run with the ID of the test and a new completion of it.
+    pub onaddcompletion: Callback<(i32, CompletionData)>,
+
+    /// The callback to save new details for a test. See [`UpdateTestCallback`].
+    pub onupdatetest: UpdateTestCallback,
+
+    /// The callback to run with the ID of the test once the user has confirmed that they want to
+    /// delete it.
+    pub ondeletetest: Callback<i32>,
+
+    /// The user's settings, for the grade boundaries and the default total marks.
+    pub settings: Settings,
+}
+
+/// A callback to save new details for a test. It takes the ID of the test, the new details, and a
+/// callback to run with the result of saving them, which is an error message to show if it failed.
+pub type UpdateTestCallback = Callback<(i32, TestData, Callback<Result<(), String>>)>;
+
+/// Render a stored link so that it opens in a new tab, without letting the new page reach back into
+/// this one. Only `http` and `https` links are clickable, so anything else, like a `javascript:`
+/// URL, is just shown as text with a warning.
+pub fn external_link(link: String) -> Html {
+    if is_web_link(&link) && Url::parse(link.trim()).is_ok() {
+        html! {
+            <a
+                href={link.trim().to_string()}
+                target="_blank"
+                rel="noopener noreferrer"
+                title={t!("test.opens-in-new-tab")}>
+                { link }
+                <span class="external-link-icon" aria-hidden="true"> { "↗" } </span>
+            </a>
+        }
+    } else {
+        html! {
+            <span class="unsafe-link" title={t!("test.unsafe-link")}>
+                { link }
+                <span class="warning-icon" aria-hidden="true"> { "⚠" } </span>
+            </span>
+        }
+    }
 }
 
 /// The component to a render an individual test with its completions.
+///
+/// When it's collapsed, it only shows the title, the best score, and how many attempts there have
+/// been. The arrow before the title expands or collapses it, and the title itself is a link to the
+/// test's own page.
 #[function_component(TestAndCompletions)]
 pub fn test_and_completion(
     Props {
-        test_and_completions: (test, completions),
+        test_and_completions:
+            test_and_completions @ SyncedTest {
+                id,
+                test,
+                completions,
+                ..
+            },
+        expanded,
+        ontoggleexpanded,
+        onaddcompletion,
+        onupdatetest,
+        ondeletetest,
+        settings,
     }: &Props,
 ) -> Html {
+    let editing = use_state(|| false);
+    let adding_completion = use_state(|| false);
+    let confirming_delete = use_state(|| false);
+    let best_completion_id = test_and_completions.personal_best_id();
+
     let TestData {
         subject,
         topic,
@@ -30,20 +109,72 @@ pub fn test_and_completion(
         comments,
     } = test.clone();
 
-    let completions: Html = completions
+    let default_total_marks = test_and_completions.next_total_marks(settings.default_total_marks);
+    let onsubmit = {
+        let (test_id, onaddcompletion) = (*id, onaddcompletion.clone());
+        let adding_completion = adding_completion.clone();
+        Callback::from(move |completion| {
+            adding_completion.set(false);
+            onaddcompletion.emit((test_id, completion))
+        })
+    };
+    let oncancel = {
+        let adding_completion = adding_completion.clone();
+        Callback::from(move |()| adding_completion.set(false))
+    };
+
+    let grade_of = {
+        let qualification_level = qualification_level.clone();
+        move |percentage: f64| {
+            settings
+                .grade_boundaries
+                .grade(qualification_level.as_deref(), percentage)
+                .map(str::to_string)
+        }
+    };
+
+    let mut sorted_completions = completions.clone();
+    sort_completions(&mut sorted_completions);
+    let completions_html: Html = sorted_completions
         .iter()
-        .map(|data| html! { <Completion data={data.clone()} /> })
+        .enumerate()
+        .map(|(index, (id, data))| {
+            let best = Some(*id) == best_completion_id;
+            // A badge on the only completion wouldn't tell the user anything
+            let latest = index == 0 && sorted_completions.len() > 1;
+            let grade = data.percentage().and_then(&grade_of);
+            html! { <Completion key={*id} data={data.clone()} {best} {latest} {grade} /> }
+        })
         .collect();
 
-    html! {
-        <div class="test">
-            <div class="title">
-                <span class="subject"> { subject } </span>
-                if let Some(topic) = topic {
-                    <span class="topic"> { format!(": {topic}") } </span>
-                }
-            </div>
-            <div class="content">
+    // A chart of just one point wouldn't show anything
+    let points = score_points(completions);
+    let chart = if points.len() >= 2 {
+        html! { <ScoreChart {points} /> }
+    } else {
+        html! {}
+    };
+
+    let details = if *editing {
+        let onsave = {
+            let (test_id, onupdatetest) = (*id, onupdatetest.clone());
+            Callback::from(
+                move |(test, done): (TestData, Callback<Result<(), String>>)| {
+                    onupdatetest.emit((test_id, test, done))
+                },
+            )
+        };
+        let onclose = {
+            let editing = editing.clone();
+            Callback::from(move |()| editing.set(false))
+        };
+
+        html! {
+            <EditTestForm test_id={*id} test={test.clone()} {onsave} {onclose} />
+        }
+    } else {
+        html! {
+            <>
                 <div class="date-or-id"> { date_or_id } </div>
                 if let Some(qual) = qualification_level {
                     <div class="qualification-level"> { qual } </div>
@@ -52,31 +183,197 @@ pub fn test_and_completion(
                     <div class="exam-board"> { board } </div>
                 }
                 if let Some(link) = paper_link {
-                    <div class="paper-link"> { "Paper: " }
-                        if Url::parse(&link).is_ok() {
-                            <a href={link.clone()}> { link } </a>
-                        } else {
-                            { link }
-                        }
-                    </div>
+                    <div class="paper-link"> { t!("test.paper") } { external_link(link) } </div>
                 }
                 if let Some(link) = mark_scheme_link {
-                    <div class="mark-scheme-link"> { "Mark scheme: " }
-                        if Url::parse(&link).is_ok() {
-                            <a href={link.clone()}> { link } </a>
-                        } else {
-                            { link }
-                        }
-                    </div>
+                    <div class="mark-scheme-link"> { t!("test.mark-scheme") } { external_link(link) } </div>
                 }
                 if let Some(comments) = comments {
-                    <div class="comments"> { comments } </div>
+                    <div class="comments"> { render_markdown(&comments) } </div>
                 }
+            </>
+        }
+    };
 
-                <div class="completions-list">
-                    {completions}
-                </div>
+    let onclick_toggle = {
+        let (test_id, ontoggleexpanded) = (*id, ontoggleexpanded.clone());
+        move |_mouse_event| ontoggleexpanded.emit(test_id)
+    };
+
+    // The edit form is in the expanded card, so editing expands it
+    let onclick_edit = {
+        let (test_id, expanded, ontoggleexpanded) = (*id, *expanded, ontoggleexpanded.clone());
+        let editing = editing.clone();
+        move |_mouse_event| {
+            if !expanded {
+                ontoggleexpanded.emit(test_id);
+            }
+            editing.set(true)
+        }
+    };
+
+    // The form is in the expanded card too
+    let onclick_log_attempt = {
+        let (test_id, expanded, ontoggleexpanded) = (*id, *expanded, ontoggleexpanded.clone());
+        let adding_completion = adding_completion.clone();
+        move |_mouse_event| {
+            if !expanded {
+                ontoggleexpanded.emit(test_id);
+            }
+            adding_completion.set(true)
+        }
+    };
+
+    let best_percentage = completions
+        .iter()
+        .find(|(id, _)| Some(*id) == best_completion_id)
+        .and_then(|(_, data)| data.percentage());
+    let attempts = tn!("test.attempts", completions.len());
+    let content_id = format!("testContent{id}");
+
+    let onclick_delete = {
+        let confirming_delete = confirming_delete.clone();
+        move |_mouse_event| confirming_delete.set(true)
+    };
+    let delete_dialog = if *confirming_delete {
+        let message = tn!("test.confirm-delete", completions.len());
+        let onconfirm = {
+            let (test_id, ondeletetest) = (*id, ondeletetest.clone());
+            let confirming_delete = confirming_delete.clone();
+            Callback::from(move |()| {
+                confirming_delete.set(false);
+                ondeletetest.emit(test_id);
+            })
+        };
+        let oncancel = {
+            let confirming_delete = confirming_delete.clone();
+            Callback::from(move |()| confirming_delete.set(false))
+        };
+
+        html! {
+            <ConfirmDialog {message} confirm_label={t!("test.delete")} {onconfirm} {oncancel} />
+        }
+    } else {
+        html! {}
+    };
+
+    let title = html! {
+        <>
+            <span class="subject"> { subject } </span>
+            if let Some(topic) = topic {
+                <span class="topic"> { format!(": {topic}") } </span>
+            }
+        </>
+    };
+
+    html! {
+        <div class="test">
+            <div class="title">
+                <button
+                    class="expand"
+                    title={if *expanded { t!("test.collapse") } else { t!("test.expand") }}
+                    aria-expanded={expanded.to_string()}
+                    aria-controls={content_id.clone()}
+                    onclick={onclick_toggle}>
+                    <span class="arrow" aria-hidden="true"> { if *expanded { "▾ " } else { "▸ " } } </span>
+                </button>
+                // A test that the server hasn't confirmed yet has a placeholder ID, which has no page
+                if *id < 0 {
+                    {title}
+                } else {
+                    <a class="open" href={Route::Test(*id).href()}> {title} </a>
+                }
+                if !*editing {
+                    <button class="delete" title={t!("test.delete")} onclick={onclick_delete}> { "🗑️" } </button>
+                    <button class="edit" title={t!("test.edit")} onclick={onclick_edit}> { "✏️" } </button>
+                }
+                if !*adding_completion {
+                    <button class="log-attempt" onclick={onclick_log_attempt}> { t!("test.log-attempt") } </button>
+                }
             </div>
+            {delete_dialog}
+            if *expanded {
+                <div class="content" id={content_id}>
+                    {details}
+                    {chart}
+
+                    <div class="completions-list">
+                        {completions_html}
+                    </div>
+                    if *adding_completion {
+                        <AddCompletionForm test_id={*id} {default_total_marks} {onsubmit} {oncancel} />
+                    }
+                </div>
+            } else {
+                <div class="summary" id={content_id}>
+                    <span class="date-or-id"> { test.date_or_id.clone() } </span>
+                    if let Some(percentage) = best_percentage {
+                        <span class="best"> { t!("test.best", percentage = format!("{percentage:.0}")) } </span>
+                        if let Some(grade) = grade_of(percentage) {
+                            <span class="grade"> { grade } </span>
+                        }
+                    }
+                    <span class="attempts"> { attempts } </span>
+                </div>
+            }
         </div>
     }
 }
+
+/// Tests for rendering cards, which render them to a string rather than in a browser.
+#[cfg(test)]
+mod tests {
+    use super::{Props, TestAndCompletions};
+    use test_tracker_shared::{
+        fixtures::{sample_completion, sample_test, synced_test},
+        settings::Settings,
+    };
+    use yew::{Callback, LocalServerRenderer};
+
+    /// Render a collapsed card for a sample test with the given ID and scores.
+    async fn render_card(id: i32, percentages: &[f64]) -> String {
+        let completions = percentages
+            .iter()
+            .enumerate()
+            .map(|(index, &percent)| (index as i32 + 1, sample_completion(percent)))
+            .collect();
+        let props = Props {
+            test_and_completions: synced_test(id, sample_test("Maths"), completions),
+            expanded: false,
+            ontoggleexpanded: Callback::noop(),
+            onaddcompletion: Callback::noop(),
+            onupdatetest: Callback::noop(),
+            ondeletetest: Callback::noop(),
+            settings: Settings::default(),
+        };
+        LocalServerRenderer::<TestAndCompletions>::with_props(props)
+            .render()
+            .await
+    }
+
+    #[tokio::test]
+    async fn collapsed_card_shows_the_summary() {
+        let html = render_card(7, &[40.0, 75.0, 60.0]).await;
+        let test = sample_test("Maths");
+
+        assert!(html.contains(&test.subject), "{html}");
+        assert!(html.contains(&test.date_or_id), "{html}");
+        assert!(html.contains("Best 75%"), "{html}");
+        assert!(html.contains("3 attempts"), "{html}");
+        assert!(html.contains(r##"href="#/tests/7""##), "{html}");
+    }
+
+    #[tokio::test]
+    async fn card_without_completions_has_no_best_score() {
+        let html = render_card(7, &[]).await;
+        assert!(!html.contains("Best"), "{html}");
+        assert!(html.contains("0 attempts"), "{html}");
+    }
+
+    #[tokio::test]
+    async fn unsaved_test_has_no_link_to_its_page() {
+        let html = render_card(-1, &[50.0]).await;
+        assert!(html.contains(&sample_test("Maths").subject), "{html}");
+        assert!(!html.contains("#/tests/"), "{html}");
+    }
+}
@@ -0,0 +1,291 @@
+//! This module provides the [`TestPage`] and [`TestNotFound`] components.
+
+use crate::{
+    comps::{
+        markdown::render_markdown,
+        test_and_completions::{external_link, UpdateTestCallback},
+        AddCompletionForm, ConfirmDialog, EditTestForm, ScoreChart,
+    },
+    i18n::{t, tn},
+};
+use test_tracker_shared::{
+    chart::score_points, route::Route, settings::Settings, sort::sort_completions, CompletionData,
+    SyncedTest, TestData,
+};
+use yew::{classes, function_component, html, use_state, Callback, Html, Properties};
+
+/// The width of the chart of scores on the page, in pixels. It's much bigger than the one on the
+/// card, since it has the page to itself.
+const CHART_WIDTH: f64 = 480.;
+
+/// The height of the chart of scores on the page, in pixels.
+const CHART_HEIGHT: f64 = 160.;
+
+/// The props for [`TestPage`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The test and completions to show.
+    pub test_and_completions: SyncedTest,
+
+    /// The callback to run with the ID of the test and a new completion of it.
+    pub onaddcompletion: Callback<(i32, CompletionData)>,
+
+    /// The callback to save new details for a test. See [`UpdateTestCallback`].
+    pub onupdatetest: UpdateTestCallback,
+
+    /// The callback to run with the ID of the test once the user has confirmed that they want to
+    /// delete it.
+    pub ondeletetest: Callback<i32>,
+
+    /// The user's settings, for the grade boundaries and the default total marks.
+    pub settings: Settings,
+}
+
+/// A link back to the list of tests.
+fn back_link() -> Html {
+    html! {
+        <a class="back" href={Route::List.href()}> { "← " } { t!("test-page.back") } </a>
+    }
+}
+
+/// The page of a single test, with all its details, every completion in a table, and a big chart
+/// of its scores, along with the same controls as its card. See [`Route::Test`].
+#[function_component(TestPage)]
+pub fn test_page(
+    Props {
+        test_and_completions:
+            test_and_completions @ SyncedTest {
+                id,
+                test,
+                completions,
+                ..
+            },
+        onaddcompletion,
+        onupdatetest,
+        ondeletetest,
+        settings,
+    }: &Props,
+) -> Html {
+    let editing = use_state(|| false);
+    let adding_completion = use_state(|| false);
+    let confirming_delete = use_state(|| false);
+    let best_completion_id = test_and_completions.personal_best_id();
+
+    let TestData {
+        subject,
+        topic,
+        date_or_id,
+        qualification_level,
+        exam_board,
+        paper_link,
+        mark_scheme_link,
+        comments,
+    } = test.clone();
+
+    let grade_of = |percentage: f64| {
+        settings
+            .grade_boundaries
+            .grade(qualification_level.as_deref(), percentage)
+            .map(str::to_string)
+    };
+
+    let details = if *editing {
+        let onsave = {
+            let (test_id, onupdatetest) = (*id, onupdatetest.clone());
+            Callback::from(
+                move |(test, done): (TestData, Callback<Result<(), String>>)| {
+                    onupdatetest.emit((test_id, test, done))
+                },
+            )
+        };
+        let onclose = {
+            let editing = editing.clone();
+            Callback::from(move |()| editing.set(false))
+        };
+
+        html! {
+            <EditTestForm test_id={*id} test={test.clone()} {onsave} {onclose} />
+        }
+    } else {
+        html! {
+            <>
+            <dl class="details">
+                <dt> { t!("test-page.date-or-id") } </dt>
+                <dd> { date_or_id } </dd>
+                if let Some(qual) = qualification_level.clone() {
+                    <dt> { t!("test-page.qualification-level") } </dt>
+                    <dd> { qual } </dd>
+                }
+                if let Some(board) = exam_board {
+                    <dt> { t!("test-page.exam-board") } </dt>
+                    <dd> { board } </dd>
+                }
+                if let Some(link) = paper_link {
+                    <dt> { t!("test-page.paper") } </dt>
+                    <dd> { external_link(link) } </dd>
+                }
+                if let Some(link) = mark_scheme_link {
+                    <dt> { t!("test-page.mark-scheme") } </dt>
+                    <dd> { external_link(link) } </dd>
+                }
+            </dl>
+            if let Some(comments) = comments {
+                <div class="comments"> { render_markdown(&comments) } </div>
+            }
+            </>
+        }
+    };
+
+    let mut sorted_completions = completions.clone();
+    sort_completions(&mut sorted_completions);
+    let rows: Html = sorted_completions
+        .iter()
+        .map(|(completion_id, data)| {
+            let best = Some(*completion_id) == best_completion_id;
+            let percentage = data.percentage();
+            html! {
+                <tr key={*completion_id} class={classes!(best.then_some("best"))}>
+                    <td class="date"> { data.date.map(|date| date.to_string()).unwrap_or_default() } </td>
+                    <td class="marks">
+                        { format!("{} / {}", data.achieved_mark, data.total_marks) }
+                        if let Some(percentage) = percentage {
+                            { format!(" ({percentage:.0}%)") }
+                        }
+                        if best {
+                            <span class="personal-best" title={t!("test-page.personal-best")}> { " ★" } </span>
+                        }
+                    </td>
+                    <td class="grade"> { percentage.and_then(grade_of).unwrap_or_default() } </td>
+                    <td class="comments">
+                        if let Some(comments) = &data.comments {
+                            { render_markdown(comments) }
+                        }
+                    </td>
+                </tr>
+            }
+        })
+        .collect();
+    let table = if completions.is_empty() {
+        html! { <p class="no-attempts"> { t!("test-page.no-attempts") } </p> }
+    } else {
+        html! {
+            <table class="completions">
+                <thead>
+                    <tr>
+                        <th> { t!("test-page.date") } </th>
+                        <th> { t!("test-page.mark") } </th>
+                        <th> { t!("test-page.grade") } </th>
+                        <th> { t!("test-page.comments") } </th>
+                    </tr>
+                </thead>
+                <tbody> {rows} </tbody>
+            </table>
+        }
+    };
+
+    // A chart of just one point wouldn't show anything
+    let points = score_points(completions);
+    let chart = if points.len() >= 2 {
+        html! { <ScoreChart {points} width={CHART_WIDTH} height={CHART_HEIGHT} /> }
+    } else {
+        html! {}
+    };
+
+    let add_completion_form = if *adding_completion {
+        let default_total_marks =
+            test_and_completions.next_total_marks(settings.default_total_marks);
+        let onsubmit = {
+            let (test_id, onaddcompletion) = (*id, onaddcompletion.clone());
+            let adding_completion = adding_completion.clone();
+            Callback::from(move |completion| {
+                adding_completion.set(false);
+                onaddcompletion.emit((test_id, completion))
+            })
+        };
+        let oncancel = {
+            let adding_completion = adding_completion.clone();
+            Callback::from(move |()| adding_completion.set(false))
+        };
+
+        html! {
+            <AddCompletionForm test_id={*id} {default_total_marks} {onsubmit} {oncancel} />
+        }
+    } else {
+        html! {}
+    };
+
+    let delete_dialog = if *confirming_delete {
+        let message = tn!("test.confirm-delete", completions.len());
+        let onconfirm = {
+            let (test_id, ondeletetest) = (*id, ondeletetest.clone());
+            let confirming_delete = confirming_delete.clone();
+            Callback::from(move |()| {
+                confirming_delete.set(false);
+                ondeletetest.emit(test_id);
+            })
+        };
+        let oncancel = {
+            let confirming_delete = confirming_delete.clone();
+            Callback::from(move |()| confirming_delete.set(false))
+        };
+
+        html! {
+            <ConfirmDialog {message} confirm_label={t!("test.delete")} {onconfirm} {oncancel} />
+        }
+    } else {
+        html! {}
+    };
+
+    let onclick_edit = {
+        let editing = editing.clone();
+        move |_mouse_event| editing.set(true)
+    };
+    let onclick_log_attempt = {
+        let adding_completion = adding_completion.clone();
+        move |_mouse_event| adding_completion.set(true)
+    };
+    let onclick_delete = {
+        let confirming_delete = confirming_delete.clone();
+        move |_mouse_event| confirming_delete.set(true)
+    };
+
+    html! {
+        <div class="test-page">
+            {back_link()}
+            <div class="title">
+                <h2>
+                    <span class="subject"> { subject } </span>
+                    if let Some(topic) = topic {
+                        <span class="topic"> { format!(": {topic}") } </span>
+                    }
+                </h2>
+                if !*editing {
+                    <button class="delete" title={t!("test.delete")} onclick={onclick_delete}> { "🗑️" } </button>
+                    <button class="edit" title={t!("test.edit")} onclick={onclick_edit}> { "✏️" } </button>
+                }
+                if !*adding_completion {
+                    <button class="log-attempt" onclick={onclick_log_attempt}> { t!("test.log-attempt") } </button>
+                }
+            </div>
+            {delete_dialog}
+            {details}
+            {chart}
+            <h3> { t!("test-page.attempts") } </h3>
+            {add_completion_form}
+            {table}
+        </div>
+    }
+}
+
+/// What the page of a test shows when there's no such test, or it belongs to someone else, with a
+/// link back to the list.
+#[function_component(TestNotFound)]
+pub fn test_not_found() -> Html {
+    html! {
+        <div class="test-page not-found">
+            <h2> { t!("test-page.not-found") } </h2>
+            <p> { t!("test-page.not-found-reason") } </p>
+            {back_link()}
+        </div>
+    }
+}
@@ -0,0 +1,52 @@
+//! This module provides the [`ThemeSelect`] component.
+
+use test_tracker_shared::theme::ThemePreference;
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// The themes that the user can pick from, with the labels to show for them, in the order that
+/// they appear in the dropdown.
+const OPTIONS: [(ThemePreference, &str); 3] = [
+    (ThemePreference::System, "Follow the system"),
+    (ThemePreference::Light, "Light"),
+    (ThemePreference::Dark, "Dark"),
+];
+
+/// The props for [`ThemeSelect`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// The theme that's currently selected.
+    pub theme: ThemePreference,
+
+    /// The callback to run with the new theme when the user picks one.
+    pub onchange: Callback<ThemePreference>,
+}
+
+/// A section of the settings to pick the theme.
+#[function_component(ThemeSelect)]
+pub fn theme_select(props: &Props) -> Html {
+    let onchange = {
+        let onchange = props.onchange.clone();
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a theme");
+            if let Some(&(theme, _)) = usize::try_from(index).ok().and_then(|i| OPTIONS.get(i)) {
+                onchange.emit(theme);
+            }
+        }
+    };
+
+    html! {
+        <fieldset class="theme-select">
+            <legend> { "Theme" } </legend>
+            <select id="themeSelect" aria-label="Theme" {onchange}>
+                { for OPTIONS.iter().map(|&(theme, label)| html! {
+                    <option selected={theme == props.theme}> { label } </option>
+                }) }
+            </select>
+        </fieldset>
+    }
+}
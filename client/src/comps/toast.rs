@@ -0,0 +1,62 @@
+//! This module provides the [`Toast`] component.
+
+use yew::{classes, function_component, html, Callback, Html, Properties};
+
+/// How serious a toast is, which decides how it looks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    /// Something the user might want to know.
+    Info,
+
+    /// Something the user did has worked.
+    Success,
+
+    /// Something went wrong.
+    Error,
+}
+
+impl Severity {
+    /// The CSS class for toasts of this severity.
+    fn class(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Success => "success",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// The props for [`Toast`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// How serious the toast is.
+    pub severity: Severity,
+
+    /// The message to show.
+    pub message: String,
+
+    /// The callback to run when the user dismisses the toast.
+    pub ondismiss: Callback<()>,
+}
+
+/// A small notification that pops up in the corner of the screen, without getting in the way like
+/// `window.alert` does. The app decides when it goes away; see `App::push_toast`.
+#[function_component(Toast)]
+pub fn toast(props: &Props) -> Html {
+    // Errors interrupt screen readers, but anything else can wait until they're done
+    let role = match props.severity {
+        Severity::Error => "alert",
+        Severity::Info | Severity::Success => "status",
+    };
+    let onclick = {
+        let ondismiss = props.ondismiss.clone();
+        move |_mouse_event| ondismiss.emit(())
+    };
+
+    html! {
+        <div class={classes!("toast", props.severity.class())} {role}>
+            <span class="message"> { props.message.clone() } </span>
+            <button aria-label="Dismiss" {onclick}> { "✕" } </button>
+        </div>
+    }
+}
@@ -0,0 +1,185 @@
+//! This module provides the [`TrendChart`] component.
+
+use std::collections::BTreeMap;
+use test_tracker_shared::chart::{
+    percentage_y, plot_trend, trend_series, trend_weeks, ChartSize, TrendPoint,
+};
+use tracing::trace;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::HtmlSelectElement;
+use yew::{function_component, html, use_state, Html, Properties};
+
+/// How many colours there are for the lines, in the stylesheet. Any more subjects than this
+/// reuse them.
+const SERIES_COLOURS: usize = 6;
+
+/// The percentages to draw gridlines at.
+const GRIDLINES: [f64; 5] = [0., 25., 50., 75., 100.];
+
+/// The size of the chart. The padding is wide enough for the labels on the axes.
+const SIZE: ChartSize = ChartSize {
+    width: 600.,
+    height: 240.,
+    padding: 32.,
+};
+
+/// The props for [`TrendChart`].
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct Props {
+    /// Every dated score to plot, in date order. See
+    /// [`trend_points`](test_tracker_shared::chart::trend_points).
+    pub points: Vec<TrendPoint>,
+
+    /// How many completions without a date were left out of the points, in each subject.
+    pub undated: BTreeMap<String, usize>,
+}
+
+/// A line chart of the user's scores over time, averaged by week, for one subject or for all of
+/// them at once with a line each and a legend. Each point shows its week, its percentage, and the
+/// tests it's from when hovered over.
+#[function_component(TrendChart)]
+pub fn trend_chart(props: &Props) -> Html {
+    let subject = use_state(|| None::<String>);
+
+    let mut subjects: Vec<String> = props
+        .points
+        .iter()
+        .map(|point| point.subject.clone())
+        .collect();
+    subjects.sort();
+    subjects.dedup();
+
+    let onchange = {
+        let (subjects, subject) = (subjects.clone(), subject.clone());
+        move |event: yew::Event| {
+            let select: HtmlSelectElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+            let index = select.selected_index();
+            trace!(?index, "Picked a subject for the trend chart");
+            // The first option is "All subjects", so the rest are one along
+            subject.set(
+                usize::try_from(index - 1)
+                    .ok()
+                    .and_then(|index| subjects.get(index))
+                    .cloned(),
+            );
+        }
+    };
+
+    let series = trend_series(&props.points, subject.as_deref());
+    let coords = plot_trend(&series, SIZE);
+    let skipped: usize = match &*subject {
+        Some(subject) => props.undated.get(subject).copied().unwrap_or(0),
+        None => props.undated.values().sum(),
+    };
+
+    let chart = match trend_weeks(&series) {
+        None => html! { <p class="no-trend"> { "There aren't any attempts with dates yet." } </p> },
+        Some((first, last)) => {
+            let gridlines: Html = GRIDLINES
+                .iter()
+                .map(|&percentage| {
+                    let y = format!("{:.1}", percentage_y(percentage, SIZE));
+                    html! {
+                        <>
+                        <line
+                            class="gridline"
+                            x1={SIZE.padding.to_string()}
+                            x2={(SIZE.width - SIZE.padding).to_string()}
+                            y1={y.clone()}
+                            y2={y.clone()} />
+                        <text class="axis-label" x={(SIZE.padding - 4.).to_string()} y={y} text-anchor="end">
+                            { format!("{percentage:.0}%") }
+                        </text>
+                        </>
+                    }
+                })
+                .collect();
+            let bottom = (SIZE.height - SIZE.padding / 3.).to_string();
+            let lines: Html = series
+                .iter()
+                .zip(&coords)
+                .enumerate()
+                .map(|(index, (series, coords))| {
+                    let line = coords
+                        .iter()
+                        .map(|(x, y)| format!("{x:.1},{y:.1}"))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    html! {
+                        <g class={format!("series series-{}", index % SERIES_COLOURS)}>
+                            <polyline class="line" points={line} />
+                            { for series.buckets.iter().zip(coords).map(|(bucket, (x, y))| html! {
+                                <circle class="point" cx={format!("{x:.1}")} cy={format!("{y:.1}")} r="3.5">
+                                    <title> { format!(
+                                        "{}, week of {}: {:.0}%\n{}",
+                                        series.subject,
+                                        bucket.week,
+                                        bucket.percentage,
+                                        bucket.tests.join("\n")
+                                    ) } </title>
+                                </circle>
+                            }) }
+                        </g>
+                    }
+                })
+                .collect();
+
+            html! {
+                <svg
+                    class="trend-chart"
+                    width={SIZE.width.to_string()}
+                    height={SIZE.height.to_string()}
+                    viewBox={format!("0 0 {} {}", SIZE.width, SIZE.height)}
+                    role="img"
+                    aria-label="Scores over time by week">
+                    {gridlines}
+                    <text class="axis-label" x={SIZE.padding.to_string()} y={bottom.clone()} text-anchor="start">
+                        { first.to_string() }
+                    </text>
+                    if last != first {
+                        <text class="axis-label" x={(SIZE.width - SIZE.padding).to_string()} y={bottom} text-anchor="end">
+                            { last.to_string() }
+                        </text>
+                    }
+                    {lines}
+                </svg>
+            }
+        }
+    };
+
+    html! {
+        <details class="trend">
+            <summary> { "Scores over time" } </summary>
+            <div class="trend-subject">
+                <label for="trendSubject"> { "Subject" } </label>
+                <select id="trendSubject" {onchange}>
+                    <option selected={subject.is_none()}> { "All subjects" } </option>
+                    { for subjects.iter().map(|option| html! {
+                        <option selected={subject.as_ref() == Some(option)}>
+                            { option.clone() }
+                        </option>
+                    }) }
+                </select>
+            </div>
+            {chart}
+            if series.len() > 1 {
+                <ul class="legend">
+                    { for series.iter().enumerate().map(|(index, series)| html! {
+                        <li class={format!("series-{}", index % SERIES_COLOURS)}>
+                            <span class="swatch" aria-hidden="true" />
+                            { series.subject.clone() }
+                        </li>
+                    }) }
+                </ul>
+            }
+            if skipped > 0 {
+                <p class="skipped">
+                    { match skipped {
+                        1 => "1 attempt without a date isn't shown.".to_string(),
+                        skipped => format!("{skipped} attempts without dates aren't shown."),
+                    } }
+                </p>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,58 @@
+//! This module remembers the `ETag` of the last response to each kind of message that the server
+//! can cache, so that we can send it back in `If-None-Match` and get a `304 Not Modified` with no
+//! body if nothing has changed.
+//!
+//! The `ETag`s are kept by the kind of message rather than by path, since most messages share a
+//! path. A mutation on the same path as the list of tests mustn't send the list's `ETag`, or
+//! replace it with its own.
+//!
+//! A `304` means that the response would be the same as the last one, so the stored `ETag`s must
+//! be cleared with [`clear`] whenever the data from those responses is thrown away.
+
+use std::{cell::RefCell, collections::HashMap};
+use test_tracker_shared::ClientToServerMsg;
+
+/// A kind of message whose responses the server can cache, and give an `ETag`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CachedMsg {
+    /// [`ClientToServerMsg::GetTestsAndCompletions`].
+    GetTestsAndCompletions,
+}
+
+impl CachedMsg {
+    /// Get the kind of the given message, if its responses can be cached.
+    pub fn of(msg: &ClientToServerMsg) -> Option<Self> {
+        match msg {
+            ClientToServerMsg::GetTestsAndCompletions { .. } => Some(Self::GetTestsAndCompletions),
+            _ => None,
+        }
+    }
+}
+
+thread_local! {
+    /// The last `ETag` for each kind of message.
+    static ETAGS: RefCell<HashMap<CachedMsg, String>> = RefCell::new(HashMap::new());
+}
+
+/// Get the `ETag` of the last response to the given kind of message, if it had one.
+pub fn get(kind: CachedMsg) -> Option<String> {
+    ETAGS.with(|etags| etags.borrow().get(&kind).cloned())
+}
+
+/// Remember the `ETag` of the latest response to the given kind of message, or forget the old one
+/// if this response didn't have one.
+pub fn set(kind: CachedMsg, etag: Option<String>) {
+    ETAGS.with(|etags| match etag {
+        Some(etag) => {
+            etags.borrow_mut().insert(kind, etag);
+        }
+        None => {
+            etags.borrow_mut().remove(&kind);
+        }
+    });
+}
+
+/// Forget every `ETag`.
+pub fn clear() {
+    ETAGS.with(|etags| etags.borrow_mut().clear());
+}
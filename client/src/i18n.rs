@@ -0,0 +1,100 @@
+//! This module shows the text in the UI in the user's language, using the translations in
+//! [`test_tracker_shared::i18n`].
+//!
+//! Components get their text with the [`t!`] macro, like `t!("login.username")`, or
+//! `t!("test.best", percentage = 90)` to fill in placeholders, and with [`tn!`] for text that
+//! depends on a number, like `tn!("test.attempts", 3)`, which fills in `{count}`.
+
+use gloo_utils::{document, window};
+use std::cell::Cell;
+use test_tracker_shared::i18n::{fill, Language};
+use tracing::{debug, warn};
+
+thread_local! {
+    /// The language that the UI is being shown in. The client only has one thread, so this is
+    /// global.
+    static LANGUAGE: Cell<Language> = Cell::new(Language::default());
+}
+
+/// The browser's language, if the client can be shown in it, or English if it can't.
+pub fn browser_language() -> Language {
+    window()
+        .navigator()
+        .language()
+        .and_then(|tag| Language::from_tag(&tag))
+        .unwrap_or_default()
+}
+
+/// Show the UI in the language that the user picked, or the browser's language if they haven't
+/// picked one. Components only pick this up when they're rendered again.
+pub fn set_language(picked: Option<Language>) {
+    let language = picked.unwrap_or_else(browser_language);
+    debug!(?picked, ?language, "Setting language");
+    LANGUAGE.with(|cell| cell.set(language));
+
+    if let Some(root) = document().document_element() {
+        if let Err(e) = root.set_attribute("lang", language.tag()) {
+            warn!(?e, "Couldn't set the lang attribute");
+        }
+    }
+}
+
+/// The language that the UI is being shown in.
+pub fn language() -> Language {
+    LANGUAGE.with(Cell::get)
+}
+
+/// Get the message with the given key, with its placeholders filled in. If there's no such
+/// message, then the key itself is shown, so that it's obvious what's missing.
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    match language().translate(key) {
+        Some(message) => fill(message, args),
+        None => {
+            warn!(?key, "Missing translation");
+            key.to_string()
+        }
+    }
+}
+
+/// Get the message with the given key for a count, with `{count}` and the other placeholders
+/// filled in.
+pub fn translate_plural(key: &str, count: usize, args: &[(&str, String)]) -> String {
+    match language().translate_plural(key, count as u64) {
+        Some(message) => {
+            let message = fill(message, &[("count", count.to_string())]);
+            fill(&message, args)
+        }
+        None => {
+            warn!(?key, "Missing plural translation");
+            key.to_string()
+        }
+    }
+}
+
+/// Get a translated message by its key, optionally filling in placeholders by name. See
+/// [`translate`].
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+/// Get a translated message for a count by its key, optionally filling in other placeholders by
+/// name. See [`translate_plural`].
+macro_rules! tn {
+    ($key:literal, $count:expr) => {
+        $crate::i18n::translate_plural($key, $count, &[])
+    };
+    ($key:literal, $count:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate_plural(
+            $key,
+            $count,
+            &[$((stringify!($name), $value.to_string())),+],
+        )
+    };
+}
+
+pub(crate) use {t, tn};
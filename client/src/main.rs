@@ -3,47 +3,333 @@
 #![feature(min_specialization)]
 
 use self::{
-    comps::{ErrorMessage, ListOfTestsAndCompletions, LoginOrCreateAccountForm, Navbar},
-    web::{get_user, local_storage, session_storage},
+    comps::{
+        add_test_form::RejectedTest,
+        change_password_form::ChangePasswordError,
+        login_form::{LoginOrCreateAccountCallback, LOGIN_FIELDS},
+        startup_error::Props as StartupErrorProps,
+        toast::Severity,
+        AddTestForm, EmptyState, ErrorMessage, ExamCountdown, ImportForm,
+        ListOfTestsAndCompletions, LoginOrCreateAccountForm, Navbar, OfflineBanner, PageControls,
+        PrintSummary, SearchBox, SettingsDialog, SortSelect, Spinner, StartupError, StreakPanel,
+        SubjectFilter, SubjectSummaryPanel, TestNotFound, TestPage, Toast, TrendChart,
+    },
+    etags::CachedMsg,
+    i18n::t,
+    offline::{is_online, ConnectivityListener, QueuedChange},
+    push::PushSocket,
+    router::{current_route, navigate, RouteListener},
+    web::{
+        clear_cached_tests, download_file, format_date_input, get_cached_tests, get_exam_dates,
+        get_offline_queue, get_page_size, get_session, get_settings, get_view_preferences,
+        local_storage, print_page, session_storage, set_body_class, set_cached_tests,
+        set_offline_queue, set_preference, set_settings, set_view_preferences, today,
+    },
 };
-use gloo_utils::window;
+use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
-use reqwest_wasm::Client;
-use std::{error::Error, sync::Arc};
+use reqwest_wasm::{Client, StatusCode};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    error::Error,
+    mem,
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
 use test_tracker_shared::{
-    error::DieselError as SharedDieselError, ClientToServerMsg, Error as SharedError,
-    ServerToClientMsg, TestAndCompletions, User,
+    chart::trend_points,
+    countdown::ExamDate,
+    error::{DieselError as SharedDieselError, FieldErrors},
+    export::tests_to_csv,
+    page::{paginate, PageSize},
+    paths,
+    route::Route,
+    settings::Settings,
+    streak::{completion_dates, streaks, Streaks},
+    summary::subject_summaries,
+    view::ViewPreferences,
+    ClientToServerMsg, CompletionData, Error as SharedError, ImportSummary, ServerToClientMsg,
+    Session, SyncedTest, TestData, TestSort, TestsSync,
 };
 use tracing::{debug, error, info, instrument, trace, warn};
 use tracing_unwrap::ResultExt;
 use tracing_wasm::WASMLayerConfigBuilder;
-use yew::{html, Component, Context, Html};
+use yew::{html, platform::time::sleep, Callback, Component, Context, Html};
 
 mod comps;
+mod etags;
+mod i18n;
+mod offline;
+mod push;
+mod router;
+mod server_url;
 mod web;
 
-/// The key for the dark mode key in browser storage.
+/// The key for the user's [`Settings`] in browser storage.
+pub(crate) const STORAGE_KEY_SETTINGS: &str = "testTrackerSettings";
+
+/// The key that older clients stored the theme under in browser storage. It's only read to move
+/// the theme into the [`Settings`].
 pub(crate) const STORAGE_KEY_DARK_MODE: &str = "testTrackerDarkMode";
 
-/// The key for the user key in browser storage.
+/// The key for the user's session in browser storage.
 pub(crate) const STORAGE_KEY_USER: &str = "testTrackerUser";
 
+/// The key that older clients stored the order of the tests under in browser storage. It's only
+/// read to move it into the [`ViewPreferences`].
+pub(crate) const STORAGE_KEY_SORT: &str = "testTrackerSort";
+
+/// The key that older clients stored the subject filter under in browser storage. It's only read
+/// to move it into the [`ViewPreferences`].
+pub(crate) const STORAGE_KEY_SUBJECT_FILTER: &str = "testTrackerSubjectFilter";
+
+/// The key that older clients stored the IDs of the expanded tests under in browser storage. It's
+/// only read to move them into the [`ViewPreferences`].
+pub(crate) const STORAGE_KEY_EXPANDED_TESTS: &str = "testTrackerExpandedTests";
+
+/// The key that older clients stored whether to group the tests by subject under in browser
+/// storage. It's only read to move it into the [`ViewPreferences`].
+pub(crate) const STORAGE_KEY_GROUPED: &str = "testTrackerGrouped";
+
+/// The key for how many tests the user wants on each page, in browser storage.
+pub(crate) const STORAGE_KEY_PAGE_SIZE: &str = "testTrackerPageSize";
+
+/// The key for the dates of the user's exams, in browser storage.
+pub(crate) const STORAGE_KEY_EXAM_DATES: &str = "testTrackerExamDates";
+
+/// The key that older clients stored the grade boundaries under in browser storage. It's only
+/// read to move them into the [`Settings`].
+pub(crate) const STORAGE_KEY_GRADE_BOUNDARIES: &str = "testTrackerGradeBoundaries";
+
+/// The prefix of the keys for each user's cached tests in browser storage.
+pub(crate) const STORAGE_KEY_TESTS_CACHE: &str = "testTrackerTestsCache";
+
+/// The key for the changes that were made offline and haven't been sent yet, in browser storage.
+pub(crate) const STORAGE_KEY_OFFLINE_QUEUE: &str = "testTrackerOfflineQueue";
+
+/// How long to wait before automatically trying again when fetching the list of tests fails.
+const REFRESH_RETRY_DELAY: Duration = Duration::from_secs(3);
+
+/// The class on the body of the HTML in the print view, which hides everything but the summary.
+const PRINT_VIEW_CLASS: &str = "print-view";
+
+/// How long a toast stays up before it goes away by itself.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// The most toasts to show at once. If there are more, then the oldest ones go first.
+const MAX_TOASTS: usize = 5;
+
+/// The error to show when the user tries to change a test that the server hasn't confirmed yet.
+const STILL_SAVING: &str = "This test is still being saved, so try again in a moment";
+
+/// The error to show when the user tries to change a test that they added while offline, which
+/// can't be changed until it's been sent to the server.
+const SAVED_WHEN_ONLINE: &str = "This test will be saved with the other changes that you made \
+                                 offline, and can only be changed after that";
+
+/// How long to wait for the server to confirm a change that's already shown, before rolling it
+/// back.
+const MUTATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long to wait for the server to answer a login or a new account, before letting the user
+/// try again.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 lazy_static! {
     /// The client to use for making async requests to the server.
     static ref REQWEST_CLIENT: Arc<Client> = Arc::new(Client::new());
 }
 
+/// How far along fetching the list of tests is, so that the main screen doesn't just look empty
+/// while it's loading or if it failed.
+///
+/// This only covers the first full sync. Later syncs just merge into the list that's already
+/// shown, so they don't go back to [`Loading`](ListState::Loading).
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ListState {
+    /// We're waiting for the first sync.
+    Loading,
+
+    /// The list from the last visit has been loaded from the cache in `localStorage`, and is
+    /// shown while it's being fetched again. This is also the state while the browser is offline,
+    /// and while the list is fetched again after sending the changes made offline.
+    Cached,
+
+    /// The list has been fetched, and is in [`App::tests_and_completions`]. This is also the state
+    /// if the cached list is shown but fetching it again has failed.
+    Loaded,
+
+    /// The first sync failed, with this error message.
+    Failed(String),
+}
+
+/// How far along fetching a test for its page is, when it isn't in the list. See
+/// [`App::fetch_missing_test`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TestFetch {
+    /// We're waiting for the server.
+    Fetching,
+
+    /// There's no such test, or it belongs to someone else.
+    NotFound,
+
+    /// Fetching the test failed, with this error message.
+    Failed(String),
+}
+
+/// A change that's already shown in the list, but that the server hasn't confirmed yet. See
+/// [`App::begin_mutation`].
+#[derive(Clone, Debug)]
+struct PendingMutation {
+    /// The ID of the test that was changed.
+    test_id: i32,
+
+    /// The test as it was before the change, or `None` if it didn't exist, like for a new test.
+    /// Rolling back the change puts this back.
+    snapshot: Option<SyncedTest>,
+
+    /// What to tell the user if the change fails, like "Couldn't delete the test".
+    failure: &'static str,
+}
+
+/// A notification in the corner of the screen. See [`App::push_toast`].
+#[derive(Clone, Debug)]
+struct QueuedToast {
+    /// The ID of the toast, so that it can be dismissed.
+    id: u32,
+
+    /// How serious the toast is.
+    severity: Severity,
+
+    /// The message to show.
+    message: String,
+}
+
+/// Describe why a request failed, for the end of an error message.
+fn error_reason(error: &AppMsg) -> String {
+    match error {
+        AppMsg::SharedError(e) => e.to_string(),
+        _ => "the server couldn't be reached".to_string(),
+    }
+}
+
+/// The name of a test to show the user, like `"Maths Mock Set 1"`, with the quotes.
+fn test_name(test: &TestData) -> String {
+    format!("\"{} {}\"", test.subject, test.date_or_id)
+}
+
 /// The model for the whole web app.
 #[derive(Clone, Debug)]
 struct App {
-    /// The user that we may or may not have authenticated.
-    user: Option<User>,
+    /// The session of the user that we may or may not have authenticated.
+    session: Option<Session>,
+
+    /// The tests and completions of the user, keyed by test ID.
+    tests_and_completions: BTreeMap<i32, SyncedTest>,
+
+    /// Whether the tests and completions have been fetched yet.
+    list_state: ListState,
+
+    /// Whether we've already automatically retried a failed fetch of the list. We only retry once,
+    /// and then give up until the user asks to try again.
+    refresh_retried: bool,
+
+    /// When we last synced the tests and completions with the server, according to the server's
+    /// clock. See [`TestsSync::synced_at`].
+    synced_at: Option<DateTime<Utc>>,
 
-    /// The tests and completions of the user.
-    tests_and_completions: Vec<TestAndCompletions>,
+    /// How the user wants the list of tests to look: the sort, the subject filter, the grouping,
+    /// and which cards are expanded. The tests are sorted and filtered here rather than by the
+    /// server, so changing these doesn't need a refresh. They're kept for each user in
+    /// `localStorage` by [`save_preferences`](App::save_preferences).
+    view: ViewPreferences,
+
+    /// The text in the search box, which only shows the tests that match it, as well as the
+    /// subject filter. See [`TestData::matches_search`].
+    search: String,
+
+    /// Whether to show the print view instead of the normal list. See [`PrintSummary`].
+    print_view: bool,
+
+    /// How many tests to show on each page.
+    page_size: PageSize,
+
+    /// The index of the page of tests that's showing. This goes back to the first page whenever
+    /// the search, filter, sort, or grouping changes.
+    page: usize,
+
+    /// The user's settings, which are loaded once when the app starts and kept in `localStorage`.
+    settings: Settings,
+
+    /// Whether the settings dialog is open.
+    settings_open: bool,
+
+    /// The exams to count down to, which are kept in `localStorage`.
+    exam_dates: Vec<ExamDate>,
 
     /// An optional error message to display.
     error_message: Option<String>,
+
+    /// The errors from the server to show under the fields of the login form, from the last time
+    /// it was submitted. Other errors go in [`error_message`](App::error_message).
+    login_field_errors: FieldErrors,
+
+    /// The last new test that the server refused because of one of its fields, which goes back in
+    /// the add test form so that the user can fix it.
+    rejected_test: Option<RejectedTest>,
+
+    /// The socket that tells us when the user's data changes on the server, if it's open.
+    push_socket: Option<Rc<PushSocket>>,
+
+    /// The placeholder ID to give the next test or completion that's shown before the server has
+    /// confirmed it. These count down from -1, so they can never clash with real IDs.
+    next_pending_id: i32,
+
+    /// The changes that are shown but not confirmed yet, keyed by the IDs from
+    /// [`begin_mutation`](App::begin_mutation).
+    pending_mutations: BTreeMap<u32, PendingMutation>,
+
+    /// The ID to give the next pending mutation.
+    next_mutation_id: u32,
+
+    /// The ID of the login or new account request that's waiting for the server, if there is one.
+    /// The login form is disabled while it's waiting. See [`AppMsg::LoginStarted`].
+    login_in_flight: Option<u32>,
+
+    /// The ID to give the next login or new account request.
+    next_login_id: u32,
+
+    /// The toasts that are showing, oldest first.
+    toasts: VecDeque<QueuedToast>,
+
+    /// The ID to give the next toast.
+    next_toast_id: u32,
+
+    /// Whether the browser is online. While it's offline, changes to the tests are queued in
+    /// [`offline_queue`](App::offline_queue) instead of being sent.
+    online: bool,
+
+    /// The listener that keeps [`online`](App::online) up to date.
+    connectivity: Option<Rc<ConnectivityListener>>,
+
+    /// The changes that were made offline and haven't been sent yet, oldest first. This is kept
+    /// for the tab in `sessionStorage`.
+    offline_queue: VecDeque<QueuedChange>,
+
+    /// Whether the changes in the [`offline_queue`](App::offline_queue) are being sent. They're
+    /// sent one at a time, in order.
+    sending_queue: bool,
+
+    /// The page to show, from the fragment of the URL, or `None` if it doesn't match any page.
+    route: Option<Route>,
+
+    /// The listener that keeps [`route`](App::route) up to date.
+    router: Option<Rc<RouteListener>>,
+
+    /// The test that's being fetched for its page, because it isn't in the list, and how far along
+    /// that is.
+    test_fetch: Option<(i32, TestFetch)>,
 }
 
 /// A message to send to the app.
@@ -52,6 +338,23 @@ enum AppMsg {
     /// Manually change or reset the internal [`error_message`](App::error_message).
     ChangeErrorMessage(Option<String>),
 
+    /// Remove the toast with the given ID, either because the user dismissed it or because it's been
+    /// up for [`TOAST_DURATION`].
+    DismissToast(u32),
+
+    /// The browser has gone online, if this is `true`, or offline.
+    SetOnline(bool),
+
+    /// Send the changes that were made offline, if they aren't already being sent.
+    SendQueuedChanges,
+
+    /// The server has responded to the oldest change that was made offline, either by saving it
+    /// or by refusing it with the given error.
+    QueuedChangeSent(Result<(), SharedError>),
+
+    /// The oldest change that was made offline couldn't be sent.
+    SendingQueuedChangeFailed(Box<AppMsg>),
+
     /// An error from the server has occured. Tell the user.
     SharedError(SharedError),
 
@@ -61,11 +364,246 @@ enum AppMsg {
     /// We received an unexpected (but valid) message from the server.
     UnexpectedServerMsg(ServerToClientMsg),
 
-    /// Authenticate a user. The bool reflects the "remember me" checkbox.
-    AuthenticateUser(User, bool),
+    /// The login form has been submitted, so disable it until the server answers, or until
+    /// [`LOGIN_TIMEOUT`] has passed.
+    LoginStarted,
+
+    /// Logging in or creating an account failed, so enable the login form again and report the
+    /// error.
+    LoginFailed(Box<AppMsg>),
+
+    /// The server hasn't answered the login request with the given ID within [`LOGIN_TIMEOUT`],
+    /// so let the user try again, unless it's already been answered.
+    LoginTimedOut(u32),
+
+    /// Authenticate a user with a new session. The bool reflects the "remember me" checkbox.
+    AuthenticateUser(Session, bool),
+
+    /// Merge the changes to the tests and completions since the last sync.
+    MergeTestsSync(TestsSync),
+
+    /// Fetching the changes to the tests and completions failed.
+    RefreshFailed(Box<AppMsg>),
+
+    /// The user asked to try fetching the tests and completions again after it failed.
+    RetryRefresh,
+
+    /// Sort the list of tests into a different order, and remember it for next time.
+    ChangeSort(Option<TestSort>),
+
+    /// Only show the tests of the given subject, or all of them if it's `None`, and remember it
+    /// for next time.
+    ChangeSubjectFilter(Option<String>),
+
+    /// Only show the tests that match the given search.
+    ChangeSearch(String),
+
+    /// Expand the card of the test with the given ID, or collapse it if it's already expanded.
+    ToggleExpanded(i32),
+
+    /// Switch between grouping the tests by subject and showing them in one flat list, and
+    /// remember it for next time.
+    SetGrouped(bool),
+
+    /// Show the page of tests with the given index.
+    ChangePage(usize),
+
+    /// Change how many tests to show on each page, and remember it for next time.
+    ChangePageSize(PageSize),
+
+    /// Change the settings, and remember them for next time.
+    ChangeSettings(Settings),
+
+    /// Open or close the settings dialog.
+    SetSettingsOpen(bool),
+
+    /// Send a new password to the server, which has already passed the password policy in the
+    /// form.
+    ChangePassword {
+        /// The user's current password.
+        current_password: String,
+
+        /// The new password.
+        new_password: String,
+
+        /// The callback to tell the form how changing the password went.
+        done: Callback<Result<(), ChangePasswordError>>,
+    },
+
+    /// The server has changed the password, so tell the form. The `Callback` is the one from
+    /// [`AppMsg::ChangePassword`].
+    PasswordChanged(Callback<Result<(), ChangePasswordError>>),
+
+    /// Changing the password failed, so tell the form.
+    ChangePasswordFailed {
+        /// The message to report the error with.
+        error: Box<AppMsg>,
+
+        /// The callback to tell the form what went wrong.
+        done: Callback<Result<(), ChangePasswordError>>,
+    },
+
+    /// Ask the server to delete the user's account. The user has already confirmed this by typing
+    /// their username.
+    DeleteAccount {
+        /// The user's password.
+        password: String,
+
+        /// The callback to tell the dialog how deleting the account went, with an error message
+        /// if it failed.
+        done: Callback<Result<(), String>>,
+    },
+
+    /// The server has deleted the user's account, so forget everything about it and go back to
+    /// the login screen. The `Callback` is the one from [`AppMsg::DeleteAccount`].
+    AccountDeleted(Callback<Result<(), String>>),
+
+    /// Deleting the account failed, so tell the dialog.
+    DeleteAccountFailed {
+        /// The message to report the error with.
+        error: Box<AppMsg>,
+
+        /// The callback to tell the dialog what went wrong.
+        done: Callback<Result<(), String>>,
+    },
+
+    /// Change the list of exams to count down to, and remember it for next time.
+    SetExamDates(Vec<ExamDate>),
+
+    /// Switch to or from the print view, which shows a summary of the filtered tests that's
+    /// meant to be printed. See [`PrintSummary`].
+    SetPrintView(bool),
+
+    /// Open the browser's print dialog.
+    Print,
+
+    /// Show a new test straight away with a placeholder ID, and send it to the server. It has
+    /// already passed validation in the form.
+    AddTest(TestData),
+
+    /// The server has added a test that was shown before it was confirmed, so swap its
+    /// placeholder ID for the real one.
+    TestAdded {
+        /// The placeholder ID. See [`App::next_pending_id`].
+        pending_id: i32,
+
+        /// The ID that the server gave the test.
+        id: i32,
+
+        /// The pending mutation. See [`App::begin_mutation`].
+        mutation: u32,
+    },
+
+    /// Send new details for a test to the server, which have already passed validation in the
+    /// form.
+    UpdateTest {
+        /// The ID of the test.
+        test_id: i32,
+
+        /// The new details.
+        test: TestData,
+
+        /// The callback to tell the form how saving went, with an error message if it failed.
+        done: Callback<Result<(), String>>,
+    },
+
+    /// The server has saved new details for a test, so show them.
+    TestUpdated {
+        /// The ID of the test.
+        test_id: i32,
+
+        /// The new details.
+        test: TestData,
+
+        /// The callback to tell the form that saving worked.
+        done: Callback<Result<(), String>>,
+    },
+
+    /// Saving new details for a test failed, so tell the form.
+    UpdateTestFailed {
+        /// The message to report the error with.
+        error: Box<AppMsg>,
+
+        /// The callback to tell the form what went wrong.
+        done: Callback<Result<(), String>>,
+    },
+
+    /// Remove the test with the given ID from the list straight away, and ask the server to delete
+    /// it. The user has already confirmed this.
+    DeleteTest(i32),
+
+    /// The server has deleted a test, which was removed from the list before it was confirmed. The
+    /// `u32` is the pending mutation. See [`App::begin_mutation`].
+    TestDeleted(u32),
+
+    /// Show a new completion of the test with the given ID straight away, and send it to the
+    /// server. It has already passed validation in the form.
+    AddCompletion(i32, CompletionData),
+
+    /// The server has added a completion that was shown before it was confirmed, so swap its
+    /// placeholder ID for the real one.
+    CompletionAdded {
+        /// The ID of the test.
+        test_id: i32,
+
+        /// The placeholder ID. See [`App::next_pending_id`].
+        pending_id: i32,
+
+        /// The ID that the server gave the completion.
+        id: i32,
+
+        /// The pending mutation. See [`App::begin_mutation`].
+        mutation: u32,
+    },
+
+    /// The server refused a change that was shown before it was confirmed, so roll it back and
+    /// report the error.
+    MutationFailed {
+        /// The pending mutation. See [`App::begin_mutation`].
+        mutation: u32,
+
+        /// The message to report the error with.
+        error: Box<AppMsg>,
+    },
+
+    /// The server hasn't confirmed a change within [`MUTATION_TIMEOUT`], so roll it back, unless
+    /// it's already been confirmed. The `u32` is the pending mutation.
+    MutationTimedOut(u32),
 
-    /// Set the list of tests and completions.
-    SetTestsAndCompletionsList(Vec<TestAndCompletions>),
+    /// The user's data has changed on the server, so fetch it again.
+    DataChanged,
+
+    /// Download all the user's completions as a CSV file.
+    ExportCsv,
+
+    /// Send a CSV of tests and completions to import, in the same format as the export. The user
+    /// has already picked the rows in the [`ImportForm`].
+    ImportCsv(String),
+
+    /// The server has imported some tests, so report how many and fetch the list again.
+    Imported(ImportSummary),
+
+    /// The URL has changed to show the given page, or one that doesn't exist if it's `None`.
+    Navigate(Option<Route>),
+
+    /// Fetch the test with the given ID for its page, since it isn't in the list.
+    FetchTest(i32),
+
+    /// The server has sent a test for its page, which wasn't in the list, so add it.
+    TestFetched(SyncedTest),
+
+    /// Fetching a test for its page failed.
+    FetchTestFailed {
+        /// The ID of the test.
+        test_id: i32,
+
+        /// The message to report the error with.
+        error: Box<AppMsg>,
+    },
+
+    /// The server responded with `304 Not Modified`, so the last response still applies and
+    /// there's nothing to do.
+    NotModified,
 }
 
 impl<E: Error + 'static> From<E> for AppMsg {
@@ -86,6 +624,8 @@ impl From<SharedError> for AppMsg {
 /// reacts accordingly.
 ///
 /// `ctx` is the yew context to create the callback future with.
+/// `path` is the path on the server to send the message to, which must match the message. See
+/// [`test_tracker_shared::paths`].
 /// `args` is the arguments (in a tuple) to the callback.
 /// `args_type` is the type of the arguments tuple.
 /// `pre_send` is any code that you want to execute before sending the message. This code could
@@ -93,49 +633,107 @@ impl From<SharedError> for AppMsg {
 /// `msg` is the message to send to the server.
 /// `expected_result => reaction` is the "happy path" of the match pattern, where you get the
 /// response that you were expecting. It matches against a [`ServerToClientMsg`].
+///
+/// `else |error| on_error` is optional, and turns the message for any other response or failure
+/// into the message to send instead, like to undo something that was done before sending.
+///
+/// If the server can cache responses to the message, then the `ETag` of the last response to the
+/// same kind of message is sent in `If-None-Match`, and if the server responds with
+/// `304 Not Modified`, then the reaction is [`AppMsg::NotModified`]. See [`CachedMsg`].
 macro_rules! send_message_to_server {
     (
         $ctx:expr;
+        $path:expr;
         |$args:tt: $args_type:ty|;
         $pre_send:block;
         $msg:expr;
         $expected_result:pat => $reaction:expr
+    ) => {
+        send_message_to_server! {
+            $ctx;
+            $path;
+            |$args: $args_type|;
+            $pre_send;
+            $msg;
+            $expected_result => $reaction;
+            else |error| error
+        }
+    };
+    (
+        $ctx:expr;
+        $path:expr;
+        |$args:tt: $args_type:ty|;
+        $pre_send:block;
+        $msg:expr;
+        $expected_result:pat => $reaction:expr;
+        else |$error:ident| $on_error:expr
     ) => {
         $ctx.link().callback_future(move |$args: $args_type| {
             let client = Arc::clone(&REQWEST_CLIENT);
 
             async move {
                 $pre_send;
+                let on_error = |$error: AppMsg| $on_error;
 
-                match client
-                    .post(env!("SERVER_URL"))
-                    .body(ron::to_string(&$msg).expect_or_log(
+                let msg = $msg;
+                let cached = CachedMsg::of(&msg);
+                let mut request = client.post(server_url::endpoint($path)).body(
+                    ron::to_string(&msg).expect_or_log(
                         "Converting a ClientToServerMsg to a RON string shouldn't fail",
-                    ))
-                    .send()
-                    .await
-                {
+                    ),
+                );
+                if let Some(etag) = cached.and_then(etags::get) {
+                    request = request.header("If-None-Match", etag);
+                }
+
+                match request.send().await {
+                    Ok(response) if response.status() == StatusCode::NOT_MODIFIED => {
+                        trace!(?response, "Response not modified");
+                        AppMsg::NotModified
+                    }
                     Ok(response) => {
                         trace!(?response, "Received raw response from server");
+                        if let Some(kind) = cached {
+                            etags::set(
+                                kind,
+                                response
+                                    .headers()
+                                    .get("ETag")
+                                    .and_then(|etag| etag.to_str().ok())
+                                    .map(ToString::to_string),
+                            );
+                        }
                         let text = response.text().await;
                         trace!(?text, "Got text from server response");
 
                         match text {
                             Ok(body) => {
-                                let msg = ron::from_str(&body);
+                                let msg: Result<ServerToClientMsg, _> = ron::from_str(&body);
                                 trace!(?msg, "Deserialized msg from server response");
                                 match msg {
+                                    // A session that's no longer valid logs the user out, whatever
+                                    // the request was, so that nothing keeps showing stale data
+                                    Ok(msg)
+                                        if matches!(
+                                            msg.error(),
+                                            Some(SharedError::Unauthorized)
+                                        ) =>
+                                    {
+                                        warn!("Session is no longer valid");
+                                        SharedError::Unauthorized.into()
+                                    }
                                     Ok(msg) => match msg {
                                         $expected_result => $reaction,
-                                        msg => AppMsg::UnexpectedServerMsg(msg),
+                                        ServerToClientMsg::Error(e) => on_error(e.into()),
+                                        msg => on_error(AppMsg::UnexpectedServerMsg(msg)),
                                     },
-                                    Err(e) => e.into(),
+                                    Err(e) => on_error(e.into()),
                                 }
                             }
-                            Err(e) => e.into(),
+                            Err(e) => on_error(e.into()),
                         }
                     }
-                    Err(e) => e.into(),
+                    Err(e) => on_error(e.into()),
                 }
             }
         })
@@ -148,37 +746,55 @@ impl App {
     fn view_login_screen(&self, ctx: &Context<Self>) -> Html {
         /// Generate an `onsubmit` callback for logging in or creating an account.
         macro_rules! onsubmit_login_or_create_account {
-            ($message:ident) => {
+            ($message:ident, $path:expr) => {
                 send_message_to_server! {
                     ctx;
+                    $path;
                     |(username, password, remember_me): (String, String, bool)|;
                     {
+                        // The form has already checked that neither is empty
                         debug!(
-                            ?username, ?password, ?remember_me,
+                            ?username, ?remember_me,
                             concat!("Trying to authenticate with ", stringify!($message))
                         );
-
-                        if username.is_empty() || password.is_empty() {
-                            return AppMsg::ChangeErrorMessage(
-                                Some("Please enter a username or password".to_string())
-                            );
-                        }
                     };
-                    ClientToServerMsg::$message { username, password };
+                    ClientToServerMsg::$message { username, password, remember_me };
                     ServerToClientMsg::AuthenticationResponse(result) => match result {
-                        Ok(user) => AppMsg::AuthenticateUser(user, remember_me),
-                        Err(e) => e.into(),
-                    }
+                        Ok(session) => AppMsg::AuthenticateUser(session, remember_me),
+                        Err(e) => AppMsg::LoginFailed(Box::new(e.into())),
+                    };
+                    else |error| AppMsg::LoginFailed(Box::new(error))
                 }
             };
         }
 
-        let onsubmit_login = onsubmit_login_or_create_account!(Authenticate);
-        let onsubmit_create_account = onsubmit_login_or_create_account!(CreateUser);
+        /// Disable the login form as soon as it's submitted, and then send it.
+        fn start_login(
+            ctx: &Context<App>,
+            send: LoginOrCreateAccountCallback,
+        ) -> LoginOrCreateAccountCallback {
+            let link = ctx.link().clone();
+            Callback::from(move |params| {
+                link.send_message(AppMsg::LoginStarted);
+                send.emit(params);
+            })
+        }
+
+        let onsubmit_login = start_login(
+            ctx,
+            onsubmit_login_or_create_account!(Authenticate, paths::AUTH),
+        );
+        let onsubmit_create_account = start_login(
+            ctx,
+            onsubmit_login_or_create_account!(CreateUser, paths::USERS),
+        );
+        let onerror = ctx.link().callback(AppMsg::ChangeErrorMessage);
 
         let error_message = match &self.error_message {
             Some(msg) => html! {
-                <ErrorMessage msg={msg.clone()} />
+                <ErrorMessage
+                    msg={msg.clone()}
+                    on_dismiss={ctx.link().callback(|()| AppMsg::ChangeErrorMessage(None))} />
             },
             None => html! {},
         };
@@ -187,120 +803,1650 @@ impl App {
             <>
             <LoginOrCreateAccountForm
                 {onsubmit_login}
-                {onsubmit_create_account} />
+                {onsubmit_create_account}
+                in_flight={self.login_in_flight.is_some()}
+                server_errors={self.login_field_errors.clone()}
+                {onerror} />
             {error_message}
             </>
         }
     }
 
-    /// Get the HTML for the main screen.
+    /// Get the HTML for the main screen, which is the list of tests or the page of one of them,
+    /// depending on the [`route`](App::route).
     #[instrument(skip_all)]
-    fn view_main_screen(&self, _ctx: &Context<Self>) -> Html {
-        html! {
-            <ListOfTestsAndCompletions list={self.tests_and_completions.clone()} />
+    fn view_main_screen(&self, ctx: &Context<Self>) -> Html {
+        match self.route {
+            Some(Route::List) => {}
+            Some(Route::Test(test_id)) => return self.view_test_page(ctx, test_id),
+            None => return html! { <TestNotFound /> },
         }
-    }
 
-    /// Refresh the internal [`tests_and_completions`](App::tests_and_completions) attribute by
-    /// creating an async callback to get the list from the server and send the
-    /// [`SetTestsAndCompletionsList`](AppMsg::SetTestsAndCompletionsList) message to the app.
-    fn refresh_tests_and_completions_list(&self, ctx: &Context<Self>) {
-        match &self.user {
-            Some(user) => send_message_to_server! {
-                ctx;
-                |user_id: String|;
-                {};
-                ClientToServerMsg::GetTestsAndCompletions { user_id };
-                ServerToClientMsg::TestsAndCompletionsForUser(result) => match result {
-                    Ok(tests_and_completions) => {
-                        debug!(?tests_and_completions);
-                        AppMsg::SetTestsAndCompletionsList(tests_and_completions)
-                    }
-                    Err(e) => e.into(),
+        let total = self.tests_and_completions.len();
+        let mut tests: Vec<SyncedTest> = self
+            .tests_and_completions
+            .values()
+            .filter(|test| {
+                self.view
+                    .subject_filter
+                    .as_ref()
+                    .is_none_or(|subject| test.test.subject == *subject)
+                    && test.test.matches_search(&self.search)
+            })
+            .cloned()
+            .collect();
+        if let Some(sort) = self.view.sort {
+            sort.sort_tests(&mut tests);
+        }
+
+        if self.print_view {
+            let onclick_print = ctx.link().callback(|_| AppMsg::Print);
+            let onclick_back = ctx.link().callback(|_| AppMsg::SetPrintView(false));
+            return html! {
+                <>
+                <div class="print-toolbar">
+                    <button class="primary" onclick={onclick_print}> { "Print" } </button>
+                    <button onclick={onclick_back}> { "Back to the list" } </button>
+                </div>
+                <PrintSummary {tests} />
+                </>
+            };
+        }
+
+        let onsubmit = ctx.link().callback(AppMsg::AddTest);
+        let onimport = ctx.link().callback(AppMsg::ImportCsv);
+        let onaddcompletion =
+            ctx.link()
+                .callback(|(test_id, completion): (i32, CompletionData)| {
+                    AppMsg::AddCompletion(test_id, completion)
+                });
+        let onupdatetest = ctx.link().callback(
+            |(test_id, test, done): (i32, TestData, Callback<Result<(), String>>)| {
+                AppMsg::UpdateTest {
+                    test_id,
+                    test,
+                    done,
+                }
+            },
+        );
+        let ondeletetest = ctx.link().callback(AppMsg::DeleteTest);
+        let onchangesort = ctx.link().callback(AppMsg::ChangeSort);
+        let onchangesubject = ctx.link().callback(AppMsg::ChangeSubjectFilter);
+        let onselectsubject = ctx.link().callback(AppMsg::ChangeSubjectFilter);
+        let onchangesearch = ctx.link().callback(AppMsg::ChangeSearch);
+        let ontoggleexpanded = ctx.link().callback(AppMsg::ToggleExpanded);
+        let onclick_refresh = ctx.link().callback(|_| AppMsg::DataChanged);
+        let onclick_export = ctx.link().callback(|_| AppMsg::ExportCsv);
+        let onclick_print_view = ctx.link().callback(|_| AppMsg::SetPrintView(true));
+        let onclick_grouped = {
+            let grouped = self.view.grouped;
+            ctx.link().callback(move |_| AppMsg::SetGrouped(!grouped))
+        };
+        let list = match &self.list_state {
+            ListState::Loading => html! { <Spinner /> },
+            ListState::Failed(message) => {
+                let onclick = ctx.link().callback(|_| AppMsg::RetryRefresh);
+                html! {
+                    <div class="list-failed">
+                        <p> { format!("Couldn't load your tests: {message}") } </p>
+                        <button disabled={!self.online} {onclick}> { "Retry" } </button>
+                    </div>
                 }
             }
-            .emit(user.id.clone()),
-            None => {
-                panic!("Cannot refresh tests_and_completions list until the user has logged in")
+            ListState::Loaded if self.tests_and_completions.is_empty() => html! { <EmptyState /> },
+            ListState::Loaded | ListState::Cached => {
+                let shown = tests.len();
+                let (points, undated) = trend_points(self.tests_and_completions.values());
+                // Only render one page of cards, since rendering hundreds at once is slow
+                let page = paginate(shown, self.page_size, self.page);
+                let page_controls = html! {
+                    <PageControls
+                        page={page.clone()}
+                        size={self.page_size}
+                        onchangepage={ctx.link().callback(AppMsg::ChangePage)}
+                        onchangesize={ctx.link().callback(AppMsg::ChangePageSize)} />
+                };
+                let list = if shown == 0 && !self.search.trim().is_empty() {
+                    let onclick = ctx.link().callback(|_| AppMsg::ChangeSearch(String::new()));
+                    html! {
+                        <div class="no-matches">
+                            <p> { format!("No tests match \"{}\"", self.search.trim()) } </p>
+                            <button {onclick}> { "Clear search" } </button>
+                        </div>
+                    }
+                } else {
+                    html! {
+                        <ListOfTestsAndCompletions
+                            list={tests[page.range].to_vec()}
+                            grouped={self.view.grouped}
+                            expanded={self.view.expanded_tests.clone()}
+                            {ontoggleexpanded}
+                            {onaddcompletion}
+                            {onupdatetest}
+                            {ondeletetest}
+                            settings={self.settings.clone()} />
+                    }
+                };
+
+                html! {
+                    <>
+                    if self.list_state == ListState::Cached && self.online {
+                        <div class="refreshing" role="status"> { "Refreshing…" } </div>
+                    }
+                    <StreakPanel streaks={self.streaks()} unit={self.settings.streak_unit} />
+                    <SubjectSummaryPanel
+                        summaries={subject_summaries(self.tests_and_completions.values())}
+                        selected={self.view.subject_filter.clone()}
+                        onselect={onselectsubject} />
+                    <TrendChart {points} {undated} />
+                    <div class="list-controls">
+                        <SearchBox search={self.search.clone()} onchange={onchangesearch} />
+                        <SortSelect sort={self.view.sort} onchange={onchangesort} />
+                        <SubjectFilter
+                            subjects={self.subjects()}
+                            subject={self.view.subject_filter.clone()}
+                            onchange={onchangesubject} />
+                        <span class="test-count"> { format!("Showing {shown} of {total}") } </span>
+                        <button
+                            class="toggle-grouped"
+                            aria-pressed={self.view.grouped.to_string()}
+                            onclick={onclick_grouped}>
+                            { "Group by subject" }
+                        </button>
+                        <button
+                            class="refresh"
+                            title="Fetch your tests again"
+                            disabled={!self.online}
+                            onclick={onclick_refresh}>
+                            { "↻ Refresh" }
+                        </button>
+                        <button class="export" title="Download all your attempts as a spreadsheet" onclick={onclick_export}>
+                            { "Export CSV" }
+                        </button>
+                        <button class="print-view" title="Show a summary of these tests to print" onclick={onclick_print_view}>
+                            { "Print view" }
+                        </button>
+                    </div>
+                    {list}
+                    {page_controls}
+                    </>
+                }
             }
         };
-    }
-}
+        let error_message = match &self.error_message {
+            Some(msg) => html! {
+                <ErrorMessage
+                    msg={msg.clone()}
+                    on_dismiss={ctx.link().callback(|()| AppMsg::ChangeErrorMessage(None))} />
+            },
+            None => html! {},
+        };
 
-impl Default for App {
-    fn default() -> Self {
-        Self {
-            user: get_user(),
-            tests_and_completions: vec![],
-            error_message: None,
+        html! {
+            <>
+            <OfflineBanner
+                online={self.online}
+                queued={self.offline_queue.len()}
+                sending={self.sending_queue}
+                onsend={ctx.link().callback(|()| AppMsg::SendQueuedChanges)} />
+            <ExamCountdown
+                exams={self.exam_dates.clone()}
+                onchange={ctx.link().callback(AppMsg::SetExamDates)} />
+            <AddTestForm {onsubmit} rejected={self.rejected_test.clone()} />
+            <ImportForm {onimport} offline={!self.online} />
+            {error_message}
+            {list}
+            </>
         }
     }
-}
-
-impl Component for App {
-    type Message = AppMsg;
-    type Properties = ();
 
-    fn create(ctx: &Context<Self>) -> Self {
-        let app = Self::default();
-        // If the user is logged in from last time, then initiate the
-        // async callback to refresh the list
-        if app.user.is_some() {
-            app.refresh_tests_and_completions_list(ctx);
-        }
-        app
-    }
+    /// Get the HTML for the page of the test with the given ID. If it isn't in the list, then it's
+    /// being fetched, and the page shows how that's going.
+    #[instrument(skip(self, ctx))]
+    fn view_test_page(&self, ctx: &Context<Self>, test_id: i32) -> Html {
+        let fetch = self
+            .test_fetch
+            .as_ref()
+            .filter(|(id, _)| *id == test_id)
+            .map(|(_, fetch)| fetch);
+        let page = match (self.tests_and_completions.get(&test_id), fetch) {
+            (Some(test), _) => {
+                let onaddcompletion =
+                    ctx.link()
+                        .callback(|(test_id, completion): (i32, CompletionData)| {
+                            AppMsg::AddCompletion(test_id, completion)
+                        });
+                let onupdatetest = ctx.link().callback(
+                    |(test_id, test, done): (i32, TestData, Callback<Result<(), String>>)| {
+                        AppMsg::UpdateTest {
+                            test_id,
+                            test,
+                            done,
+                        }
+                    },
+                );
+                let ondeletetest = ctx.link().callback(AppMsg::DeleteTest);
 
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        let content = match self.user {
-            Some(_) => self.view_main_screen(ctx),
-            None => self.view_login_screen(ctx),
+                // The key stops the edit form from staying open when going to another test
+                html! {
+                    <TestPage
+                        key={test_id}
+                        test_and_completions={test.clone()}
+                        {onaddcompletion}
+                        {onupdatetest}
+                        {ondeletetest}
+                        settings={self.settings.clone()} />
+                }
+            }
+            (None, Some(TestFetch::NotFound)) => html! { <TestNotFound /> },
+            (None, Some(TestFetch::Failed(message))) => {
+                let onclick = ctx.link().callback(move |_| AppMsg::FetchTest(test_id));
+                html! {
+                    <div class="list-failed">
+                        <p> { t!("test-page.load-failed", reason = message) } </p>
+                        <button disabled={!self.online} {onclick}> { t!("test-page.retry") } </button>
+                    </div>
+                }
+            }
+            (None, Some(TestFetch::Fetching) | None) => html! { <Spinner /> },
+        };
+        let error_message = match &self.error_message {
+            Some(msg) => html! {
+                <ErrorMessage
+                    msg={msg.clone()}
+                    on_dismiss={ctx.link().callback(|()| AppMsg::ChangeErrorMessage(None))} />
+            },
+            None => html! {},
         };
 
         html! {
             <>
-            <Navbar />
-            <div id="content">
-                {content}
-            </div>
+            <OfflineBanner
+                online={self.online}
+                queued={self.offline_queue.len()}
+                sending={self.sending_queue}
+                onsend={ctx.link().callback(|()| AppMsg::SendQueuedChanges)} />
+            {error_message}
+            {page}
             </>
         }
     }
 
-    #[instrument(skip_all)]
-    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
-        trace!(?msg, "Updating in reponse to message");
-        match msg {
-            AppMsg::AuthenticateUser(user, remember_me) => {
-                let user_str = ron::to_string(&user)
-                    .expect_or_log("We should be able to serialize a User to a String");
+    /// Show a new test straight away with a placeholder ID, and then send it to the server, which
+    /// either confirms it with its real ID or fails, in which case it gets removed again. While
+    /// offline, it's queued instead.
+    fn add_test(&mut self, ctx: &Context<Self>, test: TestData) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot add a test until the user has logged in")
+        };
 
-                session_storage()
-                    .set_item(STORAGE_KEY_USER, &user_str)
-                    .expect_or_log(
-                        "We should be able to set a sessionStorage value without a problem",
-                    );
+        let pending_id = self.next_pending_id;
+        self.next_pending_id -= 1;
+        let mutation = self
+            .online
+            .then(|| self.begin_mutation(ctx, pending_id, "Couldn't add the test"));
+        self.tests_and_completions.insert(
+            pending_id,
+            SyncedTest {
+                id: pending_id,
+                test: test.clone(),
+                completions: vec![],
+                best_completion_id: None,
+            },
+        );
 
-                if remember_me {
-                    local_storage()
-                        .set_item(STORAGE_KEY_USER, &user_str)
-                        .expect_or_log(
-                            "We should be able to set a localStorage value without a problem",
-                        );
-                }
+        let Some(mutation) = mutation else {
+            let description = format!("the new test {}", test_name(&test));
+            let user_id = user.id;
+            self.queue_change(
+                description,
+                ClientToServerMsg::AddTest {
+                    token,
+                    user_id,
+                    test,
+                },
+            );
+            return;
+        };
 
-                self.user = Some(user);
-                self.error_message = None;
+        send_message_to_server! {
+            ctx;
+            paths::TESTS;
+            |(token, user_id, test, pending_id, mutation): (String, String, TestData, i32, u32)|;
+            {
+                debug!(?test, "Adding test");
+            };
+            ClientToServerMsg::AddTest { token, user_id, test };
+            ServerToClientMsg::AddTestResponse(result) => match result {
+                Ok(id) => {
+                    debug!(?id, "Added test");
+                    AppMsg::TestAdded { pending_id, id, mutation }
+                }
+                Err(e) => AppMsg::MutationFailed { mutation, error: Box::new(e.into()) },
+            };
+            else |error| AppMsg::MutationFailed { mutation, error: Box::new(error) }
+        }
+        .emit((token, user.id, test, pending_id, mutation));
+    }
 
-                self.tests_and_completions = vec![];
-                self.refresh_tests_and_completions_list(ctx);
+    /// Send a CSV of tests and completions to the server to import. Invalid rows are reported
+    /// rather than stopping the whole import.
+    fn import_csv(&self, ctx: &Context<Self>, csv: String) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot import tests until the user has logged in")
+        };
 
-                true
+        send_message_to_server! {
+            ctx;
+            paths::IMPORT;
+            |(token, user_id, csv): (String, String, String)|;
+            {
+                debug!(bytes = csv.len(), "Importing CSV");
+            };
+            ClientToServerMsg::ImportCsv { token, user_id, csv, strict: false };
+            ServerToClientMsg::ImportResult(result) => match result {
+                Ok(summary) => AppMsg::Imported(summary),
+                Err(e) => e.into(),
             }
-            AppMsg::SetTestsAndCompletionsList(list) => {
-                self.tests_and_completions = list;
-                true
+        }
+        .emit((token, user.id, csv));
+    }
+
+    /// Send new details for a test to the server, and then tell the form how it went. While
+    /// offline, they're shown straight away and queued instead.
+    fn update_test(
+        &mut self,
+        ctx: &Context<Self>,
+        test_id: i32,
+        test: TestData,
+        done: Callback<Result<(), String>>,
+    ) {
+        if !self.online {
+            let Some(Session { user, token }) = self.session.clone() else {
+                panic!("Cannot update a test until the user has logged in")
+            };
+            let description = format!("the changes to {}", test_name(&test));
+            let msg = ClientToServerMsg::UpdateTest {
+                token,
+                user_id: user.id,
+                test_id,
+                test: test.clone(),
+            };
+            self.queue_change(description, msg);
+            if let Some(synced) = self.tests_and_completions.get_mut(&test_id) {
+                synced.test = test;
             }
+            done.emit(Ok(()));
+            return;
+        }
+
+        match &self.session {
+            Some(session) => send_message_to_server! {
+                ctx;
+                paths::TESTS;
+                |(token, user_id, test_id, test, done): (
+                    String,
+                    String,
+                    i32,
+                    TestData,
+                    Callback<Result<(), String>>,
+                )|;
+                {
+                    debug!(?test_id, ?test, "Updating test");
+                };
+                ClientToServerMsg::UpdateTest { token, user_id, test_id, test: test.clone() };
+                ServerToClientMsg::UpdateTestResponse(result) => match result {
+                    Ok(()) => AppMsg::TestUpdated { test_id, test, done: done.clone() },
+                    Err(e) => AppMsg::UpdateTestFailed {
+                        error: Box::new(e.into()),
+                        done: done.clone(),
+                    },
+                };
+                else |error| AppMsg::UpdateTestFailed {
+                    error: Box::new(error),
+                    done: done.clone(),
+                }
+            }
+            .emit((
+                session.token.clone(),
+                session.user.id.clone(),
+                test_id,
+                test,
+                done,
+            )),
+            None => panic!("Cannot update a test until the user has logged in"),
+        };
+    }
+
+    /// Send the user's current and new passwords to the server, and then tell the form how it went.
+    fn change_password(
+        &self,
+        ctx: &Context<Self>,
+        current_password: String,
+        new_password: String,
+        done: Callback<Result<(), ChangePasswordError>>,
+    ) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot change the password until the user has logged in")
+        };
+
+        send_message_to_server! {
+            ctx;
+            paths::USERS;
+            |(token, user_id, current_password, new_password, done): (
+                String,
+                String,
+                String,
+                String,
+                Callback<Result<(), ChangePasswordError>>,
+            )|;
+            {
+                debug!("Changing password");
+            };
+            ClientToServerMsg::ChangePassword { token, user_id, current_password, new_password };
+            ServerToClientMsg::ChangePasswordResponse(result) => match result {
+                Ok(()) => AppMsg::PasswordChanged(done.clone()),
+                Err(e) => AppMsg::ChangePasswordFailed {
+                    error: Box::new(e.into()),
+                    done: done.clone(),
+                },
+            };
+            else |error| AppMsg::ChangePasswordFailed {
+                error: Box::new(error),
+                done: done.clone(),
+            }
+        }
+        .emit((token, user.id, current_password, new_password, done));
+    }
+
+    /// Ask the server to delete the user's account, and then tell the dialog if it failed.
+    fn delete_account(
+        &self,
+        ctx: &Context<Self>,
+        password: String,
+        done: Callback<Result<(), String>>,
+    ) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot delete the account until the user has logged in")
+        };
+
+        send_message_to_server! {
+            ctx;
+            paths::USERS;
+            |(token, user_id, password, done): (
+                String,
+                String,
+                String,
+                Callback<Result<(), String>>,
+            )|;
+            {
+                debug!("Deleting account");
+            };
+            ClientToServerMsg::DeleteAccount { token, user_id, password };
+            ServerToClientMsg::DeleteAccountResponse(result) => match result {
+                Ok(()) => AppMsg::AccountDeleted(done.clone()),
+                Err(e) => AppMsg::DeleteAccountFailed {
+                    error: Box::new(e.into()),
+                    done: done.clone(),
+                },
+            };
+            else |error| AppMsg::DeleteAccountFailed {
+                error: Box::new(error),
+                done: done.clone(),
+            }
+        }
+        .emit((token, user.id, password, done));
+    }
+
+    /// Remove a test from the list straight away, and then ask the server to delete it. If that
+    /// fails, then the test gets put back. While offline, it's queued instead.
+    fn delete_test(&mut self, ctx: &Context<Self>, test_id: i32) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot delete a test until the user has logged in")
+        };
+
+        let mutation = self
+            .online
+            .then(|| self.begin_mutation(ctx, test_id, "Couldn't delete the test"));
+        let removed = self.tests_and_completions.remove(&test_id);
+        if self.view.expanded_tests.remove(&test_id) {
+            self.save_preferences();
+        }
+        self.reset_subject_filter_if_gone();
+
+        let Some(mutation) = mutation else {
+            let description = match removed {
+                Some(removed) => format!("the deletion of {}", test_name(&removed.test)),
+                None => "the deletion of a test".to_string(),
+            };
+            let user_id = user.id;
+            self.queue_change(
+                description,
+                ClientToServerMsg::DeleteTest {
+                    token,
+                    user_id,
+                    test_id,
+                },
+            );
+            return;
+        };
+
+        send_message_to_server! {
+            ctx;
+            paths::TESTS;
+            |(token, user_id, test_id, mutation): (String, String, i32, u32)|;
+            {
+                debug!(?test_id, "Deleting test");
+            };
+            ClientToServerMsg::DeleteTest { token, user_id, test_id };
+            ServerToClientMsg::DeleteTestResponse(result) => match result {
+                Ok(()) => AppMsg::TestDeleted(mutation),
+                Err(e) => AppMsg::MutationFailed { mutation, error: Box::new(e.into()) },
+            };
+            else |error| AppMsg::MutationFailed { mutation, error: Box::new(error) }
+        }
+        .emit((token, user.id, test_id, mutation));
+    }
+
+    /// Show a new completion straight away with a placeholder ID, and then send it to the server,
+    /// which either confirms it with its real ID or fails, in which case it gets removed again.
+    /// While offline, it's queued instead.
+    fn add_completion(&mut self, ctx: &Context<Self>, test_id: i32, completion: CompletionData) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot add a completion until the user has logged in")
+        };
+        if !self.tests_and_completions.contains_key(&test_id) {
+            warn!(
+                ?test_id,
+                "Tried to add a completion to a test that isn't in the list"
+            );
+            return;
+        }
+
+        let pending_id = self.next_pending_id;
+        self.next_pending_id -= 1;
+        let mutation = self
+            .online
+            .then(|| self.begin_mutation(ctx, test_id, "Couldn't add the attempt"));
+        let mut name = None;
+        if let Some(test) = self.tests_and_completions.get_mut(&test_id) {
+            test.completions.push((pending_id, completion.clone()));
+            name = Some(test_name(&test.test));
+        }
+
+        let Some(mutation) = mutation else {
+            let description = format!(
+                "the new attempt at {}",
+                name.unwrap_or_else(|| "a test".to_string())
+            );
+            let msg = ClientToServerMsg::AddCompletion {
+                token,
+                user_id: user.id,
+                test_id,
+                completion,
+            };
+            self.queue_change(description, msg);
+            return;
+        };
+
+        send_message_to_server! {
+            ctx;
+            paths::TESTS;
+            |(token, user_id, test_id, pending_id, mutation, completion): (
+                String,
+                String,
+                i32,
+                i32,
+                u32,
+                CompletionData,
+            )|;
+            {
+                debug!(?test_id, ?completion, "Adding completion");
+            };
+            ClientToServerMsg::AddCompletion { token, user_id, test_id, completion };
+            ServerToClientMsg::AddCompletionResponse(result) => match result {
+                Ok(id) => {
+                    debug!(?id, "Added completion");
+                    AppMsg::CompletionAdded { test_id, pending_id, id, mutation }
+                }
+                Err(e) => AppMsg::MutationFailed { mutation, error: Box::new(e.into()) },
+            };
+            else |error| AppMsg::MutationFailed { mutation, error: Box::new(error) }
+        }
+        .emit((token, user.id, test_id, pending_id, mutation, completion));
+    }
+
+    /// Keep a change that was made offline, so that it can be sent to the server once the browser
+    /// is back online. The change has already been shown, and it can't be rolled back, so if the
+    /// server refuses it, then it stays shown until the list is fetched again.
+    fn queue_change(&mut self, description: String, msg: ClientToServerMsg) {
+        debug!(?description, "Queueing change made offline");
+        self.offline_queue
+            .push_back(QueuedChange { description, msg });
+        set_offline_queue(&self.offline_queue);
+    }
+
+    /// Send the oldest change that was made offline to the server. The rest are sent after it, one
+    /// at a time, so that they're applied in the order that they were made.
+    ///
+    /// Once there are none left, the whole list is fetched again, since the changes were only
+    /// shown with placeholder IDs. If there weren't any changes to send, then the list is just
+    /// refreshed as normal.
+    fn send_queued_changes(&mut self, ctx: &Context<Self>) {
+        let Some(change) = self.offline_queue.front().cloned() else {
+            if mem::take(&mut self.sending_queue) {
+                self.synced_at = None;
+                etags::clear();
+                if !self.tests_and_completions.is_empty() {
+                    self.list_state = ListState::Cached;
+                }
+            }
+            self.refresh_tests_and_completions_list(ctx);
+            return;
+        };
+
+        self.sending_queue = true;
+        send_message_to_server! {
+            ctx;
+            paths::TESTS;
+            |msg: ClientToServerMsg|;
+            {
+                debug!(?msg, "Sending change made offline");
+            };
+            msg;
+            response @ (ServerToClientMsg::AddTestResponse(_)
+                | ServerToClientMsg::UpdateTestResponse(_)
+                | ServerToClientMsg::DeleteTestResponse(_)
+                | ServerToClientMsg::AddCompletionResponse(_)) => {
+                AppMsg::QueuedChangeSent(response.error().cloned().map_or(Ok(()), Err))
+            };
+            else |error| AppMsg::SendingQueuedChangeFailed(Box::new(error))
+        }
+        .emit(change.msg);
+    }
+
+    /// Record a change to the given test before showing it, so that it can be rolled back if the
+    /// server refuses it or doesn't confirm it within [`MUTATION_TIMEOUT`]. This must be called
+    /// before changing the test, since it takes a snapshot of it.
+    ///
+    /// This returns the ID of the pending mutation, which the response must include, so that it can
+    /// be confirmed or rolled back.
+    fn begin_mutation(&mut self, ctx: &Context<Self>, test_id: i32, failure: &'static str) -> u32 {
+        let mutation = self.next_mutation_id;
+        self.next_mutation_id += 1;
+        self.pending_mutations.insert(
+            mutation,
+            PendingMutation {
+                test_id,
+                snapshot: self.tests_and_completions.get(&test_id).cloned(),
+                failure,
+            },
+        );
+
+        ctx.link().send_future(async move {
+            sleep(MUTATION_TIMEOUT).await;
+            AppMsg::MutationTimedOut(mutation)
+        });
+        mutation
+    }
+
+    /// Why the test with the given placeholder ID can't be changed yet. It's either waiting for the
+    /// server to confirm it, or it was added offline, in which case there's no pending mutation.
+    fn still_saving(&self, test_id: i32) -> &'static str {
+        if self
+            .pending_mutations
+            .values()
+            .any(|mutation| mutation.test_id == test_id)
+        {
+            STILL_SAVING
+        } else {
+            SAVED_WHEN_ONLINE
+        }
+    }
+
+    /// Roll back a change that the server didn't confirm, by putting back the snapshot of its
+    /// test, and tell the user why. This does nothing and returns `false` if the change has
+    /// already been confirmed or rolled back.
+    fn roll_back(&mut self, ctx: &Context<Self>, mutation: u32, reason: &str) -> bool {
+        let Some(PendingMutation {
+            test_id,
+            snapshot,
+            failure,
+        }) = self.pending_mutations.remove(&mutation)
+        else {
+            return false;
+        };
+
+        warn!(?test_id, ?reason, "Rolling back change");
+        match snapshot {
+            Some(test) => {
+                self.tests_and_completions.insert(test_id, test);
+            }
+            None => {
+                self.tests_and_completions.remove(&test_id);
+            }
+        }
+        self.push_toast(ctx, Severity::Error, format!("{failure}: {reason}"));
+        true
+    }
+
+    /// If the server refused a new test because one of its fields is invalid, then remove the test
+    /// and put it back in the add test form, with the error under that field. Any other error is
+    /// given back, to roll the change back and report it as usual.
+    fn reject_new_test(&mut self, mutation: u32, error: SharedError) -> Result<(), SharedError> {
+        let Some(PendingMutation {
+            test_id,
+            snapshot: None,
+            ..
+        }) = self.pending_mutations.get(&mutation)
+        else {
+            return Err(error);
+        };
+        let test_id = *test_id;
+        let Some(test) = self
+            .tests_and_completions
+            .get(&test_id)
+            .map(|synced| synced.test.clone())
+        else {
+            return Err(error);
+        };
+
+        let mut field_errors = FieldErrors::default();
+        field_errors.insert_error(error, &TestData::FIELDS)?;
+
+        warn!(?test_id, ?field_errors, "The server refused the new test");
+        self.pending_mutations.remove(&mutation);
+        self.tests_and_completions.remove(&test_id);
+        self.rejected_test = Some(RejectedTest { test, field_errors });
+        Ok(())
+    }
+
+    /// Show a toast in the corner of the screen, which goes away by itself after
+    /// [`TOAST_DURATION`]. Toasts stack up, so that showing one never hides another, unless there
+    /// are more than [`MAX_TOASTS`].
+    fn push_toast(&mut self, ctx: &Context<Self>, severity: Severity, message: String) {
+        let id = self.next_toast_id;
+        self.next_toast_id += 1;
+        self.toasts.push_back(QueuedToast {
+            id,
+            severity,
+            message,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+
+        ctx.link().send_future(async move {
+            sleep(TOAST_DURATION).await;
+            AppMsg::DismissToast(id)
+        });
+    }
+
+    /// Refresh the internal [`tests_and_completions`](App::tests_and_completions) attribute by
+    /// creating an async callback to get the changes since the last sync from the server and send
+    /// the [`MergeTestsSync`](AppMsg::MergeTestsSync) message to the app.
+    ///
+    /// If this is the first sync, then the list goes back to [`ListState::Loading`] until it's done,
+    /// unless the cached list is being shown.
+    ///
+    /// While the browser is offline, this doesn't try, and just leaves the cached list showing.
+    fn refresh_tests_and_completions_list(&mut self, ctx: &Context<Self>) {
+        if !self.online {
+            debug!("Not refreshing the list while offline");
+            if self.list_state == ListState::Loading {
+                self.list_state = ListState::Failed(
+                    "you're offline, and they haven't been saved on this device".to_string(),
+                );
+            }
+            return;
+        }
+
+        if self.synced_at.is_none() && self.list_state != ListState::Cached {
+            self.list_state = ListState::Loading;
+        }
+
+        match &self.session {
+            Some(session) => send_message_to_server! {
+                ctx;
+                paths::TESTS;
+                |(token, user_id, since): (String, String, Option<DateTime<Utc>>)|;
+                {};
+                ClientToServerMsg::GetTestsAndCompletions { token, user_id, since, sort: None };
+                ServerToClientMsg::TestsAndCompletionsForUser(result) => match result {
+                    Ok(sync) => {
+                        debug!(?sync);
+                        AppMsg::MergeTestsSync(sync)
+                    }
+                    Err(e) => AppMsg::RefreshFailed(Box::new(e.into())),
+                };
+                else |error| AppMsg::RefreshFailed(Box::new(error))
+            }
+            .emit((
+                session.token.clone(),
+                session.user.id.clone(),
+                self.synced_at,
+            )),
+            None => {
+                panic!("Cannot refresh tests_and_completions list until the user has logged in")
+            }
+        };
+    }
+
+    /// If the page of a test is showing, but the test isn't in the list, then fetch it, unless it's
+    /// already been fetched. This happens when a link to the page is opened before the list has
+    /// loaded, or when the test has gone from the list.
+    fn fetch_missing_test(&mut self, ctx: &Context<Self>) {
+        let Some(Route::Test(test_id)) = self.route else {
+            self.test_fetch = None;
+            return;
+        };
+        if self.tests_and_completions.contains_key(&test_id)
+            || matches!(self.test_fetch, Some((id, _)) if id == test_id)
+        {
+            return;
+        }
+        self.fetch_test(ctx, test_id);
+    }
+
+    /// Ask the server for the test with the given ID, for its page. While offline, this doesn't
+    /// try, and just says so on the page.
+    fn fetch_test(&mut self, ctx: &Context<Self>, test_id: i32) {
+        let Some(Session { user, token }) = self.session.clone() else {
+            panic!("Cannot fetch a test until the user has logged in")
+        };
+        if !self.online {
+            debug!(?test_id, "Not fetching a test while offline");
+            self.test_fetch = Some((
+                test_id,
+                TestFetch::Failed(
+                    "you're offline, and it hasn't been saved on this device".to_string(),
+                ),
+            ));
+            return;
+        }
+
+        self.test_fetch = Some((test_id, TestFetch::Fetching));
+        send_message_to_server! {
+            ctx;
+            paths::TESTS;
+            |(token, user_id, test_id): (String, String, i32)|;
+            {
+                debug!(?test_id, "Fetching test");
+            };
+            ClientToServerMsg::GetTest { token, user_id, test_id };
+            ServerToClientMsg::TestResponse(result) => match result {
+                Ok(test) => AppMsg::TestFetched(test),
+                Err(e) => AppMsg::FetchTestFailed { test_id, error: Box::new(e.into()) },
+            };
+            else |error| AppMsg::FetchTestFailed { test_id, error: Box::new(error) }
+        }
+        .emit((token, user.id, test_id));
+    }
+
+    /// Show the current user's cached tests, if there are any, until the first sync replaces
+    /// them.
+    fn load_cached_tests(&mut self) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        if let Some(tests) = get_cached_tests(&session.user.id) {
+            // An empty cache would show the empty state, which might be wrong by now
+            if !tests.is_empty() {
+                debug!(count = tests.len(), "Showing cached tests");
+                self.tests_and_completions = tests;
+                self.list_state = ListState::Cached;
+            }
+        }
+    }
+
+    /// Forget the current session, both in the model and in browser storage, and start again as
+    /// if the app had just been opened, which sends the user back to the login screen.
+    ///
+    /// Toasts are kept, so that they can say why. So are the counters for pending IDs, so that a
+    /// response to a request from before can never be mistaken for one from after.
+    fn log_out(&mut self) {
+        if let Some(session) = &self.session {
+            clear_cached_tests(&session.user.id);
+        }
+
+        session_storage()
+            .remove_item(STORAGE_KEY_USER)
+            .expect_or_log("We should be able to remove a sessionStorage value without a problem");
+        local_storage()
+            .remove_item(STORAGE_KEY_USER)
+            .expect_or_log("We should be able to remove a localStorage value without a problem");
+        // Queued changes have the old session's token in them, so they couldn't be sent anyway
+        if !self.offline_queue.is_empty() {
+            warn!(
+                queued = self.offline_queue.len(),
+                "Logging out with changes made offline that haven't been sent"
+            );
+        }
+        session_storage()
+            .remove_item(STORAGE_KEY_OFFLINE_QUEUE)
+            .expect_or_log("We should be able to remove a sessionStorage value without a problem");
+        set_body_class(PRINT_VIEW_CLASS, false);
+        etags::clear();
+
+        // This also drops the push socket, which closes it
+        *self = Self {
+            toasts: mem::take(&mut self.toasts),
+            next_toast_id: self.next_toast_id,
+            next_pending_id: self.next_pending_id,
+            next_mutation_id: self.next_mutation_id,
+            next_login_id: self.next_login_id,
+            online: self.online,
+            connectivity: self.connectivity.take(),
+            router: self.router.take(),
+            offline_queue: VecDeque::new(),
+            ..Self::default()
+        };
+    }
+
+    /// The user's practice streaks as of today, in the unit from their settings.
+    fn streaks(&self) -> Streaks {
+        streaks(
+            &completion_dates(self.tests_and_completions.values()),
+            today(),
+            self.settings.streak_unit,
+        )
+    }
+
+    /// The distinct subjects of the user's tests, in alphabetical order.
+    fn subjects(&self) -> Vec<String> {
+        self.tests_and_completions
+            .values()
+            .map(|test| test.test.subject.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Stop filtering by subject if none of the tests have that subject any more, like after its
+    /// last test was deleted, so that the list doesn't go empty for no obvious reason.
+    fn reset_subject_filter_if_gone(&mut self) {
+        if let Some(subject) = &self.view.subject_filter {
+            if !self
+                .tests_and_completions
+                .values()
+                .any(|test| test.test.subject == *subject)
+            {
+                debug!(
+                    ?subject,
+                    "Filtered subject has gone, so showing all subjects"
+                );
+                self.view.subject_filter = None;
+                self.save_preferences();
+            }
+        }
+    }
+
+    /// Save how the list of tests looks for the current user, so that it's the same next time.
+    /// Every change to [`view`](App::view) goes through here.
+    fn save_preferences(&self) {
+        if let Some(session) = &self.session {
+            set_view_preferences(&session.user.id, &self.view);
+        }
+    }
+
+    /// Open a new push socket for the current session, replacing any old one.
+    fn open_push_socket(&mut self, ctx: &Context<Self>) {
+        self.push_socket = self.session.as_ref().and_then(|session| {
+            PushSocket::open(session, ctx.link().callback(|()| AppMsg::DataChanged)).map(Rc::new)
+        });
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        let session = get_session();
+        Self {
+            view: session
+                .as_ref()
+                .map(|session| get_view_preferences(&session.user.id))
+                .unwrap_or_default(),
+            session,
+            tests_and_completions: BTreeMap::new(),
+            list_state: ListState::Loading,
+            refresh_retried: false,
+            synced_at: None,
+            search: String::new(),
+            print_view: false,
+            page_size: get_page_size(),
+            page: 0,
+            settings: get_settings(),
+            settings_open: false,
+            exam_dates: get_exam_dates(),
+            error_message: None,
+            login_field_errors: FieldErrors::default(),
+            rejected_test: None,
+            push_socket: None,
+            next_pending_id: -1,
+            pending_mutations: BTreeMap::new(),
+            next_mutation_id: 0,
+            login_in_flight: None,
+            next_login_id: 0,
+            toasts: VecDeque::new(),
+            next_toast_id: 0,
+            online: is_online(),
+            connectivity: None,
+            offline_queue: get_offline_queue(),
+            sending_queue: false,
+            route: current_route(),
+            router: None,
+            test_fetch: None,
+        }
+    }
+}
+
+impl Component for App {
+    type Message = AppMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut app = Self::default();
+        i18n::set_language(app.settings.language);
+        app.connectivity = Some(Rc::new(ConnectivityListener::listen(
+            ctx.link().callback(AppMsg::SetOnline),
+        )));
+        app.router = Some(Rc::new(RouteListener::listen(
+            ctx.link().callback(AppMsg::Navigate),
+        )));
+
+        // If the user is logged in from last time, then initiate the
+        // async callback to refresh the list, after sending anything
+        // that was changed offline before the page was reloaded
+        if app.session.is_some() {
+            app.load_cached_tests();
+            app.send_queued_changes(ctx);
+            app.open_push_socket(ctx);
+            app.fetch_missing_test(ctx);
+        }
+        app
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let content = match self.session {
+            Some(_) => self.view_main_screen(ctx),
+            None => self.view_login_screen(ctx),
+        };
+        let onchangetheme = {
+            let settings = self.settings.clone();
+            ctx.link().callback(move |theme| {
+                AppMsg::ChangeSettings(Settings {
+                    theme,
+                    ..settings.clone()
+                })
+            })
+        };
+        let onchangepassword = ctx.link().callback(
+            |(current_password, new_password, done): (
+                String,
+                String,
+                Callback<Result<(), ChangePasswordError>>,
+            )| AppMsg::ChangePassword {
+                current_password,
+                new_password,
+                done,
+            },
+        );
+        let ondeleteaccount =
+            ctx.link()
+                .callback(|(password, done): (String, Callback<Result<(), String>>)| {
+                    AppMsg::DeleteAccount { password, done }
+                });
+        let toasts: Html = self
+            .toasts
+            .iter()
+            .map(|toast| {
+                let id = toast.id;
+                html! {
+                    <Toast
+                        key={id}
+                        severity={toast.severity}
+                        message={toast.message.clone()}
+                        ondismiss={ctx.link().callback(move |()| AppMsg::DismissToast(id))} />
+                }
+            })
+            .collect();
+
+        html! {
+            <>
+            <Navbar
+                theme={self.settings.theme}
+                language={i18n::language()}
+                onchangetheme={onchangetheme}
+                onopensettings={ctx.link().callback(|()| AppMsg::SetSettingsOpen(true))}
+                streak={self.streaks().current}
+                streak_unit={self.settings.streak_unit} />
+            // Function components only render again when their props change, so the content is
+            // rebuilt from scratch when the language changes
+            <div id="content" key={i18n::language().tag()}>
+                {content}
+            </div>
+            if self.settings_open {
+                <SettingsDialog
+                    settings={self.settings.clone()}
+                    onchange={ctx.link().callback(AppMsg::ChangeSettings)}
+                    onclose={ctx.link().callback(|()| AppMsg::SetSettingsOpen(false))}
+                    username={self.session.as_ref().map(|session| session.user.username.clone())}
+                    {onchangepassword}
+                    {ondeleteaccount}
+                    offline={!self.online} />
+            }
+            <div class="toasts"> {toasts} </div>
+            </>
+        }
+    }
+
+    #[instrument(skip_all)]
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        trace!(?msg, "Updating in reponse to message");
+        match msg {
+            AppMsg::LoginStarted => {
+                let id = self.next_login_id;
+                self.next_login_id += 1;
+                self.login_in_flight = Some(id);
+                self.login_field_errors = FieldErrors::default();
+                ctx.link().send_future(async move {
+                    sleep(LOGIN_TIMEOUT).await;
+                    AppMsg::LoginTimedOut(id)
+                });
+                true
+            }
+            AppMsg::LoginFailed(error) => {
+                self.login_in_flight = None;
+                match *error {
+                    AppMsg::SharedError(error) => {
+                        let mut field_errors = FieldErrors::default();
+                        match field_errors.insert_error(error, LOGIN_FIELDS) {
+                            Ok(()) => {
+                                warn!(?field_errors, "Validation error");
+                                self.error_message = None;
+                                self.login_field_errors = field_errors;
+                            }
+                            Err(error) => {
+                                self.update(ctx, AppMsg::SharedError(error));
+                            }
+                        }
+                    }
+                    error => {
+                        self.update(ctx, error);
+                    }
+                }
+                true
+            }
+            AppMsg::LoginTimedOut(id) => {
+                if self.login_in_flight != Some(id) {
+                    return false;
+                }
+                warn!("The server took too long to answer the login request");
+                self.login_in_flight = None;
+                self.error_message =
+                    Some("The server took too long to respond, so please try again".to_string());
+                true
+            }
+            AppMsg::AuthenticateUser(session, remember_me) => {
+                self.login_in_flight = None;
+                let session_str = ron::to_string(&session)
+                    .expect_or_log("We should be able to serialize a Session to a String");
+
+                session_storage()
+                    .set_item(STORAGE_KEY_USER, &session_str)
+                    .expect_or_log(
+                        "We should be able to set a sessionStorage value without a problem",
+                    );
+
+                if remember_me {
+                    local_storage()
+                        .set_item(STORAGE_KEY_USER, &session_str)
+                        .expect_or_log(
+                            "We should be able to set a localStorage value without a problem",
+                        );
+                }
+
+                self.view = get_view_preferences(&session.user.id);
+                self.session = Some(session);
+                self.error_message = None;
+
+                self.tests_and_completions.clear();
+                self.list_state = ListState::Loading;
+                self.synced_at = None;
+                etags::clear();
+                self.load_cached_tests();
+                self.refresh_tests_and_completions_list(ctx);
+                self.open_push_socket(ctx);
+                self.fetch_missing_test(ctx);
+
+                true
+            }
+            AppMsg::DataChanged => {
+                if self.session.is_some() {
+                    self.refresh_tests_and_completions_list(ctx);
+                }
+                false
+            }
+            AppMsg::MergeTestsSync(sync) => {
+                // If the last refresh failed, then the error about it is out of date now
+                if self.refresh_retried {
+                    self.error_message = None;
+                }
+                self.synced_at = Some(sync.synced_at);
+                sync.merge_into(&mut self.tests_and_completions);
+                if let Some(session) = &self.session {
+                    set_cached_tests(&session.user.id, &self.tests_and_completions);
+                }
+                self.reset_subject_filter_if_gone();
+                self.list_state = ListState::Loaded;
+                self.refresh_retried = false;
+                self.fetch_missing_test(ctx);
+                true
+            }
+            AppMsg::RefreshFailed(error) => {
+                if !self.refresh_retried {
+                    warn!(
+                        ?error,
+                        "Fetching the list of tests failed, so trying again soon"
+                    );
+                    self.refresh_retried = true;
+                    ctx.link().send_future(async {
+                        sleep(REFRESH_RETRY_DELAY).await;
+                        AppMsg::DataChanged
+                    });
+                    return false;
+                }
+
+                warn!(
+                    ?error,
+                    "Fetching the list of tests failed again, so giving up"
+                );
+                let message = error_reason(&error);
+                // Once the list has loaded, a failed refresh leaves it alone and just gets reported
+                if self.list_state == ListState::Loaded {
+                    self.error_message = Some(format!("Couldn't refresh your tests: {message}"));
+                } else if self.list_state == ListState::Cached {
+                    self.error_message = Some(format!(
+                        "Couldn't refresh your tests, so these are from your last visit: {message}"
+                    ));
+                    self.list_state = ListState::Loaded;
+                } else {
+                    self.list_state = ListState::Failed(message);
+                }
+                true
+            }
+            AppMsg::ExportCsv => {
+                let tests: Vec<SyncedTest> = self.tests_and_completions.values().cloned().collect();
+                let filename = format!(
+                    "test-tracker-export-{}.csv",
+                    format_date_input(Some(today()))
+                );
+                debug!(?filename, tests = tests.len(), "Exporting CSV");
+
+                match download_file(&filename, "text/csv", &tests_to_csv(&tests)) {
+                    Ok(()) => false,
+                    Err(e) => {
+                        error!(?e, "Couldn't download the CSV");
+                        self.push_toast(
+                            ctx,
+                            Severity::Error,
+                            "Couldn't export your tests".to_string(),
+                        );
+                        true
+                    }
+                }
+            }
+            AppMsg::ImportCsv(csv) => {
+                if self.session.is_some() {
+                    self.import_csv(ctx, csv);
+                }
+                false
+            }
+            AppMsg::Imported(ImportSummary {
+                tests_imported,
+                completions_imported,
+                errors,
+            }) => {
+                info!(
+                    tests_imported,
+                    completions_imported,
+                    errors = errors.len(),
+                    "Imported CSV"
+                );
+                self.push_toast(
+                    ctx,
+                    Severity::Success,
+                    format!("Imported {tests_imported} tests with {completions_imported} attempts"),
+                );
+                if let Some(first) = errors.first() {
+                    self.push_toast(
+                        ctx,
+                        Severity::Error,
+                        format!(
+                            "{} rows couldn't be imported, like line {}: {}",
+                            errors.len(),
+                            first.line,
+                            first.message
+                        ),
+                    );
+                }
+                self.update(ctx, AppMsg::DataChanged);
+                true
+            }
+            AppMsg::RetryRefresh => {
+                if self.session.is_some() {
+                    self.refresh_retried = false;
+                    self.refresh_tests_and_completions_list(ctx);
+                }
+                true
+            }
+            AppMsg::ChangeSort(sort) => {
+                debug!(?sort, "Changing sort");
+                self.view.sort = sort;
+                self.save_preferences();
+                self.page = 0;
+                true
+            }
+            AppMsg::ChangeSearch(search) => {
+                trace!(?search, "Changing search");
+                self.search = search;
+                self.page = 0;
+                true
+            }
+            AppMsg::ToggleExpanded(test_id) => {
+                if !self.view.expanded_tests.remove(&test_id) {
+                    self.view.expanded_tests.insert(test_id);
+                }
+                self.save_preferences();
+                true
+            }
+            AppMsg::SetPrintView(print_view) => {
+                debug!(?print_view, "Changing print view");
+                set_body_class(PRINT_VIEW_CLASS, print_view);
+                self.print_view = print_view;
+                true
+            }
+            AppMsg::Print => {
+                print_page();
+                false
+            }
+            AppMsg::SetGrouped(grouped) => {
+                debug!(?grouped, "Changing grouping");
+                self.view.grouped = grouped;
+                self.save_preferences();
+                self.page = 0;
+                true
+            }
+            AppMsg::ChangePage(page) => {
+                trace!(?page, "Changing page");
+                self.page = page;
+                true
+            }
+            AppMsg::ChangePageSize(page_size) => {
+                debug!(?page_size, "Changing page size");
+                set_preference(STORAGE_KEY_PAGE_SIZE, Some(&page_size));
+                self.page_size = page_size;
+                self.page = 0;
+                true
+            }
+            AppMsg::ChangeSettings(settings) => {
+                debug!(?settings, "Changing settings");
+                set_settings(&settings);
+                if settings.language != self.settings.language {
+                    i18n::set_language(settings.language);
+                }
+                self.settings = settings;
+                true
+            }
+            AppMsg::SetSettingsOpen(settings_open) => {
+                self.settings_open = settings_open;
+                true
+            }
+            AppMsg::ChangePassword {
+                current_password,
+                new_password,
+                done,
+            } => {
+                if self.session.is_some() {
+                    self.change_password(ctx, current_password, new_password, done);
+                }
+                false
+            }
+            AppMsg::PasswordChanged(done) => {
+                done.emit(Ok(()));
+                self.push_toast(ctx, Severity::Success, "Password changed".to_string());
+                true
+            }
+            AppMsg::DeleteAccount { password, done } => {
+                if self.session.is_some() {
+                    self.delete_account(ctx, password, done);
+                }
+                false
+            }
+            AppMsg::AccountDeleted(done) => {
+                done.emit(Ok(()));
+                info!("Account deleted");
+                self.log_out();
+                self.push_toast(
+                    ctx,
+                    Severity::Info,
+                    "Your account has been deleted".to_string(),
+                );
+                true
+            }
+            AppMsg::DeleteAccountFailed { error, done } => match *error {
+                AppMsg::SharedError(SharedError::InvalidPassword) => {
+                    warn!("Wrong password when deleting account");
+                    done.emit(Err("That password is wrong".to_string()));
+                    false
+                }
+                error => {
+                    done.emit(Err(format!(
+                        "Couldn't delete your account: {}",
+                        error_reason(&error)
+                    )));
+                    false
+                }
+            },
+            AppMsg::ChangePasswordFailed { error, done } => match *error {
+                AppMsg::SharedError(SharedError::InvalidPassword) => {
+                    warn!("Wrong current password");
+                    done.emit(Err(ChangePasswordError::WrongPassword));
+                    false
+                }
+                AppMsg::SharedError(SharedError::Validation { field, reason }) => {
+                    warn!(?field, ?reason, "Validation error");
+                    done.emit(Err(ChangePasswordError::InvalidNewPassword(reason)));
+                    false
+                }
+                error => {
+                    done.emit(Err(ChangePasswordError::Other(format!(
+                        "Couldn't change the password: {}",
+                        error_reason(&error)
+                    ))));
+                    false
+                }
+            },
+            AppMsg::SetExamDates(exam_dates) => {
+                debug!(?exam_dates, "Changing exam dates");
+                set_preference(STORAGE_KEY_EXAM_DATES, Some(&exam_dates));
+                self.exam_dates = exam_dates;
+                true
+            }
+            AppMsg::ChangeSubjectFilter(subject) => {
+                debug!(?subject, "Changing subject filter");
+                self.view.subject_filter = subject;
+                self.save_preferences();
+                self.page = 0;
+                true
+            }
+            AppMsg::AddTest(test) => {
+                if self.session.is_some() {
+                    self.error_message = None;
+                    self.rejected_test = None;
+                    self.add_test(ctx, test);
+                }
+                true
+            }
+            AppMsg::TestAdded {
+                pending_id,
+                id,
+                mutation,
+            } => {
+                self.pending_mutations.remove(&mutation);
+                self.push_toast(ctx, Severity::Success, "Test added".to_string());
+                // A sync might have already added the real test
+                if let Some(mut test) = self.tests_and_completions.remove(&pending_id) {
+                    test.id = id;
+                    self.tests_and_completions.entry(id).or_insert(test);
+                }
+                true
+            }
+            AppMsg::DeleteTest(test_id) | AppMsg::AddCompletion(test_id, _) if test_id < 0 => {
+                let message = self.still_saving(test_id);
+                self.push_toast(ctx, Severity::Info, message.to_string());
+                true
+            }
+            AppMsg::UpdateTest {
+                test_id,
+                test,
+                done,
+            } => {
+                if test_id < 0 {
+                    done.emit(Err(self.still_saving(test_id).to_string()));
+                } else if self.session.is_some() {
+                    self.update_test(ctx, test_id, test, done);
+                }
+                // While offline, the new details are shown straight away
+                !self.online
+            }
+            AppMsg::TestUpdated {
+                test_id,
+                test,
+                done,
+            } => {
+                done.emit(Ok(()));
+                self.error_message = None;
+                if let Some(synced) = self.tests_and_completions.get_mut(&test_id) {
+                    synced.test = test;
+                }
+                true
+            }
+            AppMsg::UpdateTestFailed { error, done } => match *error {
+                AppMsg::SharedError(SharedError::Validation { field, reason }) => {
+                    warn!(?field, ?reason, "Validation error");
+                    done.emit(Err(reason));
+                    false
+                }
+                AppMsg::SharedError(e @ SharedError::DuplicateTest { .. }) => {
+                    warn!(?e, "Test already exists");
+                    done.emit(Err(e.to_string()));
+                    false
+                }
+                error => {
+                    done.emit(Err("Couldn't save the changes".to_string()));
+                    self.update(ctx, error)
+                }
+            },
+            AppMsg::DeleteTest(test_id) => {
+                if self.session.is_some() {
+                    self.error_message = None;
+                    self.delete_test(ctx, test_id);
+                    // The test's page would only say that it's not found now
+                    if self.route == Some(Route::Test(test_id)) {
+                        navigate(Route::List);
+                    }
+                }
+                true
+            }
+            AppMsg::TestDeleted(mutation) => {
+                self.pending_mutations.remove(&mutation);
+                self.push_toast(ctx, Severity::Success, "Test deleted".to_string());
+                true
+            }
+            AppMsg::AddCompletion(test_id, completion) => {
+                if self.session.is_some() {
+                    self.error_message = None;
+                    self.add_completion(ctx, test_id, completion);
+                }
+                true
+            }
+            AppMsg::CompletionAdded {
+                test_id,
+                pending_id,
+                id,
+                mutation,
+            } => {
+                self.pending_mutations.remove(&mutation);
+                // A sync might have already replaced the placeholder with the real completion
+                if let Some((completion_id, _)) = self
+                    .tests_and_completions
+                    .get_mut(&test_id)
+                    .and_then(|test| {
+                        test.completions
+                            .iter_mut()
+                            .find(|(id, _)| *id == pending_id)
+                    })
+                {
+                    *completion_id = id;
+                }
+                true
+            }
+            AppMsg::MutationFailed { mutation, error } => match *error {
+                AppMsg::SharedError(error) => match self.reject_new_test(mutation, error) {
+                    Ok(()) => true,
+                    Err(error) => {
+                        let error = AppMsg::SharedError(error);
+                        self.roll_back(ctx, mutation, &error_reason(&error))
+                    }
+                },
+                error => self.roll_back(ctx, mutation, &error_reason(&error)),
+            },
+            AppMsg::MutationTimedOut(mutation) => {
+                self.roll_back(ctx, mutation, "the server took too long to respond")
+            }
+            AppMsg::Navigate(route) => {
+                debug!(?route, "Navigating");
+                self.route = route;
+                self.test_fetch = None;
+                if matches!(route, Some(Route::Test(_))) && self.print_view {
+                    set_body_class(PRINT_VIEW_CLASS, false);
+                    self.print_view = false;
+                }
+                if self.session.is_some() {
+                    self.fetch_missing_test(ctx);
+                }
+                true
+            }
+            AppMsg::FetchTest(test_id) => {
+                if self.session.is_some() {
+                    self.fetch_test(ctx, test_id);
+                }
+                true
+            }
+            AppMsg::TestFetched(test) => {
+                debug!(id = test.id, "Fetched test");
+                self.test_fetch = None;
+                // A sync might have already added it
+                self.tests_and_completions.entry(test.id).or_insert(test);
+                true
+            }
+            AppMsg::FetchTestFailed { test_id, error } => {
+                let fetch = match *error {
+                    AppMsg::SharedError(SharedError::DatabaseError(
+                        SharedDieselError::NotFound,
+                    )) => {
+                        warn!(?test_id, "Test not found");
+                        TestFetch::NotFound
+                    }
+                    error => {
+                        warn!(?test_id, ?error, "Fetching the test failed");
+                        TestFetch::Failed(error_reason(&error))
+                    }
+                };
+                self.test_fetch = Some((test_id, fetch));
+                true
+            }
+            AppMsg::NotModified => false,
             AppMsg::ChangeErrorMessage(msg) => {
                 self.error_message = msg;
                 true
@@ -310,34 +2456,135 @@ impl Component for App {
                 true
             }
             AppMsg::SharedError(error) => match error {
+                // When logging in, a missing user is the same as a wrong password, but otherwise
+                // something that the user was looking at has gone, so the list is out of date
+                SharedError::DatabaseError(SharedDieselError::NotFound)
+                    if self.session.is_some() =>
+                {
+                    warn!("Something in the list has gone from the server");
+                    self.push_toast(
+                        ctx,
+                        Severity::Error,
+                        "That test or attempt doesn't exist any more".to_string(),
+                    );
+                    self.refresh_tests_and_completions_list(ctx);
+                    true
+                }
                 SharedError::DatabaseError(SharedDieselError::NotFound)
                 | SharedError::InvalidPassword => {
                     warn!("Invalid username or password");
                     self.error_message = Some("Invalid username or password".to_string());
                     true
                 }
-                SharedError::DatabaseError(SharedDieselError::UniqueViolation(_, details, _))
-                    if details.clone().is_some_and(|s| s.contains("username")) =>
-                {
+                SharedError::UsernameTaken => {
                     warn!("Username already taken");
                     self.error_message = Some("Username already taken".to_string());
                     true
                 }
+                e @ SharedError::DuplicateTest { .. } => {
+                    warn!(?e, "Test already exists");
+                    self.error_message = Some(e.to_string());
+                    true
+                }
+                SharedError::Validation { field, reason } => {
+                    warn!(?field, ?reason, "Validation error");
+                    self.error_message = Some(reason);
+                    true
+                }
+                SharedError::Unauthorized => {
+                    // Every request that was in flight fails the same way, but only the first one
+                    // needs to log the user out
+                    if self.session.is_none() {
+                        return false;
+                    }
+
+                    warn!("Session has lapsed");
+                    self.log_out();
+                    self.error_message =
+                        Some("Your session has expired, please log in again".to_string());
+                    true
+                }
                 e => {
                     error!(?e);
-                    window()
-                        .alert_with_message(&format!("Error: {e:?}"))
-                        .expect_or_log("Error alerting the user");
+                    self.push_toast(ctx, Severity::Error, format!("Error: {e}"));
                     true
                 }
             },
             AppMsg::UnknownError(error) => {
                 error!(?error, "Unknown error");
-                window()
-                    .alert_with_message(&format!("Unknown error: {error:?}"))
-                    .expect_or_log("Error alerting the user");
+                self.push_toast(ctx, Severity::Error, format!("Unknown error: {error}"));
                 true
             }
+            AppMsg::DismissToast(id) => {
+                let count = self.toasts.len();
+                self.toasts.retain(|toast| toast.id != id);
+                self.toasts.len() != count
+            }
+            AppMsg::SetOnline(online) => {
+                info!(?online, "Connectivity changed");
+                self.online = online;
+                if online && self.session.is_some() {
+                    // The push socket will have closed while offline
+                    self.open_push_socket(ctx);
+                    if !self.sending_queue {
+                        self.send_queued_changes(ctx);
+                    }
+                }
+                true
+            }
+            AppMsg::SendQueuedChanges => {
+                if self.online && self.session.is_some() && !self.sending_queue {
+                    self.send_queued_changes(ctx);
+                }
+                true
+            }
+            AppMsg::QueuedChangeSent(result) => {
+                let Some(QueuedChange { description, .. }) = self.offline_queue.pop_front() else {
+                    return false;
+                };
+                set_offline_queue(&self.offline_queue);
+
+                match result {
+                    Ok(()) => {
+                        self.push_toast(ctx, Severity::Success, format!("Saved {description}"))
+                    }
+                    Err(e) => {
+                        warn!(?description, ?e, "Server refused a change made offline");
+                        self.push_toast(
+                            ctx,
+                            Severity::Error,
+                            format!("Couldn't save {description}: {e}"),
+                        );
+                    }
+                }
+
+                if self.online {
+                    self.send_queued_changes(ctx);
+                } else {
+                    self.sending_queue = false;
+                }
+                true
+            }
+            AppMsg::SendingQueuedChangeFailed(error) => match *error {
+                // The server got the change and refused it, so there's no point sending it again
+                AppMsg::SharedError(e) => self.update(ctx, AppMsg::QueuedChangeSent(Err(e))),
+                error => {
+                    warn!(
+                        ?error,
+                        "Couldn't send a change made offline, so keeping the rest for later"
+                    );
+                    self.sending_queue = false;
+                    self.push_toast(
+                        ctx,
+                        Severity::Error,
+                        format!(
+                            "Couldn't save the changes that you made offline: {}",
+                            error_reason(&error)
+                        ),
+                    );
+                    true
+                }
+            },
         }
     }
 }
@@ -357,5 +2604,16 @@ fn main() {
     );
 
     info!("Starting app");
-    yew::Renderer::<App>::new().render();
+    match server_url::server_url() {
+        Ok(_) => {
+            yew::Renderer::<App>::new().render();
+        }
+        Err(e) => {
+            error!(%e, "Can't start without a valid server URL");
+            yew::Renderer::<StartupError>::with_props(StartupErrorProps {
+                message: e.to_string(),
+            })
+            .render();
+        }
+    }
 }
@@ -0,0 +1,92 @@
+//! This module keeps track of whether the browser is online, and holds the changes that the user
+//! made while it wasn't, so that they can be sent to the server once it's back.
+
+use gloo_utils::window;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use test_tracker_shared::ClientToServerMsg;
+use tracing::{debug, warn};
+use wasm_bindgen::{closure::Closure, JsCast};
+use yew::Callback;
+
+/// Whether the browser thinks that it's online right now. This is only a hint, since being
+/// connected to a network doesn't mean that the server can be reached, but it's never wrong when
+/// it says that the browser is offline.
+pub fn is_online() -> bool {
+    window().navigator().on_line()
+}
+
+/// A change to the user's tests that was made while offline, and hasn't been sent to the server
+/// yet. These are kept for the tab in `sessionStorage`, so that reloading the page doesn't lose
+/// them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueuedChange {
+    /// What the change is, like `the new test "Maths Mock Set 1"`, to tell the user how
+    /// sending it went.
+    pub description: String,
+
+    /// The message to send to the server at [`paths::TESTS`](test_tracker_shared::paths::TESTS).
+    pub msg: ClientToServerMsg,
+}
+
+/// Listens for the browser going online or offline, and calls a callback with whether it's online
+/// whenever that changes. The listeners are removed when this is dropped.
+pub struct ConnectivityListener {
+    /// The handler for the `online` event. This must be kept alive for as long as it's listening.
+    ononline: Closure<dyn FnMut()>,
+
+    /// The handler for the `offline` event. This must be kept alive for as long as it's
+    /// listening.
+    onoffline: Closure<dyn FnMut()>,
+}
+
+impl fmt::Debug for ConnectivityListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectivityListener")
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for ConnectivityListener {
+    fn drop(&mut self) {
+        let window = window();
+        let _ = window
+            .remove_event_listener_with_callback("online", self.ononline.as_ref().unchecked_ref());
+        let _ = window.remove_event_listener_with_callback(
+            "offline",
+            self.onoffline.as_ref().unchecked_ref(),
+        );
+    }
+}
+
+impl ConnectivityListener {
+    /// Start listening, and call `on_change` with `true` when the browser goes online and `false`
+    /// when it goes offline.
+    pub fn listen(on_change: Callback<bool>) -> Self {
+        let ononline = Closure::<dyn FnMut()>::new({
+            let on_change = on_change.clone();
+            move || {
+                debug!("Browser is online");
+                on_change.emit(true);
+            }
+        });
+        let onoffline = Closure::<dyn FnMut()>::new(move || {
+            debug!("Browser is offline");
+            on_change.emit(false);
+        });
+
+        let window = window();
+        for (event, handler) in [("online", &ononline), ("offline", &onoffline)] {
+            if let Err(e) =
+                window.add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())
+            {
+                warn!(?e, event, "Unable to listen for connectivity changes");
+            }
+        }
+
+        Self {
+            ononline,
+            onoffline,
+        }
+    }
+}
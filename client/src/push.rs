@@ -0,0 +1,89 @@
+//! This module listens for push notifications from the server over a WebSocket, so that data
+//! changed in another tab or on another device shows up here without refreshing.
+
+use crate::server_url;
+use std::fmt;
+use test_tracker_shared::{ServerToClientMsg, Session};
+use tracing::{debug, warn};
+use tracing_unwrap::ResultExt;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{MessageEvent, WebSocket};
+use yew::Callback;
+
+/// An open WebSocket to the server, which calls a callback whenever the user's data changes. The
+/// socket is closed when this is dropped.
+pub struct PushSocket {
+    /// The socket itself.
+    socket: WebSocket,
+
+    /// The handler that sends the session token once the socket opens. This must be kept alive
+    /// for as long as the socket.
+    _onopen: Closure<dyn FnMut()>,
+
+    /// The handler for messages from the server. This must be kept alive for as long as the
+    /// socket.
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl fmt::Debug for PushSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PushSocket")
+            .field("url", &self.socket.url())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PushSocket {
+    fn drop(&mut self) {
+        let _ = self.socket.close();
+    }
+}
+
+impl PushSocket {
+    /// Open a socket for the given session, which calls `on_change` whenever the user's data
+    /// changes on the server. Returns `None` if the socket couldn't be opened, in which case the
+    /// user just has to refresh manually.
+    pub fn open(session: &Session, on_change: Callback<()>) -> Option<Self> {
+        let socket = match WebSocket::new(server_url::websocket()) {
+            Ok(socket) => socket,
+            Err(e) => {
+                warn!(?e, "Unable to open push socket");
+                return None;
+            }
+        };
+
+        let onopen = Closure::<dyn FnMut()>::new({
+            let socket = socket.clone();
+            let token = session.token.clone();
+            move || {
+                socket
+                    .send_with_str(&token)
+                    .expect_or_log("We should be able to send on a socket that just opened");
+            }
+        });
+
+        let user_id = session.user.id.clone();
+        let onmessage = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+
+            match ron::from_str(&text) {
+                Ok(ServerToClientMsg::DataChanged { user_id: changed }) if changed == user_id => {
+                    debug!("Data changed on the server");
+                    on_change.emit(());
+                }
+                msg => warn!(?msg, "Unexpected message on push socket"),
+            }
+        });
+
+        socket.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+
+        Some(Self {
+            socket,
+            _onopen: onopen,
+            _onmessage: onmessage,
+        })
+    }
+}
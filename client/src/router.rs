@@ -0,0 +1,67 @@
+//! This module keeps the page that the app shows in step with the fragment of its URL. See
+//! [`Route`].
+
+use gloo_utils::window;
+use std::fmt;
+use test_tracker_shared::route::Route;
+use tracing::{debug, warn};
+use wasm_bindgen::{closure::Closure, JsCast};
+use yew::Callback;
+
+/// The page for the current URL, or `None` if it doesn't match any page.
+pub fn current_route() -> Option<Route> {
+    let hash = window().location().hash().unwrap_or_default();
+    Route::from_hash(&hash)
+}
+
+/// Go to the given page, by changing the fragment of the URL. This adds an entry to the browser's
+/// history, so the back button comes back here, and it fires a `hashchange` event, which is how
+/// the app finds out.
+pub fn navigate(route: Route) {
+    if let Err(e) = window().location().set_hash(&route.href()) {
+        warn!(?e, ?route, "Unable to change the URL");
+    }
+}
+
+/// Listens for the fragment of the URL changing, whether from a link, the back button, or
+/// [`navigate`], and calls a callback with the new page. The listener is removed when this is
+/// dropped.
+pub struct RouteListener {
+    /// The handler for the `hashchange` event. This must be kept alive for as long as it's
+    /// listening.
+    onhashchange: Closure<dyn FnMut()>,
+}
+
+impl fmt::Debug for RouteListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteListener").finish_non_exhaustive()
+    }
+}
+
+impl Drop for RouteListener {
+    fn drop(&mut self) {
+        let _ = window().remove_event_listener_with_callback(
+            "hashchange",
+            self.onhashchange.as_ref().unchecked_ref(),
+        );
+    }
+}
+
+impl RouteListener {
+    /// Start listening, and call `on_change` with the page for the new URL whenever it changes.
+    pub fn listen(on_change: Callback<Option<Route>>) -> Self {
+        let onhashchange = Closure::<dyn FnMut()>::new(move || {
+            let route = current_route();
+            debug!(?route, "URL changed");
+            on_change.emit(route);
+        });
+
+        if let Err(e) = window()
+            .add_event_listener_with_callback("hashchange", onhashchange.as_ref().unchecked_ref())
+        {
+            warn!(?e, "Unable to listen for changes to the URL");
+        }
+
+        Self { onhashchange }
+    }
+}
@@ -0,0 +1,186 @@
+//! This module works out where the server is when the app starts, so that the same build can be
+//! pointed at any server without rebuilding it.
+//!
+//! The URL comes from the first of these that's set:
+//! 1. A `<meta name="test-tracker-server" content="...">` tag in `index.html`.
+//! 2. A `?server=...` query parameter, but only in debug builds.
+//! 3. The origin that the client itself was loaded from.
+
+use gloo_utils::{document, window};
+use lazy_static::lazy_static;
+use std::fmt;
+use test_tracker_shared::paths;
+use tracing::{info, warn};
+use tracing_unwrap::ResultExt;
+use url::Url;
+
+/// The name of the `<meta>` tag which holds the server URL.
+const META_TAG_NAME: &str = "test-tracker-server";
+
+/// The name of the query parameter which holds the server URL in debug builds.
+const QUERY_PARAMETER: &str = "server";
+
+/// Where the server URL came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The `<meta>` tag in `index.html`.
+    MetaTag,
+
+    /// The query parameter, in a debug build.
+    QueryParameter,
+
+    /// The origin that the client was loaded from.
+    SameOrigin,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MetaTag => write!(f, "the <meta name=\"{META_TAG_NAME}\"> tag"),
+            Self::QueryParameter => write!(f, "the ?{QUERY_PARAMETER}= query parameter"),
+            Self::SameOrigin => write!(f, "the page's own address"),
+        }
+    }
+}
+
+/// Why the server URL couldn't be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerUrlError {
+    /// The URL that was found.
+    pub url: String,
+
+    /// Where it was found.
+    pub source: Source,
+
+    /// What's wrong with it.
+    pub reason: String,
+}
+
+impl fmt::Display for ServerUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the server URL {:?} from {} isn't valid: {}",
+            self.url, self.source, self.reason
+        )
+    }
+}
+
+/// The addresses of the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerUrl {
+    /// The base URL of the server, like `https://example.com:20519`, without a trailing slash, so
+    /// that any of the [`paths`] can be added straight onto the end.
+    pub http: String,
+
+    /// The URL of the push notification WebSocket, at [`paths::WEBSOCKET`].
+    pub websocket: String,
+}
+
+impl ServerUrl {
+    /// Check a server URL, which must be an absolute `http` or `https` URL. It may have a path, for
+    /// servers behind a reverse proxy, but no query string or fragment.
+    pub fn parse(url: &str, source: Source) -> Result<Self, ServerUrlError> {
+        let error = |reason: String| ServerUrlError {
+            url: url.to_string(),
+            source,
+            reason,
+        };
+
+        let parsed = Url::parse(url.trim()).map_err(|e| error(e.to_string()))?;
+        let websocket_scheme = match parsed.scheme() {
+            "http" => "ws",
+            "https" => "wss",
+            scheme => return Err(error(format!("it must be http or https, not {scheme}"))),
+        };
+        if parsed.query().is_some() || parsed.fragment().is_some() {
+            return Err(error(
+                "it can't have a query string or a fragment".to_string(),
+            ));
+        }
+
+        let http = parsed.as_str().trim_end_matches('/').to_string();
+        let websocket = format!(
+            "{websocket_scheme}{}{}",
+            &http[parsed.scheme().len()..],
+            paths::WEBSOCKET
+        );
+        Ok(Self { http, websocket })
+    }
+}
+
+/// Get the server URL from the `<meta>` tag, if there is one and it isn't empty.
+fn from_meta_tag() -> Option<String> {
+    document()
+        .query_selector(&format!("meta[name=\"{META_TAG_NAME}\"]"))
+        .ok()
+        .flatten()
+        .and_then(|meta| meta.get_attribute("content"))
+        .filter(|content| !content.trim().is_empty())
+}
+
+/// Get the server URL from the query parameter, if there is one. This is only for development, so
+/// release builds always return `None`, so that a link can't point someone's client at another
+/// server.
+fn from_query_parameter() -> Option<String> {
+    #[cfg(debug_assertions)]
+    {
+        let href = window().location().href().ok()?;
+        Url::parse(&href)
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == QUERY_PARAMETER)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    #[cfg(not(debug_assertions))]
+    None
+}
+
+/// Work out the server URL from wherever it's set first.
+fn find_server_url() -> Result<ServerUrl, ServerUrlError> {
+    let (url, source) = if let Some(url) = from_meta_tag() {
+        (url, Source::MetaTag)
+    } else if let Some(url) = from_query_parameter() {
+        (url, Source::QueryParameter)
+    } else {
+        let origin = window()
+            .location()
+            .origin()
+            .expect_or_log("We should be able to get the page's origin");
+        (origin, Source::SameOrigin)
+    };
+
+    let result = ServerUrl::parse(&url, source);
+    match &result {
+        Ok(server_url) => info!(?server_url, %source, "Found server URL"),
+        Err(error) => warn!(%error, "Server URL is invalid"),
+    }
+    result
+}
+
+lazy_static! {
+    /// The server URL, which is worked out the first time that it's needed.
+    static ref SERVER_URL: Result<ServerUrl, ServerUrlError> = find_server_url();
+}
+
+/// Get the server URL, or why it couldn't be worked out. The app only starts if this is `Ok`.
+pub fn server_url() -> Result<&'static ServerUrl, &'static ServerUrlError> {
+    SERVER_URL.as_ref()
+}
+
+/// Get the server URL, once the app has started.
+fn valid_server_url() -> &'static ServerUrl {
+    server_url()
+        .expect_or_log("The app only starts if the server URL is valid, so it must be by now")
+}
+
+/// Get the full URL of the given path on the server, like [`paths::TESTS`].
+pub fn endpoint(path: &str) -> String {
+    format!("{}{path}", valid_server_url().http)
+}
+
+/// Get the URL of the push notification WebSocket.
+pub fn websocket() -> &'static str {
+    &valid_server_url().websocket
+}
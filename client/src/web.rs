@@ -1,10 +1,24 @@
 //! This module handles various interfaces to web APIs.
 
-use crate::STORAGE_KEY_USER;
-use gloo_utils::window;
-use serde::Deserialize;
-use test_tracker_shared::User;
-use web_sys::Storage;
+use crate::{
+    offline::QueuedChange, STORAGE_KEY_DARK_MODE, STORAGE_KEY_EXAM_DATES,
+    STORAGE_KEY_EXPANDED_TESTS, STORAGE_KEY_GRADE_BOUNDARIES, STORAGE_KEY_GROUPED,
+    STORAGE_KEY_OFFLINE_QUEUE, STORAGE_KEY_PAGE_SIZE, STORAGE_KEY_SETTINGS, STORAGE_KEY_SORT,
+    STORAGE_KEY_SUBJECT_FILTER, STORAGE_KEY_TESTS_CACHE, STORAGE_KEY_USER,
+};
+use chrono::NaiveDate;
+use gloo_timers::callback::Timeout;
+use gloo_utils::{body, document, window};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use test_tracker_shared::{
+    countdown::ExamDate, page::PageSize, settings::Settings, view::ViewPreferences, Session,
+    SyncedTest,
+};
+use tracing::{debug, warn};
+use tracing_unwrap::{OptionExt, ResultExt};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Storage, Url};
 
 /// Return the `localStorage`.
 pub fn local_storage() -> Storage {
@@ -38,11 +52,308 @@ fn get_item_from_storage<T: for<'a> Deserialize<'a>>(storage: Storage, key: &str
         .flatten()
 }
 
-/// Try `localStorage`, then `sessionStorage` for the user.
-pub fn get_user() -> Option<User> {
-    if let Some(user) = get_item_from_storage(local_storage(), STORAGE_KEY_USER) {
-        Some(user)
+/// Try `localStorage`, then `sessionStorage` for the user's session.
+pub fn get_session() -> Option<Session> {
+    if let Some(session) = get_item_from_storage(local_storage(), STORAGE_KEY_USER) {
+        Some(session)
     } else {
         get_item_from_storage(session_storage(), STORAGE_KEY_USER)
     }
 }
+
+/// Get how the given user last left the list of tests from `localStorage`. If they can't be read,
+/// like if they're corrupt, then the defaults are used instead.
+///
+/// If there aren't any, then the preferences that older clients stored under their own keys, for
+/// everyone using the browser, are gathered up instead, saved for this user, and removed, so this
+/// only happens once.
+pub fn get_view_preferences(user_id: &str) -> ViewPreferences {
+    let storage = local_storage();
+    let key = ViewPreferences::storage_key(user_id);
+    if storage.get_item(&key).ok().flatten().is_some() {
+        return get_item_from_storage(storage, &key).unwrap_or_default();
+    }
+
+    let preferences = ViewPreferences {
+        sort: get_item_from_storage(storage.clone(), STORAGE_KEY_SORT),
+        subject_filter: get_item_from_storage(storage.clone(), STORAGE_KEY_SUBJECT_FILTER),
+        grouped: get_item_from_storage(storage.clone(), STORAGE_KEY_GROUPED).unwrap_or(false),
+        expanded_tests: get_item_from_storage(session_storage(), STORAGE_KEY_EXPANDED_TESTS)
+            .unwrap_or_default(),
+    };
+    if preferences == ViewPreferences::default() {
+        return preferences;
+    }
+    debug!(
+        ?preferences,
+        "Migrating view preferences from their old keys"
+    );
+
+    set_view_preferences(user_id, &preferences);
+    for key in [
+        STORAGE_KEY_SORT,
+        STORAGE_KEY_SUBJECT_FILTER,
+        STORAGE_KEY_GROUPED,
+    ] {
+        if let Err(e) = storage.remove_item(key) {
+            warn!(?e, ?key, "Couldn't remove an old view preferences key");
+        }
+    }
+    if let Err(e) = session_storage().remove_item(STORAGE_KEY_EXPANDED_TESTS) {
+        warn!(?e, "Couldn't remove the old expanded tests key");
+    }
+    preferences
+}
+
+/// Save how the given user has left the list of tests in `localStorage`, so that it's the same
+/// next time.
+pub fn set_view_preferences(user_id: &str, preferences: &ViewPreferences) {
+    set_preference(&ViewPreferences::storage_key(user_id), Some(preferences));
+}
+
+/// Get the user's settings from `localStorage`.
+///
+/// If there aren't any, then the settings that older clients stored under their own keys are
+/// gathered up instead, saved as [`Settings`], and removed, so this only happens once.
+pub fn get_settings() -> Settings {
+    if let Some(settings) = get_item_from_storage(local_storage(), STORAGE_KEY_SETTINGS) {
+        return settings;
+    }
+
+    let storage = local_storage();
+    let theme = storage.get_item(STORAGE_KEY_DARK_MODE).ok().flatten();
+    let grade_boundaries = get_item_from_storage(storage.clone(), STORAGE_KEY_GRADE_BOUNDARIES);
+    let settings = Settings::from_legacy(theme, grade_boundaries);
+    debug!(?settings, "Migrating settings from their old keys");
+
+    set_settings(&settings);
+    for key in [STORAGE_KEY_DARK_MODE, STORAGE_KEY_GRADE_BOUNDARIES] {
+        if let Err(e) = storage.remove_item(key) {
+            warn!(?e, ?key, "Couldn't remove an old settings key");
+        }
+    }
+    settings
+}
+
+/// Save the user's settings in `localStorage`.
+pub fn set_settings(settings: &Settings) {
+    set_preference(STORAGE_KEY_SETTINGS, Some(settings));
+}
+
+/// Get how many tests the user last chose to show on each page from `localStorage`.
+pub fn get_page_size() -> PageSize {
+    get_item_from_storage(local_storage(), STORAGE_KEY_PAGE_SIZE).unwrap_or_default()
+}
+
+/// Get the exams that the user has entered from `localStorage`.
+pub fn get_exam_dates() -> Vec<ExamDate> {
+    get_item_from_storage(local_storage(), STORAGE_KEY_EXAM_DATES).unwrap_or_default()
+}
+
+/// Set an item in the given storage, or remove it if it's `None`.
+fn set_item_in_storage<T: Serialize>(storage: Storage, key: &str, value: Option<&T>) {
+    match value {
+        Some(value) => storage.set_item(
+            key,
+            &ron::to_string(value)
+                .expect_or_log("We should be able to serialize a preference to a String"),
+        ),
+        None => storage.remove_item(key),
+    }
+    .expect_or_log("We should be able to set a browser storage value without a problem");
+}
+
+/// Save a preference in `localStorage`, or remove it if it's `None`, so that it's still there next
+/// time.
+pub fn set_preference<T: Serialize>(key: &str, value: Option<&T>) {
+    set_item_in_storage(local_storage(), key, value);
+}
+
+/// Get the changes that were made offline in this tab and haven't been sent yet from
+/// `sessionStorage`, oldest first.
+pub fn get_offline_queue() -> VecDeque<QueuedChange> {
+    get_item_from_storage(session_storage(), STORAGE_KEY_OFFLINE_QUEUE).unwrap_or_default()
+}
+
+/// Save the changes that haven't been sent yet in `sessionStorage`, or remove them if there
+/// aren't any, so that reloading the page while offline doesn't lose them.
+pub fn set_offline_queue(queue: &VecDeque<QueuedChange>) {
+    set_item_in_storage(
+        session_storage(),
+        STORAGE_KEY_OFFLINE_QUEUE,
+        (!queue.is_empty()).then_some(queue),
+    );
+}
+
+/// The key for the given user's cached tests in `localStorage`. Each user gets their own key, so
+/// that nobody sees someone else's tests on a shared computer.
+fn tests_cache_key(user_id: &str) -> String {
+    format!("{STORAGE_KEY_TESTS_CACHE}:{user_id}")
+}
+
+/// Get the given user's tests from the last time that they were fetched, if they're cached in
+/// `localStorage`.
+///
+/// If the cache can't be read, like if it's from an older version of the client, then it's
+/// discarded.
+pub fn get_cached_tests(user_id: &str) -> Option<BTreeMap<i32, SyncedTest>> {
+    let key = tests_cache_key(user_id);
+    let cached = local_storage().get_item(&key).ok().flatten()?;
+
+    match ron::from_str::<Vec<SyncedTest>>(&cached) {
+        Ok(tests) => Some(tests.into_iter().map(|test| (test.id, test)).collect()),
+        Err(e) => {
+            warn!(
+                ?e,
+                "Discarding the cached tests, since they couldn't be read"
+            );
+            clear_cached_tests(user_id);
+            None
+        }
+    }
+}
+
+/// Cache the given user's tests in `localStorage`, so that they can be shown straight away next
+/// time, even without a connection.
+///
+/// The cache is just a copy, so if it can't be saved, like if `localStorage` is full, then that's
+/// only logged. Tests and completions with negative placeholder IDs haven't been confirmed by the
+/// server yet, so they're left out.
+pub fn set_cached_tests(user_id: &str, tests: &BTreeMap<i32, SyncedTest>) {
+    let tests: Vec<SyncedTest> = tests
+        .values()
+        .filter(|test| test.id >= 0)
+        .map(|test| SyncedTest {
+            completions: test
+                .completions
+                .iter()
+                .filter(|(id, _)| *id >= 0)
+                .cloned()
+                .collect(),
+            ..test.clone()
+        })
+        .collect();
+    let cached = ron::to_string(&tests)
+        .expect_or_log("We should be able to serialize the tests to a String");
+    if let Err(e) = local_storage().set_item(&tests_cache_key(user_id), &cached) {
+        warn!(?e, "Couldn't cache the tests");
+    }
+}
+
+/// Remove the given user's cached tests from `localStorage`.
+pub fn clear_cached_tests(user_id: &str) {
+    local_storage()
+        .remove_item(&tests_cache_key(user_id))
+        .expect_or_log("We should be able to remove a localStorage value without a problem");
+}
+
+/// Make the browser download a file with the given name and contents, by linking to a temporary
+/// object URL and clicking the link.
+pub fn download_file(filename: &str, mime_type: &str, contents: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor: HtmlAnchorElement = document().create_element("a")?.unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    // Some browsers only start the download after the click handler, so the URL has to last a bit
+    Timeout::new(1_000, move || {
+        if let Err(e) = Url::revoke_object_url(&url) {
+            warn!(?e, "Couldn't revoke the object URL of a download");
+        }
+    })
+    .forget();
+    Ok(())
+}
+
+/// Add or remove a class on the body of the HTML, so that CSS can change the whole page.
+pub fn set_body_class(class: &str, on: bool) {
+    let class_list = body().class_list();
+    class_list
+        .toggle_with_force(class, on)
+        .expect_or_log("We should be able to change the classes on the body");
+}
+
+/// Open the browser's print dialog.
+pub fn print_page() {
+    if let Err(e) = window().print() {
+        warn!(?e, "Couldn't open the print dialog");
+    }
+}
+
+/// Today's date in the user's timezone, according to the browser.
+pub fn today() -> NaiveDate {
+    let now = js_sys::Date::new_0();
+    // JavaScript counts months from 0
+    NaiveDate::from_ymd_opt(
+        now.get_full_year() as i32,
+        now.get_month() + 1,
+        now.get_date(),
+    )
+    .expect_or_log("The browser should always give a valid date")
+}
+
+/// Parse the value of an `<input type="date">`, which is like `YYYY-MM-DD`, or `None` if it's
+/// empty or invalid.
+pub fn parse_date_input(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+/// Format a date as the value of an `<input type="date">`, which is empty for no date.
+pub fn format_date_input(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Tests for reading and writing the values of date inputs, which don't need a browser.
+#[cfg(test)]
+mod tests {
+    use super::{format_date_input, parse_date_input};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn dates_round_trip() {
+        for (year, month, day) in [(2024, 3, 1), (2024, 2, 29), (1999, 12, 31)] {
+            let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            let value = format_date_input(Some(date));
+            assert_eq!(value, format!("{year:04}-{month:02}-{day:02}"));
+            assert_eq!(parse_date_input(&value), Some(date));
+        }
+    }
+
+    #[test]
+    fn no_date_round_trips_through_an_empty_input() {
+        assert_eq!(format_date_input(None), "");
+        assert_eq!(parse_date_input(""), None);
+        assert_eq!(parse_date_input("   "), None);
+        assert_eq!(parse_date_input(&format_date_input(None)), None);
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        assert_eq!(
+            parse_date_input(" 2024-03-01\n"),
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+        );
+    }
+
+    #[test]
+    fn invalid_dates_are_none() {
+        for value in [
+            "not a date",
+            "01/03/2024",
+            "2024-13-01",
+            "2024-02-30",
+            "2023-02-29",
+            "2024-03",
+            "2024-03-01T12:00",
+        ] {
+            assert_eq!(parse_date_input(value), None, "{value:?}");
+        }
+    }
+}
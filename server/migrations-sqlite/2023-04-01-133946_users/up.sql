@@ -0,0 +1,6 @@
+-- User IDs are generated by the server as UUIDv4s
+CREATE TABLE users (
+	id TEXT PRIMARY KEY NOT NULL,
+	username TEXT NOT NULL UNIQUE,
+	hashed_password TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE audit_log (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, -- Simple ID
+	user_id TEXT REFERENCES users(id) ON DELETE SET NULL, -- The user that this happened to, if known
+	event TEXT NOT NULL, -- What happened: login, failed_login, account_created, etc.
+	remote_addr TEXT, -- The address that the request came from, if known
+	created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP, -- When it happened
+	details JSON NOT NULL DEFAULT '{}' -- Any extra details about the event, as JSON text
+);
+
+CREATE INDEX audit_log_user_id_created_at ON audit_log (user_id, created_at DESC);
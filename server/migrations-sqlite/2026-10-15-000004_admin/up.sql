@@ -0,0 +1,7 @@
+-- Admins can list every account and reset passwords. Promote a user with
+-- `test-tracker-server promote-admin <username>`
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- SQLite can't add a column with a default of CURRENT_TIMESTAMP, so existing
+-- users get the epoch instead
+ALTER TABLE users ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT '1970-01-01 00:00:00+00:00';
@@ -0,0 +1,3 @@
+DROP TABLE tombstones;
+DROP INDEX tests_user_id_updated_at;
+ALTER TABLE tests DROP COLUMN updated_at;
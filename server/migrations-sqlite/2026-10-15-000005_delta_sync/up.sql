@@ -0,0 +1,19 @@
+-- Any change to a test or its completions bumps the test's updated_at, so that
+-- clients can fetch only the tests that have changed since they last synced.
+-- SQLite can't add a column with a default of CURRENT_TIMESTAMP, so existing
+-- tests get the epoch instead, which just means they're in every sync until
+-- they next change
+ALTER TABLE tests ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT '1970-01-01 00:00:00+00:00';
+
+CREATE INDEX tests_user_id_updated_at ON tests (user_id, updated_at);
+
+-- Deleting a test leaves a tombstone behind, so that clients syncing later know
+-- to remove it
+CREATE TABLE tombstones (
+	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, -- Simple ID
+	user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- The user that owned the test
+	test_id INTEGER NOT NULL, -- The ID of the deleted test
+	deleted_at TIMESTAMPTZ NOT NULL -- When it was deleted
+);
+
+CREATE INDEX tombstones_user_id_deleted_at ON tombstones (user_id, deleted_at);
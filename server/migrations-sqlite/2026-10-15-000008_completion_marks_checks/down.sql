@@ -0,0 +1,2 @@
+DROP TRIGGER completions_marks_update;
+DROP TRIGGER completions_marks_insert;
@@ -0,0 +1,16 @@
+-- The server checks marks before saving them, but these triggers make sure
+-- that nothing else can save a completion that would break percentages.
+-- SQLite can't add a CHECK constraint to an existing table, and triggers also
+-- leave alone any existing completions that were saved out of 0 marks before
+-- this was checked, like the PostgreSQL constraints do
+CREATE TRIGGER completions_marks_insert BEFORE INSERT ON completions
+WHEN NEW.total_marks <= 0 OR NEW.achieved_mark < 0 OR NEW.achieved_mark > NEW.total_marks
+BEGIN
+	SELECT RAISE(ABORT, 'CHECK constraint failed: completions_marks');
+END;
+
+CREATE TRIGGER completions_marks_update BEFORE UPDATE OF achieved_mark, total_marks ON completions
+WHEN NEW.total_marks <= 0 OR NEW.achieved_mark < 0 OR NEW.achieved_mark > NEW.total_marks
+BEGIN
+	SELECT RAISE(ABORT, 'CHECK constraint failed: completions_marks');
+END;
@@ -0,0 +1,6 @@
+CREATE TABLE sessions (
+	token TEXT PRIMARY KEY, -- The random token that the client sends to prove who it is
+	user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- The user that this session authenticates
+	remember_me BOOLEAN NOT NULL, -- Whether this session uses the longer "remember me" length
+	expires_at TIMESTAMPTZ NOT NULL -- When this session lapses, unless activity extends it first
+);
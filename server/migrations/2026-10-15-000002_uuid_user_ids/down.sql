@@ -0,0 +1 @@
+ALTER TABLE users ALTER COLUMN id SET DEFAULT ('user_' || generate_uid(50));
@@ -0,0 +1,3 @@
+-- User IDs are now generated by the server as UUIDv4s, so the database
+-- shouldn't silently make one up if the server forgets
+ALTER TABLE users ALTER COLUMN id DROP DEFAULT;
@@ -0,0 +1,6 @@
+-- Admins can list every account and reset passwords. Promote a user with
+-- `test-tracker-server promote-admin <username>`
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Existing users get the time of this migration
+ALTER TABLE users ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1 @@
+DROP INDEX tests_user_id_subject_date_or_id;
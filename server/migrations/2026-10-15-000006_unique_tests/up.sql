@@ -0,0 +1,43 @@
+-- A race between two tabs could create identical tests, so merge any duplicates
+-- before a unique index stops it happening again.
+--
+-- In each set of tests with the same user, subject, and date or ID, we keep the
+-- one with the most completions (or the oldest, if there's a tie), move every
+-- other duplicate's completions onto it, fill in any of its missing details
+-- from the duplicates, and then delete the duplicates, leaving tombstones so
+-- that clients drop them on their next sync
+CREATE TEMPORARY TABLE duplicate_tests AS
+SELECT id, keeper_id FROM (
+	SELECT
+		tests.id,
+		FIRST_VALUE(tests.id) OVER (
+			PARTITION BY tests.user_id, tests.subject, tests.date_or_id
+			ORDER BY (SELECT COUNT(*) FROM completions WHERE completions.test_id = tests.id) DESC, tests.id
+		) AS keeper_id
+	FROM tests
+) AS ranked
+WHERE id <> keeper_id;
+
+UPDATE tests SET
+	topic = COALESCE(topic, (SELECT MIN(duplicate.topic) FROM tests AS duplicate JOIN duplicate_tests ON duplicate.id = duplicate_tests.id WHERE duplicate_tests.keeper_id = tests.id)),
+	qualification_level = COALESCE(qualification_level, (SELECT MIN(duplicate.qualification_level) FROM tests AS duplicate JOIN duplicate_tests ON duplicate.id = duplicate_tests.id WHERE duplicate_tests.keeper_id = tests.id)),
+	exam_board = COALESCE(exam_board, (SELECT MIN(duplicate.exam_board) FROM tests AS duplicate JOIN duplicate_tests ON duplicate.id = duplicate_tests.id WHERE duplicate_tests.keeper_id = tests.id)),
+	paper_link = COALESCE(paper_link, (SELECT MIN(duplicate.paper_link) FROM tests AS duplicate JOIN duplicate_tests ON duplicate.id = duplicate_tests.id WHERE duplicate_tests.keeper_id = tests.id)),
+	mark_scheme_link = COALESCE(mark_scheme_link, (SELECT MIN(duplicate.mark_scheme_link) FROM tests AS duplicate JOIN duplicate_tests ON duplicate.id = duplicate_tests.id WHERE duplicate_tests.keeper_id = tests.id)),
+	comments = COALESCE(comments, (SELECT MIN(duplicate.comments) FROM tests AS duplicate JOIN duplicate_tests ON duplicate.id = duplicate_tests.id WHERE duplicate_tests.keeper_id = tests.id)),
+	updated_at = NOW()
+WHERE id IN (SELECT keeper_id FROM duplicate_tests);
+
+UPDATE completions
+SET test_id = (SELECT keeper_id FROM duplicate_tests WHERE duplicate_tests.id = completions.test_id)
+WHERE test_id IN (SELECT id FROM duplicate_tests);
+
+INSERT INTO tombstones (user_id, test_id, deleted_at)
+SELECT user_id, id, NOW() FROM tests WHERE id IN (SELECT id FROM duplicate_tests);
+
+DELETE FROM tests WHERE id IN (SELECT id FROM duplicate_tests);
+
+DROP TABLE duplicate_tests;
+
+-- There's no soft deletion of tests, so every row is covered by the index
+CREATE UNIQUE INDEX tests_user_id_subject_date_or_id ON tests (user_id, subject, date_or_id);
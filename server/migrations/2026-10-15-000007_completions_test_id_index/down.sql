@@ -0,0 +1 @@
+DROP INDEX completions_test_id;
@@ -0,0 +1,6 @@
+-- Listing and syncing tests loads the completions for a whole set of tests at
+-- once, which needs an index on completions.test_id, since foreign keys don't
+-- get one automatically. tests.user_id doesn't need its own index, since
+-- tests_user_id_updated_at and tests_user_id_subject_date_or_id both start
+-- with it
+CREATE INDEX completions_test_id ON completions (test_id);
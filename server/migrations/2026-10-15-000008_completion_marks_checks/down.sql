@@ -0,0 +1,2 @@
+ALTER TABLE completions DROP CONSTRAINT completions_achieved_mark_in_range;
+ALTER TABLE completions DROP CONSTRAINT completions_total_marks_positive;
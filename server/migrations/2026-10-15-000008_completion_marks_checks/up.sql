@@ -0,0 +1,9 @@
+-- The server checks marks before saving them, but these constraints make sure
+-- that nothing else can save a completion that would break percentages. They
+-- aren't validated against existing completions, which may have been saved
+-- out of 0 marks before this was checked, and are still handled everywhere
+ALTER TABLE completions
+	ADD CONSTRAINT completions_total_marks_positive CHECK (total_marks > 0) NOT VALID;
+
+ALTER TABLE completions
+	ADD CONSTRAINT completions_achieved_mark_in_range CHECK (achieved_mark >= 0 AND achieved_mark <= total_marks) NOT VALID;
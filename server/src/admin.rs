@@ -0,0 +1,227 @@
+//! This module handles the admin-only capabilities, like listing every user and resetting
+//! passwords.
+//!
+//! Whether the caller is an admin is always read from the database using their session, never
+//! from anything the client sends.
+
+use crate::{
+    audit,
+    db::{
+        models::Test,
+        schema::{tests, users},
+        DbConnection, UniqueConstraint,
+    },
+    passwords::set_password,
+    sessions::{end_all_sessions, resolve_token},
+};
+use chrono::{DateTime, Utc};
+use diesel::{dsl::count, prelude::*, result::Error as DbError};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use std::net::SocketAddr;
+use test_tracker_shared::{policy::normalize_username, AuditEvent, Error, TestData, UserSummary};
+use tracing::{info, instrument, warn};
+
+/// Resolve the caller from their session token and check that they're an admin. Returns the
+/// caller's user ID.
+#[instrument(skip_all)]
+pub async fn require_admin(conn: &mut DbConnection, token: &str) -> Result<String, Error> {
+    let caller_id = resolve_token(conn, token).await?;
+
+    let is_admin: bool = users::table
+        .find(&caller_id)
+        .select(users::is_admin)
+        .first(conn)
+        .await?;
+
+    if is_admin {
+        Ok(caller_id)
+    } else {
+        warn!(?caller_id, "Non-admin tried to use an admin capability");
+        Err(Error::Forbidden)
+    }
+}
+
+/// List every user, sorted by username, along with how many tests each has.
+#[instrument(skip(conn))]
+pub async fn list_users(conn: &mut DbConnection) -> Result<Vec<UserSummary>, Error> {
+    let rows: Vec<(String, String, DateTime<Utc>, i64)> = users::table
+        .left_join(tests::table)
+        .group_by(users::id)
+        .select((
+            users::id,
+            users::username,
+            users::created_at,
+            count(tests::id.nullable()),
+        ))
+        .order_by(users::username)
+        .load(conn)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, username, created_at, test_count)| UserSummary {
+            id,
+            username,
+            created_at,
+            test_count,
+        })
+        .collect())
+}
+
+/// Reset the target user's password and end all their sessions, on behalf of the given admin.
+#[instrument(skip(conn, new_password))]
+pub async fn reset_password(
+    conn: &mut DbConnection,
+    admin_id: &str,
+    target_user_id: &str,
+    new_password: &str,
+    remote_addr: Option<SocketAddr>,
+) -> Result<(), Error> {
+    set_password(conn, target_user_id, new_password).await?;
+    let ended = end_all_sessions(conn, target_user_id).await?;
+    info!(?ended, "Reset password");
+
+    audit::record(
+        conn,
+        Some(target_user_id),
+        AuditEvent::PasswordChanged,
+        remote_addr,
+        serde_json::json!({ "reset_by_admin": admin_id }),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Set a new password for the user with the given username, and end all their sessions. This is
+/// only available from the command line, for when nobody can log in to reset it.
+#[instrument(skip(conn, new_password))]
+pub async fn reset_password_by_username(
+    conn: &mut DbConnection,
+    username: &str,
+    new_password: &str,
+) -> Result<(), Error> {
+    let user_id: String = users::table
+        .filter(users::username.eq(normalize_username(username)))
+        .select(users::id)
+        .first(conn)
+        .await?;
+
+    set_password(conn, &user_id, new_password).await?;
+    let ended = end_all_sessions(conn, &user_id).await?;
+    info!(?user_id, ?ended, "Reset password from the command line");
+
+    audit::record(
+        conn,
+        Some(&user_id),
+        AuditEvent::PasswordChanged,
+        None,
+        serde_json::json!({ "reset_from_command_line": true }),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Make the user with the given username an admin. This is only available from the command line.
+#[instrument(skip(conn))]
+pub async fn promote_to_admin(conn: &mut DbConnection, username: &str) -> Result<(), DbError> {
+    let updated =
+        diesel::update(users::table.filter(users::username.eq(normalize_username(username))))
+            .set(users::is_admin.eq(true))
+            .execute(conn)
+            .await?;
+
+    if updated == 0 {
+        Err(DbError::NotFound)
+    } else {
+        Ok(())
+    }
+}
+
+/// What normalizing the existing tests did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizeSummary {
+    /// How many tests were changed.
+    pub updated: usize,
+
+    /// The IDs of the tests that were left alone, because normalizing them would make them
+    /// duplicates of other tests that their users already have.
+    pub conflicts: Vec<i32>,
+}
+
+/// Normalize the whitespace in every existing test with [`TestData::normalized`], for tests that
+/// were inserted before the server started doing it. This is only available from the command
+/// line.
+///
+/// A test which would clash with another one after normalizing is skipped. Changed tests are
+/// touched, so that clients pick them up on their next sync.
+#[instrument(skip(conn))]
+pub async fn normalize_existing_tests(
+    conn: &mut DbConnection,
+) -> Result<NormalizeSummary, DbError> {
+    let existing: Vec<Test> = tests::table
+        .select(Test::as_select())
+        .order_by(tests::id)
+        .load(conn)
+        .await?;
+
+    let mut summary = NormalizeSummary::default();
+    for test in existing {
+        let id = test.id;
+        let data = TestData::from(test);
+        let normalized = data.normalized();
+        if normalized == data {
+            continue;
+        }
+
+        let TestData {
+            subject,
+            topic,
+            date_or_id,
+            qualification_level,
+            exam_board,
+            paper_link,
+            mark_scheme_link,
+            comments,
+        } = normalized;
+        // Each update gets its own transaction, so that a clash doesn't abort the rest
+        let result = conn
+            .transaction::<_, DbError, _>(|conn| {
+                diesel::update(tests::table.find(id))
+                    .set((
+                        tests::subject.eq(subject),
+                        tests::topic.eq(topic),
+                        tests::date_or_id.eq(date_or_id),
+                        tests::qualification_level.eq(qualification_level),
+                        tests::exam_board.eq(exam_board),
+                        tests::paper_link.eq(paper_link),
+                        tests::mark_scheme_link.eq(mark_scheme_link),
+                        tests::comments.eq(comments),
+                        tests::updated_at.eq(Utc::now()),
+                    ))
+                    .execute(conn)
+                    .scope_boxed()
+            })
+            .await;
+
+        match result {
+            Ok(_) => summary.updated += 1,
+            Err(e) if UniqueConstraint::violated_by(&e) == Some(UniqueConstraint::Test) => {
+                warn!(
+                    ?id,
+                    "Normalizing test would make it a duplicate; skipping it"
+                );
+                summary.conflicts.push(id);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    info!(
+        updated = summary.updated,
+        conflicts = summary.conflicts.len(),
+        "Normalized existing tests"
+    );
+    Ok(summary)
+}
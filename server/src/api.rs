@@ -0,0 +1,625 @@
+//! This module handles the HTTP requests that the [`router`](crate::routes::router) sends to it,
+//! including every [`ClientToServerMsg`] and the monitoring routes.
+
+use crate::{
+    admin::{list_users, require_admin, reset_password},
+    audit::{self, get_audit_log_for_user},
+    config,
+    db::{self, DbConnection},
+    etag, export,
+    format::Format,
+    import::import_csv,
+    limits, logging,
+    messages::{parse_message, Route},
+    passwords::{add_new_user, change_password, delete_account, validate_user},
+    push,
+    sessions::{authorize, end_other_sessions, resolve_token, start_session},
+    statistics::get_statistics_for_user,
+    tests_and_completions::{
+        add_completion, delete_completion, delete_test, get_test, get_tests_and_completions_since,
+        insert_test_and_completions, update_completion, update_test,
+    },
+};
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, ETAG},
+        HeaderMap, StatusCode,
+    },
+    response::{Html, IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::{net::SocketAddr, pin::pin, time::Duration};
+use test_tracker_shared::{
+    error::DieselError as SharedDieselError, AuditEvent, ClientToServerMsg, Error as SharedError,
+    ServerToClientMsg, PROTOCOL_VERSION,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, instrument, warn, Instrument};
+
+/// How long the health check waits for the database before declaring it unreachable.
+const HEALTH_CHECK_DB_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many chunks of a CSV export can be waiting to be sent before the database stream has to
+/// wait for the client. Each chunk is about [`export::CHUNK_SIZE`] bytes.
+const EXPORT_CHANNEL_CAPACITY: usize = 4;
+
+/// Respond to `GET /health` with the server version and whether the database is reachable. This
+/// needs no authentication, so that a reverse proxy can use it.
+#[instrument]
+pub async fn respond_to_health_check() -> Response {
+    let database = matches!(
+        tokio::time::timeout(HEALTH_CHECK_DB_TIMEOUT, db::database_is_reachable()).await,
+        Ok(true)
+    );
+    debug!(?database, "Health check");
+
+    (
+        [(CONTENT_TYPE, "application/json")],
+        format!(
+            r#"{{"version":"{}","database":{database}}}"#,
+            env!("CARGO_PKG_VERSION")
+        ),
+    )
+        .into_response()
+}
+
+/// Escape text to go inside HTML, including inside quoted attributes.
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+/// Respond to `GET /` with a small HTML page about the server, for anyone who opens the API's URL
+/// in a browser. Axum responds to `HEAD /` with the same headers and no body.
+pub async fn respond_to_info_page() -> Response {
+    let client_link = match &config::config().client_url {
+        Some(url) => {
+            let url = escape_html(url);
+            format!(r#"<p>You probably want the app itself, at <a href="{url}">{url}</a>.</p>"#)
+        }
+        None => {
+            "<p>This is the API server. You probably want the app itself instead.</p>".to_string()
+        }
+    };
+
+    Html(format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head><meta charset=\"utf-8\"><title>TestTracker server</title></head>\n\
+         <body>\n\
+         <h1>TestTracker server</h1>\n\
+         {client_link}\n\
+         <ul>\n\
+         <li>Version: {}</li>\n\
+         <li>Protocol version: {PROTOCOL_VERSION}</li>\n\
+         </ul>\n\
+         </body>\n\
+         </html>\n",
+        env!("CARGO_PKG_VERSION")
+    ))
+    .into_response()
+}
+
+/// Respond to `GET /metrics` in the Prometheus text format.
+pub async fn respond_to_metrics() -> Response {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        format!(
+            "# HELP test_tracker_requests_in_flight The number of requests currently being handled.\n\
+             # TYPE test_tracker_requests_in_flight gauge\n\
+             test_tracker_requests_in_flight {}\n\
+             # HELP test_tracker_push_subscribers The number of open WebSockets for push notifications.\n\
+             # TYPE test_tracker_push_subscribers gauge\n\
+             test_tracker_push_subscribers {}\n",
+            limits::in_flight(),
+            push::subscriber_count()
+        ),
+    )
+        .into_response()
+}
+
+/// Handle a single message from the client and return the response to send back.
+///
+/// Every step of handling the message shares the given connection. The remote address is only used
+/// for the audit log.
+pub async fn handle_message(
+    conn: &mut DbConnection,
+    msg: ClientToServerMsg,
+    remote_addr: Option<SocketAddr>,
+) -> ServerToClientMsg {
+    match msg {
+        ClientToServerMsg::Authenticate {
+            username,
+            password,
+            remember_me,
+        } => {
+            info!(?username, ?remember_me, "Authenticating");
+            let validation_result = match validate_user(conn, &username, &password).await {
+                Ok(user) => {
+                    audit::record(
+                        conn,
+                        Some(&user.id),
+                        AuditEvent::Login,
+                        remote_addr,
+                        serde_json::json!({ "remember_me": remember_me }),
+                    )
+                    .await;
+                    start_session(conn, user, remember_me).await
+                }
+                Err(e) => {
+                    let e = SharedError::from(e);
+                    if matches!(
+                        e,
+                        SharedError::InvalidPassword
+                            | SharedError::DatabaseError(SharedDieselError::NotFound)
+                    ) {
+                        audit::record_failed_login(conn, &username, remote_addr).await;
+                    }
+                    Err(e)
+                }
+            };
+            debug!(?validation_result);
+
+            ServerToClientMsg::AuthenticationResponse(validation_result)
+        }
+        ClientToServerMsg::CreateUser {
+            username,
+            password,
+            remember_me,
+        } => {
+            info!(?username, ?remember_me, "Creating new user");
+            let add_new_user_result = match add_new_user(conn, &username, &password).await {
+                Ok(user) => {
+                    audit::record(
+                        conn,
+                        Some(&user.id),
+                        AuditEvent::AccountCreated,
+                        remote_addr,
+                        serde_json::json!({}),
+                    )
+                    .await;
+                    start_session(conn, user, remember_me).await
+                }
+                Err(e) => Err(e.into()),
+            };
+            debug!(?add_new_user_result);
+
+            ServerToClientMsg::AuthenticationResponse(add_new_user_result)
+        }
+        ClientToServerMsg::ChangePassword {
+            token,
+            user_id,
+            current_password,
+            new_password,
+        } => {
+            info!(?user_id, "Changing password");
+            let change_password_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => change_password(conn, &user_id, &current_password, &new_password)
+                    .await
+                    .map_err(SharedError::from),
+                Err(e) => Err(e),
+            };
+            debug!(?change_password_result);
+            if change_password_result.is_ok() {
+                audit::record(
+                    conn,
+                    Some(&user_id),
+                    AuditEvent::PasswordChanged,
+                    remote_addr,
+                    serde_json::json!({}),
+                )
+                .await;
+                // The password has already changed, so failing to end the other sessions
+                // shouldn't be reported as failing to change it
+                match end_other_sessions(conn, &user_id, &token).await {
+                    Ok(ended) => debug!(?ended, "Ended other sessions"),
+                    Err(e) => warn!(?e, "Unable to end other sessions"),
+                }
+            }
+
+            ServerToClientMsg::ChangePasswordResponse(change_password_result)
+        }
+        ClientToServerMsg::DeleteAccount {
+            token,
+            user_id,
+            password,
+        } => {
+            info!(?user_id, "Deleting account");
+            let delete_account_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => delete_account(conn, &user_id, &password)
+                    .await
+                    .map_err(SharedError::from),
+                Err(e) => Err(e),
+            };
+            debug!(?delete_account_result);
+            if delete_account_result.is_ok() {
+                audit::record(
+                    conn,
+                    None,
+                    AuditEvent::AccountDeleted,
+                    remote_addr,
+                    serde_json::json!({ "user_id": user_id }),
+                )
+                .await;
+                // Any other clients that are still open will find that they've been logged out
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::DeleteAccountResponse(delete_account_result)
+        }
+        ClientToServerMsg::GetTestsAndCompletions {
+            token,
+            user_id,
+            since,
+            sort,
+        } => {
+            info!(?user_id, ?since, ?sort, "Getting tests and completions");
+            let tests_and_completions_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => get_tests_and_completions_since(conn, &user_id, since, sort).await,
+                Err(e) => Err(e),
+            };
+            debug!(?tests_and_completions_result);
+
+            ServerToClientMsg::TestsAndCompletionsForUser(tests_and_completions_result)
+        }
+        ClientToServerMsg::GetTest {
+            token,
+            user_id,
+            test_id,
+        } => {
+            info!(?user_id, ?test_id, "Getting test");
+            let test_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => get_test(conn, &user_id, test_id).await,
+                Err(e) => Err(e),
+            };
+            debug!(?test_result);
+
+            ServerToClientMsg::TestResponse(test_result)
+        }
+        ClientToServerMsg::AddTest {
+            token,
+            user_id,
+            test,
+        } => {
+            info!(?user_id, subject = ?test.subject, "Adding test");
+            // There are no completions, so this is a single insert that doesn't need a transaction
+            let add_test_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => insert_test_and_completions(conn, &user_id, test, vec![]).await,
+                Err(e) => Err(e),
+            };
+            debug!(?add_test_result);
+            if add_test_result.is_ok() {
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::AddTestResponse(add_test_result)
+        }
+        ClientToServerMsg::UpdateTest {
+            token,
+            user_id,
+            test_id,
+            test,
+        } => {
+            info!(?user_id, ?test_id, "Updating test");
+            let update_test_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => update_test(conn, &user_id, test_id, test).await,
+                Err(e) => Err(e),
+            };
+            debug!(?update_test_result);
+            if update_test_result.is_ok() {
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::UpdateTestResponse(update_test_result)
+        }
+        ClientToServerMsg::DeleteTest {
+            token,
+            user_id,
+            test_id,
+        } => {
+            info!(?user_id, ?test_id, "Deleting test");
+            let delete_test_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => delete_test(conn, &user_id, test_id).await,
+                Err(e) => Err(e),
+            };
+            debug!(?delete_test_result);
+            if delete_test_result.is_ok() {
+                // Deleting a test can't be undone, so the owner should be able to see when it
+                // happened and from where
+                audit::record(
+                    conn,
+                    Some(&user_id),
+                    AuditEvent::TestDeleted,
+                    remote_addr,
+                    serde_json::json!({ "test_id": test_id }),
+                )
+                .await;
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::DeleteTestResponse(delete_test_result)
+        }
+        ClientToServerMsg::AddCompletion {
+            token,
+            user_id,
+            test_id,
+            completion,
+        } => {
+            info!(?user_id, ?test_id, "Adding completion");
+            let add_completion_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => add_completion(conn, &user_id, test_id, completion).await,
+                Err(e) => Err(e),
+            };
+            debug!(?add_completion_result);
+            if add_completion_result.is_ok() {
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::AddCompletionResponse(add_completion_result)
+        }
+        ClientToServerMsg::UpdateCompletion {
+            token,
+            user_id,
+            completion_id,
+            completion,
+        } => {
+            info!(?user_id, ?completion_id, "Updating completion");
+            let update_completion_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => update_completion(conn, &user_id, completion_id, completion).await,
+                Err(e) => Err(e),
+            };
+            debug!(?update_completion_result);
+            if update_completion_result.is_ok() {
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::UpdateCompletionResponse(update_completion_result)
+        }
+        ClientToServerMsg::DeleteCompletion {
+            token,
+            user_id,
+            completion_id,
+        } => {
+            info!(?user_id, ?completion_id, "Deleting completion");
+            let delete_completion_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => delete_completion(conn, &user_id, completion_id).await,
+                Err(e) => Err(e),
+            };
+            debug!(?delete_completion_result);
+            if delete_completion_result.is_ok() {
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::DeleteCompletionResponse(delete_completion_result)
+        }
+        ClientToServerMsg::GetStatistics { token, user_id } => {
+            info!(?user_id, "Getting statistics");
+            let statistics_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => get_statistics_for_user(conn, &user_id).await,
+                Err(e) => Err(e),
+            };
+            debug!(?statistics_result);
+
+            ServerToClientMsg::StatisticsForUser(statistics_result)
+        }
+        ClientToServerMsg::GetAuditLog { token, user_id } => {
+            info!(?user_id, "Getting audit log");
+            let audit_log_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => get_audit_log_for_user(conn, &user_id).await,
+                Err(e) => Err(e),
+            };
+            debug!(?audit_log_result);
+
+            ServerToClientMsg::AuditLog(audit_log_result)
+        }
+        ClientToServerMsg::ListUsers { token } => {
+            info!("Listing users");
+            let list_users_result = match require_admin(conn, &token).await {
+                Ok(_) => list_users(conn).await,
+                Err(e) => Err(e),
+            };
+            debug!(?list_users_result);
+
+            ServerToClientMsg::UserList(list_users_result)
+        }
+        ClientToServerMsg::AdminResetPassword {
+            token,
+            target_user_id,
+            new_password,
+        } => {
+            info!(?target_user_id, "Resetting password");
+            let reset_result = match require_admin(conn, &token).await {
+                Ok(admin_id) => {
+                    reset_password(conn, &admin_id, &target_user_id, &new_password, remote_addr)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            debug!(?reset_result);
+
+            ServerToClientMsg::PasswordResetResponse(reset_result)
+        }
+        ClientToServerMsg::SetLogFilter {
+            token,
+            stdout,
+            file,
+        } => {
+            info!(?stdout, ?file, "Setting log filters");
+            let set_filters_result = match require_admin(conn, &token).await {
+                Ok(_) => logging::set_filters(stdout.as_deref(), file.as_deref())
+                    .map_err(SharedError::from),
+                Err(e) => Err(e),
+            };
+            debug!(?set_filters_result);
+
+            ServerToClientMsg::LogFilterResponse(set_filters_result)
+        }
+        ClientToServerMsg::ImportCsv {
+            token,
+            user_id,
+            csv,
+            strict,
+        } => {
+            info!(?user_id, ?strict, bytes = csv.len(), "Importing CSV");
+            let import_result = match authorize(conn, &token, &user_id).await {
+                Ok(()) => import_csv(conn, &user_id, &csv, strict).await,
+                Err(e) => Err(e),
+            };
+            debug!(?import_result);
+            if matches!(&import_result, Ok(summary) if summary.tests_imported > 0) {
+                push::data_changed(&user_id);
+            }
+
+            ServerToClientMsg::ImportResult(import_result)
+        }
+    }
+}
+
+/// The HTTP status code for a response message. Most errors are still `200 OK`, since the client
+/// reads the error from the body, but errors that mean the server can't currently do its job give
+/// `503 Service Unavailable`.
+pub fn status_code(msg: &ServerToClientMsg) -> StatusCode {
+    match msg.error() {
+        Some(SharedError::DatabaseUnavailable | SharedError::Timeout | SharedError::Overloaded) => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        _ => StatusCode::OK,
+    }
+}
+
+/// Read a message from the body of a request on the given route and build the response to it.
+///
+/// The message must be valid and belong on the given route, or else we respond with
+/// `400 Bad Request`. If the response has an `ETag` that matches the request's `If-None-Match`
+/// header, then we respond with `304 Not Modified` and no body instead.
+pub async fn respond_to_message(
+    route: Route,
+    remote_addr: SocketAddr,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let format = Format::of(&headers);
+    let msg = match parse_message(format, route, &body) {
+        Ok(msg) => msg,
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format.response(&ServerToClientMsg::Error(error)),
+            )
+                .into_response()
+        }
+    };
+
+    // One connection from the pool is shared across every step of handling the message
+    let response_msg = match db::connection().await {
+        Ok(mut conn) => handle_message(&mut conn, msg, Some(remote_addr)).await,
+        Err(e) => {
+            error!(?e, "Unable to connect to the database");
+            ServerToClientMsg::Error(e.into())
+        }
+    };
+
+    match etag::of(&response_msg) {
+        Some(etag)
+            if etag::if_none_match(&headers).is_some_and(|value| etag::matches(value, &etag)) =>
+        {
+            debug!(%etag, "Response not modified");
+            (StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response()
+        }
+        Some(etag) => (
+            status_code(&response_msg),
+            [(ETAG, etag)],
+            format.response(&response_msg),
+        )
+            .into_response(),
+        None => (status_code(&response_msg), format.response(&response_msg)).into_response(),
+    }
+}
+
+/// The query string of a `GET /api/export.csv` request.
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    /// The session token.
+    pub token: Option<String>,
+}
+
+/// Respond to `GET /api/export.csv?token=...` by streaming a CSV of all the caller's completions.
+///
+/// The rows are encoded as they come out of the database and sent down a channel, which feeds the
+/// response body, so the whole export never has to be in memory at once.
+pub async fn respond_to_export(Query(ExportParams { token }): Query<ExportParams>) -> Response {
+    let Some(token) = token else {
+        warn!("Export requested without a token");
+        return (StatusCode::UNAUTHORIZED, "Unauthorized").into_response();
+    };
+
+    let mut conn = match db::connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!(?e, "Unable to connect to the database");
+            return (StatusCode::SERVICE_UNAVAILABLE, "Service unavailable").into_response();
+        }
+    };
+
+    let user_id = match resolve_token(&mut conn, &token).await {
+        Ok(user_id) => user_id,
+        Err(SharedError::Unauthorized) => {
+            return (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+        }
+        Err(e) => {
+            error!(?e, "Unable to resolve session token");
+            return (StatusCode::SERVICE_UNAVAILABLE, "Service unavailable").into_response();
+        }
+    };
+    info!(?user_id, "Exporting completions as CSV");
+
+    let (sender, mut receiver) = mpsc::channel::<std::io::Result<Vec<u8>>>(EXPORT_CHANNEL_CAPACITY);
+    tokio::spawn(
+        async move {
+            let result: color_eyre::Result<()> = async {
+                let mut chunks = pin!(export::stream_csv(&mut conn, &user_id).await?);
+                while let Some(chunk) = chunks.next().await {
+                    sender.send(Ok(chunk?)).await?;
+                }
+                Ok(())
+            }
+            .await;
+
+            if let Err(e) = result {
+                error!(?e, "Unable to finish CSV export");
+                // If the client has already gone, then there's nobody to tell
+                let _ = sender
+                    .send(Err(std::io::Error::other("export failed")))
+                    .await;
+            }
+        }
+        .in_current_span(),
+    );
+
+    (
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                CONTENT_DISPOSITION,
+                r#"attachment; filename="test-tracker-export.csv""#,
+            ),
+        ],
+        Body::from_stream(futures_util::stream::poll_fn(move |cx| {
+            receiver.poll_recv(cx)
+        })),
+    )
+        .into_response()
+}
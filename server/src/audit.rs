@@ -0,0 +1,113 @@
+//! This module keeps an audit log of security-relevant events, like logins and failed logins, so
+//! that users can check their own login history.
+//!
+//! Recording an event never fails the operation that it's recording. If the audit row can't be
+//! written, then we just log the error and carry on.
+
+use crate::db::{
+    models::{AuditLogEntry, NewAuditLogEntry},
+    schema::{audit_log, users},
+    DbConnection,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::Value;
+use std::net::SocketAddr;
+use test_tracker_shared::{policy::normalize_username, AuditEntry, AuditEvent, Error};
+use tracing::{instrument, warn};
+
+/// The most audit log entries that a user can get at once.
+pub const AUDIT_LOG_LIMIT: i64 = 50;
+
+/// Record an event in the audit log. Errors are logged rather than returned.
+#[instrument(skip(conn))]
+pub async fn record(
+    conn: &mut DbConnection,
+    user_id: Option<&str>,
+    event: AuditEvent,
+    remote_addr: Option<SocketAddr>,
+    details: Value,
+) {
+    let result = diesel::insert_into(audit_log::table)
+        .values(&NewAuditLogEntry {
+            user_id: user_id.map(ToString::to_string),
+            event: event.as_str().to_string(),
+            remote_addr: remote_addr.map(|addr| addr.to_string()),
+            created_at: Utc::now(),
+            details,
+        })
+        .execute(conn)
+        .await;
+
+    if let Err(error) = result {
+        warn!(?error, "Unable to write to the audit log");
+    }
+}
+
+/// Record a failed login for the given username. If there's an account with that username, then
+/// the event is attached to it, so that the owner can see it.
+pub async fn record_failed_login(
+    conn: &mut DbConnection,
+    username: &str,
+    remote_addr: Option<SocketAddr>,
+) {
+    let user_id = users::table
+        .filter(users::username.eq(normalize_username(username)))
+        .select(users::id)
+        .first::<String>(conn)
+        .await
+        .optional()
+        .unwrap_or_else(|error| {
+            warn!(?error, "Unable to look up user for the audit log");
+            None
+        });
+
+    record(
+        conn,
+        user_id.as_deref(),
+        AuditEvent::FailedLogin,
+        remote_addr,
+        serde_json::json!({ "username": username }),
+    )
+    .await;
+}
+
+/// Get the most recent entries in the audit log for the given user, newest first.
+#[instrument(skip(conn))]
+pub async fn get_audit_log_for_user(
+    conn: &mut DbConnection,
+    user_id: &str,
+) -> Result<Vec<AuditEntry>, Error> {
+    let entries = audit_log::table
+        .filter(audit_log::user_id.eq(user_id))
+        .order_by((audit_log::created_at.desc(), audit_log::id.desc()))
+        .limit(AUDIT_LOG_LIMIT)
+        .select(AuditLogEntry::as_select())
+        .load(conn)
+        .await?;
+
+    Ok(entries
+        .into_iter()
+        .filter_map(
+            |AuditLogEntry {
+                 event,
+                 remote_addr,
+                 created_at,
+                 details,
+                 ..
+             }| match event.parse() {
+                Ok(event) => Some(AuditEntry {
+                    event,
+                    remote_addr,
+                    created_at,
+                    details: details.to_string(),
+                }),
+                Err(error) => {
+                    warn!(?error, "Skipping unknown audit log event");
+                    None
+                }
+            },
+        )
+        .collect())
+}
@@ -0,0 +1,288 @@
+//! This module backs up all the data to a single portable RON document, and restores it again.
+//!
+//! A backup holds every user (including their hashed password and whether they're an admin), every
+//! test, and every completion, with their original IDs. Sessions, the audit log, and tombstones
+//! aren't included, so everyone has to log in again after a restore, and clients do a full sync.
+//!
+//! The document starts with a version number, so that a backup from an older server can still be
+//! recognised after the format changes.
+
+use crate::db::{
+    schema::{completions, tests, users},
+    DbConnection,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{dsl::count_star, prelude::*, result::Error as DbError};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, instrument};
+
+/// The version of the backup format written by this server.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// How many rows to insert in one statement when restoring, to stay well under the limit on bind
+/// parameters.
+const INSERT_CHUNK_SIZE: usize = 500;
+
+/// An error that could occur when reading or restoring a backup.
+#[derive(Debug, Error)]
+pub enum BackupError {
+    /// The backup was written by a server with a different backup format.
+    #[error("unsupported backup version {found} (expected {BACKUP_VERSION})")]
+    UnsupportedVersion {
+        /// The version of the backup.
+        found: u32,
+    },
+
+    /// The backup couldn't be parsed.
+    #[error("invalid backup: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+
+    /// The database already has data in it, which the backup would clash with.
+    #[error("the database must be empty to restore a backup")]
+    NotEmpty,
+
+    /// Some other database error occurred.
+    #[error("database error: {0}")]
+    Db(#[from] DbError),
+}
+
+/// A user in a backup.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Queryable, Selectable, Insertable, Serialize, Deserialize,
+)]
+#[diesel(table_name = users)]
+pub struct BackupUser {
+    /// The ID of the user.
+    pub id: String,
+
+    /// The username of the user.
+    pub username: String,
+
+    /// The hashed password of the user, hashed with Argon2id.
+    pub hashed_password: String,
+
+    /// Whether the user can manage other users' accounts.
+    pub is_admin: bool,
+
+    /// When the user created their account.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A test in a backup.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Queryable, Selectable, Insertable, Serialize, Deserialize,
+)]
+#[diesel(table_name = tests)]
+pub struct BackupTest {
+    /// Unique ID.
+    pub id: i32,
+
+    /// The ID of the user that owns this test.
+    pub user_id: String,
+
+    /// The subject of the test: maths, English, science, etc.
+    pub subject: String,
+
+    /// The topic of the test: statistics, Shakespeare, organic chemistry, etc.
+    pub topic: Option<String>,
+
+    /// The date or ID of the test: Monday 3 June 2019, Mock Set 1, etc.
+    pub date_or_id: String,
+
+    /// The qualification_level of the test: GCSE, A Level, etc.
+    pub qualification_level: Option<String>,
+
+    /// The exam board for the test: Edexcel, AQA, OCR, etc.
+    pub exam_board: Option<String>,
+
+    /// A link to the paper.
+    pub paper_link: Option<String>,
+
+    /// A link to the mark scheme.
+    pub mark_scheme_link: Option<String>,
+
+    /// Any extra comments.
+    pub comments: Option<String>,
+
+    /// When this test or any of its completions last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A completion in a backup.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Queryable, Selectable, Insertable, Serialize, Deserialize,
+)]
+#[diesel(table_name = completions)]
+pub struct BackupCompletion {
+    /// Unique ID.
+    pub id: i32,
+
+    /// The ID of the test that this completion belongs to.
+    pub test_id: i32,
+
+    /// The mark that was actually achieved.
+    pub achieved_mark: i32,
+
+    /// The total marks available.
+    pub total_marks: i32,
+
+    /// The date of the completion.
+    pub date: Option<NaiveDate>,
+
+    /// Any extra comments.
+    pub comments: Option<String>,
+}
+
+/// A backup of all the data in the database.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backup {
+    /// The version of the backup format. See [`BACKUP_VERSION`].
+    pub version: u32,
+
+    /// When the backup was made.
+    pub created_at: DateTime<Utc>,
+
+    /// Every user, sorted by ID.
+    pub users: Vec<BackupUser>,
+
+    /// Every test, sorted by ID.
+    pub tests: Vec<BackupTest>,
+
+    /// Every completion, sorted by ID.
+    pub completions: Vec<BackupCompletion>,
+}
+
+/// Just the version of a backup, so that we can check it before trying to parse the rest.
+#[derive(Deserialize)]
+struct BackupHeader {
+    /// The version of the backup format.
+    version: u32,
+}
+
+impl Backup {
+    /// Serialize the backup as pretty RON.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, PrettyConfig::default())
+    }
+
+    /// Parse a backup from RON, checking that it's a version that we understand.
+    pub fn from_ron(text: &str) -> Result<Self, BackupError> {
+        let BackupHeader { version } = ron::from_str(text)?;
+        if version != BACKUP_VERSION {
+            return Err(BackupError::UnsupportedVersion { found: version });
+        }
+
+        Ok(ron::from_str(text)?)
+    }
+}
+
+/// Read all the data from the database into a backup. Everything is read in one transaction, so
+/// the backup is consistent even if the server is running.
+#[instrument(skip(conn))]
+pub async fn create_backup(conn: &mut DbConnection) -> Result<Backup, DbError> {
+    let backup = conn
+        .transaction::<_, DbError, _>(|conn| {
+            async move {
+                Ok(Backup {
+                    version: BACKUP_VERSION,
+                    created_at: Utc::now(),
+                    users: users::table
+                        .select(BackupUser::as_select())
+                        .order_by(users::id)
+                        .load(conn)
+                        .await?,
+                    tests: tests::table
+                        .select(BackupTest::as_select())
+                        .order_by(tests::id)
+                        .load(conn)
+                        .await?,
+                    completions: completions::table
+                        .select(BackupCompletion::as_select())
+                        .order_by(completions::id)
+                        .load(conn)
+                        .await?,
+                })
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    info!(
+        users = backup.users.len(),
+        tests = backup.tests.len(),
+        completions = backup.completions.len(),
+        "Created backup"
+    );
+    Ok(backup)
+}
+
+/// Restore a backup into an empty database, keeping all the original IDs. Everything happens in
+/// one transaction, so a failed restore leaves the database empty.
+#[instrument(skip_all)]
+pub async fn restore_backup(conn: &mut DbConnection, backup: &Backup) -> Result<(), BackupError> {
+    conn.transaction::<_, BackupError, _>(|conn| {
+        async move {
+            let existing_users: i64 = users::table.select(count_star()).first(conn).await?;
+            let existing_tests: i64 = tests::table.select(count_star()).first(conn).await?;
+            if existing_users > 0 || existing_tests > 0 {
+                return Err(BackupError::NotEmpty);
+            }
+
+            for chunk in backup.users.chunks(INSERT_CHUNK_SIZE) {
+                diesel::insert_into(users::table)
+                    .values(chunk)
+                    .execute(conn)
+                    .await?;
+            }
+            for chunk in backup.tests.chunks(INSERT_CHUNK_SIZE) {
+                diesel::insert_into(tests::table)
+                    .values(chunk)
+                    .execute(conn)
+                    .await?;
+            }
+            for chunk in backup.completions.chunks(INSERT_CHUNK_SIZE) {
+                diesel::insert_into(completions::table)
+                    .values(chunk)
+                    .execute(conn)
+                    .await?;
+            }
+
+            reset_id_sequences(conn).await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    info!(
+        users = backup.users.len(),
+        tests = backup.tests.len(),
+        completions = backup.completions.len(),
+        "Restored backup"
+    );
+    Ok(())
+}
+
+/// Move the sequences behind the serial ID columns past the restored IDs, so that new rows don't
+/// clash with them.
+#[cfg(not(feature = "sqlite"))]
+async fn reset_id_sequences(conn: &mut DbConnection) -> Result<(), DbError> {
+    for table in ["tests", "completions"] {
+        diesel::sql_query(format!(
+            "SELECT setval(pg_get_serial_sequence('{table}', 'id'), COALESCE(MAX(id), 0) + 1, false) \
+             FROM {table}"
+        ))
+        .execute(conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// SQLite always gives new rows an ID past the biggest one in the table, so there's nothing to do.
+#[cfg(feature = "sqlite")]
+async fn reset_id_sequences(_conn: &mut DbConnection) -> Result<(), DbError> {
+    Ok(())
+}
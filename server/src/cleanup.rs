@@ -0,0 +1,63 @@
+//! This module deletes rows that can never be used again, so that they don't pile up forever.
+//!
+//! The server runs [`run`] in the background, which cleans up every
+//! [`Config::cleanup_interval`](crate::config::Config::cleanup_interval). Lapsed sessions are also
+//! deleted when someone tries to use them, but most are simply never used again.
+
+use crate::{db, db::DbConnection, sessions::delete_expired_sessions};
+use chrono::{DateTime, Utc};
+use diesel::result::Error as DbError;
+use std::time::Duration as StdDuration;
+use tokio::{
+    sync::oneshot,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{error, info, instrument};
+
+/// What one round of cleaning up deleted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CleanupSummary {
+    /// How many sessions had lapsed.
+    pub expired_sessions: usize,
+}
+
+/// Delete every row that had expired by the given time.
+#[instrument(skip(conn))]
+pub async fn delete_expired_rows(
+    conn: &mut DbConnection,
+    now: DateTime<Utc>,
+) -> Result<CleanupSummary, DbError> {
+    let summary = CleanupSummary {
+        expired_sessions: delete_expired_sessions(conn, now).await?,
+    };
+
+    info!(expired_sessions = summary.expired_sessions, "Cleaned up");
+    Ok(summary)
+}
+
+/// Clean up straight away and then after every interval, until told to shut down. Errors are
+/// logged, and cleaning up is tried again after the next interval.
+pub async fn run(every: StdDuration, mut shutdown: oneshot::Receiver<()>) {
+    let mut ticks = interval(every);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = &mut shutdown => break,
+        }
+
+        let mut conn = match db::connection().await {
+            Ok(conn) => conn,
+            Err(error) => {
+                error!(?error, "Unable to connect to the database to clean up");
+                continue;
+            }
+        };
+        if let Err(error) = delete_expired_rows(&mut conn, Utc::now()).await {
+            error!(?error, "Unable to clean up expired rows");
+        }
+    }
+
+    info!("Cleanup task stopped");
+}
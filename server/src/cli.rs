@@ -0,0 +1,259 @@
+//! This module handles the command line, which runs the server by default but also has some
+//! subcommands for administration.
+
+use clap::{Parser, Subcommand};
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use std::{
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+};
+use test_tracker_server::{
+    admin::{normalize_existing_tests, promote_to_admin, reset_password_by_username},
+    audit,
+    backup::{create_backup, restore_backup, Backup},
+    db,
+    passwords::add_new_user,
+    seed::{check_database_name, seed_demo_data, DEMO_PASSWORD, DEMO_USERNAME},
+};
+use test_tracker_shared::AuditEvent;
+use tracing::info;
+
+/// The server for TestTracker.
+#[derive(Clone, Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// What to do. Defaults to running the server.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Before doing anything else, create a demo user with some tests. This refuses to touch a
+    /// database unless its name contains `dev` or `test`.
+    #[arg(long, global = true)]
+    pub seed_demo_data: bool,
+}
+
+impl Cli {
+    /// What to do, which defaults to running the server.
+    pub fn command(&self) -> Command {
+        self.command.clone().unwrap_or(Command::Serve)
+    }
+}
+
+/// What to do when the server is run.
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Run the server (the default).
+    Serve,
+
+    /// Make the user with the given username an admin, and exit.
+    PromoteAdmin {
+        /// The username of the user to promote.
+        username: String,
+    },
+
+    /// Create a user or reset their password, and exit. The password is prompted for, or read
+    /// from the first line of stdin if it isn't a terminal.
+    User {
+        /// What to do to the user.
+        #[command(subcommand)]
+        command: UserCommand,
+    },
+
+    /// Normalize the whitespace in every existing test, and exit.
+    NormalizeTests,
+
+    /// Dump all the users, tests, and completions to a file, and exit.
+    Backup {
+        /// The file to write the backup to.
+        #[arg(long)]
+        out: PathBuf,
+    },
+
+    /// Restore a backup made with `backup` into an empty database, and exit.
+    Restore {
+        /// The backup file to read.
+        #[arg(long = "in", value_name = "IN")]
+        input: PathBuf,
+    },
+}
+
+/// What to do to a user from the command line.
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum UserCommand {
+    /// Create a new user.
+    Create {
+        /// The username of the new user.
+        username: String,
+    },
+
+    /// Set a new password for an existing user, ending all their sessions.
+    SetPassword {
+        /// The username of the user.
+        username: String,
+    },
+}
+
+/// Parse the command line arguments. If they're invalid, then this prints the usage and exits.
+pub fn parse() -> Cli {
+    Cli::parse()
+}
+
+/// Make the user with the given username an admin.
+pub async fn promote_admin(username: &str) -> Result<()> {
+    let mut conn = db::connection()
+        .await
+        .wrap_err("Unable to connect to the database")?;
+    promote_to_admin(&mut conn, username)
+        .await
+        .wrap_err_with(|| format!("Unable to promote {username:?} to admin"))?;
+
+    info!(?username, "Promoted user to admin");
+    println!("{username} is now an admin");
+    Ok(())
+}
+
+/// Read a password without echoing it, asking twice to catch typos. If stdin isn't a terminal,
+/// then the password is the first line of stdin instead, so that scripts can pipe it in.
+fn read_password() -> Result<String> {
+    let stdin = io::stdin();
+    let password = if stdin.is_terminal() {
+        let password = rpassword::prompt_password("Password: ")?;
+        if rpassword::prompt_password("Confirm password: ")? != password {
+            bail!("The passwords don't match");
+        }
+        password
+    } else {
+        let mut line = String::new();
+        stdin
+            .read_line(&mut line)
+            .wrap_err("Unable to read the password from stdin")?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    };
+
+    if password.is_empty() {
+        bail!("The password can't be empty");
+    }
+    Ok(password)
+}
+
+/// Create a user or reset their password.
+pub async fn user(command: &UserCommand) -> Result<()> {
+    let password = read_password()?;
+    let mut conn = db::connection()
+        .await
+        .wrap_err("Unable to connect to the database")?;
+
+    match command {
+        UserCommand::Create { username } => {
+            let user = add_new_user(&mut conn, username, &password)
+                .await
+                .wrap_err_with(|| format!("Unable to create {username:?}"))?;
+            audit::record(
+                &mut conn,
+                Some(&user.id),
+                AuditEvent::AccountCreated,
+                None,
+                serde_json::json!({ "created_from_command_line": true }),
+            )
+            .await;
+
+            info!(id = ?user.id, username = ?user.username, "Created user");
+            println!("Created {}", user.username);
+        }
+        UserCommand::SetPassword { username } => {
+            reset_password_by_username(&mut conn, username, &password)
+                .await
+                .wrap_err_with(|| format!("Unable to set the password of {username:?}"))?;
+            println!("Set the password of {username}");
+        }
+    }
+    Ok(())
+}
+
+/// Normalize the whitespace in every existing test.
+pub async fn normalize_tests() -> Result<()> {
+    let mut conn = db::connection()
+        .await
+        .wrap_err("Unable to connect to the database")?;
+    let summary = normalize_existing_tests(&mut conn)
+        .await
+        .wrap_err("Unable to normalize the existing tests")?;
+
+    println!("Normalized {} tests", summary.updated);
+    if !summary.conflicts.is_empty() {
+        println!(
+            "Skipped {} tests that would have become duplicates, with IDs {:?}",
+            summary.conflicts.len(),
+            summary.conflicts
+        );
+    }
+    Ok(())
+}
+
+/// Back up all the data to the given file.
+pub async fn backup(out: &Path) -> Result<()> {
+    let mut conn = db::connection()
+        .await
+        .wrap_err("Unable to connect to the database")?;
+    let backup = create_backup(&mut conn)
+        .await
+        .wrap_err("Unable to read the data to back up")?;
+
+    fs::write(out, backup.to_ron()?)
+        .wrap_err_with(|| format!("Unable to write the backup to {out:?}"))?;
+
+    println!(
+        "Backed up {} users, {} tests, and {} completions to {}",
+        backup.users.len(),
+        backup.tests.len(),
+        backup.completions.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Restore the backup in the given file into an empty database.
+pub async fn restore(input: &Path) -> Result<()> {
+    let text = fs::read_to_string(input)
+        .wrap_err_with(|| format!("Unable to read the backup from {input:?}"))?;
+    let backup = Backup::from_ron(&text).wrap_err("Unable to parse the backup")?;
+
+    let mut conn = db::connection()
+        .await
+        .wrap_err("Unable to connect to the database")?;
+    restore_backup(&mut conn, &backup)
+        .await
+        .wrap_err("Unable to restore the backup")?;
+
+    println!(
+        "Restored {} users, {} tests, and {} completions from {}",
+        backup.users.len(),
+        backup.tests.len(),
+        backup.completions.len(),
+        input.display()
+    );
+    Ok(())
+}
+
+/// Create the demo user and give it some tests, if the database looks like a development database.
+pub async fn seed() -> Result<()> {
+    check_database_name(&db::database_name())?;
+
+    let mut conn = db::connection()
+        .await
+        .wrap_err("Unable to connect to the database")?;
+    let summary = seed_demo_data(&mut conn)
+        .await
+        .wrap_err("Unable to seed the demo data")?;
+
+    println!(
+        "Seeded {} demo tests ({} already existed); log in as {DEMO_USERNAME:?} with the password \
+         {DEMO_PASSWORD:?}",
+        summary.tests_inserted, summary.tests_skipped
+    );
+    Ok(())
+}
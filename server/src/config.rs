@@ -0,0 +1,289 @@
+//! This module handles the runtime configuration of the server.
+//!
+//! Everything here is read from environment variables once at startup, and every variable has a
+//! sensible default, so an empty environment gives a working server.
+
+use crate::logging::rotation::RotationPolicy;
+use argon2::Params as Argon2Params;
+use chrono::Duration;
+use std::{
+    env, fmt, fs, net::SocketAddr, path::PathBuf, str::FromStr, sync::OnceLock,
+    time::Duration as StdDuration,
+};
+use thiserror::Error;
+use tracing_unwrap::ResultExt;
+
+/// The port to listen on if neither `$BIND_ADDRESSES` nor `$PORT` is set.
+const DEFAULT_PORT: u16 = 20519;
+
+/// The global config, initialised by [`init`] or lazily by [`config`].
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// An error that could occur when reading the config from the environment.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    /// The environment variable was set, but its value couldn't be parsed.
+    #[error("invalid value for ${name}: {value:?}")]
+    InvalidValue {
+        /// The name of the environment variable.
+        name: &'static str,
+
+        /// The value that we couldn't parse.
+        value: String,
+    },
+
+    /// One of the bind addresses couldn't be parsed.
+    #[error("invalid bind address in ${name}: {value:?}")]
+    InvalidBindAddress {
+        /// The name of the environment variable.
+        name: &'static str,
+
+        /// The address that we couldn't parse.
+        value: String,
+    },
+
+    /// Only one of the TLS certificate and private key paths was set.
+    #[error("$SERVER_SSL_CERT_PATH and $SERVER_SSL_KEY_PATH must be set together")]
+    IncompleteTls,
+
+    /// The pepper file couldn't be read, or didn't have any peppers in it.
+    #[error("invalid pepper file {path:?}: {reason}")]
+    InvalidPepperFile {
+        /// The path to the pepper file.
+        path: PathBuf,
+
+        /// Why we couldn't use it.
+        reason: String,
+    },
+
+    /// The Argon2 parameters were out of range.
+    #[error("invalid Argon2 parameters: {0}")]
+    InvalidArgon2Params(String),
+}
+
+/// Where to find the TLS certificate and private key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// The path to the PEM-encoded certificate (chain).
+    pub cert_path: PathBuf,
+
+    /// The path to the PEM-encoded private key.
+    pub key_path: PathBuf,
+}
+
+/// The secret peppers that get mixed into password hashes, so that hashes leaked from the database
+/// can't be cracked without also stealing the pepper file.
+///
+/// The first pepper is used for new hashes, and the rest are only accepted when verifying, so that
+/// the pepper can be rotated without locking anyone out. The peppers never appear in logs.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Peppers(Vec<Vec<u8>>);
+
+impl Peppers {
+    /// Make a list of peppers, where the first is the current one.
+    pub fn new(peppers: Vec<Vec<u8>>) -> Self {
+        Self(peppers)
+    }
+
+    /// Parse the contents of a pepper file, which has one pepper per line, with the current one
+    /// first. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(contents: &str) -> Option<Self> {
+        let peppers: Vec<Vec<u8>> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.as_bytes().to_vec())
+            .collect();
+
+        (!peppers.is_empty()).then_some(Self(peppers))
+    }
+
+    /// The pepper to use for new hashes, if there is one.
+    pub fn current(&self) -> Option<&[u8]> {
+        self.0.first().map(Vec::as_slice)
+    }
+
+    /// Every accepted pepper, starting with the current one.
+    pub fn all(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.iter().map(Vec::as_slice)
+    }
+}
+
+impl fmt::Debug for Peppers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Peppers({} redacted)", self.0.len())
+    }
+}
+
+/// The runtime configuration of the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// How long an ordinary session lasts without any activity. Set with `$SESSION_LENGTH_HOURS`.
+    pub session_length: Duration,
+
+    /// How long a "remember me" session lasts without any activity. Set with
+    /// `$REMEMBER_ME_SESSION_LENGTH_DAYS`.
+    pub remember_me_session_length: Duration,
+
+    /// The parameters used to hash new passwords. Set with `$ARGON2_MEMORY_KIB`,
+    /// `$ARGON2_ITERATIONS`, and `$ARGON2_PARALLELISM`. Existing hashes with weaker parameters are
+    /// upgraded the next time that user logs in.
+    pub argon2_params: Argon2Params,
+
+    /// The peppers for password hashes, read from the file at `$PASSWORD_PEPPER_FILE`. If that's
+    /// not set, then passwords aren't peppered.
+    pub peppers: Peppers,
+
+    /// How long the server spends handling a request before giving up and responding with
+    /// `503 Service Unavailable`. Set with `$REQUEST_TIMEOUT_SECS`.
+    pub request_timeout: StdDuration,
+
+    /// How long to keep retrying to connect to the database at startup before giving up. Set with
+    /// `$DATABASE_STARTUP_TIMEOUT_SECS`.
+    pub database_startup_timeout: StdDuration,
+
+    /// The maximum number of requests handled at once. Any more get `503 Service Unavailable`
+    /// straight away. Set with `$MAX_CONCURRENT_REQUESTS`.
+    pub max_concurrent_requests: usize,
+
+    /// How often to delete expired sessions from the database. Set with
+    /// `$CLEANUP_INTERVAL_MINS`, which is at least 1.
+    pub cleanup_interval: StdDuration,
+
+    /// The addresses to listen on, like `0.0.0.0:8443` and `[::]:8443`. Set with `$BIND_ADDRESSES`
+    /// as a comma-separated list, which defaults to `127.0.0.1:$PORT`.
+    pub bind_addresses: Vec<SocketAddr>,
+
+    /// Where to find the TLS certificate and private key. Set with `$SERVER_SSL_CERT_PATH` and
+    /// `$SERVER_SSL_KEY_PATH`. If neither is set, then the server uses plain HTTP.
+    pub tls: Option<TlsConfig>,
+
+    /// When to rotate the log file and how many old log files to keep. Set with
+    /// `$LOG_MAX_FILE_SIZE_MIB` and `$LOG_MAX_FILES`.
+    pub log_rotation: RotationPolicy,
+
+    /// The URL of the client, which the info page links to. Set with `$CLIENT_URL`.
+    pub client_url: Option<String>,
+
+    /// The path to the SQLite database file, which gets created if it doesn't exist. Set with
+    /// `$SQLITE_DATABASE_PATH`, which defaults to `test-tracker.sqlite3`.
+    #[cfg(feature = "sqlite")]
+    pub sqlite_database_path: PathBuf,
+}
+
+impl Config {
+    /// Read the config from the environment.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            session_length: Duration::hours(var_or("SESSION_LENGTH_HOURS", 24)?),
+            remember_me_session_length: Duration::days(var_or(
+                "REMEMBER_ME_SESSION_LENGTH_DAYS",
+                30,
+            )?),
+            argon2_params: Argon2Params::new(
+                var_or("ARGON2_MEMORY_KIB", Argon2Params::DEFAULT_M_COST)?,
+                var_or("ARGON2_ITERATIONS", Argon2Params::DEFAULT_T_COST)?,
+                var_or("ARGON2_PARALLELISM", Argon2Params::DEFAULT_P_COST)?,
+                None,
+            )
+            .map_err(|e| ConfigError::InvalidArgon2Params(e.to_string()))?,
+            peppers: match env::var_os("PASSWORD_PEPPER_FILE") {
+                Some(path) => read_peppers(path.into())?,
+                None => Peppers::default(),
+            },
+            request_timeout: StdDuration::from_secs(var_or("REQUEST_TIMEOUT_SECS", 10)?),
+            database_startup_timeout: StdDuration::from_secs(var_or(
+                "DATABASE_STARTUP_TIMEOUT_SECS",
+                60,
+            )?),
+            max_concurrent_requests: var_or("MAX_CONCURRENT_REQUESTS", 64)?,
+            cleanup_interval: StdDuration::from_secs(
+                var_or::<u64>("CLEANUP_INTERVAL_MINS", 60)?
+                    .max(1)
+                    .saturating_mul(60),
+            ),
+            bind_addresses: addresses_var(
+                "BIND_ADDRESSES",
+                SocketAddr::from(([127, 0, 0, 1], var_or("PORT", DEFAULT_PORT)?)),
+            )?,
+            tls: match (
+                env::var_os("SERVER_SSL_CERT_PATH"),
+                env::var_os("SERVER_SSL_KEY_PATH"),
+            ) {
+                (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                    cert_path: cert_path.into(),
+                    key_path: key_path.into(),
+                }),
+                (None, None) => None,
+                _ => return Err(ConfigError::IncompleteTls),
+            },
+            log_rotation: RotationPolicy {
+                max_file_size: var_or::<u64>("LOG_MAX_FILE_SIZE_MIB", 50)?
+                    .saturating_mul(1024 * 1024),
+                max_files: var_or("LOG_MAX_FILES", 14)?,
+            },
+            client_url: env::var("CLIENT_URL")
+                .ok()
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty()),
+            #[cfg(feature = "sqlite")]
+            sqlite_database_path: env::var_os("SQLITE_DATABASE_PATH")
+                .map_or_else(|| PathBuf::from("test-tracker.sqlite3"), PathBuf::from),
+        })
+    }
+}
+
+/// Parse the given environment variable, or return the default if it's not set.
+fn var_or<T: FromStr>(name: &'static str, default: T) -> Result<T, ConfigError> {
+    match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| ConfigError::InvalidValue { name, value }),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Read the peppers from the pepper file at the given path.
+fn read_peppers(path: PathBuf) -> Result<Peppers, ConfigError> {
+    let contents = fs::read_to_string(&path).map_err(|e| ConfigError::InvalidPepperFile {
+        path: path.clone(),
+        reason: e.to_string(),
+    })?;
+
+    Peppers::parse(&contents).ok_or_else(|| ConfigError::InvalidPepperFile {
+        path,
+        reason: "there are no peppers in it".to_string(),
+    })
+}
+
+/// Parse the given environment variable as a comma-separated list of socket addresses, or return
+/// just the default if it's not set.
+fn addresses_var(name: &'static str, default: SocketAddr) -> Result<Vec<SocketAddr>, ConfigError> {
+    match env::var(name) {
+        Ok(addresses) => addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .map(|addr| {
+                addr.parse().map_err(|_| ConfigError::InvalidBindAddress {
+                    name,
+                    value: addr.to_string(),
+                })
+            })
+            .collect(),
+        Err(_) => Ok(vec![default]),
+    }
+}
+
+/// Read the config from the environment and store it globally. This should be called once at
+/// startup so that a bad config fails fast.
+pub fn init() -> Result<&'static Config, ConfigError> {
+    let config = Config::from_env()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Get the global config, reading it from the environment if [`init`] hasn't been called yet.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(|| Config::from_env().expect_or_log("The config should be valid"))
+}
@@ -1,19 +1,314 @@
 //! This module handles interfacing with the PostgreSQL database running on the server.
 //!
 //! Use `$DATABASE_URL` in `/.env` to specify the URL for the database on the server.
+//!
+//! With the `sqlite` feature, the server uses a SQLite database file at `$SQLITE_DATABASE_PATH`
+//! instead, which is handy for local development without a running PostgreSQL instance. All the
+//! code outside this module is written against [`DbConnection`], so it works with either backend.
+
+use crate::config::config;
+use diesel::result::{
+    ConnectionError, DatabaseErrorInformation, DatabaseErrorKind, Error as DbError,
+};
+use diesel_async::{
+    pooled_connection::{
+        deadpool::{Object, Pool, PoolError},
+        AsyncDieselConnectionManager, ManagerConfig, PoolError as ManagerError,
+    },
+    AsyncConnection, RunQueryDsl,
+};
+use std::{
+    borrow::Cow,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::{info, instrument, warn};
+use tracing_unwrap::ResultExt;
+use uuid::Uuid;
 
-use diesel::{Connection, PgConnection};
-use std::env;
+/// How long to wait before the first retry when the database is unreachable at startup.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// The longest we wait between retries when the database is unreachable at startup.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
 
 pub mod models;
 
 /// This module contains all the DB schema generated by diesel.
 #[rustfmt::skip]
+#[cfg_attr(feature = "sqlite", path = "schema_sqlite.rs")]
 pub mod schema;
 
-/// Establish a connection to the PostgreSQL database using `$DATABASE_URL`.
-pub fn establish_connection() -> PgConnection {
-    let database_url = env!("DATABASE_URL");
-    PgConnection::establish(database_url)
-        .unwrap_or_else(|_| panic!("Error connecting to {database_url}"))
+/// The connection type for the database backend that the server was compiled for.
+#[cfg(not(feature = "sqlite"))]
+pub type DbConnection = diesel_async::AsyncPgConnection;
+
+/// The connection type for the database backend that the server was compiled for. SQLite has no
+/// async driver, so this runs the synchronous connection on a blocking thread.
+#[cfg(feature = "sqlite")]
+pub type DbConnection =
+    diesel_async::sync_connection_wrapper::SyncConnectionWrapper<diesel::SqliteConnection>;
+
+/// A connection borrowed from the pool, which goes back into the pool when dropped.
+pub type PooledConnection = Object<DbConnection>;
+
+/// The global connection pool, created lazily by [`pool`].
+static POOL: OnceLock<Pool<DbConnection>> = OnceLock::new();
+
+/// The connection for health checks, created lazily by [`health_check_connection`].
+static HEALTH_CHECK_CONNECTION: OnceLock<Mutex<Option<DbConnection>>> = OnceLock::new();
+
+/// The migrations for the SQLite database, which get run at startup, since there's no separate
+/// setup step like there is for PostgreSQL.
+#[cfg(feature = "sqlite")]
+const SQLITE_MIGRATIONS: diesel_migrations::EmbeddedMigrations =
+    diesel_migrations::embed_migrations!("migrations-sqlite");
+
+/// The URL to connect to the database with.
+#[cfg(not(feature = "sqlite"))]
+fn database_url() -> Cow<'static, str> {
+    Cow::Borrowed(env!("DATABASE_URL"))
+}
+
+/// The URL to connect to the database with.
+#[cfg(feature = "sqlite")]
+fn database_url() -> Cow<'static, str> {
+    config().sqlite_database_path.to_string_lossy()
+}
+
+/// The name of the database, which is the last part of the path in `$DATABASE_URL`.
+#[cfg(not(feature = "sqlite"))]
+pub fn database_name() -> String {
+    let url = database_url();
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or_default().to_string()
+}
+
+/// The name of the database, which is the file name of the SQLite database.
+#[cfg(feature = "sqlite")]
+pub fn database_name() -> String {
+    config()
+        .sqlite_database_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Set up a fresh connection so that no query can run for longer than the given timeout.
+#[cfg(not(feature = "sqlite"))]
+async fn set_timeout(conn: &mut DbConnection, timeout: Duration) -> Result<(), ConnectionError> {
+    diesel::sql_query(format!("SET statement_timeout = {}", timeout.as_millis()))
+        .execute(conn)
+        .await
+        .map_err(ConnectionError::CouldntSetupConfiguration)?;
+    Ok(())
+}
+
+/// Set up a fresh connection so that no query waits for a lock for longer than the given timeout.
+/// SQLite doesn't enforce foreign keys unless asked to, so we turn that on here too.
+#[cfg(feature = "sqlite")]
+async fn set_timeout(conn: &mut DbConnection, timeout: Duration) -> Result<(), ConnectionError> {
+    diesel::sql_query(format!("PRAGMA busy_timeout = {}", timeout.as_millis()))
+        .execute(conn)
+        .await
+        .map_err(ConnectionError::CouldntSetupConfiguration)?;
+    diesel::sql_query("PRAGMA foreign_keys = ON")
+        .execute(conn)
+        .await
+        .map_err(ConnectionError::CouldntSetupConfiguration)?;
+    Ok(())
+}
+
+/// Establish a new connection to the database using `$DATABASE_URL`, or `$SQLITE_DATABASE_PATH`
+/// with the `sqlite` feature. Request handlers should use [`connection`] instead, which reuses
+/// connections from the pool.
+///
+/// The connection has a `statement_timeout` matching the request timeout, so that a query from a
+/// request that has timed out gets cancelled rather than left running.
+pub async fn establish_connection() -> Result<DbConnection, ConnectionError> {
+    let mut conn = DbConnection::establish(&database_url()).await?;
+    set_timeout(&mut conn, config().request_timeout).await?;
+    Ok(conn)
+}
+
+/// Get the global connection pool, creating it if this is the first call. The pool holds at most
+/// one connection per request that can be handled at once, and connects lazily.
+fn pool() -> &'static Pool<DbConnection> {
+    POOL.get_or_init(|| {
+        let mut manager_config = ManagerConfig::default();
+        manager_config.custom_setup = Box::new(|_url| Box::pin(establish_connection()));
+
+        Pool::builder(AsyncDieselConnectionManager::new_with_config(
+            database_url(),
+            manager_config,
+        ))
+        .max_size(config().max_concurrent_requests)
+        .build()
+        .expect_or_log("We should be able to build the connection pool")
+    })
+}
+
+/// Get a connection to the database from the pool.
+///
+/// If the database is unreachable, then the error converts into
+/// [`DatabaseUnavailable`](test_tracker_shared::Error::DatabaseUnavailable), which the client
+/// receives as a `503 Service Unavailable`.
+pub async fn connection() -> Result<PooledConnection, ConnectionError> {
+    pool().get().await.map_err(|error| match error {
+        PoolError::Backend(ManagerError::ConnectionError(error)) => error,
+        error => ConnectionError::BadConnection(error.to_string()),
+    })
+}
+
+/// Run any pending migrations on the SQLite database, creating the tables if the file is new.
+///
+/// The migration harness is synchronous, so this should be run on a blocking thread.
+#[cfg(feature = "sqlite")]
+#[instrument]
+pub fn run_sqlite_migrations() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use diesel::Connection;
+    use diesel_migrations::MigrationHarness;
+
+    let mut conn = diesel::SqliteConnection::establish(&database_url())?;
+    let applied = conn.run_pending_migrations(SQLITE_MIGRATIONS)?;
+    info!(count = applied.len(), "Ran pending SQLite migrations");
+    Ok(())
+}
+
+/// Wait until we can connect to the database, retrying with exponential backoff for up to the
+/// given duration. This is useful at startup, since the database might start after the server.
+#[instrument]
+pub async fn wait_for_database(max_wait: Duration) -> Result<(), ConnectionError> {
+    let start = Instant::now();
+    let mut delay = INITIAL_RETRY_DELAY;
+
+    loop {
+        match connection().await {
+            Ok(_) => {
+                info!(elapsed = ?start.elapsed(), "Connected to the database");
+                return Ok(());
+            }
+            Err(error) if start.elapsed() + delay < max_wait => {
+                warn!(
+                    ?error,
+                    ?delay,
+                    "Unable to connect to the database; retrying"
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// A unique constraint in the schema that we expect to be violated sometimes, so that violations of
+/// it can be turned into precise errors rather than generic database errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UniqueConstraint {
+    /// The primary key of the users table, which is only violated by a UUID collision.
+    UserId,
+
+    /// The uniqueness of usernames.
+    Username,
+
+    /// The uniqueness of tests by user, subject, and date or ID.
+    Test,
+}
+
+impl UniqueConstraint {
+    /// Every known constraint.
+    const ALL: [Self; 3] = [Self::UserId, Self::Username, Self::Test];
+
+    /// The name of the constraint in PostgreSQL.
+    #[cfg(not(feature = "sqlite"))]
+    fn name(self) -> &'static str {
+        match self {
+            Self::UserId => "users_pkey",
+            Self::Username => "users_username_key",
+            Self::Test => "tests_user_id_subject_date_or_id",
+        }
+    }
+
+    /// The columns of the constraint, as SQLite lists them in its error message.
+    #[cfg(feature = "sqlite")]
+    fn sqlite_columns(self) -> &'static str {
+        match self {
+            Self::UserId => "users.id",
+            Self::Username => "users.username",
+            Self::Test => "tests.user_id, tests.subject, tests.date_or_id",
+        }
+    }
+
+    /// Work out which known constraint an error was a violation of, if any.
+    pub fn violated_by(error: &DbError) -> Option<Self> {
+        match error {
+            DbError::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
+                Self::from_info(info.as_ref())
+            }
+            _ => None,
+        }
+    }
+
+    /// Work out which known constraint a unique violation was for, if any.
+    #[cfg(not(feature = "sqlite"))]
+    fn from_info(info: &dyn DatabaseErrorInformation) -> Option<Self> {
+        let name = info.constraint_name()?;
+        Self::ALL
+            .into_iter()
+            .find(|constraint| constraint.name() == name)
+    }
+
+    /// Work out which known constraint a unique violation was for, if any.
+    ///
+    /// SQLite doesn't report the name of the constraint, only its columns at the end of the
+    /// message, so we compare those exactly.
+    #[cfg(feature = "sqlite")]
+    fn from_info(info: &dyn DatabaseErrorInformation) -> Option<Self> {
+        let columns = info.message().strip_prefix("UNIQUE constraint failed: ")?;
+        Self::ALL
+            .into_iter()
+            .find(|constraint| constraint.sqlite_columns() == columns)
+    }
+}
+
+/// Parse a user ID back into a [`Uuid`].
+///
+/// Users created before IDs were generated by the server have IDs like `user_` followed by 50
+/// random alphanumeric characters, which aren't UUIDs, so this fails for them.
+pub fn parse_user_id(id: &str) -> Result<Uuid, uuid::Error> {
+    Uuid::parse_str(id)
+}
+
+/// Get the connection that [`database_is_reachable`] uses, which is kept apart from the pool so
+/// that the health check still works when every pooled connection is busy. It's `None` until the
+/// first health check, and after any health check that failed.
+fn health_check_connection() -> &'static Mutex<Option<DbConnection>> {
+    HEALTH_CHECK_CONNECTION.get_or_init(|| Mutex::new(None))
+}
+
+/// Check whether the database is reachable with a cheap `SELECT 1`.
+///
+/// This doesn't use the pool, since the pool being exhausted by slow requests doesn't mean that
+/// the database is down. It has its own connection instead, which gets reconnected if a check
+/// fails or gets cancelled.
+pub async fn database_is_reachable() -> bool {
+    let mut slot = health_check_connection().lock().await;
+    let mut conn = match slot.take() {
+        Some(conn) => conn,
+        None => match establish_connection().await {
+            Ok(conn) => conn,
+            Err(_) => return false,
+        },
+    };
+
+    let reachable = diesel::sql_query("SELECT 1")
+        .execute(&mut conn)
+        .await
+        .is_ok();
+    if reachable {
+        *slot = Some(conn);
+    }
+    reachable
 }
@@ -1,8 +1,8 @@
 //! This module contains models for interacting with the DB.
 
-use crate::db::schema::{completions, tests, users};
-use chrono::naive::NaiveDate;
-use diesel::{Associations, Insertable, Queryable, Selectable};
+use crate::db::schema::{audit_log, completions, sessions, tests, tombstones, users};
+use chrono::{naive::NaiveDate, DateTime, Utc};
+use diesel::{Associations, Identifiable, Insertable, Queryable, Selectable};
 use test_tracker_shared::User as SharedUser;
 
 /// Query a user from `users`.
@@ -16,6 +16,12 @@ pub struct User {
 
     /// The hashed password of the user, hashed with Argon2id.
     pub hashed_password: String,
+
+    /// Whether the user can manage other users' accounts.
+    pub is_admin: bool,
+
+    /// When the user created their account.
+    pub created_at: DateTime<Utc>,
 }
 
 impl From<User> for SharedUser {
@@ -29,15 +35,22 @@ impl From<User> for SharedUser {
 #[derive(Clone, Debug, PartialEq, Insertable)]
 #[diesel(table_name = users)]
 pub struct NewUser {
+    /// The ID of the user, which is a UUIDv4 generated by the server. See
+    /// [`parse_user_id`](crate::db::parse_user_id).
+    pub id: String,
+
     /// The username of the user.
     pub username: String,
 
     /// The hashed password of the user, hashed with Argon2id.
     pub hashed_password: String,
+
+    /// When the user created their account.
+    pub created_at: DateTime<Utc>,
 }
 
 /// Query a test from `tests`.
-#[derive(Clone, Debug, PartialEq, Queryable, Selectable, Associations)]
+#[derive(Clone, Debug, PartialEq, Queryable, Selectable, Identifiable, Associations)]
 #[diesel(belongs_to(User))]
 pub struct Test {
     /// Unique ID.
@@ -69,10 +82,48 @@ pub struct Test {
 
     /// Any extra comments.
     pub comments: Option<String>,
+
+    /// When this test or any of its completions last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Insert a test into `tests`.
+#[derive(Clone, Debug, PartialEq, Insertable)]
+#[diesel(table_name = tests)]
+pub struct NewTest {
+    /// The subject of the test: maths, English, science, etc.
+    pub subject: String,
+
+    /// The topic of the test: statistics, Shakespeare, organic chemistry, etc.
+    pub topic: Option<String>,
+
+    /// The date or ID of the test: Monday 3 June 2019, Mock Set 1, etc.
+    pub date_or_id: String,
+
+    /// The qualification_level of the test: GCSE, A Level, etc.
+    pub qualification_level: Option<String>,
+
+    /// The exam board for the test: Edexcel, AQA, OCR, etc.
+    pub exam_board: Option<String>,
+
+    /// The ID of the user that owns this paper.
+    pub user_id: String,
+
+    /// A link to the paper.
+    pub paper_link: Option<String>,
+
+    /// A link to the mark scheme.
+    pub mark_scheme_link: Option<String>,
+
+    /// Any extra comments.
+    pub comments: Option<String>,
+
+    /// When this test or any of its completions last changed.
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Query a completion from `completions`.
-#[derive(Clone, Debug, PartialEq, Queryable, Selectable, Associations)]
+#[derive(Clone, Debug, PartialEq, Queryable, Selectable, Identifiable, Associations)]
 #[diesel(belongs_to(Test))]
 pub struct Completion {
     /// Unique ID.
@@ -93,3 +144,114 @@ pub struct Completion {
     /// The ID of the test that this completion belongs to.
     pub test_id: i32,
 }
+
+/// Insert a completion into `completions`.
+#[derive(Clone, Debug, PartialEq, Insertable)]
+#[diesel(table_name = completions)]
+pub struct NewCompletion {
+    /// The mark that was actually achieved.
+    pub achieved_mark: i32,
+
+    /// The total marks available.
+    pub total_marks: i32,
+
+    /// The date of the completion.
+    pub date: Option<NaiveDate>,
+
+    /// Any extra comments.
+    pub comments: Option<String>,
+
+    /// The ID of the test that this completion belongs to.
+    pub test_id: i32,
+}
+
+/// Query a session from `sessions`.
+#[derive(Clone, Debug, PartialEq, Queryable, Selectable, Associations)]
+#[diesel(belongs_to(User))]
+pub struct Session {
+    /// The random token that the client sends to prove who it is.
+    pub token: String,
+
+    /// The ID of the user that this session authenticates.
+    pub user_id: String,
+
+    /// Whether this session uses the longer "remember me" length.
+    pub remember_me: bool,
+
+    /// When this session lapses, unless activity extends it first.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Insert a session into `sessions`.
+#[derive(Clone, Debug, PartialEq, Insertable)]
+#[diesel(table_name = sessions)]
+pub struct NewSession {
+    /// The random token that the client sends to prove who it is.
+    pub token: String,
+
+    /// The ID of the user that this session authenticates.
+    pub user_id: String,
+
+    /// Whether this session uses the longer "remember me" length.
+    pub remember_me: bool,
+
+    /// When this session lapses, unless activity extends it first.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Query an entry from `audit_log`.
+#[derive(Clone, Debug, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLogEntry {
+    /// Unique ID.
+    pub id: i32,
+
+    /// The ID of the user that this happened to, if known.
+    pub user_id: Option<String>,
+
+    /// What happened. See [`AuditEvent`](test_tracker_shared::AuditEvent).
+    pub event: String,
+
+    /// The address that the request came from, if known.
+    pub remote_addr: Option<String>,
+
+    /// When it happened.
+    pub created_at: DateTime<Utc>,
+
+    /// Any extra details about the event.
+    pub details: serde_json::Value,
+}
+
+/// Insert an entry into `audit_log`.
+#[derive(Clone, Debug, PartialEq, Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLogEntry {
+    /// The ID of the user that this happened to, if known.
+    pub user_id: Option<String>,
+
+    /// What happened. See [`AuditEvent`](test_tracker_shared::AuditEvent).
+    pub event: String,
+
+    /// The address that the request came from, if known.
+    pub remote_addr: Option<String>,
+
+    /// When it happened.
+    pub created_at: DateTime<Utc>,
+
+    /// Any extra details about the event.
+    pub details: serde_json::Value,
+}
+
+/// Insert a tombstone into `tombstones`, which records that a test was deleted.
+#[derive(Clone, Debug, PartialEq, Insertable)]
+#[diesel(table_name = tombstones)]
+pub struct NewTombstone {
+    /// The ID of the user that owned the test.
+    pub user_id: String,
+
+    /// The ID of the deleted test.
+    pub test_id: i32,
+
+    /// When the test was deleted.
+    pub deleted_at: DateTime<Utc>,
+}
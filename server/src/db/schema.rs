@@ -1,5 +1,16 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_log (id) {
+        id -> Int4,
+        user_id -> Nullable<Text>,
+        event -> Text,
+        remote_addr -> Nullable<Text>,
+        created_at -> Timestamptz,
+        details -> Jsonb,
+    }
+}
+
 diesel::table! {
     completions (id) {
         id -> Int4,
@@ -11,6 +22,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    sessions (token) {
+        token -> Text,
+        user_id -> Text,
+        remember_me -> Bool,
+        expires_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     tests (id) {
         id -> Int4,
@@ -23,6 +43,16 @@ diesel::table! {
         mark_scheme_link -> Nullable<Text>,
         comments -> Nullable<Text>,
         user_id -> Text,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    tombstones (id) {
+        id -> Int4,
+        user_id -> Text,
+        test_id -> Int4,
+        deleted_at -> Timestamptz,
     }
 }
 
@@ -31,14 +61,22 @@ diesel::table! {
         id -> Text,
         username -> Text,
         hashed_password -> Text,
+        is_admin -> Bool,
+        created_at -> Timestamptz,
     }
 }
 
+diesel::joinable!(audit_log -> users (user_id));
 diesel::joinable!(completions -> tests (test_id));
+diesel::joinable!(sessions -> users (user_id));
 diesel::joinable!(tests -> users (user_id));
+diesel::joinable!(tombstones -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_log,
     completions,
+    sessions,
     tests,
+    tombstones,
     users,
 );
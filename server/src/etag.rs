@@ -0,0 +1,49 @@
+//! This module handles `ETag`s, so that a client asking for the same data again can get a
+//! `304 Not Modified` with no body instead of downloading it all over again.
+
+use axum::http::{header::IF_NONE_MATCH, HeaderMap};
+use test_tracker_shared::{ServerToClientMsg, TestsSync};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Get the `ETag` for a response message, if it's the kind of message that can be cached.
+///
+/// The hash is over a canonical RON serialization of the data, rather than the response body, so
+/// that it doesn't depend on the format that the client asked for, and so that it ignores
+/// [`synced_at`](TestsSync::synced_at), which is different every time.
+pub fn of(msg: &ServerToClientMsg) -> Option<String> {
+    match msg {
+        ServerToClientMsg::TestsAndCompletionsForUser(Ok(TestsSync {
+            full,
+            changed,
+            deleted,
+            synced_at: _,
+            order,
+        })) => {
+            let canonical = ron::to_string(&(full, changed, deleted, order))
+                .expect("Serializing a TestsSync should never fail");
+            Some(format!("\"{:016x}\"", xxh3_64(canonical.as_bytes())))
+        }
+        _ => None,
+    }
+}
+
+/// Get the value of the `If-None-Match` header of a request, if it has one.
+pub fn if_none_match(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Does the given `If-None-Match` header value match the given `ETag`? The header can be a list of
+/// `ETag`s or `*`, and uses weak comparison, so `W/` prefixes are ignored.
+pub fn matches(if_none_match: &str, etag: &str) -> bool {
+    /// Strip the weakness indicator from an `ETag`.
+    fn strip_weak(etag: &str) -> &str {
+        etag.strip_prefix("W/").unwrap_or(etag)
+    }
+
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || strip_weak(candidate) == strip_weak(etag))
+}
@@ -0,0 +1,188 @@
+//! This module exports all of a user's completions as CSV, with one row per completion joined with
+//! the metadata of its test.
+//!
+//! The rows are streamed from the database and encoded one at a time into chunks of about
+//! [`CHUNK_SIZE`] bytes, so that a big export never has to fit in memory all at once.
+
+use crate::db::{
+    schema::{completions, tests},
+    DbConnection,
+};
+use chrono::NaiveDate;
+use diesel::{prelude::*, result::Error as DbError};
+use diesel_async::RunQueryDsl;
+use futures_util::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::instrument;
+
+/// The names of the columns in the CSV, which match the fields of [`ExportRow`]. These are shared
+/// with the client, which can build the same CSV from the tests that it already has.
+pub use test_tracker_shared::export::CSV_HEADERS;
+
+/// How many bytes of CSV to collect before sending them on as one chunk. A chunk can be bigger than
+/// this by up to one row.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An error that could occur partway through an export.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// The rows couldn't be read from the database.
+    #[error("database error: {0}")]
+    Db(#[from] DbError),
+
+    /// A row couldn't be encoded as CSV.
+    #[error("unable to encode CSV: {0}")]
+    Csv(#[from] csv::Error),
+}
+
+/// A single completion along with the metadata of its test, as a row of the CSV.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportRow {
+    /// The subject of the test: maths, English, science, etc.
+    pub subject: String,
+
+    /// The topic of the test: statistics, Shakespeare, organic chemistry, etc.
+    pub topic: Option<String>,
+
+    /// The date or ID of the test: Monday 3 June 2019, Mock Set 1, etc.
+    pub date_or_id: String,
+
+    /// The exam board for the test: Edexcel, AQA, OCR, etc.
+    pub exam_board: Option<String>,
+
+    /// The mark that was actually achieved.
+    pub achieved_mark: i32,
+
+    /// The total marks available.
+    pub total_marks: i32,
+
+    /// The achieved mark as a percentage of the total marks, or nothing if the total is 0.
+    pub percentage: Option<f64>,
+
+    /// The date of the completion.
+    pub date: Option<NaiveDate>,
+
+    /// Any extra comments on the completion.
+    pub comments: Option<String>,
+}
+
+/// The columns that we select to build an [`ExportRow`].
+type ExportColumns = (
+    String,
+    Option<String>,
+    String,
+    Option<String>,
+    i32,
+    i32,
+    Option<NaiveDate>,
+    Option<String>,
+);
+
+impl From<ExportColumns> for ExportRow {
+    fn from(value: ExportColumns) -> Self {
+        let (subject, topic, date_or_id, exam_board, achieved_mark, total_marks, date, comments) =
+            value;
+
+        Self {
+            subject,
+            topic,
+            date_or_id,
+            exam_board,
+            achieved_mark,
+            total_marks,
+            percentage: (total_marks != 0)
+                .then(|| 100. * f64::from(achieved_mark) / f64::from(total_marks)),
+            date,
+            comments,
+        }
+    }
+}
+
+/// Stream every completion that the given user owns, sorted by subject and test.
+#[instrument(skip(conn))]
+pub async fn stream_rows<'conn>(
+    conn: &'conn mut DbConnection,
+    user_id: &str,
+) -> Result<impl Stream<Item = Result<ExportRow, DbError>> + Send + 'conn, DbError> {
+    let stream = tests::table
+        .inner_join(completions::table)
+        .filter(tests::user_id.eq(user_id.to_string()))
+        .order_by((tests::subject, tests::date_or_id, completions::id))
+        .select((
+            tests::subject,
+            tests::topic,
+            tests::date_or_id,
+            tests::exam_board,
+            completions::achieved_mark,
+            completions::total_marks,
+            completions::date,
+            completions::comments,
+        ))
+        .load_stream::<ExportColumns>(conn)
+        .await?;
+
+    Ok(stream.map(|row| row.map(ExportRow::from)))
+}
+
+/// Encode the header row of the CSV.
+pub fn encode_headers() -> Result<Vec<u8>, csv::Error> {
+    encode(|writer| writer.write_record(CSV_HEADERS))
+}
+
+/// Encode a single row of the CSV, quoting any fields that need it, like comments with commas or
+/// newlines in them.
+pub fn encode_row(row: &ExportRow) -> Result<Vec<u8>, csv::Error> {
+    encode(|writer| writer.serialize(row))
+}
+
+/// Encode some CSV into bytes with the given function.
+fn encode(
+    write: impl FnOnce(&mut csv::Writer<Vec<u8>>) -> Result<(), csv::Error>,
+) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    write(&mut writer)?;
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
+
+/// Stream the whole CSV export for the given user, starting with the header row, in chunks of
+/// about [`CHUNK_SIZE`] bytes.
+///
+/// Only one chunk is built at a time, so memory use doesn't depend on how many completions the
+/// user has, as long as the consumer doesn't hold on to the chunks. The stream ends after the first
+/// error.
+pub async fn stream_csv<'conn>(
+    conn: &'conn mut DbConnection,
+    user_id: &str,
+) -> Result<impl Stream<Item = Result<Vec<u8>, ExportError>> + Send + 'conn, ExportError> {
+    let header = encode_headers()?;
+    let rows = Box::pin(stream_rows(conn, user_id).await?);
+
+    // The state is the rows and the chunk being built, which is `None` once the stream is over
+    Ok(stream::unfold(
+        (rows, Some(header)),
+        |(mut rows, chunk)| async move {
+            let mut chunk = chunk?;
+            loop {
+                match rows.next().await {
+                    Some(Ok(row)) => match encode_row(&row) {
+                        Ok(bytes) => chunk.extend(bytes),
+                        Err(e) => return Some((Err(e.into()), (rows, None))),
+                    },
+                    Some(Err(e)) => return Some((Err(e.into()), (rows, None))),
+                    None if chunk.is_empty() => return None,
+                    None => return Some((Ok(chunk), (rows, None))),
+                }
+
+                if chunk.len() >= CHUNK_SIZE {
+                    let next = Vec::with_capacity(CHUNK_SIZE);
+                    return Some((Ok(chunk), (rows, Some(next))));
+                }
+            }
+        },
+    ))
+}
@@ -0,0 +1,93 @@
+//! This module handles the serialization formats that the server understands. The client uses
+//! RON, but JSON is much easier to write by hand when scripting against the server.
+
+use axum::{
+    http::{header::CONTENT_TYPE, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use ron::Options;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// The `.expect()` error message for serializing a [`ServerToClientMsg`].
+///
+/// [`ServerToClientMsg`]: test_tracker_shared::ServerToClientMsg
+const EXPECT_SERIALIZE_MSG: &str = "Serializing a ServerToClientMsg should never fail";
+
+/// How deeply a request body can nest. No message nests more than a few levels, so this is just a
+/// guard against bodies designed to overflow the stack.
+pub const MAX_NESTING: usize = 32;
+
+/// An error that could occur when deserializing a request body.
+#[derive(Debug, Error)]
+pub enum DeserializeError {
+    /// The body wasn't valid RON for the expected type.
+    #[error("invalid RON: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+
+    /// The body wasn't valid JSON for the expected type.
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A serialization format for request and response bodies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// [RON](https://github.com/ron-rs/ron), which the client uses.
+    Ron,
+
+    /// JSON, which is used if the request has `Content-Type: application/json`.
+    Json,
+}
+
+impl Format {
+    /// Get the format of a request from its `Content-Type` header, defaulting to RON.
+    pub fn of(headers: &HeaderMap) -> Self {
+        let is_json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"));
+
+        if is_json {
+            Self::Json
+        } else {
+            Self::Ron
+        }
+    }
+
+    /// The value of the `Content-Type` header for this format.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Ron => "application/ron",
+            Self::Json => "application/json",
+        }
+    }
+
+    /// Deserialize a value from a string in this format.
+    ///
+    /// Both formats refuse to nest deeper than [`MAX_NESTING`], so a body like `[[[[...]]]]` is an
+    /// error rather than a stack overflow.
+    pub fn deserialize<T: DeserializeOwned>(self, body: &str) -> Result<T, DeserializeError> {
+        Ok(match self {
+            Self::Ron => Options::default()
+                .with_recursion_limit(MAX_NESTING)
+                .from_str(body)?,
+            // serde_json has its own limit of 128, which is plenty
+            Self::Json => serde_json::from_str(body)?,
+        })
+    }
+
+    /// Serialize a value to a string in this format.
+    pub fn serialize<T: Serialize>(self, value: &T) -> String {
+        match self {
+            Self::Ron => ron::to_string(value).expect(EXPECT_SERIALIZE_MSG),
+            Self::Json => serde_json::to_string(value).expect(EXPECT_SERIALIZE_MSG),
+        }
+    }
+
+    /// Serialize a value into a response with the matching `Content-Type` header.
+    pub fn response<T: Serialize>(self, value: &T) -> Response {
+        ([(CONTENT_TYPE, self.content_type())], self.serialize(value)).into_response()
+    }
+}
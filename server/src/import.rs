@@ -0,0 +1,218 @@
+//! This module imports tests and completions from CSV, in the same format as the export. See
+//! [`crate::export`].
+//!
+//! Consecutive rows with the same test details are grouped into one test with several completions,
+//! which is exactly how the export lays them out.
+
+use crate::{
+    db::DbConnection,
+    export::{ExportRow, CSV_HEADERS},
+    tests_and_completions::insert_test_and_completions,
+};
+use csv::{ErrorKind, Position, StringRecord};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection};
+use test_tracker_shared::{
+    import::check_row, CompletionData, Error, ImportRowError, ImportSummary, TestData,
+};
+use tracing::{instrument, warn};
+
+/// A test parsed from the CSV along with its completions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedTest {
+    /// The line that the first row of this test starts on.
+    pub line: u64,
+
+    /// The details of the test.
+    pub test: TestData,
+
+    /// The completions of the test, one per row.
+    pub completions: Vec<CompletionData>,
+}
+
+/// Describe an error from reading or deserializing a row, naming the column if we can.
+fn describe(error: &csv::Error) -> String {
+    match error.kind() {
+        ErrorKind::Deserialize { err, .. } => {
+            match err.field().and_then(|i| CSV_HEADERS.get(i as usize)) {
+                Some(column) => format!("invalid {column}: {}", err.kind()),
+                None => err.kind().to_string(),
+            }
+        }
+        _ => error.to_string(),
+    }
+}
+
+/// Turn a row into the test and completion that it describes, checking for required fields and
+/// valid marks.
+fn split_row(row: ExportRow) -> Result<(TestData, CompletionData), String> {
+    let ExportRow {
+        subject,
+        topic,
+        date_or_id,
+        exam_board,
+        achieved_mark,
+        total_marks,
+        percentage: _,
+        date,
+        comments,
+    } = row;
+
+    check_row(
+        TestData {
+            subject,
+            topic,
+            date_or_id,
+            qualification_level: None,
+            exam_board,
+            paper_link: None,
+            mark_scheme_link: None,
+            comments: None,
+        },
+        CompletionData {
+            achieved_mark,
+            total_marks,
+            date,
+            comments,
+        },
+    )
+}
+
+/// Parse a CSV into tests and their completions, along with the problems with any invalid rows.
+/// Invalid rows are skipped, so they don't split up the rows around them.
+pub fn parse_csv(csv: &str) -> (Vec<ParsedTest>, Vec<ImportRowError>) {
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let mut tests: Vec<ParsedTest> = Vec::new();
+    let mut errors = Vec::new();
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            errors.push(ImportRowError {
+                line: 1,
+                message: describe(&e),
+            });
+            return (tests, errors);
+        }
+    };
+
+    let mut record = StringRecord::new();
+    loop {
+        let result = reader.read_record(&mut record).and_then(|more| {
+            more.then(|| record.deserialize::<ExportRow>(Some(&headers)))
+                .transpose()
+        });
+        let line = record.position().map_or(0, Position::line);
+
+        let row = match result {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(e) => {
+                errors.push(ImportRowError {
+                    line: e.position().map_or(line, Position::line),
+                    message: describe(&e),
+                });
+                continue;
+            }
+        };
+
+        match split_row(row) {
+            Ok((test, completion)) => match tests.last_mut() {
+                Some(last) if last.test == test => last.completions.push(completion),
+                _ => tests.push(ParsedTest {
+                    line,
+                    test,
+                    completions: vec![completion],
+                }),
+            },
+            Err(message) => errors.push(ImportRowError { line, message }),
+        }
+    }
+
+    (tests, errors)
+}
+
+/// Import tests and completions from the given CSV for the given user.
+///
+/// If `strict` is false, then every valid test is imported in its own transaction, and problems
+/// with the other rows are reported in the summary. If `strict` is true, then nothing is imported
+/// if any row is invalid, and everything is imported in one transaction that is rolled back if
+/// any of it fails.
+#[instrument(skip(conn, csv))]
+pub async fn import_csv(
+    conn: &mut DbConnection,
+    user_id: &str,
+    csv: &str,
+    strict: bool,
+) -> Result<ImportSummary, Error> {
+    let (tests, mut errors) = parse_csv(csv);
+
+    if strict {
+        if !errors.is_empty() {
+            warn!(count = errors.len(), "Invalid rows in strict import");
+            return Ok(ImportSummary {
+                tests_imported: 0,
+                completions_imported: 0,
+                errors,
+            });
+        }
+
+        let tests_imported = tests.len();
+        let completions_imported = tests.iter().map(|test| test.completions.len()).sum();
+        conn.transaction::<_, Error, _>(|conn| {
+            async move {
+                for ParsedTest {
+                    test, completions, ..
+                } in tests
+                {
+                    insert_test_and_completions(conn, user_id, test, completions).await?;
+                }
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+        return Ok(ImportSummary {
+            tests_imported,
+            completions_imported,
+            errors,
+        });
+    }
+
+    let mut tests_imported = 0;
+    let mut completions_imported = 0;
+    for ParsedTest {
+        line,
+        test,
+        completions,
+    } in tests
+    {
+        let count = completions.len();
+        let result = conn
+            .transaction::<_, Error, _>(|conn| {
+                insert_test_and_completions(conn, user_id, test, completions).scope_boxed()
+            })
+            .await;
+
+        match result {
+            Ok(_) => {
+                tests_imported += 1;
+                completions_imported += count;
+            }
+            Err(e) => {
+                warn!(?line, ?e, "Unable to import test");
+                errors.push(ImportRowError {
+                    line,
+                    message: format!("unable to save this test: {e}"),
+                });
+            }
+        }
+    }
+    errors.sort_by_key(|error| error.line);
+
+    Ok(ImportSummary {
+        tests_imported,
+        completions_imported,
+        errors,
+    })
+}
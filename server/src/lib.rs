@@ -0,0 +1,29 @@
+//! This is the library half of the TestTracker server. It holds everything that talks to the
+//! database, and the [`router`](routes::router) that serves it over HTTP, so that it can be tested
+//! against a real database without a real listener. The binary only reads the config, listens, and
+//! shuts down.
+//!
+//! Every function that touches the database takes a `&mut DbConnection`, so that the caller
+//! decides where the connection comes from and several steps can share one connection.
+
+pub mod admin;
+pub mod api;
+pub mod audit;
+pub mod backup;
+pub mod cleanup;
+pub mod config;
+pub mod db;
+pub mod etag;
+pub mod export;
+pub mod format;
+pub mod import;
+pub mod limits;
+pub mod logging;
+pub mod messages;
+pub mod passwords;
+pub mod push;
+pub mod routes;
+pub mod seed;
+pub mod sessions;
+pub mod statistics;
+pub mod tests_and_completions;
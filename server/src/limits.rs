@@ -0,0 +1,71 @@
+//! This module limits how many requests the server handles at once, so that a burst of requests
+//! can't spawn an unbounded number of tasks all wanting a database connection, and how long the
+//! server spends on any one request.
+//!
+//! Both limits are middleware on the API routes. The monitoring routes are exempt, so that we can
+//! still see what's going on while the server is saturated.
+
+use crate::{config::config, format::Format};
+use axum::{
+    extract::Request,
+    http::{header::RETRY_AFTER, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::{Arc, OnceLock};
+use test_tracker_shared::{Error as SharedError, ServerToClientMsg};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::warn;
+
+/// The semaphore with one permit per request that can be handled at once.
+static PERMITS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Get the global semaphore, creating it with the size from the config if necessary.
+fn permits() -> &'static Arc<Semaphore> {
+    PERMITS.get_or_init(|| Arc::new(Semaphore::new(config().max_concurrent_requests)))
+}
+
+/// Try to get a permit to handle a request. The request counts as in flight until the permit is
+/// dropped. If the server is saturated, this returns `None` immediately rather than waiting.
+fn try_acquire() -> Option<OwnedSemaphorePermit> {
+    Arc::clone(permits()).try_acquire_owned().ok()
+}
+
+/// The number of requests that are currently in flight.
+pub fn in_flight() -> usize {
+    config().max_concurrent_requests - permits().available_permits()
+}
+
+/// Middleware that responds with `503 Service Unavailable` straight away if the server is already
+/// handling as many requests as it's allowed to.
+pub async fn limit_concurrency(req: Request, next: Next) -> Response {
+    let Some(_permit) = try_acquire() else {
+        warn!("Too many requests in flight; rejecting request");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, "1")],
+            Format::of(req.headers()).response(&ServerToClientMsg::Error(SharedError::Overloaded)),
+        )
+            .into_response();
+    };
+
+    next.run(req).await
+}
+
+/// Middleware that gives up on a request with `503 Service Unavailable` if handling it takes
+/// longer than the request timeout in the config.
+pub async fn timeout(req: Request, next: Next) -> Response {
+    let format = Format::of(req.headers());
+
+    match tokio::time::timeout(config().request_timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!("Request timed out");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format.response(&ServerToClientMsg::Error(SharedError::Timeout)),
+            )
+                .into_response()
+        }
+    }
+}
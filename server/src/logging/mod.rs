@@ -0,0 +1,142 @@
+//! This module sets up logging to stdout and to a `server.log` file, which is rotated by size (see
+//! [`rotation`]).
+//!
+//! Each destination has its own filter, in the same syntax as `RUST_LOG`, so the file can keep
+//! `debug` logs while the console stays at `info`. The console uses `$RUST_LOG` and the file uses
+//! `$RUST_LOG_FILE`, which defaults to the same as the console. Both filters can be changed while
+//! the server is running with [`set_filters`].
+
+pub mod rotation;
+
+use self::rotation::{RotatingFile, RotationPolicy};
+use std::{env, io, sync::OnceLock};
+use test_tracker_shared::Error as SharedError;
+use thiserror::Error;
+use tracing::info;
+use tracing_subscriber::{
+    filter::{EnvFilter, ParseError},
+    fmt,
+    prelude::*,
+    reload, Registry,
+};
+
+/// The filter to use if `$RUST_LOG` isn't set.
+pub const DEFAULT_FILTER: &str = "info";
+
+/// The handles for swapping the filters at runtime, set by [`init`].
+static HANDLES: OnceLock<FilterHandles> = OnceLock::new();
+
+/// The handles for swapping each of the filters at runtime.
+#[derive(Debug)]
+struct FilterHandles {
+    /// The handle for the stdout filter.
+    stdout: reload::Handle<EnvFilter, Registry>,
+
+    /// The handle for the log file filter.
+    file: reload::Handle<EnvFilter, Registry>,
+}
+
+/// An error that could occur when setting up or changing the logging.
+#[derive(Debug, Error)]
+pub enum LoggingError {
+    /// The filter couldn't be parsed.
+    #[error("invalid log filter {directives:?}: {source}")]
+    InvalidFilter {
+        /// The filter that we couldn't parse.
+        directives: String,
+
+        /// Why we couldn't parse it.
+        source: ParseError,
+    },
+
+    /// The log file couldn't be opened.
+    #[error("unable to open the log file: {0}")]
+    OpenLogFile(#[from] io::Error),
+
+    /// The global subscriber couldn't be set, probably because one has already been set.
+    #[error("unable to set the global subscriber: {0}")]
+    SetGlobalDefault(#[from] tracing_subscriber::util::TryInitError),
+
+    /// The filter couldn't be swapped.
+    #[error("unable to reload the log filter: {0}")]
+    Reload(#[from] reload::Error),
+
+    /// [`set_filters`] was called before [`init`].
+    #[error("logging hasn't been set up yet")]
+    NotInitialised,
+}
+
+impl From<LoggingError> for SharedError {
+    fn from(value: LoggingError) -> Self {
+        match value {
+            LoggingError::InvalidFilter { .. } => SharedError::Validation {
+                field: "log filter".to_string(),
+                reason: value.to_string(),
+            },
+            err => SharedError::Internal(err.to_string()),
+        }
+    }
+}
+
+/// Parse a filter in the same syntax as `RUST_LOG`, like `info` or
+/// `warn,test_tracker_server=debug`. Unlike `RUST_LOG` itself, an invalid directive is an error
+/// rather than being silently ignored.
+pub fn parse_filter(directives: &str) -> Result<EnvFilter, LoggingError> {
+    EnvFilter::builder()
+        .parse(directives)
+        .map_err(|source| LoggingError::InvalidFilter {
+            directives: directives.to_string(),
+            source,
+        })
+}
+
+/// Set up the global tracing subscriber to log to stdout and to `server.log` files in the given
+/// directory, rotated with the given policy, with the filters from `$RUST_LOG` and `$RUST_LOG_FILE`.
+pub fn init(log_path: &str, rotation: RotationPolicy) -> Result<(), LoggingError> {
+    let stdout_directives = env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let file_directives = env::var("RUST_LOG_FILE").unwrap_or_else(|_| stdout_directives.clone());
+
+    let (stdout_filter, stdout) = reload::Layer::new(parse_filter(&stdout_directives)?);
+    let (file_filter, file) = reload::Layer::new(parse_filter(&file_directives)?);
+
+    let appender = RotatingFile::new(log_path, "server.log", rotation)?;
+
+    tracing_subscriber::registry()
+        .with(vec![
+            fmt::Layer::new()
+                .with_writer(appender)
+                .with_ansi(false)
+                .with_filter(file_filter)
+                .boxed(),
+            fmt::Layer::new()
+                .with_writer(std::io::stdout)
+                .with_ansi(true)
+                .with_filter(stdout_filter)
+                .boxed(),
+        ])
+        .try_init()?;
+
+    // This can only fail if we've already set up logging, which `try_init` would have caught
+    let _ = HANDLES.set(FilterHandles { stdout, file });
+    Ok(())
+}
+
+/// Change the filters for stdout and the log file while the server is running. A `None` leaves
+/// that filter as it is.
+///
+/// Both filters are parsed before either is changed, so an invalid filter changes nothing.
+pub fn set_filters(stdout: Option<&str>, file: Option<&str>) -> Result<(), LoggingError> {
+    let handles = HANDLES.get().ok_or(LoggingError::NotInitialised)?;
+    let stdout_filter = stdout.map(parse_filter).transpose()?;
+    let file_filter = file.map(parse_filter).transpose()?;
+
+    if let Some(filter) = stdout_filter {
+        handles.stdout.reload(filter)?;
+    }
+    if let Some(filter) = file_filter {
+        handles.file.reload(filter)?;
+    }
+
+    info!(?stdout, ?file, "Changed log filters");
+    Ok(())
+}
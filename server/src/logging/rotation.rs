@@ -0,0 +1,164 @@
+//! This module rotates the log file by size, so that a single busy day can't produce a huge file
+//! and old logs don't accumulate forever.
+//!
+//! The current file is always `<name>`, like `server.log`. When writing to it would take it past
+//! the size limit, it gets renamed to `<name>.1`, every older file moves up by one, and anything
+//! past the retention limit is deleted, so `<name>.1` is always the newest old file.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// When to rotate the log file and how many old files to keep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// The largest that a log file can get, in bytes. A single log line bigger than this still
+    /// gets written, in a file on its own.
+    pub max_file_size: u64,
+
+    /// How many old files to keep, not counting the current one.
+    pub max_files: usize,
+}
+
+/// The file that we're currently writing to, and everything we need to rotate it.
+#[derive(Debug)]
+struct State {
+    /// The directory that the log files are in.
+    directory: PathBuf,
+
+    /// The name of the current log file, like `server.log`.
+    name: String,
+
+    /// When to rotate.
+    policy: RotationPolicy,
+
+    /// The current log file.
+    file: File,
+
+    /// How many bytes are in the current log file.
+    size: u64,
+}
+
+impl State {
+    /// The path of the log file with the given index, where 0 is the current file and higher
+    /// numbers are older.
+    fn path(&self, index: usize) -> PathBuf {
+        path(&self.directory, &self.name, index)
+    }
+
+    /// Move every file up by one, deleting the oldest if there are too many, and start a new
+    /// current file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let oldest = self.path(self.policy.max_files);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+
+        for index in (0..self.policy.max_files).rev() {
+            let from = self.path(index);
+            if from.exists() {
+                fs::rename(from, self.path(index + 1))?;
+            }
+        }
+
+        // With no old files to keep, the current file was the oldest, so it's gone now
+        self.file = open(&self.path(0))?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// The path of the log file with the given index. See [`State::path`].
+fn path(directory: &Path, name: &str, index: usize) -> PathBuf {
+    if index == 0 {
+        directory.join(name)
+    } else {
+        directory.join(format!("{name}.{index}"))
+    }
+}
+
+/// Open a log file for appending, creating it if necessary.
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// A log file that rotates itself by size. This is a [`MakeWriter`], so it can be given straight
+/// to a `tracing_subscriber` layer.
+#[derive(Debug)]
+pub struct RotatingFile {
+    /// The current state, shared between all the writers.
+    state: Mutex<State>,
+}
+
+impl RotatingFile {
+    /// Open the log file with the given name in the given directory, creating the directory and
+    /// the file if necessary. If the file already exists, then we append to it.
+    pub fn new(
+        directory: impl AsRef<Path>,
+        name: &str,
+        policy: RotationPolicy,
+    ) -> io::Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+
+        let file = open(&path(&directory, name, 0))?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            state: Mutex::new(State {
+                directory,
+                name: name.to_string(),
+                policy,
+                file,
+                size,
+            }),
+        })
+    }
+
+    /// Lock the state. A panic while holding the lock can't leave the state inconsistent in any
+    /// way that matters, so we ignore poisoning rather than losing logs.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A handle for writing to a [`RotatingFile`].
+#[derive(Debug)]
+pub struct RotatingFileWriter<'a> {
+    /// The file to write to.
+    file: &'a RotatingFile,
+}
+
+impl Write for RotatingFileWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.file.lock();
+
+        if state.size > 0 && state.size + buf.len() as u64 > state.policy.max_file_size {
+            state.rotate()?;
+        }
+
+        let written = state.file.write(buf)?;
+        state.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.lock().file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFile {
+    type Writer = RotatingFileWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RotatingFileWriter { file: self }
+    }
+}
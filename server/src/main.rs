@@ -1,141 +1,144 @@
 //! This is the server for TestTracker, which uses a PostgreSQL database to store all the data.
 
 use self::{
-    passwords::{add_new_user, validate_user},
-    tests_and_completions::get_all_tests_and_completions_for_user,
+    cli::Command,
+    tls::{wait_for_reload, TlsFiles},
 };
-use color_eyre::Result;
-use test_tracker_shared::{ClientToServerMsg, ServerToClientMsg};
-use tiny_http::{Header, Request, Response};
-use tracing::{debug, error, info, instrument};
-use tracing_unwrap::ResultExt;
-
-pub(crate) mod db;
-mod passwords;
-mod tests_and_completions;
-
-/// The `.expect()` error message for serializing a [`ServerToClientMsg`].
-const EXPECT_SERIALIZE_MSG: &str = "Serializing a ServerToClientMsg should never fail";
-
-/// Create a header that will allow the client to function properly without CORS getting in the way.
-fn no_cors_header() -> Header {
-    Header {
-        field: "Access-Control-Allow-Origin"
-            .parse()
-            .expect("This &'static str should parse just fine"),
-        value: "*"
-            .parse()
-            .expect("This &'static str should parse just fine"),
-    }
-}
+use axum_server::Handle;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use std::net::SocketAddr;
+use test_tracker_server::{cleanup, config, db, logging, routes};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::oneshot,
+    task::JoinSet,
+};
+use tracing::{debug, info, instrument, warn};
 
-/// Handle a single HTTP request.
-#[instrument(skip_all, fields(addr = ?req.remote_addr()))]
-async fn handle_request(mut req: Request) -> Result<()> {
-    info!("Received a new request");
-
-    let mut body = String::new();
-    req.as_reader().read_to_string(&mut body)?;
-    let msg: ClientToServerMsg = ron::from_str(&body)?;
-
-    match msg {
-        ClientToServerMsg::Authenticate { username, password } => {
-            info!(?username, ?password, "Authenticating");
-            let validation_result = validate_user(&username, &password).map_err(|e| e.into());
-            debug!(?validation_result);
-
-            req.respond(
-                Response::from_string(
-                    ron::to_string(&ServerToClientMsg::AuthenticationResponse(
-                        validation_result,
-                    ))
-                    .expect(EXPECT_SERIALIZE_MSG),
-                )
-                .with_header(no_cors_header()),
-            )?;
-        }
-        ClientToServerMsg::CreateUser { username, password } => {
-            info!(?username, ?password, "Creating new user");
-            let add_new_user_result = add_new_user(&username, &password).map_err(|e| e.into());
-            debug!(?add_new_user_result);
-
-            req.respond(
-                Response::from_string(
-                    ron::to_string(&ServerToClientMsg::AuthenticationResponse(
-                        add_new_user_result,
-                    ))
-                    .expect(EXPECT_SERIALIZE_MSG),
-                )
-                .with_header(no_cors_header()),
-            )?;
-        }
-        ClientToServerMsg::GetTestsAndCompletions { user_id } => {
-            info!(?user_id, "Getting tests and completions");
-            let tests_and_completions_result =
-                get_all_tests_and_completions_for_user(&user_id).map_err(|e| e.into());
-            debug!(?tests_and_completions_result);
-
-            req.respond(
-                Response::from_string(
-                    ron::to_string(&ServerToClientMsg::TestsAndCompletionsForUser(
-                        tests_and_completions_result,
-                    ))
-                    .expect(EXPECT_SERIALIZE_MSG),
-                )
-                .with_header(no_cors_header()),
-            )?;
-        }
-    };
+mod cli;
+mod tls;
 
-    Ok(())
-}
+/// Create and run the server until it receives `SIGTERM` or `SIGINT`, and then shut down
+/// gracefully, giving requests in flight up to the request timeout to finish.
+#[tokio::main]
+#[instrument]
+async fn main() -> Result<()> {
+    let cli = cli::parse();
+    let config = config::init()?;
+    logging::init(env!("SERVER_LOG_PATH"), config.log_rotation)?;
+    debug!(?config);
 
-/// Setup the global tracing subscriber to send log messages to stdout, and a `server.log` file
-/// (rotated daily).
-fn setup_global_tracing_subscriber() {
-    use tracing_subscriber::{fmt::Layer, prelude::*};
+    info!("Initialising server");
 
-    let appender = tracing_appender::rolling::daily(env!("SERVER_LOG_PATH"), "server.log");
+    db::wait_for_database(config.database_startup_timeout)
+        .await
+        .wrap_err("Unable to connect to the database at startup")?;
 
-    let subscriber = tracing_subscriber::registry()
-        .with(Layer::new().with_writer(appender).with_ansi(false))
-        .with(Layer::new().with_writer(std::io::stdout).with_ansi(true));
+    #[cfg(feature = "sqlite")]
+    tokio::task::spawn_blocking(db::run_sqlite_migrations)
+        .await?
+        .map_err(|e| eyre!("Unable to migrate the SQLite database: {e}"))?;
 
-    tracing::subscriber::set_global_default(subscriber)
-        .expect("Setting the global default for tracing should be okay");
-}
+    if cli.seed_demo_data {
+        cli::seed().await?;
+    }
 
-/// Create and run the server indefinitely.
-#[tokio::main]
-#[instrument]
-async fn main() -> Result<()> {
-    setup_global_tracing_subscriber();
-
-    info!(port = env!("PORT"), "Initialising server");
-
-    let server = match tiny_http::Server::https(
-        concat!("localhost:", env!("PORT")),
-        tiny_http::SslConfig {
-            certificate: include_bytes!(env!("SERVER_SSL_CERT_PATH")).to_vec(),
-            private_key: include_bytes!(env!("SERVER_SSL_KEY_PATH")).to_vec(),
-        },
-    ) {
-        Ok(server) => server,
-        Err(error) => {
-            error!(
-                ?error,
-                "Error creating HTTPS server; defaulting to HTTP server"
-            );
-            tiny_http::Server::http(concat!("localhost:", env!("PORT")))
-                .expect_or_log("Unable to create HTTP server")
+    match cli.command() {
+        Command::Serve => {}
+        Command::PromoteAdmin { username } => return cli::promote_admin(&username).await,
+        Command::User { command } => return cli::user(&command).await,
+        Command::NormalizeTests => return cli::normalize_tests().await,
+        Command::Backup { out } => return cli::backup(&out).await,
+        Command::Restore { input } => return cli::restore(&input).await,
+    }
+
+    let rustls_config = match &config.tls {
+        Some(tls_config) => Some(
+            TlsFiles::load(tls_config)
+                .wrap_err("Unable to load TLS files at startup")?
+                .into_rustls_config()
+                .await?,
+        ),
+        None => {
+            warn!("No TLS certificate configured; using plain HTTP");
+            None
         }
     };
 
+    if config.bind_addresses.is_empty() {
+        return Err(eyre!("No bind addresses configured"));
+    }
+
+    let app = routes::router();
+    let mut servers = JoinSet::new();
+    let mut handles = Vec::new();
+
+    for &addr in &config.bind_addresses {
+        let handle = Handle::new();
+        let service = app
+            .clone()
+            .into_make_service_with_connect_info::<SocketAddr>();
+
+        match &rustls_config {
+            Some(rustls_config) => servers.spawn(
+                axum_server::bind_rustls(addr, rustls_config.clone())
+                    .handle(handle.clone())
+                    .serve(service),
+            ),
+            None => servers.spawn(
+                axum_server::bind(addr)
+                    .handle(handle.clone())
+                    .serve(service),
+            ),
+        };
+        info!(%addr, "Listening");
+        handles.push(handle);
+    }
+
+    let (stop_cleanup, cleanup_shutdown) = oneshot::channel();
+    let cleanup_task = tokio::spawn(cleanup::run(config.cleanup_interval, cleanup_shutdown));
+
     info!("Server initialised");
 
-    for req in server.incoming_requests() {
-        tokio::spawn(handle_request(req));
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    loop {
+        tokio::select! {
+            files = wait_for_reload(&mut hangup, config.tls.as_ref()) => {
+                if let Some(rustls_config) = &rustls_config {
+                    tls::reload(rustls_config, files).await;
+                }
+            }
+            _ = terminate.recv() => break,
+            result = tokio::signal::ctrl_c() => {
+                result?;
+                break;
+            }
+            Some(result) = servers.join_next() => {
+                result?.wrap_err("A listener failed")?;
+                return Err(eyre!("A listener stopped unexpectedly"));
+            }
+        }
     }
 
+    info!("Shutting down gracefully");
+    for handle in &handles {
+        handle.graceful_shutdown(Some(config.request_timeout));
+    }
+    while let Some(result) = servers.join_next().await {
+        result?.wrap_err("A listener failed while shutting down")?;
+    }
+
+    // Sending only fails if the task has already stopped, which awaiting it will report
+    let _ = stop_cleanup.send(());
+    cleanup_task
+        .await
+        .wrap_err("The cleanup task failed while shutting down")?;
+
+    info!("Server shut down");
     Ok(())
 }
@@ -0,0 +1,142 @@
+//! This module parses messages from the bodies of requests and checks that they were sent to the
+//! right route, before anything touches the database.
+//!
+//! The bodies come straight from the network, so parsing must turn any input at all into either a
+//! message or a clean [`BadRequest`](Error::BadRequest), and never panic. The exception is a message
+//! that this server doesn't know about, which gives
+//! [`UnsupportedMessage`](Error::UnsupportedMessage) instead, so that a newer client can tell the
+//! user that the server needs upgrading.
+
+use crate::format::{DeserializeError, Format};
+use test_tracker_shared::{ClientToServerMsg, Error};
+use tracing::warn;
+
+/// A route that takes a [`ClientToServerMsg`] in the body of a `POST` request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    /// `POST /api/auth`, for logging in.
+    Auth,
+
+    /// `POST /api/users`, for creating accounts.
+    Users,
+
+    /// `POST /api/tests`, for anything to do with tests and completions.
+    Tests,
+
+    /// `POST /api/statistics`, for statistics about completions.
+    Statistics,
+
+    /// `POST /api/audit`, for a user's login history.
+    Audit,
+
+    /// `POST /api/admin`, for admin-only capabilities.
+    Admin,
+
+    /// `POST /api/import`, for importing tests and completions from CSV.
+    Import,
+}
+
+impl Route {
+    /// Every route, for iterating over.
+    pub const ALL: [Self; 7] = [
+        Self::Auth,
+        Self::Users,
+        Self::Tests,
+        Self::Statistics,
+        Self::Audit,
+        Self::Admin,
+        Self::Import,
+    ];
+
+    /// Does the given message belong on this route?
+    pub fn accepts(self, msg: &ClientToServerMsg) -> bool {
+        match msg {
+            ClientToServerMsg::Authenticate { .. } => self == Self::Auth,
+            ClientToServerMsg::CreateUser { .. }
+            | ClientToServerMsg::ChangePassword { .. }
+            | ClientToServerMsg::DeleteAccount { .. } => self == Self::Users,
+            ClientToServerMsg::GetTestsAndCompletions { .. }
+            | ClientToServerMsg::GetTest { .. }
+            | ClientToServerMsg::AddTest { .. }
+            | ClientToServerMsg::UpdateTest { .. }
+            | ClientToServerMsg::DeleteTest { .. }
+            | ClientToServerMsg::AddCompletion { .. }
+            | ClientToServerMsg::UpdateCompletion { .. }
+            | ClientToServerMsg::DeleteCompletion { .. } => self == Self::Tests,
+            ClientToServerMsg::GetStatistics { .. } => self == Self::Statistics,
+            ClientToServerMsg::GetAuditLog { .. } => self == Self::Audit,
+            ClientToServerMsg::ListUsers { .. }
+            | ClientToServerMsg::AdminResetPassword { .. }
+            | ClientToServerMsg::SetLogFilter { .. } => self == Self::Admin,
+            ClientToServerMsg::ImportCsv { .. } => self == Self::Import,
+        }
+    }
+}
+
+/// The longest name of an unknown message that we report back. Anything longer is probably just
+/// garbage, so it gets a plain [`Error::BadRequest`].
+const MAX_VARIANT_NAME_LENGTH: usize = 64;
+
+/// Find the name of the message that the body claims to be, without checking anything else.
+fn variant_name(format: Format, body: &str) -> Option<String> {
+    let name = match format {
+        Format::Ron => body
+            .trim_start()
+            .chars()
+            .take_while(|&c| c.is_ascii_alphanumeric() || c == '_')
+            .collect(),
+        Format::Json => match serde_json::from_str(body).ok()? {
+            serde_json::Value::String(name) => name,
+            serde_json::Value::Object(map) if map.len() == 1 => map.into_iter().next()?.0,
+            _ => return None,
+        },
+    };
+
+    (!name.is_empty() && name.len() <= MAX_VARIANT_NAME_LENGTH).then_some(name)
+}
+
+/// If the body couldn't be deserialized because it's a message that this server doesn't know
+/// about, then get the name of that message.
+fn unknown_variant(format: Format, body: &str, error: &DeserializeError) -> Option<String> {
+    let name = variant_name(format, body)?;
+    let is_unknown = match error {
+        DeserializeError::Ron(error) => matches!(
+            &error.code,
+            ron::Error::NoSuchEnumVariant { found, .. } if *found == name
+        ),
+        DeserializeError::Json(error) => error
+            .to_string()
+            .starts_with(&format!("unknown variant `{name}`")),
+    };
+
+    is_unknown.then_some(name)
+}
+
+/// Parse a message in the given format from the body of a request on the given route.
+///
+/// If the body is a message that this server doesn't know about, then this returns
+/// [`Error::UnsupportedMessage`]. If it isn't a valid message for some other reason, or the message
+/// doesn't belong on this route, then this returns [`Error::BadRequest`] saying why.
+pub fn parse_message(format: Format, route: Route, body: &str) -> Result<ClientToServerMsg, Error> {
+    let msg: ClientToServerMsg = format.deserialize(body).map_err(|e| {
+        if let Some(variant) = unknown_variant(format, body, &e) {
+            warn!(?variant, "Unsupported message");
+            return Error::UnsupportedMessage {
+                variant,
+                server_version: env!("CARGO_PKG_VERSION").to_string(),
+            };
+        }
+
+        warn!(?e, "Unable to deserialize message");
+        Error::BadRequest(e.to_string())
+    })?;
+
+    if !route.accepts(&msg) {
+        warn!(?route, "Message sent to the wrong route");
+        return Err(Error::BadRequest(format!(
+            "this message doesn't belong on {route:?}"
+        )));
+    }
+
+    Ok(msg)
+}
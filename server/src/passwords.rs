@@ -1,34 +1,123 @@
 //! This module handles hashing and verifying passwords for the database.
+//!
+//! Argon2 is deliberately slow, so all the hashing happens on blocking threads to avoid stalling
+//! the async runtime.
 
-use crate::db::{
-    establish_connection,
-    models::{NewUser, User as DbUser},
+use crate::{
+    config::{config, Peppers},
+    db::{
+        models::{NewUser, User as DbUser},
+        DbConnection, UniqueConstraint,
+    },
 };
 use argon2::{
     password_hash::{
         Error as HashingError, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
     },
-    Argon2,
+    Algorithm, Argon2, Params, Version,
+};
+use chrono::Utc;
+use diesel::result::Error as DbError;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use test_tracker_shared::{
+    policy::{
+        check_password, check_username, normalize_username, PasswordPolicyError,
+        UsernamePolicyError,
+    },
+    Error as SharedError, User as SharedUser,
 };
-use diesel::{result::Error as DbError, RunQueryDsl};
-use test_tracker_shared::{Error as SharedError, User as SharedUser};
 use thiserror::Error;
+use tracing::{info, instrument, warn};
 use tracing_unwrap::ResultExt;
+use uuid::Uuid;
+
+/// How many times we try to insert a new user with a fresh random ID before giving up.
+const MAX_USER_ID_ATTEMPTS: u32 = 3;
 
-/// Hash and salt a password for the first time. Use [`validate_user`] to validate a username and
-/// password against the DB.
-fn hash_and_salt_password(password: &str) -> Result<String, HashingError> {
-    let salt: [u8; 16] = rand::random();
-    let argon2 = Argon2::default();
-
-    Ok(argon2
-        .hash_password(
-            password.as_bytes(),
-            SaltString::encode_b64(&salt)
-                .expect_or_log("We should be able to encode any 16 bytes as B64")
-                .as_salt(),
-        )?
-        .to_string())
+/// Get an Argon2id hasher using the parameters from the config and the given pepper, if any.
+fn argon2(pepper: Option<&[u8]>) -> Result<Argon2<'_>, HashingError> {
+    let params = config().argon2_params.clone();
+    Ok(match pepper {
+        Some(pepper) => {
+            Argon2::new_with_secret(pepper, Algorithm::Argon2id, Version::V0x13, params)?
+        }
+        None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+    })
+}
+
+/// Was the given hash produced with an algorithm or parameters that are weaker than the ones in
+/// the current config? If so, the password should be rehashed.
+fn needs_rehash(hash: &PasswordHash) -> bool {
+    if hash.algorithm != Algorithm::Argon2id.ident() {
+        return true;
+    }
+
+    match Params::try_from(hash) {
+        Ok(params) => {
+            let current = &config().argon2_params;
+            params.m_cost() < current.m_cost()
+                || params.t_cost() < current.t_cost()
+                || params.p_cost() < current.p_cost()
+        }
+        Err(_) => true,
+    }
+}
+
+/// Hash and salt a password for the first time, with the current pepper if there is one. Use
+/// [`validate_user`] to validate a username and password against the DB.
+pub async fn hash_and_salt_password(
+    password: String,
+    peppers: &Peppers,
+) -> Result<String, HashingError> {
+    let pepper = peppers.current().map(<[u8]>::to_vec);
+    tokio::task::spawn_blocking(move || {
+        let salt: [u8; 16] = rand::random();
+        let argon2 = argon2(pepper.as_deref())?;
+
+        Ok(argon2
+            .hash_password(
+                password.as_bytes(),
+                SaltString::encode_b64(&salt)
+                    .expect_or_log("We should be able to encode any 16 bytes as B64")
+                    .as_salt(),
+            )?
+            .to_string())
+    })
+    .await
+    .expect_or_log("Hashing a password shouldn't panic")
+}
+
+/// Verify a password against a stored hash, and return whether the hash should be upgraded, either
+/// because of [`needs_rehash`] or because it wasn't made with the current pepper.
+///
+/// We don't know which pepper a hash was made with, so we try the current pepper, then any older
+/// ones, and then no pepper at all, for hashes from before peppering was turned on. A wrong
+/// password costs one hash per attempt, so keep the list of old peppers short.
+pub async fn verify_password(
+    password: String,
+    hashed_password: String,
+    peppers: &Peppers,
+) -> Result<bool, HashingError> {
+    let peppers: Vec<Vec<u8>> = peppers.all().map(<[u8]>::to_vec).collect();
+    tokio::task::spawn_blocking(move || {
+        let parsed_hash = PasswordHash::new(&hashed_password)?;
+        let candidates = peppers
+            .iter()
+            .map(|pepper| Some(pepper.as_slice()))
+            .chain([None]);
+
+        for (attempt, pepper) in candidates.enumerate() {
+            match argon2(pepper)?.verify_password(password.as_bytes(), &parsed_hash) {
+                Ok(()) => return Ok(attempt > 0 || needs_rehash(&parsed_hash)),
+                Err(HashingError::Password) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(HashingError::Password)
+    })
+    .await
+    .expect_or_log("Verifying a password shouldn't panic")
 }
 
 /// An error that could occur when adding a new user to the database.
@@ -41,6 +130,18 @@ pub enum NewUserError {
     /// An error occured when trying to hash the password.
     #[error("unable to hash password: {0:?}")]
     HashingError(HashingError),
+
+    /// The username doesn't satisfy the username policy.
+    #[error("invalid username: {0}")]
+    InvalidUsername(#[from] UsernamePolicyError),
+
+    /// The password doesn't satisfy the password policy.
+    #[error("weak password: {0}")]
+    WeakPassword(#[from] PasswordPolicyError),
+
+    /// Someone already has this username.
+    #[error("username already taken")]
+    UsernameTaken,
 }
 
 // We have to impl this by hand because `thiserror` needs its #[from] types to impl std `Error`, but
@@ -56,43 +157,235 @@ impl From<NewUserError> for SharedError {
         match value {
             NewUserError::DbError(err) => SharedError::DatabaseError(err.into()),
             NewUserError::HashingError(err) => err.into(),
+            NewUserError::InvalidUsername(err) => err.into(),
+            NewUserError::WeakPassword(err) => err.into(),
+            NewUserError::UsernameTaken => SharedError::UsernameTaken,
         }
     }
 }
 
 /// Validate a username and password. An error means the password is invalid.
-pub fn validate_user(username: &str, password: &str) -> Result<SharedUser, NewUserError> {
+///
+/// The username is normalized in the same way as when the account was created. Accounts created
+/// before normalization was introduced were only lowercased, so we fall back to that if there's no
+/// exact match.
+///
+/// If the stored hash was made with weaker parameters than the current config, then it gets
+/// upgraded in place now that we know the plaintext password.
+#[instrument(skip(conn, password))]
+pub async fn validate_user(
+    conn: &mut DbConnection,
+    username: &str,
+    password: &str,
+) -> Result<SharedUser, NewUserError> {
     use crate::db::schema::users::dsl;
-    use diesel::prelude::*;
+    use diesel::{ExpressionMethods, QueryDsl};
 
-    let conn = &mut establish_connection();
+    let normalized = normalize_username(username);
     let DbUser {
         id,
         username,
         hashed_password,
-    } = dsl::users
-        .filter(dsl::username.eq(username.to_lowercase()))
-        .first::<DbUser>(conn)?;
+        ..
+    } = match dsl::users
+        .filter(dsl::username.eq(&normalized))
+        .first::<DbUser>(conn)
+        .await
+    {
+        Err(DbError::NotFound) if normalized != username.to_lowercase() => {
+            dsl::users
+                .filter(dsl::username.eq(username.to_lowercase()))
+                .first::<DbUser>(conn)
+                .await?
+        }
+        result => result?,
+    };
 
-    let parsed_hash = PasswordHash::new(&hashed_password)?;
-    Argon2::default().verify_password(password.as_bytes(), &parsed_hash)?;
+    if verify_password(password.to_string(), hashed_password, &config().peppers).await? {
+        // Failing to upgrade the hash shouldn't stop the user logging in, since the old hash is
+        // still perfectly valid
+        let result = match hash_and_salt_password(password.to_string(), &config().peppers).await {
+            Ok(new_hash) => diesel::update(dsl::users.find(&id))
+                .set(dsl::hashed_password.eq(new_hash))
+                .execute(conn)
+                .await
+                .map_err(NewUserError::from),
+            Err(error) => Err(error.into()),
+        };
+
+        match result {
+            Ok(_) => info!(
+                ?id,
+                "Upgraded password hash to the current Argon2 parameters"
+            ),
+            Err(error) => warn!(?id, ?error, "Unable to upgrade password hash"),
+        }
+    }
 
     Ok(SharedUser { id, username })
 }
 
 /// Add a new user to the database and return it.
-pub fn add_new_user(username: &str, password: &str) -> Result<SharedUser, NewUserError> {
+///
+/// The password is checked against the password policy before hashing, so that weak passwords
+/// are rejected cheaply.
+pub async fn add_new_user(
+    conn: &mut DbConnection,
+    username: &str,
+    password: &str,
+) -> Result<SharedUser, NewUserError> {
     use crate::db::schema::users;
 
-    let hashed_password = hash_and_salt_password(password)?;
-    let conn = &mut establish_connection();
+    let username = normalize_username(username);
+    check_username(&username)?;
+    check_password(&username, password)?;
+
+    let hashed_password = hash_and_salt_password(password.to_string(), &config().peppers).await?;
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+
+        let result = diesel::insert_into(users::table)
+            .values(&NewUser {
+                id: Uuid::new_v4().to_string(),
+                username: username.clone(),
+                hashed_password: hashed_password.clone(),
+                created_at: Utc::now(),
+            })
+            .get_result::<DbUser>(conn)
+            .await;
+
+        match result
+            .as_ref()
+            .err()
+            .and_then(UniqueConstraint::violated_by)
+        {
+            // A UUIDv4 collision is astronomically unlikely, but it's cheap to handle
+            Some(UniqueConstraint::UserId) if attempt < MAX_USER_ID_ATTEMPTS => {
+                warn!(?attempt, "User ID collision; retrying with a new ID");
+            }
+            Some(UniqueConstraint::Username) => return Err(NewUserError::UsernameTaken),
+            _ => return Ok(result?.into()),
+        }
+    }
+}
+
+/// Change the given user's password after checking that they know their current one. A wrong
+/// current password gives a [`HashingError::Password`], and the new password must satisfy the
+/// password policy.
+///
+/// This doesn't end any sessions, so the caller is responsible for that.
+#[instrument(skip(conn, current_password, new_password))]
+pub async fn change_password(
+    conn: &mut DbConnection,
+    user_id: &str,
+    current_password: &str,
+    new_password: &str,
+) -> Result<(), NewUserError> {
+    use crate::db::schema::users::dsl;
+    use diesel::QueryDsl;
+
+    let (username, hashed_password): (String, String) = dsl::users
+        .find(user_id)
+        .select((dsl::username, dsl::hashed_password))
+        .first(conn)
+        .await?;
+    // Check the new password first, since it's cheap, and verifying the current one isn't
+    check_password(&username, new_password)?;
+    // Whether the old hash needs upgrading doesn't matter, since it's about to be replaced
+    verify_password(
+        current_password.to_string(),
+        hashed_password,
+        &config().peppers,
+    )
+    .await?;
+
+    set_password(conn, user_id, new_password).await
+}
+
+/// Delete the given user after checking their password, along with all their tests, completions,
+/// tombstones, and sessions. Their audit log entries are kept, but detached from the account. A
+/// wrong password gives a [`HashingError::Password`].
+#[instrument(skip(conn, password))]
+pub async fn delete_account(
+    conn: &mut DbConnection,
+    user_id: &str,
+    password: &str,
+) -> Result<(), NewUserError> {
+    use crate::db::schema::{audit_log, completions, sessions, tests, tombstones, users};
+    use diesel::{ExpressionMethods, QueryDsl};
+
+    let hashed_password: String = users::table
+        .find(user_id)
+        .select(users::hashed_password)
+        .first(conn)
+        .await?;
+    verify_password(password.to_string(), hashed_password, &config().peppers).await?;
+
+    let user_id = user_id.to_string();
+    conn.transaction::<_, DbError, _>(|conn| {
+        async move {
+            let test_ids = tests::table
+                .filter(tests::user_id.eq(&user_id))
+                .select(tests::id);
+            diesel::delete(completions::table.filter(completions::test_id.eq_any(test_ids)))
+                .execute(conn)
+                .await?;
+            diesel::delete(tests::table.filter(tests::user_id.eq(&user_id)))
+                .execute(conn)
+                .await?;
+            diesel::delete(tombstones::table.filter(tombstones::user_id.eq(&user_id)))
+                .execute(conn)
+                .await?;
+            diesel::delete(sessions::table.filter(sessions::user_id.eq(&user_id)))
+                .execute(conn)
+                .await?;
+            diesel::update(audit_log::table.filter(audit_log::user_id.eq(&user_id)))
+                .set(audit_log::user_id.eq(None::<String>))
+                .execute(conn)
+                .await?;
+            diesel::delete(users::table.find(&user_id))
+                .execute(conn)
+                .await?;
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    info!("Account deleted");
+    Ok(())
+}
+
+/// Set a new password for the given user, checking it against the password policy first.
+///
+/// This doesn't check the old password or end any sessions, so the caller is responsible for
+/// making sure that the caller is allowed to do this.
+#[instrument(skip(conn, new_password))]
+pub async fn set_password(
+    conn: &mut DbConnection,
+    user_id: &str,
+    new_password: &str,
+) -> Result<(), NewUserError> {
+    use crate::db::schema::users::dsl;
+    use diesel::{ExpressionMethods, QueryDsl};
+
+    let username: String = dsl::users
+        .find(user_id)
+        .select(dsl::username)
+        .first(conn)
+        .await?;
+    check_password(&username, new_password)?;
 
-    let user: DbUser = diesel::insert_into(users::table)
-        .values(&NewUser {
-            username: username.to_lowercase(),
-            hashed_password,
-        })
-        .get_result(conn)?;
+    let hashed_password =
+        hash_and_salt_password(new_password.to_string(), &config().peppers).await?;
+    diesel::update(dsl::users.find(user_id))
+        .set(dsl::hashed_password.eq(hashed_password))
+        .execute(conn)
+        .await?;
 
-    Ok(user.into())
+    info!("Password changed");
+    Ok(())
 }
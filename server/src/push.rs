@@ -0,0 +1,231 @@
+//! This module pushes notifications to clients over WebSockets, so that an open client sees new
+//! data without having to refresh.
+//!
+//! A client connects to [`paths::WEBSOCKET`](test_tracker_shared::paths::WEBSOCKET) and sends its
+//! session token as the first message. From then on, whenever that user's data changes, the server
+//! sends [`ServerToClientMsg::DataChanged`] down the socket, and the client fetches the new data
+//! itself.
+//!
+//! The session is checked again before each notification and every [`RECHECK_INTERVAL`], and the
+//! socket is closed once the session has lapsed or been logged out.
+
+use crate::{
+    db,
+    sessions::{check_token, resolve_token},
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        ConnectInfo, WebSocketUpgrade,
+    },
+    response::Response,
+};
+use color_eyre::Result;
+use futures_util::{SinkExt, StreamExt};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Duration,
+};
+use test_tracker_shared::{Error as SharedError, ServerToClientMsg};
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument};
+use tracing_unwrap::ResultExt;
+
+/// How long a new socket has to send its session token before we give up on it.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often we check that an open socket's session is still valid, even if nothing has changed.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The senders for every open socket, keyed by user ID and then by subscription ID.
+type Registry = HashMap<String, HashMap<u64, mpsc::Sender<()>>>;
+
+/// The global registry of subscribers.
+static SUBSCRIBERS: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+/// The ID to give the next subscription.
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Get the global registry of subscribers.
+fn subscribers() -> &'static Mutex<Registry> {
+    SUBSCRIBERS.get_or_init(Default::default)
+}
+
+/// A subscription to changes in one user's data, which unsubscribes itself when dropped.
+#[derive(Debug)]
+struct Subscription {
+    /// The user whose data we're subscribed to.
+    user_id: String,
+
+    /// The unique ID of this subscription.
+    id: u64,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let mut subscribers = subscribers()
+            .lock()
+            .expect_or_log("The subscriber registry lock shouldn't be poisoned");
+
+        if let Some(user_subscribers) = subscribers.get_mut(&self.user_id) {
+            user_subscribers.remove(&self.id);
+            if user_subscribers.is_empty() {
+                subscribers.remove(&self.user_id);
+            }
+        }
+    }
+}
+
+/// Subscribe to changes in the given user's data. The receiver gets a message after each change.
+fn subscribe(user_id: &str) -> (Subscription, mpsc::Receiver<()>) {
+    // A capacity of 1 is enough, since one pending notification already means "refresh"
+    let (sender, receiver) = mpsc::channel(1);
+    let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed);
+
+    subscribers()
+        .lock()
+        .expect_or_log("The subscriber registry lock shouldn't be poisoned")
+        .entry(user_id.to_string())
+        .or_default()
+        .insert(id, sender);
+
+    (
+        Subscription {
+            user_id: user_id.to_string(),
+            id,
+        },
+        receiver,
+    )
+}
+
+/// Tell every open socket for the given user that their data has changed.
+pub fn data_changed(user_id: &str) {
+    let subscribers = subscribers()
+        .lock()
+        .expect_or_log("The subscriber registry lock shouldn't be poisoned");
+
+    if let Some(user_subscribers) = subscribers.get(user_id) {
+        debug!(
+            ?user_id,
+            count = user_subscribers.len(),
+            "Pushing data change"
+        );
+        for sender in user_subscribers.values() {
+            // If the channel is full, then there's already a notification waiting, and if it's
+            // closed, then the subscription is about to be dropped
+            let _ = sender.try_send(());
+        }
+    }
+}
+
+/// The number of open sockets across all users.
+pub fn subscriber_count() -> usize {
+    subscribers()
+        .lock()
+        .expect_or_log("The subscriber registry lock shouldn't be poisoned")
+        .values()
+        .map(HashMap::len)
+        .sum()
+}
+
+/// Check that the session with the given token is still valid, without extending it. If we can't
+/// reach the database to check, then we give the socket the benefit of the doubt.
+async fn session_is_valid(token: &str) -> bool {
+    let mut conn = match db::connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            debug!(?e, "Unable to check socket's session");
+            return true;
+        }
+    };
+
+    match check_token(&mut conn, token).await {
+        Ok(_) => true,
+        Err(SharedError::Unauthorized) => false,
+        Err(e) => {
+            debug!(?e, "Unable to check socket's session");
+            true
+        }
+    }
+}
+
+/// Upgrade a `GET` request on [`paths::WEBSOCKET`](test_tracker_shared::paths::WEBSOCKET) to a
+/// WebSocket.
+pub async fn upgrade(ws: WebSocketUpgrade, ConnectInfo(addr): ConnectInfo<SocketAddr>) -> Response {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_socket(socket, addr).await {
+            debug!(?e, %addr, "WebSocket closed with an error");
+        }
+    })
+}
+
+/// Authenticate a single socket and then push notifications to it until it closes.
+#[instrument(skip(socket))]
+async fn handle_socket(socket: WebSocket, addr: SocketAddr) -> Result<()> {
+    let (mut write, mut read) = socket.split();
+
+    let token = match tokio::time::timeout(AUTH_TIMEOUT, read.next()).await {
+        Ok(Some(Ok(Message::Text(token)))) => token,
+        _ => {
+            debug!("Socket didn't send a session token in time");
+            write.send(Message::Close(None)).await?;
+            return Ok(());
+        }
+    };
+
+    let token = token.trim();
+    let user_id = {
+        let mut conn = db::connection().await?;
+        match resolve_token(&mut conn, token).await {
+            Ok(user_id) => user_id,
+            Err(e) => {
+                debug!(?e, "Unable to authenticate socket");
+                write.send(Message::Close(None)).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let (_subscription, mut notifications) = subscribe(&user_id);
+    info!(?user_id, "Socket subscribed");
+
+    let notification = ron::to_string(&ServerToClientMsg::DataChanged {
+        user_id: user_id.clone(),
+    })?;
+    let mut recheck = tokio::time::interval_at(
+        tokio::time::Instant::now() + RECHECK_INTERVAL,
+        RECHECK_INTERVAL,
+    );
+
+    loop {
+        let still_valid = tokio::select! {
+            Some(()) = notifications.recv() => {
+                let valid = session_is_valid(token).await;
+                if valid {
+                    write.send(Message::Text(notification.clone())).await?;
+                }
+                valid
+            }
+            _ = recheck.tick() => session_is_valid(token).await,
+            msg = read.next() => match msg {
+                // Pings are answered automatically, and clients have nothing else to say
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => true,
+            },
+        };
+
+        if !still_valid {
+            info!("Socket's session has ended");
+            write.send(Message::Close(None)).await?;
+            break;
+        }
+    }
+
+    info!("Socket closed");
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! This module builds the router, which sends each request to the handler for its path and method,
+//! and wraps the handlers in middleware.
+//!
+//! Anything that isn't routed gets `404 Not Found`, and a known path with the wrong method gets
+//! `405 Method Not Allowed` with an `Allow` header.
+
+use crate::{api, limits, messages::Route, push};
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{
+        header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
+    middleware,
+    routing::{get, post, MethodRouter},
+    Router,
+};
+use std::{net::SocketAddr, time::Duration};
+use test_tracker_shared::paths;
+use tower::ServiceBuilder;
+use tower_http::{
+    cors::{Any, CorsLayer},
+    request_id::{MakeRequestId, PropagateRequestIdLayer, RequestId, SetRequestIdLayer},
+    trace::{DefaultOnRequest, TraceLayer},
+};
+use tracing::{info_span, Level};
+
+/// How long browsers may cache the response to a CORS preflight.
+const CORS_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// The handler for the given route, which reads a message from the body and responds to it.
+fn handler(route: Route) -> MethodRouter {
+    post(
+        move |ConnectInfo(addr): ConnectInfo<SocketAddr>, headers: HeaderMap, body: String| {
+            api::respond_to_message(route, addr, headers, body)
+        },
+    )
+}
+
+/// Generates the random ID for each request, so that its log lines can be correlated. The ID is
+/// sent back in the `X-Request-Id` header.
+#[derive(Clone, Copy, Debug, Default)]
+struct MakeRandomRequestId;
+
+impl MakeRequestId for MakeRandomRequestId {
+    fn make_request_id<B>(&mut self, _request: &axum::http::Request<B>) -> Option<RequestId> {
+        HeaderValue::from_str(&format!("{:016x}", rand::random::<u64>()))
+            .ok()
+            .map(RequestId::new)
+    }
+}
+
+/// Build the router for the whole server.
+///
+/// The API routes are subject to the concurrency limit and the request timeout, but the monitoring
+/// routes and the WebSocket aren't. Every response allows any origin, since the client is served
+/// from a different origin.
+pub fn router() -> Router {
+    let api = Router::new()
+        .route(paths::AUTH, handler(Route::Auth))
+        .route(paths::USERS, handler(Route::Users))
+        .route(paths::TESTS, handler(Route::Tests))
+        .route(paths::STATISTICS, handler(Route::Statistics))
+        .route(paths::AUDIT, handler(Route::Audit))
+        .route(paths::ADMIN, handler(Route::Admin))
+        .route(paths::IMPORT, handler(Route::Import))
+        .route(paths::EXPORT, get(api::respond_to_export))
+        .layer(
+            ServiceBuilder::new()
+                .layer(middleware::from_fn(limits::limit_concurrency))
+                .layer(middleware::from_fn(limits::timeout)),
+        );
+
+    Router::new()
+        .merge(api)
+        .route(paths::INFO, get(api::respond_to_info_page))
+        .route(paths::HEALTH, get(api::respond_to_health_check))
+        .route(paths::METRICS, get(api::respond_to_metrics))
+        .route(paths::WEBSOCKET, get(push::upgrade))
+        .fallback(|| async { (StatusCode::NOT_FOUND, "Not found") })
+        .layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::x_request_id(MakeRandomRequestId))
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(|req: &Request| {
+                            let request_id = req
+                                .headers()
+                                .get("x-request-id")
+                                .and_then(|id| id.to_str().ok())
+                                .unwrap_or_default();
+                            info_span!(
+                                "request",
+                                method = %req.method(),
+                                uri = %req.uri(),
+                                request_id,
+                            )
+                        })
+                        .on_request(DefaultOnRequest::new().level(Level::INFO)),
+                )
+                .layer(PropagateRequestIdLayer::x_request_id())
+                .layer(
+                    CorsLayer::new()
+                        .allow_origin(Any)
+                        .allow_methods([Method::GET, Method::POST])
+                        .allow_headers([CONTENT_TYPE, IF_NONE_MATCH])
+                        .expose_headers([ETAG])
+                        .max_age(CORS_MAX_AGE),
+                ),
+        )
+}
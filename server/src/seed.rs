@@ -0,0 +1,368 @@
+//! This module fills a development database with a demo user and a realistic spread of tests, so
+//! that there's something to look at when working on the client.
+//!
+//! Everything goes through the same functions that the server uses for real users, so seeding also
+//! exercises those. Seeding is idempotent: the demo user is reused if it already exists, and tests
+//! that it already has are skipped.
+
+use crate::{
+    db::{schema::users, DbConnection},
+    passwords::{add_new_user, NewUserError},
+    tests_and_completions::insert_test_and_completions,
+};
+use chrono::NaiveDate;
+use diesel::{prelude::*, result::Error as DbError};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use test_tracker_shared::{policy::normalize_username, CompletionData, Error, TestData};
+use thiserror::Error;
+use tracing::{debug, info, instrument};
+use tracing_unwrap::ResultExt;
+
+/// The username of the demo user.
+pub const DEMO_USERNAME: &str = "demo";
+
+/// The password of the demo user.
+pub const DEMO_PASSWORD: &str = "demo password";
+
+/// A demo test, as `(subject, topic, date_or_id, qualification_level, exam_board, total_marks,
+/// completions)`, where each completion is `(achieved_mark, date, comments)`.
+type DemoTest = (
+    &'static str,
+    Option<&'static str>,
+    &'static str,
+    &'static str,
+    &'static str,
+    i32,
+    &'static [(i32, &'static str, Option<&'static str>)],
+);
+
+/// The tests that the demo user gets.
+const DEMO_TESTS: &[DemoTest] = &[
+    (
+        "Maths",
+        Some("Pure"),
+        "June 2019 Paper 1",
+        "A Level",
+        "Edexcel",
+        100,
+        &[
+            (54, "2024-01-08", Some("Ran out of time")),
+            (67, "2024-02-12", None),
+            (81, "2024-03-18", Some("Much better on integration")),
+        ],
+    ),
+    (
+        "Maths",
+        Some("Pure"),
+        "June 2019 Paper 2",
+        "A Level",
+        "Edexcel",
+        100,
+        &[(62, "2024-01-15", None), (74, "2024-03-25", None)],
+    ),
+    (
+        "Maths",
+        Some("Statistics"),
+        "June 2019 Paper 3",
+        "A Level",
+        "Edexcel",
+        100,
+        &[(71, "2024-01-22", Some("Hypothesis testing is fine now"))],
+    ),
+    (
+        "Maths",
+        Some("Pure"),
+        "June 2022 Paper 1",
+        "A Level",
+        "Edexcel",
+        100,
+        &[],
+    ),
+    (
+        "Further Maths",
+        Some("Core Pure"),
+        "June 2019 Paper 1",
+        "A Level",
+        "Edexcel",
+        75,
+        &[
+            (38, "2024-02-05", Some("Complex numbers")),
+            (51, "2024-04-01", None),
+        ],
+    ),
+    (
+        "Further Maths",
+        Some("Decision"),
+        "June 2019 Paper 3D",
+        "A Level",
+        "Edexcel",
+        75,
+        &[(60, "2024-02-19", None)],
+    ),
+    (
+        "Physics",
+        Some("Mechanics"),
+        "Mock Set 1",
+        "A Level",
+        "OCR",
+        100,
+        &[(48, "2023-11-06", None), (59, "2024-01-29", None)],
+    ),
+    (
+        "Physics",
+        Some("Fields"),
+        "Mock Set 2",
+        "A Level",
+        "OCR",
+        100,
+        &[(55, "2024-03-04", Some("Forgot the units twice"))],
+    ),
+    (
+        "Physics",
+        Some("Practical skills"),
+        "June 2019 Paper 3",
+        "A Level",
+        "OCR",
+        70,
+        &[],
+    ),
+    (
+        "Chemistry",
+        Some("Organic"),
+        "June 2018 Paper 2",
+        "A Level",
+        "AQA",
+        105,
+        &[
+            (49, "2023-10-16", None),
+            (63, "2023-12-11", None),
+            (72, "2024-02-26", None),
+            (88, "2024-04-15", Some("Mechanisms finally clicked")),
+        ],
+    ),
+    (
+        "Chemistry",
+        Some("Inorganic"),
+        "June 2018 Paper 1",
+        "A Level",
+        "AQA",
+        105,
+        &[(70, "2024-01-01", None)],
+    ),
+    (
+        "Biology",
+        Some("Genetics"),
+        "November 2020 Paper 1",
+        "GCSE",
+        "AQA",
+        100,
+        &[(82, "2022-03-14", None)],
+    ),
+    (
+        "Biology",
+        Some("Ecology"),
+        "November 2020 Paper 2",
+        "GCSE",
+        "AQA",
+        100,
+        &[(77, "2022-03-21", None), (85, "2022-04-25", None)],
+    ),
+    (
+        "English Literature",
+        Some("Shakespeare"),
+        "Mock Set 1",
+        "GCSE",
+        "AQA",
+        64,
+        &[(41, "2022-01-10", Some("Macbeth essay needs more quotes"))],
+    ),
+    (
+        "English Literature",
+        Some("Poetry"),
+        "Mock Set 2",
+        "GCSE",
+        "AQA",
+        64,
+        &[(48, "2022-02-07", None)],
+    ),
+    (
+        "History",
+        Some("Cold War"),
+        "June 2019 Paper 2",
+        "GCSE",
+        "Edexcel",
+        80,
+        &[(52, "2022-02-28", None), (61, "2022-04-04", None)],
+    ),
+    (
+        "History",
+        Some("Medicine through time"),
+        "June 2019 Paper 1",
+        "GCSE",
+        "Edexcel",
+        52,
+        &[],
+    ),
+    (
+        "Computer Science",
+        Some("Algorithms"),
+        "June 2020 Paper 1",
+        "A Level",
+        "OCR",
+        140,
+        &[(97, "2024-03-11", None), (118, "2024-04-22", None)],
+    ),
+    (
+        "Computer Science",
+        Some("Theory"),
+        "June 2020 Paper 2",
+        "A Level",
+        "OCR",
+        140,
+        &[(0, "2024-03-12", Some("Abandoned after ten minutes"))],
+    ),
+    (
+        "French",
+        Some("Listening"),
+        "Mock Set 1",
+        "GCSE",
+        "AQA",
+        50,
+        &[(29, "2022-01-17", None), (36, "2022-03-07", None)],
+    ),
+];
+
+/// An error that could occur when seeding the demo data.
+#[derive(Debug, Error)]
+pub enum SeedError {
+    /// The database doesn't look like a development database, so we refuse to touch it.
+    #[error("refusing to seed {name:?}, which doesn't look like a development database")]
+    NotADevDatabase {
+        /// The name of the database.
+        name: String,
+    },
+
+    /// The demo user couldn't be created.
+    #[error("unable to create the demo user: {0}")]
+    User(#[from] NewUserError),
+
+    /// Some other database error occurred.
+    #[error("database error: {0}")]
+    Db(#[from] DbError),
+
+    /// A test couldn't be inserted.
+    #[error("unable to insert a demo test: {0}")]
+    Test(#[from] Error),
+}
+
+/// What seeding did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeedSummary {
+    /// The ID of the demo user.
+    pub user_id: String,
+
+    /// Whether the demo user was created, rather than already existing.
+    pub created_user: bool,
+
+    /// How many tests were inserted.
+    pub tests_inserted: usize,
+
+    /// How many tests the demo user already had.
+    pub tests_skipped: usize,
+}
+
+/// Check that the database with the given name looks like a development database, meaning that
+/// its name contains `dev` or `test`.
+///
+/// The name of the app itself doesn't count, since the production database is probably called
+/// `test_tracker`.
+pub fn check_database_name(name: &str) -> Result<(), SeedError> {
+    let lowercase = ["test_tracker", "test-tracker", "testtracker"]
+        .into_iter()
+        .fold(name.to_lowercase(), |name, app| name.replace(app, ""));
+    if lowercase.contains("dev") || lowercase.contains("test") {
+        Ok(())
+    } else {
+        Err(SeedError::NotADevDatabase {
+            name: name.to_string(),
+        })
+    }
+}
+
+/// Parse a date from [`DEMO_TESTS`].
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").expect_or_log("The demo dates should be valid")
+}
+
+/// Create the demo user if it doesn't exist yet, and give it every demo test that it doesn't
+/// already have.
+///
+/// This doesn't check the database name, so callers should use [`check_database_name`] first.
+#[instrument(skip(conn))]
+pub async fn seed_demo_data(conn: &mut DbConnection) -> Result<SeedSummary, SeedError> {
+    let existing: Option<String> = users::table
+        .filter(users::username.eq(normalize_username(DEMO_USERNAME)))
+        .select(users::id)
+        .first(conn)
+        .await
+        .optional()?;
+
+    let created_user = existing.is_none();
+    let user_id = match existing {
+        Some(user_id) => user_id,
+        None => add_new_user(conn, DEMO_USERNAME, DEMO_PASSWORD).await?.id,
+    };
+
+    let mut tests_inserted = 0;
+    let mut tests_skipped = 0;
+    for &(subject, topic, date_or_id, qualification_level, exam_board, total_marks, completions) in
+        DEMO_TESTS
+    {
+        let test = TestData {
+            subject: subject.to_string(),
+            topic: topic.map(ToString::to_string),
+            date_or_id: date_or_id.to_string(),
+            qualification_level: Some(qualification_level.to_string()),
+            exam_board: Some(exam_board.to_string()),
+            paper_link: None,
+            mark_scheme_link: None,
+            comments: None,
+        };
+        let completions = completions
+            .iter()
+            .map(|&(achieved_mark, completed_on, comments)| CompletionData {
+                achieved_mark,
+                total_marks,
+                date: Some(date(completed_on)),
+                comments: comments.map(ToString::to_string),
+            })
+            .collect();
+
+        // Each test gets its own transaction, so that skipping a duplicate doesn't abort the rest
+        let result = conn
+            .transaction::<_, Error, _>(|conn| {
+                insert_test_and_completions(conn, &user_id, test, completions).scope_boxed()
+            })
+            .await;
+
+        match result {
+            Ok(_) => tests_inserted += 1,
+            Err(Error::DuplicateTest { .. }) => {
+                debug!(?subject, ?date_or_id, "Demo test already exists");
+                tests_skipped += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    info!(
+        ?user_id,
+        created_user, tests_inserted, tests_skipped, "Seeded demo data"
+    );
+    Ok(SeedSummary {
+        user_id,
+        created_user,
+        tests_inserted,
+        tests_skipped,
+    })
+}
@@ -0,0 +1,198 @@
+//! This module handles session tokens, which the client uses to prove who it is after logging in.
+//!
+//! Sessions use a sliding window: every authenticated request pushes the expiry back by the full
+//! session length, so a session only lapses after a period of inactivity.
+
+use crate::{
+    config::config,
+    db::{
+        models::{NewSession, Session},
+        schema::sessions,
+        DbConnection,
+    },
+};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{prelude::*, result::Error as DbError};
+use diesel_async::RunQueryDsl;
+use rand::{distributions::Alphanumeric, Rng};
+use test_tracker_shared::{Error as SharedError, Session as SharedSession, User as SharedUser};
+use tracing::{debug, instrument, warn};
+
+/// The length of a session token in characters.
+const TOKEN_LENGTH: usize = 64;
+
+/// Generate a new random session token.
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// How long a session lasts without activity, depending on whether it's a "remember me" session.
+fn session_length(remember_me: bool) -> Duration {
+    if remember_me {
+        config().remember_me_session_length
+    } else {
+        config().session_length
+    }
+}
+
+/// Has a session with the given expiry lapsed at the given time?
+fn has_expired(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    expires_at <= now
+}
+
+/// Create a new session for the given user and return its token.
+#[instrument(skip(conn))]
+pub async fn create_session(
+    conn: &mut DbConnection,
+    user_id: &str,
+    remember_me: bool,
+) -> Result<String, SharedError> {
+    let token = generate_token();
+
+    diesel::insert_into(sessions::table)
+        .values(&NewSession {
+            token: token.clone(),
+            user_id: user_id.to_string(),
+            remember_me,
+            expires_at: Utc::now() + session_length(remember_me),
+        })
+        .execute(conn)
+        .await?;
+
+    Ok(token)
+}
+
+/// Resolve a session token to the ID of the user that it authenticates, extending the session's
+/// expiry in the process.
+///
+/// An unknown or lapsed token gives [`SharedError::Unauthorized`], and lapsed sessions are deleted.
+#[instrument(skip_all)]
+pub async fn resolve_token(conn: &mut DbConnection, token: &str) -> Result<String, SharedError> {
+    let now = Utc::now();
+
+    let session = match sessions::table.find(token).first::<Session>(conn).await {
+        Ok(session) => session,
+        Err(DbError::NotFound) => return Err(SharedError::Unauthorized),
+        Err(e) => return Err(e.into()),
+    };
+
+    if has_expired(session.expires_at, now) {
+        debug!(user_id = ?session.user_id, expires_at = ?session.expires_at, "Session has lapsed");
+        diesel::delete(sessions::table.find(token))
+            .execute(conn)
+            .await?;
+        return Err(SharedError::Unauthorized);
+    }
+
+    diesel::update(sessions::table.find(token))
+        .set(sessions::expires_at.eq(now + session_length(session.remember_me)))
+        .execute(conn)
+        .await?;
+
+    Ok(session.user_id)
+}
+
+/// Check that a session token is still valid, and return the ID of the user that it authenticates.
+/// Unlike [`resolve_token`], this doesn't extend the session, so it's fine to call in the
+/// background without keeping an idle session alive.
+///
+/// An unknown or lapsed token gives [`SharedError::Unauthorized`].
+#[instrument(skip_all)]
+pub async fn check_token(conn: &mut DbConnection, token: &str) -> Result<String, SharedError> {
+    let session = match sessions::table.find(token).first::<Session>(conn).await {
+        Ok(session) => session,
+        Err(DbError::NotFound) => return Err(SharedError::Unauthorized),
+        Err(e) => return Err(e.into()),
+    };
+
+    if has_expired(session.expires_at, Utc::now()) {
+        return Err(SharedError::Unauthorized);
+    }
+
+    Ok(session.user_id)
+}
+
+/// Create a new session for the given user, who has just proven who they are.
+pub async fn start_session(
+    conn: &mut DbConnection,
+    user: SharedUser,
+    remember_me: bool,
+) -> Result<SharedSession, SharedError> {
+    let token = create_session(conn, &user.id, remember_me).await?;
+    Ok(SharedSession { user, token })
+}
+
+/// End every session for the given user, so that they have to log in again everywhere. Returns
+/// how many sessions were ended.
+#[instrument(skip(conn))]
+pub async fn end_all_sessions(
+    conn: &mut DbConnection,
+    user_id: &str,
+) -> Result<usize, SharedError> {
+    Ok(
+        diesel::delete(sessions::table.filter(sessions::user_id.eq(user_id)))
+            .execute(conn)
+            .await?,
+    )
+}
+
+/// End every session for the given user except the one with the given token, so that they stay
+/// logged in here but have to log in again everywhere else. Returns how many sessions were ended.
+#[instrument(skip(conn, token))]
+pub async fn end_other_sessions(
+    conn: &mut DbConnection,
+    user_id: &str,
+    token: &str,
+) -> Result<usize, SharedError> {
+    Ok(diesel::delete(
+        sessions::table
+            .filter(sessions::user_id.eq(user_id))
+            .filter(sessions::token.ne(token)),
+    )
+    .execute(conn)
+    .await?)
+}
+
+/// Delete every session that had lapsed by the given time, and return how many there were.
+#[instrument(skip(conn))]
+pub async fn delete_expired_sessions(
+    conn: &mut DbConnection,
+    now: DateTime<Utc>,
+) -> Result<usize, DbError> {
+    diesel::delete(sessions::table.filter(sessions::expires_at.le(now)))
+        .execute(conn)
+        .await
+}
+
+/// Resolve the caller's identity from their session token and check that they are the given
+/// user, whose data is being requested or changed.
+///
+/// Every handler that touches a single user's data must go through this check rather than
+/// trusting a user ID sent by the client.
+#[instrument(skip(conn, token))]
+pub async fn authorize(
+    conn: &mut DbConnection,
+    token: &str,
+    user_id: &str,
+) -> Result<(), SharedError> {
+    let caller_id = resolve_token(conn, token).await?;
+    check_is_owner(&caller_id, user_id)
+}
+
+/// Check that the caller is the owner of the data that they're trying to access.
+fn check_is_owner(caller_id: &str, owner_id: &str) -> Result<(), SharedError> {
+    if caller_id == owner_id {
+        Ok(())
+    } else {
+        warn!(
+            ?caller_id,
+            ?owner_id,
+            "Caller tried to access another user's data"
+        );
+        Err(SharedError::Unauthorized)
+    }
+}
@@ -0,0 +1,66 @@
+//! This module computes statistics about a user's completions.
+//!
+//! Everything is aggregated by the database, so we never load the individual completions.
+
+use crate::db::{
+    schema::{completions, tests},
+    DbConnection,
+};
+use diesel::{
+    dsl::{self, count, sql},
+    expression::SqlLiteral,
+    prelude::*,
+    sql_types::Double,
+};
+use diesel_async::RunQueryDsl;
+use test_tracker_shared::{Error, SubjectStats};
+use tracing::{instrument, trace};
+
+/// The SQL for the score of a completion as a fraction of the total marks. Completions out of 0
+/// marks give `NULL`, which the aggregates ignore.
+///
+/// We cast with `CAST(... AS DOUBLE PRECISION)` rather than Postgres's `::float`, so that this
+/// also works on SQLite.
+pub(crate) const SCORE_SQL: &str = "CAST(completions.achieved_mark AS DOUBLE PRECISION) \
+     / CAST(NULLIF(completions.total_marks, 0) AS DOUBLE PRECISION)";
+
+/// The score of a completion as a fraction of the total marks, computed in SQL. See
+/// [`SCORE_SQL`].
+fn score() -> SqlLiteral<Double> {
+    sql::<Double>(SCORE_SQL)
+}
+
+/// For the given user, compute the statistics of their completions in each subject, sorted by
+/// subject. Subjects with no completions are left out.
+#[instrument(skip(conn))]
+pub async fn get_statistics_for_user(
+    conn: &mut DbConnection,
+    user_id: &str,
+) -> Result<Vec<SubjectStats>, Error> {
+    let rows: Vec<(String, i64, Option<f64>, Option<f64>)> = tests::table
+        .inner_join(completions::table)
+        .filter(tests::user_id.eq(user_id))
+        .group_by(tests::subject)
+        .select((
+            tests::subject,
+            count(completions::id),
+            dsl::avg(score()),
+            dsl::max(score()),
+        ))
+        .order_by(tests::subject)
+        .load(conn)
+        .await?;
+    trace!(?rows);
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(subject, completion_count, average_score, best_score)| SubjectStats {
+                subject,
+                completion_count,
+                average_score,
+                best_score,
+            },
+        )
+        .collect())
+}
@@ -1,14 +1,31 @@
 //! This module handles querying and inserting tests and completions.
+//!
+//! Every change to a test or its completions bumps the test's `updated_at`, and deleting a test
+//! leaves a tombstone behind, so that clients can fetch just the changes since they last synced.
 
-use crate::db::{
-    establish_connection,
-    models::{Completion, Test},
-    schema::{completions, tests, users},
+use crate::{
+    config::config,
+    db::{
+        models::{Completion, NewCompletion, NewTest, NewTombstone, Test},
+        schema::{completions, tests, tombstones},
+        DbConnection, UniqueConstraint,
+    },
+    statistics::SCORE_SQL,
 };
-use diesel::{prelude::*, result::Error};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{
+    dsl::sql,
+    prelude::*,
+    result::Error as DbError,
+    sql_types::{Bool, Integer},
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use std::collections::HashMap;
-use test_tracker_shared::{CompletionData, TestAndCompletions, TestData};
-use tracing::{instrument, trace};
+use test_tracker_shared::{
+    sort::exam_date, CompletionData, Error, SyncedTest, TestAndCompletions, TestData, TestSort,
+    TestsSync,
+};
+use tracing::{debug, instrument, trace};
 
 impl From<Test> for TestData {
     fn from(value: Test) -> Self {
@@ -57,34 +74,610 @@ impl From<Completion> for CompletionData {
 }
 
 /// For the given user, find all the tests they own and all the completions that each of those
-/// tests have.
-#[instrument]
-pub fn get_all_tests_and_completions_for_user(
+/// tests have, sorted by ID.
+///
+/// This takes two indexed queries, one for the tests and one for all their completions, and then
+/// groups the completions by test in a single pass.
+#[instrument(skip(conn))]
+pub async fn get_all_tests_and_completions_for_user(
+    conn: &mut DbConnection,
     user_id: &str,
 ) -> Result<Vec<TestAndCompletions>, Error> {
-    let tests_and_completions: Vec<(Test, Completion)> = users::table
-        .inner_join(tests::table.inner_join(completions::table))
-        .filter(users::id.eq(user_id))
-        .select((Test::as_select(), Completion::as_select()))
-        .load::<(Test, Completion)>(&mut establish_connection())?;
-    trace!(?tests_and_completions);
+    let tests: Vec<Test> = tests::table
+        .filter(tests::user_id.eq(user_id))
+        .select(Test::as_select())
+        .order(tests::id)
+        .load(conn)
+        .await?;
 
-    let mut map: HashMap<TestData, Vec<CompletionData>> = HashMap::new();
+    let completions: Vec<Completion> = Completion::belonging_to(&tests)
+        .select(Completion::as_select())
+        .order(completions::id)
+        .load(conn)
+        .await?;
+    trace!(tests = tests.len(), completions = completions.len());
 
-    for (test, completion) in
-        tests_and_completions
-            .into_iter()
-            .map(|(test, completion)| -> (TestData, CompletionData) {
-                (test.into(), completion.into())
-            })
-    {
-        match map.get_mut(&test) {
-            None => {
-                map.insert(test, vec![completion]);
+    Ok(completions
+        .grouped_by(&tests)
+        .into_iter()
+        .zip(tests)
+        .map(|(completions, test)| {
+            (
+                test.into(),
+                completions.into_iter().map(Into::into).collect(),
+            )
+        })
+        .collect())
+}
+
+/// Insert a new test for the given user along with its completions, and return the ID of the new
+/// test.
+///
+/// The test is normalized with [`TestData::normalized`] first, so tests that only differ in
+/// whitespace count as the same test.
+///
+/// If the user already has a test with the same subject and date or ID, then this fails with
+/// [`Error::DuplicateTest`], even if the other test was inserted by a concurrent request. If the
+/// test is missing a required field or any completion has invalid marks, then this fails with
+/// [`Error::Validation`] before inserting anything.
+///
+/// This doesn't start a transaction itself, so callers that need the test and its completions to
+/// be inserted together should call this inside one.
+#[instrument(skip(conn, completions))]
+pub async fn insert_test_and_completions(
+    conn: &mut DbConnection,
+    user_id: &str,
+    test: TestData,
+    completions: Vec<CompletionData>,
+) -> Result<i32, Error> {
+    let test = test.normalized();
+    test.validate()?;
+    for completion in &completions {
+        completion.validate()?;
+    }
+
+    let TestData {
+        subject,
+        topic,
+        date_or_id,
+        qualification_level,
+        exam_board,
+        paper_link,
+        mark_scheme_link,
+        comments,
+    } = test;
+
+    let duplicate = Error::DuplicateTest {
+        subject: subject.clone(),
+        date_or_id: date_or_id.clone(),
+    };
+
+    let test_id = diesel::insert_into(tests::table)
+        .values(&NewTest {
+            subject,
+            topic,
+            date_or_id,
+            qualification_level,
+            exam_board,
+            user_id: user_id.to_string(),
+            paper_link,
+            mark_scheme_link,
+            comments,
+            updated_at: Utc::now(),
+        })
+        .returning(tests::id)
+        .get_result(conn)
+        .await
+        .map_err(|e| match UniqueConstraint::violated_by(&e) {
+            Some(UniqueConstraint::Test) => {
+                debug!(?duplicate, "Test already exists");
+                duplicate
             }
-            Some(completions) => completions.push(completion),
-        };
+            _ => e.into(),
+        })?;
+
+    let new_completions: Vec<NewCompletion> = completions
+        .into_iter()
+        .map(
+            |CompletionData {
+                 achieved_mark,
+                 total_marks,
+                 date,
+                 comments,
+             }| NewCompletion {
+                achieved_mark,
+                total_marks,
+                date,
+                comments,
+                test_id,
+            },
+        )
+        .collect();
+
+    if !new_completions.is_empty() {
+        diesel::insert_into(completions::table)
+            .values(&new_completions)
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(test_id)
+}
+
+/// How far before `since` to look for changes, to catch changes made by transactions that started
+/// before the last sync but committed after it. These can't take longer than a request, so this
+/// matches the request timeout. The client might get a few tests twice, but merging is idempotent.
+fn sync_overlap() -> Duration {
+    Duration::from_std(config().request_timeout).unwrap_or_else(|_| Duration::seconds(10))
+}
+
+/// Work out how far back to look for changes for a sync at `now`, or `None` for a full sync.
+///
+/// A `since` in the future can't be trusted, so that gives a full sync. So does a `since` so close
+/// to the beginning of time that we can't subtract the overlap from it, since the client has sent
+/// something strange and a full sync is always correct.
+pub fn sync_cutoff(since: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    since
+        .filter(|&since| since <= now)
+        .and_then(|since| since.checked_sub_signed(sync_overlap()))
+}
+
+/// Get the IDs of all the given user's tests in the given order, or `None` if they should just be
+/// sorted by ID.
+///
+/// Everything but [`TestSort::ExamDateAsc`] is sorted by the database. `NULLS LAST` isn't
+/// portable, so we sort by whether each aggregate is `NULL` first instead, which does the same
+/// thing on both backends. The exam date has to be parsed out of the date or ID, so that sort is
+/// done here instead.
+async fn sorted_test_ids(
+    conn: &mut DbConnection,
+    user_id: &str,
+    sort: Option<TestSort>,
+) -> Result<Option<Vec<i32>>, DbError> {
+    let best = format!("MAX({SCORE_SQL})");
+    let order = match sort {
+        None => return Ok(None),
+        Some(TestSort::Unknown) => {
+            debug!("Unknown sort, so sorting by ID");
+            return Ok(None);
+        }
+        Some(TestSort::ExamDateAsc) => {
+            let mut tests: Vec<(i32, String)> = tests::table
+                .filter(tests::user_id.eq(user_id))
+                .select((tests::id, tests::date_or_id))
+                .order(tests::id)
+                .load(conn)
+                .await?;
+
+            // This sort is stable, so ties stay sorted by ID
+            tests.sort_by_cached_key(|(_, date_or_id)| {
+                let date = exam_date(date_or_id);
+                (date.is_none(), date)
+            });
+            return Ok(Some(tests.into_iter().map(|(id, _)| id).collect()));
+        }
+        Some(TestSort::SubjectAsc) => "tests.subject, tests.date_or_id, tests.id".to_string(),
+        Some(TestSort::RecentlyAttempted) => {
+            "MAX(completions.date) IS NULL, MAX(completions.date) DESC, tests.id".to_string()
+        }
+        Some(TestSort::BestPercentageAsc) => format!("{best} IS NULL, {best}, tests.id"),
+        Some(TestSort::BestPercentageDesc) => format!("{best} IS NULL, {best} DESC, tests.id"),
+    };
+
+    tests::table
+        .left_join(completions::table)
+        .filter(tests::user_id.eq(user_id))
+        .group_by(tests::id)
+        .select(tests::id)
+        .order_by(sql::<Integer>(&order))
+        .load(conn)
+        .await
+        .map(Some)
+}
+
+/// Find the personal best completion of each of the given tests, keyed by test ID. See
+/// [`SyncedTest::best_completion_id`].
+///
+/// The database ranks the completions of each test, best first, and we take the first of each.
+/// Dates are compared after whether they're `NULL`, like in [`sorted_test_ids`], so completions
+/// without dates lose ties. If even the dates are tied, then the newest completion wins.
+async fn best_completion_ids(
+    conn: &mut DbConnection,
+    test_ids: &[i32],
+) -> Result<HashMap<i32, i32>, DbError> {
+    let ranked: Vec<(i32, i32)> = completions::table
+        .filter(completions::test_id.eq_any(test_ids))
+        .filter(sql::<Bool>(&format!("({SCORE_SQL}) IS NOT NULL")))
+        .select((completions::test_id, completions::id))
+        .order_by(sql::<Integer>(&format!(
+            "completions.test_id, {SCORE_SQL} DESC, completions.date IS NULL, \
+             completions.date DESC, completions.id DESC"
+        )))
+        .load(conn)
+        .await?;
+
+    let mut best = HashMap::new();
+    for (test_id, completion_id) in ranked {
+        best.entry(test_id).or_insert(completion_id);
+    }
+    Ok(best)
+}
+
+/// Get the tests and completions that have changed for the given user since the given time, or
+/// all of them if `since` is `None`.
+///
+/// `since` should be the [`synced_at`](TestsSync::synced_at) from the last sync, which comes from
+/// the server's clock. If it's in the server's future anyway, then we can't trust it, so this
+/// falls back to a full sync.
+///
+/// If `sort` is given, then the sync includes the [`order`](TestsSync::order) of all the user's
+/// tests, and the changed tests are in that order too.
+#[instrument(skip(conn))]
+pub async fn get_tests_and_completions_since(
+    conn: &mut DbConnection,
+    user_id: &str,
+    since: Option<DateTime<Utc>>,
+    sort: Option<TestSort>,
+) -> Result<TestsSync, Error> {
+    let synced_at = Utc::now();
+    let cutoff = sync_cutoff(since, synced_at);
+
+    let mut query = tests::table
+        .filter(tests::user_id.eq(user_id))
+        .select(Test::as_select())
+        .order(tests::id)
+        .into_boxed();
+    if let Some(cutoff) = cutoff {
+        query = query.filter(tests::updated_at.gt(cutoff));
+    }
+    let changed_tests: Vec<Test> = query.load(conn).await?;
+
+    let changed_completions: Vec<Completion> = Completion::belonging_to(&changed_tests)
+        .select(Completion::as_select())
+        .order(completions::id)
+        .load(conn)
+        .await?;
+
+    let deleted: Vec<i32> = match cutoff {
+        Some(cutoff) => {
+            tombstones::table
+                .filter(tombstones::user_id.eq(user_id))
+                .filter(tombstones::deleted_at.gt(cutoff))
+                .select(tombstones::test_id)
+                .order(tombstones::test_id)
+                .load(conn)
+                .await?
+        }
+        None => vec![],
+    };
+
+    let test_ids: Vec<i32> = changed_tests.iter().map(|test| test.id).collect();
+    let best = best_completion_ids(conn, &test_ids).await?;
+
+    let mut changed: Vec<SyncedTest> = changed_completions
+        .grouped_by(&changed_tests)
+        .into_iter()
+        .zip(changed_tests)
+        .map(|(completions, test)| SyncedTest {
+            id: test.id,
+            completions: completions
+                .into_iter()
+                .map(|completion| (completion.id, completion.into()))
+                .collect(),
+            best_completion_id: best.get(&test.id).copied(),
+            test: test.into(),
+        })
+        .collect();
+
+    let order = sorted_test_ids(conn, user_id, sort).await?;
+    if let Some(order) = &order {
+        let positions: HashMap<i32, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(position, &id)| (id, position))
+            .collect();
+        changed.sort_by_key(|test| positions.get(&test.id).copied());
+    }
+
+    debug!(
+        full = cutoff.is_none(),
+        changed = changed.len(),
+        deleted = deleted.len(),
+        "Synced tests"
+    );
+
+    Ok(TestsSync {
+        full: cutoff.is_none(),
+        changed,
+        deleted,
+        synced_at,
+        order,
+    })
+}
+
+/// Get one of the given user's tests along with all its completions, sorted by ID.
+///
+/// A test that belongs to someone else gives the same `NotFound` error as one that doesn't exist,
+/// so that users can't find out which IDs are in use.
+#[instrument(skip(conn))]
+pub async fn get_test(
+    conn: &mut DbConnection,
+    user_id: &str,
+    test_id: i32,
+) -> Result<SyncedTest, Error> {
+    let test: Test = tests::table
+        .filter(tests::id.eq(test_id))
+        .filter(tests::user_id.eq(user_id))
+        .select(Test::as_select())
+        .first(conn)
+        .await?;
+
+    let completions: Vec<Completion> = Completion::belonging_to(&test)
+        .select(Completion::as_select())
+        .order(completions::id)
+        .load(conn)
+        .await?;
+    let best = best_completion_ids(conn, &[test.id]).await?;
+    trace!(completions = completions.len());
+
+    Ok(SyncedTest {
+        id: test.id,
+        completions: completions
+            .into_iter()
+            .map(|completion| (completion.id, completion.into()))
+            .collect(),
+        best_completion_id: best.get(&test.id).copied(),
+        test: test.into(),
+    })
+}
+
+/// Mark the given test as changed, so that it gets sent in the next sync.
+async fn touch_test(conn: &mut DbConnection, test_id: i32) -> Result<(), DbError> {
+    diesel::update(tests::table.find(test_id))
+        .set(tests::updated_at.eq(Utc::now()))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Get the given completion, as long as its test belongs to the given user. Every handler that
+/// reads or changes a single completion must check this first.
+///
+/// A completion that belongs to someone else gives exactly the same `NotFound` error as one that
+/// doesn't exist at all, so that users can't find out which IDs are in use.
+#[instrument(skip(conn))]
+pub async fn assert_completion_owner(
+    conn: &mut DbConnection,
+    completion_id: i32,
+    user_id: &str,
+) -> Result<Completion, Error> {
+    Ok(completions::table
+        .inner_join(tests::table)
+        .filter(completions::id.eq(completion_id))
+        .filter(tests::user_id.eq(user_id))
+        .select(Completion::as_select())
+        .first(conn)
+        .await?)
+}
+
+/// Add a new completion to one of the given user's tests, which must have valid marks, and return
+/// the ID of the new completion.
+///
+/// A test that belongs to someone else gives the same `NotFound` error as one that doesn't exist.
+#[instrument(skip(conn))]
+pub async fn add_completion(
+    conn: &mut DbConnection,
+    user_id: &str,
+    test_id: i32,
+    completion: CompletionData,
+) -> Result<i32, Error> {
+    completion.validate()?;
+
+    let user_id = user_id.to_string();
+    conn.transaction::<_, Error, _>(|conn| {
+        async move {
+            let test_id: i32 = tests::table
+                .filter(tests::id.eq(test_id))
+                .filter(tests::user_id.eq(&user_id))
+                .select(tests::id)
+                .first(conn)
+                .await?;
+
+            let CompletionData {
+                achieved_mark,
+                total_marks,
+                date,
+                comments,
+            } = completion;
+            let completion_id = diesel::insert_into(completions::table)
+                .values(&NewCompletion {
+                    achieved_mark,
+                    total_marks,
+                    date,
+                    comments,
+                    test_id,
+                })
+                .returning(completions::id)
+                .get_result(conn)
+                .await?;
+
+            touch_test(conn, test_id).await?;
+            Ok(completion_id)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Replace the data of one of the given user's tests, which is normalized and validated just like
+/// a new test. If the new data would make it a duplicate of another of the user's tests, then this
+/// fails with [`Error::DuplicateTest`] and nothing changes.
+///
+/// A test that belongs to someone else gives the same `NotFound` error as one that doesn't exist.
+#[instrument(skip(conn))]
+pub async fn update_test(
+    conn: &mut DbConnection,
+    user_id: &str,
+    test_id: i32,
+    test: TestData,
+) -> Result<(), Error> {
+    let test = test.normalized();
+    test.validate()?;
+
+    let TestData {
+        subject,
+        topic,
+        date_or_id,
+        qualification_level,
+        exam_board,
+        paper_link,
+        mark_scheme_link,
+        comments,
+    } = test;
+
+    let duplicate = Error::DuplicateTest {
+        subject: subject.clone(),
+        date_or_id: date_or_id.clone(),
+    };
+
+    let updated = diesel::update(
+        tests::table
+            .filter(tests::id.eq(test_id))
+            .filter(tests::user_id.eq(user_id)),
+    )
+    .set((
+        tests::subject.eq(subject),
+        tests::topic.eq(topic),
+        tests::date_or_id.eq(date_or_id),
+        tests::qualification_level.eq(qualification_level),
+        tests::exam_board.eq(exam_board),
+        tests::paper_link.eq(paper_link),
+        tests::mark_scheme_link.eq(mark_scheme_link),
+        tests::comments.eq(comments),
+        tests::updated_at.eq(Utc::now()),
+    ))
+    .execute(conn)
+    .await
+    .map_err(|e| match UniqueConstraint::violated_by(&e) {
+        Some(UniqueConstraint::Test) => {
+            debug!(?duplicate, "Test already exists");
+            duplicate
+        }
+        _ => e.into(),
+    })?;
+
+    if updated == 0 {
+        return Err(DbError::NotFound.into());
     }
+    Ok(())
+}
+
+/// Replace the data of one of the given user's completions, which must have valid marks.
+#[instrument(skip(conn))]
+pub async fn update_completion(
+    conn: &mut DbConnection,
+    user_id: &str,
+    completion_id: i32,
+    completion: CompletionData,
+) -> Result<(), Error> {
+    completion.validate()?;
+
+    let user_id = user_id.to_string();
+    conn.transaction::<_, Error, _>(|conn| {
+        async move {
+            let Completion { test_id, .. } =
+                assert_completion_owner(conn, completion_id, &user_id).await?;
+
+            let CompletionData {
+                achieved_mark,
+                total_marks,
+                date,
+                comments,
+            } = completion;
+            diesel::update(completions::table.find(completion_id))
+                .set((
+                    completions::achieved_mark.eq(achieved_mark),
+                    completions::total_marks.eq(total_marks),
+                    completions::date.eq(date),
+                    completions::comments.eq(comments),
+                ))
+                .execute(conn)
+                .await?;
+
+            Ok(touch_test(conn, test_id).await?)
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Delete one of the given user's completions.
+#[instrument(skip(conn))]
+pub async fn delete_completion(
+    conn: &mut DbConnection,
+    user_id: &str,
+    completion_id: i32,
+) -> Result<(), Error> {
+    let user_id = user_id.to_string();
+    conn.transaction::<_, Error, _>(|conn| {
+        async move {
+            let Completion { test_id, .. } =
+                assert_completion_owner(conn, completion_id, &user_id).await?;
+
+            diesel::delete(completions::table.find(completion_id))
+                .execute(conn)
+                .await?;
+
+            Ok(touch_test(conn, test_id).await?)
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Delete one of the given user's tests along with all its completions, and leave a tombstone so
+/// that other clients find out about it when they next sync.
+#[instrument(skip(conn))]
+pub async fn delete_test(
+    conn: &mut DbConnection,
+    user_id: &str,
+    test_id: i32,
+) -> Result<(), Error> {
+    let user_id = user_id.to_string();
+    conn.transaction::<_, DbError, _>(|conn| {
+        async move {
+            let test_id: i32 = tests::table
+                .filter(tests::id.eq(test_id))
+                .filter(tests::user_id.eq(&user_id))
+                .select(tests::id)
+                .first(conn)
+                .await?;
+
+            diesel::delete(completions::table.filter(completions::test_id.eq(test_id)))
+                .execute(conn)
+                .await?;
+            diesel::delete(tests::table.find(test_id))
+                .execute(conn)
+                .await?;
+
+            diesel::insert_into(tombstones::table)
+                .values(&NewTombstone {
+                    user_id,
+                    test_id,
+                    deleted_at: Utc::now(),
+                })
+                .execute(conn)
+                .await?;
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
 
-    Ok(map.into_iter().collect())
+    Ok(())
 }
@@ -0,0 +1,97 @@
+//! This module handles loading the TLS certificate and private key from disk, and reloading them
+//! when the server receives `SIGHUP`, so that a renewed certificate doesn't need a rebuild.
+
+use axum_server::tls_rustls::RustlsConfig;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use std::{fs, path::Path};
+use test_tracker_server::config::TlsConfig;
+use tokio::signal::unix::Signal;
+use tracing::{error, info, instrument};
+
+/// The contents of the TLS certificate and private key files.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+    /// The PEM-encoded certificate (chain).
+    certificate: Vec<u8>,
+
+    /// The PEM-encoded private key.
+    private_key: Vec<u8>,
+}
+
+// The private key should never end up in the logs
+impl std::fmt::Debug for TlsFiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsFiles").finish_non_exhaustive()
+    }
+}
+
+/// Read a PEM file and check that it at least looks like PEM.
+fn read_pem(path: &Path, what: &str) -> Result<Vec<u8>> {
+    let contents = fs::read(path)
+        .wrap_err_with(|| format!("Unable to read TLS {what} from {}", path.display()))?;
+
+    if !contents.windows(11).any(|window| window == b"-----BEGIN ") {
+        return Err(eyre!(
+            "TLS {what} at {} doesn't look like a PEM file",
+            path.display()
+        ));
+    }
+
+    Ok(contents)
+}
+
+impl TlsFiles {
+    /// Read the certificate and private key from the paths in the config.
+    #[instrument]
+    pub fn load(config: &TlsConfig) -> Result<Self> {
+        Ok(Self {
+            certificate: read_pem(&config.cert_path, "certificate")?,
+            private_key: read_pem(&config.key_path, "private key")?,
+        })
+    }
+
+    /// Build the rustls config that every listener shares.
+    pub async fn into_rustls_config(self) -> Result<RustlsConfig> {
+        RustlsConfig::from_pem(self.certificate, self.private_key)
+            .await
+            .wrap_err("Unable to use the TLS certificate and private key")
+    }
+}
+
+/// Swap the certificate and private key in the shared rustls config, so that new connections on
+/// every listener use them. If the new files don't work, then we keep the old ones.
+pub async fn reload(rustls_config: &RustlsConfig, files: TlsFiles) {
+    match rustls_config
+        .reload_from_pem(files.certificate, files.private_key)
+        .await
+    {
+        Ok(()) => info!("Reloaded TLS files"),
+        Err(error) => error!(
+            ?error,
+            "Unable to use reloaded TLS files; keeping the old ones"
+        ),
+    }
+}
+
+/// Wait for `SIGHUP` and then re-read the TLS files. If they can't be read, then the error is
+/// logged and we keep waiting, so that a botched renewal doesn't take the server down.
+///
+/// If TLS isn't configured, then this never returns.
+pub async fn wait_for_reload(hangup: &mut Signal, config: Option<&TlsConfig>) -> TlsFiles {
+    let Some(config) = config else {
+        return std::future::pending().await;
+    };
+
+    loop {
+        hangup.recv().await;
+        info!("Received SIGHUP; reloading TLS certificate and private key");
+
+        match TlsFiles::load(config) {
+            Ok(files) => return files,
+            Err(error) => error!(?error, "Unable to reload TLS files; keeping the old ones"),
+        }
+    }
+}
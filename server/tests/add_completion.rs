@@ -0,0 +1,82 @@
+//! Tests for adding a single completion to a test, which the client's "Log attempt" form does.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::tests_and_completions::{add_completion, get_tests_and_completions_since};
+use test_tracker_shared::{
+    error::DieselError,
+    fixtures::{bare_completion, sample_completion},
+    policy::MarksPolicyError,
+    CompletionData, Error,
+};
+
+#[tokio::test]
+async fn completions_are_added_to_the_test() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+
+    let completion = CompletionData {
+        comments: Some("Timed".to_string()),
+        ..sample_completion(75.)
+    };
+    let completion_id = add_completion(&mut db, &user.id, test_id, completion.clone())
+        .await
+        .unwrap();
+
+    let sync = get_tests_and_completions_since(&mut db, &user.id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        sync.changed[0].completions,
+        vec![(completion_id, completion)]
+    );
+}
+
+#[tokio::test]
+async fn invalid_marks_are_rejected() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+
+    for (achieved_mark, total_marks, error) in [
+        (-1, 80, MarksPolicyError::NegativeMark),
+        (81, 80, MarksPolicyError::MoreThanTotal),
+        (0, 0, MarksPolicyError::NoMarksAvailable),
+    ] {
+        assert_eq!(
+            add_completion(
+                &mut db,
+                &user.id,
+                test_id,
+                bare_completion(achieved_mark, total_marks, None)
+            )
+            .await,
+            Err(error.into())
+        );
+    }
+}
+
+#[tokio::test]
+async fn other_users_tests_look_like_they_dont_exist() {
+    let mut db = TestDb::new().await;
+    let owner = db.user("owner").await;
+    let other = db.user("other").await;
+    let test_id = db.test(&owner.id, "Maths", "June 2019 Paper 1").await;
+
+    let not_found = Err(Error::DatabaseError(DieselError::NotFound));
+    assert_eq!(
+        add_completion(&mut db, &other.id, test_id, sample_completion(75.)).await,
+        not_found
+    );
+    assert_eq!(
+        add_completion(&mut db, &other.id, i32::MAX, sample_completion(75.)).await,
+        not_found
+    );
+
+    let sync = get_tests_and_completions_since(&mut db, &owner.id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(sync.changed[0].completions, vec![]);
+}
@@ -0,0 +1,66 @@
+//! Tests for adding a single test, which the client's "Add test" form does.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::tests_and_completions::{
+    get_all_tests_and_completions_for_user, insert_test_and_completions,
+};
+use test_tracker_shared::{
+    fixtures::{bare_test, sample_test},
+    policy::TestPolicyError,
+    Error,
+};
+
+#[test]
+fn subject_and_date_or_id_are_required() {
+    assert_eq!(bare_test("Maths", "June 2019 Paper 1").validate(), Ok(()));
+    assert_eq!(
+        bare_test("", "June 2019 Paper 1").validate(),
+        Err(TestPolicyError::MissingSubject)
+    );
+    assert_eq!(
+        bare_test("Maths", " \u{a0}\t").validate(),
+        Err(TestPolicyError::MissingDateOrId)
+    );
+    assert_eq!(TestPolicyError::MissingSubject.field(), "subject");
+    assert_eq!(TestPolicyError::MissingDateOrId.field(), "date_or_id");
+}
+
+#[tokio::test]
+async fn valid_tests_are_added_without_completions() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    insert_test_and_completions(&mut db, &user.id, sample_test("Maths"), vec![])
+        .await
+        .unwrap();
+    assert_eq!(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+        vec![(sample_test("Maths"), vec![])]
+    );
+}
+
+#[tokio::test]
+async fn invalid_tests_are_rejected_by_the_server_too() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    let result =
+        insert_test_and_completions(&mut db, &user.id, bare_test("  ", "Mock Set 1"), vec![]).await;
+    assert_eq!(
+        result,
+        Err(Error::Validation {
+            field: "subject".to_string(),
+            reason: TestPolicyError::MissingSubject.to_string(),
+        })
+    );
+    assert_eq!(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+        vec![]
+    );
+}
@@ -0,0 +1,87 @@
+//! Tests for the admin-only capabilities.
+
+mod common;
+
+use common::{insert_test, test_connection};
+use test_tracker_server::{
+    admin::{list_users, promote_to_admin, require_admin, reset_password},
+    passwords::{add_new_user, validate_user},
+    sessions::{authorize, start_session},
+};
+use test_tracker_shared::Error as SharedError;
+
+#[tokio::test]
+async fn only_admins_pass_the_admin_check() {
+    let conn = &mut test_connection().await;
+    let admin = add_new_user(conn, "the_admin", "correct horse battery")
+        .await
+        .unwrap();
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    promote_to_admin(conn, "The_Admin").await.unwrap();
+
+    let admin_session = start_session(conn, admin.clone(), false).await.unwrap();
+    let user_session = start_session(conn, user, false).await.unwrap();
+
+    assert_eq!(
+        require_admin(conn, &admin_session.token).await,
+        Ok(admin.id)
+    );
+    assert_eq!(
+        require_admin(conn, &user_session.token).await,
+        Err(SharedError::Forbidden)
+    );
+}
+
+#[tokio::test]
+async fn list_users_counts_tests() {
+    let conn = &mut test_connection().await;
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    add_new_user(conn, "bob_smith", "correct horse battery")
+        .await
+        .unwrap();
+    insert_test(conn, &alice.id, "Maths", "June 2019").await;
+    insert_test(conn, &alice.id, "Physics", "June 2019").await;
+
+    let users = list_users(conn).await.unwrap();
+    let alice = users.iter().find(|user| user.username == "alice").unwrap();
+    let bob = users
+        .iter()
+        .find(|user| user.username == "bob_smith")
+        .unwrap();
+    assert_eq!(alice.test_count, 2);
+    assert_eq!(bob.test_count, 0);
+}
+
+#[tokio::test]
+async fn reset_password_ends_sessions() {
+    let conn = &mut test_connection().await;
+    let admin = add_new_user(conn, "the_admin", "correct horse battery")
+        .await
+        .unwrap();
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let session = start_session(conn, user.clone(), false).await.unwrap();
+
+    reset_password(conn, &admin.id, &user.id, "a brand new password", None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        authorize(conn, &session.token, &user.id).await,
+        Err(SharedError::Unauthorized)
+    );
+    assert!(validate_user(conn, "test_user", "correct horse battery")
+        .await
+        .is_err());
+    assert_eq!(
+        validate_user(conn, "test_user", "a brand new password")
+            .await
+            .unwrap(),
+        user
+    );
+}
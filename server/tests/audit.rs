@@ -0,0 +1,97 @@
+//! Tests for the audit log.
+
+mod common;
+
+use common::{test_connection, TestDb};
+use test_tracker_server::{
+    api::handle_message,
+    audit::{get_audit_log_for_user, record, record_failed_login, AUDIT_LOG_LIMIT},
+    passwords::add_new_user,
+    sessions::start_session,
+};
+use test_tracker_shared::{AuditEvent, ClientToServerMsg, ServerToClientMsg};
+
+#[tokio::test]
+async fn failed_logins_are_attached_to_the_account() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    record(
+        conn,
+        Some(&user.id),
+        AuditEvent::Login,
+        Some("127.0.0.1:1234".parse().unwrap()),
+        serde_json::json!({}),
+    )
+    .await;
+    record_failed_login(conn, "Test_User", None).await;
+    record_failed_login(conn, "someone_else", None).await;
+
+    let log = get_audit_log_for_user(conn, &user.id).await.unwrap();
+    let events: Vec<AuditEvent> = log.iter().map(|entry| entry.event).collect();
+    assert_eq!(events, vec![AuditEvent::FailedLogin, AuditEvent::Login]);
+    assert_eq!(log[1].remote_addr.as_deref(), Some("127.0.0.1:1234"));
+}
+
+#[tokio::test]
+async fn audit_log_is_capped() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    for _ in 0..AUDIT_LOG_LIMIT + 5 {
+        record(
+            conn,
+            Some(&user.id),
+            AuditEvent::Login,
+            None,
+            serde_json::json!({}),
+        )
+        .await;
+    }
+
+    let log = get_audit_log_for_user(conn, &user.id).await.unwrap();
+    assert_eq!(log.len() as i64, AUDIT_LOG_LIMIT);
+}
+
+#[tokio::test]
+async fn deleting_a_test_is_audited() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let bob = db.user("bob_smith").await;
+    let alices_test = db.test(&alice.id, "Maths", "June 2019 Paper 1").await;
+    let bobs_test = db.test(&bob.id, "Maths", "June 2019 Paper 1").await;
+    let session = start_session(&mut db, alice.clone(), false).await.unwrap();
+    let delete = |test_id| ClientToServerMsg::DeleteTest {
+        token: session.token.clone(),
+        user_id: alice.id.clone(),
+        test_id,
+    };
+
+    // Trying to delete someone else's test fails, so there's nothing to audit
+    let response = handle_message(&mut db, delete(bobs_test), None).await;
+    assert!(matches!(
+        response,
+        ServerToClientMsg::DeleteTestResponse(Err(_))
+    ));
+    assert!(get_audit_log_for_user(&mut db, &alice.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let remote_addr = "127.0.0.1:1234".parse().unwrap();
+    let response = handle_message(&mut db, delete(alices_test), Some(remote_addr)).await;
+    assert_eq!(response, ServerToClientMsg::DeleteTestResponse(Ok(())));
+
+    let log = get_audit_log_for_user(&mut db, &alice.id).await.unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].event, AuditEvent::TestDeleted);
+    assert_eq!(log[0].remote_addr.as_deref(), Some("127.0.0.1:1234"));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&log[0].details).unwrap(),
+        serde_json::json!({ "test_id": alices_test })
+    );
+}
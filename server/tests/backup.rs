@@ -0,0 +1,121 @@
+//! Tests for backing up all the data to a file and restoring it into an empty database.
+
+mod common;
+
+use common::{insert_completion, insert_test, test_connection};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{
+    admin::promote_to_admin,
+    backup::{create_backup, restore_backup, Backup, BackupError, BACKUP_VERSION},
+    db::{
+        schema::{audit_log, completions, sessions, tests, tombstones, users},
+        DbConnection,
+    },
+    passwords::{add_new_user, validate_user},
+};
+
+/// Fill the database with a couple of users with tests and completions, one of them an admin.
+async fn insert_fixtures(conn: &mut DbConnection) {
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    let bob = add_new_user(conn, "bob", "tr0ub4dor & 3").await.unwrap();
+    promote_to_admin(conn, "alice").await.unwrap();
+
+    let maths = insert_test(conn, &alice.id, "Maths", "June 2019 Paper 1").await;
+    insert_completion(conn, maths, 45, 80, Some("Ran out of time")).await;
+    insert_completion(conn, maths, 62, 80, None).await;
+
+    let physics = insert_test(conn, &bob.id, "Physics", "Mock Set 1").await;
+    insert_completion(conn, physics, 30, 60, Some("Unicode too: ∫ x dx")).await;
+    insert_test(conn, &bob.id, "Chemistry", "Never attempted").await;
+}
+
+/// Delete everything, as if the database were freshly migrated.
+async fn wipe(conn: &mut DbConnection) {
+    diesel::delete(audit_log::table)
+        .execute(conn)
+        .await
+        .unwrap();
+    diesel::delete(sessions::table).execute(conn).await.unwrap();
+    diesel::delete(tombstones::table)
+        .execute(conn)
+        .await
+        .unwrap();
+    diesel::delete(completions::table)
+        .execute(conn)
+        .await
+        .unwrap();
+    diesel::delete(tests::table).execute(conn).await.unwrap();
+    diesel::delete(users::table).execute(conn).await.unwrap();
+}
+
+#[tokio::test]
+async fn backup_round_trips() {
+    let conn = &mut test_connection().await;
+    insert_fixtures(conn).await;
+
+    let backup = create_backup(conn).await.unwrap();
+    assert_eq!(backup.version, BACKUP_VERSION);
+    assert_eq!(backup.users.len(), 2);
+    assert_eq!(backup.tests.len(), 3);
+    assert_eq!(backup.completions.len(), 3);
+
+    let text = backup.to_ron().unwrap();
+    wipe(conn).await;
+    restore_backup(conn, &Backup::from_ron(&text).unwrap())
+        .await
+        .unwrap();
+
+    let restored = create_backup(conn).await.unwrap();
+    assert_eq!(restored.users, backup.users);
+    assert_eq!(restored.tests, backup.tests);
+    assert_eq!(restored.completions, backup.completions);
+
+    // Hashed passwords survive, so everyone can still log in
+    assert!(validate_user(conn, "bob", "tr0ub4dor & 3").await.is_ok());
+}
+
+#[tokio::test]
+async fn new_rows_after_a_restore_get_fresh_ids() {
+    let conn = &mut test_connection().await;
+    insert_fixtures(conn).await;
+    let backup = create_backup(conn).await.unwrap();
+    wipe(conn).await;
+    restore_backup(conn, &backup).await.unwrap();
+
+    let user_id = &backup.users[0].id;
+    let max_test_id = backup.tests.iter().map(|test| test.id).max().unwrap();
+    let new_test = insert_test(conn, user_id, "Biology", "Paper 2").await;
+    assert!(new_test > max_test_id);
+}
+
+#[tokio::test]
+async fn restoring_needs_an_empty_database() {
+    let conn = &mut test_connection().await;
+    insert_fixtures(conn).await;
+    let backup = create_backup(conn).await.unwrap();
+
+    assert!(matches!(
+        restore_backup(conn, &backup).await,
+        Err(BackupError::NotEmpty)
+    ));
+    assert_eq!(create_backup(conn).await.unwrap().tests, backup.tests);
+}
+
+#[tokio::test]
+async fn unknown_versions_are_rejected() {
+    let conn = &mut test_connection().await;
+    insert_fixtures(conn).await;
+    let text = create_backup(conn).await.unwrap().to_ron().unwrap();
+
+    let future = text.replacen(
+        &format!("version: {BACKUP_VERSION}"),
+        &format!("version: {}", BACKUP_VERSION + 1),
+        1,
+    );
+    assert!(matches!(
+        Backup::from_ron(&future),
+        Err(BackupError::UnsupportedVersion { found }) if found == BACKUP_VERSION + 1
+    ));
+}
@@ -0,0 +1,117 @@
+//! Tests for users changing their own passwords.
+
+mod common;
+
+use common::test_connection;
+use test_tracker_server::{
+    passwords::{add_new_user, change_password, validate_user},
+    sessions::{authorize, end_other_sessions, start_session},
+};
+use test_tracker_shared::Error as SharedError;
+
+#[tokio::test]
+async fn changed_password_is_the_only_one_that_works() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    change_password(
+        conn,
+        &user.id,
+        "correct horse battery",
+        "a brand new password",
+    )
+    .await
+    .unwrap();
+
+    assert!(validate_user(conn, "test_user", "correct horse battery")
+        .await
+        .is_err());
+    assert_eq!(
+        validate_user(conn, "test_user", "a brand new password")
+            .await
+            .unwrap(),
+        user
+    );
+}
+
+#[tokio::test]
+async fn wrong_current_password_is_rejected() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let error = SharedError::from(
+        change_password(
+            conn,
+            &user.id,
+            "incorrect horse battery",
+            "a brand new password",
+        )
+        .await
+        .unwrap_err(),
+    );
+    assert_eq!(error, SharedError::InvalidPassword);
+    assert!(validate_user(conn, "test_user", "correct horse battery")
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn new_password_must_satisfy_the_policy() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let error = SharedError::from(
+        change_password(conn, &user.id, "correct horse battery", "short")
+            .await
+            .unwrap_err(),
+    );
+    assert!(
+        matches!(&error, SharedError::Validation { field, .. } if field == "password"),
+        "{error:?}"
+    );
+    assert!(validate_user(conn, "test_user", "correct horse battery")
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn new_password_is_checked_before_the_current_one() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let error = SharedError::from(
+        change_password(conn, &user.id, "incorrect horse battery", "short")
+            .await
+            .unwrap_err(),
+    );
+    assert!(
+        matches!(&error, SharedError::Validation { field, .. } if field == "password"),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn other_sessions_end_but_this_one_stays() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let here = start_session(conn, user.clone(), false).await.unwrap();
+    let elsewhere = start_session(conn, user.clone(), true).await.unwrap();
+
+    assert_eq!(end_other_sessions(conn, &user.id, &here.token).await, Ok(1));
+
+    assert_eq!(authorize(conn, &here.token, &user.id).await, Ok(()));
+    assert_eq!(
+        authorize(conn, &elsewhere.token, &user.id).await,
+        Err(SharedError::Unauthorized)
+    );
+}
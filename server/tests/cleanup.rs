@@ -0,0 +1,51 @@
+//! Tests for cleaning up rows that can never be used again.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::TestDb;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{
+    cleanup::{delete_expired_rows, CleanupSummary},
+    db::schema::sessions,
+    sessions::{authorize, start_session},
+};
+
+#[tokio::test]
+async fn only_lapsed_sessions_are_deleted() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let lapsed = start_session(&mut db, user.clone(), false).await.unwrap();
+    let lapsing = start_session(&mut db, user.clone(), false).await.unwrap();
+    let current = start_session(&mut db, user.clone(), true).await.unwrap();
+
+    let now = Utc::now();
+    for (session, expires_at) in [(&lapsed, now - Duration::days(3)), (&lapsing, now)] {
+        diesel::update(sessions::table.find(&session.token))
+            .set(sessions::expires_at.eq(expires_at))
+            .execute(&mut *db)
+            .await
+            .unwrap();
+    }
+
+    let summary = delete_expired_rows(&mut db, now).await.unwrap();
+    assert_eq!(
+        summary,
+        CleanupSummary {
+            expired_sessions: 2
+        }
+    );
+
+    let remaining: Vec<String> = sessions::table
+        .filter(sessions::user_id.eq(&user.id))
+        .select(sessions::token)
+        .load(&mut *db)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![current.token.clone()]);
+    assert_eq!(authorize(&mut db, &current.token, &user.id).await, Ok(()));
+
+    let again = delete_expired_rows(&mut db, now).await.unwrap();
+    assert_eq!(again, CleanupSummary::default());
+}
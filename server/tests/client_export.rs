@@ -0,0 +1,145 @@
+//! Tests for the CSV export that the client builds from the tests that it already has, which must
+//! read back the same as the server's export.
+
+use test_tracker_server::export::{ExportRow, CSV_HEADERS};
+use test_tracker_shared::{
+    export::tests_to_csv,
+    fixtures::{bare_completion, bare_test, date, synced_test},
+    CompletionData,
+};
+
+/// Read a CSV back in the way that the server's import does.
+fn read_csv(csv: &str) -> (Vec<String>, Vec<ExportRow>) {
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let headers = reader
+        .headers()
+        .unwrap()
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let rows = reader.deserialize().collect::<Result<_, _>>().unwrap();
+    (headers, rows)
+}
+
+#[test]
+fn awkward_text_round_trips() {
+    let commented = |achieved_mark, comments: &str| CompletionData {
+        comments: Some(comments.to_string()),
+        ..bare_completion(achieved_mark, 80, None)
+    };
+    let mut maths = synced_test(
+        1,
+        bare_test("Maths", "Paper 1"),
+        vec![
+            (1, commented(45, "Ran out of time, \"again\"")),
+            (2, commented(62, "Better\nStill slow on Q7")),
+            (3, commented(70, "Windows\r\nline endings")),
+        ],
+    );
+    maths.test.topic = Some("Statistics, probability".to_string());
+    maths.test.exam_board = Some("\"Edexcel\"".to_string());
+    maths.completions[0].1.date = Some(date("2023-05-01"));
+
+    let (headers, rows) = read_csv(&tests_to_csv(&[maths]));
+    assert_eq!(headers, CSV_HEADERS);
+    assert_eq!(
+        rows,
+        [
+            ExportRow {
+                subject: "Maths".to_string(),
+                topic: Some("Statistics, probability".to_string()),
+                date_or_id: "Paper 1".to_string(),
+                exam_board: Some("\"Edexcel\"".to_string()),
+                achieved_mark: 45,
+                total_marks: 80,
+                percentage: Some(56.25),
+                date: Some(date("2023-05-01")),
+                comments: Some("Ran out of time, \"again\"".to_string()),
+            },
+            ExportRow {
+                subject: "Maths".to_string(),
+                topic: Some("Statistics, probability".to_string()),
+                date_or_id: "Paper 1".to_string(),
+                exam_board: Some("\"Edexcel\"".to_string()),
+                achieved_mark: 62,
+                total_marks: 80,
+                percentage: Some(77.5),
+                date: None,
+                comments: Some("Better\nStill slow on Q7".to_string()),
+            },
+            ExportRow {
+                subject: "Maths".to_string(),
+                topic: Some("Statistics, probability".to_string()),
+                date_or_id: "Paper 1".to_string(),
+                exam_board: Some("\"Edexcel\"".to_string()),
+                achieved_mark: 70,
+                total_marks: 80,
+                percentage: Some(87.5),
+                date: None,
+                comments: Some("Windows\r\nline endings".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn rows_are_sorted_by_subject_and_test() {
+    let tests = [
+        synced_test(
+            3,
+            bare_test("Physics", "Paper 3"),
+            vec![(5, bare_completion(1, 2, None))],
+        ),
+        synced_test(
+            2,
+            bare_test("Maths", "Paper 2"),
+            vec![
+                (4, bare_completion(2, 2, None)),
+                (3, bare_completion(1, 2, None)),
+            ],
+        ),
+        synced_test(
+            1,
+            bare_test("Maths", "Paper 1"),
+            vec![(6, bare_completion(0, 2, None))],
+        ),
+    ];
+
+    let (_, rows) = read_csv(&tests_to_csv(&tests));
+    let order: Vec<_> = rows
+        .iter()
+        .map(|row| {
+            (
+                row.subject.as_str(),
+                row.date_or_id.as_str(),
+                row.achieved_mark,
+            )
+        })
+        .collect();
+    assert_eq!(
+        order,
+        [
+            ("Maths", "Paper 1", 0),
+            ("Maths", "Paper 2", 1),
+            ("Maths", "Paper 2", 2),
+            ("Physics", "Paper 3", 1),
+        ]
+    );
+}
+
+#[test]
+fn tests_without_completions_have_no_rows() {
+    let csv = tests_to_csv(&[synced_test(1, bare_test("Maths", "Paper 1"), vec![])]);
+    assert_eq!(csv, format!("{}\n", CSV_HEADERS.join(",")));
+}
+
+#[test]
+fn out_of_zero_completions_have_no_percentage() {
+    let (_, rows) = read_csv(&tests_to_csv(&[synced_test(
+        1,
+        bare_test("Maths", "Paper 1"),
+        vec![(1, bare_completion(0, 0, None))],
+    )]));
+    assert_eq!(rows[0].percentage, None);
+    assert_eq!(rows[0].comments, None);
+}
@@ -0,0 +1,151 @@
+//! Tests for parsing files to import on the client, which must agree with how the server parses
+//! the same CSV.
+
+use test_tracker_server::import::parse_csv as server_parse_csv;
+use test_tracker_shared::{
+    export::{rows_to_csv, tests_to_csv},
+    fixtures::{bare_completion, bare_test, date, synced_test},
+    import::{group_rows, parse_csv, rows_from_tests, ImportRow},
+    CompletionData, TestData,
+};
+
+/// A valid CSV with quoted commas, quotes, and newlines.
+const VALID: &str = include_str!("fixtures/import_valid.csv");
+
+/// A CSV with a missing subject, a bad number, and a missing date or ID.
+const WITH_ERRORS: &str = include_str!("fixtures/import_with_errors.csv");
+
+/// The valid rows, in order.
+fn valid_rows(rows: &[ImportRow]) -> Vec<(TestData, CompletionData)> {
+    rows.iter().filter_map(|row| row.row.clone().ok()).collect()
+}
+
+#[test]
+fn valid_csv_matches_the_server() {
+    let rows = parse_csv(VALID).unwrap();
+    assert!(rows.iter().all(|row| row.row.is_ok()), "{rows:?}");
+    assert_eq!(
+        rows.iter().map(|row| row.line).collect::<Vec<_>>(),
+        [2, 3, 5, 6]
+    );
+
+    let (server_tests, server_errors) = server_parse_csv(VALID);
+    assert_eq!(server_errors, vec![]);
+    let server_tests: Vec<_> = server_tests
+        .into_iter()
+        .map(|parsed| (parsed.test, parsed.completions))
+        .collect();
+    assert_eq!(group_rows(&valid_rows(&rows)), server_tests);
+}
+
+#[test]
+fn row_errors_match_the_server() {
+    let rows = parse_csv(WITH_ERRORS).unwrap();
+    let errors: Vec<(u64, &str)> = rows
+        .iter()
+        .filter_map(|row| row.row.as_ref().err().map(|e| (row.line, e.as_str())))
+        .collect();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0], (3, "missing subject"));
+    assert_eq!(errors[1].0, 4);
+    assert!(errors[1].1.contains("achieved_mark"), "{errors:?}");
+    assert_eq!(errors[2], (7, "missing date_or_id"));
+
+    let (server_tests, server_errors) = server_parse_csv(WITH_ERRORS);
+    assert_eq!(
+        server_errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+        [3, 4, 7]
+    );
+    assert_eq!(
+        group_rows(&valid_rows(&rows)),
+        server_tests
+            .into_iter()
+            .map(|parsed| (parsed.test, parsed.completions))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn exports_round_trip() {
+    let mut maths = bare_test("Maths", "June 2019, Paper 1");
+    maths.topic = Some("Statistics".to_string());
+    let mut tricky = bare_completion(45, 80, None);
+    tricky.comments = Some("Ran out of time, \"again\"\r\nand again".to_string());
+    tricky.date = Some(date("2023-04-01"));
+
+    let synced = synced_test(
+        1,
+        maths.clone(),
+        vec![(1, tricky.clone()), (2, bare_completion(62, 80, None))],
+    );
+    let rows = parse_csv(&tests_to_csv(&[synced])).unwrap();
+    assert_eq!(
+        valid_rows(&rows),
+        [
+            (maths.clone(), tricky),
+            (maths, bare_completion(62, 80, None))
+        ]
+    );
+}
+
+#[test]
+fn only_picked_rows_are_sent() {
+    let rows = parse_csv(VALID).unwrap();
+    let picked: Vec<_> = valid_rows(&rows).into_iter().skip(1).collect();
+    let csv = rows_to_csv(picked.iter().map(|(test, completion)| (test, completion)));
+
+    let (server_tests, server_errors) = server_parse_csv(&csv);
+    assert_eq!(server_errors, vec![]);
+    assert_eq!(
+        server_tests
+            .iter()
+            .map(|parsed| parsed.completions.len())
+            .collect::<Vec<_>>(),
+        [1, 1, 1]
+    );
+}
+
+#[test]
+fn broken_files_are_errors() {
+    let missing_column = "subject,date_or_id,achieved_mark\nMaths,Paper 1,4\n";
+    let error = parse_csv(missing_column).unwrap_err();
+    assert_eq!(error.line, 1);
+    assert_eq!(error.message, "missing column: total_marks");
+
+    let unclosed = "subject,date_or_id,achieved_mark,total_marks\nMaths,\"Paper 1,4,5\n";
+    assert_eq!(parse_csv(unclosed).unwrap_err().line, 2);
+
+    assert!(parse_csv("").is_err());
+}
+
+#[test]
+fn columns_can_be_missing_or_reordered() {
+    let csv = "total_marks,achieved_mark,date_or_id,subject\r\n80,45,Paper 1,Maths\r\n\r\n80,x\r\n";
+    let rows = parse_csv(csv).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0].row,
+        Ok((bare_test("Maths", "Paper 1"), bare_completion(45, 80, None)))
+    );
+    assert_eq!(rows[1].line, 4);
+    assert_eq!(
+        rows[1].row,
+        Err("expected 4 fields, but found 2".to_string())
+    );
+}
+
+#[test]
+fn ron_tests_become_rows() {
+    let rows = rows_from_tests(vec![
+        (
+            bare_test("Maths", "Paper 1"),
+            vec![bare_completion(45, 80, None), bare_completion(90, 80, None)],
+        ),
+        (bare_test("Physics", "Paper 2"), vec![]),
+        (bare_test("", "Paper 3"), vec![bare_completion(1, 2, None)]),
+    ]);
+
+    let summary: Vec<(u64, bool)> = rows.iter().map(|row| (row.line, row.row.is_ok())).collect();
+    assert_eq!(summary, [(1, true), (1, false), (2, false), (3, false)]);
+    assert_eq!(rows[3].row, Err("missing subject".to_string()));
+}
@@ -0,0 +1,206 @@
+//! Shared helpers for the server's integration tests.
+//!
+//! These tests need a running PostgreSQL database at `$DATABASE_URL`, which gets migrated
+//! automatically the first time each test binary connects. With the `sqlite` feature, they use the
+//! database file at `$SQLITE_DATABASE_PATH` instead.
+//!
+//! Every test runs inside a transaction that never gets committed, so tests can run in parallel
+//! against the same database without seeing each other's data, and never leave anything behind.
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request},
+    http::Method,
+    response::Response,
+};
+use chrono::Utc;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use std::{
+    net::SocketAddr,
+    ops::{Deref, DerefMut},
+};
+use test_tracker_server::{
+    db::{
+        establish_connection,
+        models::{NewCompletion, NewTest},
+        schema::{completions, tests},
+        DbConnection,
+    },
+    passwords::add_new_user,
+    routes::router,
+};
+use test_tracker_shared::User;
+use tokio::sync::OnceCell;
+use tower::ServiceExt;
+
+/// The password that [`TestDb::user`] gives every user.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub const PASSWORD: &str = "correct horse battery";
+
+/// Set once the database has been migrated by this test binary.
+static MIGRATED: OnceCell<()> = OnceCell::const_new();
+
+/// Run any pending migrations on the test database.
+#[cfg(not(feature = "sqlite"))]
+fn run_migrations() {
+    use diesel::{Connection, PgConnection};
+    use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+    PgConnection::establish(env!("DATABASE_URL"))
+        .expect("We should be able to connect to the test DB")
+        .run_pending_migrations(MIGRATIONS)
+        .expect("We should be able to migrate the test DB");
+}
+
+/// Run any pending migrations on the test database.
+#[cfg(feature = "sqlite")]
+fn run_migrations() {
+    test_tracker_server::db::run_sqlite_migrations()
+        .expect("We should be able to migrate the test DB");
+}
+
+/// A connection to the test database for a single test, inside a transaction that gets rolled back
+/// when it's dropped.
+///
+/// This derefs to [`DbConnection`], so `&mut db` can be passed anywhere that wants a connection.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub struct TestDb {
+    /// The connection, which is inside a test transaction.
+    conn: DbConnection,
+}
+
+impl TestDb {
+    /// Migrate the test database if this test binary hasn't already, and connect to it.
+    #[allow(dead_code, reason = "Not every test binary uses this")]
+    pub async fn new() -> Self {
+        MIGRATED
+            .get_or_init(|| async {
+                tokio::task::spawn_blocking(run_migrations).await.unwrap();
+            })
+            .await;
+
+        let mut conn = establish_connection()
+            .await
+            .expect("We should be able to connect to the test DB");
+        conn.begin_test_transaction()
+            .await
+            .expect("We should be able to start a test transaction");
+        Self { conn }
+    }
+
+    /// Add a user with the given username and [`PASSWORD`].
+    #[allow(dead_code, reason = "Not every test binary uses this")]
+    pub async fn user(&mut self, username: &str) -> User {
+        add_new_user(&mut self.conn, username, PASSWORD)
+            .await
+            .expect("We should be able to add a user")
+    }
+
+    /// Add a test with just a subject and date or ID for the given user, and return its ID.
+    #[allow(dead_code, reason = "Not every test binary uses this")]
+    pub async fn test(&mut self, user_id: &str, subject: &str, date_or_id: &str) -> i32 {
+        insert_test(&mut self.conn, user_id, subject, date_or_id).await
+    }
+
+    /// Add a completion of the given test with no date or comments.
+    #[allow(dead_code, reason = "Not every test binary uses this")]
+    pub async fn completion(&mut self, test_id: i32, achieved_mark: i32, total_marks: i32) {
+        insert_completion(&mut self.conn, test_id, achieved_mark, total_marks, None).await;
+    }
+}
+
+impl Deref for TestDb {
+    type Target = DbConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl DerefMut for TestDb {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+/// Get a connection for a single test. Everything happens inside a transaction that never gets
+/// committed, so the test never leaves anything behind in the database.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub async fn test_connection() -> DbConnection {
+    TestDb::new().await.conn
+}
+
+/// Insert a test with just a subject and date or ID for the given user, and return its ID.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub async fn insert_test(
+    conn: &mut DbConnection,
+    user_id: &str,
+    subject: &str,
+    date_or_id: &str,
+) -> i32 {
+    diesel::insert_into(tests::table)
+        .values(&NewTest {
+            subject: subject.to_string(),
+            topic: None,
+            date_or_id: date_or_id.to_string(),
+            qualification_level: None,
+            exam_board: None,
+            user_id: user_id.to_string(),
+            paper_link: None,
+            mark_scheme_link: None,
+            comments: None,
+            updated_at: Utc::now(),
+        })
+        .returning(tests::id)
+        .get_result(conn)
+        .await
+        .expect("We should be able to insert a test")
+}
+
+/// Insert a completion of the given test.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub async fn insert_completion(
+    conn: &mut DbConnection,
+    test_id: i32,
+    achieved_mark: i32,
+    total_marks: i32,
+    comments: Option<&str>,
+) {
+    diesel::insert_into(completions::table)
+        .values(&NewCompletion {
+            achieved_mark,
+            total_marks,
+            date: None,
+            comments: comments.map(ToString::to_string),
+            test_id,
+        })
+        .execute(conn)
+        .await
+        .expect("We should be able to insert a completion");
+}
+
+/// Build a request to the router, as if it came from a client on this machine through a real
+/// listener.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub fn request(method: Method, uri: &str, body: impl Into<Body>) -> Request {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(body.into())
+        .expect("We should be able to build a request");
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 12345))));
+    request
+}
+
+/// Send a request through the whole router, including its middleware, and return the response.
+#[allow(dead_code, reason = "Not every test binary uses this")]
+pub async fn send(request: Request) -> Response {
+    router()
+        .oneshot(request)
+        .await
+        .expect("The router should never fail")
+}
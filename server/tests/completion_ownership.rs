@@ -0,0 +1,188 @@
+//! Tests for checking who owns a completion before anything is done with it.
+//!
+//! A completion that belongs to someone else must be indistinguishable from one that doesn't exist.
+
+mod common;
+
+use common::{insert_completion, insert_test, test_connection, TestDb};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{
+    api::handle_message,
+    db::{models::Completion, schema::completions, DbConnection},
+    passwords::add_new_user,
+    sessions::create_session,
+    tests_and_completions::{assert_completion_owner, delete_completion, update_completion},
+};
+use test_tracker_shared::{
+    error::DieselError, ClientToServerMsg, CompletionData, Error, ServerToClientMsg,
+};
+
+/// An ID that no completion has.
+const NONEXISTENT_ID: i32 = i32::MAX;
+
+/// Create two users, give the first one a test with a completion, and return both user IDs and
+/// the ID of the completion.
+async fn setup(conn: &mut DbConnection) -> (String, String, i32) {
+    let owner = add_new_user(conn, "owner", "correct horse battery")
+        .await
+        .unwrap();
+    let other = add_new_user(conn, "other", "correct horse battery")
+        .await
+        .unwrap();
+
+    let test_id = insert_test(conn, &owner.id, "Maths", "June 2019 Paper 1").await;
+    insert_completion(conn, test_id, 45, 80, Some("Owned")).await;
+    let completion_id = completions::table
+        .filter(completions::test_id.eq(test_id))
+        .select(completions::id)
+        .first(conn)
+        .await
+        .unwrap();
+
+    (owner.id, other.id, completion_id)
+}
+
+/// A completion for updating with.
+fn new_data() -> CompletionData {
+    CompletionData {
+        achieved_mark: 70,
+        total_marks: 80,
+        date: None,
+        comments: None,
+    }
+}
+
+#[tokio::test]
+async fn owned_completion_is_returned() {
+    let conn = &mut test_connection().await;
+    let (owner, _, completion_id) = setup(conn).await;
+
+    let completion = assert_completion_owner(conn, completion_id, &owner)
+        .await
+        .unwrap();
+    assert_eq!(completion.id, completion_id);
+    assert_eq!(completion.achieved_mark, 45);
+    assert_eq!(completion.total_marks, 80);
+    assert_eq!(completion.comments.as_deref(), Some("Owned"));
+}
+
+#[tokio::test]
+async fn foreign_and_nonexistent_completions_are_indistinguishable() {
+    let conn = &mut test_connection().await;
+    let (_, other, completion_id) = setup(conn).await;
+
+    let foreign = assert_completion_owner(conn, completion_id, &other).await;
+    let nonexistent = assert_completion_owner(conn, NONEXISTENT_ID, &other).await;
+    assert_eq!(foreign, Err(Error::DatabaseError(DieselError::NotFound)));
+    assert_eq!(foreign, nonexistent);
+}
+
+#[tokio::test]
+async fn foreign_completions_cant_be_changed() {
+    let conn = &mut test_connection().await;
+    let (owner, other, completion_id) = setup(conn).await;
+    let before: Completion = assert_completion_owner(conn, completion_id, &owner)
+        .await
+        .unwrap();
+
+    let foreign = update_completion(conn, &other, completion_id, new_data()).await;
+    let nonexistent = update_completion(conn, &other, NONEXISTENT_ID, new_data()).await;
+    assert_eq!(foreign, Err(Error::DatabaseError(DieselError::NotFound)));
+    assert_eq!(foreign, nonexistent);
+
+    let foreign = delete_completion(conn, &other, completion_id).await;
+    let nonexistent = delete_completion(conn, &other, NONEXISTENT_ID).await;
+    assert_eq!(foreign, Err(Error::DatabaseError(DieselError::NotFound)));
+    assert_eq!(foreign, nonexistent);
+
+    let after = assert_completion_owner(conn, completion_id, &owner)
+        .await
+        .unwrap();
+    assert_eq!(before, after);
+}
+
+/// Ask to update a completion as the given user.
+fn update_msg(token: &str, user_id: &str, completion_id: i32) -> ClientToServerMsg {
+    ClientToServerMsg::UpdateCompletion {
+        token: token.to_string(),
+        user_id: user_id.to_string(),
+        completion_id,
+        completion: new_data(),
+    }
+}
+
+/// Ask to delete a completion as the given user.
+fn delete_msg(token: &str, user_id: &str, completion_id: i32) -> ClientToServerMsg {
+    ClientToServerMsg::DeleteCompletion {
+        token: token.to_string(),
+        user_id: user_id.to_string(),
+        completion_id,
+    }
+}
+
+#[tokio::test]
+async fn owner_can_update_and_delete_through_messages() {
+    let mut db = TestDb::new().await;
+    let (owner, _, completion_id) = setup(&mut db).await;
+    let token = create_session(&mut db, &owner, false).await.unwrap();
+
+    let response = handle_message(&mut db, update_msg(&token, &owner, completion_id), None).await;
+    assert_eq!(
+        response,
+        ServerToClientMsg::UpdateCompletionResponse(Ok(()))
+    );
+    let updated = assert_completion_owner(&mut db, completion_id, &owner)
+        .await
+        .unwrap();
+    assert_eq!((updated.achieved_mark, updated.total_marks), (70, 80));
+
+    let response = handle_message(&mut db, delete_msg(&token, &owner, completion_id), None).await;
+    assert_eq!(
+        response,
+        ServerToClientMsg::DeleteCompletionResponse(Ok(()))
+    );
+    assert_eq!(
+        assert_completion_owner(&mut db, completion_id, &owner).await,
+        Err(Error::DatabaseError(DieselError::NotFound))
+    );
+}
+
+#[tokio::test]
+async fn foreign_completions_cant_be_changed_through_messages() {
+    let mut db = TestDb::new().await;
+    let (owner, other, completion_id) = setup(&mut db).await;
+    let token = create_session(&mut db, &other, false).await.unwrap();
+    let before = assert_completion_owner(&mut db, completion_id, &owner)
+        .await
+        .unwrap();
+    let not_found = Err(Error::DatabaseError(DieselError::NotFound));
+
+    for completion_id in [completion_id, NONEXISTENT_ID] {
+        let response =
+            handle_message(&mut db, update_msg(&token, &other, completion_id), None).await;
+        assert_eq!(
+            response,
+            ServerToClientMsg::UpdateCompletionResponse(not_found.clone())
+        );
+
+        let response =
+            handle_message(&mut db, delete_msg(&token, &other, completion_id), None).await;
+        assert_eq!(
+            response,
+            ServerToClientMsg::DeleteCompletionResponse(not_found.clone())
+        );
+    }
+
+    // Claiming to be the owner doesn't help without the owner's session
+    let response = handle_message(&mut db, delete_msg(&token, &owner, completion_id), None).await;
+    assert_eq!(
+        response,
+        ServerToClientMsg::DeleteCompletionResponse(Err(Error::Unauthorized))
+    );
+
+    let after = assert_completion_owner(&mut db, completion_id, &owner)
+        .await
+        .unwrap();
+    assert_eq!(before, after);
+}
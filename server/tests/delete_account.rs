@@ -0,0 +1,77 @@
+//! Tests for users deleting their own accounts.
+
+mod common;
+
+use common::{insert_completion, insert_test, test_connection};
+use test_tracker_server::{
+    passwords::{add_new_user, delete_account, validate_user},
+    sessions::{authorize, start_session},
+    tests_and_completions::get_all_tests_and_completions_for_user,
+};
+use test_tracker_shared::Error as SharedError;
+
+#[tokio::test]
+async fn deleting_an_account_removes_everything_of_theirs() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let other = add_new_user(conn, "other_user", "correct horse battery")
+        .await
+        .unwrap();
+    let session = start_session(conn, user.clone(), false).await.unwrap();
+    let test_id = insert_test(conn, &user.id, "Maths", "June 2019").await;
+    insert_completion(conn, test_id, 40, 50, None).await;
+    let other_test_id = insert_test(conn, &other.id, "Maths", "June 2019").await;
+    insert_completion(conn, other_test_id, 30, 50, None).await;
+
+    delete_account(conn, &user.id, "correct horse battery")
+        .await
+        .unwrap();
+
+    assert!(validate_user(conn, "test_user", "correct horse battery")
+        .await
+        .is_err());
+    assert_eq!(
+        authorize(conn, &session.token, &user.id).await,
+        Err(SharedError::Unauthorized)
+    );
+    assert!(get_all_tests_and_completions_for_user(conn, &user.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let others = get_all_tests_and_completions_for_user(conn, &other.id)
+        .await
+        .unwrap();
+    assert_eq!(others.len(), 1);
+    assert_eq!(others[0].1.len(), 1);
+}
+
+#[tokio::test]
+async fn wrong_password_keeps_the_account() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    insert_test(conn, &user.id, "Maths", "June 2019").await;
+
+    let error = SharedError::from(
+        delete_account(conn, &user.id, "incorrect horse battery")
+            .await
+            .unwrap_err(),
+    );
+    assert_eq!(error, SharedError::InvalidPassword);
+
+    assert_eq!(
+        validate_user(conn, "test_user", "correct horse battery")
+            .await
+            .unwrap(),
+        user
+    );
+    let tests = get_all_tests_and_completions_for_user(conn, &user.id)
+        .await
+        .unwrap();
+    assert_eq!(tests.len(), 1);
+    assert_eq!(tests[0].0.date_or_id, "June 2019");
+}
@@ -0,0 +1,214 @@
+//! Tests for the unique index on each user's tests, and the migration that merged existing
+//! duplicates before adding it.
+
+mod common;
+
+use common::{insert_completion, insert_test, test_connection};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl, SimpleAsyncConnection,
+};
+use test_tracker_server::{
+    db::{
+        schema::{completions, tests, tombstones},
+        DbConnection,
+    },
+    import::import_csv,
+    passwords::add_new_user,
+    tests_and_completions::insert_test_and_completions,
+};
+use test_tracker_shared::{Error as SharedError, TestData};
+
+/// The migration that merges duplicate tests and adds the unique index.
+#[cfg(not(feature = "sqlite"))]
+const MIGRATION: &str = include_str!("../migrations/2026-10-15-000006_unique_tests/up.sql");
+
+/// The migration that merges duplicate tests and adds the unique index.
+#[cfg(feature = "sqlite")]
+const MIGRATION: &str = include_str!("../migrations-sqlite/2026-10-15-000006_unique_tests/up.sql");
+
+/// A test with just a subject and date or ID.
+fn test_data(subject: &str, date_or_id: &str) -> TestData {
+    TestData {
+        subject: subject.to_string(),
+        topic: None,
+        date_or_id: date_or_id.to_string(),
+        qualification_level: None,
+        exam_board: None,
+        paper_link: None,
+        mark_scheme_link: None,
+        comments: None,
+    }
+}
+
+/// Insert a test with no completions in its own transaction, since a failed statement would abort
+/// the test transaction on PostgreSQL.
+async fn insert(
+    conn: &mut DbConnection,
+    user_id: &str,
+    subject: &str,
+    date_or_id: &str,
+) -> Result<i32, SharedError> {
+    conn.transaction::<_, SharedError, _>(|conn| {
+        insert_test_and_completions(conn, user_id, test_data(subject, date_or_id), vec![])
+            .scope_boxed()
+    })
+    .await
+}
+
+/// The IDs of the tests for the given subject and date or ID, in order.
+async fn test_ids(
+    conn: &mut DbConnection,
+    user_id: &str,
+    subject: &str,
+    date_or_id: &str,
+) -> Vec<i32> {
+    tests::table
+        .filter(tests::user_id.eq(user_id))
+        .filter(tests::subject.eq(subject))
+        .filter(tests::date_or_id.eq(date_or_id))
+        .select(tests::id)
+        .order_by(tests::id)
+        .load(conn)
+        .await
+        .unwrap()
+}
+
+/// How many completions the given test has.
+async fn completion_count(conn: &mut DbConnection, test_id: i32) -> i64 {
+    completions::table
+        .filter(completions::test_id.eq(test_id))
+        .count()
+        .get_result(conn)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn inserting_a_duplicate_test_fails() {
+    let conn = &mut test_connection().await;
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    let bob = add_new_user(conn, "bob", "correct horse battery")
+        .await
+        .unwrap();
+
+    insert(conn, &alice.id, "Maths", "Paper 1").await.unwrap();
+
+    assert_eq!(
+        insert(conn, &alice.id, "Maths", "Paper 1").await,
+        Err(SharedError::DuplicateTest {
+            subject: "Maths".to_string(),
+            date_or_id: "Paper 1".to_string(),
+        })
+    );
+
+    // Only the exact same subject and date or ID for the same user clash
+    insert(conn, &alice.id, "Maths", "Paper 2").await.unwrap();
+    insert(conn, &alice.id, "Physics", "Paper 1").await.unwrap();
+    insert(conn, &bob.id, "Maths", "Paper 1").await.unwrap();
+}
+
+#[tokio::test]
+async fn importing_twice_reports_duplicates() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let csv = include_str!("fixtures/import_valid.csv");
+
+    let first = import_csv(conn, &user.id, csv, false).await.unwrap();
+    assert_eq!(first.errors, vec![]);
+
+    let second = import_csv(conn, &user.id, csv, false).await.unwrap();
+    assert_eq!(second.tests_imported, 0);
+    assert_eq!(second.errors.len(), first.tests_imported);
+    assert!(second
+        .errors
+        .iter()
+        .all(|error| error.message.contains("already")));
+}
+
+#[tokio::test]
+async fn migration_merges_existing_duplicates() {
+    let conn = &mut test_connection().await;
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    let bob = add_new_user(conn, "bob", "correct horse battery")
+        .await
+        .unwrap();
+
+    // Pretend that the unique index was never added, so that we can make some duplicates
+    conn.batch_execute("DROP INDEX tests_user_id_subject_date_or_id")
+        .await
+        .unwrap();
+
+    // The one with the most completions is kept, and gets the others' completions and details
+    let empty = insert_test(conn, &alice.id, "Maths", "Paper 1").await;
+    let most = insert_test(conn, &alice.id, "Maths", "Paper 1").await;
+    insert_completion(conn, most, 45, 80, None).await;
+    insert_completion(conn, most, 62, 80, None).await;
+    let fewer = insert_test(conn, &alice.id, "Maths", "Paper 1").await;
+    insert_completion(conn, fewer, 70, 80, Some("From the duplicate")).await;
+    diesel::update(tests::table.find(fewer))
+        .set(tests::topic.eq("Algebra"))
+        .execute(conn)
+        .await
+        .unwrap();
+
+    // Without any completions, the oldest is kept
+    let oldest = insert_test(conn, &alice.id, "Physics", "Mock Set 1").await;
+    let newest = insert_test(conn, &alice.id, "Physics", "Mock Set 1").await;
+
+    // Tests that aren't duplicated are left alone
+    let single = insert_test(conn, &alice.id, "English", "Mock Set 1").await;
+    let bobs = insert_test(conn, &bob.id, "Maths", "Paper 1").await;
+    insert_completion(conn, bobs, 10, 80, None).await;
+
+    conn.batch_execute(MIGRATION).await.unwrap();
+
+    assert_eq!(
+        test_ids(conn, &alice.id, "Maths", "Paper 1").await,
+        vec![most]
+    );
+    assert_eq!(completion_count(conn, most).await, 3);
+    let topic: Option<String> = tests::table
+        .find(most)
+        .select(tests::topic)
+        .first(conn)
+        .await
+        .unwrap();
+    assert_eq!(topic.as_deref(), Some("Algebra"));
+
+    assert_eq!(
+        test_ids(conn, &alice.id, "Physics", "Mock Set 1").await,
+        vec![oldest]
+    );
+    assert_eq!(
+        test_ids(conn, &alice.id, "English", "Mock Set 1").await,
+        vec![single]
+    );
+    assert_eq!(
+        test_ids(conn, &bob.id, "Maths", "Paper 1").await,
+        vec![bobs]
+    );
+    assert_eq!(completion_count(conn, bobs).await, 1);
+
+    // Clients are told to drop the merged duplicates on their next sync
+    let mut deleted: Vec<i32> = tombstones::table
+        .filter(tombstones::user_id.eq(&alice.id))
+        .select(tombstones::test_id)
+        .load(conn)
+        .await
+        .unwrap();
+    deleted.sort();
+    assert_eq!(deleted, vec![empty, fewer, newest]);
+
+    // And the index is back
+    assert!(matches!(
+        insert(conn, &alice.id, "Maths", "Paper 1").await,
+        Err(SharedError::DuplicateTest { .. })
+    ));
+}
@@ -0,0 +1,238 @@
+//! End-to-end tests of the library, from creating an account to reading back tests and
+//! completions, including the ways that it can go wrong.
+
+mod common;
+
+use chrono::{NaiveDate, Utc};
+use common::{TestDb, PASSWORD};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use test_tracker_server::{
+    db::{models::NewUser, schema::users, UniqueConstraint},
+    passwords::{add_new_user, validate_user, NewUserError},
+    tests_and_completions::{get_all_tests_and_completions_for_user, insert_test_and_completions},
+};
+use test_tracker_shared::{CompletionData, Error as SharedError, TestAndCompletions, TestData};
+
+/// Sort tests and completions so that they can be compared, since the order isn't guaranteed.
+fn sorted(mut tests_and_completions: Vec<TestAndCompletions>) -> Vec<TestAndCompletions> {
+    for (_, completions) in &mut tests_and_completions {
+        completions.sort_by_key(|completion| completion.achieved_mark);
+    }
+    tests_and_completions.sort_by(|(a, _), (b, _)| a.date_or_id.cmp(&b.date_or_id));
+    tests_and_completions
+}
+
+#[tokio::test]
+async fn sign_up_log_in_and_read_back_tests() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    let logged_in = validate_user(&mut db, "Test_User", PASSWORD).await.unwrap();
+    assert_eq!(logged_in, user);
+
+    let paper_1 = db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+    db.completion(paper_1, 45, 80).await;
+    db.completion(paper_1, 62, 80).await;
+    let paper_2 = db.test(&user.id, "Maths", "June 2019 Paper 2").await;
+    db.completion(paper_2, 50, 80).await;
+
+    let tests_and_completions = sorted(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+    );
+    let summary: Vec<(&str, Vec<i32>)> = tests_and_completions
+        .iter()
+        .map(|(test, completions)| {
+            (
+                test.date_or_id.as_str(),
+                completions.iter().map(|c| c.achieved_mark).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("June 2019 Paper 1", vec![45, 62]),
+            ("June 2019 Paper 2", vec![50]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn completions_without_dates_are_read_back() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    let dated = NaiveDate::from_ymd_opt(2024, 3, 18).unwrap();
+    insert_test_and_completions(
+        &mut db,
+        &user.id,
+        TestData {
+            subject: "Maths".to_string(),
+            topic: None,
+            date_or_id: "June 2019 Paper 1".to_string(),
+            qualification_level: None,
+            exam_board: None,
+            paper_link: None,
+            mark_scheme_link: None,
+            comments: None,
+        },
+        vec![
+            CompletionData {
+                achieved_mark: 45,
+                total_marks: 80,
+                date: None,
+                comments: None,
+            },
+            CompletionData {
+                achieved_mark: 62,
+                total_marks: 80,
+                date: Some(dated),
+                comments: None,
+            },
+        ],
+    )
+    .await
+    .unwrap();
+
+    let tests_and_completions = sorted(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+    );
+    let dates: Vec<_> = tests_and_completions[0]
+        .1
+        .iter()
+        .map(|completion| completion.date)
+        .collect();
+    assert_eq!(dates, vec![None, Some(dated)]);
+}
+
+#[tokio::test]
+async fn every_test_field_is_read_back() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    let test = TestData {
+        subject: "Physics".to_string(),
+        topic: Some("Fields".to_string()),
+        date_or_id: "Mock Set 2".to_string(),
+        qualification_level: Some("A Level".to_string()),
+        exam_board: Some("OCR".to_string()),
+        paper_link: Some("https://example.com/paper.pdf".to_string()),
+        mark_scheme_link: Some("https://example.com/mark-scheme.pdf".to_string()),
+        comments: Some("Forgot the units twice".to_string()),
+    };
+    insert_test_and_completions(&mut db, &user.id, test.clone(), vec![])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+        vec![(test, vec![])]
+    );
+}
+
+#[tokio::test]
+async fn duplicate_usernames_are_rejected() {
+    let mut db = TestDb::new().await;
+    db.user("test_user").await;
+
+    // A failed statement aborts the whole transaction on PostgreSQL, so try in a savepoint
+    let result = db
+        .transaction::<_, NewUserError, _>(|conn| {
+            add_new_user(conn, "TEST_USER", "another good password").scope_boxed()
+        })
+        .await;
+
+    match result {
+        Err(e @ NewUserError::UsernameTaken) => {
+            assert_eq!(SharedError::from(e), SharedError::UsernameTaken)
+        }
+        other => panic!("expected the username to be taken, got {other:?}"),
+    }
+
+    // The original account is untouched
+    assert!(validate_user(&mut db, "test_user", PASSWORD).await.is_ok());
+}
+
+#[tokio::test]
+async fn unique_violations_are_classified_by_constraint() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    // Each insert fails on its own constraint, so try them in savepoints
+    for (id, username, expected) in [
+        (user.id.clone(), "someone_else", UniqueConstraint::UserId),
+        (
+            "00000000-0000-4000-8000-000000000000".to_string(),
+            "test_user",
+            UniqueConstraint::Username,
+        ),
+    ] {
+        let error = db
+            .transaction::<(), _, _>(|conn| {
+                async move {
+                    diesel::insert_into(users::table)
+                        .values(&NewUser {
+                            id,
+                            username: username.to_string(),
+                            hashed_password: String::new(),
+                            created_at: Utc::now(),
+                        })
+                        .execute(conn)
+                        .await
+                        .map(|_| ())
+                }
+                .scope_boxed()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(UniqueConstraint::violated_by(&error), Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn wrong_passwords_and_unknown_users_are_rejected() {
+    let mut db = TestDb::new().await;
+    db.user("test_user").await;
+
+    let wrong_password = validate_user(&mut db, "test_user", "not the password").await;
+    assert_eq!(
+        wrong_password.map_err(SharedError::from),
+        Err(SharedError::InvalidPassword)
+    );
+
+    let unknown_user = validate_user(&mut db, "nobody", PASSWORD).await;
+    assert!(matches!(
+        unknown_user,
+        Err(NewUserError::DbError(diesel::result::Error::NotFound))
+    ));
+}
+
+#[tokio::test]
+async fn other_users_data_is_invisible() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let bob = db.user("bob").await;
+
+    let alices_test = db.test(&alice.id, "Maths", "June 2019 Paper 1").await;
+    db.completion(alices_test, 45, 80).await;
+    let bobs_test = db.test(&bob.id, "Physics", "Mock Set 1").await;
+    db.completion(bobs_test, 30, 60).await;
+
+    let alices = get_all_tests_and_completions_for_user(&mut db, &alice.id)
+        .await
+        .unwrap();
+    assert_eq!(alices.len(), 1);
+    assert_eq!(alices[0].0.subject, "Maths");
+
+    let bobs = get_all_tests_and_completions_for_user(&mut db, &bob.id)
+        .await
+        .unwrap();
+    assert_eq!(bobs.len(), 1);
+    assert_eq!(bobs[0].0.subject, "Physics");
+}
@@ -0,0 +1,122 @@
+//! Tests for the CSV export.
+
+mod common;
+
+use common::{insert_completion, insert_test, test_connection};
+use diesel_async::RunQueryDsl;
+use futures_util::{StreamExt, TryStreamExt};
+use std::pin::pin;
+use test_tracker_server::{
+    db::{models::NewCompletion, schema::completions},
+    export::{encode_headers, encode_row, stream_csv, stream_rows, ExportRow, CHUNK_SIZE},
+    passwords::add_new_user,
+};
+
+/// How many tests the big account in [`big_exports_are_streamed_in_bounded_chunks`] has.
+const BIG_TESTS: i32 = 50;
+
+/// How many completions each of those tests has.
+const BIG_COMPLETIONS_PER_TEST: i32 = 1_000;
+
+/// The longest that a single row of the big export can be, which is much more than it needs.
+const MAX_ROW_LENGTH: usize = 256;
+
+#[tokio::test]
+async fn export_round_trips_through_csv() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let maths = insert_test(conn, &user.id, "Maths", "June 2019, Paper 1").await;
+    insert_completion(conn, maths, 45, 80, Some("Ran out of time, \"again\"")).await;
+    insert_completion(conn, maths, 62, 80, Some("Better\nStill slow on Q7")).await;
+    let physics = insert_test(conn, &user.id, "Physics", "Mock Set 1").await;
+    insert_completion(conn, physics, 0, 60, None).await;
+
+    let rows: Vec<ExportRow> = stream_rows(conn, &user.id)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+
+    let mut csv = encode_headers().unwrap();
+    for row in &rows {
+        csv.extend(encode_row(row).unwrap());
+    }
+
+    let parsed: Vec<ExportRow> = csv::Reader::from_reader(csv.as_slice())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(parsed, rows);
+
+    assert_eq!(parsed[0].date_or_id, "June 2019, Paper 1");
+    assert_eq!(
+        parsed[0].comments.as_deref(),
+        Some("Ran out of time, \"again\"")
+    );
+    assert_eq!(
+        parsed[1].comments.as_deref(),
+        Some("Better\nStill slow on Q7")
+    );
+    assert_eq!(parsed[0].percentage, Some(56.25));
+    assert_eq!(parsed[2].percentage, Some(0.0));
+}
+
+#[tokio::test]
+async fn big_exports_are_streamed_in_bounded_chunks() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    for test in 0..BIG_TESTS {
+        let test_id = insert_test(conn, &user.id, "Maths", &format!("Practice paper {test}")).await;
+        let completions: Vec<NewCompletion> = (0..BIG_COMPLETIONS_PER_TEST)
+            .map(|completion| NewCompletion {
+                achieved_mark: completion % 81,
+                total_marks: 80,
+                date: None,
+                comments: Some(format!("Attempt {completion}")),
+                test_id,
+            })
+            .collect();
+        diesel::insert_into(completions::table)
+            .values(&completions)
+            .execute(conn)
+            .await
+            .unwrap();
+    }
+
+    let mut chunks = pin!(stream_csv(conn, &user.id).await.unwrap());
+    let mut chunk_count = 0;
+    let mut line_count = 0;
+    let mut last_line = Vec::new();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.unwrap();
+        assert!(chunk.len() <= CHUNK_SIZE + MAX_ROW_LENGTH);
+        if chunk_count == 0 {
+            assert!(chunk.starts_with(b"subject,topic,date_or_id,"));
+        }
+
+        chunk_count += 1;
+        line_count += chunk.iter().filter(|&&b| b == b'\n').count();
+        last_line = chunk
+            .rsplit(|&b| b == b'\n')
+            .nth(1)
+            .unwrap_or_default()
+            .to_vec();
+    }
+
+    let completions = (BIG_TESTS * BIG_COMPLETIONS_PER_TEST) as usize;
+    assert_eq!(line_count, completions + 1);
+    // Every row is at least 20 bytes, so this many chunks means that it was sent progressively
+    assert!(chunk_count > completions * 20 / CHUNK_SIZE);
+    assert_eq!(
+        String::from_utf8(last_line).unwrap(),
+        "Maths,,Practice paper 9,,27,80,33.75,,Attempt 999"
+    );
+}
@@ -0,0 +1,39 @@
+//! Tests that the sample data from the shared crate's `fixtures` feature can be inserted into the
+//! database, which the benchmarks rely on.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::tests_and_completions::{
+    get_all_tests_and_completions_for_user, insert_test_and_completions,
+};
+use test_tracker_shared::fixtures::sample_dataset;
+
+#[tokio::test]
+async fn sample_dataset_can_be_inserted_for_one_user() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let dataset = sample_dataset(60, 2);
+
+    for (test, completions) in dataset.clone() {
+        insert_test_and_completions(&mut db, &user.id, test, completions)
+            .await
+            .unwrap();
+    }
+
+    let mut stored = get_all_tests_and_completions_for_user(&mut db, &user.id)
+        .await
+        .unwrap();
+    let mut expected = dataset;
+    stored.sort_by(|a, b| {
+        a.0.date_or_id
+            .cmp(&b.0.date_or_id)
+            .then(a.0.subject.cmp(&b.0.subject))
+    });
+    expected.sort_by(|a, b| {
+        a.0.date_or_id
+            .cmp(&b.0.date_or_id)
+            .then(a.0.subject.cmp(&b.0.subject))
+    });
+    assert_eq!(stored, expected);
+}
@@ -0,0 +1,116 @@
+//! Tests for [`GetTest`](ClientToServerMsg::GetTest), which gets a single test for its page in the
+//! client.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::{api::handle_message, sessions::start_session};
+use test_tracker_shared::{
+    error::DieselError, ClientToServerMsg, Error as SharedError, ServerToClientMsg,
+};
+
+/// Ask for the test with the given ID, as the user with the given ID and session token.
+fn get_test(token: &str, user_id: &str, test_id: i32) -> ClientToServerMsg {
+    ClientToServerMsg::GetTest {
+        token: token.to_string(),
+        user_id: user_id.to_string(),
+        test_id,
+    }
+}
+
+#[tokio::test]
+async fn owner_gets_the_test_with_its_completions() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let test_id = db.test(&alice.id, "Maths", "June 2019 Paper 1").await;
+    db.completion(test_id, 45, 80).await;
+    db.completion(test_id, 60, 80).await;
+    db.test(&alice.id, "Physics", "June 2019 Paper 1").await;
+    let session = start_session(&mut db, alice.clone(), false).await.unwrap();
+
+    let response =
+        handle_message(&mut db, get_test(&session.token, &alice.id, test_id), None).await;
+    let ServerToClientMsg::TestResponse(Ok(test)) = response else {
+        panic!("Expected the test, got {response:?}");
+    };
+    assert_eq!(test.id, test_id);
+    assert_eq!(test.test.subject, "Maths");
+    assert_eq!(test.test.date_or_id, "June 2019 Paper 1");
+    let marks: Vec<i32> = test
+        .completions
+        .iter()
+        .map(|(_, completion)| completion.achieved_mark)
+        .collect();
+    assert_eq!(marks, vec![45, 60]);
+    assert_eq!(test.best_completion_id, Some(test.completions[1].0));
+}
+
+#[tokio::test]
+async fn test_is_the_same_as_in_a_sync() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let test_id = db.test(&alice.id, "Maths", "June 2019 Paper 1").await;
+    db.completion(test_id, 45, 80).await;
+    let session = start_session(&mut db, alice.clone(), false).await.unwrap();
+
+    let sync = handle_message(
+        &mut db,
+        ClientToServerMsg::GetTestsAndCompletions {
+            token: session.token.clone(),
+            user_id: alice.id.clone(),
+            since: None,
+            sort: None,
+        },
+        None,
+    )
+    .await;
+    let ServerToClientMsg::TestsAndCompletionsForUser(Ok(sync)) = sync else {
+        panic!("Expected Alice's tests, got {sync:?}");
+    };
+
+    let response =
+        handle_message(&mut db, get_test(&session.token, &alice.id, test_id), None).await;
+    assert_eq!(
+        response,
+        ServerToClientMsg::TestResponse(Ok(sync.changed[0].clone()))
+    );
+}
+
+#[tokio::test]
+async fn foreign_test_is_the_same_as_a_missing_one() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let bob = db.user("bob_smith").await;
+    let bobs_test = db.test(&bob.id, "Maths", "June 2019 Paper 1").await;
+    let session = start_session(&mut db, alice.clone(), false).await.unwrap();
+
+    let not_found =
+        ServerToClientMsg::TestResponse(Err(SharedError::DatabaseError(DieselError::NotFound)));
+    assert_eq!(
+        handle_message(
+            &mut db,
+            get_test(&session.token, &alice.id, bobs_test),
+            None
+        )
+        .await,
+        not_found
+    );
+    assert_eq!(
+        handle_message(&mut db, get_test(&session.token, &alice.id, i32::MAX), None).await,
+        not_found
+    );
+}
+
+#[tokio::test]
+async fn foreign_user_id_is_unauthorized() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let bob = db.user("bob_smith").await;
+    let bobs_test = db.test(&bob.id, "Maths", "June 2019 Paper 1").await;
+    let session = start_session(&mut db, alice, false).await.unwrap();
+
+    assert_eq!(
+        handle_message(&mut db, get_test(&session.token, &bob.id, bobs_test), None).await,
+        ServerToClientMsg::TestResponse(Err(SharedError::Unauthorized))
+    );
+}
@@ -0,0 +1,59 @@
+//! Tests that the health check still reports the database as reachable while every connection in
+//! the pool is busy, since a saturated pool doesn't mean that the database is down.
+
+mod common;
+
+use axum::{
+    body::to_bytes,
+    http::{Method, StatusCode},
+};
+use common::{request, send};
+use std::{sync::Once, time::Duration};
+use test_tracker_server::{
+    config::config,
+    db::{connection, database_is_reachable},
+};
+use test_tracker_shared::paths;
+
+/// The size of the pool for this test binary, which is small so that it's easy to use up.
+const POOL_SIZE: usize = 2;
+
+/// Guards setting the pool size, which has to happen before anything reads the config.
+static SMALL_POOL: Once = Once::new();
+
+/// Set the pool size in the config to [`POOL_SIZE`].
+fn use_small_pool() {
+    SMALL_POOL.call_once(|| {
+        std::env::set_var("MAX_CONCURRENT_REQUESTS", POOL_SIZE.to_string());
+    });
+    assert_eq!(config().max_concurrent_requests, POOL_SIZE);
+}
+
+#[tokio::test]
+async fn health_check_works_while_the_pool_is_saturated() {
+    use_small_pool();
+
+    let mut held = Vec::new();
+    for _ in 0..POOL_SIZE {
+        held.push(connection().await.unwrap());
+    }
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), connection())
+            .await
+            .is_err(),
+        "The pool should be saturated"
+    );
+
+    assert!(database_is_reachable().await);
+
+    let response = send(request(Method::GET, paths::HEALTH, "")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(
+        body.contains(r#""database":true"#),
+        "Expected the database to be reachable, got {body}"
+    );
+
+    drop(held);
+}
@@ -0,0 +1,101 @@
+//! Tests for importing tests and completions from CSV.
+
+mod common;
+
+use common::test_connection;
+use test_tracker_server::{
+    import::{import_csv, parse_csv},
+    passwords::add_new_user,
+    tests_and_completions::get_all_tests_and_completions_for_user,
+};
+
+/// A valid CSV with quoted commas, quotes, and newlines.
+const VALID: &str = include_str!("fixtures/import_valid.csv");
+
+/// A CSV with a missing subject, a bad number, and a missing date or ID.
+const WITH_ERRORS: &str = include_str!("fixtures/import_with_errors.csv");
+
+#[test]
+fn tricky_quoting_is_parsed() {
+    let (tests, errors) = parse_csv(VALID);
+    assert_eq!(errors, vec![]);
+    assert_eq!(tests.len(), 3);
+
+    assert_eq!(tests[0].line, 2);
+    assert_eq!(tests[0].test.date_or_id, "June 2019, Paper 1");
+    assert_eq!(tests[0].completions.len(), 2);
+    assert_eq!(
+        tests[0].completions[0].comments.as_deref(),
+        Some("Ran out of time, \"again\"")
+    );
+    assert_eq!(
+        tests[0].completions[1].comments.as_deref(),
+        Some("Better\nStill slow on Q7")
+    );
+
+    assert_eq!(tests[1].line, 5);
+    assert_eq!(tests[1].completions.len(), 1);
+    assert_eq!(tests[1].completions[0].date, None);
+
+    assert_eq!(tests[2].test.topic.as_deref(), Some("Shakespeare, mostly"));
+}
+
+#[test]
+fn errors_are_reported_with_line_numbers() {
+    let (tests, errors) = parse_csv(WITH_ERRORS);
+
+    let lines: Vec<u64> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, vec![3, 4, 7]);
+    assert_eq!(errors[0].message, "missing subject");
+    assert!(errors[1].message.contains("achieved_mark"), "{errors:?}");
+    assert_eq!(errors[2].message, "missing date_or_id");
+
+    assert_eq!(tests.len(), 2);
+    assert_eq!(tests[0].test.subject, "Maths");
+    assert_eq!(tests[1].test.subject, "Physics");
+    assert_eq!(
+        tests[1].completions[0].comments.as_deref(),
+        Some("Multi\nline")
+    );
+}
+
+#[tokio::test]
+async fn lenient_import_keeps_valid_rows() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let summary = import_csv(conn, &user.id, WITH_ERRORS, false)
+        .await
+        .unwrap();
+    assert_eq!(summary.tests_imported, 2);
+    assert_eq!(summary.completions_imported, 2);
+    assert_eq!(summary.errors.len(), 3);
+
+    let imported = get_all_tests_and_completions_for_user(conn, &user.id)
+        .await
+        .unwrap();
+    assert_eq!(imported.len(), 2);
+}
+
+#[tokio::test]
+async fn strict_import_imports_nothing_on_error() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let summary = import_csv(conn, &user.id, WITH_ERRORS, true).await.unwrap();
+    assert_eq!(summary.tests_imported, 0);
+    assert_eq!(summary.errors.len(), 3);
+    assert_eq!(
+        get_all_tests_and_completions_for_user(conn, &user.id).await,
+        Ok(vec![])
+    );
+
+    let summary = import_csv(conn, &user.id, VALID, true).await.unwrap();
+    assert_eq!(summary.tests_imported, 3);
+    assert_eq!(summary.completions_imported, 4);
+    assert_eq!(summary.errors, vec![]);
+}
@@ -0,0 +1,160 @@
+//! Tests for rotating the log file by size and deleting old log files.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use test_tracker_server::logging::rotation::{RotatingFile, RotationPolicy};
+use tracing_subscriber::fmt::MakeWriter;
+use uuid::Uuid;
+
+/// A fresh empty directory for the log files of one test.
+fn temp_log_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("test-tracker-logs-{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The names of all the files in the given directory, sorted.
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Write a single line in one call, like the fmt layer writes a single event.
+fn write_line(file: &RotatingFile, line: &str) {
+    file.make_writer()
+        .write_all(format!("{line}\n").as_bytes())
+        .unwrap();
+}
+
+/// Read the given log file.
+fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name)).unwrap()
+}
+
+#[test]
+fn writing_past_the_threshold_rotates() {
+    let dir = temp_log_dir();
+    let file = RotatingFile::new(
+        &dir,
+        "server.log",
+        RotationPolicy {
+            max_file_size: 20,
+            max_files: 3,
+        },
+    )
+    .unwrap();
+
+    // Each line is 10 bytes, so two lines fill a file exactly
+    write_line(&file, "line 0001");
+    write_line(&file, "line 0002");
+    assert_eq!(file_names(&dir), vec!["server.log"]);
+
+    write_line(&file, "line 0003");
+    assert_eq!(file_names(&dir), vec!["server.log", "server.log.1"]);
+    assert_eq!(read(&dir, "server.log.1"), "line 0001\nline 0002\n");
+    assert_eq!(read(&dir, "server.log"), "line 0003\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn oldest_files_are_deleted() {
+    let dir = temp_log_dir();
+    let file = RotatingFile::new(
+        &dir,
+        "server.log",
+        RotationPolicy {
+            max_file_size: 10,
+            max_files: 2,
+        },
+    )
+    .unwrap();
+
+    for i in 1..=5 {
+        write_line(&file, &format!("line {i:04}"));
+    }
+
+    assert_eq!(
+        file_names(&dir),
+        vec!["server.log", "server.log.1", "server.log.2"]
+    );
+    assert_eq!(read(&dir, "server.log"), "line 0005\n");
+    assert_eq!(read(&dir, "server.log.1"), "line 0004\n");
+    assert_eq!(read(&dir, "server.log.2"), "line 0003\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn existing_file_counts_towards_the_threshold() {
+    let dir = temp_log_dir();
+    fs::write(dir.join("server.log"), "old line!\n").unwrap();
+    let policy = RotationPolicy {
+        max_file_size: 20,
+        max_files: 1,
+    };
+
+    let file = RotatingFile::new(&dir, "server.log", policy).unwrap();
+    write_line(&file, "new line!");
+    assert_eq!(read(&dir, "server.log"), "old line!\nnew line!\n");
+
+    write_line(&file, "newer one");
+    assert_eq!(read(&dir, "server.log.1"), "old line!\nnew line!\n");
+    assert_eq!(read(&dir, "server.log"), "newer one\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn oversized_lines_get_a_file_of_their_own() {
+    let dir = temp_log_dir();
+    let file = RotatingFile::new(
+        &dir,
+        "server.log",
+        RotationPolicy {
+            max_file_size: 10,
+            max_files: 2,
+        },
+    )
+    .unwrap();
+
+    let long_line = "x".repeat(25);
+    write_line(&file, "short");
+    write_line(&file, &long_line);
+    write_line(&file, "short");
+
+    assert_eq!(read(&dir, "server.log.2"), "short\n");
+    assert_eq!(read(&dir, "server.log.1"), format!("{long_line}\n"));
+    assert_eq!(read(&dir, "server.log"), "short\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn keeping_no_old_files_just_truncates() {
+    let dir = temp_log_dir();
+    let file = RotatingFile::new(
+        &dir,
+        "server.log",
+        RotationPolicy {
+            max_file_size: 10,
+            max_files: 0,
+        },
+    )
+    .unwrap();
+
+    write_line(&file, "line 0001");
+    write_line(&file, "line 0002");
+
+    assert_eq!(file_names(&dir), vec!["server.log"]);
+    assert_eq!(read(&dir, "server.log"), "line 0002\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,45 @@
+//! Tests for parsing log filters, which come from `$RUST_LOG`, `$RUST_LOG_FILE`, and admins.
+
+use test_tracker_server::logging::{parse_filter, DEFAULT_FILTER};
+use tracing::level_filters::LevelFilter;
+
+#[test]
+fn default_filter_is_info() {
+    assert_eq!(
+        parse_filter(DEFAULT_FILTER).unwrap().max_level_hint(),
+        Some(LevelFilter::INFO)
+    );
+}
+
+#[test]
+fn representative_filters_parse() {
+    for (directives, max_level) in [
+        ("warn", LevelFilter::WARN),
+        ("debug", LevelFilter::DEBUG),
+        ("info,test_tracker_server=debug", LevelFilter::DEBUG),
+        ("warn,tower_http=trace", LevelFilter::TRACE),
+        (
+            "info,test_tracker_server::db=debug,diesel=warn",
+            LevelFilter::DEBUG,
+        ),
+        ("off", LevelFilter::OFF),
+    ] {
+        let filter =
+            parse_filter(directives).unwrap_or_else(|e| panic!("{directives:?} should parse: {e}"));
+        assert_eq!(filter.max_level_hint(), Some(max_level), "{directives:?}");
+    }
+}
+
+#[test]
+fn invalid_filters_are_rejected() {
+    for directives in [
+        "test_tracker_server=lots",
+        "info,test_tracker_server=verbose",
+        "test_tracker_server=debug,tower_http=[",
+    ] {
+        assert!(
+            parse_filter(directives).is_err(),
+            "{directives:?} shouldn't parse"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Tests for checking the marks of completions, both in the server and in the database itself.
+
+mod common;
+
+use common::TestDb;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use test_tracker_server::{
+    db::{models::NewCompletion, schema::completions},
+    import::import_csv,
+    tests_and_completions::{
+        get_all_tests_and_completions_for_user, get_tests_and_completions_since,
+        insert_test_and_completions, update_completion,
+    },
+};
+use test_tracker_shared::{
+    fixtures::{bare_completion, bare_test},
+    policy::{check_marks, MarksPolicyError},
+    Error as SharedError,
+};
+
+/// Check that a result is a validation error for the given field.
+fn assert_invalid<T: std::fmt::Debug>(result: Result<T, SharedError>, expected_field: &str) {
+    match result {
+        Err(SharedError::Validation { field, .. }) => assert_eq!(field, expected_field),
+        other => panic!("expected an invalid {expected_field}, got {other:?}"),
+    }
+}
+
+#[test]
+fn marks_policy() {
+    assert_eq!(check_marks(0, 80), Ok(()));
+    assert_eq!(check_marks(45, 80), Ok(()));
+    assert_eq!(check_marks(80, 80), Ok(()));
+
+    assert_eq!(check_marks(0, 0), Err(MarksPolicyError::NoMarksAvailable));
+    assert_eq!(check_marks(-5, -1), Err(MarksPolicyError::NoMarksAvailable));
+    assert_eq!(check_marks(-5, 80), Err(MarksPolicyError::NegativeMark));
+    assert_eq!(check_marks(81, 80), Err(MarksPolicyError::MoreThanTotal));
+
+    assert_eq!(MarksPolicyError::NoMarksAvailable.field(), "total_marks");
+    assert_eq!(MarksPolicyError::MoreThanTotal.field(), "achieved_mark");
+}
+
+#[test]
+fn percentages() {
+    assert_eq!(bare_completion(42, 60, None).percentage(), Some(70.));
+    assert_eq!(bare_completion(0, 80, None).percentage(), Some(0.));
+    assert_eq!(bare_completion(80, 80, None).percentage(), Some(100.));
+    assert_eq!(bare_completion(0, 0, None).percentage(), None);
+}
+
+#[tokio::test]
+async fn invalid_completions_are_not_inserted() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    for (completions, field) in [
+        (
+            vec![bare_completion(45, 80, None), bare_completion(0, 0, None)],
+            "total_marks",
+        ),
+        (vec![bare_completion(-5, 80, None)], "achieved_mark"),
+        (vec![bare_completion(81, 80, None)], "achieved_mark"),
+    ] {
+        assert_invalid(
+            insert_test_and_completions(
+                &mut db,
+                &user.id,
+                bare_test("Maths", "June 2019 Paper 1"),
+                completions,
+            )
+            .await,
+            field,
+        );
+    }
+
+    // Not even the test itself was inserted
+    assert_eq!(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+        vec![]
+    );
+}
+
+#[tokio::test]
+async fn invalid_updates_are_rejected() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = insert_test_and_completions(
+        &mut db,
+        &user.id,
+        bare_test("Maths", "June 2019 Paper 1"),
+        vec![bare_completion(45, 80, None)],
+    )
+    .await
+    .unwrap();
+
+    let sync = get_tests_and_completions_since(&mut db, &user.id, None, None)
+        .await
+        .unwrap();
+    let (completion_id, _) = sync.changed[0].completions[0];
+    assert_eq!(sync.changed[0].id, test_id);
+
+    assert_invalid(
+        update_completion(
+            &mut db,
+            &user.id,
+            completion_id,
+            bare_completion(90, 80, None),
+        )
+        .await,
+        "achieved_mark",
+    );
+    assert_invalid(
+        update_completion(
+            &mut db,
+            &user.id,
+            completion_id,
+            bare_completion(0, 0, None),
+        )
+        .await,
+        "total_marks",
+    );
+
+    let tests_and_completions = get_all_tests_and_completions_for_user(&mut db, &user.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        tests_and_completions[0].1,
+        vec![bare_completion(45, 80, None)]
+    );
+}
+
+#[tokio::test]
+async fn imported_rows_with_invalid_marks_are_reported() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    let csv = concat!(
+        "subject,topic,date_or_id,exam_board,achieved_mark,total_marks,percentage,date,comments\n",
+        "Maths,,June 2019,,45,80,,,\n",
+        "Physics,,Mock Set 1,,30,0,,,\n",
+        "Physics,,Mock Set 2,,70,60,,,\n",
+    );
+    let summary = import_csv(&mut db, &user.id, csv, false).await.unwrap();
+
+    assert_eq!(summary.tests_imported, 1);
+    let lines: Vec<u64> = summary.errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, vec![3, 4]);
+    assert!(summary.errors[0].message.contains("total_marks"));
+    assert!(summary.errors[1].message.contains("achieved_mark"));
+}
+
+#[tokio::test]
+async fn the_database_rejects_invalid_marks_too() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+
+    for (achieved_mark, total_marks) in [(0, 0), (-5, 80), (81, 80)] {
+        // A failed statement aborts the whole transaction on PostgreSQL, so try in a savepoint
+        let result = db
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    diesel::insert_into(completions::table)
+                        .values(&NewCompletion {
+                            achieved_mark,
+                            total_marks,
+                            date: None,
+                            comments: None,
+                            test_id,
+                        })
+                        .execute(conn)
+                        .await
+                }
+                .scope_boxed()
+            })
+            .await;
+        assert!(
+            result.is_err(),
+            "{achieved_mark}/{total_marks} should have been rejected"
+        );
+    }
+
+    db.completion(test_id, 80, 80).await;
+}
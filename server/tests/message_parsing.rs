@@ -0,0 +1,453 @@
+//! Property tests for parsing messages from request bodies, which come straight from the network.
+//!
+//! Whatever the body is, parsing must give either a message or a clean `BadRequest` (or
+//! `UnsupportedMessage`), and never panic or hang.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use proptest::prelude::*;
+use test_tracker_server::{
+    format::Format,
+    messages::{parse_message, Route},
+    tests_and_completions::sync_cutoff,
+};
+use test_tracker_shared::{
+    ClientToServerMsg, CompletionData, Error as SharedError, TestData, TestSort,
+};
+
+/// Both formats that the server understands.
+const FORMATS: [Format; 2] = [Format::Ron, Format::Json];
+
+/// Characters that are likely to upset a parser when inserted into a valid message.
+const STRUCTURAL: &[char] = &[
+    '(', ')', '[', ']', '{', '}', ',', ':', '"', '\'', '\\', '#', '/', '*', '-', '.', 'e', '\0',
+    'é', '🦀',
+];
+
+/// Every sort that a client can ask for. [`TestSort::Unknown`] is left out, since it only comes
+/// from parsing a sort that this server doesn't know about.
+const SORTS: &[TestSort] = &[
+    TestSort::SubjectAsc,
+    TestSort::RecentlyAttempted,
+    TestSort::BestPercentageAsc,
+    TestSort::BestPercentageDesc,
+    TestSort::ExamDateAsc,
+];
+
+/// Any time that chrono can represent, including the very ends of its range.
+fn any_datetime() -> impl Strategy<Value = DateTime<Utc>> {
+    prop_oneof![
+        Just(DateTime::<Utc>::MIN_UTC),
+        Just(DateTime::<Utc>::MAX_UTC),
+        (
+            DateTime::<Utc>::MIN_UTC.timestamp()..=DateTime::<Utc>::MAX_UTC.timestamp(),
+            0u32..1_000_000_000
+        )
+            .prop_filter_map("out of range", |(secs, nanos)| Utc
+                .timestamp_opt(secs, nanos)
+                .single()),
+    ]
+}
+
+/// A time that a client might plausibly send, between 1970 and 2100.
+fn plausible_datetime() -> impl Strategy<Value = DateTime<Utc>> {
+    (0i64..4_102_444_800, 0u32..1_000_000_000).prop_filter_map("out of range", |(secs, nanos)| {
+        Utc.timestamp_opt(secs, nanos).single()
+    })
+}
+
+/// Any valid message.
+fn any_message() -> impl Strategy<Value = ClientToServerMsg> {
+    let text = || "\\PC{0,40}";
+    prop_oneof![
+        (text(), text(), any::<bool>()).prop_map(|(username, password, remember_me)| {
+            ClientToServerMsg::Authenticate {
+                username,
+                password,
+                remember_me,
+            }
+        }),
+        (text(), text(), any::<bool>()).prop_map(|(username, password, remember_me)| {
+            ClientToServerMsg::CreateUser {
+                username,
+                password,
+                remember_me,
+            }
+        }),
+        (
+            text(),
+            text(),
+            proptest::option::of(plausible_datetime()),
+            proptest::option::of(proptest::sample::select(SORTS))
+        )
+            .prop_map(|(token, user_id, since, sort)| {
+                ClientToServerMsg::GetTestsAndCompletions {
+                    token,
+                    user_id,
+                    since,
+                    sort,
+                }
+            }),
+        (text(), text(), any::<i32>()).prop_map(|(token, user_id, test_id)| {
+            ClientToServerMsg::GetTest {
+                token,
+                user_id,
+                test_id,
+            }
+        }),
+        (
+            text(),
+            text(),
+            (
+                text(),
+                proptest::option::of(text()),
+                text(),
+                proptest::option::of(text())
+            )
+        )
+            .prop_map(|(token, user_id, (subject, topic, date_or_id, comments))| {
+                ClientToServerMsg::AddTest {
+                    token,
+                    user_id,
+                    test: TestData {
+                        subject,
+                        topic,
+                        date_or_id,
+                        qualification_level: None,
+                        exam_board: None,
+                        paper_link: None,
+                        mark_scheme_link: None,
+                        comments,
+                    },
+                }
+            }),
+        (
+            text(),
+            text(),
+            any::<i32>(),
+            (text(), proptest::option::of(text()), text())
+        )
+            .prop_map(
+                |(token, user_id, test_id, (subject, exam_board, date_or_id))| {
+                    ClientToServerMsg::UpdateTest {
+                        token,
+                        user_id,
+                        test_id,
+                        test: TestData {
+                            subject,
+                            topic: None,
+                            date_or_id,
+                            qualification_level: None,
+                            exam_board,
+                            paper_link: None,
+                            mark_scheme_link: None,
+                            comments: None,
+                        },
+                    }
+                }
+            ),
+        (text(), text(), any::<i32>()).prop_map(|(token, user_id, test_id)| {
+            ClientToServerMsg::DeleteTest {
+                token,
+                user_id,
+                test_id,
+            }
+        }),
+        (
+            text(),
+            text(),
+            any::<i32>(),
+            (
+                any::<i32>(),
+                any::<i32>(),
+                proptest::option::of((2000i32..2100, 1u32..=365)),
+                proptest::option::of(text())
+            )
+        )
+            .prop_map(
+                |(token, user_id, test_id, (achieved_mark, total_marks, date, comments))| {
+                    ClientToServerMsg::AddCompletion {
+                        token,
+                        user_id,
+                        test_id,
+                        completion: CompletionData {
+                            achieved_mark,
+                            total_marks,
+                            date: date.and_then(|(year, day)| NaiveDate::from_yo_opt(year, day)),
+                            comments,
+                        },
+                    }
+                }
+            ),
+        (
+            text(),
+            text(),
+            any::<i32>(),
+            (any::<i32>(), any::<i32>(), proptest::option::of(text()))
+        )
+            .prop_map(
+                |(token, user_id, completion_id, (achieved_mark, total_marks, comments))| {
+                    ClientToServerMsg::UpdateCompletion {
+                        token,
+                        user_id,
+                        completion_id,
+                        completion: CompletionData {
+                            achieved_mark,
+                            total_marks,
+                            date: None,
+                            comments,
+                        },
+                    }
+                }
+            ),
+        (text(), text(), any::<i32>()).prop_map(|(token, user_id, completion_id)| {
+            ClientToServerMsg::DeleteCompletion {
+                token,
+                user_id,
+                completion_id,
+            }
+        }),
+        (text(), text())
+            .prop_map(|(token, user_id)| ClientToServerMsg::GetStatistics { token, user_id }),
+        (text(), text())
+            .prop_map(|(token, user_id)| ClientToServerMsg::GetAuditLog { token, user_id }),
+        (text(), text(), text(), text()).prop_map(
+            |(token, user_id, current_password, new_password)| {
+                ClientToServerMsg::ChangePassword {
+                    token,
+                    user_id,
+                    current_password,
+                    new_password,
+                }
+            }
+        ),
+        (text(), text(), text()).prop_map(|(token, user_id, password)| {
+            ClientToServerMsg::DeleteAccount {
+                token,
+                user_id,
+                password,
+            }
+        }),
+        text().prop_map(|token| ClientToServerMsg::ListUsers { token }),
+        (text(), text(), text()).prop_map(|(token, target_user_id, new_password)| {
+            ClientToServerMsg::AdminResetPassword {
+                token,
+                target_user_id,
+                new_password,
+            }
+        }),
+        (
+            text(),
+            proptest::option::of(text()),
+            proptest::option::of(text())
+        )
+            .prop_map(|(token, stdout, file)| ClientToServerMsg::SetLogFilter {
+                token,
+                stdout,
+                file,
+            }),
+        (text(), text(), "\\PC{0,200}", any::<bool>()).prop_map(|(token, user_id, csv, strict)| {
+            ClientToServerMsg::ImportCsv {
+                token,
+                user_id,
+                csv,
+                strict,
+            }
+        }),
+    ]
+}
+
+/// A way to mangle a serialized message.
+#[derive(Clone, Debug)]
+enum Mutation {
+    /// Cut the body off at this fraction of the way through.
+    Truncate(f64),
+
+    /// Delete the character at this fraction of the way through.
+    Delete(f64),
+
+    /// Insert the character at this fraction of the way through.
+    Insert(f64, char),
+
+    /// Repeat the part of the body between these two fractions of the way through.
+    Duplicate(f64, f64),
+}
+
+/// Any mutation.
+fn any_mutation() -> impl Strategy<Value = Mutation> {
+    let fraction = || 0.0..=1.0;
+    prop_oneof![
+        fraction().prop_map(Mutation::Truncate),
+        fraction().prop_map(Mutation::Delete),
+        (fraction(), proptest::sample::select(STRUCTURAL))
+            .prop_map(|(at, c)| Mutation::Insert(at, c)),
+        (fraction(), fraction())
+            .prop_map(|(a, b): (f64, f64)| Mutation::Duplicate(a.min(b), a.max(b))),
+    ]
+}
+
+/// Apply a mutation to a body, working in characters so that the result is still a valid string.
+fn mutate(body: &str, mutation: &Mutation) -> String {
+    let mut chars: Vec<char> = body.chars().collect();
+    let len = chars.len();
+    let index = |fraction: f64| ((len as f64 * fraction) as usize).min(len);
+
+    match *mutation {
+        Mutation::Truncate(at) => chars.truncate(index(at)),
+        Mutation::Delete(at) => {
+            let at = index(at);
+            if at < chars.len() {
+                chars.remove(at);
+            }
+        }
+        Mutation::Insert(at, c) => chars.insert(index(at), c),
+        Mutation::Duplicate(start, end) => {
+            let (start, end) = (index(start), index(end));
+            let repeated: Vec<char> = chars[start..end].to_vec();
+            chars.splice(end..end, repeated);
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Check that parsing the body on every route gives a message or a clean `BadRequest` or
+/// `UnsupportedMessage`.
+fn assert_parses_cleanly(body: &str) {
+    for format in FORMATS {
+        for route in Route::ALL {
+            match parse_message(format, route, body) {
+                Ok(msg) => assert!(route.accepts(&msg)),
+                Err(SharedError::BadRequest(_) | SharedError::UnsupportedMessage { .. }) => {}
+                Err(other) => panic!("expected BadRequest, got {other:?}"),
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 512,
+        timeout: 5_000,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn arbitrary_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+        assert_parses_cleanly(&String::from_utf8_lossy(&bytes));
+    }
+
+    #[test]
+    fn arbitrary_text_never_panics(body in "\\PC{0,256}") {
+        assert_parses_cleanly(&body);
+    }
+
+    #[test]
+    fn valid_messages_parse_only_on_their_route(msg in any_message()) {
+        for format in FORMATS {
+            let body = format.serialize(&msg);
+            for route in Route::ALL {
+                match parse_message(format, route, &body) {
+                    Ok(parsed) => {
+                        prop_assert!(route.accepts(&msg));
+                        prop_assert_eq!(&parsed, &msg);
+                    }
+                    Err(SharedError::BadRequest(_)) => prop_assert!(!route.accepts(&msg)),
+                    Err(other) => prop_assert!(false, "expected BadRequest, got {:?}", other),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mutated_messages_never_panic(
+        msg in any_message(),
+        mutations in proptest::collection::vec(any_mutation(), 1..8),
+    ) {
+        for format in FORMATS {
+            let body = mutations
+                .iter()
+                .fold(format.serialize(&msg), |body, mutation| mutate(&body, mutation));
+            assert_parses_cleanly(&body);
+        }
+    }
+
+    #[test]
+    fn sync_cutoff_never_panics(since in any_datetime(), now in any_datetime()) {
+        if let Some(cutoff) = sync_cutoff(Some(since), now) {
+            prop_assert!(cutoff <= since && since <= now);
+        }
+    }
+}
+
+#[test]
+fn deeply_nested_bodies_are_rejected() {
+    for depth in [100, 10_000, 1_000_000] {
+        for (open, close) in [("(", ")"), ("[", "]"), ("{", "}")] {
+            let nested = format!("{}{}", open.repeat(depth), close.repeat(depth));
+            assert_parses_cleanly(&nested);
+
+            // Nesting inside a field that would otherwise be ignored or fail late
+            let in_ron_field =
+                format!("Authenticate(username: {nested}, password: \"\", remember_me: false)");
+            assert_parses_cleanly(&in_ron_field);
+            let in_json_field =
+                format!("{{\"ListUsers\": {{\"token\": \"t\", \"extra\": {nested}}}}}");
+            assert_parses_cleanly(&in_json_field);
+        }
+    }
+}
+
+#[test]
+fn unknown_messages_are_reported_as_unsupported() {
+    let unsupported = |variant: &str| SharedError::UnsupportedMessage {
+        variant: variant.to_string(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    for (format, body) in [
+        (Format::Ron, r#"DeleteEverything(token: "t", user_id: "u")"#),
+        (Format::Ron, "  DeleteEverything"),
+        (
+            Format::Json,
+            r#"{"DeleteEverything": {"token": "t", "user_id": "u"}}"#,
+        ),
+        (Format::Json, r#""DeleteEverything""#),
+    ] {
+        for route in Route::ALL {
+            assert_eq!(
+                parse_message(format, route, body),
+                Err(unsupported("DeleteEverything")),
+                "{format:?} {body:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn malformed_known_messages_are_still_bad_requests() {
+    let long_name = format!("{}(token: \"t\")", "X".repeat(65));
+    for (format, body) in [
+        (Format::Ron, r#"ListUsers(token: 5)"#),
+        (Format::Ron, r#"ListUsers(token: "t""#),
+        (Format::Ron, long_name.as_str()),
+        (Format::Json, r#"{"ListUsers": {"token": 5}}"#),
+        (Format::Json, r#"{"ListUsers": {}, "DeleteEverything": {}}"#),
+    ] {
+        assert!(
+            matches!(
+                parse_message(format, Route::Admin, body),
+                Err(SharedError::BadRequest(_))
+            ),
+            "{format:?} {body:?}"
+        );
+    }
+}
+
+#[test]
+fn extreme_sync_times_fall_back_to_a_full_sync() {
+    let now = Utc::now();
+    assert_eq!(sync_cutoff(Some(DateTime::<Utc>::MIN_UTC), now), None);
+    assert_eq!(sync_cutoff(Some(DateTime::<Utc>::MAX_UTC), now), None);
+    assert_eq!(sync_cutoff(None, now), None);
+    assert!(sync_cutoff(Some(now), now).is_some_and(|cutoff| cutoff < now));
+}
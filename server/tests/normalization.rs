@@ -0,0 +1,141 @@
+//! Tests for normalizing the whitespace in tests, both when they're inserted and when cleaning up
+//! existing ones.
+
+mod common;
+
+use common::{insert_test, test_connection};
+use diesel::{QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{
+    admin::{normalize_existing_tests, NormalizeSummary},
+    db::{models::Test, schema::tests, DbConnection},
+    passwords::add_new_user,
+    tests_and_completions::insert_test_and_completions,
+};
+use test_tracker_shared::{Error, TestData};
+
+/// A test with messy whitespace, as if copied from an exam board's website.
+fn messy_test() -> TestData {
+    TestData {
+        subject: " Further\u{a0}Maths ".to_string(),
+        topic: Some("Core   Pure\t".to_string()),
+        date_or_id: "June 2019\u{a0} Paper 1\n".to_string(),
+        qualification_level: Some("   ".to_string()),
+        exam_board: Some("\u{a0}Edexcel".to_string()),
+        paper_link: Some(" https://example.com/paper%201.pdf ".to_string()),
+        mark_scheme_link: None,
+        comments: Some("\n Line one\n\n  Line two  \n".to_string()),
+    }
+}
+
+/// Get the test with the given ID.
+async fn get_test(conn: &mut DbConnection, id: i32) -> TestData {
+    let Test {
+        subject,
+        topic,
+        date_or_id,
+        qualification_level,
+        exam_board,
+        paper_link,
+        mark_scheme_link,
+        comments,
+        ..
+    } = tests::table
+        .find(id)
+        .select(Test::as_select())
+        .first(conn)
+        .await
+        .unwrap();
+
+    TestData {
+        subject,
+        topic,
+        date_or_id,
+        qualification_level,
+        exam_board,
+        paper_link,
+        mark_scheme_link,
+        comments,
+    }
+}
+
+#[test]
+fn normalizing_collapses_whitespace_but_only_trims_links_and_comments() {
+    let normalized = messy_test().normalized();
+    assert_eq!(
+        normalized,
+        TestData {
+            subject: "Further Maths".to_string(),
+            topic: Some("Core Pure".to_string()),
+            date_or_id: "June 2019 Paper 1".to_string(),
+            qualification_level: None,
+            exam_board: Some("Edexcel".to_string()),
+            paper_link: Some("https://example.com/paper%201.pdf".to_string()),
+            mark_scheme_link: None,
+            comments: Some("Line one\n\n  Line two".to_string()),
+        }
+    );
+    assert_eq!(normalized.normalized(), normalized);
+}
+
+#[tokio::test]
+async fn inserted_tests_are_normalized() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let id = insert_test_and_completions(conn, &user.id, messy_test(), vec![])
+        .await
+        .unwrap();
+    assert_eq!(get_test(conn, id).await, messy_test().normalized());
+}
+
+#[tokio::test]
+async fn tests_differing_only_in_whitespace_are_duplicates() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    insert_test_and_completions(conn, &user.id, messy_test().normalized(), vec![])
+        .await
+        .unwrap();
+    let result = insert_test_and_completions(conn, &user.id, messy_test(), vec![]).await;
+    assert_eq!(
+        result,
+        Err(Error::DuplicateTest {
+            subject: "Further Maths".to_string(),
+            date_or_id: "June 2019 Paper 1".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn existing_tests_are_normalized_except_for_clashes() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let messy = insert_test(conn, &user.id, "Physics\u{a0}", " Mock  Set 1").await;
+    let clean = insert_test(conn, &user.id, "Chemistry", "Mock Set 1").await;
+    let clashing = insert_test(conn, &user.id, "Chemistry ", "Mock Set 1").await;
+
+    let summary = normalize_existing_tests(conn).await.unwrap();
+    assert_eq!(
+        summary,
+        NormalizeSummary {
+            updated: 1,
+            conflicts: vec![clashing],
+        }
+    );
+
+    assert_eq!(get_test(conn, messy).await.subject, "Physics");
+    assert_eq!(get_test(conn, messy).await.date_or_id, "Mock Set 1");
+    assert_eq!(get_test(conn, clean).await.subject, "Chemistry");
+    assert_eq!(get_test(conn, clashing).await.subject, "Chemistry ");
+
+    let again = normalize_existing_tests(conn).await.unwrap();
+    assert_eq!(again.updated, 0);
+}
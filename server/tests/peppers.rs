@@ -0,0 +1,99 @@
+//! Tests for peppering password hashes, including turning peppering on for existing hashes and
+//! rotating the pepper.
+
+use test_tracker_server::{
+    config::Peppers,
+    passwords::{hash_and_salt_password, verify_password},
+};
+
+/// The password used throughout these tests.
+const PASSWORD: &str = "correct horse battery";
+
+/// Make a list of peppers from strings, with the current one first.
+fn peppers(peppers: &[&str]) -> Peppers {
+    Peppers::new(peppers.iter().map(|p| p.as_bytes().to_vec()).collect())
+}
+
+/// Hash [`PASSWORD`] with the current pepper from the given list.
+async fn hash(with: &Peppers) -> String {
+    hash_and_salt_password(PASSWORD.to_string(), with)
+        .await
+        .unwrap()
+}
+
+/// Verify a password against a hash with the given peppers, returning whether it needs rehashing,
+/// or `None` if it doesn't verify.
+async fn verify(password: &str, hash: &str, with: &Peppers) -> Option<bool> {
+    verify_password(password.to_string(), hash.to_string(), with)
+        .await
+        .ok()
+}
+
+#[tokio::test]
+async fn peppered_hashes_need_the_pepper() {
+    let pepper = peppers(&["first pepper"]);
+    let hash = hash(&pepper).await;
+
+    assert_eq!(verify(PASSWORD, &hash, &pepper).await, Some(false));
+    assert_eq!(verify("wrong password", &hash, &pepper).await, None);
+
+    // Without the pepper, the hash is useless, which is the whole point
+    assert_eq!(verify(PASSWORD, &hash, &Peppers::default()).await, None);
+    assert_eq!(
+        verify(PASSWORD, &hash, &peppers(&["another pepper"])).await,
+        None
+    );
+}
+
+#[tokio::test]
+async fn unpeppered_hashes_still_verify_and_get_upgraded() {
+    let unpeppered = hash(&Peppers::default()).await;
+    let pepper = peppers(&["first pepper"]);
+
+    // Turning on the pepper doesn't lock anyone out, but their hash should be redone
+    assert_eq!(verify(PASSWORD, &unpeppered, &pepper).await, Some(true));
+    assert_eq!(verify("wrong password", &unpeppered, &pepper).await, None);
+
+    let upgraded = hash(&pepper).await;
+    assert_eq!(verify(PASSWORD, &upgraded, &pepper).await, Some(false));
+}
+
+#[tokio::test]
+async fn pepper_rotation() {
+    let old = peppers(&["old pepper"]);
+    let rotated = peppers(&["new pepper", "old pepper"]);
+    let new_only = peppers(&["new pepper"]);
+
+    let old_hash = hash(&old).await;
+
+    // While the old pepper is still accepted, old hashes verify but need rehashing
+    assert_eq!(verify(PASSWORD, &old_hash, &rotated).await, Some(true));
+    assert_eq!(verify("wrong password", &old_hash, &rotated).await, None);
+
+    // New hashes use the new pepper, so they survive dropping the old one
+    let new_hash = hash(&rotated).await;
+    assert_eq!(verify(PASSWORD, &new_hash, &rotated).await, Some(false));
+    assert_eq!(verify(PASSWORD, &new_hash, &new_only).await, Some(false));
+
+    // Once the old pepper is dropped, anyone who didn't log in during the rotation is locked out
+    assert_eq!(verify(PASSWORD, &old_hash, &new_only).await, None);
+}
+
+#[test]
+fn pepper_files_are_parsed() {
+    let parsed = Peppers::parse(
+        "# The current pepper\n  new pepper  \n\n# Still accepted until 2027\nold pepper\n",
+    )
+    .unwrap();
+    assert_eq!(parsed, peppers(&["new pepper", "old pepper"]));
+    assert_eq!(parsed.current(), Some(b"new pepper".as_slice()));
+
+    assert_eq!(Peppers::parse(""), None);
+    assert_eq!(Peppers::parse("# Just a comment\n\n"), None);
+}
+
+#[test]
+fn peppers_are_never_printed() {
+    let debug = format!("{:?}", peppers(&["super secret"]));
+    assert!(!debug.contains("super secret"));
+}
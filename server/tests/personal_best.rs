@@ -0,0 +1,202 @@
+//! Tests for picking out the personal best completion of each test.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::tests_and_completions::{
+    get_tests_and_completions_since, insert_test_and_completions,
+};
+use test_tracker_shared::{
+    fixtures::{bare_completion, bare_test},
+    sort::best_completion,
+    CompletionData, SyncedTest,
+};
+
+/// Insert a test with the given completions for a new user, and return it as it syncs, with the
+/// IDs of its completions in the order they were given. This also checks that the client would
+/// pick the same best completion as the server.
+async fn synced(completions: Vec<CompletionData>) -> (SyncedTest, Vec<i32>) {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    insert_test_and_completions(
+        &mut db,
+        &user.id,
+        bare_test("Maths", "June 2019 Paper 1"),
+        completions,
+    )
+    .await
+    .unwrap();
+
+    let mut sync = get_tests_and_completions_since(&mut db, &user.id, None, None)
+        .await
+        .unwrap();
+    let test = sync.changed.remove(0);
+    assert_eq!(test.personal_best_id(), test.best_completion_id);
+    let ids = test.completions.iter().map(|&(id, _)| id).collect();
+    (test, ids)
+}
+
+#[tokio::test]
+async fn untried_tests_have_no_best() {
+    let (test, _) = synced(vec![]).await;
+    assert_eq!(test.best_completion_id, None);
+}
+
+#[tokio::test]
+async fn best_is_the_highest_fraction_not_the_highest_mark() {
+    let (test, ids) = synced(vec![
+        bare_completion(60, 100, Some("2024-01-01")),
+        bare_completion(45, 50, Some("2024-01-02")),
+        bare_completion(70, 100, Some("2024-01-03")),
+    ])
+    .await;
+    assert_eq!(test.best_completion_id, Some(ids[1]));
+}
+
+#[tokio::test]
+async fn ties_go_to_the_most_recent_date() {
+    let (test, ids) = synced(vec![
+        bare_completion(40, 80, Some("2024-01-03")),
+        bare_completion(50, 100, Some("2024-01-09")),
+        bare_completion(30, 60, Some("2024-01-05")),
+    ])
+    .await;
+    assert_eq!(test.best_completion_id, Some(ids[1]));
+}
+
+#[tokio::test]
+async fn ties_go_to_dated_completions_over_undated_ones() {
+    let (test, ids) = synced(vec![
+        bare_completion(40, 80, None),
+        bare_completion(50, 100, Some("2024-01-01")),
+        bare_completion(30, 60, None),
+    ])
+    .await;
+    assert_eq!(test.best_completion_id, Some(ids[1]));
+}
+
+#[tokio::test]
+async fn ties_on_the_same_date_go_to_the_newest_completion() {
+    let (test, ids) = synced(vec![
+        bare_completion(40, 80, Some("2024-01-01")),
+        bare_completion(50, 100, Some("2024-01-01")),
+        bare_completion(1, 3, Some("2024-01-01")),
+    ])
+    .await;
+    assert_eq!(test.best_completion_id, Some(ids[1]));
+
+    let (test, ids) = synced(vec![
+        bare_completion(1, 3, None),
+        bare_completion(2, 6, None),
+    ])
+    .await;
+    assert_eq!(test.best_completion_id, Some(ids[1]));
+}
+
+#[tokio::test]
+async fn zero_marks_can_still_be_the_best() {
+    let (test, ids) = synced(vec![
+        bare_completion(0, 50, Some("2024-01-02")),
+        bare_completion(0, 80, Some("2024-01-01")),
+    ])
+    .await;
+    assert_eq!(test.best_completion_id, Some(ids[0]));
+}
+
+#[tokio::test]
+async fn each_test_gets_its_own_best() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    for (date_or_id, completions) in [
+        (
+            "Paper 1",
+            vec![
+                bare_completion(10, 20, Some("2024-01-01")),
+                bare_completion(5, 20, Some("2024-01-02")),
+            ],
+        ),
+        (
+            "Paper 2",
+            vec![
+                bare_completion(5, 20, Some("2024-01-01")),
+                bare_completion(10, 20, Some("2024-01-02")),
+            ],
+        ),
+        ("Paper 3", vec![]),
+    ] {
+        insert_test_and_completions(
+            &mut db,
+            &user.id,
+            bare_test("Maths", date_or_id),
+            completions,
+        )
+        .await
+        .unwrap();
+    }
+
+    let sync = get_tests_and_completions_since(&mut db, &user.id, None, None)
+        .await
+        .unwrap();
+    let bests: Vec<Option<i32>> = sync
+        .changed
+        .iter()
+        .map(|test| test.best_completion_id)
+        .collect();
+    assert_eq!(
+        bests,
+        vec![
+            Some(sync.changed[0].completions[0].0),
+            Some(sync.changed[1].completions[1].0),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn client_side_best_of_nothing_is_none() {
+    assert_eq!(best_completion(&[]), None);
+}
+
+#[test]
+fn client_side_best_of_a_single_attempt_is_that_attempt() {
+    assert_eq!(best_completion(&[bare_completion(12, 40, None)]), Some(0));
+}
+
+#[test]
+fn client_side_best_ignores_zero_total_marks() {
+    assert_eq!(
+        best_completion(&[bare_completion(0, 0, Some("2024-01-09"))]),
+        None
+    );
+    assert_eq!(
+        best_completion(&[
+            bare_completion(0, 0, Some("2024-01-09")),
+            bare_completion(10, 40, Some("2024-01-01")),
+            bare_completion(0, 0, Some("2024-01-05")),
+        ]),
+        Some(1)
+    );
+}
+
+#[test]
+fn client_side_best_breaks_ties_by_date_then_position() {
+    assert_eq!(
+        best_completion(&[
+            bare_completion(40, 80, Some("2024-01-03")),
+            bare_completion(50, 100, Some("2024-01-09")),
+            bare_completion(30, 60, Some("2024-01-05")),
+        ]),
+        Some(1)
+    );
+    assert_eq!(
+        best_completion(&[
+            bare_completion(50, 100, Some("2024-01-01")),
+            bare_completion(40, 80, None),
+        ]),
+        Some(0)
+    );
+    assert_eq!(
+        best_completion(&[bare_completion(1, 3, None), bare_completion(2, 6, None)]),
+        Some(1)
+    );
+}
@@ -0,0 +1,62 @@
+//! Tests that [`GetTestsAndCompletions`](ClientToServerMsg::GetTestsAndCompletions) only gives the
+//! caller their own tests, whatever user ID they send.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::{api::handle_message, sessions::start_session};
+use test_tracker_shared::{ClientToServerMsg, Error as SharedError, ServerToClientMsg};
+
+/// Ask for the tests of the user with the given ID, using the given session token.
+fn get_tests(token: &str, user_id: &str) -> ClientToServerMsg {
+    ClientToServerMsg::GetTestsAndCompletions {
+        token: token.to_string(),
+        user_id: user_id.to_string(),
+        since: None,
+        sort: None,
+    }
+}
+
+#[tokio::test]
+async fn foreign_user_id_is_unauthorized() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let bob = db.user("bob_smith").await;
+    db.test(&bob.id, "Maths", "June 2019 Paper 1").await;
+    let session = start_session(&mut db, alice, false).await.unwrap();
+
+    let response = handle_message(&mut db, get_tests(&session.token, &bob.id), None).await;
+    assert_eq!(
+        response,
+        ServerToClientMsg::TestsAndCompletionsForUser(Err(SharedError::Unauthorized))
+    );
+}
+
+#[tokio::test]
+async fn own_user_id_gets_own_tests() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+    let bob = db.user("bob_smith").await;
+    let alices_test = db.test(&alice.id, "Maths", "June 2019 Paper 1").await;
+    db.test(&bob.id, "Physics", "June 2019 Paper 1").await;
+    let session = start_session(&mut db, alice.clone(), false).await.unwrap();
+
+    let response = handle_message(&mut db, get_tests(&session.token, &alice.id), None).await;
+    let ServerToClientMsg::TestsAndCompletionsForUser(Ok(sync)) = response else {
+        panic!("Expected Alice's tests, got {response:?}");
+    };
+    let ids: Vec<i32> = sync.changed.iter().map(|test| test.id).collect();
+    assert_eq!(ids, vec![alices_test]);
+}
+
+#[tokio::test]
+async fn unknown_token_is_unauthorized() {
+    let mut db = TestDb::new().await;
+    let alice = db.user("alice").await;
+
+    let response = handle_message(&mut db, get_tests("not a real token", &alice.id), None).await;
+    assert_eq!(
+        response,
+        ServerToClientMsg::TestsAndCompletionsForUser(Err(SharedError::Unauthorized))
+    );
+}
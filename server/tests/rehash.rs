@@ -0,0 +1,129 @@
+//! Tests for upgrading password hashes made with weaker Argon2 parameters when the user logs in.
+
+mod common;
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use common::{TestDb, PASSWORD};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{config::config, db::schema::users, passwords::validate_user};
+
+/// Hash [`PASSWORD`] with Argon2 parameters far weaker than the defaults, like a hash from before
+/// the parameters were raised.
+fn weak_hash() -> String {
+    let params = Params::new(Params::MIN_M_COST, Params::MIN_T_COST, 1, None).unwrap();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(
+            PASSWORD.as_bytes(),
+            SaltString::encode_b64(&[7; 16]).unwrap().as_salt(),
+        )
+        .unwrap()
+        .to_string()
+}
+
+/// Get the stored hash of the user with the given ID.
+async fn stored_hash(db: &mut TestDb, user_id: &str) -> String {
+    users::table
+        .find(user_id)
+        .select(users::hashed_password)
+        .first(&mut **db)
+        .await
+        .unwrap()
+}
+
+/// Replace the stored hash of the user with the given ID.
+async fn set_stored_hash(db: &mut TestDb, user_id: &str, hash: &str) {
+    diesel::update(users::table.find(user_id))
+        .set(users::hashed_password.eq(hash))
+        .execute(&mut **db)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn weak_hash_is_upgraded_on_login() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let weak = weak_hash();
+    set_stored_hash(&mut db, &user.id, &weak).await;
+
+    assert_eq!(
+        validate_user(&mut db, "test_user", PASSWORD).await.unwrap(),
+        user
+    );
+
+    let upgraded = stored_hash(&mut db, &user.id).await;
+    assert_ne!(upgraded, weak);
+    let params = Params::try_from(&PasswordHash::new(&upgraded).unwrap()).unwrap();
+    let current = &config().argon2_params;
+    assert_eq!(
+        (params.m_cost(), params.t_cost(), params.p_cost()),
+        (current.m_cost(), current.t_cost(), current.p_cost())
+    );
+
+    // The new hash still lets them in, and doesn't need upgrading again
+    assert_eq!(
+        validate_user(&mut db, "test_user", PASSWORD).await.unwrap(),
+        user
+    );
+    assert_eq!(stored_hash(&mut db, &user.id).await, upgraded);
+}
+
+#[tokio::test]
+async fn current_hash_is_left_alone() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let hash = stored_hash(&mut db, &user.id).await;
+
+    validate_user(&mut db, "test_user", PASSWORD).await.unwrap();
+    assert_eq!(stored_hash(&mut db, &user.id).await, hash);
+}
+
+/// Stop the stored hash from being changed, by making any update to it fail.
+#[cfg(not(feature = "sqlite"))]
+async fn freeze_hashes(db: &mut TestDb) {
+    diesel::sql_query(
+        "CREATE FUNCTION refuse_rehash() RETURNS trigger AS $$
+         BEGIN RAISE EXCEPTION 'hashes are frozen'; END;
+         $$ LANGUAGE plpgsql",
+    )
+    .execute(&mut **db)
+    .await
+    .unwrap();
+    diesel::sql_query(
+        "CREATE TRIGGER refuse_rehash BEFORE UPDATE OF hashed_password ON users
+         FOR EACH ROW EXECUTE FUNCTION refuse_rehash()",
+    )
+    .execute(&mut **db)
+    .await
+    .unwrap();
+}
+
+/// Stop the stored hash from being changed, by making any update to it fail.
+#[cfg(feature = "sqlite")]
+async fn freeze_hashes(db: &mut TestDb) {
+    diesel::sql_query(
+        "CREATE TRIGGER refuse_rehash BEFORE UPDATE OF hashed_password ON users
+         BEGIN SELECT RAISE(ABORT, 'hashes are frozen'); END",
+    )
+    .execute(&mut **db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn failed_upgrade_still_logs_in() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    set_stored_hash(&mut db, &user.id, &weak_hash()).await;
+    freeze_hashes(&mut db).await;
+
+    // The old hash is still valid, so failing to replace it mustn't lock the user out
+    assert_eq!(
+        validate_user(&mut db, "test_user", PASSWORD).await.unwrap(),
+        user
+    );
+}
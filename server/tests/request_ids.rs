@@ -0,0 +1,49 @@
+//! Tests that every response carries the `X-Request-Id` of its request, so that a response can be
+//! matched up with its log lines.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{request, send};
+use test_tracker_shared::paths;
+
+/// The header that holds the request ID.
+const REQUEST_ID: &str = "x-request-id";
+
+#[tokio::test]
+async fn responses_get_a_unique_request_id() {
+    let first = send(request(Method::GET, paths::INFO, "")).await;
+    let second = send(request(Method::GET, paths::INFO, "")).await;
+    assert_eq!(first.status(), StatusCode::OK);
+
+    let first = first.headers().get(REQUEST_ID).unwrap();
+    let second = second.headers().get(REQUEST_ID).unwrap();
+    assert!(!first.is_empty());
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn error_responses_get_a_request_id() {
+    let bad_request = send(request(Method::POST, paths::AUTH, "not a message")).await;
+    assert_eq!(bad_request.status(), StatusCode::BAD_REQUEST);
+    assert!(bad_request.headers().contains_key(REQUEST_ID));
+
+    let not_found = send(request(Method::GET, "/no/such/page", "")).await;
+    assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+    assert!(not_found.headers().contains_key(REQUEST_ID));
+}
+
+#[tokio::test]
+async fn incoming_request_id_is_kept() {
+    let mut with_id = request(Method::POST, paths::AUTH, "not a message");
+    with_id
+        .headers_mut()
+        .insert(REQUEST_ID, "from-the-proxy-1234".parse().unwrap());
+
+    let response = send(with_id).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.headers().get(REQUEST_ID).unwrap(),
+        "from-the-proxy-1234"
+    );
+}
@@ -0,0 +1,93 @@
+//! Tests that a slow request gets `503 Service Unavailable` once the request timeout is up, and
+//! that the database cancels its query rather than leaving it running.
+//!
+//! These use `pg_sleep` to make the database slow, so they only run against PostgreSQL.
+
+#![cfg(not(feature = "sqlite"))]
+
+mod common;
+
+use axum::{
+    body::to_bytes,
+    http::{Method, StatusCode},
+    middleware,
+    routing::post,
+    Router,
+};
+use diesel_async::RunQueryDsl;
+use std::{sync::Once, time::Duration};
+use test_tracker_server::{config::config, db::establish_connection, limits};
+use test_tracker_shared::{Error as SharedError, ServerToClientMsg};
+use tower::ServiceExt;
+
+/// The request timeout for this test binary, which is much shorter than the default so that the
+/// tests don't take long.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Guards setting the request timeout, which has to happen before anything reads the config.
+static SHORT_TIMEOUT: Once = Once::new();
+
+/// Set the request timeout in the config to [`TIMEOUT`].
+fn use_short_timeout() {
+    SHORT_TIMEOUT.call_once(|| {
+        std::env::set_var("REQUEST_TIMEOUT_SECS", TIMEOUT.as_secs().to_string());
+    });
+    assert_eq!(config().request_timeout, TIMEOUT);
+}
+
+#[tokio::test]
+async fn slow_request_times_out_with_503() {
+    use_short_timeout();
+
+    // A route with the same timeout as the API routes, whose handler waits on a query that takes
+    // longer than the timeout. The connection has no statement timeout, so only the middleware can
+    // stop it.
+    let app = Router::new()
+        .route(
+            "/slow",
+            post(|| async {
+                let mut conn = establish_connection().await.unwrap();
+                diesel::sql_query("SET statement_timeout = 0")
+                    .execute(&mut conn)
+                    .await
+                    .unwrap();
+                diesel::sql_query("SELECT pg_sleep(5)")
+                    .execute(&mut conn)
+                    .await
+                    .unwrap();
+                "finished"
+            }),
+        )
+        .layer(middleware::from_fn(limits::timeout));
+
+    let response = app
+        .oneshot(common::request(Method::POST, "/slow", ""))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let msg: ServerToClientMsg = ron::de::from_bytes(&body).unwrap();
+    assert_eq!(msg, ServerToClientMsg::Error(SharedError::Timeout));
+}
+
+#[tokio::test]
+async fn slow_query_is_cancelled_by_the_database() {
+    use_short_timeout();
+
+    let mut conn = establish_connection().await.unwrap();
+    let error = diesel::sql_query("SELECT pg_sleep(3)")
+        .execute(&mut conn)
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("statement timeout"),
+        "Expected the statement timeout to cancel the query, got {error}"
+    );
+
+    // The connection is still usable afterwards
+    diesel::sql_query("SELECT 1")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,71 @@
+//! Tests for seeding a development database with demo data.
+
+mod common;
+
+use common::test_connection;
+use test_tracker_server::{
+    passwords::validate_user,
+    seed::{check_database_name, seed_demo_data, DEMO_PASSWORD, DEMO_USERNAME},
+    tests_and_completions::get_tests_and_completions_since,
+};
+
+#[tokio::test]
+async fn seeding_creates_a_demo_user_with_tests() {
+    let conn = &mut test_connection().await;
+
+    let summary = seed_demo_data(conn).await.unwrap();
+    assert!(summary.created_user);
+    assert_eq!(summary.tests_skipped, 0);
+    assert!(summary.tests_inserted >= 15);
+
+    let user = validate_user(conn, DEMO_USERNAME, DEMO_PASSWORD)
+        .await
+        .unwrap();
+    assert_eq!(user.id, summary.user_id);
+
+    let sync = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(sync.changed.len(), summary.tests_inserted);
+    assert!(sync.changed.iter().any(|test| test.completions.is_empty()));
+    assert!(sync.changed.iter().any(|test| test.completions.len() > 2));
+}
+
+#[tokio::test]
+async fn seeding_twice_changes_nothing() {
+    let conn = &mut test_connection().await;
+
+    let first = seed_demo_data(conn).await.unwrap();
+    let before = get_tests_and_completions_since(conn, &first.user_id, None, None)
+        .await
+        .unwrap();
+
+    let second = seed_demo_data(conn).await.unwrap();
+    assert_eq!(second.user_id, first.user_id);
+    assert!(!second.created_user);
+    assert_eq!(second.tests_inserted, 0);
+    assert_eq!(second.tests_skipped, first.tests_inserted);
+
+    let after = get_tests_and_completions_since(conn, &first.user_id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(after.changed, before.changed);
+}
+
+#[test]
+fn only_development_databases_can_be_seeded() {
+    for name in [
+        "test_tracker_dev",
+        "test_tracker_test",
+        "dev",
+        "TestTracker-Dev",
+        "test-tracker-dev.sqlite3",
+        "integration_tests",
+    ] {
+        assert!(check_database_name(name).is_ok(), "{name:?}");
+    }
+
+    for name in ["test_tracker", "test-tracker.sqlite3", "production", ""] {
+        assert!(check_database_name(name).is_err(), "{name:?}");
+    }
+}
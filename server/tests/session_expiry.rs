@@ -0,0 +1,122 @@
+//! Tests for session tokens lapsing after a period of inactivity, every use of a token pushing its
+//! expiry back, and checking a token in the background without pushing it back.
+
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use common::TestDb;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{
+    config::config,
+    db::schema::sessions,
+    sessions::{check_token, end_all_sessions, resolve_token, start_session},
+};
+use test_tracker_shared::Error as SharedError;
+
+/// How far an expiry can be from the expected one, since the database stores timestamps with less
+/// precision than [`Utc::now`].
+const SLACK: Duration = Duration::milliseconds(1);
+
+/// Get the expiry of the session with the given token, or `None` if it's gone.
+async fn expires_at(db: &mut TestDb, token: &str) -> Option<DateTime<Utc>> {
+    sessions::table
+        .find(token)
+        .select(sessions::expires_at)
+        .first(&mut **db)
+        .await
+        .ok()
+}
+
+/// Set the expiry of the session with the given token.
+async fn set_expires_at(db: &mut TestDb, token: &str, expires_at: DateTime<Utc>) {
+    diesel::update(sessions::table.find(token))
+        .set(sessions::expires_at.eq(expires_at))
+        .execute(&mut **db)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn expired_token_is_unauthorized_and_deleted() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let session = start_session(&mut db, user, false).await.unwrap();
+    set_expires_at(&mut db, &session.token, Utc::now() - Duration::minutes(1)).await;
+
+    assert_eq!(
+        resolve_token(&mut db, &session.token).await,
+        Err(SharedError::Unauthorized)
+    );
+    assert_eq!(expires_at(&mut db, &session.token).await, None);
+}
+
+#[tokio::test]
+async fn using_a_token_inside_its_window_extends_it() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let session = start_session(&mut db, user.clone(), false).await.unwrap();
+    let nearly_lapsed = Utc::now() + Duration::minutes(1);
+    set_expires_at(&mut db, &session.token, nearly_lapsed).await;
+
+    let before = Utc::now();
+    assert_eq!(resolve_token(&mut db, &session.token).await, Ok(user.id));
+    let after = Utc::now();
+
+    let extended = expires_at(&mut db, &session.token).await.unwrap();
+    assert!(extended > nearly_lapsed);
+    assert!(extended >= before + config().session_length - SLACK);
+    assert!(extended <= after + config().session_length);
+}
+
+#[tokio::test]
+async fn remember_me_decides_the_session_length() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+
+    let before = Utc::now();
+    let short = start_session(&mut db, user.clone(), false).await.unwrap();
+    let long = start_session(&mut db, user, true).await.unwrap();
+    let after = Utc::now();
+
+    let short = expires_at(&mut db, &short.token).await.unwrap();
+    let long = expires_at(&mut db, &long.token).await.unwrap();
+    assert!(short >= before + config().session_length - SLACK);
+    assert!(short <= after + config().session_length);
+    assert!(long >= before + config().remember_me_session_length - SLACK);
+    assert!(long <= after + config().remember_me_session_length);
+    assert!(short < long);
+}
+
+#[tokio::test]
+async fn checking_a_token_does_not_extend_it() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let session = start_session(&mut db, user.clone(), false).await.unwrap();
+    let nearly_lapsed = Utc::now() + Duration::minutes(1);
+    set_expires_at(&mut db, &session.token, nearly_lapsed).await;
+
+    assert_eq!(check_token(&mut db, &session.token).await, Ok(user.id));
+    let unchanged = expires_at(&mut db, &session.token).await.unwrap();
+    assert!((unchanged - nearly_lapsed).abs() <= SLACK);
+}
+
+#[tokio::test]
+async fn checking_an_ended_session_is_unauthorized() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let session = start_session(&mut db, user.clone(), false).await.unwrap();
+
+    set_expires_at(&mut db, &session.token, Utc::now() - Duration::minutes(1)).await;
+    assert_eq!(
+        check_token(&mut db, &session.token).await,
+        Err(SharedError::Unauthorized)
+    );
+
+    let session = start_session(&mut db, user.clone(), false).await.unwrap();
+    end_all_sessions(&mut db, &user.id).await.unwrap();
+    assert_eq!(
+        check_token(&mut db, &session.token).await,
+        Err(SharedError::Unauthorized)
+    );
+}
@@ -0,0 +1,263 @@
+//! Tests for the orders that the client can ask for its tests to be sorted in.
+
+mod common;
+
+use chrono::NaiveDate;
+use common::TestDb;
+use test_tracker_server::{
+    format::Format,
+    messages::{parse_message, Route},
+    tests_and_completions::{get_tests_and_completions_since, insert_test_and_completions},
+};
+use test_tracker_shared::{ClientToServerMsg, CompletionData, TestData, TestSort};
+
+/// A completion in [`FIXTURES`], as `(achieved_mark, total_marks, date)`.
+type FixtureCompletion = (i32, i32, Option<&'static str>);
+
+/// The fixture dataset, as `(subject, date_or_id, [completion])`, in the order that they get
+/// inserted, so in order of ID.
+const FIXTURES: &[(&str, &str, &[FixtureCompletion])] = &[
+    (
+        "Physics",
+        "Mock Set 1",
+        &[(30, 60, Some("2024-03-01")), (45, 60, Some("2024-01-10"))],
+    ),
+    (
+        "Maths",
+        "June 2019 Paper 1",
+        &[(40, 80, Some("2024-02-01"))],
+    ),
+    ("Maths", "November 2018 Paper 2", &[]),
+    ("English", "Monday 3rd June 2019", &[(36, 40, None)]),
+    ("Chemistry", "2017-05-20", &[(20, 100, Some("2024-04-01"))]),
+    ("Biology", "Mock Set 2", &[(60, 80, Some("2024-02-01"))]),
+];
+
+/// Insert the fixture dataset for the given user, and return the IDs of the tests, in the same
+/// order as [`FIXTURES`].
+async fn insert_fixtures(db: &mut TestDb, user_id: &str) -> Vec<i32> {
+    let mut ids = Vec::new();
+    for &(subject, date_or_id, completions) in FIXTURES {
+        let test = TestData {
+            subject: subject.to_string(),
+            topic: None,
+            date_or_id: date_or_id.to_string(),
+            qualification_level: None,
+            exam_board: None,
+            paper_link: None,
+            mark_scheme_link: None,
+            comments: None,
+        };
+        let completions = completions
+            .iter()
+            .map(|&(achieved_mark, total_marks, date)| CompletionData {
+                achieved_mark,
+                total_marks,
+                date: date.map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()),
+                comments: None,
+            })
+            .collect();
+        ids.push(
+            insert_test_and_completions(db, user_id, test, completions)
+                .await
+                .unwrap(),
+        );
+    }
+    ids
+}
+
+#[tokio::test]
+async fn every_sort_gives_the_expected_order() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let ids = insert_fixtures(&mut db, &user.id).await;
+    let [physics, june_2019, november_2018, english, chemistry, biology] = ids[..] else {
+        unreachable!()
+    };
+
+    for (sort, expected) in [
+        (
+            TestSort::SubjectAsc,
+            [
+                biology,
+                chemistry,
+                english,
+                june_2019,
+                november_2018,
+                physics,
+            ],
+        ),
+        (
+            // Ties on the latest date are broken by ID, and undated completions don't count
+            TestSort::RecentlyAttempted,
+            [
+                chemistry,
+                physics,
+                june_2019,
+                biology,
+                november_2018,
+                english,
+            ],
+        ),
+        (
+            TestSort::BestPercentageAsc,
+            [
+                chemistry,
+                june_2019,
+                physics,
+                biology,
+                english,
+                november_2018,
+            ],
+        ),
+        (
+            TestSort::BestPercentageDesc,
+            [
+                english,
+                physics,
+                biology,
+                june_2019,
+                chemistry,
+                november_2018,
+            ],
+        ),
+        (
+            // The 1st of June 2019 comes before Monday the 3rd
+            TestSort::ExamDateAsc,
+            [
+                chemistry,
+                november_2018,
+                june_2019,
+                english,
+                physics,
+                biology,
+            ],
+        ),
+    ] {
+        let sync = get_tests_and_completions_since(&mut db, &user.id, None, Some(sort))
+            .await
+            .unwrap();
+        assert_eq!(sync.order.as_deref(), Some(&expected[..]), "{sort:?}");
+
+        let changed: Vec<i32> = sync.changed.iter().map(|test| test.id).collect();
+        assert_eq!(changed, expected, "{sort:?}");
+    }
+}
+
+#[tokio::test]
+async fn client_side_sorts_match_the_server() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    insert_fixtures(&mut db, &user.id).await;
+
+    let mut tests = get_tests_and_completions_since(&mut db, &user.id, None, None)
+        .await
+        .unwrap()
+        .changed;
+    tests.reverse();
+
+    for sort in [
+        TestSort::SubjectAsc,
+        TestSort::RecentlyAttempted,
+        TestSort::BestPercentageAsc,
+        TestSort::BestPercentageDesc,
+        TestSort::ExamDateAsc,
+    ] {
+        let server_order = get_tests_and_completions_since(&mut db, &user.id, None, Some(sort))
+            .await
+            .unwrap()
+            .order;
+
+        sort.sort_tests(&mut tests);
+        let client_order: Vec<i32> = tests.iter().map(|test| test.id).collect();
+        assert_eq!(Some(client_order), server_order, "{sort:?}");
+    }
+}
+
+#[tokio::test]
+async fn never_completed_tests_go_last_in_client_side_score_sorts() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let never = db.test(&user.id, "Maths", "Mock Set 1").await;
+    let zero = db.test(&user.id, "Maths", "Mock Set 2").await;
+    db.completion(zero, 0, 80).await;
+    let full = db.test(&user.id, "Maths", "Mock Set 3").await;
+    db.completion(full, 80, 80).await;
+
+    let mut tests = get_tests_and_completions_since(&mut db, &user.id, None, None)
+        .await
+        .unwrap()
+        .changed;
+    for (sort, expected) in [
+        (TestSort::BestPercentageAsc, [zero, full, never]),
+        (TestSort::BestPercentageDesc, [full, zero, never]),
+    ] {
+        sort.sort_tests(&mut tests);
+        let order: Vec<i32> = tests.iter().map(|test| test.id).collect();
+        assert_eq!(order, expected, "{sort:?}");
+    }
+}
+
+#[tokio::test]
+async fn no_sort_or_an_unknown_sort_sorts_by_id() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let ids = insert_fixtures(&mut db, &user.id).await;
+
+    for sort in [None, Some(TestSort::Unknown)] {
+        let sync = get_tests_and_completions_since(&mut db, &user.id, None, sort)
+            .await
+            .unwrap();
+        assert_eq!(sync.order, None);
+
+        let changed: Vec<i32> = sync.changed.iter().map(|test| test.id).collect();
+        assert_eq!(changed, ids);
+    }
+}
+
+#[tokio::test]
+async fn delta_syncs_still_give_the_whole_order() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let ids = insert_fixtures(&mut db, &user.id).await;
+
+    let full = get_tests_and_completions_since(&mut db, &user.id, None, Some(TestSort::SubjectAsc))
+        .await
+        .unwrap();
+    let delta = get_tests_and_completions_since(
+        &mut db,
+        &user.id,
+        Some(full.synced_at),
+        Some(TestSort::SubjectAsc),
+    )
+    .await
+    .unwrap();
+    assert!(!delta.full);
+    assert_eq!(delta.order, full.order);
+    assert_eq!(delta.order.map(|order| order.len()), Some(ids.len()));
+}
+
+#[test]
+fn sorts_are_optional_and_unknown_sorts_are_tolerated() {
+    let parse = |body: &str| match parse_message(Format::Json, Route::Tests, body).unwrap() {
+        ClientToServerMsg::GetTestsAndCompletions { sort, .. } => sort,
+        other => panic!("expected GetTestsAndCompletions, got {other:?}"),
+    };
+
+    assert_eq!(
+        parse(r#"{"GetTestsAndCompletions": {"token": "t", "user_id": "u", "since": null}}"#),
+        None
+    );
+    assert_eq!(
+        parse(
+            r#"{"GetTestsAndCompletions": {"token": "t", "user_id": "u", "since": null, "sort": "ExamDateAsc"}}"#
+        ),
+        Some(TestSort::ExamDateAsc)
+    );
+    assert_eq!(
+        parse(
+            r#"{"GetTestsAndCompletions": {"token": "t", "user_id": "u", "since": null, "sort": "HardestFirst"}}"#
+        ),
+        Some(TestSort::Unknown)
+    );
+}
@@ -0,0 +1,199 @@
+//! Tests for the per-subject statistics, which are aggregated in SQL.
+
+mod common;
+
+use common::{insert_completion, insert_test, test_connection};
+use test_tracker_server::{
+    db::DbConnection,
+    passwords::add_new_user,
+    statistics::get_statistics_for_user,
+    tests_and_completions::{
+        get_all_tests_and_completions_for_user, get_tests_and_completions_since,
+    },
+};
+use test_tracker_shared::{summary::subject_summaries, SubjectStats};
+
+/// A completion in [`FIXTURES`], as `(achieved_mark, total_marks)`.
+type FixtureCompletion = (i32, i32);
+
+/// The fixture dataset, as `(subject, date_or_id, [completion])`.
+const FIXTURES: &[(&str, &str, &[FixtureCompletion])] = &[
+    (
+        "Maths",
+        "June 2019 Paper 1",
+        &[(45, 80), (62, 80), (71, 80)],
+    ),
+    ("Maths", "June 2019 Paper 2", &[(50, 80)]),
+    ("Physics", "Mock Set 1", &[(30, 60), (0, 60)]),
+    ("English", "November 2020", &[(33, 40)]),
+    ("Chemistry", "Mock Set 2", &[]),
+];
+
+/// Insert the fixture dataset for the given user.
+async fn insert_fixtures(conn: &mut DbConnection, user_id: &str) {
+    for (subject, date_or_id, marks) in FIXTURES {
+        let test_id = insert_test(conn, user_id, subject, date_or_id).await;
+        for &(achieved_mark, total_marks) in *marks {
+            insert_completion(conn, test_id, achieved_mark, total_marks, None).await;
+        }
+    }
+}
+
+/// Compute the statistics in Rust from every completion, to check the SQL against.
+fn naive_statistics(
+    tests_and_completions: &[test_tracker_shared::TestAndCompletions],
+) -> Vec<SubjectStats> {
+    let mut subjects: Vec<String> = tests_and_completions
+        .iter()
+        .filter(|(_, completions)| !completions.is_empty())
+        .map(|(test, _)| test.subject.clone())
+        .collect();
+    subjects.sort();
+    subjects.dedup();
+
+    subjects
+        .into_iter()
+        .map(|subject| {
+            let completions: Vec<_> = tests_and_completions
+                .iter()
+                .filter(|(test, _)| test.subject == subject)
+                .flat_map(|(_, completions)| completions)
+                .collect();
+            let scores: Vec<f64> = completions
+                .iter()
+                .filter(|completion| completion.total_marks != 0)
+                .map(|completion| completion.achieved_mark as f64 / completion.total_marks as f64)
+                .collect();
+
+            SubjectStats {
+                subject,
+                completion_count: completions.len() as i64,
+                average_score: (!scores.is_empty())
+                    .then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+                best_score: scores.iter().copied().reduce(f64::max),
+            }
+        })
+        .collect()
+}
+
+/// Are the two optional scores equal, allowing for floating point error?
+fn scores_match(a: Option<f64>, b: Option<f64>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => (a - b).abs() < 1e-9,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[tokio::test]
+async fn sql_statistics_match_naive_statistics() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    insert_fixtures(conn, &user.id).await;
+
+    let statistics = get_statistics_for_user(conn, &user.id).await.unwrap();
+    let expected = naive_statistics(
+        &get_all_tests_and_completions_for_user(conn, &user.id)
+            .await
+            .unwrap(),
+    );
+
+    assert_eq!(statistics.len(), expected.len());
+    for (actual, expected) in statistics.iter().zip(&expected) {
+        assert_eq!(actual.subject, expected.subject);
+        assert_eq!(actual.completion_count, expected.completion_count);
+        assert!(
+            scores_match(actual.average_score, expected.average_score),
+            "{actual:?} != {expected:?}"
+        );
+        assert!(
+            scores_match(actual.best_score, expected.best_score),
+            "{actual:?} != {expected:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn client_summaries_match_sql_statistics() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    insert_fixtures(conn, &user.id).await;
+
+    let statistics = get_statistics_for_user(conn, &user.id).await.unwrap();
+    let tests = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap()
+        .changed;
+    let summaries = subject_summaries(&tests);
+
+    // Subjects without completions are only in the summaries
+    let summaries_with_completions: Vec<_> = summaries
+        .iter()
+        .filter(|summary| summary.completion_count > 0)
+        .collect();
+    assert_eq!(summaries_with_completions.len(), statistics.len());
+    for (summary, stats) in summaries_with_completions.iter().zip(&statistics) {
+        assert_eq!(summary.subject, stats.subject);
+        assert_eq!(summary.completion_count as i64, stats.completion_count);
+        assert!(
+            scores_match(summary.average_score, stats.average_score),
+            "{summary:?} != {stats:?}"
+        );
+        assert!(
+            scores_match(summary.best_score, stats.best_score),
+            "{summary:?} != {stats:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn client_summaries_count_tests_and_include_untested_subjects() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    insert_fixtures(conn, &user.id).await;
+
+    let tests = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap()
+        .changed;
+    let summaries = subject_summaries(&tests);
+
+    assert_eq!(
+        summaries
+            .iter()
+            .map(|summary| (
+                summary.subject.as_str(),
+                summary.test_count,
+                summary.completion_count
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Chemistry", 1, 0),
+            ("English", 1, 1),
+            ("Maths", 2, 4),
+            ("Physics", 1, 2),
+        ]
+    );
+    assert_eq!(summaries[0].average_score, None);
+    assert_eq!(summaries[0].best_score, None);
+}
+
+#[tokio::test]
+async fn statistics_only_include_the_given_user() {
+    let conn = &mut test_connection().await;
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    let bob = add_new_user(conn, "bob_smith", "correct horse battery")
+        .await
+        .unwrap();
+    insert_fixtures(conn, &alice.id).await;
+
+    assert_eq!(get_statistics_for_user(conn, &bob.id).await, Ok(vec![]));
+}
@@ -0,0 +1,241 @@
+//! Tests for delta sync, where the client only fetches the tests that have changed since it last
+//! synced.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{insert_completion, insert_test, test_connection};
+use diesel::ExpressionMethods;
+use diesel_async::RunQueryDsl;
+use std::collections::BTreeMap;
+use test_tracker_server::{
+    db::{schema::tests, DbConnection},
+    passwords::add_new_user,
+    tests_and_completions::{
+        delete_completion, delete_test, get_tests_and_completions_since, update_completion,
+    },
+};
+use test_tracker_shared::{CompletionData, SyncedTest, TestsSync};
+
+/// Insert two tests with a couple of completions for the given user, and return the test IDs.
+async fn insert_fixtures(conn: &mut DbConnection, user_id: &str) -> (i32, i32) {
+    let maths = insert_test(conn, user_id, "Maths", "June 2019 Paper 1").await;
+    insert_completion(conn, maths, 45, 80, None).await;
+    insert_completion(conn, maths, 62, 80, None).await;
+
+    let physics = insert_test(conn, user_id, "Physics", "Mock Set 1").await;
+    insert_completion(conn, physics, 30, 60, None).await;
+
+    (maths, physics)
+}
+
+/// Pretend that every test was last changed an hour ago, so that later changes stand out, and
+/// return a `since` from half an hour ago.
+async fn backdate_everything(conn: &mut DbConnection) -> chrono::DateTime<Utc> {
+    let now = Utc::now();
+    diesel::update(tests::table)
+        .set(tests::updated_at.eq(now - Duration::hours(1)))
+        .execute(conn)
+        .await
+        .unwrap();
+    now - Duration::minutes(30)
+}
+
+/// Get the IDs of the changed tests in a sync.
+fn changed_ids(sync: &TestsSync) -> Vec<i32> {
+    sync.changed.iter().map(|test| test.id).collect()
+}
+
+#[tokio::test]
+async fn full_sync_returns_everything() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let (maths, physics) = insert_fixtures(conn, &user.id).await;
+
+    let sync = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap();
+    assert!(sync.full);
+    assert_eq!(changed_ids(&sync), vec![maths, physics]);
+    assert_eq!(sync.changed[0].completions.len(), 2);
+    assert_eq!(sync.changed[1].completions.len(), 1);
+    assert!(sync.deleted.is_empty());
+}
+
+#[tokio::test]
+async fn updated_completion_resends_only_its_test() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let (maths, _) = insert_fixtures(conn, &user.id).await;
+    let full = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap();
+    let since = backdate_everything(conn).await;
+
+    let sync = get_tests_and_completions_since(conn, &user.id, Some(since), None)
+        .await
+        .unwrap();
+    assert!(!sync.full);
+    assert!(changed_ids(&sync).is_empty());
+
+    let (completion_id, _) = full.changed[0].completions[0];
+    let new_completion = CompletionData {
+        achieved_mark: 70,
+        total_marks: 80,
+        date: None,
+        comments: Some("Much better".to_string()),
+    };
+    update_completion(conn, &user.id, completion_id, new_completion.clone())
+        .await
+        .unwrap();
+
+    let sync = get_tests_and_completions_since(conn, &user.id, Some(since), None)
+        .await
+        .unwrap();
+    assert_eq!(changed_ids(&sync), vec![maths]);
+    assert_eq!(
+        sync.changed[0].completions[0],
+        (completion_id, new_completion)
+    );
+    assert_eq!(sync.changed[0].completions.len(), 2);
+}
+
+#[tokio::test]
+async fn deletions_are_synced() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let (maths, physics) = insert_fixtures(conn, &user.id).await;
+    let full = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap();
+    let since = backdate_everything(conn).await;
+
+    let (completion_id, _) = full.changed[0].completions[1];
+    delete_completion(conn, &user.id, completion_id)
+        .await
+        .unwrap();
+    delete_test(conn, &user.id, physics).await.unwrap();
+
+    let sync = get_tests_and_completions_since(conn, &user.id, Some(since), None)
+        .await
+        .unwrap();
+    assert_eq!(changed_ids(&sync), vec![maths]);
+    assert_eq!(sync.changed[0].completions.len(), 1);
+    assert_eq!(sync.deleted, vec![physics]);
+
+    // A full sync doesn't need tombstones, since the test is just missing
+    let sync = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(changed_ids(&sync), vec![maths]);
+    assert!(sync.deleted.is_empty());
+}
+
+#[tokio::test]
+async fn other_users_changes_are_invisible() {
+    let conn = &mut test_connection().await;
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    let bob = add_new_user(conn, "bob", "correct horse battery")
+        .await
+        .unwrap();
+    let (_, physics) = insert_fixtures(conn, &alice.id).await;
+    let since = backdate_everything(conn).await;
+
+    assert!(delete_test(conn, &bob.id, physics).await.is_err());
+    delete_test(conn, &alice.id, physics).await.unwrap();
+
+    let sync = get_tests_and_completions_since(conn, &bob.id, Some(since), None)
+        .await
+        .unwrap();
+    assert!(changed_ids(&sync).is_empty());
+    assert!(sync.deleted.is_empty());
+}
+
+#[tokio::test]
+async fn server_timestamp_is_authoritative() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let (maths, physics) = insert_fixtures(conn, &user.id).await;
+
+    let before = Utc::now();
+    let sync = get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap();
+    assert!(before <= sync.synced_at && sync.synced_at <= Utc::now());
+
+    // A client with a clock running a day fast must not be able to skip changes by sending its own
+    // idea of the time, so a `since` in the server's future gives a full sync
+    let skewed = Utc::now() + Duration::days(1);
+    let sync = get_tests_and_completions_since(conn, &user.id, Some(skewed), None)
+        .await
+        .unwrap();
+    assert!(sync.full);
+    assert_eq!(changed_ids(&sync), vec![maths, physics]);
+    assert!(sync.synced_at < skewed);
+
+    // A client with a clock running slow just gets some changes twice
+    let since = backdate_everything(conn).await;
+    let sync =
+        get_tests_and_completions_since(conn, &user.id, Some(since - Duration::days(1)), None)
+            .await
+            .unwrap();
+    assert!(!sync.full);
+    assert_eq!(changed_ids(&sync), vec![maths, physics]);
+}
+
+#[tokio::test]
+async fn merging_deltas_matches_a_full_sync() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    let (maths, physics) = insert_fixtures(conn, &user.id).await;
+
+    let mut cache: BTreeMap<i32, SyncedTest> = BTreeMap::new();
+    get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap()
+        .merge_into(&mut cache);
+    let since = backdate_everything(conn).await;
+
+    let (completion_id, _) = cache[&maths].completions[0];
+    update_completion(
+        conn,
+        &user.id,
+        completion_id,
+        CompletionData {
+            achieved_mark: 80,
+            total_marks: 80,
+            date: None,
+            comments: None,
+        },
+    )
+    .await
+    .unwrap();
+    delete_test(conn, &user.id, physics).await.unwrap();
+
+    let delta = get_tests_and_completions_since(conn, &user.id, Some(since), None)
+        .await
+        .unwrap();
+    // Merging the same delta twice is harmless
+    delta.clone().merge_into(&mut cache);
+    delta.merge_into(&mut cache);
+
+    let mut expected = BTreeMap::new();
+    get_tests_and_completions_since(conn, &user.id, None, None)
+        .await
+        .unwrap()
+        .merge_into(&mut expected);
+    assert_eq!(cache, expected);
+    assert_eq!(cache.keys().copied().collect::<Vec<_>>(), vec![maths]);
+}
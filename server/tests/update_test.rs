@@ -0,0 +1,118 @@
+//! Tests for changing the details of a test, which the client's edit form does.
+
+mod common;
+
+use common::TestDb;
+use test_tracker_server::tests_and_completions::{
+    get_all_tests_and_completions_for_user, update_test,
+};
+use test_tracker_shared::{
+    error::DieselError,
+    fixtures::{bare_completion, bare_test},
+    policy::TestPolicyError,
+    Error, TestData,
+};
+
+#[tokio::test]
+async fn details_are_replaced_and_completions_are_kept() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = db.test(&user.id, "Mahts", "June 2019 Paper 1").await;
+    db.completion(test_id, 60, 80).await;
+
+    let fixed = TestData {
+        topic: Some("Pure".to_string()),
+        ..bare_test("Maths", "June 2019 Paper 1")
+    };
+    update_test(&mut db, &user.id, test_id, fixed.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_all_tests_and_completions_for_user(&mut db, &user.id)
+            .await
+            .unwrap(),
+        vec![(fixed, vec![bare_completion(60, 80, None)])]
+    );
+}
+
+#[tokio::test]
+async fn optional_details_can_be_cleared() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+
+    let with_topic = TestData {
+        topic: Some("Pure".to_string()),
+        ..bare_test("Maths", "June 2019 Paper 1")
+    };
+    update_test(&mut db, &user.id, test_id, with_topic)
+        .await
+        .unwrap();
+    update_test(
+        &mut db,
+        &user.id,
+        test_id,
+        bare_test("Maths", "June 2019 Paper 1"),
+    )
+    .await
+    .unwrap();
+
+    let tests = get_all_tests_and_completions_for_user(&mut db, &user.id)
+        .await
+        .unwrap();
+    assert_eq!(tests[0].0.topic, None);
+}
+
+#[tokio::test]
+async fn invalid_details_are_rejected() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    let test_id = db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+
+    assert_eq!(
+        update_test(&mut db, &user.id, test_id, bare_test("Maths", "  ")).await,
+        Err(TestPolicyError::MissingDateOrId.into())
+    );
+}
+
+#[tokio::test]
+async fn duplicates_are_rejected() {
+    let mut db = TestDb::new().await;
+    let user = db.user("test_user").await;
+    db.test(&user.id, "Maths", "June 2019 Paper 1").await;
+    let test_id = db.test(&user.id, "Maths", "June 2019 Paper 2").await;
+
+    assert_eq!(
+        update_test(
+            &mut db,
+            &user.id,
+            test_id,
+            bare_test("Maths", "June 2019 Paper 1")
+        )
+        .await,
+        Err(Error::DuplicateTest {
+            subject: "Maths".to_string(),
+            date_or_id: "June 2019 Paper 1".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn other_users_tests_look_like_they_dont_exist() {
+    let mut db = TestDb::new().await;
+    let owner = db.user("owner").await;
+    let other = db.user("other").await;
+    let test_id = db.test(&owner.id, "Maths", "June 2019 Paper 1").await;
+
+    assert_eq!(
+        update_test(&mut db, &other.id, test_id, bare_test("History", "Mock")).await,
+        Err(Error::DatabaseError(DieselError::NotFound))
+    );
+    assert_eq!(
+        get_all_tests_and_completions_for_user(&mut db, &owner.id)
+            .await
+            .unwrap(),
+        vec![(bare_test("Maths", "June 2019 Paper 1"), vec![])]
+    );
+}
@@ -0,0 +1,103 @@
+//! Tests for the `user` subcommands, which create users and reset their passwords from the command
+//! line.
+//!
+//! These run the real binary against the test database, so unlike the other tests, their changes
+//! are committed. Every test uses its own random username and deletes that user when it's done.
+
+mod common;
+
+use assert_cmd::Command;
+use common::TestDb;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use test_tracker_server::{
+    db::{establish_connection, schema::users, DbConnection},
+    passwords::validate_user,
+};
+
+/// A good password.
+const PASSWORD: &str = "correct horse battery";
+
+/// Another good password.
+const NEW_PASSWORD: &str = "staple battery horse";
+
+/// A username that no other test uses.
+fn unique_username() -> String {
+    format!("cli-{:016x}", rand::random::<u64>())
+}
+
+/// Connect to the test database outside of a test transaction, so that we can see what the binary
+/// committed. The database is migrated first if it hasn't been already.
+async fn committed_connection() -> DbConnection {
+    drop(TestDb::new().await);
+    establish_connection()
+        .await
+        .expect("We should be able to connect to the test DB")
+}
+
+/// Delete the user with the given username, if there is one.
+async fn delete_user(conn: &mut DbConnection, username: &str) {
+    diesel::delete(users::table.filter(users::username.eq(username)))
+        .execute(conn)
+        .await
+        .unwrap();
+}
+
+/// Run a `user` subcommand for the given username with the given stdin.
+fn user_command(subcommand: &str, username: &str, stdin: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("test-tracker-server")
+        .unwrap()
+        .args(["user", subcommand, username])
+        .write_stdin(stdin)
+        .assert()
+}
+
+#[tokio::test]
+async fn create_reads_the_password_from_stdin() {
+    let conn = &mut committed_connection().await;
+    let username = unique_username();
+
+    user_command("create", &username, &format!("{PASSWORD}\n")).success();
+    let result = validate_user(conn, &username, PASSWORD).await;
+
+    delete_user(conn, &username).await;
+    assert_eq!(result.unwrap().username, username);
+}
+
+#[tokio::test]
+async fn create_refuses_an_empty_password() {
+    let conn = &mut committed_connection().await;
+    let username = unique_username();
+
+    user_command("create", &username, "\n").failure();
+    let count: i64 = users::table
+        .filter(users::username.eq(&username))
+        .count()
+        .get_result(conn)
+        .await
+        .unwrap();
+
+    delete_user(conn, &username).await;
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn set_password_replaces_the_password() {
+    let conn = &mut committed_connection().await;
+    let username = unique_username();
+
+    user_command("create", &username, PASSWORD).success();
+    user_command("set-password", &username, NEW_PASSWORD).success();
+    user_command("set-password", &username, "").failure();
+    let old = validate_user(conn, &username, PASSWORD).await;
+    let new = validate_user(conn, &username, NEW_PASSWORD).await;
+
+    delete_user(conn, &username).await;
+    assert!(old.is_err());
+    assert_eq!(new.unwrap().username, username);
+}
+
+#[tokio::test]
+async fn set_password_fails_for_unknown_users() {
+    user_command("set-password", &unique_username(), NEW_PASSWORD).failure();
+}
@@ -0,0 +1,76 @@
+//! Tests for creating users, logging in, and authorizing with session tokens.
+
+mod common;
+
+use common::test_connection;
+use test_tracker_server::{
+    passwords::{add_new_user, validate_user},
+    sessions::{authorize, start_session},
+    tests_and_completions::get_all_tests_and_completions_for_user,
+};
+use test_tracker_shared::Error as SharedError;
+
+#[tokio::test]
+async fn new_user_can_log_in() {
+    let conn = &mut test_connection().await;
+
+    let user = add_new_user(conn, "Test_User", "correct horse battery")
+        .await
+        .unwrap();
+    assert_eq!(user.username, "test_user");
+
+    let validated = validate_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+    assert_eq!(validated, user);
+}
+
+#[tokio::test]
+async fn wrong_password_is_rejected() {
+    let conn = &mut test_connection().await;
+    add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    let error = SharedError::from(
+        validate_user(conn, "test_user", "incorrect horse battery")
+            .await
+            .unwrap_err(),
+    );
+    assert_eq!(error, SharedError::InvalidPassword);
+}
+
+#[tokio::test]
+async fn session_only_authorizes_its_own_user() {
+    let conn = &mut test_connection().await;
+    let alice = add_new_user(conn, "alice", "correct horse battery")
+        .await
+        .unwrap();
+    let bob = add_new_user(conn, "bob_smith", "correct horse battery")
+        .await
+        .unwrap();
+    let session = start_session(conn, alice.clone(), false).await.unwrap();
+
+    assert_eq!(authorize(conn, &session.token, &alice.id).await, Ok(()));
+    assert_eq!(
+        authorize(conn, &session.token, &bob.id).await,
+        Err(SharedError::Unauthorized)
+    );
+    assert_eq!(
+        authorize(conn, "not a real token", &alice.id).await,
+        Err(SharedError::Unauthorized)
+    );
+}
+
+#[tokio::test]
+async fn new_user_has_no_tests() {
+    let conn = &mut test_connection().await;
+    let user = add_new_user(conn, "test_user", "correct horse battery")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_all_tests_and_completions_for_user(conn, &user.id).await,
+        Ok(vec![])
+    );
+}
@@ -0,0 +1,241 @@
+//! This module works out where to draw the points of a chart of scores over time, so that the
+//! client's charts can be tested without rendering them.
+//!
+//! There are two kinds of chart: the small one of each test's scores, from [`score_points`], and
+//! the trend chart of every test's scores by week, for one subject or all of them at once, from
+//! [`trend_points`] and [`trend_series`].
+
+use crate::{CompletionData, SyncedTest};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+/// A score to plot, as the date it was achieved and the percentage.
+pub type ScorePoint = (NaiveDate, f64);
+
+/// The size of a chart, in the units of the SVG that it's drawn in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChartSize {
+    /// The width of the whole chart.
+    pub width: f64,
+
+    /// The height of the whole chart.
+    pub height: f64,
+
+    /// The space to leave around the edges, so that points at the very edges aren't cut off.
+    pub padding: f64,
+}
+
+/// Get the scores to plot for the given completions, sorted by date and then by ID.
+///
+/// Completions without a date are left out, since there's nowhere honest to put them on a time
+/// axis, and so are completions out of 0 marks, which have no percentage.
+pub fn score_points(completions: &[(i32, CompletionData)]) -> Vec<ScorePoint> {
+    let mut points: Vec<(NaiveDate, i32, f64)> = completions
+        .iter()
+        .filter_map(|(id, completion)| Some((completion.date?, *id, completion.percentage()?)))
+        .collect();
+    points.sort_by_key(|&(date, id, _)| (date, id));
+    points
+        .into_iter()
+        .map(|(date, _, percentage)| (date, percentage))
+        .collect()
+}
+
+/// Map the given points to `(x, y)` coordinates in a chart of the given size, where `y` goes down
+/// the screen like in SVG.
+///
+/// The x axis goes from the earliest date to the latest, and the y axis always goes from 0% at the
+/// bottom to 100% at the top, so that charts of different tests can be compared. If all the points
+/// are on the same date, then they go in the middle.
+pub fn plot_points(points: &[ScorePoint], size: ChartSize) -> Vec<(f64, f64)> {
+    let (Some(first), Some(last)) = (
+        points.iter().map(|&(date, _)| date).min(),
+        points.iter().map(|&(date, _)| date).max(),
+    ) else {
+        return Vec::new();
+    };
+
+    points
+        .iter()
+        .map(|&(date, percentage)| {
+            (
+                date_x(date, (first, last), size),
+                percentage_y(percentage, size),
+            )
+        })
+        .collect()
+}
+
+/// Where the given date goes across a chart of the given size whose x axis goes from the first
+/// date to the last. If they're the same date, then it goes in the middle.
+pub fn date_x(date: NaiveDate, (first, last): (NaiveDate, NaiveDate), size: ChartSize) -> f64 {
+    let days = (last - first).num_days() as f64;
+    if days == 0. {
+        size.width / 2.
+    } else {
+        size.padding + (size.width - 2. * size.padding) * (date - first).num_days() as f64 / days
+    }
+}
+
+/// Where the given percentage goes down a chart of the given size, with 0% at the bottom and 100%
+/// at the top. Percentages outside that range are clamped to it.
+pub fn percentage_y(percentage: f64, size: ChartSize) -> f64 {
+    size.padding + (size.height - 2. * size.padding) * (1. - percentage.clamp(0., 100.) / 100.)
+}
+
+/// A score to plot on the trend chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrendPoint {
+    /// The date that the score was achieved.
+    pub date: NaiveDate,
+
+    /// The score, as a percentage.
+    pub percentage: f64,
+
+    /// The subject of the test.
+    pub subject: String,
+
+    /// The name of the test, to show when the point is hovered over.
+    pub test: String,
+}
+
+/// The scores of every week in one subject, in order, to draw as one line of the trend chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrendSeries {
+    /// The subject that these scores are in.
+    pub subject: String,
+
+    /// The weeks with any scores, in order.
+    pub buckets: Vec<TrendBucket>,
+}
+
+/// The scores from one week, which are drawn as one point of the trend chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrendBucket {
+    /// The Monday that the week starts on. See [`week_of`].
+    pub week: NaiveDate,
+
+    /// The mean percentage of the scores in the week.
+    pub percentage: f64,
+
+    /// The names of the tests that the scores are from, in the order that they were done, without
+    /// repeats.
+    pub tests: Vec<String>,
+}
+
+/// Get the scores to plot on the trend chart from every completion of the given tests, sorted by
+/// date, and how many completions without a date were left out in each subject.
+///
+/// Like [`score_points`], completions without a date have nowhere to go on the chart, and
+/// completions out of 0 marks have no percentage, but only the ones without a date are counted,
+/// so that the chart can say that they're missing.
+pub fn trend_points<'a>(
+    tests: impl IntoIterator<Item = &'a SyncedTest>,
+) -> (Vec<TrendPoint>, BTreeMap<String, usize>) {
+    let mut points = Vec::new();
+    let mut undated = BTreeMap::new();
+    for SyncedTest {
+        test, completions, ..
+    } in tests
+    {
+        for (_, completion) in completions {
+            let Some(date) = completion.date else {
+                *undated.entry(test.subject.clone()).or_insert(0) += 1;
+                continue;
+            };
+            if let Some(percentage) = completion.percentage() {
+                points.push(TrendPoint {
+                    date,
+                    percentage,
+                    subject: test.subject.clone(),
+                    test: test.date_or_id.clone(),
+                });
+            }
+        }
+    }
+    points.sort_by_key(|point| point.date);
+    (points, undated)
+}
+
+/// The Monday at the start of the week of the given date.
+pub fn week_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+/// Group the given points into one series for each subject, in alphabetical order, or just for the
+/// given subject, with the scores in each week averaged. The points should be in date order, like
+/// from [`trend_points`].
+pub fn trend_series(points: &[TrendPoint], subject: Option<&str>) -> Vec<TrendSeries> {
+    let mut weeks: BTreeMap<&str, BTreeMap<NaiveDate, Vec<&TrendPoint>>> = BTreeMap::new();
+    for point in points
+        .iter()
+        .filter(|point| subject.is_none_or(|subject| point.subject == subject))
+    {
+        weeks
+            .entry(&point.subject)
+            .or_default()
+            .entry(week_of(point.date))
+            .or_default()
+            .push(point);
+    }
+
+    weeks
+        .into_iter()
+        .map(|(subject, weeks)| TrendSeries {
+            subject: subject.to_string(),
+            buckets: weeks
+                .into_iter()
+                .map(|(week, points)| {
+                    let mut tests: Vec<String> = Vec::new();
+                    for point in &points {
+                        if !tests.contains(&point.test) {
+                            tests.push(point.test.clone());
+                        }
+                    }
+                    TrendBucket {
+                        week,
+                        percentage: points.iter().map(|point| point.percentage).sum::<f64>()
+                            / points.len() as f64,
+                        tests,
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// The first and last weeks of any of the given series, which is the range of the x axis when
+/// they're all drawn on the same chart, or `None` if there aren't any weeks at all.
+pub fn trend_weeks(series: &[TrendSeries]) -> Option<(NaiveDate, NaiveDate)> {
+    let weeks = || {
+        series
+            .iter()
+            .flat_map(|series| &series.buckets)
+            .map(|bucket| bucket.week)
+    };
+    Some((weeks().min()?, weeks().max()?))
+}
+
+/// Map the weeks of each of the given series to `(x, y)` coordinates in a chart of the given size,
+/// like [`plot_points`], with every series sharing the same axes so that they can be compared.
+pub fn plot_trend(series: &[TrendSeries], size: ChartSize) -> Vec<Vec<(f64, f64)>> {
+    let Some(weeks) = trend_weeks(series) else {
+        return Vec::new();
+    };
+
+    series
+        .iter()
+        .map(|series| {
+            series
+                .buckets
+                .iter()
+                .map(|bucket| {
+                    (
+                        date_x(bucket.week, weeks, size),
+                        percentage_y(bucket.percentage, size),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,51 @@
+//! This module counts down to the exams that the user has coming up, from dates that they enter in
+//! the client.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// An exam that the user is revising for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExamDate {
+    /// What the exam is, like "Maths Paper 1".
+    pub name: String,
+
+    /// The day of the exam.
+    pub date: NaiveDate,
+}
+
+/// An exam that hasn't happened yet, with how long there is until it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Countdown<'a> {
+    /// The exam.
+    pub exam: &'a ExamDate,
+
+    /// How many days there are until the exam, where 0 means it's today.
+    pub days: i64,
+}
+
+impl Countdown<'_> {
+    /// Describe how long there is until the exam, like "today!", "tomorrow", or "in 12 days".
+    pub fn label(&self) -> String {
+        match self.days {
+            0 => "today!".to_string(),
+            1 => "tomorrow".to_string(),
+            days => format!("in {days} days"),
+        }
+    }
+}
+
+/// Count the days from `today` until each of the given exams, soonest first. Exams that have
+/// already happened are left out, and exams on the same day are sorted by name.
+pub fn upcoming_exams(exams: &[ExamDate], today: NaiveDate) -> Vec<Countdown<'_>> {
+    let mut upcoming: Vec<Countdown<'_>> = exams
+        .iter()
+        .map(|exam| Countdown {
+            exam,
+            days: (exam.date - today).num_days(),
+        })
+        .filter(|countdown| countdown.days >= 0)
+        .collect();
+    upcoming.sort_by(|a, b| (a.days, &a.exam.name).cmp(&(b.days, &b.exam.name)));
+    upcoming
+}
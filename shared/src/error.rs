@@ -1,6 +1,7 @@
 //! This module handles shared error handling.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 /// The shared error type that can be used by both server and client.
@@ -10,6 +11,10 @@ pub enum Error {
     #[error("error accessing the database: {0}")]
     DatabaseError(DieselError),
 
+    /// The server couldn't connect to the database, so try again later.
+    #[error("the database is unavailable")]
+    DatabaseUnavailable,
+
     /// The given password didn't match.
     #[error("invalid password")]
     InvalidPassword,
@@ -17,6 +22,118 @@ pub enum Error {
     /// An error occurred when trying to hash the user's password.
     #[error("error hashing password: {0}")]
     HashingError(String),
+
+    /// The session token was unknown or has lapsed, so the user needs to log in again.
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// The user is logged in, but isn't allowed to do this, like a non-admin trying to use an
+    /// admin-only message.
+    #[error("forbidden")]
+    Forbidden,
+
+    /// The request was malformed, or sent to the wrong place.
+    #[error("bad request: {0}")]
+    BadRequest(String),
+
+    /// The request was a message that the server doesn't know about, probably because the client
+    /// is newer than the server.
+    #[error("the server (version {server_version}) doesn't support {variant} messages yet")]
+    UnsupportedMessage {
+        /// The name of the message.
+        variant: String,
+
+        /// The version of the server.
+        server_version: String,
+    },
+
+    /// The server took too long to handle the request.
+    #[error("the server took too long to respond")]
+    Timeout,
+
+    /// The server is handling too many requests already, so try again shortly.
+    #[error("the server is too busy")]
+    Overloaded,
+
+    /// Some user input didn't satisfy the relevant policy.
+    #[error("invalid {field}: {reason}")]
+    Validation {
+        /// The name of the field that was invalid, like `"password"`.
+        field: String,
+
+        /// A human-readable reason why it was invalid.
+        reason: String,
+    },
+
+    /// Someone already has an account with this username, once normalized.
+    #[error("that username is already taken")]
+    UsernameTaken,
+
+    /// The user already has a test with the same subject and date or ID.
+    #[error("there's already a {subject} test called {date_or_id:?}")]
+    DuplicateTest {
+        /// The subject of the test.
+        subject: String,
+
+        /// The date or ID of the test.
+        date_or_id: String,
+    },
+
+    /// Something went wrong on the server that the client can't do anything about.
+    #[error("internal server error: {0}")]
+    Internal(String),
+}
+
+/// The error messages to show under the fields of a form, keyed by the name of the field, like
+/// `"password"`. The names are the same as in [`Error::Validation`], so that an error from the
+/// server can be shown under the field that it's about.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldErrors(BTreeMap<String, String>);
+
+impl FieldErrors {
+    /// Show the given reason under the given field, replacing any error that it already had.
+    pub fn insert(&mut self, field: impl Into<String>, reason: impl Into<String>) {
+        self.0.insert(field.into(), reason.into());
+    }
+
+    /// The error for the given field, if it has one.
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+
+    /// Clear the error for the given field, like when the user edits it. This is `true` if the
+    /// field had an error.
+    pub fn remove(&mut self, field: &str) -> bool {
+        self.0.remove(field).is_some()
+    }
+
+    /// Are there no errors at all?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Show all the errors from `other` as well, replacing any for the same fields.
+    pub fn merge(&mut self, other: &Self) {
+        self.0.extend(
+            other
+                .0
+                .iter()
+                .map(|(field, reason)| (field.clone(), reason.clone())),
+        );
+    }
+
+    /// Show an error from the server under its field, if it's an [`Error::Validation`] for one of
+    /// the given fields of the form. Any other error is given back, so that it can be shown in the
+    /// form's general error message instead.
+    pub fn insert_error(&mut self, error: Error, fields: &[&str]) -> Result<(), Error> {
+        match error {
+            Error::Validation { field, reason } if fields.contains(&field.as_str()) => {
+                self.insert(field, reason);
+                Ok(())
+            }
+            error => Err(error),
+        }
+    }
 }
 
 /// An error that comes from Diesel, which is used to manage the database.
@@ -26,9 +143,27 @@ pub enum DieselError {
     #[error("something in the DB was not found")]
     NotFound,
 
-    /// A duplicate key value violates a uniqueness constraint. The values are (message, details, hint).
-    #[error("duplicate key value violates unique constraint: {0}")]
-    UniqueViolation(String, Option<String>, Option<String>),
+    /// A duplicate key value violates a uniqueness constraint.
+    ///
+    /// The server reports violations of the constraints that it knows about with more precise
+    /// errors, like [`Error::UsernameTaken`], so this is only for unexpected ones.
+    #[error("duplicate key value violates unique constraint: {message}")]
+    UniqueViolation {
+        /// The message from the database.
+        message: String,
+
+        /// Any details from the database.
+        details: Option<String>,
+
+        /// Any hint from the database.
+        hint: Option<String>,
+
+        /// The name of the violated constraint. SQLite doesn't report this.
+        constraint: Option<String>,
+
+        /// The table of the violated constraint. SQLite doesn't report this.
+        table: Option<String>,
+    },
 
     /// Some other error occurred.
     #[error("{0}")]
@@ -39,7 +174,7 @@ pub enum DieselError {
 #[cfg(feature = "diesel")]
 mod diesel {
     use super::*;
-    use ::diesel::result::{DatabaseErrorKind as Kind, Error as DsErr};
+    use ::diesel::result::{ConnectionError, DatabaseErrorKind as Kind, Error as DsErr};
     use tracing::{debug, instrument};
 
     impl From<DsErr> for DieselError {
@@ -51,8 +186,22 @@ mod diesel {
                     let message = info.message().to_string();
                     let details = info.details().map(ToString::to_string);
                     let hint = info.hint().map(ToString::to_string);
-                    debug!(?details, ?hint, "UniqueViolation: {message}");
-                    Self::UniqueViolation(message, details, hint)
+                    let constraint = info.constraint_name().map(ToString::to_string);
+                    let table = info.table_name().map(ToString::to_string);
+                    debug!(
+                        ?details,
+                        ?hint,
+                        ?constraint,
+                        ?table,
+                        "UniqueViolation: {message}"
+                    );
+                    Self::UniqueViolation {
+                        message,
+                        details,
+                        hint,
+                        constraint,
+                        table,
+                    }
                 }
                 err => Self::Other(format!("{err:?}")),
             };
@@ -66,6 +215,13 @@ mod diesel {
             Self::DatabaseError(value.into())
         }
     }
+
+    impl From<ConnectionError> for Error {
+        fn from(value: ConnectionError) -> Self {
+            debug!(?value, "Unable to connect to the database");
+            Self::DatabaseUnavailable
+        }
+    }
 }
 
 /// Implement conversions from password hashing errors to [`enum@Error`].
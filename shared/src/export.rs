@@ -0,0 +1,98 @@
+//! This module builds a CSV export of a user's completions from the tests that the client already
+//! has, in the same format as the server's export at [`EXPORT`](crate::paths::EXPORT), so that it
+//! can be imported again.
+
+use crate::{CompletionData, SyncedTest, TestData};
+
+/// The names of the columns in the CSV, in order.
+pub const CSV_HEADERS: [&str; 9] = [
+    "subject",
+    "topic",
+    "date_or_id",
+    "exam_board",
+    "achieved_mark",
+    "total_marks",
+    "percentage",
+    "date",
+    "comments",
+];
+
+/// Quote a field of the CSV if it needs it, which is when it contains a comma, a quote, or a line
+/// break. Quotes inside the field are doubled, so that spreadsheets read it back exactly.
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Append a row to the CSV, ending with a newline.
+fn push_row<'a>(csv: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    let fields: Vec<String> = fields.into_iter().map(escape_field).collect();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+}
+
+/// Build a CSV with a header row and then one row for every given completion, along with the
+/// metadata of its test, in the order given. Missing values are left empty.
+pub fn rows_to_csv<'a>(
+    rows: impl IntoIterator<Item = (&'a TestData, &'a CompletionData)>,
+) -> String {
+    let mut csv = String::new();
+    push_row(&mut csv, CSV_HEADERS);
+
+    for (test, completion) in rows {
+        let percentage = completion
+            .percentage()
+            .map(|percentage| percentage.to_string())
+            .unwrap_or_default();
+        let date = completion
+            .date
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+
+        push_row(
+            &mut csv,
+            [
+                test.subject.as_str(),
+                test.topic.as_deref().unwrap_or_default(),
+                test.date_or_id.as_str(),
+                test.exam_board.as_deref().unwrap_or_default(),
+                &completion.achieved_mark.to_string(),
+                &completion.total_marks.to_string(),
+                &percentage,
+                &date,
+                completion.comments.as_deref().unwrap_or_default(),
+            ],
+        );
+    }
+
+    csv
+}
+
+/// Build a CSV with a header row and then one row for every completion, along with the metadata
+/// of its test. Rows are sorted by subject and test, like the server's export, and missing values
+/// are left empty.
+pub fn tests_to_csv(tests: &[SyncedTest]) -> String {
+    let mut tests: Vec<&SyncedTest> = tests.iter().collect();
+    tests.sort_by(|a, b| {
+        (&a.test.subject, &a.test.date_or_id, a.id).cmp(&(
+            &b.test.subject,
+            &b.test.date_or_id,
+            b.id,
+        ))
+    });
+
+    rows_to_csv(tests.into_iter().flat_map(
+        |SyncedTest {
+             test, completions, ..
+         }| {
+            let mut completions: Vec<_> = completions.iter().collect();
+            completions.sort_by_key(|(id, _)| *id);
+            completions
+                .into_iter()
+                .map(move |(_, completion)| (test, completion))
+        },
+    ))
+}
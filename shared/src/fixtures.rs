@@ -0,0 +1,192 @@
+//! This module generates realistic sample tests and completions, for tests, benchmarks, and
+//! previews that need some data but don't care exactly what it is. It also has some bare
+//! constructors with nothing optional filled in, for tests that care about exactly what's there.
+//!
+//! Everything here is deterministic: the same arguments always give the same data, so tests that
+//! use it are reproducible. It's only available with the `fixtures` feature.
+
+use crate::{CompletionData, SyncedTest, TestAndCompletions, TestData};
+use chrono::{Days, NaiveDate};
+
+/// The total marks of every sample completion made by [`sample_completion`].
+pub const SAMPLE_TOTAL_MARKS: i32 = 80;
+
+/// The subjects that [`sample_dataset`] spreads its tests across, with some topics for each.
+const SUBJECTS: [(&str, [&str; 3]); 6] = [
+    ("Maths", ["Pure", "Statistics", "Mechanics"]),
+    ("Physics", ["Mechanics", "Fields", "Practical skills"]),
+    ("Chemistry", ["Organic", "Inorganic", "Physical"]),
+    ("Biology", ["Genetics", "Ecology", "Cells"]),
+    ("English Literature", ["Shakespeare", "Poetry", "Prose"]),
+    ("History", ["Cold War", "Medicine through time", "Tudors"]),
+];
+
+/// The exam boards that sample tests use.
+const EXAM_BOARDS: [&str; 3] = ["AQA", "Edexcel", "OCR"];
+
+/// The months that exams are sat in.
+const EXAM_MONTHS: [&str; 3] = ["January", "June", "November"];
+
+/// Some comments that a student might leave on a completion.
+const COMMENTS: [&str; 4] = [
+    "Ran out of time",
+    "Much better this time",
+    "Forgot the units twice",
+    "Need to revise this topic",
+];
+
+/// A small deterministic pseudorandom number generator (SplitMix64), so that the shared crate
+/// doesn't need a dependency on `rand` just for sample data.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    /// Seed a new generator from some text, so that the same text always gives the same numbers.
+    fn from_text(text: &str) -> Self {
+        // FNV-1a, which is stable across platforms and Rust versions, unlike `DefaultHasher`
+        Self(text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        }))
+    }
+
+    /// Get the next number.
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Get a number below the given bound.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Pick one of the given items.
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Make a sample test in the given subject, with a plausible topic, exam, and exam board.
+pub fn sample_test(subject: &str) -> TestData {
+    sample_test_with(&mut Rng::from_text(subject), subject, 1)
+}
+
+/// Make a sample test in the given subject with the given paper number, using the given generator.
+fn sample_test_with(rng: &mut Rng, subject: &str, paper: usize) -> TestData {
+    let topics = SUBJECTS
+        .iter()
+        .find(|(name, _)| *name == subject)
+        .map_or(&["General"][..], |(_, topics)| &topics[..]);
+
+    TestData {
+        subject: subject.to_string(),
+        topic: Some(rng.pick(topics).to_string()),
+        date_or_id: format!(
+            "{} {} Paper {paper}",
+            rng.pick(&EXAM_MONTHS),
+            2015 + rng.below(10)
+        ),
+        qualification_level: Some(rng.pick(&["GCSE", "A Level"]).to_string()),
+        exam_board: Some(rng.pick(&EXAM_BOARDS).to_string()),
+        paper_link: None,
+        mark_scheme_link: None,
+        comments: None,
+    }
+}
+
+/// Make a sample completion out of [`SAMPLE_TOTAL_MARKS`], with the achieved mark as close as
+/// possible to the given percentage, which is clamped to between 0 and 100.
+pub fn sample_completion(percent: f64) -> CompletionData {
+    let fraction = if percent.is_nan() {
+        0.0
+    } else {
+        percent.clamp(0.0, 100.0) / 100.0
+    };
+    let achieved_mark = (fraction * f64::from(SAMPLE_TOTAL_MARKS)).round() as i32;
+
+    CompletionData {
+        achieved_mark,
+        total_marks: SAMPLE_TOTAL_MARKS,
+        date: NaiveDate::from_ymd_opt(2024, 1, 1 + (achieved_mark % 28) as u32),
+        comments: None,
+    }
+}
+
+/// Make a whole account's worth of sample data: the given number of tests spread across several
+/// subjects, each with the given number of completions that mostly improve over time.
+///
+/// Every test has a different subject and date or ID, so the whole dataset can be inserted for one
+/// user without any duplicates.
+pub fn sample_dataset(n_tests: usize, attempts_per_test: usize) -> Vec<TestAndCompletions> {
+    let mut rng = Rng::from_text("sample dataset");
+
+    (0..n_tests)
+        .map(|i| {
+            let (subject, _) = SUBJECTS[i % SUBJECTS.len()];
+            let mut test = sample_test_with(&mut rng, subject, i / SUBJECTS.len() + 1);
+            if rng.below(3) == 0 {
+                test.comments = Some("Some comments about this paper".to_string());
+            }
+
+            let start = 30 + rng.below(40);
+            let completions = (0..attempts_per_test)
+                .map(|attempt| {
+                    let percent = (start + attempt * 5 + rng.below(10)) as f64 - 5.0;
+                    let mut completion = sample_completion(percent);
+                    completion.date = NaiveDate::from_ymd_opt(2023, 9, 1).and_then(|date| {
+                        date.checked_add_days(Days::new((attempt * 14 + i % 14) as u64))
+                    });
+                    if rng.below(4) == 0 {
+                        completion.comments = Some(rng.pick(&COMMENTS).to_string());
+                    }
+                    completion
+                })
+                .collect();
+
+            (test, completions)
+        })
+        .collect()
+}
+
+/// Parse a date like `"2024-03-01"`, panicking if it isn't in that format.
+pub fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").expect("Fixture dates should be like 2024-03-01")
+}
+
+/// Make a test with just the given subject and date or ID, and nothing optional.
+pub fn bare_test(subject: &str, date_or_id: &str) -> TestData {
+    TestData {
+        subject: subject.to_string(),
+        topic: None,
+        date_or_id: date_or_id.to_string(),
+        qualification_level: None,
+        exam_board: None,
+        paper_link: None,
+        mark_scheme_link: None,
+        comments: None,
+    }
+}
+
+/// Make a completion with just the given marks and date, like `"2024-03-01"`, and no comments.
+pub fn bare_completion(achieved_mark: i32, total_marks: i32, date: Option<&str>) -> CompletionData {
+    CompletionData {
+        achieved_mark,
+        total_marks,
+        date: date.map(self::date),
+        comments: None,
+    }
+}
+
+/// Make a test as the client gets it from a sync, with the given ID and completions. The personal
+/// best isn't worked out, since that's the server's job.
+pub fn synced_test(id: i32, test: TestData, completions: Vec<(i32, CompletionData)>) -> SyncedTest {
+    SyncedTest {
+        id,
+        test,
+        completions,
+        best_completion_id: None,
+    }
+}
@@ -0,0 +1,140 @@
+//! This module works out grades from percentages, using grade boundaries that the user sets for
+//! each qualification level, like "A* 90, A 80, B 70" for A Level.
+//!
+//! Boundaries are only stored by the client, so they're the same for every test of a level.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// The lowest percentage that gets a grade.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Boundary {
+    /// The name of the grade, like "A*" or "7".
+    pub grade: String,
+
+    /// The lowest percentage that gets this grade, between 0 and 100.
+    pub min_percentage: f64,
+}
+
+/// A reason that some grade boundaries couldn't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum BoundaryParseError {
+    /// A boundary didn't have both a grade and a percentage, like "A" or "80".
+    #[error("\"{0}\" should be a grade and then a percentage, like \"A 80\"")]
+    MissingPart(String),
+
+    /// The percentage of a boundary wasn't a number between 0 and 100.
+    #[error("\"{0}\" isn't a percentage between 0 and 100")]
+    InvalidPercentage(String),
+
+    /// The same grade was given twice.
+    #[error("{0} is given more than once")]
+    DuplicateGrade(String),
+}
+
+/// The grade boundaries for each qualification level.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GradeBoundaries {
+    /// The boundaries of each level, highest first, keyed by the level's normalized name. See
+    /// [`level_key`].
+    levels: BTreeMap<String, (String, Vec<Boundary>)>,
+}
+
+/// The key of a qualification level, so that "A Level", "a level", and " A  level " all match.
+fn level_key(qualification_level: &str) -> String {
+    qualification_level
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl GradeBoundaries {
+    /// Set the boundaries of a qualification level, or remove them if there aren't any.
+    pub fn set_level(&mut self, qualification_level: &str, mut boundaries: Vec<Boundary>) {
+        let key = level_key(qualification_level);
+        if key.is_empty() || boundaries.is_empty() {
+            self.levels.remove(&key);
+            return;
+        }
+
+        boundaries.sort_by(|a, b| b.min_percentage.total_cmp(&a.min_percentage));
+        let name = qualification_level
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.levels.insert(key, (name, boundaries));
+    }
+
+    /// Get the boundaries of a qualification level, highest first, if it has any.
+    pub fn level(&self, qualification_level: &str) -> Option<&[Boundary]> {
+        self.levels
+            .get(&level_key(qualification_level))
+            .map(|(_, boundaries)| &boundaries[..])
+    }
+
+    /// Every qualification level with boundaries, as the user named it, with its boundaries.
+    pub fn levels(&self) -> impl Iterator<Item = (&str, &[Boundary])> {
+        self.levels
+            .values()
+            .map(|(name, boundaries)| (&name[..], &boundaries[..]))
+    }
+
+    /// Work out the grade of a percentage in a test of the given qualification level.
+    ///
+    /// A percentage exactly on a boundary gets that grade. This is `None` if the test has no level,
+    /// if its level has no boundaries, or if the percentage is below the lowest boundary, since no
+    /// grade is better than a wrong one.
+    pub fn grade(&self, qualification_level: Option<&str>, percentage: f64) -> Option<&str> {
+        self.level(qualification_level?)?
+            .iter()
+            .find(|boundary| percentage >= boundary.min_percentage)
+            .map(|boundary| &boundary.grade[..])
+    }
+}
+
+/// Parse some boundaries written like "A* 90, A 80, B 70". The percentages can end in `%`.
+pub fn parse_boundaries(text: &str) -> Result<Vec<Boundary>, BoundaryParseError> {
+    let mut boundaries: Vec<Boundary> = Vec::new();
+
+    for part in text
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let Some((grade, percentage)) = part.rsplit_once(char::is_whitespace) else {
+            return Err(BoundaryParseError::MissingPart(part.to_string()));
+        };
+        let grade = grade.trim();
+        if grade.is_empty() {
+            return Err(BoundaryParseError::MissingPart(part.to_string()));
+        }
+
+        let min_percentage = percentage
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .ok()
+            .filter(|percentage| (0. ..=100.).contains(percentage))
+            .ok_or_else(|| BoundaryParseError::InvalidPercentage(percentage.to_string()))?;
+
+        if boundaries.iter().any(|boundary| boundary.grade == grade) {
+            return Err(BoundaryParseError::DuplicateGrade(grade.to_string()));
+        }
+        boundaries.push(Boundary {
+            grade: grade.to_string(),
+            min_percentage,
+        });
+    }
+
+    Ok(boundaries)
+}
+
+/// Format some boundaries like [`parse_boundaries`] expects, like "A* 90, A 80, B 70".
+pub fn format_boundaries(boundaries: &[Boundary]) -> String {
+    boundaries
+        .iter()
+        .map(|boundary| format!("{} {}", boundary.grade, boundary.min_percentage))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
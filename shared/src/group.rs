@@ -0,0 +1,28 @@
+//! This module groups tests by subject, for the client's grouped view of the tests list.
+
+use crate::SyncedTest;
+use std::collections::BTreeMap;
+
+/// The group for tests without a subject. Validation doesn't allow these any more, but tests from
+/// before then might still have them.
+pub const UNCATEGORISED: &str = "Uncategorised";
+
+/// Group the given tests by subject, in alphabetical order of subject. The tests in each group stay
+/// in the same order as they were given in, so that sorting still applies within a group.
+///
+/// Surrounding whitespace in subjects is ignored, and tests with a blank subject go in the
+/// [`UNCATEGORISED`] group.
+pub fn group_by_subject(tests: &[SyncedTest]) -> BTreeMap<String, Vec<SyncedTest>> {
+    let mut groups: BTreeMap<String, Vec<SyncedTest>> = BTreeMap::new();
+    for test in tests {
+        let subject = match test.test.subject.trim() {
+            "" => UNCATEGORISED,
+            subject => subject,
+        };
+        groups
+            .entry(subject.to_string())
+            .or_default()
+            .push(test.clone());
+    }
+    groups
+}
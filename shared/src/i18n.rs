@@ -0,0 +1,368 @@
+//! This module holds the client's translations, so that the text in the UI can be shown in the
+//! user's language.
+//!
+//! Each language has a table of messages, keyed by strings like `"login.username"`. Messages can
+//! have placeholders like `{name}`, which are filled in by [`fill`]. Messages that depend on a
+//! number have a key for each plural category that the language uses, like `"test.attempts.one"`
+//! and `"test.attempts.other"`, which are looked up by [`Language::translate_plural`].
+//!
+//! Anything missing from a language falls back to English, so a partial translation still works.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A language that the client can be shown in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Language {
+    /// English, which every message has a translation in.
+    #[default]
+    English,
+
+    /// Welsh.
+    Welsh,
+}
+
+impl fmt::Display for Language {
+    /// The name of the language in itself, like "Cymraeg" for Welsh, so that people can find their
+    /// own language whichever one the UI is in.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::English => "English",
+            Self::Welsh => "Cymraeg",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// The plural categories from the Unicode CLDR. Each language only uses some of them, and every
+/// language uses [`Other`](PluralCategory::Other).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PluralCategory {
+    /// Zero, in languages that treat it specially.
+    Zero,
+
+    /// One.
+    One,
+
+    /// Two, in languages that treat it specially.
+    Two,
+
+    /// A few.
+    Few,
+
+    /// Many.
+    Many,
+
+    /// Everything else.
+    Other,
+}
+
+impl PluralCategory {
+    /// The suffix for this category on a message key, like `"one"`.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl Language {
+    /// Every language, in the order that they appear in the settings.
+    pub const ALL: [Self; 2] = [Self::English, Self::Welsh];
+
+    /// Find the language for a BCP 47 language tag, like `"cy-GB"` from `navigator.language`, if
+    /// the client can be shown in it. Only the primary language subtag matters.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim().to_lowercase();
+        match primary.as_str() {
+            "en" => Some(Self::English),
+            "cy" => Some(Self::Welsh),
+            _ => None,
+        }
+    }
+
+    /// The BCP 47 language tag for this language, like `"cy"`, for the `lang` attribute.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Welsh => "cy",
+        }
+    }
+
+    /// The plural category of the given count in this language.
+    pub fn plural_category(self, count: u64) -> PluralCategory {
+        match (self, count) {
+            (Self::English, 1) => PluralCategory::One,
+            (Self::English, _) => PluralCategory::Other,
+            (Self::Welsh, 0) => PluralCategory::Zero,
+            (Self::Welsh, 1) => PluralCategory::One,
+            (Self::Welsh, 2) => PluralCategory::Two,
+            (Self::Welsh, 3) => PluralCategory::Few,
+            (Self::Welsh, 6) => PluralCategory::Many,
+            (Self::Welsh, _) => PluralCategory::Other,
+        }
+    }
+
+    /// The table of messages in this language.
+    pub fn messages(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::English => ENGLISH,
+            Self::Welsh => WELSH,
+        }
+    }
+
+    /// Look up a message in this language's table, without falling back to English.
+    fn lookup(self, key: &str) -> Option<&'static str> {
+        self.messages()
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, message)| *message)
+    }
+
+    /// Get the message with the given key in this language, or in English if it hasn't been
+    /// translated. This is `None` if there's no such message at all.
+    pub fn translate(self, key: &str) -> Option<&'static str> {
+        self.lookup(key).or_else(|| Self::English.lookup(key))
+    }
+
+    /// Get the message with the given key for a count, from the right plural category for this
+    /// language. If this language doesn't have that category for the key, then its `other`
+    /// category is used, and then English.
+    pub fn translate_plural(self, key: &str, count: u64) -> Option<&'static str> {
+        let category = self.plural_category(count);
+        self.lookup(&format!("{key}.{}", category.suffix()))
+            .or_else(|| self.lookup(&format!("{key}.other")))
+            .or_else(|| {
+                let category = Self::English.plural_category(count);
+                Self::English.lookup(&format!("{key}.{}", category.suffix()))
+            })
+    }
+}
+
+/// Fill in the `{name}` placeholders in a message with the given values. Placeholders without a
+/// value are left as they are.
+pub fn fill(message: &str, args: &[(&str, String)]) -> String {
+    args.iter()
+        .fold(message.to_string(), |message, (name, value)| {
+            message.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// The messages in English.
+const ENGLISH: &[(&str, &str)] = &[
+    // The navbar
+    ("navbar.settings", "Settings"),
+    (
+        "navbar.switch-theme",
+        "Switch to {new} (currently {current})",
+    ),
+    ("navbar.theme-light", "light mode"),
+    ("navbar.theme-dark", "dark mode"),
+    ("navbar.theme-system", "following the system"),
+    (
+        "navbar.theme-system-current",
+        "following the system, which is in {mode}",
+    ),
+    ("navbar.streak-days.one", "Current streak: {count} day"),
+    ("navbar.streak-days.other", "Current streak: {count} days"),
+    ("navbar.streak-weeks.one", "Current streak: {count} week"),
+    ("navbar.streak-weeks.other", "Current streak: {count} weeks"),
+    // The login form
+    ("login.login", "Login"),
+    (
+        "login.login-or-create-account",
+        "Log in or create an account",
+    ),
+    ("login.create-account", "Create account"),
+    ("login.username", "Username"),
+    ("login.password", "Password"),
+    ("login.confirm-password", "Confirm password"),
+    ("login.remember-me", "Remember me"),
+    ("login.submit", "Submit"),
+    ("login.use-weak-password", "Use weak password anyway"),
+    ("login.missing-username", "Please enter a username"),
+    ("login.missing-password", "Please enter a password"),
+    ("login.passwords-dont-match", "The passwords don't match"),
+    (
+        "login.very-weak-password",
+        "That password is very weak. Are you sure you want to use it?",
+    ),
+    ("password.very-weak", "Very weak"),
+    ("password.weak", "Weak"),
+    ("password.fair", "Fair"),
+    ("password.strong", "Strong"),
+    // Error messages
+    ("error.title", "ERROR:"),
+    ("error.dismiss", "Dismiss error"),
+    // The test cards
+    ("test.paper", "Paper: "),
+    ("test.mark-scheme", "Mark scheme: "),
+    ("test.opens-in-new-tab", "Opens in a new tab"),
+    (
+        "test.unsafe-link",
+        "This isn't a web link starting with http:// or https://, so it can't be opened",
+    ),
+    ("test.edit", "Edit test"),
+    ("test.delete", "Delete test"),
+    ("test.log-attempt", "Log another attempt"),
+    ("test.best", "Best {percentage}%"),
+    ("test.attempts.one", "{count} attempt"),
+    ("test.attempts.other", "{count} attempts"),
+    (
+        "test.confirm-delete.one",
+        "This will delete the test and its {count} attempt.",
+    ),
+    (
+        "test.confirm-delete.other",
+        "This will delete the test and its {count} attempts.",
+    ),
+    ("test.expand", "Show details"),
+    ("test.collapse", "Hide details"),
+    // The page of a single test
+    ("test-page.back", "Back to the list"),
+    ("test-page.date-or-id", "Date or ID"),
+    ("test-page.qualification-level", "Qualification level"),
+    ("test-page.exam-board", "Exam board"),
+    ("test-page.paper", "Paper"),
+    ("test-page.mark-scheme", "Mark scheme"),
+    ("test-page.attempts", "Attempts"),
+    ("test-page.no-attempts", "No attempts yet"),
+    ("test-page.date", "Date"),
+    ("test-page.mark", "Mark"),
+    ("test-page.grade", "Grade"),
+    ("test-page.comments", "Comments"),
+    ("test-page.personal-best", "Personal best"),
+    ("test-page.not-found", "Test not found"),
+    (
+        "test-page.not-found-reason",
+        "There's no test with that ID. It might have been deleted.",
+    ),
+    ("test-page.load-failed", "Couldn't load the test: {reason}"),
+    ("test-page.retry", "Retry"),
+];
+
+/// The messages in Welsh.
+const WELSH: &[(&str, &str)] = &[
+    // The navbar
+    ("navbar.settings", "Gosodiadau"),
+    (
+        "navbar.switch-theme",
+        "Newid i {new} (ar hyn o bryd: {current})",
+    ),
+    ("navbar.theme-light", "modd golau"),
+    ("navbar.theme-dark", "modd tywyll"),
+    ("navbar.theme-system", "dilyn y system"),
+    (
+        "navbar.theme-system-current",
+        "dilyn y system, sy'n defnyddio {mode}",
+    ),
+    (
+        "navbar.streak-days.other",
+        "Rhediad presennol: {count} diwrnod",
+    ),
+    (
+        "navbar.streak-weeks.other",
+        "Rhediad presennol: {count} wythnos",
+    ),
+    // The login form
+    ("login.login", "Mewngofnodi"),
+    (
+        "login.login-or-create-account",
+        "Mewngofnodi neu greu cyfrif",
+    ),
+    ("login.create-account", "Creu cyfrif"),
+    ("login.username", "Enw defnyddiwr"),
+    ("login.password", "Cyfrinair"),
+    ("login.confirm-password", "Cadarnhau cyfrinair"),
+    ("login.remember-me", "Cofio fi"),
+    ("login.submit", "Cyflwyno"),
+    (
+        "login.use-weak-password",
+        "Defnyddio'r cyfrinair gwan beth bynnag",
+    ),
+    ("login.missing-username", "Rhowch enw defnyddiwr"),
+    ("login.missing-password", "Rhowch gyfrinair"),
+    (
+        "login.passwords-dont-match",
+        "Nid yw'r cyfrineiriau'n cyfateb",
+    ),
+    (
+        "login.very-weak-password",
+        "Mae'r cyfrinair hwnnw'n wan iawn. Ydych chi'n siŵr eich bod am ei ddefnyddio?",
+    ),
+    ("password.very-weak", "Gwan iawn"),
+    ("password.weak", "Gwan"),
+    ("password.fair", "Gweddol"),
+    ("password.strong", "Cryf"),
+    // Error messages
+    ("error.title", "GWALL:"),
+    ("error.dismiss", "Cau'r neges gwall"),
+    // The test cards
+    ("test.paper", "Papur: "),
+    ("test.mark-scheme", "Cynllun marcio: "),
+    ("test.opens-in-new-tab", "Yn agor mewn tab newydd"),
+    (
+        "test.unsafe-link",
+        "Nid yw hon yn ddolen we sy'n dechrau gyda http:// neu https://, felly does dim modd ei \
+         hagor",
+    ),
+    ("test.edit", "Golygu'r prawf"),
+    ("test.delete", "Dileu'r prawf"),
+    ("test.log-attempt", "Cofnodi ymgais arall"),
+    ("test.best", "Gorau {percentage}%"),
+    ("test.attempts.zero", "Dim ymgeisiau"),
+    ("test.attempts.one", "{count} ymgais"),
+    ("test.attempts.two", "{count} ymgais"),
+    ("test.attempts.few", "{count} ymgais"),
+    ("test.attempts.many", "{count} ymgais"),
+    ("test.attempts.other", "{count} o ymgeisiau"),
+    ("test.confirm-delete.zero", "Bydd hyn yn dileu'r prawf."),
+    (
+        "test.confirm-delete.one",
+        "Bydd hyn yn dileu'r prawf a'i {count} ymgais.",
+    ),
+    (
+        "test.confirm-delete.two",
+        "Bydd hyn yn dileu'r prawf a'i {count} ymgais.",
+    ),
+    (
+        "test.confirm-delete.few",
+        "Bydd hyn yn dileu'r prawf a'i {count} ymgais.",
+    ),
+    (
+        "test.confirm-delete.many",
+        "Bydd hyn yn dileu'r prawf a'i {count} ymgais.",
+    ),
+    (
+        "test.confirm-delete.other",
+        "Bydd hyn yn dileu'r prawf a'i {count} o ymgeisiau.",
+    ),
+    ("test.expand", "Dangos y manylion"),
+    ("test.collapse", "Cuddio'r manylion"),
+    // The page of a single test
+    ("test-page.back", "Yn ôl i'r rhestr"),
+    ("test-page.date-or-id", "Dyddiad neu ID"),
+    ("test-page.qualification-level", "Lefel cymhwyster"),
+    ("test-page.exam-board", "Bwrdd arholi"),
+    ("test-page.paper", "Papur"),
+    ("test-page.mark-scheme", "Cynllun marcio"),
+    ("test-page.attempts", "Ymgeisiau"),
+    ("test-page.no-attempts", "Dim ymgeisiau eto"),
+    ("test-page.date", "Dyddiad"),
+    ("test-page.mark", "Marc"),
+    ("test-page.grade", "Gradd"),
+    ("test-page.comments", "Sylwadau"),
+    ("test-page.personal-best", "Gorau personol"),
+    ("test-page.not-found", "Heb ddod o hyd i'r prawf"),
+    (
+        "test-page.not-found-reason",
+        "Does dim prawf gyda'r ID hwnnw. Efallai ei fod wedi cael ei ddileu.",
+    ),
+    ("test-page.load-failed", "Methu llwytho'r prawf: {reason}"),
+    ("test-page.retry", "Rhoi cynnig arall arni"),
+];
@@ -0,0 +1,225 @@
+//! This module parses files to import on the client, so that the user can see what will be
+//! imported, and leave out rows, before anything is sent to the server.
+//!
+//! CSVs are in the same format as the export. See [`crate::export`]. The rows that the user picks
+//! are encoded again with [`rows_to_csv`](crate::export::rows_to_csv) and sent with
+//! [`ImportCsv`](crate::ClientToServerMsg::ImportCsv), which checks them all again.
+
+use crate::{CompletionData, Error, ImportRowError, TestAndCompletions, TestData};
+use chrono::NaiveDate;
+
+/// The columns that every CSV must have. The rest can be left out.
+const REQUIRED_COLUMNS: [&str; 4] = ["subject", "date_or_id", "achieved_mark", "total_marks"];
+
+/// A single row of a file to import, which is one completion along with its test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportRow {
+    /// The line of a CSV that the row starts on, counting the header as line 1. For a RON file,
+    /// this is the number of the test instead, counting from 1.
+    pub line: u64,
+
+    /// The test and completion, or what was wrong with the row.
+    pub row: Result<(TestData, CompletionData), String>,
+}
+
+/// Check that a test and completion can be imported, which means that the test has a subject and
+/// a date or ID, and the completion has valid marks. The server checks rows in the same way.
+pub fn check_row(
+    test: TestData,
+    completion: CompletionData,
+) -> Result<(TestData, CompletionData), String> {
+    if test.subject.trim().is_empty() {
+        return Err("missing subject".to_string());
+    }
+    if test.date_or_id.trim().is_empty() {
+        return Err("missing date_or_id".to_string());
+    }
+    if let Err(e) = completion.validate() {
+        return Err(Error::from(e).to_string());
+    }
+
+    Ok((test, completion))
+}
+
+/// Split a CSV into records of fields, each with the line that it starts on. Fields in quotes can
+/// contain commas, line breaks, and doubled quotes. Blank lines are skipped.
+fn records(csv: &str) -> Result<Vec<(u64, Vec<String>)>, ImportRowError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            '\n' if in_quotes => {
+                line += 1;
+                field.push(c);
+            }
+            _ if in_quotes => field.push(c),
+            ',' => fields.push(std::mem::take(&mut field)),
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                fields.push(std::mem::take(&mut field));
+                if fields != [""] {
+                    records.push((record_line, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ImportRowError {
+            line: record_line,
+            message: "a quoted field is never closed".to_string(),
+        });
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+
+    Ok(records)
+}
+
+/// Parse a single row of a CSV, given the header row.
+fn parse_row(headers: &[String], record: &[String]) -> Result<(TestData, CompletionData), String> {
+    if record.len() != headers.len() {
+        return Err(format!(
+            "expected {} fields, but found {}",
+            headers.len(),
+            record.len()
+        ));
+    }
+
+    // A column that isn't in the header row is treated as empty
+    let field = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .map_or("", |i| record[i].as_str())
+    };
+    let optional = |name: &str| Some(field(name).to_string()).filter(|value| !value.is_empty());
+    let mark = |name: &str| {
+        field(name)
+            .parse::<i32>()
+            .map_err(|e| format!("invalid {name}: {e}"))
+    };
+
+    let completion = CompletionData {
+        achieved_mark: mark("achieved_mark")?,
+        total_marks: mark("total_marks")?,
+        date: match field("date") {
+            "" => None,
+            date => Some(
+                date.parse::<NaiveDate>()
+                    .map_err(|e| format!("invalid date: {e}"))?,
+            ),
+        },
+        comments: optional("comments"),
+    };
+    let test = TestData {
+        subject: field("subject").to_string(),
+        topic: optional("topic"),
+        date_or_id: field("date_or_id").to_string(),
+        qualification_level: None,
+        exam_board: optional("exam_board"),
+        paper_link: None,
+        mark_scheme_link: None,
+        comments: None,
+    };
+
+    check_row(test, completion)
+}
+
+/// Parse a CSV in the same format as the export into rows, each of which is either valid or has a
+/// problem. The columns can be in any order, as long as the header row names them.
+///
+/// This returns an error if the file as a whole can't be read, like if the header row is missing
+/// a column, or a quoted field is never closed.
+pub fn parse_csv(csv: &str) -> Result<Vec<ImportRow>, ImportRowError> {
+    let mut records = records(csv)?.into_iter();
+    let Some((_, headers)) = records.next() else {
+        return Err(ImportRowError {
+            line: 1,
+            message: "the file is empty".to_string(),
+        });
+    };
+
+    for name in REQUIRED_COLUMNS {
+        if !headers.iter().any(|header| header.trim() == name) {
+            return Err(ImportRowError {
+                line: 1,
+                message: format!("missing column: {name}"),
+            });
+        }
+    }
+
+    Ok(records
+        .map(|(line, record)| ImportRow {
+            line,
+            row: parse_row(&headers, &record),
+        })
+        .collect())
+}
+
+/// Turn tests and their completions, like from a RON file, into rows to import, one for each
+/// completion. Tests without any completions can't be imported, since the CSV that gets sent to
+/// the server only has rows for completions, so they become rows with a problem.
+pub fn rows_from_tests(tests: Vec<TestAndCompletions>) -> Vec<ImportRow> {
+    tests
+        .into_iter()
+        .zip(1..)
+        .flat_map(|((test, completions), line)| {
+            if completions.is_empty() {
+                return vec![ImportRow {
+                    line,
+                    row: Err(format!(
+                        "{} {} has no attempts to import",
+                        test.subject, test.date_or_id
+                    )),
+                }];
+            }
+
+            completions
+                .into_iter()
+                .map(|completion| ImportRow {
+                    line,
+                    row: check_row(test.clone(), completion),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Group valid rows into tests with their completions, in the same way as the server, where
+/// consecutive rows with the same test details become one test.
+pub fn group_rows<'a>(
+    rows: impl IntoIterator<Item = &'a (TestData, CompletionData)>,
+) -> Vec<TestAndCompletions> {
+    let mut tests: Vec<TestAndCompletions> = Vec::new();
+    for (test, completion) in rows {
+        match tests.last_mut() {
+            Some((last, completions)) if last == test => completions.push(completion.clone()),
+            _ => tests.push((test.clone(), vec![completion.clone()])),
+        }
+    }
+    tests
+}
@@ -1,58 +1,483 @@
 //! This crate is a library to be shared between the client and server halves of TestTracker.
 
+pub mod chart;
+pub mod countdown;
 pub mod error;
+pub mod export;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod grade;
+pub mod group;
+pub mod i18n;
+pub mod import;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod page;
+pub mod policy;
+pub mod route;
+pub mod search;
+pub mod settings;
+pub mod sort;
+pub mod streak;
+pub mod summary;
+pub mod theme;
+pub mod view;
 
 pub use self::error::Error;
 
-use chrono::naive::NaiveDate;
+use chrono::{naive::NaiveDate, DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// The version of the protocol between the client and the server. This gets bumped whenever
+/// [`ClientToServerMsg`] or [`ServerToClientMsg`] change in a way that older clients or servers
+/// can't understand.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The paths of the routes on the server. Each [`ClientToServerMsg`] must be sent to the right
+/// path, as documented on each variant.
+pub mod paths {
+    /// Where to send [`Authenticate`](crate::ClientToServerMsg::Authenticate).
+    pub const AUTH: &str = "/api/auth";
+
+    /// Where to send [`CreateUser`](crate::ClientToServerMsg::CreateUser),
+    /// [`ChangePassword`](crate::ClientToServerMsg::ChangePassword), and
+    /// [`DeleteAccount`](crate::ClientToServerMsg::DeleteAccount).
+    pub const USERS: &str = "/api/users";
+
+    /// Where to send [`GetTestsAndCompletions`](crate::ClientToServerMsg::GetTestsAndCompletions),
+    /// [`GetTest`](crate::ClientToServerMsg::GetTest),
+    /// [`AddTest`](crate::ClientToServerMsg::AddTest),
+    /// [`UpdateTest`](crate::ClientToServerMsg::UpdateTest),
+    /// [`DeleteTest`](crate::ClientToServerMsg::DeleteTest),
+    /// [`AddCompletion`](crate::ClientToServerMsg::AddCompletion),
+    /// [`UpdateCompletion`](crate::ClientToServerMsg::UpdateCompletion), and
+    /// [`DeleteCompletion`](crate::ClientToServerMsg::DeleteCompletion).
+    pub const TESTS: &str = "/api/tests";
+
+    /// Where to send [`GetStatistics`](crate::ClientToServerMsg::GetStatistics).
+    pub const STATISTICS: &str = "/api/statistics";
+
+    /// Where to send [`GetAuditLog`](crate::ClientToServerMsg::GetAuditLog).
+    pub const AUDIT: &str = "/api/audit";
+
+    /// Where to send the admin-only messages
+    /// [`ListUsers`](crate::ClientToServerMsg::ListUsers),
+    /// [`AdminResetPassword`](crate::ClientToServerMsg::AdminResetPassword), and
+    /// [`SetLogFilter`](crate::ClientToServerMsg::SetLogFilter).
+    pub const ADMIN: &str = "/api/admin";
+
+    /// Where to send [`ImportCsv`](crate::ClientToServerMsg::ImportCsv).
+    pub const IMPORT: &str = "/api/import";
+
+    /// A CSV export of all the user's completions, which responds to `GET` with the session token
+    /// in the query string, like `/api/export.csv?token=...`. This is a plain URL so that the
+    /// browser can download it directly.
+    pub const EXPORT: &str = "/api/export.csv";
+
+    /// A human-readable page about the server, which responds to `GET` with HTML, for anyone who
+    /// visits the server's URL in a browser.
+    pub const INFO: &str = "/";
+
+    /// A simple health check, which responds to `GET` with a small JSON object.
+    pub const HEALTH: &str = "/health";
+
+    /// Metrics in the Prometheus text format, which responds to `GET`.
+    pub const METRICS: &str = "/metrics";
+
+    /// A WebSocket for push notifications of [`DataChanged`](crate::ServerToClientMsg::DataChanged).
+    /// The client must send its session token as the first message.
+    pub const WEBSOCKET: &str = "/ws";
+}
 
 /// A message that the client can send to the server.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ClientToServerMsg {
-    /// Authenticate a currently existing user with their username and password.
+    /// Authenticate a currently existing user with their username and password. Send this to
+    /// [`paths::AUTH`].
     Authenticate {
         /// The username of the user.
         username: String,
 
         /// The plaintext, unhashed password of the user.
         password: String,
+
+        /// Should the new session use the longer "remember me" length?
+        remember_me: bool,
     },
 
-    /// Create a new user with the given username and password.
+    /// Create a new user with the given username and password. Send this to [`paths::USERS`].
     CreateUser {
         /// The username of the user.
         username: String,
 
         /// The plaintext, unhashed password of the user.
         password: String,
+
+        /// Should the new session use the longer "remember me" length?
+        remember_me: bool,
+    },
+
+    /// Change the user's own password, which needs their current password as well as their
+    /// session, so that someone who finds the user logged in can't lock them out. The new password
+    /// must satisfy the password policy. Every other session for the user is ended. Send this to
+    /// [`paths::USERS`].
+    ChangePassword {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The user's current plaintext password.
+        current_password: String,
+
+        /// The new plaintext password.
+        new_password: String,
+    },
+
+    /// Delete the user's account, along with all their tests, completions, and sessions. This
+    /// needs their password as well as their session, since it can't be undone. Send this to
+    /// [`paths::USERS`].
+    DeleteAccount {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The user's plaintext password.
+        password: String,
     },
 
-    /// Get all the tests and completions for each test for the given user.
+    /// Get the tests and completions for each test for the given user. Send this to
+    /// [`paths::TESTS`].
     GetTestsAndCompletions {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The [`synced_at`](TestsSync::synced_at) of the last sync, to only get what has changed
+        /// since then, or `None` to get everything.
+        since: Option<DateTime<Utc>>,
+
+        /// The order to put the tests in, or `None` to leave them sorted by ID. See
+        /// [`TestsSync::order`].
+        #[serde(default)]
+        sort: Option<TestSort>,
+    },
+
+    /// Get one of the given user's tests along with all its completions, for when the client
+    /// needs a test that it hasn't synced yet. Send this to [`paths::TESTS`].
+    GetTest {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The ID of the test to get. See [`SyncedTest::id`].
+        test_id: i32,
+    },
+
+    /// Add a new test for the given user, without any completions. The test must be valid (see
+    /// [`TestData::validate`]) and not a duplicate. Send this to [`paths::TESTS`].
+    AddTest {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The new test.
+        test: TestData,
+    },
+
+    /// Replace the details of one of the given user's tests, leaving its completions alone. The new
+    /// details must be valid (see [`TestData::validate`]) and not a duplicate of another test. Send
+    /// this to [`paths::TESTS`].
+    UpdateTest {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The ID of the test to change. See [`SyncedTest::id`].
+        test_id: i32,
+
+        /// The new details of the test.
+        test: TestData,
+    },
+
+    /// Delete one of the given user's tests along with all its completions. Send this to
+    /// [`paths::TESTS`].
+    DeleteTest {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The ID of the test to delete. See [`SyncedTest::id`].
+        test_id: i32,
+    },
+
+    /// Add a new completion to one of the given user's tests. The marks must be valid (see
+    /// [`CompletionData::validate`]). Send this to [`paths::TESTS`].
+    AddCompletion {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The ID of the test that was attempted. See [`SyncedTest::id`].
+        test_id: i32,
+
+        /// The new completion.
+        completion: CompletionData,
+    },
+
+    /// Replace the data of one of the given user's completions. The marks must be valid (see
+    /// [`CompletionData::validate`]). Send this to [`paths::TESTS`].
+    UpdateCompletion {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The ID of the completion to change. See [`SyncedTest::completions`].
+        completion_id: i32,
+
+        /// The new data for the completion.
+        completion: CompletionData,
+    },
+
+    /// Delete one of the given user's completions. Send this to [`paths::TESTS`].
+    DeleteCompletion {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The ID of the completion to delete. See [`SyncedTest::completions`].
+        completion_id: i32,
+    },
+
+    /// Get statistics about the completions for each subject for the given user. Send this to
+    /// [`paths::STATISTICS`].
+    GetStatistics {
+        /// The session token. See [`Session::token`].
+        token: String,
+
         /// The user's unique ID. See [`User::id`].
         user_id: String,
     },
+
+    /// Get the most recent entries in the audit log for the given user, like logins and failed
+    /// logins, newest first. Send this to [`paths::AUDIT`].
+    GetAuditLog {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+    },
+
+    /// List every user, for admins only. Send this to [`paths::ADMIN`].
+    ListUsers {
+        /// The session token of an admin. See [`Session::token`].
+        token: String,
+    },
+
+    /// Reset another user's password and end all their sessions, for admins only. Send this to
+    /// [`paths::ADMIN`].
+    AdminResetPassword {
+        /// The session token of an admin. See [`Session::token`].
+        token: String,
+
+        /// The ID of the user whose password should be reset. See [`User::id`].
+        target_user_id: String,
+
+        /// The new plaintext password, which must satisfy the password policy.
+        new_password: String,
+    },
+
+    /// Change what the server logs while it's running, for admins only. Send this to
+    /// [`paths::ADMIN`].
+    SetLogFilter {
+        /// The session token of an admin. See [`Session::token`].
+        token: String,
+
+        /// The new filter for the server's stdout, in the same syntax as `RUST_LOG`, or `None` to
+        /// leave it alone.
+        stdout: Option<String>,
+
+        /// The new filter for the server's log file, in the same syntax as `RUST_LOG`, or `None`
+        /// to leave it alone.
+        file: Option<String>,
+    },
+
+    /// Import tests and completions from a CSV in the same format as the export at
+    /// [`paths::EXPORT`]. Consecutive rows with the same test details become one test with several
+    /// completions. Send this to [`paths::IMPORT`].
+    ImportCsv {
+        /// The session token. See [`Session::token`].
+        token: String,
+
+        /// The user's unique ID. See [`User::id`].
+        user_id: String,
+
+        /// The contents of the CSV file, including the header row.
+        csv: String,
+
+        /// If this is true, then nothing is imported unless every row is valid. Otherwise, the
+        /// valid rows are imported and the invalid ones are reported.
+        strict: bool,
+    },
 }
 
 /// A message that the server can send to the client.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ServerToClientMsg {
-    /// A response to authentication.
-    AuthenticationResponse(Result<User, Error>),
+    /// A response to authentication, containing a new session if successful.
+    AuthenticationResponse(Result<Session, Error>),
 
     /// All the tests that the requested user has done, along with all the completions for each test.
-    TestsAndCompletionsForUser(Result<Vec<TestAndCompletions>, Error>),
+    TestsAndCompletionsForUser(Result<TestsSync, Error>),
+
+    /// A single test that the requested user has done, along with all its completions. A test that
+    /// belongs to someone else gives the same `NotFound` error as one that doesn't exist.
+    TestResponse(Result<SyncedTest, Error>),
+
+    /// The result of adding a test, containing the ID of the new test if successful.
+    AddTestResponse(Result<i32, Error>),
+
+    /// The result of changing the details of a test.
+    UpdateTestResponse(Result<(), Error>),
+
+    /// The result of deleting a test.
+    DeleteTestResponse(Result<(), Error>),
+
+    /// The result of adding a completion, containing the ID of the new completion if successful.
+    AddCompletionResponse(Result<i32, Error>),
+
+    /// The result of changing the data of a completion.
+    UpdateCompletionResponse(Result<(), Error>),
+
+    /// The result of deleting a completion.
+    DeleteCompletionResponse(Result<(), Error>),
+
+    /// Statistics for each subject that the requested user has done any completions in, sorted
+    /// by subject.
+    StatisticsForUser(Result<Vec<SubjectStats>, Error>),
+
+    /// The most recent entries in the requested user's audit log, newest first.
+    AuditLog(Result<Vec<AuditEntry>, Error>),
+
+    /// Every user, sorted by username.
+    UserList(Result<Vec<UserSummary>, Error>),
+
+    /// The result of resetting a user's password.
+    PasswordResetResponse(Result<(), Error>),
+
+    /// The result of the user changing their own password. A wrong current password gives
+    /// [`Error::InvalidPassword`], and a new password that breaks the policy gives
+    /// [`Error::Validation`].
+    ChangePasswordResponse(Result<(), Error>),
+
+    /// The result of the user deleting their account. A wrong password gives
+    /// [`Error::InvalidPassword`].
+    DeleteAccountResponse(Result<(), Error>),
+
+    /// The result of changing the server's log filters.
+    LogFilterResponse(Result<(), Error>),
+
+    /// The result of importing a CSV. Problems with individual rows are reported in the summary
+    /// rather than as an error.
+    ImportResult(Result<ImportSummary, Error>),
+
+    /// The given user's data has changed, so the client should fetch it again. This is only ever
+    /// pushed down a WebSocket, never sent in response to a message.
+    DataChanged {
+        /// The ID of the user whose data changed.
+        user_id: String,
+    },
+
+    /// The server couldn't handle the request at all, like if it timed out. This can be sent in
+    /// response to any message.
+    Error(Error),
+}
+
+impl ServerToClientMsg {
+    /// Get the error in this message, if there is one.
+    pub fn error(&self) -> Option<&Error> {
+        match self {
+            Self::AuthenticationResponse(Err(e))
+            | Self::TestsAndCompletionsForUser(Err(e))
+            | Self::TestResponse(Err(e))
+            | Self::AddTestResponse(Err(e))
+            | Self::UpdateTestResponse(Err(e))
+            | Self::DeleteTestResponse(Err(e))
+            | Self::AddCompletionResponse(Err(e))
+            | Self::UpdateCompletionResponse(Err(e))
+            | Self::DeleteCompletionResponse(Err(e))
+            | Self::StatisticsForUser(Err(e))
+            | Self::AuditLog(Err(e))
+            | Self::UserList(Err(e))
+            | Self::PasswordResetResponse(Err(e))
+            | Self::ChangePasswordResponse(Err(e))
+            | Self::DeleteAccountResponse(Err(e))
+            | Self::LogFilterResponse(Err(e))
+            | Self::ImportResult(Err(e))
+            | Self::Error(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 /// The relevant information about a user.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct User {
-    /// A unique ID used by the server to identify the user.
+    /// A unique ID used by the server to identify the user. New users get a UUIDv4, but older
+    /// users may have IDs in a different format, so the client should treat this as opaque.
+    pub id: String,
+
+    /// The user's username.
+    pub username: String,
+}
+
+/// A summary of a user's account, which only admins can see.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserSummary {
+    /// The user's unique ID.
     pub id: String,
 
     /// The user's username.
     pub username: String,
+
+    /// When the user created their account.
+    pub created_at: DateTime<Utc>,
+
+    /// How many tests the user has.
+    pub test_count: i64,
+}
+
+/// An authenticated user and the token for their session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// The user that this session authenticates.
+    pub user: User,
+
+    /// The session token, which must be sent with every request that needs authentication. The
+    /// session expires after a period of inactivity, after which the server will respond with
+    /// [`Error::Unauthorized`].
+    pub token: String,
 }
 
 /// The important data of the test.
@@ -83,6 +508,45 @@ pub struct TestData {
     pub comments: Option<String>,
 }
 
+impl TestData {
+    /// The names of the fields, as used in [`Error::Validation`].
+    pub const FIELDS: [&'static str; 8] = [
+        "subject",
+        "topic",
+        "date_or_id",
+        "qualification_level",
+        "exam_board",
+        "paper_link",
+        "mark_scheme_link",
+        "comments",
+    ];
+
+    /// Check that the required fields are filled in: every test needs a subject and a date or ID,
+    /// which aren't just whitespace.
+    pub fn validate(&self) -> Result<(), policy::TestPolicyError> {
+        policy::check_test_fields(&self.subject, &self.date_or_id)
+    }
+
+    /// Normalize the whitespace in every field, so that the same test copied from different places
+    /// is stored the same way. See [`policy::normalize_text`].
+    ///
+    /// The links and comments are only trimmed, since the whitespace inside them might matter.
+    pub fn normalized(&self) -> Self {
+        Self {
+            subject: policy::normalize_text(&self.subject),
+            topic: policy::normalize_optional_text(self.topic.as_deref()),
+            date_or_id: policy::normalize_text(&self.date_or_id),
+            qualification_level: policy::normalize_optional_text(
+                self.qualification_level.as_deref(),
+            ),
+            exam_board: policy::normalize_optional_text(self.exam_board.as_deref()),
+            paper_link: policy::trim_optional_text(self.paper_link.as_deref()),
+            mark_scheme_link: policy::trim_optional_text(self.mark_scheme_link.as_deref()),
+            comments: policy::trim_optional_text(self.comments.as_deref()),
+        }
+    }
+}
+
 /// The important data of the completion.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CompletionData {
@@ -99,5 +563,246 @@ pub struct CompletionData {
     pub comments: Option<String>,
 }
 
+impl CompletionData {
+    /// Check that the marks make sense: there must be some marks available, and the achieved mark
+    /// must be between 0 and the total.
+    pub fn validate(&self) -> Result<(), policy::MarksPolicyError> {
+        policy::check_marks(self.achieved_mark, self.total_marks)
+    }
+
+    /// The achieved mark as a percentage of the total marks, or `None` if the test was out of 0
+    /// marks, so there's no sensible percentage.
+    pub fn percentage(&self) -> Option<f64> {
+        (self.total_marks != 0)
+            .then(|| 100. * f64::from(self.achieved_mark) / f64::from(self.total_marks))
+    }
+}
+
+/// Statistics about all the completions of every test in one subject.
+///
+/// Scores are fractions of the total marks, between 0 and 1. Completions out of 0 marks are
+/// counted, but don't affect the scores.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SubjectStats {
+    /// The subject: maths, English, science, etc.
+    pub subject: String,
+
+    /// How many completions there are across all the tests in this subject.
+    pub completion_count: i64,
+
+    /// The mean score across all the completions, if any have a non-zero total.
+    pub average_score: Option<f64>,
+
+    /// The best score of any completion, if any have a non-zero total.
+    pub best_score: Option<f64>,
+}
+
+/// Something security-relevant that happened to an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditEvent {
+    /// Someone logged in successfully.
+    Login,
+
+    /// Someone tried to log in with the wrong password.
+    FailedLogin,
+
+    /// The account was created.
+    AccountCreated,
+
+    /// The password was changed.
+    PasswordChanged,
+
+    /// The account was deleted. The entry isn't attached to the account, since that's gone.
+    AccountDeleted,
+
+    /// One of the account's tests was deleted, along with all its completions.
+    TestDeleted,
+}
+
+impl AuditEvent {
+    /// The name of this event, as stored in the database.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::FailedLogin => "failed_login",
+            Self::AccountCreated => "account_created",
+            Self::PasswordChanged => "password_changed",
+            Self::AccountDeleted => "account_deleted",
+            Self::TestDeleted => "test_deleted",
+        }
+    }
+}
+
+impl fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "login" => Ok(Self::Login),
+            "failed_login" => Ok(Self::FailedLogin),
+            "account_created" => Ok(Self::AccountCreated),
+            "password_changed" => Ok(Self::PasswordChanged),
+            "account_deleted" => Ok(Self::AccountDeleted),
+            "test_deleted" => Ok(Self::TestDeleted),
+            _ => Err(format!("unknown audit event: {s:?}")),
+        }
+    }
+}
+
+/// A single entry in a user's audit log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// What happened.
+    pub event: AuditEvent,
+
+    /// The IP address and port that the request came from, if known.
+    pub remote_addr: Option<String>,
+
+    /// When it happened.
+    pub created_at: DateTime<Utc>,
+
+    /// Any extra details about the event, as a JSON object.
+    pub details: String,
+}
+
+/// What happened when importing a CSV.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// How many tests were created.
+    pub tests_imported: usize,
+
+    /// How many completions were created.
+    pub completions_imported: usize,
+
+    /// The problems with any rows that couldn't be imported.
+    pub errors: Vec<ImportRowError>,
+}
+
+/// A problem with a single row of an imported CSV.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportRowError {
+    /// The line of the file that the row starts on, counting the header as line 1.
+    pub line: u64,
+
+    /// What was wrong with the row.
+    pub message: String,
+}
+
 /// A convenience type for a tuple containing a test and its completions.
 pub type TestAndCompletions = (TestData, Vec<CompletionData>);
+
+/// A test and all its completions, along with their IDs in the database, so that changes can be
+/// merged into a list that the client already has.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedTest {
+    /// The ID of the test.
+    pub id: i32,
+
+    /// The test itself.
+    pub test: TestData,
+
+    /// Every completion of this test, with their IDs.
+    pub completions: Vec<(i32, CompletionData)>,
+
+    /// The ID of the personal best completion, which has the best fraction of the total marks.
+    /// Ties go to the most recent completion, and completions out of 0 marks never count, so this
+    /// is `None` if there aren't any other completions.
+    #[serde(default)]
+    pub best_completion_id: Option<i32>,
+}
+
+impl From<SyncedTest> for TestAndCompletions {
+    fn from(value: SyncedTest) -> Self {
+        let SyncedTest {
+            test, completions, ..
+        } = value;
+        (
+            test,
+            completions
+                .into_iter()
+                .map(|(_, completion)| completion)
+                .collect(),
+        )
+    }
+}
+
+/// An order that the client can ask for the tests to be in. Ties are always broken by ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TestSort {
+    /// Alphabetically by subject, then by date or ID.
+    SubjectAsc,
+
+    /// The tests with the most recent completion first, with tests that have never been
+    /// completed (or only without dates) at the end.
+    RecentlyAttempted,
+
+    /// The tests with the worst best percentage first, with tests that have never been completed
+    /// at the end.
+    BestPercentageAsc,
+
+    /// The tests with the best best percentage first, with tests that have never been completed at
+    /// the end.
+    BestPercentageDesc,
+
+    /// The oldest exams first, going by the date in the date or ID, like "June 2019 Paper 1".
+    /// Tests without a recognisable date go at the end.
+    ExamDateAsc,
+
+    /// An order from a newer client that this server doesn't know about, which leaves the tests
+    /// sorted by ID.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The changes to a user's tests and completions since the last sync.
+///
+/// Changes are tracked per test, so adding, changing, or deleting a completion means that its
+/// test gets sent again with all its completions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestsSync {
+    /// Whether this is a full sync of everything, rather than just the changes since `since`.
+    /// The server falls back to a full sync if `since` is missing or in its future.
+    pub full: bool,
+
+    /// Every test that has changed since the last sync, or every test for a full sync.
+    pub changed: Vec<SyncedTest>,
+
+    /// The IDs of the tests that have been deleted since the last sync. This is always empty for
+    /// a full sync.
+    pub deleted: Vec<i32>,
+
+    /// The time of this sync according to the server's clock, to send back as `since` next time.
+    /// The client must never use its own clock for this, since it may not agree with the server.
+    pub synced_at: DateTime<Utc>,
+
+    /// The IDs of all the user's tests in the order that was asked for, including those that
+    /// haven't changed, or `None` if they should just be sorted by ID. `changed` is in this order
+    /// too.
+    #[serde(default)]
+    pub order: Option<Vec<i32>>,
+}
+
+impl TestsSync {
+    /// Merge these changes into the tests that the client already has, keyed by their IDs.
+    ///
+    /// Syncs may overlap slightly, so merging the same changes twice is harmless.
+    pub fn merge_into(self, tests: &mut BTreeMap<i32, SyncedTest>) {
+        if self.full {
+            tests.clear();
+        }
+
+        for id in self.deleted {
+            tests.remove(&id);
+        }
+
+        for test in self.changed {
+            tests.insert(test.id, test);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! This module parses the small subset of Markdown that the client renders in comments: emphasis,
+//! lists, and links. Everything else is kept as plain text.
+//!
+//! The result is a tree of [`Block`]s rather than HTML, so the client builds every element itself
+//! and raw HTML in a comment can never reach the page. Links only keep their destination if it
+//! passes [`is_web_link`], so a `javascript:` link just becomes its text.
+
+use crate::policy::is_web_link;
+use pulldown_cmark::{Event, Parser, Tag};
+
+/// How deeply lists, and emphasis and links, can be nested before anything deeper is flattened, so
+/// that a pathological comment can't make a tree so deep that rendering or dropping it overflows
+/// the stack.
+pub const MAX_NESTING: usize = 16;
+
+/// A block of a comment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    /// A paragraph of text. Headings, quotes, and code blocks become paragraphs too.
+    Paragraph(Vec<Inline>),
+
+    /// A bulleted or numbered list.
+    List {
+        /// The number of the first item, or `None` for bullets.
+        start: Option<u64>,
+
+        /// The blocks in each item.
+        items: Vec<Vec<Block>>,
+    },
+}
+
+/// A piece of text inside a [`Block`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inline {
+    /// Plain text, including any raw HTML, which is shown as it was written.
+    Text(String),
+
+    /// A line break. Every line break in a comment is kept, since people write comments like
+    /// notes rather than like Markdown.
+    LineBreak,
+
+    /// Emphasised text, which is usually shown in italics.
+    Emphasis(Vec<Inline>),
+
+    /// Strongly emphasised text, which is usually shown in bold.
+    Strong(Vec<Inline>),
+
+    /// A link to a web page. See [`is_web_link`].
+    Link {
+        /// Where the link goes.
+        href: String,
+
+        /// The text of the link.
+        children: Vec<Inline>,
+    },
+}
+
+/// The kind of inline element that's being built while parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum InlineKind {
+    /// The paragraph that contains everything else.
+    Paragraph,
+
+    /// See [`Inline::Emphasis`].
+    Emphasis,
+
+    /// See [`Inline::Strong`].
+    Strong,
+
+    /// A link, with its destination if it's safe to keep. An unsafe link just becomes its text.
+    Link(Option<String>),
+
+    /// Anything else that we don't render, like an image, which just becomes its text.
+    Plain,
+}
+
+/// The state of parsing, with a stack for each kind of thing that can be nested.
+#[derive(Debug)]
+struct Builder {
+    /// The blocks of the whole comment, and then of each list item that's open.
+    blocks: Vec<Vec<Block>>,
+
+    /// The lists that are open, with the start number and the items finished so far.
+    lists: Vec<(Option<u64>, Vec<Vec<Block>>)>,
+
+    /// How many lists are open beyond [`MAX_NESTING`], whose items go in the innermost list that
+    /// isn't flattened.
+    flattened_lists: usize,
+
+    /// The paragraph that's open, if any, and then the inline elements open inside it.
+    inlines: Vec<(InlineKind, Vec<Inline>)>,
+}
+
+impl Builder {
+    /// Start with an empty comment.
+    fn new() -> Self {
+        Self {
+            blocks: vec![vec![]],
+            lists: vec![],
+            flattened_lists: 0,
+            inlines: vec![],
+        }
+    }
+
+    /// Add a block to the innermost open list item, or the whole comment.
+    fn push_block(&mut self, block: Block) {
+        if let Some(blocks) = self.blocks.last_mut() {
+            blocks.push(block);
+        }
+    }
+
+    /// Add an inline element to the innermost open element, opening a paragraph first if there
+    /// isn't one, like for the text of an item in a tight list.
+    fn push_inline(&mut self, inline: Inline) {
+        if self.inlines.is_empty() {
+            self.inlines.push((InlineKind::Paragraph, vec![]));
+        }
+        if let Some((_, children)) = self.inlines.last_mut() {
+            children.push(inline);
+        }
+    }
+
+    /// Open an inline element.
+    fn open_inline(&mut self, kind: InlineKind) {
+        if self.inlines.is_empty() {
+            self.inlines.push((InlineKind::Paragraph, vec![]));
+        }
+        if self.inlines.len() > MAX_NESTING {
+            self.inlines.push((InlineKind::Plain, vec![]));
+        } else {
+            self.inlines.push((kind, vec![]));
+        }
+    }
+
+    /// Close the innermost inline element and add it to its parent.
+    fn close_inline(&mut self) {
+        if self.inlines.len() < 2 {
+            return;
+        }
+        let Some((kind, children)) = self.inlines.pop() else {
+            return;
+        };
+
+        match kind {
+            InlineKind::Emphasis => self.push_inline(Inline::Emphasis(children)),
+            InlineKind::Strong => self.push_inline(Inline::Strong(children)),
+            InlineKind::Link(Some(href)) => self.push_inline(Inline::Link { href, children }),
+            InlineKind::Link(None) | InlineKind::Plain | InlineKind::Paragraph => {
+                for child in children {
+                    self.push_inline(child);
+                }
+            }
+        }
+    }
+
+    /// Close the open paragraph, if there is one, along with anything still open inside it.
+    fn close_paragraph(&mut self) {
+        while self.inlines.len() > 1 {
+            self.close_inline();
+        }
+        if let Some((_, children)) = self.inlines.pop() {
+            if !children.is_empty() {
+                self.push_block(Block::Paragraph(children));
+            }
+        }
+    }
+
+    /// Handle the start of a tag.
+    fn start(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph | Tag::Heading(..) | Tag::CodeBlock(_) => {
+                self.close_paragraph();
+                self.inlines.push((InlineKind::Paragraph, vec![]));
+            }
+            Tag::List(start) => {
+                self.close_paragraph();
+                if self.lists.len() < MAX_NESTING {
+                    self.lists.push((start, vec![]));
+                } else {
+                    self.flattened_lists += 1;
+                }
+            }
+            Tag::Item => {
+                self.close_paragraph();
+                self.blocks.push(vec![]);
+            }
+            // Only the paragraphs inside these are kept
+            Tag::BlockQuote | Tag::FootnoteDefinition(_) => self.close_paragraph(),
+            Tag::Emphasis => self.open_inline(InlineKind::Emphasis),
+            Tag::Strong => self.open_inline(InlineKind::Strong),
+            Tag::Link(_, href, _) => self.open_inline(InlineKind::Link(
+                is_web_link(&href).then(|| href.trim().to_string()),
+            )),
+            _ => self.open_inline(InlineKind::Plain),
+        }
+    }
+
+    /// Handle the end of a tag.
+    fn end(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph | Tag::Heading(..) | Tag::CodeBlock(_) => self.close_paragraph(),
+            Tag::List(_) => {
+                self.close_paragraph();
+                if self.flattened_lists > 0 {
+                    self.flattened_lists -= 1;
+                } else if let Some((start, items)) = self.lists.pop() {
+                    self.push_block(Block::List { start, items });
+                }
+            }
+            Tag::BlockQuote | Tag::FootnoteDefinition(_) => self.close_paragraph(),
+            Tag::Item => {
+                self.close_paragraph();
+                if self.blocks.len() > 1 {
+                    let item = self.blocks.pop().unwrap_or_default();
+                    if let Some((_, items)) = self.lists.last_mut() {
+                        items.push(item);
+                    }
+                }
+            }
+            _ => self.close_inline(),
+        }
+    }
+
+    /// Finish parsing, closing anything that's still open.
+    fn finish(mut self) -> Vec<Block> {
+        self.close_paragraph();
+        self.blocks.into_iter().next().unwrap_or_default()
+    }
+}
+
+/// Parse a comment as Markdown. Raw HTML is kept as text, and any link that isn't a web link
+/// becomes its text.
+pub fn parse_markdown(text: &str) -> Vec<Block> {
+    let mut builder = Builder::new();
+
+    for event in Parser::new(text) {
+        match event {
+            Event::Start(tag) => builder.start(tag),
+            Event::End(tag) => builder.end(tag),
+            Event::Text(text) | Event::Code(text) | Event::Html(text) => {
+                builder.push_inline(Inline::Text(text.into_string()))
+            }
+            Event::SoftBreak | Event::HardBreak => builder.push_inline(Inline::LineBreak),
+            Event::FootnoteReference(_) | Event::Rule | Event::TaskListMarker(_) => {}
+        }
+    }
+
+    builder.finish()
+}
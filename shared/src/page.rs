@@ -0,0 +1,78 @@
+//! This module splits the client's list of tests into pages, so that only one page of cards is
+//! rendered at a time.
+//!
+//! Pages are taken after searching, filtering, and sorting, so those still cover every test.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// How many tests to show on each page.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PageSize {
+    /// 25 tests.
+    #[default]
+    TwentyFive,
+
+    /// 50 tests.
+    Fifty,
+
+    /// Every test on one page.
+    All,
+}
+
+impl PageSize {
+    /// Every page size, in the order to offer them.
+    pub const ALL: [Self; 3] = [Self::TwentyFive, Self::Fifty, Self::All];
+
+    /// The most tests on a page of this size, or `None` for no limit.
+    pub fn limit(self) -> Option<usize> {
+        match self {
+            Self::TwentyFive => Some(25),
+            Self::Fifty => Some(50),
+            Self::All => None,
+        }
+    }
+
+    /// The label to show for this page size, like "25" or "All".
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TwentyFive => "25",
+            Self::Fifty => "50",
+            Self::All => "All",
+        }
+    }
+}
+
+/// One page of a list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page {
+    /// The index of this page, starting from 0.
+    pub index: usize,
+
+    /// How many pages there are. This is always at least 1, even for an empty list.
+    pub count: usize,
+
+    /// The indices of the items on this page.
+    pub range: Range<usize>,
+}
+
+/// Find the page with the given index in a list of `total` items. An index past the end gives the
+/// last page, so that deleting or filtering out tests never leaves the user on an empty page.
+pub fn paginate(total: usize, size: PageSize, index: usize) -> Page {
+    let Some(limit) = size.limit() else {
+        return Page {
+            index: 0,
+            count: 1,
+            range: 0..total,
+        };
+    };
+
+    let count = total.div_ceil(limit).max(1);
+    let index = index.min(count - 1);
+    let start = index * limit;
+    Page {
+        index,
+        count,
+        range: start..total.min(start + limit),
+    }
+}
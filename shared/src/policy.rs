@@ -0,0 +1,380 @@
+//! This module contains the policies that user input must satisfy, like password strength and
+//! sensible marks.
+//!
+//! These live in the shared crate so that the client can check them before sending anything, but
+//! the server always enforces them itself.
+
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+/// The minimum length of a username, in characters (not bytes).
+pub const MIN_USERNAME_LENGTH: usize = 3;
+
+/// The maximum length of a username, in characters (not bytes).
+pub const MAX_USERNAME_LENGTH: usize = 32;
+
+/// Usernames that nobody is allowed to register, since they could be mistaken for something
+/// official.
+const RESERVED_USERNAMES: &[&str] = &[
+    "admin",
+    "administrator",
+    "moderator",
+    "root",
+    "support",
+    "system",
+    "testtracker",
+    "test-tracker",
+];
+
+/// The minimum length of a password, in characters (not bytes).
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// A small list of the most commonly used passwords, which are refused outright. All of these are
+/// at least [`MIN_PASSWORD_LENGTH`] characters, since anything shorter is already refused.
+const COMMON_PASSWORDS: &[&str] = &[
+    "00000000",
+    "11111111",
+    "12121212",
+    "12341234",
+    "12344321",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "1q2w3e4r",
+    "1qaz2wsx",
+    "87654321",
+    "88888888",
+    "abcd1234",
+    "aaaaaaaa",
+    "baseball",
+    "football",
+    "iloveyou",
+    "letmein1",
+    "passw0rd",
+    "password",
+    "password1",
+    "password123",
+    "princess",
+    "qwerty123",
+    "qwertyuiop",
+    "starwars",
+    "sunshine",
+    "superman",
+    "trustno1",
+    "welcome1",
+];
+
+/// A way that a username can violate the username policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Error)]
+pub enum UsernamePolicyError {
+    /// The username has fewer than [`MIN_USERNAME_LENGTH`] characters.
+    #[error("username must be at least {MIN_USERNAME_LENGTH} characters long")]
+    TooShort,
+
+    /// The username has more than [`MAX_USERNAME_LENGTH`] characters.
+    #[error("username must be at most {MAX_USERNAME_LENGTH} characters long")]
+    TooLong,
+
+    /// The username contains a character other than a letter, digit, `_`, `-`, or `.`.
+    #[error("username must only contain letters, digits, '_', '-', and '.', not {0:?}")]
+    InvalidCharacter(char),
+
+    /// The username is reserved.
+    #[error("username is reserved")]
+    Reserved,
+}
+
+impl From<UsernamePolicyError> for Error {
+    fn from(value: UsernamePolicyError) -> Self {
+        Self::Validation {
+            field: "username".to_string(),
+            reason: value.to_string(),
+        }
+    }
+}
+
+/// Normalize a username so that visually identical usernames are actually identical. This trims
+/// whitespace, applies Unicode NFC normalization, and lowercases it.
+///
+/// This must be applied to the username both when creating an account and when logging in.
+pub fn normalize_username(username: &str) -> String {
+    username.trim().nfc().collect::<String>().to_lowercase()
+}
+
+/// Check that an already normalized username satisfies the username policy. See
+/// [`normalize_username`].
+pub fn check_username(username: &str) -> Result<(), UsernamePolicyError> {
+    let length = username.chars().count();
+    if length < MIN_USERNAME_LENGTH {
+        return Err(UsernamePolicyError::TooShort);
+    }
+    if length > MAX_USERNAME_LENGTH {
+        return Err(UsernamePolicyError::TooLong);
+    }
+
+    if let Some(c) = username
+        .chars()
+        .find(|&c| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+    {
+        return Err(UsernamePolicyError::InvalidCharacter(c));
+    }
+
+    if RESERVED_USERNAMES.contains(&username) {
+        return Err(UsernamePolicyError::Reserved);
+    }
+
+    Ok(())
+}
+
+/// A way that a password can violate the password policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Error)]
+pub enum PasswordPolicyError {
+    /// The password has fewer than [`MIN_PASSWORD_LENGTH`] characters.
+    #[error("password must be at least {MIN_PASSWORD_LENGTH} characters long")]
+    TooShort,
+
+    /// The password is the same as the username.
+    #[error("password must not be the same as the username")]
+    SameAsUsername,
+
+    /// The password is one of the most commonly used passwords.
+    #[error("password is too common")]
+    TooCommon,
+}
+
+impl From<PasswordPolicyError> for Error {
+    fn from(value: PasswordPolicyError) -> Self {
+        Self::Validation {
+            field: "password".to_string(),
+            reason: value.to_string(),
+        }
+    }
+}
+
+/// Check that the password satisfies the password policy for the given username.
+pub fn check_password(username: &str, password: &str) -> Result<(), PasswordPolicyError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(PasswordPolicyError::TooShort);
+    }
+
+    let lowercase = password.to_lowercase();
+
+    if lowercase == username.trim().to_lowercase() {
+        return Err(PasswordPolicyError::SameAsUsername);
+    }
+
+    if COMMON_PASSWORDS.contains(&lowercase.as_str()) {
+        return Err(PasswordPolicyError::TooCommon);
+    }
+
+    Ok(())
+}
+
+/// How strong a password that satisfies the password policy is, going by
+/// [`password_entropy_bits`]. See [`password_strength`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PasswordStrength {
+    /// Less than [`WEAK_PASSWORD_BITS`], like a short word in lowercase letters.
+    VeryWeak,
+
+    /// At least [`WEAK_PASSWORD_BITS`], but less than [`FAIR_PASSWORD_BITS`].
+    Weak,
+
+    /// At least [`FAIR_PASSWORD_BITS`], but less than [`STRONG_PASSWORD_BITS`].
+    Fair,
+
+    /// At least [`STRONG_PASSWORD_BITS`].
+    Strong,
+}
+
+/// The estimated entropy that a password needs to be [`PasswordStrength::Weak`] rather than
+/// [`PasswordStrength::VeryWeak`].
+pub const WEAK_PASSWORD_BITS: f64 = 40.;
+
+/// The estimated entropy that a password needs to be [`PasswordStrength::Fair`].
+pub const FAIR_PASSWORD_BITS: f64 = 55.;
+
+/// The estimated entropy that a password needs to be [`PasswordStrength::Strong`].
+pub const STRONG_PASSWORD_BITS: f64 = 70.;
+
+/// Estimate the entropy of a password in bits, as if each character was picked at random from
+/// all the characters in the classes that it uses: lowercase letters, uppercase letters, digits,
+/// ASCII symbols, and anything else.
+///
+/// This overestimates the strength of words and patterns, which is why the most common passwords
+/// are refused by [`check_password`] separately.
+pub fn password_entropy_bits(password: &str) -> f64 {
+    let has = |class: fn(&char) -> bool| password.chars().any(|c| class(&c));
+    let pool_size: u32 = [
+        (has(char::is_ascii_lowercase), 26),
+        (has(char::is_ascii_uppercase), 26),
+        (has(char::is_ascii_digit), 10),
+        (has(char::is_ascii_punctuation), 32),
+        (
+            has(|c| !c.is_ascii_alphanumeric() && !c.is_ascii_punctuation()),
+            100,
+        ),
+    ]
+    .into_iter()
+    .filter(|&(has_class, _)| has_class)
+    .map(|(_, size)| size)
+    .sum();
+
+    if pool_size == 0 {
+        return 0.;
+    }
+    password.chars().count() as f64 * f64::from(pool_size).log2()
+}
+
+/// Check the password against the password policy with [`check_password`], and if it passes,
+/// rate how strong it is.
+///
+/// The server only enforces the policy, so even [`PasswordStrength::VeryWeak`] passwords are
+/// allowed, but the client warns about them.
+pub fn password_strength(
+    username: &str,
+    password: &str,
+) -> Result<PasswordStrength, PasswordPolicyError> {
+    check_password(username, password)?;
+
+    let bits = password_entropy_bits(password);
+    Ok(if bits < WEAK_PASSWORD_BITS {
+        PasswordStrength::VeryWeak
+    } else if bits < FAIR_PASSWORD_BITS {
+        PasswordStrength::Weak
+    } else if bits < STRONG_PASSWORD_BITS {
+        PasswordStrength::Fair
+    } else {
+        PasswordStrength::Strong
+    })
+}
+
+/// A way that the marks of a completion can be invalid.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Error)]
+pub enum MarksPolicyError {
+    /// The total marks available isn't positive.
+    #[error("total marks must be more than 0")]
+    NoMarksAvailable,
+
+    /// The achieved mark is negative.
+    #[error("achieved mark must not be negative")]
+    NegativeMark,
+
+    /// The achieved mark is more than the total marks available.
+    #[error("achieved mark must not be more than the total marks")]
+    MoreThanTotal,
+}
+
+impl MarksPolicyError {
+    /// The name of the field that's invalid.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::NoMarksAvailable => "total_marks",
+            Self::NegativeMark | Self::MoreThanTotal => "achieved_mark",
+        }
+    }
+}
+
+impl From<MarksPolicyError> for Error {
+    fn from(value: MarksPolicyError) -> Self {
+        Self::Validation {
+            field: value.field().to_string(),
+            reason: value.to_string(),
+        }
+    }
+}
+
+/// Check that a completion has a positive number of total marks, and an achieved mark between 0
+/// and the total marks. See [`CompletionData::validate`](crate::CompletionData::validate).
+pub fn check_marks(achieved_mark: i32, total_marks: i32) -> Result<(), MarksPolicyError> {
+    if total_marks <= 0 {
+        return Err(MarksPolicyError::NoMarksAvailable);
+    }
+    if achieved_mark < 0 {
+        return Err(MarksPolicyError::NegativeMark);
+    }
+    if achieved_mark > total_marks {
+        return Err(MarksPolicyError::MoreThanTotal);
+    }
+
+    Ok(())
+}
+
+/// A way that a test can be invalid.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Error)]
+pub enum TestPolicyError {
+    /// The subject is empty.
+    #[error("please enter a subject")]
+    MissingSubject,
+
+    /// The date or ID is empty.
+    #[error("please enter a date or ID")]
+    MissingDateOrId,
+}
+
+impl TestPolicyError {
+    /// The name of the field that's invalid.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::MissingSubject => "subject",
+            Self::MissingDateOrId => "date_or_id",
+        }
+    }
+}
+
+impl From<TestPolicyError> for Error {
+    fn from(value: TestPolicyError) -> Self {
+        Self::Validation {
+            field: value.field().to_string(),
+            reason: value.to_string(),
+        }
+    }
+}
+
+/// Check that a test has a subject and a date or ID, once normalized. See
+/// [`TestData::validate`](crate::TestData::validate).
+pub fn check_test_fields(subject: &str, date_or_id: &str) -> Result<(), TestPolicyError> {
+    if normalize_text(subject).is_empty() {
+        return Err(TestPolicyError::MissingSubject);
+    }
+    if normalize_text(date_or_id).is_empty() {
+        return Err(TestPolicyError::MissingDateOrId);
+    }
+
+    Ok(())
+}
+
+/// Normalize a short piece of text, like a subject or topic, so that text copied from elsewhere
+/// compares equal to text typed by hand. This trims it, and collapses every run of whitespace
+/// (including non-breaking spaces) into a single normal space.
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Normalize an optional piece of text with [`normalize_text`], treating text that's only
+/// whitespace as missing.
+pub fn normalize_optional_text(text: Option<&str>) -> Option<String> {
+    text.map(normalize_text).filter(|text| !text.is_empty())
+}
+
+/// Trim an optional piece of text, like a link or a comment, without touching the inside of it,
+/// and treat text that's only whitespace as missing.
+pub fn trim_optional_text(text: Option<&str>) -> Option<String> {
+    text.map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Check whether a stored link is safe to make clickable, which means that it's an `http` or
+/// `https` URL with something after the scheme. Anything else, like a `javascript:` URL, should
+/// only be shown as text.
+pub fn is_web_link(link: &str) -> bool {
+    let link = link.trim();
+    let Some((scheme, rest)) = link.split_once("://") else {
+        return false;
+    };
+    (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https"))
+        && !rest.is_empty()
+}
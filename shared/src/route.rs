@@ -0,0 +1,41 @@
+//! This module works out which page of the client to show from the fragment of its URL, like
+//! `#/tests/12`, so that a test's page can be linked to and the browser's back button works.
+//!
+//! The routes are in the fragment rather than the path, so that whatever serves the client doesn't
+//! need to know about them.
+
+/// A page of the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    /// The list of all the user's tests, at `#/`. This is also the page for an empty fragment.
+    List,
+
+    /// The page of a single test, with every detail and completion, at `#/tests/:id`. See
+    /// [`SyncedTest::id`](crate::SyncedTest::id).
+    Test(i32),
+}
+
+impl Route {
+    /// Find the page for the given fragment, with or without the `#`. This is `None` if it doesn't
+    /// match any page, like `#/tests/abc`, which the client shows as not found.
+    pub fn from_hash(hash: &str) -> Option<Self> {
+        let path = hash.strip_prefix('#').unwrap_or(hash);
+        let path = path.strip_suffix('/').unwrap_or(path);
+        match path.strip_prefix('/').unwrap_or(path) {
+            "" => Some(Self::List),
+            path => match path.split('/').collect::<Vec<_>>()[..] {
+                ["tests", id] => id.parse().ok().map(Self::Test),
+                _ => None,
+            },
+        }
+    }
+
+    /// The link to this page, including the `#`. [`from_hash`](Route::from_hash) gives back the
+    /// same route for it.
+    pub fn href(self) -> String {
+        match self {
+            Self::List => "#/".to_string(),
+            Self::Test(id) => format!("#/tests/{id}"),
+        }
+    }
+}
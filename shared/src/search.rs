@@ -0,0 +1,29 @@
+//! This module matches tests against the text in the search box, so that the client can filter
+//! the tests that it already has as the user types.
+
+use crate::TestData;
+
+impl TestData {
+    /// Check whether this test matches the given search. Every word of the search has to appear,
+    /// ignoring case, in at least one of the subject, topic, date or ID, exam board, or comments,
+    /// but the words don't have to be together or in the same field. An empty search matches
+    /// everything.
+    pub fn matches_search(&self, search: &str) -> bool {
+        let fields: Vec<String> = [
+            Some(&self.subject),
+            self.topic.as_ref(),
+            Some(&self.date_or_id),
+            self.exam_board.as_ref(),
+            self.comments.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|field| field.to_lowercase())
+        .collect();
+
+        search.split_whitespace().all(|word| {
+            let word = word.to_lowercase();
+            fields.iter().any(|field| field.contains(&word))
+        })
+    }
+}
@@ -0,0 +1,45 @@
+//! This module holds the client's settings, which are stored together in `localStorage` as one
+//! [`Settings`] value.
+
+use crate::{grade::GradeBoundaries, i18n::Language, streak::StreakUnit, theme::ThemePreference};
+use serde::{Deserialize, Serialize};
+
+/// Everything that the user can change in the client's settings.
+///
+/// Missing fields take their defaults when this is deserialized, so that settings saved by an
+/// older client still load after new ones are added.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The theme that the user picked.
+    pub theme: ThemePreference,
+
+    /// The grade boundaries to show grades with.
+    pub grade_boundaries: GradeBoundaries,
+
+    /// The total marks to fill in when adding a completion to a test that hasn't been completed
+    /// before. Tests that have been completed use the total marks of their latest completion
+    /// instead.
+    pub default_total_marks: Option<i32>,
+
+    /// The language that the user picked, or `None` to use the browser's language.
+    pub language: Option<Language>,
+
+    /// Whether practice streaks count days or weeks in a row.
+    pub streak_unit: StreakUnit,
+}
+
+impl Settings {
+    /// Gather the settings that older clients stored under their own keys: the theme, which was
+    /// stored as plain text like "dark", and the grade boundaries. Anything missing takes its
+    /// default.
+    pub fn from_legacy(theme: Option<String>, grade_boundaries: Option<GradeBoundaries>) -> Self {
+        Self {
+            theme: theme.map(ThemePreference::from).unwrap_or_default(),
+            grade_boundaries: grade_boundaries.unwrap_or_default(),
+            default_total_marks: None,
+            language: None,
+            streak_unit: StreakUnit::Days,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! This module sorts tests into the orders in [`TestSort`], so that the client can sort the tests
+//! that it already has without asking the server again.
+//!
+//! The server sorts in the database, but the results must match these, apart from
+//! [`TestSort::SubjectAsc`], where the database might use a different collation.
+
+use crate::{CompletionData, SyncedTest, TestSort};
+use chrono::NaiveDate;
+use std::cmp::Ordering;
+
+/// The full names of the months, in order.
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Parse the name of a month, or an abbreviation of at least three letters, like "Jun" or "Sept".
+fn month(word: &str) -> Option<u32> {
+    let word = word.to_lowercase();
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(&word))
+        .map(|index| index as u32 + 1)
+}
+
+/// Find the date of an exam in its date or ID, like "June 2019 Paper 1", "Monday 3rd June 2019",
+/// or "2019-06-03". A month and year without a day count as the first of the month.
+pub fn exam_date(date_or_id: &str) -> Option<NaiveDate> {
+    let words: Vec<&str> = date_or_id
+        .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    if let Some(date) = words
+        .iter()
+        .find_map(|word| NaiveDate::parse_from_str(word, "%Y-%m-%d").ok())
+    {
+        return Some(date);
+    }
+
+    words.windows(2).enumerate().find_map(|(index, pair)| {
+        let month = month(pair[0])?;
+        if pair[1].len() != 4 || !pair[1].bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let year: i32 = pair[1].parse().ok()?;
+        let day = index
+            .checked_sub(1)
+            .and_then(|before| {
+                words[before]
+                    .trim_end_matches(|c: char| c.is_alphabetic())
+                    .parse()
+                    .ok()
+            })
+            .unwrap_or(1);
+        NaiveDate::from_ymd_opt(year, month, day)
+            .or_else(|| NaiveDate::from_ymd_opt(year, month, 1))
+    })
+}
+
+impl SyncedTest {
+    /// The best score of any completion of this test, as a fraction of the total marks, or `None`
+    /// if it's never been completed. Completions out of 0 marks don't count.
+    pub fn best_score(&self) -> Option<f64> {
+        self.completions
+            .iter()
+            .filter(|(_, completion)| completion.total_marks != 0)
+            .map(|(_, completion)| {
+                f64::from(completion.achieved_mark) / f64::from(completion.total_marks)
+            })
+            .max_by(f64::total_cmp)
+    }
+
+    /// The ID of the personal best completion of this test, like
+    /// [`best_completion_id`](Self::best_completion_id), but worked out from the completions that
+    /// the client has right now, so that it includes any that haven't been saved yet. Ties on the
+    /// same date go to the one that was added last.
+    pub fn personal_best_id(&self) -> Option<i32> {
+        let data: Vec<CompletionData> = self
+            .completions
+            .iter()
+            .map(|(_, completion)| completion.clone())
+            .collect();
+        best_completion(&data).map(|index| self.completions[index].0)
+    }
+
+    /// The date of the most recent completion of this test, or `None` if it's never been
+    /// completed, or only without dates.
+    pub fn last_attempted(&self) -> Option<NaiveDate> {
+        self.completions
+            .iter()
+            .filter_map(|(_, completion)| completion.date)
+            .max()
+    }
+
+    /// The most recent completion of this test, or `None` if it's never been completed.
+    /// Completions without dates count as older than any with dates, and ties go to the one that
+    /// comes last, which is the one that was added last, even if it hasn't been saved yet.
+    pub fn latest_completion(&self) -> Option<&CompletionData> {
+        self.completions
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, (_, completion))| (completion.date, *index))
+            .map(|(_, (_, completion))| completion)
+    }
+
+    /// The total marks to fill in when logging another attempt at this test: those of its
+    /// [latest completion](Self::latest_completion), since a re-sit is out of the same marks, or
+    /// the given default if it's never been completed.
+    pub fn next_total_marks(&self, default_total_marks: Option<i32>) -> Option<i32> {
+        self.latest_completion()
+            .map(|completion| completion.total_marks)
+            .or(default_total_marks)
+    }
+}
+
+/// Compare two optional keys so that `None` always comes last, whichever way the keys themselves
+/// are being sorted.
+fn nones_last<T>(a: Option<T>, b: Option<T>, cmp: impl FnOnce(T, T) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => cmp(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Find the index of the personal best of the given completions: the one with the best
+/// [`percentage`](CompletionData::percentage). Ties go to the most recent date, with completions
+/// without dates losing, and then to the one that comes last.
+///
+/// Completions out of 0 marks have no percentage, so they can never be the best, and this is
+/// `None` if there aren't any others.
+pub fn best_completion(completions: &[CompletionData]) -> Option<usize> {
+    completions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, completion)| Some((index, completion.percentage()?, completion.date)))
+        .max_by(
+            |(a_index, a_percentage, a_date), (b_index, b_percentage, b_date)| {
+                a_percentage
+                    .total_cmp(b_percentage)
+                    .then_with(|| a_date.cmp(b_date))
+                    .then_with(|| a_index.cmp(b_index))
+            },
+        )
+        .map(|(index, _, _)| index)
+}
+
+/// Sort the completions of a test newest first, so that the most recent attempt is always at the
+/// top of its card. Completions without a date go last, and ties go to the one that was added
+/// last, so the order never depends on the order that the server sent them in.
+pub fn sort_completions(completions: &mut [(i32, CompletionData)]) {
+    completions.sort_by(|(a_id, a), (b_id, b)| {
+        nones_last(a.date, b.date, |a, b| b.cmp(&a)).then_with(|| b_id.cmp(a_id))
+    });
+}
+
+impl TestSort {
+    /// Compare two tests in this order. Ties aren't broken here, so sort by ID first and then use
+    /// a stable sort, like [`sort_tests`](Self::sort_tests) does.
+    ///
+    /// Tests that have never been completed go last in the score and date sorts, just like on the
+    /// server. [`TestSort::Unknown`] considers every test equal.
+    pub fn compare(self, a: &SyncedTest, b: &SyncedTest) -> Ordering {
+        match self {
+            Self::SubjectAsc => {
+                (&a.test.subject, &a.test.date_or_id).cmp(&(&b.test.subject, &b.test.date_or_id))
+            }
+            Self::RecentlyAttempted => {
+                nones_last(a.last_attempted(), b.last_attempted(), |a, b| b.cmp(&a))
+            }
+            Self::BestPercentageAsc => {
+                nones_last(a.best_score(), b.best_score(), |a, b| a.total_cmp(&b))
+            }
+            Self::BestPercentageDesc => {
+                nones_last(a.best_score(), b.best_score(), |a, b| b.total_cmp(&a))
+            }
+            Self::ExamDateAsc => nones_last(
+                exam_date(&a.test.date_or_id),
+                exam_date(&b.test.date_or_id),
+                |a, b| a.cmp(&b),
+            ),
+            Self::Unknown => Ordering::Equal,
+        }
+    }
+
+    /// Sort the given tests into this order, breaking ties by ID.
+    pub fn sort_tests(self, tests: &mut [SyncedTest]) {
+        tests.sort_by_key(|test| test.id);
+        tests.sort_by(|a, b| self.compare(a, b));
+    }
+}
@@ -0,0 +1,90 @@
+//! This module works out the user's practice streaks: how many days or weeks in a row they've
+//! logged at least one attempt.
+
+use crate::{chart::week_of, SyncedTest};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Whether a streak counts days or weeks in a row.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum StreakUnit {
+    /// Calendar days, so an attempt is needed every day.
+    #[default]
+    Days,
+
+    /// Weeks starting on Monday, so an attempt is needed every week.
+    Weeks,
+}
+
+impl StreakUnit {
+    /// The first day of the day or week that the given date is in.
+    fn period_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Days => date,
+            Self::Weeks => week_of(date),
+        }
+    }
+
+    /// The number of days from the start of one day or week to the start of the next.
+    fn length(self) -> Duration {
+        match self {
+            Self::Days => Duration::days(1),
+            Self::Weeks => Duration::weeks(1),
+        }
+    }
+}
+
+/// The user's current and longest streaks, in whichever [`StreakUnit`] they were worked out in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Streaks {
+    /// How many days or weeks in a row there have been attempts, up to today or this week. This is
+    /// 0 if the last attempt was before yesterday or last week.
+    pub current: u32,
+
+    /// The most days or weeks in a row there have ever been attempts.
+    pub longest: u32,
+}
+
+/// Work out the streaks from the dates of the user's attempts, as of the given day. The dates
+/// should be sorted, like from [`completion_dates`], and the same date can be there more than
+/// once.
+///
+/// The current streak still counts if the last attempt was yesterday, or last week, since there's
+/// still time to keep it going. Dates are plain calendar dates in the user's timezone, so "today"
+/// should be too. Dates after today, which can only be typos, are ignored.
+pub fn streaks(dates: &[NaiveDate], today: NaiveDate, unit: StreakUnit) -> Streaks {
+    let today = unit.period_of(today);
+    let mut periods: Vec<NaiveDate> = dates
+        .iter()
+        .map(|&date| unit.period_of(date))
+        .filter(|&period| period <= today)
+        .collect();
+    periods.dedup();
+
+    let (mut run, mut longest) = (0, 0);
+    for (index, &period) in periods.iter().enumerate() {
+        let follows_on = index
+            .checked_sub(1)
+            .is_some_and(|before| period - periods[before] == unit.length());
+        run = if follows_on { run + 1 } else { 1 };
+        longest = longest.max(run);
+    }
+
+    let current = match periods.last() {
+        Some(&last) if today - last <= unit.length() => run,
+        _ => 0,
+    };
+    Streaks { current, longest }
+}
+
+/// The dates of every completion of the given tests, sorted, for [`streaks`]. Completions without
+/// a date are left out.
+pub fn completion_dates<'a>(tests: impl IntoIterator<Item = &'a SyncedTest>) -> Vec<NaiveDate> {
+    let mut dates: Vec<NaiveDate> = tests
+        .into_iter()
+        .flat_map(|test| &test.completions)
+        .filter_map(|(_, completion)| completion.date)
+        .collect();
+    dates.sort_unstable();
+    dates
+}
@@ -0,0 +1,65 @@
+//! This module summarises the tests in each subject, so that the client can show a summary of the
+//! tests that it already has without asking the server.
+//!
+//! The scores match the server's [`SubjectStats`](crate::SubjectStats), but every subject with a
+//! test is included, even if none of its tests have been completed.
+
+use crate::SyncedTest;
+use std::collections::BTreeMap;
+
+/// A summary of the tests and completions in one subject.
+///
+/// Scores are fractions of the total marks, between 0 and 1. Completions out of 0 marks are
+/// counted, but don't affect the scores.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubjectSummary {
+    /// The subject: maths, English, science, etc.
+    pub subject: String,
+
+    /// How many tests there are in this subject.
+    pub test_count: usize,
+
+    /// How many completions there are across all the tests in this subject.
+    pub completion_count: usize,
+
+    /// The mean score across all the completions, if any have a non-zero total.
+    pub average_score: Option<f64>,
+
+    /// The best score of any completion, if any have a non-zero total.
+    pub best_score: Option<f64>,
+}
+
+/// Summarise the given tests by subject, sorted by subject.
+pub fn subject_summaries<'a>(
+    tests: impl IntoIterator<Item = &'a SyncedTest>,
+) -> Vec<SubjectSummary> {
+    let mut by_subject: BTreeMap<&str, Vec<&SyncedTest>> = BTreeMap::new();
+    for test in tests {
+        by_subject.entry(&test.test.subject).or_default().push(test);
+    }
+
+    by_subject
+        .into_iter()
+        .map(|(subject, tests)| {
+            let completions: Vec<_> = tests
+                .iter()
+                .flat_map(|test| &test.completions)
+                .map(|(_, completion)| completion)
+                .collect();
+            let scores: Vec<f64> = completions
+                .iter()
+                .filter_map(|completion| completion.percentage())
+                .map(|percentage| percentage / 100.)
+                .collect();
+
+            SubjectSummary {
+                subject: subject.to_string(),
+                test_count: tests.len(),
+                completion_count: completions.len(),
+                average_score: (!scores.is_empty())
+                    .then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+                best_score: scores.iter().copied().reduce(f64::max),
+            }
+        })
+        .collect()
+}
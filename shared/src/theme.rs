@@ -0,0 +1,104 @@
+//! This module decides whether the client should use light mode or dark mode, from the theme that
+//! the user picked and the operating system's setting.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Dark mode or light mode.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DarkMode {
+    /// Light mode.
+    #[default]
+    Light,
+
+    /// Dark mode.
+    Dark,
+}
+
+impl From<bool> for DarkMode {
+    /// Dark mode if `true`, like when the `(prefers-color-scheme: dark)` media query matches.
+    fn from(dark: bool) -> Self {
+        if dark {
+            Self::Dark
+        } else {
+            Self::Light
+        }
+    }
+}
+
+impl fmt::Display for DarkMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl DarkMode {
+    /// Return the opposite mode.
+    pub fn other(&self) -> Self {
+        match self {
+            Self::Light => Self::Dark,
+            Self::Dark => Self::Light,
+        }
+    }
+}
+
+/// The theme that the user has picked.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ThemePreference {
+    /// Always use light mode.
+    Light,
+
+    /// Always use dark mode.
+    Dark,
+
+    /// Use whichever mode the operating system is using, and change when it does.
+    #[default]
+    System,
+}
+
+impl From<String> for ThemePreference {
+    /// Parse a stored preference. Before there was a system theme, only "light" and "dark" were
+    /// stored, so those still work, and anything unknown follows the system.
+    fn from(value: String) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "light" => Self::Light,
+            "dark" => Self::Dark,
+            _ => Self::System,
+        }
+    }
+}
+
+impl fmt::Display for ThemePreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+            Self::System => "system",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl ThemePreference {
+    /// The mode to actually use, given the operating system's mode.
+    pub fn resolve(self, system: DarkMode) -> DarkMode {
+        match self {
+            Self::Light => DarkMode::Light,
+            Self::Dark => DarkMode::Dark,
+            Self::System => system,
+        }
+    }
+
+    /// The preference after this one when cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            Self::System => Self::Light,
+            Self::Light => Self::Dark,
+            Self::Dark => Self::System,
+        }
+    }
+}
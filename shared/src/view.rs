@@ -0,0 +1,37 @@
+//! This module holds how the user last left the list of tests, which the client stores in
+//! `localStorage` as one [`ViewPreferences`] value for each user.
+
+use crate::TestSort;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// The prefix of the keys for each user's [`ViewPreferences`] in browser storage.
+const STORAGE_KEY_PREFIX: &str = "testTrackerViewPreferences";
+
+/// How the user wants the list of tests to look, which is kept between visits.
+///
+/// Missing fields take their defaults when this is deserialized, so that preferences saved by an
+/// older client still load after new ones are added.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewPreferences {
+    /// The order that the user wants their tests in, or `None` to leave them sorted by ID.
+    pub sort: Option<TestSort>,
+
+    /// The subject to show the tests of, or `None` to show all of them.
+    pub subject_filter: Option<String>,
+
+    /// Whether to group the tests by subject, rather than showing them in one flat list.
+    pub grouped: bool,
+
+    /// The IDs of the tests whose cards are expanded to show everything.
+    pub expanded_tests: BTreeSet<i32>,
+}
+
+impl ViewPreferences {
+    /// The key for the given user's preferences in browser storage. Each user gets their own key,
+    /// so that people sharing a browser don't overwrite each other's.
+    pub fn storage_key(user_id: &str) -> String {
+        format!("{STORAGE_KEY_PREFIX}:{user_id}")
+    }
+}
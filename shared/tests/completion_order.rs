@@ -0,0 +1,65 @@
+//! Tests for the order that the completions of a test are shown in.
+
+use test_tracker_shared::{fixtures::bare_completion, sort::sort_completions, CompletionData};
+
+/// The IDs of the given completions, in order.
+fn ids(completions: &[(i32, CompletionData)]) -> Vec<i32> {
+    completions.iter().map(|(id, _)| *id).collect()
+}
+
+#[test]
+fn newest_completions_go_first() {
+    let mut completions = vec![
+        (1, bare_completion(30, 60, Some("2024-01-10"))),
+        (2, bare_completion(30, 60, Some("2024-03-01"))),
+        (3, bare_completion(30, 60, Some("2024-02-01"))),
+    ];
+    sort_completions(&mut completions);
+    assert_eq!(ids(&completions), vec![2, 3, 1]);
+}
+
+#[test]
+fn undated_completions_go_last() {
+    let mut completions = vec![
+        (1, bare_completion(30, 60, None)),
+        (2, bare_completion(30, 60, Some("2024-01-10"))),
+        (3, bare_completion(30, 60, None)),
+        (4, bare_completion(30, 60, Some("2024-03-01"))),
+    ];
+    sort_completions(&mut completions);
+    assert_eq!(ids(&completions), vec![4, 2, 3, 1]);
+}
+
+#[test]
+fn ties_go_to_the_completion_added_last() {
+    let mut completions = vec![
+        (5, bare_completion(30, 60, Some("2024-02-01"))),
+        (9, bare_completion(30, 60, Some("2024-02-01"))),
+        (7, bare_completion(30, 60, Some("2024-02-01"))),
+    ];
+    sort_completions(&mut completions);
+    assert_eq!(ids(&completions), vec![9, 7, 5]);
+}
+
+#[test]
+fn order_does_not_depend_on_the_input_order() {
+    let completions = vec![
+        (1, bare_completion(30, 60, Some("2024-01-10"))),
+        (2, bare_completion(30, 60, None)),
+        (3, bare_completion(30, 60, Some("2024-03-01"))),
+        (4, bare_completion(30, 60, Some("2024-03-01"))),
+        (5, bare_completion(30, 60, None)),
+    ];
+
+    let mut expected = completions.clone();
+    sort_completions(&mut expected);
+    assert_eq!(ids(&expected), vec![4, 3, 1, 5, 2]);
+
+    for rotation in 0..completions.len() {
+        let mut shuffled = completions.clone();
+        shuffled.rotate_left(rotation);
+        shuffled.reverse();
+        sort_completions(&mut shuffled);
+        assert_eq!(shuffled, expected);
+    }
+}
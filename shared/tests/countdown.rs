@@ -0,0 +1,92 @@
+//! Tests for counting down the days until the user's exams.
+
+use chrono::NaiveDate;
+use test_tracker_shared::countdown::{upcoming_exams, ExamDate};
+
+/// Parse a date like "2024-06-03".
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+/// An exam with the given name on the given date.
+fn exam(name: &str, on: &str) -> ExamDate {
+    ExamDate {
+        name: name.to_string(),
+        date: date(on),
+    }
+}
+
+/// The names of the upcoming exams with their labels, soonest first.
+fn labels(exams: &[ExamDate], today: &str) -> Vec<(String, String)> {
+    upcoming_exams(exams, date(today))
+        .into_iter()
+        .map(|countdown| (countdown.exam.name.clone(), countdown.label()))
+        .collect()
+}
+
+#[test]
+fn today_and_tomorrow_are_special() {
+    let exams = [
+        exam("Physics", "2024-06-05"),
+        exam("Maths", "2024-06-03"),
+        exam("English", "2024-06-04"),
+    ];
+    assert_eq!(
+        labels(&exams, "2024-06-03"),
+        vec![
+            ("Maths".to_string(), "today!".to_string()),
+            ("English".to_string(), "tomorrow".to_string()),
+            ("Physics".to_string(), "in 2 days".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn past_exams_are_hidden() {
+    let exams = [exam("Maths", "2024-06-02"), exam("English", "2024-06-10")];
+    let upcoming = upcoming_exams(&exams, date("2024-06-03"));
+    assert_eq!(upcoming.len(), 1);
+    assert_eq!(upcoming[0].exam.name, "English");
+    assert_eq!(upcoming[0].days, 7);
+
+    assert!(upcoming_exams(&exams, date("2024-06-11")).is_empty());
+    assert!(upcoming_exams(&[], date("2024-06-11")).is_empty());
+}
+
+#[test]
+fn days_are_counted_across_month_boundaries() {
+    let exams = [exam("Maths", "2024-07-01")];
+    assert_eq!(upcoming_exams(&exams, date("2024-06-30"))[0].days, 1);
+    assert_eq!(upcoming_exams(&exams, date("2024-06-01"))[0].days, 30);
+    assert_eq!(upcoming_exams(&exams, date("2024-05-31"))[0].days, 31);
+}
+
+#[test]
+fn days_are_counted_across_year_boundaries_and_leap_days() {
+    let exams = [exam("Maths", "2024-01-01")];
+    assert_eq!(labels(&exams, "2023-12-31")[0].1, "tomorrow");
+    assert_eq!(upcoming_exams(&exams, date("2023-01-01"))[0].days, 365);
+
+    let exams = [exam("Maths", "2024-03-01")];
+    assert_eq!(upcoming_exams(&exams, date("2024-02-28"))[0].days, 2);
+    assert_eq!(upcoming_exams(&exams, date("2023-02-28"))[0].days, 367);
+
+    let exams = [exam("Maths", "2025-03-01")];
+    assert_eq!(upcoming_exams(&exams, date("2025-02-28"))[0].days, 1);
+}
+
+#[test]
+fn exams_on_the_same_day_are_sorted_by_name() {
+    let exams = [
+        exam("Physics Paper 2", "2024-06-10"),
+        exam("Chemistry Paper 1", "2024-06-10"),
+        exam("Biology Paper 1", "2024-06-04"),
+    ];
+    assert_eq!(
+        labels(&exams, "2024-06-01")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["Biology Paper 1", "Chemistry Paper 1", "Physics Paper 2"]
+    );
+}
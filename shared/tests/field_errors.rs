@@ -0,0 +1,76 @@
+//! Tests for showing validation errors from the server under the form fields that they're about.
+
+use test_tracker_shared::{
+    error::FieldErrors,
+    policy::{PasswordPolicyError, TestPolicyError},
+    Error, TestData,
+};
+
+/// The fields of the login form.
+const LOGIN_FIELDS: &[&str] = &["username", "password"];
+
+#[test]
+fn validation_errors_go_under_their_field() {
+    let mut errors = FieldErrors::default();
+    assert!(errors.is_empty());
+
+    let error: Error = PasswordPolicyError::TooShort.into();
+    assert_eq!(errors.insert_error(error, LOGIN_FIELDS), Ok(()));
+    assert_eq!(
+        errors.get("password"),
+        Some(PasswordPolicyError::TooShort.to_string().as_str())
+    );
+    assert_eq!(errors.get("username"), None);
+
+    let error: Error = TestPolicyError::MissingSubject.into();
+    assert_eq!(errors.insert_error(error, &TestData::FIELDS), Ok(()));
+    assert_eq!(errors.get("subject"), Some("please enter a subject"));
+}
+
+#[test]
+fn unknown_fields_and_other_errors_are_given_back() {
+    let mut errors = FieldErrors::default();
+
+    let error: Error = TestPolicyError::MissingDateOrId.into();
+    assert_eq!(errors.insert_error(error.clone(), LOGIN_FIELDS), Err(error));
+    assert_eq!(
+        errors.insert_error(Error::UsernameTaken, LOGIN_FIELDS),
+        Err(Error::UsernameTaken)
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn editing_a_field_clears_only_its_error() {
+    let mut errors = FieldErrors::default();
+    errors.insert("username", "username is reserved");
+    errors.insert("password", "password is too common");
+
+    assert!(errors.remove("username"));
+    assert!(!errors.remove("username"));
+    assert_eq!(errors.get("username"), None);
+    assert_eq!(errors.get("password"), Some("password is too common"));
+}
+
+#[test]
+fn merging_replaces_errors_for_the_same_field() {
+    let mut errors = FieldErrors::default();
+    errors.insert("username", "please enter a username");
+
+    let mut from_server = FieldErrors::default();
+    from_server.insert("username", "username is reserved");
+    from_server.insert("password", "password is too common");
+    errors.merge(&from_server);
+
+    assert_eq!(errors, from_server);
+}
+
+#[test]
+fn every_test_policy_error_is_a_test_field() {
+    for error in [
+        TestPolicyError::MissingSubject,
+        TestPolicyError::MissingDateOrId,
+    ] {
+        assert!(TestData::FIELDS.contains(&error.field()));
+    }
+}
@@ -0,0 +1,36 @@
+//! Tests for the sample data generators in the `fixtures` feature.
+
+use test_tracker_shared::fixtures::{
+    sample_completion, sample_dataset, sample_test, SAMPLE_TOTAL_MARKS,
+};
+
+#[test]
+fn samples_are_deterministic() {
+    assert_eq!(sample_test("Maths"), sample_test("Maths"));
+    assert_eq!(sample_test("Maths").subject, "Maths");
+    assert_eq!(sample_dataset(20, 3), sample_dataset(20, 3));
+    assert_eq!(sample_dataset(20, 3)[..5], sample_dataset(5, 3)[..]);
+}
+
+#[test]
+fn sample_completions_have_valid_marks() {
+    for (percent, expected) in [
+        (-10.0, 0),
+        (0.0, 0),
+        (50.0, SAMPLE_TOTAL_MARKS / 2),
+        (100.0, SAMPLE_TOTAL_MARKS),
+        (250.0, SAMPLE_TOTAL_MARKS),
+        (f64::NAN, 0),
+    ] {
+        let completion = sample_completion(percent);
+        assert_eq!(completion.achieved_mark, expected, "{percent}%");
+        assert_eq!(completion.validate(), Ok(()));
+    }
+
+    for (_, completions) in sample_dataset(100, 5) {
+        assert_eq!(completions.len(), 5);
+        for completion in completions {
+            assert_eq!(completion.validate(), Ok(()));
+        }
+    }
+}
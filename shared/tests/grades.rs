@@ -0,0 +1,133 @@
+//! Tests for working out grades from the grade boundaries that the user sets in the client.
+
+use test_tracker_shared::grade::{
+    format_boundaries, parse_boundaries, Boundary, BoundaryParseError, GradeBoundaries,
+};
+
+/// Grade boundaries with just A Level set, to "A* 90, A 80, B 70, C 60".
+fn a_level() -> GradeBoundaries {
+    let mut boundaries = GradeBoundaries::default();
+    boundaries.set_level(
+        "A Level",
+        parse_boundaries("A* 90, A 80, B 70, C 60").unwrap(),
+    );
+    boundaries
+}
+
+#[test]
+fn grades_come_from_the_highest_boundary_reached() {
+    let boundaries = a_level();
+    assert_eq!(boundaries.grade(Some("A Level"), 95.), Some("A*"));
+    assert_eq!(boundaries.grade(Some("A Level"), 85.5), Some("A"));
+    assert_eq!(boundaries.grade(Some("A Level"), 100.), Some("A*"));
+    assert_eq!(boundaries.grade(Some("A Level"), 61.), Some("C"));
+}
+
+#[test]
+fn percentages_on_a_boundary_get_that_grade() {
+    let boundaries = a_level();
+    assert_eq!(boundaries.grade(Some("A Level"), 90.), Some("A*"));
+    assert_eq!(boundaries.grade(Some("A Level"), 80.), Some("A"));
+    assert_eq!(boundaries.grade(Some("A Level"), 79.999), Some("B"));
+    assert_eq!(boundaries.grade(Some("A Level"), 60.), Some("C"));
+}
+
+#[test]
+fn no_grade_is_given_without_a_matching_boundary() {
+    let boundaries = a_level();
+    assert_eq!(boundaries.grade(Some("A Level"), 59.9), None);
+    assert_eq!(boundaries.grade(Some("GCSE"), 95.), None);
+    assert_eq!(boundaries.grade(None, 95.), None);
+    assert_eq!(GradeBoundaries::default().grade(Some("A Level"), 95.), None);
+}
+
+#[test]
+fn qualification_levels_match_loosely() {
+    let boundaries = a_level();
+    assert_eq!(boundaries.grade(Some("a level"), 95.), Some("A*"));
+    assert_eq!(boundaries.grade(Some("  A   LEVEL "), 95.), Some("A*"));
+    assert_eq!(
+        boundaries
+            .levels()
+            .map(|(level, _)| level)
+            .collect::<Vec<_>>(),
+        vec!["A Level"]
+    );
+}
+
+#[test]
+fn boundaries_are_kept_highest_first() {
+    let mut boundaries = GradeBoundaries::default();
+    boundaries.set_level("GCSE", parse_boundaries("4 40, 9 90, 7 70").unwrap());
+    assert_eq!(
+        format_boundaries(boundaries.level("GCSE").unwrap()),
+        "9 90, 7 70, 4 40"
+    );
+    assert_eq!(boundaries.grade(Some("GCSE"), 75.), Some("7"));
+}
+
+#[test]
+fn setting_no_boundaries_removes_a_level() {
+    let mut boundaries = a_level();
+    boundaries.set_level("a level", vec![]);
+    assert_eq!(boundaries, GradeBoundaries::default());
+}
+
+#[test]
+fn boundaries_round_trip_through_text() {
+    let parsed = parse_boundaries("A* 90%, A 80, B 72.5,").unwrap();
+    assert_eq!(
+        parsed,
+        vec![
+            Boundary {
+                grade: "A*".to_string(),
+                min_percentage: 90.,
+            },
+            Boundary {
+                grade: "A".to_string(),
+                min_percentage: 80.,
+            },
+            Boundary {
+                grade: "B".to_string(),
+                min_percentage: 72.5,
+            },
+        ]
+    );
+    assert_eq!(format_boundaries(&parsed), "A* 90, A 80, B 72.5");
+    assert_eq!(parse_boundaries(&format_boundaries(&parsed)), Ok(parsed));
+    assert_eq!(parse_boundaries(""), Ok(vec![]));
+}
+
+#[test]
+fn invalid_boundaries_are_refused() {
+    assert_eq!(
+        parse_boundaries("A* 90, A"),
+        Err(BoundaryParseError::MissingPart("A".to_string()))
+    );
+    assert_eq!(
+        parse_boundaries("A 120"),
+        Err(BoundaryParseError::InvalidPercentage("120".to_string()))
+    );
+    assert_eq!(
+        parse_boundaries("A lots"),
+        Err(BoundaryParseError::InvalidPercentage("lots".to_string()))
+    );
+    assert_eq!(
+        parse_boundaries("A NaN"),
+        Err(BoundaryParseError::InvalidPercentage("NaN".to_string()))
+    );
+    assert_eq!(
+        parse_boundaries("A 80, A 70"),
+        Err(BoundaryParseError::DuplicateGrade("A".to_string()))
+    );
+}
+
+#[test]
+fn boundaries_survive_storage() {
+    let boundaries = a_level();
+    let stored = ron::to_string(&boundaries).unwrap();
+    assert_eq!(
+        ron::from_str::<GradeBoundaries>(&stored).unwrap(),
+        boundaries
+    );
+}
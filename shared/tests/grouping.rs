@@ -0,0 +1,83 @@
+//! Tests for grouping tests by subject, for the client's grouped view.
+
+use test_tracker_shared::{
+    fixtures::{bare_test, synced_test},
+    group::{group_by_subject, UNCATEGORISED},
+    SyncedTest,
+};
+
+/// The IDs of the tests in each group, in order.
+fn group_ids(tests: &[SyncedTest]) -> Vec<(String, Vec<i32>)> {
+    group_by_subject(tests)
+        .into_iter()
+        .map(|(subject, tests)| (subject, tests.iter().map(|test| test.id).collect()))
+        .collect()
+}
+
+#[test]
+fn groups_are_in_alphabetical_order() {
+    let tests = [
+        synced_test(1, bare_test("Physics", "Paper 1"), vec![]),
+        synced_test(2, bare_test("Biology", "Paper 2"), vec![]),
+        synced_test(3, bare_test("Maths", "Paper 3"), vec![]),
+    ];
+
+    assert_eq!(
+        group_ids(&tests),
+        vec![
+            ("Biology".to_string(), vec![2]),
+            ("Maths".to_string(), vec![3]),
+            ("Physics".to_string(), vec![1]),
+        ]
+    );
+}
+
+#[test]
+fn tests_keep_their_order_within_a_group() {
+    let tests = [
+        synced_test(3, bare_test("Maths", "Paper 3"), vec![]),
+        synced_test(1, bare_test("Physics", "Paper 1"), vec![]),
+        synced_test(5, bare_test("Maths", "Paper 5"), vec![]),
+        synced_test(2, bare_test("Maths", "Paper 2"), vec![]),
+    ];
+
+    assert_eq!(
+        group_ids(&tests),
+        vec![
+            ("Maths".to_string(), vec![3, 5, 2]),
+            ("Physics".to_string(), vec![1]),
+        ]
+    );
+}
+
+#[test]
+fn surrounding_whitespace_is_ignored() {
+    let tests = [
+        synced_test(1, bare_test("Maths", "Paper 1"), vec![]),
+        synced_test(2, bare_test(" Maths\t", "Paper 2"), vec![]),
+    ];
+
+    assert_eq!(group_ids(&tests), vec![("Maths".to_string(), vec![1, 2])]);
+}
+
+#[test]
+fn blank_subjects_are_uncategorised() {
+    let tests = [
+        synced_test(1, bare_test("", "Paper 1"), vec![]),
+        synced_test(2, bare_test("Maths", "Paper 2"), vec![]),
+        synced_test(3, bare_test("  ", "Paper 3"), vec![]),
+    ];
+
+    assert_eq!(
+        group_ids(&tests),
+        vec![
+            ("Maths".to_string(), vec![2]),
+            (UNCATEGORISED.to_string(), vec![1, 3]),
+        ]
+    );
+}
+
+#[test]
+fn no_tests_make_no_groups() {
+    assert!(group_by_subject(&[]).is_empty());
+}
@@ -0,0 +1,186 @@
+//! Tests for the client's translations, and that every language has all of them.
+
+use std::collections::BTreeSet;
+use test_tracker_shared::i18n::{fill, Language, PluralCategory};
+
+/// The suffixes that mark a message as one plural category of a key.
+const PLURAL_SUFFIXES: [&str; 6] = [".zero", ".one", ".two", ".few", ".many", ".other"];
+
+/// Split a key into its base and whether it's a plural category, like `"test.attempts.one"` into
+/// `("test.attempts", true)`.
+fn base_key(key: &str) -> (&str, bool) {
+    PLURAL_SUFFIXES
+        .iter()
+        .find_map(|suffix| key.strip_suffix(suffix))
+        .map_or((key, false), |base| (base, true))
+}
+
+/// The names of the placeholders in a message, apart from `{count}`, which some plural categories
+/// leave out, like "No attempts".
+fn placeholders(message: &str) -> BTreeSet<&str> {
+    message
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .filter(|name| *name != "count")
+        .collect()
+}
+
+#[test]
+fn language_tags_are_recognised() {
+    assert_eq!(Language::from_tag("en"), Some(Language::English));
+    assert_eq!(Language::from_tag("en-GB"), Some(Language::English));
+    assert_eq!(Language::from_tag("cy"), Some(Language::Welsh));
+    assert_eq!(Language::from_tag("CY-gb"), Some(Language::Welsh));
+    assert_eq!(Language::from_tag("cy_GB"), Some(Language::Welsh));
+    assert_eq!(Language::from_tag("fr-FR"), None);
+    assert_eq!(Language::from_tag(""), None);
+
+    for language in Language::ALL {
+        assert_eq!(Language::from_tag(language.tag()), Some(language));
+    }
+}
+
+#[test]
+fn plural_categories_follow_each_language() {
+    let english: Vec<_> = [0, 1, 2, 5]
+        .into_iter()
+        .map(|n| Language::English.plural_category(n))
+        .collect();
+    assert_eq!(
+        english,
+        [
+            PluralCategory::Other,
+            PluralCategory::One,
+            PluralCategory::Other,
+            PluralCategory::Other
+        ]
+    );
+
+    let welsh: Vec<_> = [0, 1, 2, 3, 4, 6, 100]
+        .into_iter()
+        .map(|n| Language::Welsh.plural_category(n))
+        .collect();
+    assert_eq!(
+        welsh,
+        [
+            PluralCategory::Zero,
+            PluralCategory::One,
+            PluralCategory::Two,
+            PluralCategory::Few,
+            PluralCategory::Other,
+            PluralCategory::Many,
+            PluralCategory::Other
+        ]
+    );
+}
+
+#[test]
+fn plural_messages_are_picked_by_count() {
+    let attempts = |language: Language, count: u64| {
+        fill(
+            language.translate_plural("test.attempts", count).unwrap(),
+            &[("count", count.to_string())],
+        )
+    };
+
+    assert_eq!(attempts(Language::English, 1), "1 attempt");
+    assert_eq!(attempts(Language::English, 0), "0 attempts");
+    assert_eq!(attempts(Language::English, 7), "7 attempts");
+    assert_eq!(attempts(Language::Welsh, 0), "Dim ymgeisiau");
+    assert_eq!(attempts(Language::Welsh, 2), "2 ymgais");
+    assert_eq!(attempts(Language::Welsh, 20), "20 o ymgeisiau");
+}
+
+#[test]
+fn missing_messages_fall_back_to_english() {
+    assert_eq!(Language::Welsh.translate("no.such.key"), None);
+    assert_eq!(
+        Language::Welsh.translate("login.username"),
+        Some("Enw defnyddiwr")
+    );
+    assert_eq!(
+        Language::English.translate("login.username"),
+        Some("Username")
+    );
+    assert_eq!(Language::English.translate_plural("no.such.key", 1), None);
+}
+
+#[test]
+fn placeholders_are_filled_by_name() {
+    assert_eq!(
+        fill(
+            "Switch to {new} (currently {current})",
+            &[
+                ("current", "dark mode".to_string()),
+                ("new", "light mode".to_string())
+            ]
+        ),
+        "Switch to light mode (currently dark mode)"
+    );
+    assert_eq!(fill("Best {percentage}%", &[]), "Best {percentage}%");
+}
+
+#[test]
+fn every_language_has_every_message() {
+    let english: BTreeSet<(&str, bool)> = Language::English
+        .messages()
+        .iter()
+        .map(|(key, _)| base_key(key))
+        .collect();
+
+    for language in Language::ALL {
+        let keys: BTreeSet<(&str, bool)> = language
+            .messages()
+            .iter()
+            .map(|(key, _)| base_key(key))
+            .collect();
+        assert_eq!(keys, english, "{language:?} has different messages");
+
+        for (base, plural) in &english {
+            let message = if *plural {
+                language
+                    .messages()
+                    .iter()
+                    .find(|(key, _)| *key == format!("{base}.other"))
+                    .map(|(_, message)| *message)
+            } else {
+                language.translate(base)
+            };
+            assert!(
+                message.is_some(),
+                "{language:?} is missing {base}{}",
+                if *plural { ".other" } else { "" }
+            );
+        }
+    }
+}
+
+#[test]
+fn translations_keep_their_placeholders() {
+    for language in Language::ALL {
+        for (key, message) in language.messages() {
+            let (base, plural) = base_key(key);
+            let english = if plural {
+                Language::English.translate_plural(base, 2)
+            } else {
+                Language::English.translate(key)
+            };
+            assert_eq!(
+                placeholders(message),
+                placeholders(english.unwrap()),
+                "{language:?} {key} has different placeholders to English"
+            );
+        }
+    }
+}
+
+#[test]
+fn keys_are_unique() {
+    for language in Language::ALL {
+        let mut seen = BTreeSet::new();
+        for (key, _) in language.messages() {
+            assert!(seen.insert(key), "{language:?} has {key} twice");
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Tests for deciding which stored links are safe for the client to make clickable.
+
+use test_tracker_shared::policy::is_web_link;
+
+#[test]
+fn web_links_are_allowed() {
+    assert!(is_web_link("https://example.com/paper.pdf"));
+    assert!(is_web_link("http://example.com"));
+    assert!(is_web_link("HTTPS://EXAMPLE.COM"));
+    assert!(is_web_link("  https://example.com/with spaces around  "));
+}
+
+#[test]
+fn other_schemes_are_refused() {
+    assert!(!is_web_link("javascript:alert(1)"));
+    assert!(!is_web_link("JavaScript://%0aalert(1)"));
+    assert!(!is_web_link("data:text/html,<script>alert(1)</script>"));
+    assert!(!is_web_link("file:///etc/passwd"));
+    assert!(!is_web_link("ftp://example.com/paper.pdf"));
+    assert!(!is_web_link("java\tscript://example.com"));
+}
+
+#[test]
+fn incomplete_links_are_refused() {
+    assert!(!is_web_link(""));
+    assert!(!is_web_link("https://"));
+    assert!(!is_web_link("example.com/paper.pdf"));
+    assert!(!is_web_link("https:example.com"));
+    assert!(!is_web_link("//example.com"));
+}
@@ -0,0 +1,62 @@
+//! Tests for what's filled in when logging another attempt at a test.
+
+use test_tracker_shared::{
+    fixtures::{bare_completion, bare_test, synced_test},
+    CompletionData, SyncedTest,
+};
+
+/// A test with the given completions, numbered from 1 in the order they're given.
+fn synced(completions: Vec<CompletionData>) -> SyncedTest {
+    synced_test(
+        1,
+        bare_test("Maths", "June 2019 Paper 1"),
+        (1..).zip(completions).collect(),
+    )
+}
+
+#[test]
+fn total_marks_come_from_the_latest_completion() {
+    let test = synced(vec![
+        bare_completion(40, 80, Some("2024-01-03")),
+        bare_completion(70, 100, Some("2024-01-20")),
+        bare_completion(50, 90, Some("2024-01-10")),
+    ]);
+    assert_eq!(
+        test.latest_completion(),
+        Some(&bare_completion(70, 100, Some("2024-01-20")))
+    );
+    assert_eq!(test.next_total_marks(Some(60)), Some(100));
+}
+
+#[test]
+fn completions_without_dates_are_older_than_any_with_dates() {
+    let test = synced(vec![
+        bare_completion(40, 80, Some("2024-01-03")),
+        bare_completion(70, 100, None),
+    ]);
+    assert_eq!(test.next_total_marks(None), Some(80));
+
+    let test = synced(vec![
+        bare_completion(40, 80, None),
+        bare_completion(70, 100, None),
+    ]);
+    assert_eq!(test.next_total_marks(None), Some(100));
+}
+
+#[test]
+fn ties_go_to_the_completion_added_last() {
+    // A completion that hasn't been saved yet has a negative placeholder ID, but it's still the
+    // latest
+    let mut test = synced(vec![bare_completion(40, 80, Some("2024-01-05"))]);
+    test.completions
+        .push((-1, bare_completion(60, 75, Some("2024-01-05"))));
+    assert_eq!(test.next_total_marks(None), Some(75));
+}
+
+#[test]
+fn tests_without_completions_use_the_default() {
+    let test = synced(vec![]);
+    assert_eq!(test.latest_completion(), None);
+    assert_eq!(test.next_total_marks(None), None);
+    assert_eq!(test.next_total_marks(Some(60)), Some(60));
+}
@@ -0,0 +1,243 @@
+//! Tests for parsing the Markdown in comments, which the client renders.
+
+use std::time::{Duration, Instant};
+use test_tracker_shared::markdown::{parse_markdown, Block, Inline, MAX_NESTING};
+
+/// All the text in some inline elements, with line breaks as newlines.
+fn inline_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => text.clone(),
+            Inline::LineBreak => "\n".to_string(),
+            Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Link { children, .. } => inline_text(children),
+        })
+        .collect()
+}
+
+/// All the text in some blocks.
+fn text(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(|block| match block {
+            Block::Paragraph(inlines) => inline_text(inlines),
+            Block::List { items, .. } => items.iter().map(|item| text(item)).collect(),
+        })
+        .collect()
+}
+
+/// The destinations of all the links in some inline elements.
+fn inline_links(inlines: &[Inline]) -> Vec<String> {
+    inlines
+        .iter()
+        .flat_map(|inline| match inline {
+            Inline::Text(_) | Inline::LineBreak => vec![],
+            Inline::Emphasis(children) | Inline::Strong(children) => inline_links(children),
+            Inline::Link { href, children } => {
+                let mut links = vec![href.clone()];
+                links.extend(inline_links(children));
+                links
+            }
+        })
+        .collect()
+}
+
+/// The destinations of all the links in some blocks.
+fn links(blocks: &[Block]) -> Vec<String> {
+    blocks
+        .iter()
+        .flat_map(|block| match block {
+            Block::Paragraph(inlines) => inline_links(inlines),
+            Block::List { items, .. } => items.iter().flat_map(|item| links(item)).collect(),
+        })
+        .collect()
+}
+
+/// How deeply lists are nested in some blocks.
+fn list_depth(blocks: &[Block]) -> usize {
+    blocks
+        .iter()
+        .map(|block| match block {
+            Block::Paragraph(_) => 0,
+            Block::List { items, .. } => {
+                1 + items.iter().map(|item| list_depth(item)).max().unwrap_or(0)
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// How deeply emphasis and links are nested in some inline elements.
+fn inline_depth(inlines: &[Inline]) -> usize {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(_) | Inline::LineBreak => 0,
+            Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Link { children, .. } => 1 + inline_depth(children),
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn plain_text_is_one_paragraph() {
+    assert_eq!(
+        parse_markdown("Ran out of time"),
+        vec![Block::Paragraph(vec![Inline::Text(
+            "Ran out of time".to_string()
+        )])]
+    );
+    assert_eq!(parse_markdown(""), vec![]);
+}
+
+#[test]
+fn emphasis_is_parsed() {
+    assert_eq!(
+        parse_markdown("Check *every* step, **especially** q3"),
+        vec![Block::Paragraph(vec![
+            Inline::Text("Check ".to_string()),
+            Inline::Emphasis(vec![Inline::Text("every".to_string())]),
+            Inline::Text(" step, ".to_string()),
+            Inline::Strong(vec![Inline::Text("especially".to_string())]),
+            Inline::Text(" q3".to_string()),
+        ])]
+    );
+}
+
+#[test]
+fn line_breaks_are_kept() {
+    assert_eq!(
+        parse_markdown("Good on integration\nWeak on vectors"),
+        vec![Block::Paragraph(vec![
+            Inline::Text("Good on integration".to_string()),
+            Inline::LineBreak,
+            Inline::Text("Weak on vectors".to_string()),
+        ])]
+    );
+}
+
+#[test]
+fn lists_are_parsed() {
+    let item = |text: &str| vec![Block::Paragraph(vec![Inline::Text(text.to_string())])];
+
+    assert_eq!(
+        parse_markdown("Revise:\n\n- q7\n- q9"),
+        vec![
+            Block::Paragraph(vec![Inline::Text("Revise:".to_string())]),
+            Block::List {
+                start: None,
+                items: vec![item("q7"), item("q9")],
+            },
+        ]
+    );
+    assert_eq!(
+        parse_markdown("3. q3\n4. q4"),
+        vec![Block::List {
+            start: Some(3),
+            items: vec![item("q3"), item("q4")],
+        }]
+    );
+
+    let nested = parse_markdown("- vectors\n  - dot product\n  - cross product\n- calculus");
+    assert_eq!(list_depth(&nested), 2);
+    assert_eq!(text(&nested), "vectorsdot productcross productcalculus");
+}
+
+#[test]
+fn web_links_are_kept() {
+    let blocks =
+        parse_markdown("See [the paper](https://example.com/paper.pdf) and <http://example.com>");
+    assert_eq!(
+        links(&blocks),
+        vec!["https://example.com/paper.pdf", "http://example.com"]
+    );
+    assert_eq!(text(&blocks), "See the paper and http://example.com");
+}
+
+#[test]
+fn unsafe_links_become_text() {
+    for comment in [
+        "[click me](javascript:alert(1))",
+        "[click me](JavaScript:alert(1))",
+        "[click me](data:text/html,<script>alert(1)</script>)",
+        "[click me](vbscript:msgbox(1))",
+        "[click me](/relative/path)",
+    ] {
+        let blocks = parse_markdown(comment);
+        assert_eq!(links(&blocks), Vec::<String>::new(), "{comment:?}");
+        assert_eq!(text(&blocks), "click me", "{comment:?}");
+    }
+
+    let blocks = parse_markdown("[click me][evil]\n\n[evil]: javascript:alert(1)");
+    assert_eq!(links(&blocks), Vec::<String>::new());
+}
+
+#[test]
+fn raw_html_is_kept_as_text() {
+    let blocks = parse_markdown("<script>alert(1)</script>");
+    assert_eq!(links(&blocks), Vec::<String>::new());
+    assert!(text(&blocks).contains("<script>alert(1)</script>"));
+
+    let blocks = parse_markdown("Click <a href=\"javascript:alert(1)\">here</a>");
+    assert_eq!(links(&blocks), Vec::<String>::new());
+    assert_eq!(
+        text(&blocks),
+        "Click <a href=\"javascript:alert(1)\">here</a>"
+    );
+
+    let blocks = parse_markdown("<img src=x onerror=alert(1)>");
+    assert!(text(&blocks).contains("<img src=x onerror=alert(1)>"));
+}
+
+#[test]
+fn images_become_their_text() {
+    let blocks = parse_markdown("![a graph](https://example.com/graph.png)");
+    assert_eq!(links(&blocks), Vec::<String>::new());
+    assert_eq!(text(&blocks), "a graph");
+}
+
+#[test]
+fn deep_nesting_is_flattened() {
+    let lists = "- ".repeat(10 * MAX_NESTING) + "deep";
+    let blocks = parse_markdown(&lists);
+    assert!(list_depth(&blocks) <= MAX_NESTING);
+    assert_eq!(text(&blocks), "deep");
+
+    let emphasis = "*".repeat(20 * MAX_NESTING) + "deep" + &"*".repeat(20 * MAX_NESTING);
+    let blocks = parse_markdown(&emphasis);
+    let [Block::Paragraph(inlines)] = &blocks[..] else {
+        panic!("expected one paragraph, got {blocks:?}");
+    };
+    assert!(inline_depth(inlines) <= MAX_NESTING);
+    assert_eq!(text(&blocks), "deep");
+}
+
+#[test]
+fn long_comments_are_parsed_quickly() {
+    let comments = [
+        "Revise *integration* by parts and **vectors**, see [notes](https://example.com).\n"
+            .repeat(5_000),
+        "- q1\n".repeat(10_000),
+        "*a **b ".repeat(10_000),
+        "[".repeat(10_000) + &"](https://example.com)".repeat(10_000),
+        "> ".repeat(5_000) + "quoted",
+        "<".repeat(50_000),
+    ];
+
+    let start = Instant::now();
+    for comment in &comments {
+        parse_markdown(comment);
+    }
+    let elapsed = start.elapsed();
+
+    // This is very generous, so that it doesn't fail on a slow machine in a debug build, but
+    // anything quadratic would take far longer
+    assert!(
+        elapsed < Duration::from_secs(10),
+        "parsing took {elapsed:?}"
+    );
+}
@@ -0,0 +1,78 @@
+//! Tests for splitting the client's list of tests into pages.
+
+use test_tracker_shared::{
+    fixtures::sample_dataset,
+    page::{paginate, Page, PageSize},
+};
+
+#[test]
+fn pages_cover_the_whole_list() {
+    assert_eq!(
+        paginate(60, PageSize::TwentyFive, 0),
+        Page {
+            index: 0,
+            count: 3,
+            range: 0..25,
+        }
+    );
+    assert_eq!(paginate(60, PageSize::TwentyFive, 1).range, 25..50);
+    assert_eq!(paginate(60, PageSize::TwentyFive, 2).range, 50..60);
+    assert_eq!(paginate(60, PageSize::Fifty, 1).range, 50..60);
+    assert_eq!(paginate(50, PageSize::Fifty, 0).count, 1);
+    assert_eq!(paginate(51, PageSize::Fifty, 0).count, 2);
+}
+
+#[test]
+fn showing_all_is_one_page() {
+    assert_eq!(
+        paginate(500, PageSize::All, 3),
+        Page {
+            index: 0,
+            count: 1,
+            range: 0..500,
+        }
+    );
+}
+
+#[test]
+fn pages_past_the_end_give_the_last_page() {
+    assert_eq!(
+        paginate(60, PageSize::TwentyFive, 7),
+        Page {
+            index: 2,
+            count: 3,
+            range: 50..60,
+        }
+    );
+}
+
+#[test]
+fn empty_lists_have_one_empty_page() {
+    for size in PageSize::ALL {
+        assert_eq!(
+            paginate(0, size, 0),
+            Page {
+                index: 0,
+                count: 1,
+                range: 0..0,
+            }
+        );
+    }
+}
+
+#[test]
+fn searching_covers_every_page() {
+    // Like the client, search first and then take a page of the matches
+    let tests = sample_dataset(500, 2);
+    let matches: Vec<_> = tests
+        .iter()
+        .filter(|test| test.0.matches_search("maths"))
+        .collect();
+    assert!(matches.len() > PageSize::TwentyFive.limit().unwrap());
+
+    let page = paginate(matches.len(), PageSize::TwentyFive, usize::MAX);
+    assert_eq!(page.range.end, matches.len());
+    assert!(matches[page.range]
+        .iter()
+        .all(|test| test.0.matches_search("maths")));
+}
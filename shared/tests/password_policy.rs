@@ -0,0 +1,98 @@
+//! Tests for the password policy, which the server enforces and the client checks before sending
+//! anything.
+
+use test_tracker_shared::{
+    policy::{check_password, PasswordPolicyError, MIN_PASSWORD_LENGTH},
+    Error,
+};
+
+#[test]
+fn length_is_counted_in_characters() {
+    assert_eq!(
+        check_password("alice", "seven77"),
+        Err(PasswordPolicyError::TooShort)
+    );
+    assert_eq!(check_password("alice", "eight888"), Ok(()));
+
+    // Seven characters, but fourteen bytes
+    let accented = "ééééééé";
+    assert_eq!(accented.chars().count(), MIN_PASSWORD_LENGTH - 1);
+    assert!(accented.len() > MIN_PASSWORD_LENGTH);
+    assert_eq!(
+        check_password("alice", accented),
+        Err(PasswordPolicyError::TooShort)
+    );
+    assert_eq!(check_password("alice", "éééééééé"), Ok(()));
+
+    // Seven characters, but twenty-eight bytes
+    assert_eq!(
+        check_password("alice", "🔒🔒🔒🔒🔒🔒🔒"),
+        Err(PasswordPolicyError::TooShort)
+    );
+    assert_eq!(check_password("alice", "🔒🔒🔒🔒🔒🔒🔒🔒"), Ok(()));
+}
+
+#[test]
+fn password_must_not_be_the_username() {
+    assert_eq!(
+        check_password("alice_smith", "alice_smith"),
+        Err(PasswordPolicyError::SameAsUsername)
+    );
+    assert_eq!(
+        check_password("  Alice_Smith ", "ALICE_SMITH"),
+        Err(PasswordPolicyError::SameAsUsername)
+    );
+    assert_eq!(
+        check_password("zoë_jones", "ZOË_JONES"),
+        Err(PasswordPolicyError::SameAsUsername)
+    );
+    assert_eq!(check_password("alice_smith", "alice_smith!"), Ok(()));
+}
+
+#[test]
+fn common_passwords_are_refused_in_any_case() {
+    for password in ["password", "Password123", "QWERTY123", "iloveyou"] {
+        assert_eq!(
+            check_password("alice", password),
+            Err(PasswordPolicyError::TooCommon),
+            "{password:?} should be refused"
+        );
+    }
+    assert_eq!(check_password("alice", "correct horse battery"), Ok(()));
+}
+
+#[test]
+fn too_short_is_checked_first() {
+    // "alice" is both too short and the same as the username
+    assert_eq!(
+        check_password("alice", "alice"),
+        Err(PasswordPolicyError::TooShort)
+    );
+}
+
+#[test]
+fn rejections_say_why() {
+    assert_eq!(
+        PasswordPolicyError::TooShort.to_string(),
+        format!("password must be at least {MIN_PASSWORD_LENGTH} characters long")
+    );
+    assert_eq!(
+        PasswordPolicyError::SameAsUsername.to_string(),
+        "password must not be the same as the username"
+    );
+    assert_eq!(
+        PasswordPolicyError::TooCommon.to_string(),
+        "password is too common"
+    );
+}
+
+#[test]
+fn rejections_are_validation_errors_for_the_password() {
+    assert_eq!(
+        Error::from(PasswordPolicyError::TooCommon),
+        Error::Validation {
+            field: "password".to_string(),
+            reason: "password is too common".to_string(),
+        }
+    );
+}
@@ -0,0 +1,65 @@
+//! Tests for rating the strength of passwords, which the client shows while creating an account.
+
+use test_tracker_shared::policy::{
+    check_password, password_entropy_bits, password_strength, PasswordPolicyError, PasswordStrength,
+};
+
+#[test]
+fn entropy_counts_length_and_character_classes() {
+    assert_eq!(password_entropy_bits(""), 0.);
+    assert_eq!(password_entropy_bits("aaaa"), 4. * 26_f64.log2());
+    assert_eq!(password_entropy_bits("aA1!"), 4. * 94_f64.log2());
+    assert_eq!(password_entropy_bits("ab12"), 4. * 36_f64.log2());
+    assert_eq!(password_entropy_bits("é"), 100_f64.log2());
+}
+
+#[test]
+fn policy_violations_are_errors() {
+    assert_eq!(
+        password_strength("alice", "short"),
+        Err(PasswordPolicyError::TooShort)
+    );
+    assert_eq!(
+        password_strength("alice_smith", "Alice_Smith"),
+        Err(PasswordPolicyError::SameAsUsername)
+    );
+    assert_eq!(
+        password_strength("alice", "Password123"),
+        Err(PasswordPolicyError::TooCommon)
+    );
+}
+
+#[test]
+fn strength_goes_up_with_length_and_variety() {
+    for (password, expected) in [
+        ("zebracat", PasswordStrength::VeryWeak),
+        ("zebracat42", PasswordStrength::Weak),
+        ("zebracat4242", PasswordStrength::Fair),
+        ("correct horse battery staple", PasswordStrength::Strong),
+    ] {
+        assert_eq!(
+            password_strength("alice", password),
+            Ok(expected),
+            "{password:?}"
+        );
+    }
+}
+
+#[test]
+fn strength_and_policy_agree() {
+    for password in [
+        "",
+        "short",
+        "alice",
+        "password",
+        "zebracat",
+        "Zebra-cat-42",
+        "correct horse battery staple",
+    ] {
+        assert_eq!(
+            password_strength("alice", password).is_ok(),
+            check_password("alice", password).is_ok(),
+            "{password:?}"
+        );
+    }
+}
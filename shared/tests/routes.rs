@@ -0,0 +1,50 @@
+//! Tests for working out which page of the client to show from the fragment of its URL.
+
+use test_tracker_shared::route::Route;
+
+#[test]
+fn empty_fragment_is_the_list() {
+    for hash in ["", "#", "#/", "/"] {
+        assert_eq!(Route::from_hash(hash), Some(Route::List), "{hash:?}");
+    }
+}
+
+#[test]
+fn tests_have_their_own_pages() {
+    assert_eq!(Route::from_hash("#/tests/12"), Some(Route::Test(12)));
+    assert_eq!(Route::from_hash("#/tests/12/"), Some(Route::Test(12)));
+    assert_eq!(Route::from_hash("/tests/7"), Some(Route::Test(7)));
+}
+
+#[test]
+fn invalid_ids_match_nothing() {
+    for hash in [
+        "#/tests/abc",
+        "#/tests/",
+        "#/tests/1.5",
+        "#/tests/99999999999",
+        "#/tests/12/completions",
+    ] {
+        assert_eq!(Route::from_hash(hash), None, "{hash:?}");
+    }
+}
+
+#[test]
+fn unknown_pages_match_nothing() {
+    for hash in ["#/settings", "#/tests", "#tests/12/edit", "#/list"] {
+        assert_eq!(Route::from_hash(hash), None, "{hash:?}");
+    }
+}
+
+#[test]
+fn links_lead_back_to_the_same_page() {
+    for route in [
+        Route::List,
+        Route::Test(1),
+        Route::Test(-3),
+        Route::Test(i32::MAX),
+    ] {
+        assert_eq!(Route::from_hash(&route.href()), Some(route));
+    }
+    assert_eq!(Route::Test(12).href(), "#/tests/12");
+}
@@ -0,0 +1,77 @@
+//! Tests for working out where the client draws the points of its score charts.
+
+use test_tracker_shared::{
+    chart::{plot_points, score_points, ChartSize},
+    fixtures::{bare_completion, date},
+};
+
+/// The size of the charts in these tests, chosen so that the coordinates are round numbers.
+const SIZE: ChartSize = ChartSize {
+    width: 120.,
+    height: 60.,
+    padding: 10.,
+};
+
+#[test]
+fn points_are_sorted_by_date_and_then_id() {
+    let completions = vec![
+        (3, bare_completion(30, 60, Some("2024-03-01"))),
+        (1, bare_completion(60, 60, Some("2024-01-01"))),
+        (4, bare_completion(15, 60, Some("2024-01-01"))),
+        (2, bare_completion(45, 60, Some("2024-01-01"))),
+    ];
+
+    assert_eq!(
+        score_points(&completions),
+        vec![
+            (date("2024-01-01"), 100.),
+            (date("2024-01-01"), 75.),
+            (date("2024-01-01"), 25.),
+            (date("2024-03-01"), 50.),
+        ]
+    );
+}
+
+#[test]
+fn undated_and_zero_mark_completions_are_left_out() {
+    let completions = vec![
+        (1, bare_completion(30, 60, None)),
+        (2, bare_completion(0, 0, Some("2024-01-01"))),
+        (3, bare_completion(45, 60, Some("2024-02-01"))),
+    ];
+
+    assert_eq!(score_points(&completions), vec![(date("2024-02-01"), 75.)]);
+}
+
+#[test]
+fn points_fill_the_chart_inside_the_padding() {
+    let points = [
+        (date("2024-01-01"), 0.),
+        (date("2024-01-06"), 50.),
+        (date("2024-01-11"), 100.),
+    ];
+
+    assert_eq!(
+        plot_points(&points, SIZE),
+        vec![(10., 50.), (60., 30.), (110., 10.)]
+    );
+}
+
+#[test]
+fn the_y_axis_always_goes_from_0_to_100_percent() {
+    let points = [(date("2024-01-01"), 40.), (date("2024-01-11"), 60.)];
+
+    assert_eq!(plot_points(&points, SIZE), vec![(10., 34.), (110., 26.)]);
+}
+
+#[test]
+fn points_on_the_same_date_go_in_the_middle() {
+    let points = [(date("2024-01-01"), 25.), (date("2024-01-01"), 75.)];
+
+    assert_eq!(plot_points(&points, SIZE), vec![(60., 40.), (60., 20.)]);
+}
+
+#[test]
+fn no_points_plot_nothing() {
+    assert_eq!(plot_points(&[], SIZE), vec![]);
+}
@@ -0,0 +1,58 @@
+//! Tests for matching tests against the client's search box.
+
+use test_tracker_shared::{fixtures::bare_test, TestData};
+
+/// A test with every field that can be searched filled in, and a link that shouldn't be searched.
+fn searchable_test() -> TestData {
+    TestData {
+        topic: Some("Core Pure".to_string()),
+        qualification_level: Some("A Level".to_string()),
+        exam_board: Some("Edexcel".to_string()),
+        paper_link: Some("https://example.com/paper.pdf".to_string()),
+        comments: Some("Ran out of time on Q7".to_string()),
+        ..bare_test("Further Maths", "June 2019 Paper 1")
+    }
+}
+
+#[test]
+fn an_empty_search_matches_everything() {
+    assert!(searchable_test().matches_search(""));
+    assert!(searchable_test().matches_search("   "));
+}
+
+#[test]
+fn every_searchable_field_is_searched() {
+    for search in ["further", "pure", "2019", "edexcel", "q7"] {
+        assert!(searchable_test().matches_search(search), "{search:?}");
+    }
+}
+
+#[test]
+fn case_is_ignored() {
+    assert!(searchable_test().matches_search("FURTHER maths"));
+    assert!(searchable_test().matches_search("eDeXcEl"));
+}
+
+#[test]
+fn every_word_has_to_match_but_not_in_the_same_field() {
+    assert!(searchable_test().matches_search("maths june edexcel"));
+    assert!(!searchable_test().matches_search("maths november"));
+}
+
+#[test]
+fn other_fields_are_not_searched() {
+    assert!(!searchable_test().matches_search("level"));
+    assert!(!searchable_test().matches_search("example.com"));
+}
+
+#[test]
+fn missing_optional_fields_dont_match() {
+    let test = TestData {
+        topic: None,
+        exam_board: None,
+        comments: None,
+        ..searchable_test()
+    };
+    assert!(!test.matches_search("edexcel"));
+    assert!(test.matches_search("maths"));
+}
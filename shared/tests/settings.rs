@@ -0,0 +1,73 @@
+//! Tests for the client's settings, and for moving settings out of the keys that older clients
+//! stored them under.
+
+use test_tracker_shared::{
+    grade::{parse_boundaries, GradeBoundaries},
+    i18n::Language,
+    settings::Settings,
+    streak::StreakUnit,
+    theme::ThemePreference,
+};
+
+/// Grade boundaries with just GCSE set, to "9 90, 8 80, 7 70".
+fn gcse() -> GradeBoundaries {
+    let mut boundaries = GradeBoundaries::default();
+    boundaries.set_level("GCSE", parse_boundaries("9 90, 8 80, 7 70").unwrap());
+    boundaries
+}
+
+#[test]
+fn legacy_settings_keep_the_old_theme_and_boundaries() {
+    assert_eq!(
+        Settings::from_legacy(Some("dark".to_string()), Some(gcse())),
+        Settings {
+            theme: ThemePreference::Dark,
+            grade_boundaries: gcse(),
+            default_total_marks: None,
+            language: None,
+            streak_unit: StreakUnit::Days,
+        }
+    );
+    assert_eq!(
+        Settings::from_legacy(Some("light".to_string()), None).theme,
+        ThemePreference::Light
+    );
+}
+
+#[test]
+fn missing_or_unknown_legacy_settings_take_their_defaults() {
+    assert_eq!(Settings::from_legacy(None, None), Settings::default());
+    assert_eq!(
+        Settings::from_legacy(Some("purple".to_string()), None).theme,
+        ThemePreference::System
+    );
+}
+
+#[test]
+fn settings_survive_storage() {
+    let settings = Settings {
+        theme: ThemePreference::Light,
+        grade_boundaries: gcse(),
+        default_total_marks: Some(80),
+        language: Some(Language::Welsh),
+        streak_unit: StreakUnit::Weeks,
+    };
+
+    let stored = ron::to_string(&settings).unwrap();
+    assert_eq!(ron::from_str::<Settings>(&stored).unwrap(), settings);
+}
+
+#[test]
+fn settings_missing_newer_fields_still_load() {
+    assert_eq!(
+        ron::from_str::<Settings>("(theme: Dark)").unwrap(),
+        Settings {
+            theme: ThemePreference::Dark,
+            ..Settings::default()
+        }
+    );
+    assert_eq!(
+        ron::from_str::<Settings>("()").unwrap(),
+        Settings::default()
+    );
+}
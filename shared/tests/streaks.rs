@@ -0,0 +1,192 @@
+//! Tests for working out the user's practice streaks from the dates of their attempts.
+
+use chrono::NaiveDate;
+use test_tracker_shared::{
+    streak::{completion_dates, streaks, StreakUnit, Streaks},
+    CompletionData, SyncedTest, TestData,
+};
+
+/// Parse a date in ISO format.
+fn date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+/// Parse some dates in ISO format.
+fn dates(dates: &[&str]) -> Vec<NaiveDate> {
+    dates.iter().map(|d| date(d)).collect()
+}
+
+/// The streaks in days as of the given day.
+fn daily(attempts: &[&str], today: &str) -> Streaks {
+    streaks(&dates(attempts), date(today), StreakUnit::Days)
+}
+
+#[test]
+fn no_attempts_means_no_streaks() {
+    assert_eq!(daily(&[], "2024-01-10"), Streaks::default());
+    assert_eq!(
+        streaks(&[], date("2024-01-10"), StreakUnit::Weeks),
+        Streaks::default()
+    );
+}
+
+#[test]
+fn consecutive_days_make_a_streak() {
+    assert_eq!(
+        daily(&["2024-01-08", "2024-01-09", "2024-01-10"], "2024-01-10"),
+        Streaks {
+            current: 3,
+            longest: 3
+        }
+    );
+}
+
+#[test]
+fn several_attempts_on_one_day_count_once() {
+    assert_eq!(
+        daily(
+            &[
+                "2024-01-09",
+                "2024-01-09",
+                "2024-01-10",
+                "2024-01-10",
+                "2024-01-10"
+            ],
+            "2024-01-10"
+        ),
+        Streaks {
+            current: 2,
+            longest: 2
+        }
+    );
+}
+
+#[test]
+fn a_gap_ends_a_streak() {
+    assert_eq!(
+        daily(
+            &[
+                "2024-01-01",
+                "2024-01-02",
+                "2024-01-03",
+                "2024-01-04",
+                "2024-01-08",
+                "2024-01-09"
+            ],
+            "2024-01-09"
+        ),
+        Streaks {
+            current: 2,
+            longest: 4
+        }
+    );
+}
+
+#[test]
+fn the_current_streak_lasts_until_the_end_of_the_next_day() {
+    let attempts = ["2024-01-08", "2024-01-09"];
+    // There's no attempt yet today, but there's still time
+    assert_eq!(daily(&attempts, "2024-01-10").current, 2);
+    // Once a whole day has passed without one, it's gone, but it's still the longest
+    assert_eq!(
+        daily(&attempts, "2024-01-11"),
+        Streaks {
+            current: 0,
+            longest: 2
+        }
+    );
+}
+
+#[test]
+fn streaks_go_across_months_and_years() {
+    // Dates are calendar days, so the day before midnight and the day after are always in a row,
+    // even across the end of a month, a leap day, or a year
+    assert_eq!(
+        daily(&["2023-12-31", "2024-01-01"], "2024-01-01").current,
+        2
+    );
+    assert_eq!(
+        daily(&["2024-02-28", "2024-02-29", "2024-03-01"], "2024-03-01").current,
+        3
+    );
+    assert_eq!(
+        daily(&["2023-02-28", "2023-03-01"], "2023-03-01").current,
+        2
+    );
+}
+
+#[test]
+fn attempts_after_today_are_ignored() {
+    assert_eq!(
+        daily(&["2024-01-09", "2024-01-10", "2024-01-11"], "2024-01-10"),
+        Streaks {
+            current: 2,
+            longest: 2
+        }
+    );
+    assert_eq!(daily(&["2030-01-01"], "2024-01-10"), Streaks::default());
+}
+
+#[test]
+fn weekly_streaks_count_weeks_starting_on_monday() {
+    // 2024-01-01 was a Monday, so these are in three weeks in a row, with two in the middle one
+    let attempts = dates(&["2024-01-07", "2024-01-08", "2024-01-14", "2024-01-15"]);
+    assert_eq!(
+        streaks(&attempts, date("2024-01-17"), StreakUnit::Weeks),
+        Streaks {
+            current: 3,
+            longest: 3
+        }
+    );
+    // Last week still counts, but not the week before
+    assert_eq!(
+        streaks(&attempts, date("2024-01-22"), StreakUnit::Weeks).current,
+        3
+    );
+    assert_eq!(
+        streaks(&attempts, date("2024-01-29"), StreakUnit::Weeks).current,
+        0
+    );
+    // In days, there's never been more than two in a row
+    assert_eq!(
+        streaks(&attempts, date("2024-01-15"), StreakUnit::Days).longest,
+        2
+    );
+}
+
+#[test]
+fn dates_come_from_every_dated_completion_sorted() {
+    let completion = |date: Option<&str>| CompletionData {
+        achieved_mark: 30,
+        total_marks: 60,
+        date: date.map(self::date),
+        comments: None,
+    };
+    let test = |completions: Vec<CompletionData>| SyncedTest {
+        id: 1,
+        test: TestData {
+            subject: "Maths".to_string(),
+            topic: None,
+            date_or_id: "Paper 1".to_string(),
+            qualification_level: None,
+            exam_board: None,
+            paper_link: None,
+            mark_scheme_link: None,
+            comments: None,
+        },
+        completions: (1..).zip(completions).collect(),
+        best_completion_id: None,
+    };
+    let tests = [
+        test(vec![completion(Some("2024-01-09")), completion(None)]),
+        test(vec![
+            completion(Some("2024-01-03")),
+            completion(Some("2024-01-09")),
+        ]),
+    ];
+
+    assert_eq!(
+        completion_dates(&tests),
+        dates(&["2024-01-03", "2024-01-09", "2024-01-09"])
+    );
+}
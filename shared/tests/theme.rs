@@ -0,0 +1,76 @@
+//! Tests for picking light mode or dark mode from the user's theme and the operating system.
+
+use test_tracker_shared::theme::{DarkMode, ThemePreference};
+
+#[test]
+fn preference_resolves_with_system_mode() {
+    for (preference, system, expected) in [
+        (ThemePreference::Light, DarkMode::Light, DarkMode::Light),
+        (ThemePreference::Light, DarkMode::Dark, DarkMode::Light),
+        (ThemePreference::Dark, DarkMode::Light, DarkMode::Dark),
+        (ThemePreference::Dark, DarkMode::Dark, DarkMode::Dark),
+        (ThemePreference::System, DarkMode::Light, DarkMode::Light),
+        (ThemePreference::System, DarkMode::Dark, DarkMode::Dark),
+    ] {
+        assert_eq!(
+            preference.resolve(system),
+            expected,
+            "{preference} with a {system} system"
+        );
+    }
+}
+
+#[test]
+fn stored_values_parse() {
+    // The old values from before there was a system theme
+    assert_eq!(
+        ThemePreference::from("light".to_string()),
+        ThemePreference::Light
+    );
+    assert_eq!(
+        ThemePreference::from("dark".to_string()),
+        ThemePreference::Dark
+    );
+    assert_eq!(
+        ThemePreference::from("Dark".to_string()),
+        ThemePreference::Dark
+    );
+
+    assert_eq!(
+        ThemePreference::from("system".to_string()),
+        ThemePreference::System
+    );
+    assert_eq!(
+        ThemePreference::from("purple".to_string()),
+        ThemePreference::System
+    );
+}
+
+#[test]
+fn preferences_round_trip() {
+    for preference in [
+        ThemePreference::Light,
+        ThemePreference::Dark,
+        ThemePreference::System,
+    ] {
+        assert_eq!(ThemePreference::from(preference.to_string()), preference);
+    }
+}
+
+#[test]
+fn cycling_visits_every_preference() {
+    let start = ThemePreference::System;
+    let cycle = [
+        start.next(),
+        start.next().next(),
+        start.next().next().next(),
+    ];
+    assert_eq!(
+        cycle,
+        [
+            ThemePreference::Light,
+            ThemePreference::Dark,
+            ThemePreference::System
+        ]
+    );
+}
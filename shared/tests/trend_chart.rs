@@ -0,0 +1,183 @@
+//! Tests for working out where the client draws the points of its chart of scores over time
+//! across every test.
+
+use std::collections::BTreeMap;
+use test_tracker_shared::{
+    chart::{
+        date_x, percentage_y, plot_trend, trend_points, trend_series, trend_weeks, week_of,
+        ChartSize, TrendBucket, TrendPoint,
+    },
+    fixtures::{bare_completion, bare_test, date, synced_test},
+    CompletionData, SyncedTest,
+};
+
+/// The size of the charts in these tests, chosen so that the coordinates are round numbers.
+const SIZE: ChartSize = ChartSize {
+    width: 120.,
+    height: 60.,
+    padding: 10.,
+};
+
+/// A test in the given subject with the given date or ID and completions.
+fn synced(subject: &str, date_or_id: &str, completions: Vec<CompletionData>) -> SyncedTest {
+    synced_test(
+        1,
+        bare_test(subject, date_or_id),
+        (1..).zip(completions).collect(),
+    )
+}
+
+/// A point in the given subject from the given test.
+fn point(date: &str, percentage: f64, subject: &str, test: &str) -> TrendPoint {
+    TrendPoint {
+        date: self::date(date),
+        percentage,
+        subject: subject.to_string(),
+        test: test.to_string(),
+    }
+}
+
+#[test]
+fn points_come_from_every_dated_completion_in_date_order() {
+    let tests = [
+        synced(
+            "Maths",
+            "June 2019",
+            vec![
+                bare_completion(30, 60, Some("2024-03-01")),
+                bare_completion(10, 20, None),
+                bare_completion(0, 0, Some("2024-02-01")),
+            ],
+        ),
+        synced(
+            "Physics",
+            "Nov 2020",
+            vec![
+                bare_completion(45, 60, Some("2024-01-01")),
+                bare_completion(5, 20, None),
+                bare_completion(6, 20, None),
+            ],
+        ),
+    ];
+
+    let (points, undated) = trend_points(&tests);
+    assert_eq!(
+        points,
+        vec![
+            point("2024-01-01", 75., "Physics", "Nov 2020"),
+            point("2024-03-01", 50., "Maths", "June 2019"),
+        ]
+    );
+    assert_eq!(
+        undated,
+        BTreeMap::from([("Maths".to_string(), 1), ("Physics".to_string(), 2)])
+    );
+}
+
+#[test]
+fn weeks_start_on_monday() {
+    // 2024-01-01 was a Monday
+    assert_eq!(week_of(date("2024-01-01")), date("2024-01-01"));
+    assert_eq!(week_of(date("2024-01-03")), date("2024-01-01"));
+    assert_eq!(week_of(date("2024-01-07")), date("2024-01-01"));
+    assert_eq!(week_of(date("2024-01-08")), date("2024-01-08"));
+    // Across the end of a year
+    assert_eq!(week_of(date("2025-01-01")), date("2024-12-30"));
+}
+
+#[test]
+fn scores_in_the_same_week_are_averaged() {
+    let points = [
+        point("2024-01-01", 40., "Maths", "Paper 1"),
+        point("2024-01-03", 80., "Maths", "Paper 2"),
+        point("2024-01-05", 60., "Maths", "Paper 1"),
+        point("2024-01-10", 90., "Maths", "Paper 3"),
+    ];
+
+    let series = trend_series(&points, None);
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0].subject, "Maths");
+    assert_eq!(
+        series[0].buckets,
+        vec![
+            TrendBucket {
+                week: date("2024-01-01"),
+                percentage: 60.,
+                tests: vec!["Paper 1".to_string(), "Paper 2".to_string()],
+            },
+            TrendBucket {
+                week: date("2024-01-08"),
+                percentage: 90.,
+                tests: vec!["Paper 3".to_string()],
+            },
+        ]
+    );
+}
+
+#[test]
+fn each_subject_gets_its_own_series_unless_one_is_chosen() {
+    let points = [
+        point("2024-01-01", 40., "Physics", "Paper 1"),
+        point("2024-01-02", 80., "Maths", "Paper 1"),
+        point("2024-01-09", 60., "Physics", "Paper 2"),
+    ];
+
+    let subjects: Vec<String> = trend_series(&points, None)
+        .into_iter()
+        .map(|series| series.subject)
+        .collect();
+    assert_eq!(subjects, ["Maths", "Physics"]);
+
+    let physics = trend_series(&points, Some("Physics"));
+    assert_eq!(physics.len(), 1);
+    assert_eq!(physics[0].subject, "Physics");
+    assert_eq!(physics[0].buckets.len(), 2);
+
+    assert_eq!(trend_series(&points, Some("Chemistry")), vec![]);
+}
+
+#[test]
+fn axes_scale_to_the_chart() {
+    let range = (date("2024-01-01"), date("2024-01-11"));
+    assert_eq!(date_x(date("2024-01-01"), range, SIZE), 10.);
+    assert_eq!(date_x(date("2024-01-06"), range, SIZE), 60.);
+    assert_eq!(date_x(date("2024-01-11"), range, SIZE), 110.);
+
+    let same_day = (date("2024-01-01"), date("2024-01-01"));
+    assert_eq!(date_x(date("2024-01-01"), same_day, SIZE), 60.);
+
+    assert_eq!(percentage_y(0., SIZE), 50.);
+    assert_eq!(percentage_y(50., SIZE), 30.);
+    assert_eq!(percentage_y(100., SIZE), 10.);
+    assert_eq!(percentage_y(120., SIZE), 10.);
+}
+
+#[test]
+fn every_series_shares_the_same_axes() {
+    let points = [
+        point("2024-01-01", 0., "Maths", "Paper 1"),
+        point("2024-01-15", 100., "Maths", "Paper 2"),
+        point("2024-01-08", 50., "Physics", "Paper 1"),
+        point("2024-01-29", 100., "Physics", "Paper 2"),
+    ];
+
+    let series = trend_series(&points, None);
+    assert_eq!(
+        trend_weeks(&series),
+        Some((date("2024-01-01"), date("2024-01-29")))
+    );
+    assert_eq!(
+        plot_trend(&series, SIZE),
+        vec![vec![(10., 50.), (60., 10.)], vec![(35., 30.), (110., 10.)],]
+    );
+}
+
+#[test]
+fn no_scores_plot_nothing() {
+    assert_eq!(trend_weeks(&[]), None);
+    assert_eq!(plot_trend(&[], SIZE), Vec::<Vec<(f64, f64)>>::new());
+    assert_eq!(
+        trend_points(&Vec::<SyncedTest>::new()),
+        (vec![], BTreeMap::new())
+    );
+}
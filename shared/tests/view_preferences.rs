@@ -0,0 +1,64 @@
+//! Tests for the preferences for how the list of tests looks, which the client keeps for each
+//! user.
+
+use std::collections::BTreeSet;
+use test_tracker_shared::{view::ViewPreferences, TestSort};
+
+#[test]
+fn view_preferences_survive_storage() {
+    let preferences = ViewPreferences {
+        sort: Some(TestSort::BestPercentageAsc),
+        subject_filter: Some("Maths".to_string()),
+        grouped: true,
+        expanded_tests: BTreeSet::from([3, 17]),
+    };
+
+    let stored = ron::to_string(&preferences).unwrap();
+    assert_eq!(
+        ron::from_str::<ViewPreferences>(&stored).unwrap(),
+        preferences
+    );
+}
+
+#[test]
+fn view_preferences_missing_fields_take_their_defaults() {
+    assert_eq!(
+        ron::from_str::<ViewPreferences>("(grouped: true)").unwrap(),
+        ViewPreferences {
+            grouped: true,
+            ..ViewPreferences::default()
+        }
+    );
+    assert_eq!(
+        ron::from_str::<ViewPreferences>("()").unwrap(),
+        ViewPreferences::default()
+    );
+}
+
+#[test]
+fn corrupt_view_preferences_fall_back_to_the_defaults() {
+    for stored in [
+        "",
+        "(sort: Some(3))",
+        "(grouped: \"yes\")",
+        "not ron at all",
+    ] {
+        assert!(
+            ron::from_str::<ViewPreferences>(stored).is_err(),
+            "{stored:?} should fail to load"
+        );
+        assert_eq!(
+            ron::from_str::<ViewPreferences>(stored).unwrap_or_default(),
+            ViewPreferences::default()
+        );
+    }
+}
+
+#[test]
+fn each_user_has_their_own_storage_key() {
+    let alice = ViewPreferences::storage_key("alice-id");
+    let bob = ViewPreferences::storage_key("bob-id");
+    assert_ne!(alice, bob);
+    assert!(alice.ends_with("alice-id"));
+    assert_eq!(alice, ViewPreferences::storage_key("alice-id"));
+}